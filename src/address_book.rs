@@ -0,0 +1,38 @@
+use std::fs;
+
+use crate::common::*;
+
+/// Prefix that identifies an address book label (e.g. `@cold-wallet`).
+pub const LABEL_PREFIX: char = '@';
+
+/// Loads the address book (label → pubkey) from the address book file.
+pub fn load_address_book(path: &str) -> Result<IndexMap<String, Pubkey>> {
+    let content = fs::read_to_string(path).map_err(|err| {
+        let error = AddressBookError::FailedToOpen(path.to_string(), err.to_string());
+        error!("{:?}", error);
+        error
+    })?;
+    let entries: IndexMap<String, String> = serde_json::from_str(&content)
+        .map_err(|err| AddressBookError::WrongFormat(path.to_string(), err.to_string()))?;
+
+    let mut address_book = IndexMap::with_capacity(entries.len());
+
+    for (label, address) in entries {
+        let pubkey = Pubkey::from_str(&address)
+            .map_err(|_| AddressBookError::InvalidAddress(label.clone(), address.clone()))?;
+        address_book.insert(label, pubkey);
+    }
+
+    Ok(address_book)
+}
+
+/// Parses a pubkey, looking up `@label` values in the address book file.
+pub fn resolve_pubkey(input: &str, address_book: &str) -> Result<Pubkey> {
+    match input.strip_prefix(LABEL_PREFIX) {
+        Some(label) => load_address_book(address_book)?
+            .get(label)
+            .copied()
+            .ok_or_else(|| AddressBookError::UnknownLabel(label.to_string()).into()),
+        None => Pubkey::from_str(input).map_err(|_| anyhow!("Invalid pubkey: {}", input)),
+    }
+}
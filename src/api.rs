@@ -0,0 +1,64 @@
+//! Typed async entry points for embedding case in other Rust services.
+//!
+//! Each function takes the same arguments as the corresponding CLI command and
+//! returns a structured outcome instead of printing it. Console output can be
+//! silenced with `output::set_output_format(OutputFormat::Json)`.
+
+use anyhow::Result;
+use tokio::task::spawn_blocking;
+
+pub use crate::{
+    collections::{
+        RemoveCollectionArgs, RemoveCollectionOutcome, SetCollectionArgs, SetCollectionOutcome,
+    },
+    config::GlobalOptions,
+    deploy::{DeployArgs, DeployOutcome},
+    mint::{MintArgs, MintOutcome},
+    output::{set_output_format, OutputFormat},
+    update::{UpdateArgs, UpdateOutcome},
+    upload::{UploadArgs, UploadOutcome},
+    verify::{VerifyArgs, VerifyOutcome},
+};
+use crate::{
+    collections::{process_remove_collection, process_set_collection},
+    deploy::process_deploy,
+    mint::process_mint,
+    update::process_update,
+    upload::process_upload,
+    verify::process_verify,
+};
+
+/// Uploads the assets and updates the cache file.
+pub async fn upload(args: UploadArgs) -> Result<UploadOutcome> {
+    process_upload(args).await
+}
+
+/// Creates the tars (if needed) and writes the config lines from the cache file.
+pub async fn deploy(args: DeployArgs) -> Result<DeployOutcome> {
+    process_deploy(args).await
+}
+
+/// Mints items from a tars.
+pub async fn mint(args: MintArgs) -> Result<MintOutcome> {
+    spawn_blocking(move || process_mint(args)).await?
+}
+
+/// Verifies that the on-chain config lines match the cache file.
+pub async fn verify(args: VerifyArgs) -> Result<VerifyOutcome> {
+    process_verify(args).await
+}
+
+/// Updates the tars data (and optionally its authority) from the config file.
+pub async fn update(args: UpdateArgs) -> Result<UpdateOutcome> {
+    spawn_blocking(move || process_update(args)).await?
+}
+
+/// Sets the collection mint of a tars.
+pub async fn set_collection(args: SetCollectionArgs) -> Result<SetCollectionOutcome> {
+    spawn_blocking(move || process_set_collection(args)).await?
+}
+
+/// Removes the collection mint of a tars.
+pub async fn remove_collection(args: RemoveCollectionArgs) -> Result<RemoveCollectionOutcome> {
+    spawn_blocking(move || process_remove_collection(args)).await?
+}
@@ -0,0 +1,195 @@
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::{fs::OpenOptions, io::Write, num::NonZeroU32};
+
+use data_encoding::BASE64;
+use dialoguer::Password;
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::Serialize;
+
+use crate::{backup::BackupError, common::*, utils::get_dialoguer_theme};
+
+/// Header identifying an encrypted backup archive.
+const MAGIC: &[u8; 8] = b"CASEBAK\0";
+
+/// Version of the archive layout.
+pub const ARCHIVE_VERSION: u8 = 1;
+
+/// Environment variable used to provide the passphrase non-interactively.
+pub const PASSPHRASE_ENV: &str = "CASE_BACKUP_PASSPHRASE";
+
+const SALT_LEN: usize = 16;
+
+const KEY_LEN: usize = 32;
+
+/// Number of PBKDF2 iterations used to derive the encryption key.
+const PBKDF2_ITERATIONS: u32 = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EntryKind {
+    Config,
+    Cache,
+    CollectionKey,
+    TarsSnapshot,
+}
+
+/// File stored in the archive.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupEntry {
+    pub kind: EntryKind,
+    /// File name (without directories) used when restoring the entry.
+    pub name: String,
+    /// Base64-encoded file content.
+    pub content: String,
+}
+
+impl BackupEntry {
+    pub fn new(kind: EntryKind, name: String, content: &[u8]) -> Self {
+        BackupEntry {
+            kind,
+            name,
+            content: BASE64.encode(content),
+        }
+    }
+
+    pub fn decode(&self) -> Result<Vec<u8>> {
+        BASE64
+            .decode(self.content.as_bytes())
+            .map_err(|err| anyhow!("Invalid content for entry '{}': {}", self.name, err))
+    }
+}
+
+/// Project state bundled in a backup.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupArchive {
+    pub version: u8,
+    pub created_at: i64,
+    pub tars: Option<String>,
+    pub entries: Vec<BackupEntry>,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<LessSafeKey> {
+    let mut key = [0u8; KEY_LEN];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+
+    let key = UnboundKey::new(&AES_256_GCM, &key).map_err(|_| BackupError::EncryptionFailed)?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// Serializes and encrypts the archive: `MAGIC | version | salt | nonce | ciphertext`.
+pub fn encrypt_archive(archive: &BackupArchive, passphrase: &str) -> Result<Vec<u8>> {
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt)
+        .and_then(|_| rng.fill(&mut nonce))
+        .map_err(|_| BackupError::EncryptionFailed)?;
+
+    let key = derive_key(passphrase, &salt)?;
+    let mut content = serde_json::to_vec(archive)?;
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(MAGIC),
+        &mut content,
+    )
+    .map_err(|_| BackupError::EncryptionFailed)?;
+
+    let mut data = Vec::with_capacity(MAGIC.len() + 1 + SALT_LEN + NONCE_LEN + content.len());
+    data.extend_from_slice(MAGIC);
+    data.push(ARCHIVE_VERSION);
+    data.extend_from_slice(&salt);
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&content);
+
+    Ok(data)
+}
+
+/// Decrypts and deserializes an archive created by `encrypt_archive`.
+pub fn decrypt_archive(path: &str, data: &[u8], passphrase: &str) -> Result<BackupArchive> {
+    let header_len = MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+
+    if data.len() < header_len || &data[..MAGIC.len()] != MAGIC {
+        return Err(BackupError::InvalidArchive(path.to_string()).into());
+    }
+
+    let version = data[MAGIC.len()];
+    if version != ARCHIVE_VERSION {
+        return Err(BackupError::UnsupportedVersion(version).into());
+    }
+
+    let salt_start = MAGIC.len() + 1;
+    let nonce_start = salt_start + SALT_LEN;
+    let salt = &data[salt_start..nonce_start];
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce.copy_from_slice(&data[nonce_start..header_len]);
+
+    let key = derive_key(passphrase, salt)?;
+    let mut content = data[header_len..].to_vec();
+    let plaintext = key
+        .open_in_place(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(MAGIC),
+            &mut content,
+        )
+        .map_err(|_| BackupError::DecryptionFailed)?;
+
+    Ok(serde_json::from_slice(plaintext)?)
+}
+
+/// Reads the archive passphrase from the environment or prompts for it; when
+/// `confirm` is set, the passphrase has to be entered twice.
+pub fn read_passphrase(confirm: bool) -> Result<String> {
+    let passphrase = match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) if is_json_output() => {
+            return Err(anyhow!(
+                "Set the {} environment variable to provide the passphrase",
+                PASSPHRASE_ENV
+            ))
+        }
+        Err(_) => {
+            let theme = get_dialoguer_theme();
+            let mut prompt = Password::with_theme(&theme);
+            prompt.with_prompt("Backup passphrase");
+
+            if confirm {
+                prompt.with_confirmation("Confirm passphrase", "Passphrases do not match");
+            }
+
+            prompt.interact()?
+        }
+    };
+
+    if passphrase.is_empty() {
+        return Err(BackupError::EmptyPassphrase.into());
+    }
+
+    Ok(passphrase)
+}
+
+/// Writes a file readable only by its owner, since backups and collection keys
+/// contain secrets.
+pub fn write_private_file(path: &Path, data: &[u8]) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    let mut file = options.open(path)?;
+    file.write_all(data)?;
+
+    Ok(())
+}
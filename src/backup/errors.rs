@@ -0,0 +1,17 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error("File '{0}' is not a case backup archive")]
+    InvalidArchive(String),
+    #[error("Unsupported backup archive version {0}")]
+    UnsupportedVersion(u8),
+    #[error("Failed to decrypt backup archive (wrong passphrase or corrupted file)")]
+    DecryptionFailed,
+    #[error("Failed to encrypt backup archive")]
+    EncryptionFailed,
+    #[error("Passphrase cannot be empty")]
+    EmptyPassphrase,
+    #[error("File '{0}' already exists, use --force to overwrite it")]
+    FileExists(String),
+}
@@ -0,0 +1,198 @@
+use std::fs;
+
+use anchor_client::solana_sdk::signature::read_keypair_file;
+use chrono::Utc;
+use console::style;
+use data_encoding::BASE64;
+use serde::Serialize;
+
+use crate::{
+    backup::{
+        encrypt_archive, read_passphrase, write_private_file, BackupArchive, BackupEntry,
+        EntryKind, ARCHIVE_VERSION,
+    },
+    cache::{check_cache_cluster, load_cache},
+    common::*,
+    utils::*,
+};
+
+/// Name of the tars state snapshot inside the archive.
+pub const SNAPSHOT_NAME: &str = "tars-snapshot.json";
+
+pub struct ExportBackupArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub config: String,
+    pub cache: String,
+    pub collection_keys: Vec<String>,
+    pub output_dir: String,
+}
+
+/// Result of the export backup command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportBackupOutcome {
+    pub archive: String,
+    pub tars: Option<String>,
+    pub files: Vec<String>,
+}
+
+pub fn process_export_backup(args: ExportBackupArgs) -> Result<ExportBackupOutcome> {
+    case_println!(
+        "{} {}Collecting project files",
+        style("[1/3]").bold().dim(),
+        ASSETS_EMOJI
+    );
+
+    let mut entries = vec![BackupEntry::new(
+        EntryKind::Config,
+        file_name(&args.config)?,
+        &fs::read(&args.config)
+            .map_err(|err| anyhow!("Failed to read config file '{}': {}", args.config, err))?,
+    )];
+
+    let mut cache = None;
+
+    if Path::new(&args.cache).exists() {
+        cache = Some(load_cache(&args.cache, false)?);
+        entries.push(BackupEntry::new(
+            EntryKind::Cache,
+            file_name(&args.cache)?,
+            &fs::read(&args.cache)?,
+        ));
+    } else {
+        warn!("Cache file {} not found", args.cache);
+        case_println!(
+            "{}{}",
+            WARNING_EMOJI,
+            style(format!(
+                "Cache file '{}' not found, skipping it.",
+                args.cache
+            ))
+            .yellow()
+        );
+    }
+
+    for key in &args.collection_keys {
+        // only valid keypair files are accepted
+        read_keypair_file(key)
+            .map_err(|err| anyhow!("Failed to read keypair file: {}, {}", key, err))?;
+        entries.push(BackupEntry::new(
+            EntryKind::CollectionKey,
+            file_name(key)?,
+            &fs::read(key)?,
+        ));
+    }
+
+    case_println!(
+        "\n{} {}Snapshotting tars state",
+        style("[2/3]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let tars = cache
+        .as_ref()
+        .map(|cache| cache.program.tars.clone())
+        .filter(|tars| !tars.is_empty());
+
+    match cache
+        .as_mut()
+        .filter(|cache| !cache.program.tars.is_empty())
+    {
+        Some(cache) => {
+            let pb = spinner_with_style();
+            pb.set_message("Connecting...");
+
+            let snapshot = snapshot_tars(args.keypair, args.rpc_url, &args.global, cache)?;
+            entries.push(BackupEntry::new(
+                EntryKind::TarsSnapshot,
+                SNAPSHOT_NAME.to_string(),
+                &serde_json::to_vec_pretty(&snapshot)?,
+            ));
+
+            pb.finish_with_message(format!(
+                "{} {}",
+                style("Tars ID:").bold(),
+                cache.program.tars
+            ));
+        }
+        None => case_println!("No tars in the cache file, skipping snapshot."),
+    }
+
+    case_println!(
+        "\n{} {}Writing encrypted archive",
+        style("[3/3]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    let passphrase = read_passphrase(true)?;
+
+    let files: Vec<String> = entries.iter().map(|entry| entry.name.clone()).collect();
+    let created_at = Utc::now();
+    let archive = BackupArchive {
+        version: ARCHIVE_VERSION,
+        created_at: created_at.timestamp(),
+        tars: tars.clone(),
+        entries,
+    };
+
+    let path = Path::new(&args.output_dir).join(format!(
+        "case-backup-{}.bak",
+        created_at.format("%Y%m%d-%H%M%S")
+    ));
+    write_private_file(&path, &encrypt_archive(&archive, &passphrase)?)?;
+
+    let archive = path_to_string(&path)?;
+    case_println!(
+        "{} {}",
+        style("Backup saved to:").bold(),
+        style(&archive).green()
+    );
+
+    Ok(ExportBackupOutcome {
+        archive,
+        tars,
+        files,
+    })
+}
+
+/// Returns the raw account data of the tars of the cache at the current slot.
+fn snapshot_tars(
+    keypair: Option<String>,
+    rpc_url: Option<String>,
+    global: &GlobalOptions,
+    cache: &mut Cache,
+) -> Result<Value> {
+    let tars_id = cache.program.tars.clone();
+    let tars_pubkey =
+        Pubkey::from_str(&tars_id).map_err(|_| anyhow!("Failed to parse tars id: {}", tars_id))?;
+
+    let case_config = case_setup(keypair, rpc_url, global)?;
+    check_cache_cluster(&case_config, cache)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_PROGRAM_ID);
+    let response = program
+        .rpc()
+        .get_account_with_commitment(&tars_pubkey, CommitmentConfig::confirmed())?;
+
+    let account = response
+        .value
+        .ok_or_else(|| anyhow!("Tars account {} not found", tars_id))?;
+
+    Ok(json!({
+        "tars": tars_id,
+        "slot": response.context.slot,
+        "owner": account.owner.to_string(),
+        "lamports": account.lamports,
+        "data": BASE64.encode(&account.data),
+    }))
+}
+
+fn file_name(path: &str) -> Result<String> {
+    Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.to_string())
+        .ok_or_else(|| anyhow!("Invalid file path: {}", path))
+}
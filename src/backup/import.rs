@@ -0,0 +1,93 @@
+use std::fs;
+
+use chrono::NaiveDateTime;
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    backup::{decrypt_archive, read_passphrase, write_private_file, BackupError},
+    common::*,
+};
+
+pub struct ImportBackupArgs {
+    pub archive: String,
+    pub output_dir: String,
+    pub force: bool,
+}
+
+/// Result of the import backup command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportBackupOutcome {
+    pub tars: Option<String>,
+    pub created_at: i64,
+    pub files: Vec<String>,
+}
+
+pub fn process_import_backup(args: ImportBackupArgs) -> Result<ImportBackupOutcome> {
+    case_println!(
+        "{} {}Decrypting archive",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let data = fs::read(&args.archive)
+        .map_err(|err| anyhow!("Failed to read backup archive '{}': {}", args.archive, err))?;
+    let passphrase = read_passphrase(false)?;
+    let archive = decrypt_archive(&args.archive, &data, &passphrase)?;
+
+    case_println!(
+        "{} {}",
+        style("Backup created at:").bold(),
+        NaiveDateTime::from_timestamp(archive.created_at, 0).format("%a %B %e %Y %H:%M:%S UTC")
+    );
+    if let Some(tars) = &archive.tars {
+        case_println!("{} {}", style("Tars ID:").bold(), tars);
+    }
+
+    case_println!(
+        "\n{} {}Restoring files",
+        style("[2/2]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    let output_dir = Path::new(&args.output_dir);
+    let mut targets = Vec::with_capacity(archive.entries.len());
+
+    // checks every target before writing anything, so a restore is all or nothing
+    for entry in &archive.entries {
+        let name = Path::new(&entry.name)
+            .file_name()
+            .ok_or_else(|| BackupError::InvalidArchive(args.archive.clone()))?;
+        let target = output_dir.join(name);
+
+        if target.exists() && !args.force {
+            return Err(BackupError::FileExists(path_to_string(&target)?).into());
+        }
+
+        targets.push((target, entry.decode()?));
+    }
+
+    fs::create_dir_all(output_dir)?;
+    let mut files = Vec::with_capacity(targets.len());
+
+    for (target, content) in targets {
+        write_private_file(&target, &content)?;
+        let file = path_to_string(&target)?;
+        case_println!(" {} {}", style(":..").dim(), file);
+        files.push(file);
+    }
+
+    case_println!(
+        "\n{}",
+        style(format!("Restored {} file(s).", files.len()))
+            .green()
+            .bold()
+    );
+
+    Ok(ImportBackupOutcome {
+        tars: archive.tars,
+        created_at: archive.created_at,
+        files,
+    })
+}
@@ -0,0 +1,9 @@
+pub mod archive;
+pub mod errors;
+pub mod export;
+pub mod import;
+
+pub use archive::*;
+pub use errors::*;
+pub use export::*;
+pub use import::*;
@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anchor_client::solana_sdk::{native_token::LAMPORTS_PER_SOL, program_pack::Pack};
+use mpl_token_metadata::state::{MAX_MASTER_EDITION_LEN, MAX_METADATA_LEN};
+use solana_client::rpc_client::RpcClient;
+use spl_token::state::Account;
+
+use crate::common::*;
+
+/// Caps the lamports (fees and rent) spent by a command, as set by `--max-fee`.
+#[derive(Debug, Default)]
+pub struct FeeBudget {
+    limit: Option<u64>,
+    spent: AtomicU64,
+}
+
+impl FeeBudget {
+    /// Creates a budget from a limit in SOL; `None` means no limit.
+    pub fn new(max_fee: Option<f64>) -> Self {
+        FeeBudget {
+            limit: max_fee.map(|max_fee| (max_fee * LAMPORTS_PER_SOL as f64) as u64),
+            spent: AtomicU64::new(0),
+        }
+    }
+
+    pub fn is_limited(&self) -> bool {
+        self.limit.is_some()
+    }
+
+    /// Refuses to start an operation whose estimated cost is over the limit.
+    pub fn check_estimate(&self, estimate: u64) -> Result<()> {
+        match self.limit {
+            Some(limit) if estimate > limit => {
+                Err(FeeBudgetError::EstimateExceeded(to_sol(estimate), to_sol(limit)).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Reserves the cost of the next transaction, failing (without reserving)
+    /// when it would take the total over the limit.
+    pub fn reserve(&self, lamports: u64) -> Result<()> {
+        let limit = match self.limit {
+            Some(limit) => limit,
+            None => {
+                self.spent.fetch_add(lamports, Ordering::SeqCst);
+                return Ok(());
+            }
+        };
+
+        self.spent
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |spent| {
+                let total = spent + lamports;
+                (total <= limit).then(|| total)
+            })
+            .map(|_| ())
+            .map_err(|spent| FeeBudgetError::LimitReached(to_sol(spent), to_sol(limit)).into())
+    }
+
+    /// Lamports reserved so far.
+    pub fn spent(&self) -> u64 {
+        self.spent.load(Ordering::SeqCst)
+    }
+}
+
+/// Formats an amount of lamports in SOL, with 6 decimals.
+pub fn to_sol(lamports: u64) -> String {
+    format!("{:.6}", lamports as f64 / LAMPORTS_PER_SOL as f64)
+}
+
+/// Rent of the accounts created for a new NFT: mint, token account, metadata and
+/// master edition.
+pub fn nft_rent(rpc_client: &RpcClient) -> Result<u64> {
+    let mut rent = 0;
+
+    for size in [
+        MINT_LAYOUT as usize,
+        Account::LEN,
+        MAX_METADATA_LEN,
+        MAX_MASTER_EDITION_LEN,
+    ] {
+        rent += rpc_client.get_minimum_balance_for_rent_exemption(size)?;
+    }
+
+    Ok(rent)
+}
@@ -14,21 +14,22 @@ const LIMIT: u64 = 5000;
 pub struct BundlrArgs {
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
     pub action: BundlrAction,
 }
 
 pub async fn process_bundlr(args: BundlrArgs) -> Result<()> {
-    let case_config = case_setup(args.keypair, args.rpc_url)?;
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
     let client = setup_client(&case_config)?;
 
     // retrieving balance
 
-    println!(
+    case_println!(
         "{} {}Retrieving balance",
-        style(if let BundlrAction::Withdraw = args.action {
-            "[1/2]"
-        } else {
+        style(if let BundlrAction::Balance = args.action {
             "[1/1]"
+        } else {
+            "[1/2]"
         })
         .bold()
         .dim(),
@@ -53,25 +54,66 @@ pub async fn process_bundlr(args: BundlrArgs) -> Result<()> {
 
     pb.finish_and_clear();
 
-    println!("\nFunding address:");
-    println!("  -> pubkey: {}", address);
-    println!(
+    case_println!("\nFunding address:");
+    case_println!("  -> pubkey: {}", address);
+    case_println!(
         "  -> lamports: {} (◎ {})",
         balance,
         balance as f64 / LAMPORTS_PER_SOL as f64
     );
 
+    // funding the balance
+
+    if let BundlrAction::Fund { lamports } = args.action {
+        case_println!(
+            "\n{} {}Funding balance",
+            style("[2/2]").bold().dim(),
+            PAYMENT_EMOJI
+        );
+
+        if lamports == 0 {
+            return Err(anyhow!("The amount to fund must be greater than 0"));
+        }
+
+        let bundlr_address = Pubkey::from_str(
+            &BundlrMethod::get_bundlr_solana_address(&http_client, bundlr_node).await?,
+        )?;
+        let response = BundlrMethod::fund_bundlr_address(
+            program.rpc(),
+            &http_client,
+            &bundlr_address,
+            bundlr_node,
+            &keypair,
+            lamports,
+        )
+        .await?;
+
+        if response.status().is_success() {
+            case_println!("\nFunding completed.");
+        } else {
+            // the transfer went through, bundlr credits it once the transaction is found
+            let error = response.text().await?;
+            warn!("Bundlr did not register the funding transaction: {}", error);
+            case_println!(
+                "\n{}",
+                style("Bundlr has not registered the transfer yet, check the balance later.")
+                    .yellow()
+                    .bold()
+            );
+        }
+    }
+
     // withdrawing funds
 
     if let BundlrAction::Withdraw = args.action {
-        println!(
+        case_println!(
             "\n{} {}Withdrawing funds",
             style("[2/2]").bold().dim(),
             WITHDRAW_EMOJI
         );
 
         if balance == 0 {
-            println!("\nNo funds to withdraw.");
+            case_println!("\nNo funds to withdraw.");
         } else if (balance - LIMIT) > 0 {
             let pb = spinner_with_style();
             pb.set_message("Connecting...");
@@ -118,18 +160,18 @@ pub async fn process_bundlr(args: BundlrArgs) -> Result<()> {
             pb.finish_and_clear();
 
             if response.status() == 200 {
-                println!("\nWithdraw completed.");
+                case_println!("\nWithdraw completed.");
             } else {
-                println!("\n{}", style("Withdraw failed.").red().bold());
+                case_println!("\n{}", style("Withdraw failed.").red().bold());
                 let error = response.text().await?;
                 return Err(anyhow!("Failed to complete withdraw ({})", error));
             }
         } else {
-            println!(
+            case_println!(
                 "\n{}",
                 style("Insufficient balance for withdraw:").red().bold()
             );
-            println!(
+            case_println!(
                 "  -> required balance > {} (◎ {})",
                 LIMIT,
                 LIMIT as f64 / LAMPORTS_PER_SOL as f64
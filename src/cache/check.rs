@@ -0,0 +1,270 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt, fs,
+};
+
+use console::style;
+use serde::{
+    de::{IgnoredAny, MapAccess, Visitor},
+    Deserializer, Serialize,
+};
+
+use crate::{
+    cache::load_cluster_cache,
+    common::*,
+    pdas::get_collection_pda,
+    tars::{config_line, TARS_ID},
+    utils::spinner_with_style,
+};
+
+pub struct CheckCacheArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+}
+
+/// Problem found in a cache item.
+#[derive(Debug, Serialize)]
+pub struct CacheIssue {
+    pub index: String,
+    pub issue: String,
+}
+
+/// Result of the cache check command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckCacheOutcome {
+    pub items: usize,
+    pub issues: Vec<CacheIssue>,
+}
+
+/// Keys of the items of a cache file, in the order they appear (including duplicates,
+/// which are silently dropped when the cache is loaded).
+#[derive(Deserialize)]
+struct RawCache {
+    items: RawKeys,
+}
+
+struct RawKeys(Vec<String>);
+
+impl<'de> Deserialize<'de> for RawKeys {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct KeysVisitor;
+
+        impl<'de> Visitor<'de> for KeysVisitor {
+            type Value = RawKeys;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a map of cache items")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> std::result::Result<Self::Value, A::Error> {
+                let mut keys = Vec::new();
+                while let Some((key, _)) = map.next_entry::<String, IgnoredAny>()? {
+                    keys.push(key);
+                }
+                Ok(RawKeys(keys))
+            }
+        }
+
+        deserializer.deserialize_map(KeysVisitor)
+    }
+}
+
+pub fn process_check_cache(args: CheckCacheArgs) -> Result<CheckCacheOutcome> {
+    case_println!(
+        "{} {}Checking cache",
+        style("[1/1]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let cache = load_cluster_cache(&case_config, &args.cache, false)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    let mut issues = check_structure(&args.cache)?;
+    issues.extend(check_links(&cache));
+
+    let (items_available, expected) = expected_on_chain(&program, &cache)?;
+
+    for (index, on_chain) in expected {
+        let item = &cache.items[&index];
+
+        let issue = match (item.on_chain, on_chain) {
+            (true, false) if matches!(index.parse::<u64>(), Ok(i) if i >= items_available) => {
+                format!(
+                    "marked on-chain, but out of range of the tars ({} item(s))",
+                    items_available
+                )
+            }
+            (true, false) => "marked on-chain, but not found on-chain".to_string(),
+            (false, true) => "found on-chain, but not marked on-chain".to_string(),
+            _ => continue,
+        };
+
+        issues.push(CacheIssue { index, issue });
+    }
+
+    pb.finish_and_clear();
+
+    let outcome = CheckCacheOutcome {
+        items: cache.items.len(),
+        issues,
+    };
+
+    if outcome.issues.is_empty() {
+        case_println!(
+            "{}",
+            style(format!("Cache is consistent ({} item(s)).", outcome.items))
+                .green()
+                .bold()
+        );
+        return Ok(outcome);
+    }
+
+    let mut invalid = String::new();
+
+    for issue in &outcome.issues {
+        case_println!("- Item {}: {}", issue.index, issue.issue);
+        invalid.push_str(&format!("\n- Item {}: {}", issue.index, issue.issue));
+    }
+
+    case_println!("\nRun 'cache repair' to rebuild the on-chain flags.");
+
+    // the issues are only printed in text mode, so include them in the error
+    Err(if is_json_output() {
+        anyhow!("{} cache issue(s) found:{}", outcome.issues.len(), invalid)
+    } else {
+        anyhow!("{} cache issue(s) found.", outcome.issues.len())
+    })
+}
+
+/// Checks the keys of the items: invalid and duplicate indices, and gaps in the indices.
+fn check_structure(path: &str) -> Result<Vec<CacheIssue>> {
+    let raw: RawCache = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|err| CacheError::CacheFileWrongFormat(err.to_string()))?;
+
+    let mut issues = Vec::new();
+    let mut seen = HashSet::new();
+    let mut indices = BTreeSet::new();
+
+    for key in raw.items.0 {
+        match key.parse::<i64>() {
+            Ok(index) if index >= -1 => {
+                if !seen.insert(index) {
+                    issues.push(CacheIssue {
+                        index: key,
+                        issue: "duplicate index".to_string(),
+                    });
+                } else if index >= 0 {
+                    indices.insert(index);
+                }
+            }
+            _ => issues.push(CacheIssue {
+                index: key,
+                issue: "invalid index".to_string(),
+            }),
+        }
+    }
+
+    // indices must be contiguous from 0, since config lines are written by index
+    if let Some(last) = indices.iter().next_back() {
+        for index in 0..*last {
+            if !indices.contains(&index) {
+                issues.push(CacheIssue {
+                    index: index.to_string(),
+                    issue: "missing index".to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Checks that the uploaded files of each item have links.
+fn check_links(cache: &Cache) -> Vec<CacheIssue> {
+    let mut issues = Vec::new();
+
+    for (index, item) in cache.items.iter() {
+        let mut missing = Vec::new();
+
+        if item.image_link.is_empty() {
+            missing.push("image");
+        }
+        if item.metadata_link.is_empty() {
+            missing.push("metadata");
+        }
+        if item.animation_hash.is_some() && item.animation_link.is_none() {
+            missing.push("animation");
+        }
+
+        if !missing.is_empty() {
+            issues.push(CacheIssue {
+                index: index.clone(),
+                issue: format!("missing {} link", missing.join(", ")),
+            });
+        }
+    }
+
+    issues
+}
+
+/// Returns the number of items of the tars and whether each item is on-chain: the
+/// collection when it is set on the tars, and other items when the config line at their
+/// index matches the cache. Items of a cache without a deployed tars are not on-chain.
+pub(crate) fn expected_on_chain(
+    program: &Program,
+    cache: &Cache,
+) -> Result<(u64, Vec<(String, bool)>)> {
+    let tars_pubkey = match Pubkey::from_str(&cache.program.tars) {
+        Ok(pubkey) => Some(pubkey),
+        Err(_) if cache.program.tars.is_empty() => None,
+        Err(_) => {
+            return Err(CacheError::InvalidTarsAddress(cache.program.tars.clone()).into());
+        }
+    };
+
+    let (data, items_available) = match tars_pubkey {
+        Some(pubkey) => {
+            let data = program.rpc().get_account_data(&pubkey)?;
+            let tars = Tars::try_deserialize(&mut data.as_slice())?;
+            (data, tars.data.items_available)
+        }
+        None => (Vec::new(), 0),
+    };
+
+    let collection_mint = tars_pubkey
+        .and_then(|pubkey| get_collection_pda(&pubkey, program).ok())
+        .map(|(_, collection_pda)| collection_pda.mint.to_string());
+
+    let items = cache
+        .items
+        .iter()
+        .map(|(key, item)| {
+            let on_chain = match key.parse::<u64>() {
+                Ok(index) if index < items_available => matches!(
+                    config_line(&data, index as usize),
+                    Some((name, uri)) if name == item.name && uri == item.metadata_link
+                ),
+                // out of range of the tars
+                Ok(_) => false,
+                Err(_) if key == "-1" => {
+                    collection_mint.as_deref() == Some(cache.program.collection_mint.as_str())
+                }
+                Err(_) => false,
+            };
+            (key.clone(), on_chain)
+        })
+        .collect();
+
+    Ok((items_available, items))
+}
@@ -0,0 +1,75 @@
+use console::style;
+
+use crate::{
+    cache::Cache,
+    common::*,
+    config::CaseConfig,
+    setup::genesis_hash,
+    utils::{DEVNET_HASH, MAINNET_HASH},
+};
+
+/// Checks that the cache was created on the cluster of the RPC of the config. A cache
+/// without a cluster is tagged with it, which is saved the next time the cache is written.
+pub fn check_cache_cluster(case_config: &CaseConfig, cache: &mut Cache) -> Result<()> {
+    match &cache.program.cluster {
+        Some(cluster) => check_cluster(case_config, &cache.file_path, cluster),
+        None => {
+            cache.program.cluster = genesis_hash(case_config);
+            Ok(())
+        }
+    }
+}
+
+/// Returns the name of the cluster with the genesis hash (the hash itself for
+/// clusters other than devnet and mainnet).
+pub fn cluster_name(genesis_hash: &str) -> String {
+    match genesis_hash {
+        DEVNET_HASH => "devnet".to_string(),
+        MAINNET_HASH => "mainnet".to_string(),
+        other => format!("cluster {}", other),
+    }
+}
+
+fn check_cluster(case_config: &CaseConfig, file_path: &str, cluster: &str) -> Result<()> {
+    let rpc_cluster = match genesis_hash(case_config) {
+        Some(genesis_hash) => genesis_hash,
+        None if case_config.force_cluster => {
+            warn!("Could not check the cluster of cache file {}", file_path);
+            return Ok(());
+        }
+        None => {
+            let error = CacheError::ClusterUnknown(file_path.to_string());
+            error!("{:?}", error);
+            return Err(error.into());
+        }
+    };
+
+    if rpc_cluster == cluster {
+        return Ok(());
+    }
+
+    let error = CacheError::ClusterMismatch(
+        file_path.to_string(),
+        cluster_name(cluster),
+        cluster_name(&rpc_cluster),
+    );
+
+    if case_config.force_cluster {
+        warn!("{:?}", error);
+        case_println!(
+            "{}{}",
+            WARNING_EMOJI,
+            style(format!(
+                "Cache file '{}' was created on {}, proceeding on {} (--force-cluster).",
+                file_path,
+                cluster_name(cluster),
+                cluster_name(&rpc_cluster)
+            ))
+            .yellow()
+        );
+        Ok(())
+    } else {
+        error!("{:?}", error);
+        Err(error.into())
+    }
+}
@@ -0,0 +1,433 @@
+pub mod check;
+pub mod cluster;
+pub mod rebuild;
+pub mod remove;
+pub mod repair;
+pub mod restore;
+pub mod sync;
+
+pub use check::*;
+pub use cluster::*;
+pub use rebuild::*;
+pub use remove::*;
+pub use repair::*;
+pub use restore::*;
+pub use sync::*;
+
+use std::{
+    fs,
+    io::Write,
+    ops::{Deref, DerefMut},
+    path::Path,
+};
+
+use anyhow::Result;
+use tars::ConfigLine;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::*, config::CaseConfig, launch::LaunchState, pdas::find_tars_creator_pda,
+    upload::StorageCheck,
+};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Cache {
+    pub program: CacheProgram,
+    pub items: CacheItems,
+    /// Items soft-deleted with `cache remove`, in the order they were removed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<RemovedItem>,
+    /// Steps completed by `launch`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch: Option<LaunchState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<CacheSignature>,
+    #[serde(skip_deserializing, skip_serializing)]
+    pub file_path: String,
+    /// Keypair that signs the cache content when it is written.
+    #[serde(skip)]
+    signer: Option<Keypair>,
+}
+
+/// Signature of the cache content by the project keypair, used to detect changes made
+/// outside of case and caches of other projects.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CacheSignature {
+    pub signer: String,
+    pub signature: String,
+}
+
+impl Cache {
+    pub fn new() -> Self {
+        Cache {
+            program: CacheProgram::new(),
+            items: CacheItems::new(),
+            removed: Vec::new(),
+            launch: None,
+            signature: None,
+            file_path: String::new(),
+            signer: None,
+        }
+    }
+
+    /// Sets the keypair that signs the cache on every write. Fails if the cache was
+    /// signed by a different keypair, since it likely belongs to another project.
+    pub fn set_signer(&mut self, keypair: &Keypair) -> Result<()> {
+        let pubkey = keypair.pubkey().to_string();
+
+        if let Some(signature) = &self.signature {
+            if signature.signer != pubkey {
+                let error = CacheError::SignerMismatch(signature.signer.clone(), pubkey).into();
+                error!("{:?}", error);
+                return Err(error);
+            }
+        }
+
+        self.signer = Some(Keypair::from_bytes(&keypair.to_bytes())?);
+
+        Ok(())
+    }
+
+    /// Returns the content covered by the signature: the canonical JSON of the program
+    /// and items (and removed items and launch state, if any).
+    fn signed_content(&self) -> Result<Vec<u8>> {
+        let mut content = json!({
+            "program": serde_json::to_value(&self.program)?,
+            "items": serde_json::to_value(&self.items)?,
+        });
+
+        // only part of the content when present, so existing signatures remain valid
+        if !self.removed.is_empty() {
+            content["removed"] = serde_json::to_value(&self.removed)?;
+        }
+        if let Some(launch) = &self.launch {
+            content["launch"] = serde_json::to_value(launch)?;
+        }
+
+        Ok(serde_json::to_vec(&content)?)
+    }
+
+    /// Checks that the content matches the signature (unsigned caches are accepted).
+    pub fn verify_signature(&self) -> Result<()> {
+        if let Some(cache_signature) = &self.signature {
+            let valid = match (
+                Pubkey::from_str(&cache_signature.signer),
+                Signature::from_str(&cache_signature.signature),
+            ) {
+                (Ok(signer), Ok(signature)) => {
+                    signature.verify(signer.as_ref(), &self.signed_content()?)
+                }
+                _ => false,
+            };
+
+            if !valid {
+                let error = CacheError::InvalidSignature(self.file_path.clone()).into();
+                error!("{:?}", error);
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn write_to_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        if let Some(signer) = &self.signer {
+            let signature = signer.sign_message(&self.signed_content()?);
+            self.signature = Some(CacheSignature {
+                signer: signer.pubkey().to_string(),
+                signature: signature.to_string(),
+            });
+        } else if self.signature.is_some() {
+            // the content changed without a signer, so the signature would not match
+            warn!("Removing the signature of cache file {}", self.file_path);
+            self.signature = None;
+        }
+
+        let c = serde_json::to_string(&self)?;
+        // written to a temporary file first so that aborting while the cache is being
+        // synced does not leave a truncated file behind
+        let temp = path.as_ref().with_extension("json.tmp");
+        let mut f = File::create(&temp)?;
+        f.write_all(c.as_bytes())?;
+        fs::rename(&temp, path)?;
+
+        Ok(())
+    }
+
+    pub fn sync_file(&mut self) -> Result<()> {
+        let file_path = self.file_path.clone();
+        self.write_to_file(Path::new(&file_path))
+    }
+
+    /// Returns the number of items in the cache and their upload/deploy status.
+    pub fn stats(&self) -> CacheStats {
+        let mut stats = CacheStats {
+            items: self.items.len(),
+            ..Default::default()
+        };
+
+        for item in self.items.values() {
+            if !item.image_link.is_empty() && !item.metadata_link.is_empty() {
+                stats.uploaded += 1;
+            }
+            if item.on_chain {
+                stats.on_chain += 1;
+            }
+        }
+
+        stats
+    }
+
+    /// Soft-deletes the item at `index`, moving it to the removed section and shifting
+    /// the indices of the items after it down by one.
+    pub fn remove_item(&mut self, index: u32) -> Result<()> {
+        self.check_off_chain(index)?;
+
+        let item = match self.items.shift_remove(&index.to_string()) {
+            Some(item) => item,
+            None => return Err(CacheError::ItemNotFound(index).into()),
+        };
+
+        self.shift_indices(index, -1);
+        self.removed.push(RemovedItem { index, item });
+
+        Ok(())
+    }
+
+    /// Restores the last item removed from `index` (or the last removed item), shifting
+    /// the indices of the items from its position up by one. Returns the index of the
+    /// restored item.
+    pub fn restore_item(&mut self, index: Option<u32>) -> Result<u32> {
+        let position = match index {
+            Some(index) => self
+                .removed
+                .iter()
+                .rposition(|removed| removed.index == index),
+            None => self.removed.len().checked_sub(1),
+        }
+        .ok_or(CacheError::RemovedItemNotFound(index))?;
+
+        // an item removed from the end is restored after the last item
+        let count = self.items.keys().filter(|key| *key != "-1").count() as u32;
+        let index = self.removed[position].index.min(count);
+
+        self.check_off_chain(index)?;
+
+        let removed = self.removed.remove(position);
+        self.shift_indices(index, 1);
+        self.items.insert(index.to_string(), removed.item);
+        self.items
+            .sort_by(|a, _, b, _| item_index(a).cmp(&item_index(b)));
+
+        Ok(index)
+    }
+
+    /// Fails if an item from `index` is on-chain, since its config line would no longer
+    /// match the cache after the indices are shifted.
+    fn check_off_chain(&self, index: u32) -> Result<()> {
+        for (key, item) in self.items.iter() {
+            if item.on_chain && item_index(key) >= index as i64 {
+                return Err(CacheError::ItemOnChain(index, key.clone()).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds `offset` to the indices of the items from `index`.
+    fn shift_indices(&mut self, index: u32, offset: i64) {
+        let items: Vec<(String, CacheItem)> = self
+            .items
+            .drain(..)
+            .map(|(key, item)| match item_index(&key) {
+                i if i >= index as i64 => ((i + offset).to_string(), item),
+                _ => (key, item),
+            })
+            .collect();
+
+        self.items.extend(items);
+    }
+}
+
+/// Returns the index of an item key (-1 for the collection item).
+fn item_index(key: &str) -> i64 {
+    key.parse().unwrap_or(-1)
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub items: usize,
+    pub uploaded: usize,
+    pub on_chain: usize,
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CacheProgram {
+    #[serde(rename = "tars")]
+    pub tars: String,
+    #[serde(rename = "tarsCreator")]
+    pub tars_creator: String,
+    #[serde(rename = "collectionMint")]
+    pub collection_mint: String,
+    /// Genesis hash of the cluster the cache was created on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster: Option<String>,
+}
+
+impl CacheProgram {
+    pub fn new() -> Self {
+        CacheProgram {
+            tars: String::new(),
+            tars_creator: String::new(),
+            collection_mint: String::new(),
+            cluster: None,
+        }
+    }
+
+    pub fn new_from_cm(tars: &Pubkey) -> Self {
+        let (tars_creator_pda, _creator_bump) =
+            find_tars_creator_pda(tars);
+        CacheProgram {
+            tars: tars.to_string(),
+            tars_creator: tars_creator_pda.to_string(),
+            collection_mint: String::new(),
+            cluster: None,
+        }
+    }
+}
+
+impl Default for CacheProgram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CacheItems(pub IndexMap<String, CacheItem>);
+
+impl Deref for CacheItems {
+    type Target = IndexMap<String, CacheItem>; // Our wrapper struct will coerce into Option
+    fn deref(&self) -> &IndexMap<String, CacheItem> {
+        &self.0 // We just extract the inner element
+    }
+}
+
+impl DerefMut for CacheItems {
+    fn deref_mut(&mut self) -> &mut IndexMap<String, CacheItem> {
+        &mut self.0
+    }
+}
+
+impl CacheItems {
+    pub fn new() -> Self {
+        CacheItems(IndexMap::new())
+    }
+}
+impl Default for CacheItems {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CacheItem {
+    pub name: String,
+    #[serde(default = "String::default")]
+    pub image_hash: String,
+    pub image_link: String,
+    #[serde(default = "String::default")]
+    pub metadata_hash: String,
+    pub metadata_link: String,
+    #[serde(rename = "onChain")]
+    pub on_chain: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub animation_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub animation_link: Option<String>,
+    /// Last comparison of the stored files with the local files (`upload --verify-storage`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage_check: Option<StorageCheck>,
+}
+
+/// Cache item soft-deleted from `index`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RemovedItem {
+    pub index: u32,
+    #[serde(flatten)]
+    pub item: CacheItem,
+}
+
+impl CacheItem {
+    pub fn to_config_line(&self) -> Option<ConfigLine> {
+        if !self.on_chain {
+            Some(ConfigLine {
+                name: self.name.clone(),
+                uri: self.metadata_link.clone(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+pub fn load_cache(cache_file_path: &str, create: bool) -> Result<Cache> {
+    let cache_file_path = Path::new(cache_file_path);
+    if !cache_file_path.exists() {
+        if create {
+            // if the cache file does not exist, creates a new Cache object
+            let mut cache = Cache::new();
+            cache.file_path = path_to_string(cache_file_path)?;
+            Ok(cache)
+        } else {
+            let cache_file_string = path_to_string(cache_file_path)?;
+            let error = CacheError::CacheFileNotFound(cache_file_string).into();
+            error!("{:?}", error);
+            Err(error)
+        }
+    } else {
+        info!("Cache exists, loading...");
+        let file = match File::open(cache_file_path) {
+            Ok(file) => file,
+            Err(err) => {
+                let cache_file_string = path_to_string(cache_file_path)?;
+                let error =
+                    CacheError::FailedToOpenCacheFile(cache_file_string, err.to_string()).into();
+                error!("{:?}", error);
+                return Err(error);
+            }
+        };
+
+        let mut cache: Cache = match serde_json::from_reader(file) {
+            Ok(cache) => cache,
+            Err(err) => {
+                let error = CacheError::CacheFileWrongFormat(err.to_string()).into();
+                error!("{:?}", error);
+                return Err(error);
+            }
+        };
+        cache.file_path = path_to_string(cache_file_path)?;
+        cache.verify_signature()?;
+
+        Ok(cache)
+    }
+}
+
+/// Loads the cache of a command that runs on the cluster of the config, checking that
+/// the cache was created on it.
+pub fn load_cluster_cache(
+    case_config: &CaseConfig,
+    cache_file_path: &str,
+    create: bool,
+) -> Result<Cache> {
+    let mut cache = load_cache(cache_file_path, create)?;
+    check_cache_cluster(case_config, &mut cache)?;
+
+    Ok(cache)
+}
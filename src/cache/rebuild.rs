@@ -0,0 +1,197 @@
+use console::style;
+use futures::{stream, StreamExt};
+use serde::Serialize;
+
+use crate::{
+    cache::cache_from_tars,
+    common::*,
+    ipfs::{is_ipfs, IpfsGateways},
+    setup::genesis_hash,
+    tars::TARS_ID,
+    utils::{progress_bar_with_style, spinner_with_style},
+};
+
+pub struct RebuildCacheArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub tars: String,
+    pub force: bool,
+    pub ipfs_gateways: Vec<String>,
+}
+
+/// Result of the cache rebuild command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebuildCacheOutcome {
+    pub cache: String,
+    pub tars: String,
+    pub collection_mint: Option<String>,
+    /// Number of config lines read from the tars.
+    pub items: usize,
+    /// Number of items with the image (and animation) link read from their metadata.
+    pub links_restored: usize,
+    /// Items whose metadata could not be read.
+    pub failed: Vec<String>,
+}
+
+pub async fn process_rebuild_cache(args: RebuildCacheArgs) -> Result<RebuildCacheOutcome> {
+    if Path::new(&args.cache).exists() && !args.force {
+        return Err(anyhow!(
+            "Cache file '{}' already exists, use --force to overwrite it",
+            args.cache
+        ));
+    }
+
+    case_println!(
+        "{} {}Reading tars",
+        style("[1/3]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let tars_pubkey = resolve_pubkey(&args.tars, &args.global.address_book)?;
+    let gateways = IpfsGateways::from_options(&args.ipfs_gateways)?;
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+    let (mut cache, tars, missing) = cache_from_tars(&program, &tars_pubkey)?;
+    pb.finish_and_clear();
+
+    if tars.data.hidden_settings.is_some() {
+        return Err(anyhow!(
+            "Tars {} uses hidden settings, the items to reveal are not on-chain and cannot be rebuilt",
+            tars_pubkey
+        ));
+    }
+
+    case_println!("{} {}", style("Config lines:").bold(), cache.items.len());
+
+    case_println!(
+        "\n{} {}Reading metadata",
+        style("[2/3]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let pb = progress_bar_with_style(cache.items.len() as u64);
+    let http_client = &HttpClient::new();
+    let (gateways, progress) = (&gateways, &pb);
+
+    let results: Vec<(String, Result<(String, Option<String>)>)> = stream::iter(cache.items.iter())
+        .map(|(index, item)| async move {
+            let links = media_links(http_client, gateways, &item.metadata_link).await;
+            progress.inc(1);
+            (index.clone(), links)
+        })
+        .buffer_unordered(PARALLEL_LIMIT)
+        .collect()
+        .await;
+
+    pb.finish_and_clear();
+
+    let mut outcome = RebuildCacheOutcome {
+        cache: args.cache.clone(),
+        tars: tars_pubkey.to_string(),
+        collection_mint: Some(cache.program.collection_mint.clone()).filter(|m| !m.is_empty()),
+        items: cache.items.keys().filter(|key| *key != "-1").count(),
+        ..Default::default()
+    };
+
+    for (index, result) in results {
+        match result {
+            Ok((image_link, animation_link)) => {
+                if let Some(item) = cache.items.get_mut(&index) {
+                    item.image_link = image_link;
+                    item.animation_link = animation_link;
+                    outcome.links_restored += 1;
+                }
+            }
+            Err(err) => {
+                warn!("Failed to read the metadata of item {}: {}", index, err);
+                outcome.failed.push(format!("Item {}: {}", index, err));
+            }
+        }
+    }
+    outcome.failed.sort();
+
+    case_println!(
+        "\n{} {}Writing cache",
+        style("[3/3]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    cache.file_path = args.cache.clone();
+    cache.program.cluster = genesis_hash(&case_config);
+    cache.set_signer(&case_config.keypair)?;
+    cache.sync_file()?;
+
+    if missing > 0 {
+        case_println!(
+            "{}",
+            style(format!(
+                "{} config line(s) not written yet, run 'deploy' after restoring their items.",
+                missing
+            ))
+            .yellow()
+        );
+    }
+
+    if !outcome.failed.is_empty() {
+        for failed in &outcome.failed {
+            case_println!("{} {}", WARNING_EMOJI, failed);
+        }
+        case_println!(
+            "{}",
+            style(format!(
+                "The image link of {} item(s) could not be read, run 'cache rebuild --force' again to retry.",
+                outcome.failed.len()
+            ))
+            .yellow()
+        );
+    }
+
+    case_println!(
+        "{} {} item(s) written to '{}'",
+        COMPLETE_EMOJI,
+        outcome.items,
+        outcome.cache
+    );
+    case_println!(
+        "{}",
+        style("File hashes are not on-chain and were left empty.").dim()
+    );
+
+    Ok(outcome)
+}
+
+/// Returns the image and animation links of the metadata at `uri`.
+async fn media_links(
+    client: &HttpClient,
+    gateways: &IpfsGateways,
+    uri: &str,
+) -> Result<(String, Option<String>)> {
+    let content = if is_ipfs(uri) {
+        gateways.download(client, uri).await?
+    } else {
+        let response = client.get(uri).send().await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("metadata request failed ({})", response.status()));
+        }
+        response.bytes().await?.to_vec()
+    };
+
+    let metadata: Value = serde_json::from_slice(&content)
+        .map_err(|err| anyhow!("invalid metadata JSON ({})", err))?;
+
+    let image = metadata["image"]
+        .as_str()
+        .filter(|image| !image.is_empty())
+        .ok_or_else(|| anyhow!("metadata has no image"))?;
+    let animation = metadata["animation_url"].as_str().map(str::to_string);
+
+    Ok((image.to_string(), animation))
+}
@@ -0,0 +1,114 @@
+use console::style;
+use serde::Serialize;
+
+use crate::{cache::load_cache, common::*, config::get_config_data, setup::local_keypair};
+
+pub struct RemoveCacheItemArgs {
+    pub keypair: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub config: String,
+    pub index: u32,
+}
+
+/// Result of the cache remove and restore commands.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheItemOutcome {
+    pub index: u32,
+    pub name: String,
+    /// Number of items in the cache (without the collection item).
+    pub items: usize,
+    pub removed: usize,
+}
+
+/// Removes an item from the cache. Only the cache file is changed, so no connection is
+/// made to the cluster.
+pub fn process_remove_cache_item(args: RemoveCacheItemArgs) -> Result<CacheItemOutcome> {
+    let mut cache = load_cache(&args.cache, false)?;
+    sign_with_keypair(&mut cache, args.keypair, &args.global)?;
+
+    cache.remove_item(args.index)?;
+    cache.sync_file()?;
+
+    let name = cache
+        .removed
+        .last()
+        .map(|removed| removed.item.name.clone())
+        .unwrap_or_default();
+
+    case_println!(
+        "{} Removed item {} ({}) from '{}'",
+        COMPLETE_EMOJI,
+        args.index,
+        name,
+        args.cache
+    );
+
+    Ok(item_counts(
+        &cache,
+        &args.config,
+        &args.global.address_book,
+        args.index,
+        name,
+    ))
+}
+
+/// Keeps a signed cache signed: the keypair is only read when the cache has a signature.
+pub(crate) fn sign_with_keypair(
+    cache: &mut Cache,
+    keypair: Option<String>,
+    global: &GlobalOptions,
+) -> Result<()> {
+    if cache.signature.is_some() {
+        cache.set_signer(&local_keypair(keypair, global)?)?;
+    }
+
+    Ok(())
+}
+
+/// Prints the number of items after a change and warns when it no longer matches the
+/// `number` of the config file.
+pub(crate) fn item_counts(
+    cache: &Cache,
+    config: &str,
+    address_book: &str,
+    index: u32,
+    name: String,
+) -> CacheItemOutcome {
+    let outcome = CacheItemOutcome {
+        index,
+        name,
+        items: cache.items.keys().filter(|key| *key != "-1").count(),
+        removed: cache.removed.len(),
+    };
+
+    case_println!(
+        "{} {} ({} removed)",
+        style("Items:").bold(),
+        outcome.items,
+        outcome.removed
+    );
+
+    // hidden drops do not need a cache item for every NFT
+    if let Ok(config_data) = get_config_data(config, address_book) {
+        if config_data.hidden_settings.is_none() && config_data.number != outcome.items as u64 {
+            case_println!(
+                "{}",
+                style(format!(
+                    "{}The config file '{}' has number {}, which does not match the {} item(s) in the cache.",
+                    WARNING_EMOJI, config, config_data.number, outcome.items
+                ))
+                .yellow()
+            );
+        }
+    }
+
+    case_println!(
+        "{}",
+        style("Asset files are not renamed: update the assets directory before running 'upload'.")
+            .dim()
+    );
+
+    outcome
+}
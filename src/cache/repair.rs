@@ -0,0 +1,83 @@
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    cache::{expected_on_chain, load_cluster_cache},
+    common::*,
+    tars::TARS_ID,
+    utils::spinner_with_style,
+};
+
+pub struct RepairCacheArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+}
+
+/// Result of the cache repair command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairCacheOutcome {
+    /// Items whose on-chain flag was changed.
+    pub repaired: Vec<String>,
+    pub cache: CacheStats,
+}
+
+pub fn process_repair_cache(args: RepairCacheArgs) -> Result<RepairCacheOutcome> {
+    case_println!(
+        "{} {}Rebuilding on-chain flags",
+        style("[1/1]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let mut cache = load_cluster_cache(&case_config, &args.cache, false)?;
+    cache.set_signer(&case_config.keypair)?;
+
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+
+    let pb = spinner_with_style();
+    pb.set_message("Reading config lines...");
+    let (_, expected) = expected_on_chain(&program, &cache)?;
+    pb.finish_and_clear();
+
+    let mut outcome = RepairCacheOutcome::default();
+
+    for (index, on_chain) in expected {
+        let item = cache.items.get_mut(&index).unwrap();
+
+        if item.on_chain != on_chain {
+            case_println!(
+                "- Item {}: {}",
+                index,
+                if on_chain {
+                    "marked on-chain"
+                } else {
+                    "marked not on-chain"
+                }
+            );
+            item.on_chain = on_chain;
+            outcome.repaired.push(index);
+        }
+    }
+
+    if outcome.repaired.is_empty() {
+        case_println!(
+            "{}",
+            style("All on-chain flags are correct.").green().bold()
+        );
+    } else {
+        cache.sync_file()?;
+        case_println!(
+            "\n{} item(s) updated in '{}'. Run 'deploy' to write the missing config lines.",
+            outcome.repaired.len(),
+            args.cache
+        );
+    }
+
+    outcome.cache = cache.stats();
+
+    Ok(outcome)
+}
@@ -0,0 +1,40 @@
+use crate::{
+    cache::{item_counts, load_cache, sign_with_keypair, CacheItemOutcome},
+    common::*,
+};
+
+pub struct RestoreCacheItemArgs {
+    pub keypair: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub config: String,
+    /// Index the item was removed from (defaults to the last removed item).
+    pub index: Option<u32>,
+}
+
+/// Restores a removed item to the cache, without connecting to the cluster.
+pub fn process_restore_cache_item(args: RestoreCacheItemArgs) -> Result<CacheItemOutcome> {
+    let mut cache = load_cache(&args.cache, false)?;
+    sign_with_keypair(&mut cache, args.keypair, &args.global)?;
+
+    let index = cache.restore_item(args.index)?;
+    cache.sync_file()?;
+
+    let name = cache.items[&index.to_string()].name.clone();
+
+    case_println!(
+        "{} Restored item {} ({}) to '{}'",
+        COMPLETE_EMOJI,
+        index,
+        name,
+        args.cache
+    );
+
+    Ok(item_counts(
+        &cache,
+        &args.config,
+        &args.global.address_book,
+        index,
+        name,
+    ))
+}
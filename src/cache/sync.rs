@@ -0,0 +1,166 @@
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    cache::CacheProgram,
+    common::*,
+    pdas::{get_collection_pda, get_metadata_pda},
+    setup::genesis_hash,
+    tars::{config_line, TARS_ID},
+    utils::spinner_with_style,
+};
+
+pub struct SyncCacheArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub tars: String,
+    pub force: bool,
+}
+
+/// Result of the cache sync command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncCacheOutcome {
+    pub cache: String,
+    pub tars: String,
+    pub collection_mint: Option<String>,
+    /// Number of config lines read from the tars.
+    pub items: usize,
+}
+
+pub fn process_sync_cache(args: SyncCacheArgs) -> Result<SyncCacheOutcome> {
+    if Path::new(&args.cache).exists() && !args.force {
+        return Err(anyhow!(
+            "Cache file '{}' already exists, use --force to overwrite it",
+            args.cache
+        ));
+    }
+
+    case_println!(
+        "{} {}Reading tars",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let tars_pubkey = resolve_pubkey(&args.tars, &args.global.address_book)?;
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+    let (mut cache, tars, missing) = cache_from_tars(&program, &tars_pubkey)?;
+    pb.finish_and_clear();
+
+    case_println!(
+        "\n{} {}Rebuilding cache",
+        style("[2/2]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    cache.file_path = args.cache.clone();
+    cache.program.cluster = genesis_hash(&case_config);
+    cache.set_signer(&case_config.keypair)?;
+    cache.sync_file()?;
+
+    let outcome = SyncCacheOutcome {
+        cache: args.cache,
+        tars: tars_pubkey.to_string(),
+        collection_mint: Some(cache.program.collection_mint.clone()).filter(|m| !m.is_empty()),
+        items: cache.items.keys().filter(|key| *key != "-1").count(),
+    };
+
+    if tars.data.hidden_settings.is_some() {
+        case_println!(
+            "{}",
+            style("Tars uses hidden settings: the items to reveal are not on-chain and cannot be rebuilt.")
+                .yellow()
+        );
+    } else if missing > 0 {
+        case_println!(
+            "{}",
+            style(format!(
+                "{} config line(s) not written yet, run 'deploy' after restoring their items.",
+                missing
+            ))
+            .yellow()
+        );
+    }
+
+    case_println!(
+        "{} {} item(s) written to '{}'",
+        COMPLETE_EMOJI,
+        outcome.items,
+        outcome.cache
+    );
+    case_println!(
+        "{}",
+        style("Image links and file hashes are not on-chain and were left empty.").dim()
+    );
+
+    Ok(outcome)
+}
+
+/// Reads the collection and config lines of a tars into a new cache, with every item
+/// on-chain. Returns the cache, the tars and the number of config lines not written.
+pub(crate) fn cache_from_tars(
+    program: &Program,
+    tars_pubkey: &Pubkey,
+) -> Result<(Cache, Tars, usize)> {
+    let data = program.rpc().get_account_data(tars_pubkey).map_err(|err| {
+        anyhow!(
+            "Failed to get the account data of tars {}: {}",
+            tars_pubkey,
+            err
+        )
+    })?;
+    let tars = Tars::try_deserialize(&mut data.as_slice())?;
+
+    let mut cache = Cache::new();
+    cache.program = CacheProgram::new_from_cm(tars_pubkey);
+
+    // the collection item is rebuilt from the metadata of the collection NFT
+    if let Ok((_, collection_pda)) = get_collection_pda(tars_pubkey, program) {
+        let mint = collection_pda.mint;
+        cache.program.collection_mint = mint.to_string();
+
+        if let Ok((_, metadata)) = get_metadata_pda(&mint, program) {
+            cache.items.insert(
+                "-1".to_string(),
+                synced_item(&metadata.data.name, &metadata.data.uri),
+            );
+        }
+    }
+
+    let mut missing = 0;
+
+    for index in 0..tars.data.items_available as usize {
+        match config_line(&data, index) {
+            Some((name, uri)) => {
+                cache
+                    .items
+                    .insert(index.to_string(), synced_item(&name, &uri));
+            }
+            None => missing += 1,
+        }
+    }
+
+    Ok((cache, tars, missing))
+}
+
+/// Returns an on-chain cache item with the name and metadata link of a config line.
+fn synced_item(name: &str, uri: &str) -> CacheItem {
+    CacheItem {
+        name: name.trim_matches(char::from(0)).to_string(),
+        image_hash: String::new(),
+        image_link: String::new(),
+        metadata_hash: String::new(),
+        metadata_link: uri.trim_matches(char::from(0)).to_string(),
+        on_chain: true,
+        animation_hash: None,
+        animation_link: None,
+        storage_check: None,
+    }
+}
@@ -0,0 +1,36 @@
+use serde::Serialize;
+
+use crate::common::*;
+
+/// Serializes a value in the canonical JSON form used whenever metadata files are
+/// rewritten: object keys sorted recursively, two-space indentation and a trailing
+/// newline. The same metadata always produces the same bytes, so rewrites only show
+/// the fields that changed in a diff and the hashes of the files are reproducible.
+pub fn to_canonical_json<T: Serialize>(value: &T) -> Result<String> {
+    let value = canonicalize(serde_json::to_value(value)?);
+    let mut content = serde_json::to_string_pretty(&value)?;
+    content.push('\n');
+
+    Ok(content)
+}
+
+/// Sorts the keys of every object of the value. The order does not depend on the map
+/// implementation used by serde_json (its `preserve_order` feature keeps the insertion
+/// order instead).
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut entries: Vec<(String, Value)> = object.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, canonicalize(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(canonicalize).collect()),
+        value => value,
+    }
+}
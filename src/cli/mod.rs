@@ -1,14 +1,74 @@
 use clap::{Parser, Subcommand};
 
-use crate::constants::{DEFAULT_ASSETS, DEFAULT_CACHE, DEFAULT_CONFIG};
+use crate::{
+    config::ConfigFormat,
+    constants::{
+        CONFIG_LINES_BATCH_SIZE, DEFAULT_ADDRESS_BOOK, DEFAULT_ASSETS, DEFAULT_CACHE,
+        DEFAULT_CONFIG, DEFAULT_CONFIG_LINES, DEFAULT_DENYLIST, DEFAULT_DEPLOYMENTS,
+        DEFAULT_MAX_BUFFER_MB, DEFAULT_MAX_OPEN_FILES, DEFAULT_MINT_CONFIRM_SOL, DEFAULT_PLAN,
+        DEFAULT_REHEARSAL_BURST, DEFAULT_REHEARSAL_CONCURRENCY, DEFAULT_RESERVE_RECEIPTS,
+        DEFAULT_SANDBOX_DIR, DEFAULT_SELFTEST_DIR, DEFAULT_SHUFFLE_PROOF,
+        DEFAULT_WHITELIST_PROGRESS, OFFLINE_TX_DIR, PARALLEL_LIMIT,
+    },
+    launch::LaunchStep,
+    output::OutputFormat,
+};
 
 #[derive(Parser)]
 #[clap(author, version, about)]
 pub struct Cli {
-    /// Log level: trace, debug, info, warn, error, off
+    /// Log level (trace, debug, info, warn, error, off) or module filters, e.g.
+    /// "case::upload=debug,solana_client=warn"
     #[clap(short, long, global = true)]
     pub log_level: Option<String>,
 
+    /// Allow a keypair file readable by other users to be used on mainnet
+    #[clap(long, global = true)]
+    pub insecure_keypair: bool,
+
+    /// Use a cache file on a different cluster than the one it was created on, or when
+    /// the cluster of the RPC cannot be checked
+    #[clap(long, global = true)]
+    pub force_cluster: bool,
+
+    /// Derivation path of keypairs read from a seed phrase (e.g. "m/44'/501'/0'/0'")
+    #[clap(long, global = true)]
+    pub derivation_path: Option<String>,
+
+    /// Websocket url of the RPC, defaults to the one of the Solana config or is derived
+    /// from the RPC url
+    #[clap(long, global = true)]
+    pub ws_url: Option<String>,
+
+    /// Write a redacted diagnostic report to a local file if the command fails
+    #[clap(long, global = true)]
+    pub report_errors: bool,
+
+    /// Output format: text or json (json suppresses spinners and prints the result)
+    #[clap(long, global = true, value_enum, default_value = "text")]
+    pub output: OutputFormat,
+
+    /// Profile namespacing the default cache and config files (e.g. "cache.devnet.json"),
+    /// defaults to the one set with 'profile switch'
+    #[clap(long, global = true)]
+    pub profile: Option<String>,
+
+    /// Address book file used to resolve "@label" values given in place of pubkeys
+    #[clap(long, global = true, default_value = DEFAULT_ADDRESS_BOOK)]
+    pub address_book: String,
+
+    /// Deny list file of sanctioned or flagged addresses skipped by snapshots and transfers
+    #[clap(long, global = true, default_value = DEFAULT_DENYLIST)]
+    pub denylist: String,
+
+    /// Registry of every tars created by the project
+    #[clap(long, global = true, default_value = DEFAULT_DEPLOYMENTS)]
+    pub deployments: String,
+
+    /// File the transactions planned by a dry run are written to, as canonical JSON
+    #[clap(long, global = true, default_value = DEFAULT_PLAN)]
+    pub plan_file: String,
+
     #[clap(subcommand)]
     pub command: Commands,
 }
@@ -32,10 +92,62 @@ pub enum Commands {
         /// Path to the directory with the assets
         #[clap(default_value = DEFAULT_ASSETS)]
         assets_dir: String,
+
+        /// Format of the config file [default: json or the config file extension]
+        #[clap(long, value_enum)]
+        format: Option<ConfigFormat>,
+    },
+
+    /// Estimate the SOL needed to launch: storage, tars rent and transaction fees
+    Estimate {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the directory with the assets
+        #[clap(default_value = DEFAULT_ASSETS)]
+        assets_dir: String,
+
+        /// Path to the config file
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+    },
+
+    /// Estimate the transactions, fees and time of the reveal of a hidden drop
+    EstimateReveal {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of tars (defaults to the one in the cache)
+        #[clap(long)]
+        tars: Option<String>,
+
+        /// Number of concurrent transactions to estimate the time for (can be repeated)
+        /// [default: 1, 5, 10, 25 and 45]
+        #[clap(long)]
+        concurrency: Vec<usize>,
+
+        /// Number of minted NFTs checked for update authority
+        #[clap(long, default_value_t = 10)]
+        sample: usize,
     },
+
     /// Create a tars deployment from assets
     Launch {
-        /// Path to the directory with the assets to upload
+        /// Path to the directory (or .zip/.tar.gz archive) with the assets to upload
         #[clap(default_value = DEFAULT_ASSETS)]
         assets_dir: String,
 
@@ -62,9 +174,21 @@ pub enum Commands {
         /// Skip collection validate prompt
         #[clap(long)]
         skip_collection_prompt: bool,
+
+        /// Skip the collection step (e.g. on a cluster without the token metadata program)
+        #[clap(long)]
+        skip_collection: bool,
+
+        /// Restart the launch from this step, even if a previous launch completed it
+        #[clap(long, value_enum)]
+        from_step: Option<LaunchStep>,
     },
     /// Mint one NFT from tars
     Mint {
+        /// Path to the config file, defaults to "config.json"
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
         /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
@@ -84,6 +208,108 @@ pub enum Commands {
         /// Address of tars to mint from.
         #[clap(long)]
         tars: Option<String>,
+
+        /// Directory of payer keypair files to rotate across concurrent mints
+        #[clap(long)]
+        keypairs: Option<String>,
+
+        /// Maximum amount of SOL (fees, rent and price) to spend on the mints
+        #[clap(long)]
+        max_fee: Option<f64>,
+
+        /// Ask for confirmation when the forecast cost of a bulk mint exceeds this amount of SOL
+        #[clap(long, default_value_t = DEFAULT_MINT_CONFIRM_SOL)]
+        confirm_above: f64,
+
+        /// Start a bulk mint without asking for confirmation
+        #[clap(short, long)]
+        yes: bool,
+
+        /// Compute unit limit of mint transactions, overrides "computeUnits.mint"
+        #[clap(long)]
+        compute_units: Option<u32>,
+
+        /// Append a receipt (mint, owner, signature, timestamp) of every successful mint to this JSON file
+        #[clap(long)]
+        to_file: Option<String>,
+
+        /// Gateway token account of the payer for a tars with gatekeeper settings [default: derived from the payer]
+        #[clap(long)]
+        gateway_token: Option<String>,
+    },
+
+    /// Mint items to a team wallet before go-live, writing a receipt for each
+    Reserve {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of tars (defaults to the one in the cache)
+        #[clap(long)]
+        tars: Option<String>,
+
+        /// Number of items to reserve
+        #[clap(short, long)]
+        number: u64,
+
+        /// Team wallet receiving the items
+        #[clap(long)]
+        wallet: String,
+
+        /// File the receipts are added to
+        #[clap(long, default_value = DEFAULT_RESERVE_RECEIPTS)]
+        receipts: String,
+    },
+
+    /// Simulate a burst of mints at a scheduled time to rehearse go-live (nothing is sent)
+    RehearseGolive {
+        /// Path to the config file, defaults to "config.json"
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of tars (defaults to the one in the cache)
+        #[clap(long)]
+        tars: Option<String>,
+
+        /// Time of the rehearsal, as a RFC2822/RFC3339 date or UNIX timestamp [default: the start of the next minute]
+        #[clap(long)]
+        at: Option<String>,
+
+        /// Number of mints to simulate
+        #[clap(long, default_value_t = DEFAULT_REHEARSAL_BURST)]
+        burst: u64,
+
+        /// Number of mints simulated at the same time
+        #[clap(long, default_value_t = DEFAULT_REHEARSAL_CONCURRENCY)]
+        concurrency: usize,
+
+        /// Compute unit limit of mint transactions, overrides "computeUnits.mint"
+        #[clap(long)]
+        compute_units: Option<u32>,
+
+        /// Priority fee (in lamports) of mint transactions, needs a compute unit limit
+        #[clap(long, default_value_t = 0)]
+        priority_fee: u32,
     },
 
     /// Update the tars config on-chain
@@ -111,6 +337,76 @@ pub enum Commands {
         /// Address of tars to update.
         #[clap(long)]
         tars: Option<String>,
+
+        /// Show the changes that would be applied without sending any transaction
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Apply the changes without asking for confirmation
+        #[clap(short, long)]
+        yes: bool,
+
+        /// Write the transactions to files to be signed offline by this authority (see 'tx sign')
+        #[clap(long, value_name = "AUTHORITY")]
+        sign_offline: Option<String>,
+
+        /// Durable nonce account of the offline authority used by a transaction written for
+        /// offline signing, so that it does not expire (repeat for each transaction)
+        #[clap(long = "nonce", value_name = "ACCOUNT", requires = "sign_offline")]
+        nonces: Vec<String>,
+
+        /// Move the cache to a new tars when the number of items changes (the account of
+        /// a tars cannot be resized)
+        #[clap(long, conflicts_with_all = &["new_authority", "sign_offline"])]
+        new_tars: bool,
+    },
+
+    /// Switch the payment of a tars between SOL and an SPL token, updating the config file
+    MigratePayment {
+        /// Path to the config file, defaults to "config.json"
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of tars to update.
+        #[clap(long)]
+        tars: Option<String>,
+
+        /// Mint of the new payment token, or "sol"
+        #[clap(long)]
+        to: String,
+
+        /// New price in units of the new payment, defaults to the current price
+        #[clap(long)]
+        price: Option<f64>,
+
+        /// Token account that receives the payments, defaults to the associated token
+        /// account of the keypair
+        #[clap(long)]
+        token_account: Option<String>,
+
+        /// Wallet that receives SOL payments, defaults to the keypair
+        #[clap(long)]
+        treasury: Option<String>,
+
+        /// Show the changes that would be applied without sending any transaction
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Apply the changes without asking for confirmation
+        #[clap(short, long)]
+        yes: bool,
     },
 
     /// Deploy cache items into tars config on-chain
@@ -130,11 +426,82 @@ pub enum Commands {
         /// Path to the cache file, defaults to "cache.json"
         #[clap(long, default_value = DEFAULT_CACHE)]
         cache: String,
+
+        /// Show the changes that would be applied without sending any transaction
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Maximum amount of SOL (fees and rent) to spend on the deploy
+        #[clap(long)]
+        max_fee: Option<f64>,
+
+        /// Maximum number of config lines per transaction (reduced automatically when
+        /// transactions exceed the size limit)
+        #[clap(long, default_value_t = CONFIG_LINES_BATCH_SIZE)]
+        batch_size: usize,
+
+        /// Write the transactions to files to be signed offline by this authority (see 'tx sign')
+        #[clap(long, value_name = "AUTHORITY")]
+        sign_offline: Option<String>,
+
+        /// Durable nonce account of the offline authority used by a transaction written for
+        /// offline signing, so that it does not expire (repeat for each transaction)
+        #[clap(long = "nonce", value_name = "ACCOUNT", requires = "sign_offline")]
+        nonces: Vec<String>,
+
+        /// Compute unit limit of config line transactions, overrides "computeUnits.deploy"
+        #[clap(long)]
+        compute_units: Option<u32>,
+
+        /// Deploy without creating or setting the collection NFT (e.g. on a cluster without
+        /// the token metadata program)
+        #[clap(long)]
+        skip_collection: bool,
+    },
+
+    /// Generate the assets by composing the trait images of the layers, using the
+    /// "generation" settings of the config
+    Generate {
+        /// Directory the generated assets are written to, defaults to "assets"
+        #[clap(default_value = DEFAULT_ASSETS)]
+        assets_dir: String,
+
+        /// Path to the config file, defaults to "config.json"
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Number of items to generate [default: the number of the config]
+        #[clap(short, long)]
+        number: Option<u64>,
+
+        /// Seed of the random choice of the traits, to generate the same items again
+        #[clap(long)]
+        seed: Option<u64>,
+
+        /// Replace the asset files of the assets directory
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Resize, optimize and convert the images of the assets into a separate directory,
+    /// using the "imageProcessing" settings of the config
+    ProcessImages {
+        /// Assets directory with the images to process, defaults to "assets"
+        #[clap(default_value = DEFAULT_ASSETS)]
+        assets_dir: String,
+
+        /// Path to the config file, defaults to "config.json"
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Directory the processed assets are written to, overrides "imageProcessing.outputDir"
+        #[clap(short, long)]
+        output_dir: Option<String>,
     },
 
     /// Upload assets to storage and creates the cache config
     Upload {
-        /// Path to the directory with the assets to upload
+        /// Path to the directory (or .zip/.tar.gz archive) with the assets to upload
         #[clap(default_value = DEFAULT_ASSETS)]
         assets_dir: String,
 
@@ -153,6 +520,44 @@ pub enum Commands {
         /// Path to the cache file
         #[clap(long, default_value = DEFAULT_CACHE)]
         cache: String,
+
+        /// Overwrite the symbol and creators of the metadata files with the config values
+        #[clap(long)]
+        inject_metadata: bool,
+
+        /// Maximum number of concurrent uploads
+        #[clap(long, default_value_t = PARALLEL_LIMIT)]
+        concurrency: usize,
+
+        /// URL of a remote staging listing to upload from instead of the assets directory
+        /// (a JSON array of file URLs, e.g. S3 presigned URLs, or one URL per line)
+        #[clap(long)]
+        assets_url: Option<String>,
+
+        /// Fail instead of funding the Bundlr balance when it is below the upload cost
+        #[clap(long)]
+        no_auto_fund: bool,
+
+        /// Maximum number of files open at the same time
+        #[clap(long, default_value_t = DEFAULT_MAX_OPEN_FILES)]
+        max_open_files: usize,
+
+        /// Maximum size (in MB) of the media files held in memory while they are sent (AWS and
+        /// Bundlr hold each file in memory, so no media file can be larger)
+        #[clap(long, default_value_t = DEFAULT_MAX_BUFFER_MB)]
+        max_buffer_mb: u32,
+
+        /// Download a random sample of the uploaded files and compare them with the local files
+        #[clap(long)]
+        verify_storage: bool,
+
+        /// Compare the files of every item instead of a sample (with --verify-storage)
+        #[clap(long, requires = "verify_storage")]
+        full: bool,
+
+        /// Do not warn about items with the same image or metadata
+        #[clap(long)]
+        allow_duplicates: bool,
     },
 
     /// Withdraw funds from tars account closing it
@@ -172,14 +577,30 @@ pub enum Commands {
         /// List available tarss, no withdraw performed
         #[clap(long)]
         list: bool,
+
+        /// Authority of the tarss to look up (only with --list) [default: the keypair]
+        #[clap(long, requires = "list")]
+        authority: Option<String>,
+
+        /// Address of a tars that must not be drained (can be repeated)
+        #[clap(long, value_name = "PUBKEY")]
+        exclude: Vec<String>,
+
+        /// Drain without asking for confirmation of each tars
+        #[clap(short, long)]
+        yes: bool,
     },
 
     /// Validate JSON metadata files
     Validate {
-        /// Assets directory to upload, defaults to "assets"
+        /// Assets directory (or .zip/.tar.gz archive) to upload, defaults to "assets"
         #[clap(default_value = DEFAULT_ASSETS)]
         assets_dir: String,
 
+        /// Path to the config file, used to check the hidden settings
+        #[clap(short, long)]
+        config: Option<String>,
+
         /// Strict mode: validate against JSON metadata standard exactly
         #[clap(long)]
         strict: bool,
@@ -187,10 +608,51 @@ pub enum Commands {
         /// Skip collection prompt
         #[clap(long)]
         skip_collection_prompt: bool,
+
+        /// Keep watching the assets directory and validate files as they are written
+        #[clap(long)]
+        watch: bool,
+
+        /// Fix common metadata issues in place (originals are backed up) before validating
+        #[clap(long, conflicts_with = "watch")]
+        fix: bool,
+
+        /// Do not warn about items with the same image or metadata
+        #[clap(long)]
+        allow_duplicates: bool,
+    },
+
+    /// Write the trait statistics and rarity ranking of the items, flagging duplicate
+    /// attribute combinations
+    Rarity {
+        /// Assets directory (or .zip/.tar.gz archive) with the metadata files, defaults to "assets"
+        #[clap(default_value = DEFAULT_ASSETS)]
+        assets_dir: String,
+
+        /// Read the metadata from the uploaded links of the cache instead of the assets
+        #[clap(long)]
+        from_cache: bool,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// IPFS gateway used for "ipfs://" links, tried in order when a gateway fails
+        /// (can be repeated) [default: CASE_IPFS_GATEWAYS or a built-in list]
+        #[clap(long = "ipfs-gateway", value_name = "URL", requires = "from_cache")]
+        ipfs_gateways: Vec<String>,
+
+        /// Path of the report file, written as CSV if it has a ".csv" extension
+        #[clap(short, long, default_value = "rarity.json")]
+        output_file: String,
     },
 
     /// Verify uploaded data
     Verify {
+        /// Path to the config file, defaults to "config.json"
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
         /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
@@ -202,10 +664,37 @@ pub enum Commands {
         /// Path to the cache file, defaults to "cache.json"
         #[clap(long, default_value = DEFAULT_CACHE)]
         cache: String,
+
+        /// Download the metadata files (using the local metadata cache) and check their names
+        #[clap(long)]
+        check_metadata: bool,
+
+        /// Request the metadata and image links of every item and check their status,
+        /// content type and that the metadata parses
+        #[clap(long)]
+        check_links: bool,
+
+        /// IPFS gateway used for "ipfs://" links, tried in order when a gateway fails
+        /// (can be repeated) [default: CASE_IPFS_GATEWAYS or a built-in list]
+        #[clap(long = "ipfs-gateway", value_name = "URL")]
+        ipfs_gateways: Vec<String>,
+
+        /// Rewrite the config lines that do not match the cache instead of failing
+        #[clap(long)]
+        repair: bool,
+
+        /// Check the metadata and images against the listing requirements of the
+        /// marketplaces in the config file (all supported marketplaces by default)
+        #[clap(long)]
+        metadata_standards: bool,
     },
 
-    /// Show the on-chain config of an existing tars
-    Show {
+    /// Check that the hidden settings hash of a tars matches the cache and assets
+    ///
+    /// The hash is computed from the names and file hashes of the cache items. Deploy and
+    /// update use it when the config leaves "hiddenSettings.hash" empty, or use --offline
+    /// to print it before deploying a hidden drop.
+    VerifyConfigHash {
         /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
@@ -218,16 +707,21 @@ pub enum Commands {
         #[clap(long, default_value = DEFAULT_CACHE)]
         cache: String,
 
-        /// Address of tars
-        tars: Option<String>,
+        /// Path to the directory with the assets, checked for changes since upload
+        #[clap(long, default_value = DEFAULT_ASSETS)]
+        assets_dir: String,
 
-        /// Display a list of unminted indices
+        /// Address of tars to check (defaults to the one in the cache).
         #[clap(long)]
-        unminted: bool,
+        tars: Option<String>,
+
+        /// Print the hash of the cache without comparing it with a tars
+        #[clap(long, conflicts_with = "tars")]
+        offline: bool,
     },
 
-    /// Interact with the bundlr network
-    Bundlr {
+    /// Create a throwaway devnet tars with synthetic config lines for load testing
+    Sandbox {
         /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
         #[clap(short, long)]
         keypair: Option<String>,
@@ -236,7 +730,132 @@ pub enum Commands {
         #[clap(short, long)]
         rpc_url: Option<String>,
 
-        #[clap(subcommand)]
+        /// Number of synthetic items
+        #[clap(long)]
+        items: u64,
+
+        /// Directory where the config and cache files of the sandbox are written
+        #[clap(short, long, default_value = DEFAULT_SANDBOX_DIR)]
+        output_dir: String,
+
+        /// Maximum number of config lines per transaction
+        #[clap(long, default_value_t = CONFIG_LINES_BATCH_SIZE)]
+        batch_size: usize,
+    },
+
+    /// Run a miniature launch on devnet (deploy, mint, verify and withdraw) to check the
+    /// binary and RPC
+    Selftest {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Directory where the files of the self-test are written
+        #[clap(short, long, default_value = DEFAULT_SELFTEST_DIR)]
+        output_dir: String,
+
+        /// Keep the files of the self-test when it passes
+        #[clap(long)]
+        keep: bool,
+    },
+
+    /// Show the on-chain config of an existing tars
+    Show {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of tars
+        tars: Option<String>,
+
+        /// Display a list of unminted indices, with the name and uri of their config lines
+        #[clap(long)]
+        unminted: bool,
+
+        /// Write the unminted items to a file, as CSV if it has a ".csv" extension
+        #[clap(long, requires = "unminted")]
+        output_file: Option<String>,
+
+        /// Print the complete state of the tars as JSON (same as --output json)
+        #[clap(long)]
+        json: bool,
+    },
+
+    /// Show the keypair, its balances and whether it has authority over the tars and collection
+    Whoami {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the config file, defaults to "config.json"
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of tars (defaults to the one in the cache)
+        #[clap(long)]
+        tars: Option<String>,
+    },
+
+    /// Show the log file of the latest run
+    Logs {
+        /// Number of lines to show from the end of the log
+        #[clap(short = 'n', long, default_value_t = 50)]
+        lines: usize,
+
+        /// List the log files of previous runs
+        #[clap(long, conflicts_with = "open")]
+        list: bool,
+
+        /// Open the log file with the default application
+        #[clap(long)]
+        open: bool,
+    },
+
+    /// Decode and display the instructions of a transaction
+    InspectTx {
+        /// Transaction signature
+        signature: String,
+
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+    },
+
+    /// Interact with the bundlr network
+    Bundlr {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        #[clap(subcommand)]
         action: BundlrAction,
     },
 
@@ -245,10 +864,129 @@ pub enum Commands {
         #[clap(subcommand)]
         command: CollectionSubcommands,
     },
+
+    /// Change the metadata files of the assets
+    Metadata {
+        #[clap(subcommand)]
+        command: MetadataSubcommands,
+    },
+
+    /// Export project state
+    Export {
+        #[clap(subcommand)]
+        command: ExportSubcommands,
+    },
+
+    /// Import project state
+    Import {
+        #[clap(subcommand)]
+        command: ImportSubcommands,
+    },
+
+    /// Take snapshots of the minted NFTs
+    Snapshot {
+        #[clap(subcommand)]
+        command: SnapshotSubcommands,
+    },
+
+    /// Monitor a running sale
+    Report {
+        #[clap(subcommand)]
+        command: ReportSubcommands,
+    },
+
+    /// Verifiable shuffle of the items of a hidden drop
+    Shuffle {
+        #[clap(subcommand)]
+        command: ShuffleSubcommands,
+    },
+
+    /// Scheduled updates of the tars
+    Schedule {
+        #[clap(subcommand)]
+        command: ScheduleSubcommands,
+    },
+
+    /// Create and distribute the whitelist token of the whitelist mint settings
+    Whitelist {
+        #[clap(subcommand)]
+        command: WhitelistSubcommands,
+    },
+
+    /// Tarss created by the project, including the ones of overwritten caches
+    Deployments {
+        #[clap(subcommand)]
+        command: DeploymentsSubcommands,
+    },
+
+    /// Sign and send the transactions written with --sign-offline
+    Tx {
+        #[clap(subcommand)]
+        command: TxSubcommands,
+    },
+
+    /// Manage the items of the cache file
+    Cache {
+        #[clap(subcommand)]
+        command: CacheSubcommands,
+    },
+
+    /// Manage the profiles of the project (e.g. devnet and mainnet deployments)
+    Profile {
+        #[clap(subcommand)]
+        command: ProfileSubcommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProfileSubcommands {
+    /// List the profiles with a cache or config file in the current directory
+    List,
+
+    /// Set the profile used by the next commands
+    Switch {
+        /// Name of the profile, omit to use the files without a profile
+        name: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
 pub enum CollectionSubcommands {
+    /// Create a collection NFT from an image and a metadata file
+    Create {
+        /// Path to the config file, defaults to "config.json"
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Image file of the collection
+        #[clap(long)]
+        image: String,
+
+        /// Metadata (JSON) file of the collection
+        #[clap(long)]
+        metadata: String,
+
+        /// Set the collection on the tars once it is created
+        #[clap(long)]
+        set: bool,
+
+        /// Address of tars to set the collection on (defaults to the one in the cache)
+        #[clap(long, requires = "set")]
+        tars: Option<String>,
+    },
+
     /// Set the collection mint on the tars
     Set {
         /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
@@ -267,6 +1005,19 @@ pub enum CollectionSubcommands {
         #[clap(long)]
         tars: Option<String>,
 
+        /// Path to a JSON file with a list of tars addresses to set the collection on
+        #[clap(long, conflicts_with = "tars")]
+        machines: Option<String>,
+
+        /// Write the transaction to a file to be signed offline by this authority (see 'tx sign')
+        #[clap(long, value_name = "AUTHORITY", conflicts_with = "machines")]
+        sign_offline: Option<String>,
+
+        /// Durable nonce account of the offline authority used by a transaction written for
+        /// offline signing, so that it does not expire (repeat for each transaction)
+        #[clap(long = "nonce", value_name = "ACCOUNT", requires = "sign_offline")]
+        nonces: Vec<String>,
+
         /// Address of collection mint to set the tars to.
         collection_mint: String,
     },
@@ -289,12 +1040,650 @@ pub enum CollectionSubcommands {
         #[clap(long)]
         tars: Option<String>,
     },
+
+    /// Set and verify the collection on the NFTs already minted from the tars, or the
+    /// collection of their item group
+    VerifyItems {
+        /// Path to the config file, with the item groups verified in their own collection
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of the tars that minted the NFTs.
+        #[clap(long)]
+        tars: Option<String>,
+
+        /// Address of the collection mint, defaults to the collection of the tars
+        #[clap(long)]
+        collection_mint: Option<String>,
+
+        /// List the NFTs missing the collection without sending any transaction
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Move the NFTs already minted from the tars to a different collection
+    Migrate {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of the tars that minted the NFTs.
+        #[clap(long)]
+        tars: Option<String>,
+
+        /// Address of the collection mint the NFTs are moved from, defaults to the collection
+        /// of the tars
+        #[clap(long)]
+        old_collection_mint: Option<String>,
+
+        /// List the NFTs to migrate without sending any transaction
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Migrate without asking for confirmation
+        #[clap(short, long)]
+        yes: bool,
+
+        /// Address of the collection mint the NFTs are moved to.
+        new_collection_mint: String,
+    },
 }
 
 #[derive(Subcommand)]
-pub enum BundlrAction {
-    /// Retrieve the balance on bundlr
-    Balance,
-    /// Withdraw funds from bundlr
-    Withdraw,
+pub enum MetadataSubcommands {
+    /// Apply templated changes to every metadata file (e.g. --set "name=Name #{number}")
+    Mutate {
+        /// Assets directory to change, defaults to "assets"
+        #[clap(default_value = DEFAULT_ASSETS)]
+        assets_dir: String,
+
+        /// Path to a JSON file with the list of operations to apply
+        #[clap(long, value_name = "FILE")]
+        patch: Option<String>,
+
+        /// Set the value at a path (e.g. "external_url=https://example.com/{index}"), the
+        /// placeholders are {index}, {number}, {trait:TYPE} and the path of any value
+        /// (can be repeated)
+        #[clap(long, value_name = "PATH=TEMPLATE")]
+        set: Vec<String>,
+
+        /// Remove the value at a path (can be repeated)
+        #[clap(long, value_name = "PATH")]
+        remove: Vec<String>,
+
+        /// Set the value of an attribute, adding it when missing (can be repeated)
+        #[clap(long, value_name = "TRAIT=TEMPLATE")]
+        set_attribute: Vec<String>,
+
+        /// Remove an attribute (can be repeated)
+        #[clap(long, value_name = "TRAIT")]
+        remove_attribute: Vec<String>,
+
+        /// Preview the changes without writing any file
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Write the changes without asking for confirmation
+        #[clap(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ExportSubcommands {
+    /// Create an encrypted backup of the config, cache, collection keys and tars state
+    Backup {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the config file, defaults to "config.json"
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Path to a collection keypair file to include (can be repeated)
+        #[clap(long = "collection-key")]
+        collection_keys: Vec<String>,
+
+        /// Directory where the archive is written
+        #[clap(short, long, default_value = ".")]
+        output_dir: String,
+    },
+
+    /// Write the on-chain config lines of a tars (and whether they were minted) to a JSON file
+    ConfigLines {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of tars (defaults to the one in the cache)
+        #[clap(long)]
+        tars: Option<String>,
+
+        /// Path to the output file
+        #[clap(short, long, default_value = DEFAULT_CONFIG_LINES)]
+        output: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ImportSubcommands {
+    /// Restore the files from an encrypted backup
+    Backup {
+        /// Path to the backup archive
+        archive: String,
+
+        /// Directory where the files are restored
+        #[clap(short, long, default_value = ".")]
+        output_dir: String,
+
+        /// Overwrite existing files
+        #[clap(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SnapshotSubcommands {
+    /// List the current holders of the NFTs of a tars or collection
+    Holders {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of the tars [default: tars from the cache file]
+        #[clap(long)]
+        tars: Option<String>,
+
+        /// Address of a verified collection mint (instead of the tars)
+        #[clap(long, conflicts_with = "tars")]
+        collection: Option<String>,
+
+        /// Path of the snapshot file, written as CSV if it has a ".csv" extension
+        #[clap(short, long, default_value = "holders.json")]
+        output_file: String,
+    },
+
+    /// List the mint addresses of the NFTs of a tars or collection
+    Mints {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of the tars [default: tars from the cache file]
+        #[clap(long)]
+        tars: Option<String>,
+
+        /// Address of a verified collection mint (instead of the tars)
+        #[clap(long, conflicts_with = "tars")]
+        collection: Option<String>,
+
+        /// Include the name and URI of each mint
+        #[clap(long)]
+        include_metadata: bool,
+
+        /// Path of the snapshot file
+        #[clap(short, long, default_value = "mints.json")]
+        output_file: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ReportSubcommands {
+    /// Sample the redeemed counter and write a CSV time series of the mint velocity
+    Progress {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of the tars [default: tars from the cache file]
+        #[clap(long)]
+        tars: Option<String>,
+
+        /// Seconds between samples
+        #[clap(long, default_value = "30")]
+        interval: u64,
+
+        /// Seconds without new mints after which the sale is reported as stalled
+        #[clap(long, default_value = "300")]
+        stall_after: u64,
+
+        /// Stop after the specified number of samples [default: until sold out]
+        #[clap(long)]
+        samples: Option<u64>,
+
+        /// Path of the CSV file (rows are appended)
+        #[clap(short, long, default_value = "progress.csv")]
+        output_file: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ShuffleSubcommands {
+    /// Commit to a future randomness source for the ordering of the items
+    Commit {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of the tars [default: tars from the cache file]
+        #[clap(long)]
+        tars: Option<String>,
+
+        /// Path of the proof file
+        #[clap(long, default_value = DEFAULT_SHUFFLE_PROOF)]
+        proof: String,
+
+        /// Number of slots after the current slot whose blockhash is used as randomness
+        #[clap(long, default_value = "150")]
+        slot_offset: u64,
+
+        /// SHA-256 hash (hex) of a secret revealed later, used instead of a blockhash
+        #[clap(long)]
+        secret_hash: Option<String>,
+    },
+
+    /// Reveal the randomness and write the ordering to the proof file
+    Reveal {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Path of the proof file
+        #[clap(long, default_value = DEFAULT_SHUFFLE_PROOF)]
+        proof: String,
+
+        /// Secret committed by its hash
+        #[clap(long)]
+        secret: Option<String>,
+    },
+
+    /// Check a published proof against the on-chain data
+    Verify {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path of the proof file
+        #[clap(long, default_value = DEFAULT_SHUFFLE_PROOF)]
+        proof: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DeploymentsSubcommands {
+    /// List the tarss recorded in the deployments file
+    List {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Look up the on-chain state of the tarss of the RPC cluster
+        #[clap(long)]
+        refresh: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum WhitelistSubcommands {
+    /// Create a whitelist token (an SPL token without decimals)
+    Create {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Number of tokens minted to the keypair once the token is created
+        #[clap(long, default_value = "0")]
+        amount: u64,
+    },
+
+    /// Mint whitelist tokens
+    Mint {
+        /// Number of tokens to mint
+        amount: u64,
+
+        /// Path to the config file, defaults to "config.json"
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Address of the whitelist token [default: "whitelistMintSettings.mint" of the config file]
+        #[clap(long)]
+        mint: Option<String>,
+
+        /// Wallet receiving the tokens [default: the keypair]
+        #[clap(long)]
+        to: Option<String>,
+    },
+
+    /// Send whitelist tokens to the addresses of a JSON list, resuming from the progress file
+    Airdrop {
+        /// JSON array of addresses, or object of addresses and amounts
+        list: String,
+
+        /// Path to the config file, defaults to "config.json"
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Address of the whitelist token [default: "whitelistMintSettings.mint" of the config file]
+        #[clap(long)]
+        mint: Option<String>,
+
+        /// Tokens sent to each address of a list without amounts
+        #[clap(long, default_value = "1")]
+        amount: u64,
+
+        /// File the sent addresses are added to
+        #[clap(long, default_value = DEFAULT_WHITELIST_PROGRESS)]
+        progress: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ScheduleSubcommands {
+    /// Lower the price of the tars on time, following the "priceSchedule" of the config file
+    Price {
+        /// Path to the config file, defaults to "config.json"
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of the tars [default: tars from the cache file]
+        #[clap(long)]
+        tars: Option<String>,
+
+        /// Apply the current price of the schedule and exit
+        #[clap(long)]
+        once: bool,
+
+        /// Print the price steps of the schedule without updating the tars
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Serve a health endpoint (status, last update and error counts) on this port
+        #[clap(long)]
+        health_port: Option<u16>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TxSubcommands {
+    /// Add the signature of the keypair to transaction files (no connection required)
+    Sign {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// Sign the transactions without asking for confirmation
+        #[clap(short, long)]
+        yes: bool,
+
+        /// Transaction files or directories
+        #[clap(default_value = OFFLINE_TX_DIR)]
+        files: Vec<String>,
+    },
+
+    /// Send signed transaction files in order
+    Send {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Transaction files or directories
+        #[clap(default_value = OFFLINE_TX_DIR)]
+        files: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CacheSubcommands {
+    /// Check the cache structure, links and on-chain flags against the tars
+    Check {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+    },
+
+    /// Rebuild the on-chain flags of the cache items from the tars
+    Repair {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+    },
+
+    /// Rebuild a cache file from a tars address, reading the image links from the metadata
+    Rebuild {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file to write, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of tars
+        #[clap(long)]
+        tars: String,
+
+        /// Overwrite an existing cache file
+        #[clap(long)]
+        force: bool,
+
+        /// IPFS gateway used for "ipfs://" links, tried in order when a gateway fails
+        /// (can be repeated) [default: CASE_IPFS_GATEWAYS or a built-in list]
+        #[clap(long = "ipfs-gateway", value_name = "URL")]
+        ipfs_gateways: Vec<String>,
+    },
+
+    /// Rebuild a cache file from the config lines and collection of a tars
+    Sync {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long)]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file to write, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Address of tars
+        #[clap(long)]
+        tars: String,
+
+        /// Overwrite an existing cache file
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Soft-delete an item, shifting the indices of the items after it
+    Remove {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Path to the config file
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Index of the item to remove
+        #[clap(long)]
+        index: u32,
+    },
+
+    /// Restore a removed item to its index
+    Restore {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long)]
+        keypair: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        cache: String,
+
+        /// Path to the config file
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Index the item was removed from [default: the last removed item]
+        #[clap(long)]
+        index: Option<u32>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BundlrAction {
+    /// Retrieve the balance on bundlr
+    Balance,
+    /// Withdraw funds from bundlr
+    Withdraw,
+    /// Fund the bundlr balance
+    Fund {
+        /// Amount of lamports to transfer to bundlr
+        lamports: u64,
+    },
 }
@@ -0,0 +1,40 @@
+use chrono::{DateTime, TimeZone, Utc};
+
+/// Source of the current time of the date checks, so that they can be run at a fixed time
+/// (e.g. right before or after the go-live date).
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Returns the current time as a unix timestamp, the format of the dates of the tars.
+    fn timestamp(&self) -> i64 {
+        self.now().timestamp()
+    }
+}
+
+/// Clock of the system, used by the commands.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock stopped at a given time.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl FixedClock {
+    /// Returns a clock stopped at the unix timestamp `timestamp`.
+    pub fn at(timestamp: i64) -> FixedClock {
+        FixedClock(Utc.timestamp(timestamp, 0))
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
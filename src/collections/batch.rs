@@ -0,0 +1,228 @@
+use std::fs;
+
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    cache::load_cluster_cache,
+    collections::{check_set_collection, set_collection},
+    common::*,
+    pdas::*,
+    tars::{get_tars_state, TARS_ID},
+    utils::{assert_correct_authority, check_token_metadata_program, spinner_with_style},
+};
+
+pub struct SetCollectionBatchArgs {
+    pub collection_mint: String,
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    /// Path to a JSON file with the list of tars ids.
+    pub machines: String,
+}
+
+/// Result of setting the collection on one of the machines.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MachineCollectionOutcome {
+    pub tars: String,
+    /// Signature of the set collection transaction (none if the collection was already set).
+    pub signature: Option<String>,
+}
+
+/// Result of the collection set command with --machines.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetCollectionBatchOutcome {
+    pub collection_mint: String,
+    pub machines: Vec<MachineCollectionOutcome>,
+}
+
+/// Sets the same collection on every tars of the machines file. All machines are
+/// validated before the first transaction is sent, and machines that already have the
+/// collection are skipped, so the command can be run again after a failure.
+pub fn process_set_collection_batch(
+    args: SetCollectionBatchArgs,
+) -> Result<SetCollectionBatchOutcome> {
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+    check_token_metadata_program(&program, "set the collection", None)?;
+    let payer = case_config.keypair.pubkey();
+
+    let collection_mint_pubkey = resolve_pubkey(&args.collection_mint, &args.global.address_book)?;
+    let machines = load_machines(&args.machines, &args.global.address_book)?;
+
+    case_println!(
+        "{} {}Validating {} machine(s)",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI,
+        machines.len()
+    );
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    let collection_metadata_info = get_metadata_pda(&collection_mint_pubkey, &program)?;
+    let collection_edition_info = get_master_edition_pda(&collection_mint_pubkey, &program)?;
+
+    let mut states: Vec<(Pubkey, Option<Tars>)> = Vec::new();
+    let mut errors = Vec::new();
+
+    for tars_pubkey in &machines {
+        let result = get_tars_state(&case_config, tars_pubkey).and_then(|tars_state| {
+            assert_correct_authority(&payer, &tars_state.authority)?;
+
+            match get_collection_pda(tars_pubkey, &program) {
+                Ok((_, collection_pda)) if collection_pda.mint == collection_mint_pubkey => {
+                    Ok(None)
+                }
+                _ => {
+                    check_set_collection(
+                        &payer,
+                        &tars_state,
+                        &collection_metadata_info,
+                        &collection_edition_info,
+                    )?;
+                    Ok(Some(tars_state))
+                }
+            }
+        });
+
+        match result {
+            Ok(tars_state) => states.push((*tars_pubkey, tars_state)),
+            Err(err) => errors.push((*tars_pubkey, err)),
+        }
+    }
+
+    pb.finish_and_clear();
+
+    for (tars_pubkey, tars_state) in &states {
+        let status = if tars_state.is_some() {
+            style("ready").green()
+        } else {
+            style("already set").dim()
+        };
+        case_println!("{} {} {}", style("✔").green(), tars_pubkey, status);
+    }
+    for (tars_pubkey, err) in &errors {
+        case_println!("{} {} {}", style("✘").red(), tars_pubkey, style(err).red());
+    }
+
+    if !errors.is_empty() {
+        let error = anyhow!(
+            "{} of {} machine(s) failed validation, no transaction was sent",
+            errors.len(),
+            machines.len()
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    case_println!(
+        "\n{} {}Setting collection mint on machines",
+        style("[2/2]").bold().dim(),
+        COLLECTION_EMOJI
+    );
+
+    let mut outcome = SetCollectionBatchOutcome {
+        collection_mint: collection_mint_pubkey.to_string(),
+        machines: Vec::new(),
+    };
+
+    for (tars_pubkey, tars_state) in states {
+        let signature = match tars_state {
+            Some(tars_state) => {
+                let pb = spinner_with_style();
+                pb.set_message(format!("Setting collection on {}...", tars_pubkey));
+
+                let signature = set_collection(
+                    &case_config,
+                    &program,
+                    &tars_pubkey,
+                    &tars_state,
+                    &collection_mint_pubkey,
+                    &collection_metadata_info,
+                    &collection_edition_info,
+                )
+                .map_err(|err| {
+                    pb.finish_and_clear();
+                    let error = anyhow!(
+                        "Failed to set the collection on {} ({} of {} machine(s) set): {}",
+                        tars_pubkey,
+                        outcome.machines.len(),
+                        machines.len(),
+                        err
+                    );
+                    error!("{:?}", error);
+                    error
+                })?;
+
+                pb.finish_with_message(format!(
+                    "{} {} {}",
+                    style(tars_pubkey).bold(),
+                    style("signature:").dim(),
+                    signature
+                ));
+                Some(signature.to_string())
+            }
+            None => None,
+        };
+
+        outcome.machines.push(MachineCollectionOutcome {
+            tars: tars_pubkey.to_string(),
+            signature,
+        });
+    }
+
+    // keeps the cache in sync when its tars is one of the machines
+    if let Ok(mut cache) = load_cluster_cache(&case_config, &args.cache, false) {
+        if machines
+            .iter()
+            .any(|tars_pubkey| tars_pubkey.to_string() == cache.program.tars)
+        {
+            cache.set_signer(&case_config.keypair)?;
+            cache.items.shift_remove("-1");
+            cache.program.collection_mint = collection_mint_pubkey.to_string();
+            cache.sync_file()?;
+        }
+    }
+
+    case_println!(
+        "\nCollection {} set on {} machine(s).",
+        collection_mint_pubkey,
+        outcome.machines.len()
+    );
+
+    Ok(outcome)
+}
+
+/// Reads the machines file: a JSON array of tars ids (addresses or address book labels).
+fn load_machines(path: &str, address_book: &str) -> Result<Vec<Pubkey>> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| anyhow!("Failed to read machines file '{}': {}", path, err))?;
+    let ids: Vec<String> = serde_json::from_str(&content)
+        .map_err(|err| anyhow!("Failed to parse machines file '{}': {}", path, err))?;
+
+    if ids.is_empty() {
+        return Err(anyhow!("Machines file '{}' is empty", path));
+    }
+
+    let mut machines: Vec<Pubkey> = Vec::new();
+
+    for id in ids {
+        let tars_pubkey = resolve_pubkey(&id, address_book)?;
+
+        if machines.contains(&tars_pubkey) {
+            return Err(anyhow!(
+                "Tars {} is listed more than once in '{}'",
+                tars_pubkey,
+                path
+            ));
+        }
+        machines.push(tars_pubkey);
+    }
+
+    Ok(machines)
+}
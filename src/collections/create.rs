@@ -0,0 +1,296 @@
+use std::{
+    borrow::Borrow,
+    fs,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    cache::load_cluster_cache,
+    collections::set_collection,
+    common::*,
+    config::get_config_data,
+    deploy::create_collection_instructions,
+    pdas::*,
+    rng::{Rng, SystemRng},
+    tars::{get_tars_state, TARS_ID},
+    tx::send_and_confirm,
+    upload::{asset_file_name, get_asset_pairs, initialize, upload_data, DataType, ResourceLimits},
+    utils::{assert_correct_authority, check_token_metadata_program, spinner_with_style},
+    validate::validate_metadata,
+};
+
+pub struct CreateCollectionArgs {
+    pub config: String,
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub image: String,
+    pub metadata: String,
+    /// Sets the collection on the tars once it is created.
+    pub set: bool,
+    pub tars: Option<String>,
+    pub interrupted: Arc<AtomicBool>,
+}
+
+/// Result of the collection create command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCollectionOutcome {
+    pub collection_mint: String,
+    pub image_link: String,
+    pub metadata_link: String,
+    pub signature: String,
+    /// Tars the collection was set on (--set).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tars: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub set_signature: Option<String>,
+}
+
+pub async fn process_create_collection(
+    args: CreateCollectionArgs,
+) -> Result<CreateCollectionOutcome> {
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let config_data = get_config_data(&args.config, &args.global.address_book)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+    check_token_metadata_program(&program, "create the collection NFT", None)?;
+
+    let mut cache = load_cluster_cache(&case_config, &args.cache, true)?;
+    cache.set_signer(&case_config.keypair)?;
+
+    if let Some(item) = cache.items.get("-1").filter(|item| item.on_chain) {
+        return Err(anyhow!(
+            "The cache already has the collection NFT {} ({}), use 'collection set' to change \
+             the collection of the tars",
+            cache.program.collection_mint,
+            item.name
+        ));
+    }
+
+    case_println!(
+        "{} {}Loading collection files",
+        style("[1/4]").bold().dim(),
+        ASSETS_EMOJI
+    );
+
+    // the image can be in a different directory than the metadata file
+    let image_name = asset_file_name(&args.image);
+    let metadata_dir = Path::new(&args.metadata)
+        .parent()
+        .unwrap_or_else(|| Path::new("."));
+    let exists = |file: &str| file == image_name || metadata_dir.join(file).is_file();
+
+    validate_metadata(Path::new(&args.metadata), &exists).map_err(|errors| {
+        let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        anyhow!(
+            "Invalid collection metadata file '{}': {}",
+            args.metadata,
+            errors.join(" ")
+        )
+    })?;
+
+    // the files are staged with the names of collection assets, so that they are uploaded
+    // (and cached) as the collection item
+    let staging_dir = Path::new(COLLECTION_STAGING_DIR);
+    if staging_dir.exists() {
+        fs::remove_dir_all(staging_dir)?;
+    }
+    fs::create_dir_all(staging_dir)?;
+
+    let extension = Path::new(&args.image)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .ok_or_else(|| anyhow!("Missing extension of image file '{}'", args.image))?;
+    fs::copy(
+        &args.image,
+        staging_dir.join(format!("collection.{}", extension)),
+    )
+    .map_err(|err| anyhow!("Failed to read image file '{}': {}", args.image, err))?;
+    fs::copy(&args.metadata, staging_dir.join("collection.json"))
+        .map_err(|err| anyhow!("Failed to read metadata file '{}': {}", args.metadata, err))?;
+
+    let asset_pairs = get_asset_pairs(&path_to_string(staging_dir)?)?;
+    let pair = asset_pairs
+        .get(&-1)
+        .ok_or_else(|| anyhow!("Failed to load the collection files"))?;
+
+    // files uploaded by a previous run are not uploaded again
+    let mut indices = (vec![-1], vec![-1]);
+    match cache.items.get_mut("-1") {
+        Some(item) if item.image_hash == pair.image_hash && !item.image_link.is_empty() => {
+            indices.0.clear();
+            if item.metadata_hash == pair.metadata_hash && !item.metadata_link.is_empty() {
+                indices.1.clear();
+            } else {
+                item.metadata_hash = pair.metadata_hash.clone();
+                item.metadata_link = String::new();
+            }
+        }
+        _ => {
+            cache
+                .items
+                .insert("-1".to_string(), pair.clone().into_cache_item());
+        }
+    }
+    cache.items.sort_keys();
+    cache.sync_file()?;
+
+    case_println!("{} {}", style("Collection name:").bold(), pair.name);
+
+    case_println!(
+        "\n{} {}Uploading collection files",
+        style("[2/4]").bold().dim(),
+        UPLOAD_EMOJI
+    );
+
+    let (image_indices, metadata_indices) = indices;
+
+    if metadata_indices.is_empty() {
+        case_println!("Collection files already uploaded, skipping.");
+    } else {
+        let storage =
+            initialize(&case_config, &config_data, true, &ResourceLimits::default()).await?;
+        storage
+            .prepare(
+                &case_config,
+                &asset_pairs,
+                vec![
+                    (DataType::Image, &image_indices),
+                    (DataType::Metadata, &metadata_indices),
+                ],
+            )
+            .await?;
+
+        // clear the interruption handler value ahead of the upload
+        args.interrupted.store(false, Ordering::SeqCst);
+
+        for (indices, data_type) in [
+            (&image_indices, DataType::Image),
+            (&metadata_indices, DataType::Metadata),
+        ] {
+            if indices.is_empty() {
+                continue;
+            }
+
+            let errors = upload_data(
+                &case_config,
+                &asset_pairs,
+                &mut cache,
+                indices,
+                data_type,
+                storage.borrow(),
+                args.interrupted.clone(),
+                1,
+            )
+            .await?;
+
+            if let Some(error) = errors.into_iter().next() {
+                return Err(anyhow!("Failed to upload the collection files: {}", error));
+            }
+        }
+    }
+
+    let item = cache
+        .items
+        .get("-1")
+        .expect("Failed to get the collection item");
+    let mut outcome = CreateCollectionOutcome {
+        image_link: item.image_link.clone(),
+        metadata_link: item.metadata_link.clone(),
+        ..Default::default()
+    };
+
+    case_println!(
+        "\n{} {}Creating collection NFT",
+        style("[3/4]").bold().dim(),
+        COLLECTION_EMOJI
+    );
+
+    let collection_mint = SystemRng::default().new_keypair();
+
+    let pb = spinner_with_style();
+    pb.set_message("Sending create collection NFT transaction...");
+
+    let instructions = create_collection_instructions(
+        &program,
+        &collection_mint.pubkey(),
+        item.name.clone(),
+        config_data.symbol,
+        item.metadata_link.clone(),
+    )?;
+    let signature = send_and_confirm(&case_config, &instructions, &[&collection_mint])?;
+
+    cache
+        .items
+        .get_mut("-1")
+        .expect("Failed to get the collection item")
+        .on_chain = true;
+    cache.program.collection_mint = collection_mint.pubkey().to_string();
+    cache.sync_file()?;
+
+    pb.finish_and_clear();
+    case_println!(
+        "{} {}",
+        style("Collection mint ID:").bold(),
+        collection_mint.pubkey()
+    );
+    outcome.collection_mint = collection_mint.pubkey().to_string();
+    outcome.signature = signature.to_string();
+
+    case_println!(
+        "\n{} {}Setting collection mint for tars {}",
+        style("[4/4]").bold().dim(),
+        TARS_EMOJI,
+        if args.set { "" } else { "(skipping)" }
+    );
+
+    if !args.set {
+        case_println!(
+            "The collection is set on the tars by the next deploy, or with 'collection set'."
+        );
+        return Ok(outcome);
+    }
+
+    // the tars id specified takes precedence over the one from the cache
+    let tars_id = args.tars.unwrap_or_else(|| cache.program.tars.clone());
+    if tars_id.is_empty() {
+        return Err(anyhow!(
+            "Missing tars id to set the collection on, deploy the tars or use --tars"
+        ));
+    }
+    let tars_pubkey = resolve_pubkey(&tars_id, &args.global.address_book)?;
+
+    let pb = spinner_with_style();
+    pb.set_message("Sending set collection transaction...");
+
+    let tars_state = get_tars_state(&case_config, &tars_pubkey)?;
+    assert_correct_authority(&program.payer(), &tars_state.authority)?;
+
+    let set_signature = set_collection(
+        &case_config,
+        &program,
+        &tars_pubkey,
+        &tars_state,
+        &collection_mint.pubkey(),
+        &get_metadata_pda(&collection_mint.pubkey(), &program)?,
+        &get_master_edition_pda(&collection_mint.pubkey(), &program)?,
+    )?;
+
+    pb.finish_with_message(format!(
+        "{} {}",
+        style("Set collection signature:").bold(),
+        set_signature
+    ));
+    outcome.tars = Some(tars_pubkey.to_string());
+    outcome.set_signature = Some(set_signature.to_string());
+
+    Ok(outcome)
+}
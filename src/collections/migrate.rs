@@ -0,0 +1,288 @@
+use console::style;
+use mpl_token_metadata::instruction::{set_and_verify_collection, unverify_collection};
+use serde::Serialize;
+
+use crate::{
+    cache::load_cluster_cache,
+    collections::{send_item_transactions, FailedItem, VerifiedItem},
+    common::*,
+    pdas::*,
+    snapshot::find_tars_metadata,
+    tars::TARS_ID,
+    utils::{check_token_metadata_program, confirm_action, spinner_with_style},
+};
+
+pub struct MigrateCollectionArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub tars: Option<String>,
+    /// Collection the NFTs are moved from [default: the collection of the tars].
+    pub old_collection_mint: Option<String>,
+    pub new_collection_mint: String,
+    pub dry_run: bool,
+    pub skip_confirmation: bool,
+}
+
+/// Result of the collection migrate command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrateCollectionOutcome {
+    pub tars: String,
+    pub old_collection_mint: String,
+    pub new_collection_mint: String,
+    pub minted: usize,
+    /// NFTs already verified in the new collection.
+    pub already_migrated: usize,
+    /// NFTs verified in a collection other than the old one, which are left unchanged.
+    pub other_collection: Vec<String>,
+    /// NFTs to migrate (--dry-run).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pending: Vec<String>,
+    pub migrated: Vec<VerifiedItem>,
+    pub failed: Vec<FailedItem>,
+}
+
+pub fn process_migrate_collection(args: MigrateCollectionArgs) -> Result<MigrateCollectionOutcome> {
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+    check_token_metadata_program(&program, "migrate the collection", None)?;
+    let payer = program.payer();
+
+    // the tars id specified takes precedence over the one from the cache
+    let (tars_id, cache_collection) = match args.tars {
+        Some(tars_id) => (tars_id, None),
+        None => {
+            let cache = load_cluster_cache(&case_config, &args.cache, false)?;
+            (cache.program.tars, Some(cache.program.collection_mint))
+        }
+    };
+    let tars_pubkey = resolve_pubkey(&tars_id, &args.global.address_book)?;
+
+    case_println!(
+        "{} {}Loading collections",
+        style("[1/3]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+    case_println!("{} {}", style("Tars ID:").bold(), tars_id);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    // the collection set on the tars takes precedence over the one of the cache
+    let old_collection_mint = match args.old_collection_mint {
+        Some(collection_mint) => resolve_pubkey(&collection_mint, &args.global.address_book)?,
+        None => match (get_collection_pda(&tars_pubkey, &program), cache_collection) {
+            (Ok((_, collection_pda)), _) => collection_pda.mint,
+            (Err(_), Some(collection_mint)) if !collection_mint.is_empty() => {
+                Pubkey::from_str(&collection_mint)?
+            }
+            (Err(_), _) => {
+                return Err(anyhow!(
+                    "Tars {} has no collection, use --old-collection-mint",
+                    tars_pubkey
+                ))
+            }
+        },
+    };
+    let new_collection_mint = resolve_pubkey(&args.new_collection_mint, &args.global.address_book)?;
+
+    if old_collection_mint == new_collection_mint {
+        return Err(anyhow!(
+            "The new collection mint is the same as the old one: {}",
+            new_collection_mint
+        ));
+    }
+
+    let (old_metadata, old_collection) = get_metadata_pda(&old_collection_mint, &program)?;
+    let (new_metadata, new_collection) = get_metadata_pda(&new_collection_mint, &program)?;
+
+    pb.finish_with_message("Done");
+    case_println!(
+        "{} {}",
+        style("Old collection:").bold(),
+        old_collection_mint
+    );
+    case_println!(
+        "{} {}",
+        style("New collection:").bold(),
+        new_collection_mint
+    );
+
+    // unverifying and verifying both need the collection update authority
+    for collection in [&old_collection, &new_collection] {
+        if collection.update_authority != payer {
+            return Err(CustomTarsError::AuthorityMismatch(
+                collection.update_authority.to_string(),
+                payer.to_string(),
+            )
+            .into());
+        }
+    }
+
+    case_println!(
+        "\n{} {}Looking up minted NFTs",
+        style("[2/3]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+    let minted = find_tars_metadata(&program, &tars_pubkey)?;
+    pb.finish_with_message(format!("Found {} NFT(s)", minted.len()));
+
+    let mut outcome = MigrateCollectionOutcome {
+        tars: tars_pubkey.to_string(),
+        old_collection_mint: old_collection_mint.to_string(),
+        new_collection_mint: new_collection_mint.to_string(),
+        minted: minted.len(),
+        ..Default::default()
+    };
+    // NFTs to migrate and whether they are verified in the old collection
+    let mut pending = Vec::new();
+
+    for metadata in minted {
+        match &metadata.collection {
+            Some(item) if item.verified && item.key == new_collection_mint => {
+                outcome.already_migrated += 1
+            }
+            Some(item) if item.verified && item.key == old_collection_mint => {
+                pending.push((metadata, true))
+            }
+            Some(item) if item.verified => outcome.other_collection.push(metadata.mint.to_string()),
+            _ => pending.push((metadata, false)),
+        }
+    }
+
+    case_println!(
+        "{} NFT(s) already migrated, {} to migrate",
+        outcome.already_migrated,
+        pending.len()
+    );
+
+    if !outcome.other_collection.is_empty() {
+        case_println!(
+            "{}{}",
+            WARNING_EMOJI,
+            style(format!(
+                "{} NFT(s) are verified in another collection and are left unchanged",
+                outcome.other_collection.len()
+            ))
+            .yellow()
+        );
+    }
+
+    case_println!(
+        "\n{} {}Migrating collection",
+        style("[3/3]").bold().dim(),
+        COLLECTION_EMOJI
+    );
+
+    if pending.is_empty() {
+        case_println!("Every NFT is in the new collection, nothing to migrate.");
+        return Ok(outcome);
+    }
+
+    if args.dry_run {
+        outcome.pending = pending
+            .iter()
+            .map(|(metadata, _)| metadata.mint.to_string())
+            .collect();
+
+        case_println!("NFT(s) that would be migrated:");
+        for mint in &outcome.pending {
+            case_println!("{} {}", style(" :..").dim(), mint);
+        }
+        case_println!(
+            "\n{} NFT(s) would be migrated (dry run, nothing sent).",
+            pending.len()
+        );
+        return Ok(outcome);
+    }
+
+    let prompt = format!(
+        "Move {} NFT(s) from collection {} to {}?",
+        pending.len(),
+        old_collection_mint,
+        new_collection_mint
+    );
+    if !confirm_action(&prompt, args.skip_confirmation)? {
+        return Err(anyhow!("Migration cancelled"));
+    }
+
+    let old_edition = find_master_edition_pda(&old_collection_mint);
+    let new_edition = find_master_edition_pda(&new_collection_mint);
+
+    // the old collection is unverified in the same transaction, so that an NFT is never
+    // left without a verified collection
+    let transactions = pending
+        .iter()
+        .map(|(metadata, in_old_collection)| {
+            let instructions = if metadata.update_authority != payer {
+                Err(anyhow!(
+                    "update authority {} is not the collection authority",
+                    metadata.update_authority
+                ))
+            } else {
+                let metadata_pda = find_metadata_pda(&metadata.mint);
+                let mut instructions = Vec::new();
+
+                if *in_old_collection {
+                    instructions.push(unverify_collection(
+                        mpl_token_metadata::ID,
+                        metadata_pda,
+                        payer,
+                        old_collection_mint,
+                        old_metadata,
+                        old_edition,
+                        None,
+                    ));
+                }
+                instructions.push(set_and_verify_collection(
+                    mpl_token_metadata::ID,
+                    metadata_pda,
+                    payer,
+                    payer,
+                    payer,
+                    new_collection_mint,
+                    new_metadata,
+                    new_edition,
+                    None,
+                ));
+
+                Ok(instructions)
+            };
+            (metadata.mint, instructions)
+        })
+        .collect();
+
+    let (migrated, failed) = send_item_transactions(&case_config, transactions)?;
+    outcome.migrated = migrated;
+    outcome.failed = failed;
+
+    case_println!(
+        "\n{} NFT(s) moved to the new collection, {} failed",
+        outcome.migrated.len(),
+        outcome.failed.len()
+    );
+
+    // the tars keeps the old collection for the NFTs still to be minted
+    if get_collection_pda(&tars_pubkey, &program).map_or(false, |(_, collection_pda)| {
+        collection_pda.mint == old_collection_mint
+    }) {
+        case_println!(
+            "{}{}",
+            WARNING_EMOJI,
+            style(format!(
+                "The tars still mints into the old collection, use 'case collection set {}' \
+                 to change it",
+                new_collection_mint
+            ))
+            .yellow()
+        );
+    }
+
+    Ok(outcome)
+}
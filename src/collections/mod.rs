@@ -1,5 +1,13 @@
+pub mod batch;
+pub mod create;
+pub mod migrate;
 pub mod remove;
 pub mod set;
+pub mod verify_items;
 
+pub use batch::*;
+pub use create::*;
+pub use migrate::*;
 pub use remove::*;
 pub use set::*;
+pub use verify_items::*;
@@ -3,54 +3,62 @@ use std::str::FromStr;
 use anchor_client::solana_sdk::pubkey::Pubkey;
 use anyhow::Result;
 use console::style;
+use serde::Serialize;
 use tars::{accounts as nft_accounts, instruction as nft_instruction};
 use mpl_token_metadata::{pda::find_collection_authority_account, state::Metadata};
 
 use crate::{
-    cache::load_cache,
+    cache::load_cluster_cache,
     tars::{TARS_ID, *},
     common::*,
+    config::CaseConfig,
     pdas::*,
-    utils::{assert_correct_authority, spinner_with_style},
+    tx::send_and_confirm,
+    utils::{assert_correct_authority, check_token_metadata_program, spinner_with_style},
 };
 
 pub struct RemoveCollectionArgs {
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
     pub cache: String,
     pub tars: Option<String>,
 }
 
-pub fn process_remove_collection(args: RemoveCollectionArgs) -> Result<()> {
-    let case_config = case_setup(args.keypair, args.rpc_url)?;
+/// Result of the collection remove command.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveCollectionOutcome {
+    pub tars: String,
+    pub collection_mint: String,
+    pub signature: String,
+}
+
+pub fn process_remove_collection(args: RemoveCollectionArgs) -> Result<RemoveCollectionOutcome> {
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
     let client = setup_client(&case_config)?;
     let program = client.program(TARS_ID);
+    check_token_metadata_program(&program, "remove the collection", None)?;
     let mut cache = Cache::new();
 
     // the tars id specified takes precedence over the one from the cache
     let tars_id = match args.tars {
         Some(ref tars_id) => tars_id,
         None => {
-            cache = load_cache(&args.cache, false)?;
+            cache = load_cluster_cache(&case_config, &args.cache, false)?;
+            cache.set_signer(&case_config.keypair)?;
             &cache.program.tars
         }
     };
 
-    let tars_pubkey = match Pubkey::from_str(tars_id) {
-        Ok(tars_pubkey) => tars_pubkey,
-        Err(_) => {
-            let error = anyhow!("Failed to parse tars id: {}", tars_id);
-            error!("{:?}", error);
-            return Err(error);
-        }
-    };
+    let tars_pubkey = resolve_pubkey(tars_id, &args.global.address_book)?;
 
-    println!(
+    case_println!(
         "{} {}Loading tars",
         style("[1/2]").bold().dim(),
         LOOKING_GLASS_EMOJI
     );
-    println!("{} {}", style("Tars ID:").bold(), tars_id);
+    case_println!("{} {}", style("Tars ID:").bold(), tars_id);
 
     let pb = spinner_with_style();
     pb.set_message("Connecting...");
@@ -67,7 +75,7 @@ pub fn process_remove_collection(args: RemoveCollectionArgs) -> Result<()> {
         &tars_state.authority,
     )?;
 
-    println!(
+    case_println!(
         "\n{} {}Removing collection mint for tars",
         style("[2/2]").bold().dim(),
         TARS_EMOJI
@@ -77,6 +85,7 @@ pub fn process_remove_collection(args: RemoveCollectionArgs) -> Result<()> {
     pb.set_message("Sending remove collection transaction...");
 
     let remove_signature = remove_collection(
+        &case_config,
         &program,
         &tars_pubkey,
         &tars_state,
@@ -99,10 +108,15 @@ pub fn process_remove_collection(args: RemoveCollectionArgs) -> Result<()> {
         remove_signature
     ));
 
-    Ok(())
+    Ok(RemoveCollectionOutcome {
+        tars: tars_pubkey.to_string(),
+        collection_mint: collection_mint_pubkey.to_string(),
+        signature: remove_signature.to_string(),
+    })
 }
 
 pub fn remove_collection(
+    case_config: &CaseConfig,
     program: &Program,
     tars_pubkey: &Pubkey,
     tars_state: &Tars,
@@ -143,7 +157,5 @@ pub fn remove_collection(
         })
         .args(nft_instruction::RemoveCollection);
 
-    let sig = builder.send()?;
-
-    Ok(sig)
+    send_and_confirm(case_config, &builder.instructions()?, &[])
 }
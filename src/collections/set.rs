@@ -1,8 +1,9 @@
 use std::str::FromStr;
 
-use anchor_client::solana_sdk::{pubkey::Pubkey, system_program, sysvar};
+use anchor_client::solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_program, sysvar};
 use anyhow::Result;
 use console::style;
+use serde::Serialize;
 use tars::{accounts as nft_accounts, instruction as nft_instruction, TarsError};
 use mpl_token_metadata::{
     error::MetadataError,
@@ -11,69 +12,81 @@ use mpl_token_metadata::{
 };
 
 use crate::{
-    cache::load_cache,
+    cache::load_cluster_cache,
     tars::{TARS_ID, *},
     common::*,
+    config::CaseConfig,
     pdas::*,
-    utils::{assert_correct_authority, spinner_with_style},
+    tx::{resolve_nonces, send_and_confirm, setup_offline_client, OfflineWriter},
+    utils::{assert_correct_authority, check_token_metadata_program, spinner_with_style},
 };
 
 pub struct SetCollectionArgs {
     pub collection_mint: String,
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
     pub cache: String,
     pub tars: Option<String>,
+    /// Authority that signs the transaction offline.
+    pub sign_offline: Option<String>,
+    /// Durable nonce account of the transaction signed offline.
+    pub nonces: Vec<String>,
 }
 
-pub fn process_set_collection(args: SetCollectionArgs) -> Result<()> {
-    let case_config = case_setup(args.keypair, args.rpc_url)?;
-    let client = setup_client(&case_config)?;
+/// Result of the collection set command.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetCollectionOutcome {
+    pub tars: String,
+    pub collection_mint: String,
+    /// Signature of the transaction (empty when it was written for offline signing).
+    pub signature: String,
+    /// Transaction files written for offline signing.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub transactions: Vec<String>,
+}
+
+pub fn process_set_collection(args: SetCollectionArgs) -> Result<SetCollectionOutcome> {
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let offline_authority = args
+        .sign_offline
+        .as_deref()
+        .map(|authority| resolve_pubkey(authority, &args.global.address_book))
+        .transpose()?;
+    let client = match &offline_authority {
+        Some(authority) => setup_offline_client(&case_config, authority),
+        None => setup_client(&case_config)?,
+    };
     let program = client.program(TARS_ID);
+    check_token_metadata_program(&program, "set the collection", None)?;
     let mut cache = Cache::new();
 
     // The tars id specified takes precedence over the one from the cache.
     let tars_id = match args.tars {
         Some(ref tars_id) => tars_id,
         None => {
-            cache = load_cache(&args.cache, false)?;
+            cache = load_cluster_cache(&case_config, &args.cache, false)?;
+            cache.set_signer(&case_config.keypair)?;
             &cache.program.tars
         }
     };
 
-    let collection_mint_pubkey = match Pubkey::from_str(&args.collection_mint) {
-        Ok(tars_pubkey) => tars_pubkey,
-        Err(_) => {
-            let error = anyhow!(
-                "Failed to parse collection mint id: {}",
-                args.collection_mint
-            );
-            error!("{:?}", error);
-            return Err(error);
-        }
-    };
+    let collection_mint_pubkey = resolve_pubkey(&args.collection_mint, &args.global.address_book)?;
 
-    let tars_pubkey = match Pubkey::from_str(tars_id) {
-        Ok(tars_pubkey) => tars_pubkey,
-        Err(_) => {
-            let error = anyhow!("Failed to parse tars id: {}", tars_id);
-            error!("{:?}", error);
-            return Err(error);
-        }
-    };
+    let tars_pubkey = resolve_pubkey(tars_id, &args.global.address_book)?;
 
-    println!(
+    case_println!(
         "{} {}Loading tars",
         style("[1/2]").bold().dim(),
         LOOKING_GLASS_EMOJI
     );
-    println!("{} {}", style("Tars ID:").bold(), tars_id);
+    case_println!("{} {}", style("Tars ID:").bold(), tars_id);
 
     let pb = spinner_with_style();
     pb.set_message("Connecting...");
 
-    let tars_state =
-        get_tars_state(&case_config, &Pubkey::from_str(tars_id)?)?;
+    let tars_state = get_tars_state(&case_config, &tars_pubkey)?;
 
     let collection_metadata_info = get_metadata_pda(&collection_mint_pubkey, &program)?;
 
@@ -81,21 +94,44 @@ pub fn process_set_collection(args: SetCollectionArgs) -> Result<()> {
 
     pb.finish_with_message("Done");
 
-    assert_correct_authority(
-        &case_config.keypair.pubkey(),
-        &tars_state.authority,
-    )?;
+    assert_correct_authority(&program.payer(), &tars_state.authority)?;
 
-    println!(
+    case_println!(
         "\n{} {}Setting collection mint for tars",
         style("[2/2]").bold().dim(),
         COLLECTION_EMOJI
     );
 
+    if let Some(authority) = offline_authority {
+        let mut writer = OfflineWriter::new(
+            &program.rpc(),
+            authority,
+            &resolve_nonces(&args.nonces, &args.global.address_book)?,
+        )?;
+        let instructions = set_collection_instructions(
+            &program,
+            &tars_pubkey,
+            &tars_state,
+            &collection_mint_pubkey,
+            &collection_metadata_info,
+            &collection_edition_info,
+        )?;
+        writer.write("set collection", &instructions, &[])?;
+
+        // the cache is updated by running the command again once the transaction is sent
+        return Ok(SetCollectionOutcome {
+            tars: tars_pubkey.to_string(),
+            collection_mint: collection_mint_pubkey.to_string(),
+            signature: String::new(),
+            transactions: writer.finish(),
+        });
+    }
+
     let pb = spinner_with_style();
     pb.set_message("Sending set collection transaction...");
 
     let set_signature = set_collection(
+        &case_config,
         &program,
         &tars_pubkey,
         &tars_state,
@@ -118,10 +154,16 @@ pub fn process_set_collection(args: SetCollectionArgs) -> Result<()> {
         set_signature
     ));
 
-    Ok(())
+    Ok(SetCollectionOutcome {
+        tars: tars_pubkey.to_string(),
+        collection_mint: collection_mint_pubkey.to_string(),
+        signature: set_signature.to_string(),
+        transactions: Vec::new(),
+    })
 }
 
 pub fn set_collection(
+    case_config: &CaseConfig,
     program: &Program,
     tars_pubkey: &Pubkey,
     tars_state: &Tars,
@@ -129,20 +171,78 @@ pub fn set_collection(
     collection_metadata_info: &PdaInfo<Metadata>,
     collection_edition_info: &PdaInfo<MasterEditionV2>,
 ) -> Result<Signature> {
+    let instructions = set_collection_instructions(
+        program,
+        tars_pubkey,
+        tars_state,
+        collection_mint_pubkey,
+        collection_metadata_info,
+        collection_edition_info,
+    )?;
+
+    send_and_confirm(case_config, &instructions, &[])
+}
+
+/// Returns the instructions that set the collection on the tars.
+pub fn set_collection_instructions(
+    program: &Program,
+    tars_pubkey: &Pubkey,
+    tars_state: &Tars,
+    collection_mint_pubkey: &Pubkey,
+    collection_metadata_info: &PdaInfo<Metadata>,
+    collection_edition_info: &PdaInfo<MasterEditionV2>,
+) -> Result<Vec<Instruction>> {
     let payer = program.payer();
 
     let collection_pda_pubkey = find_collection_pda(tars_pubkey).0;
-    let (collection_metadata_pubkey, collection_metadata) = collection_metadata_info;
-    let (collection_edition_pubkey, collection_edition) = collection_edition_info;
+    let (collection_metadata_pubkey, _) = collection_metadata_info;
+    let (collection_edition_pubkey, _) = collection_edition_info;
 
     let collection_authority_record =
         find_collection_authority_account(collection_mint_pubkey, &collection_pda_pubkey).0;
 
+    check_set_collection(
+        &payer,
+        tars_state,
+        collection_metadata_info,
+        collection_edition_info,
+    )?;
+
+    let builder = program
+        .request()
+        .accounts(nft_accounts::SetCollection {
+            tars: *tars_pubkey,
+            authority: payer,
+            collection_pda: collection_pda_pubkey,
+            payer,
+            system_program: system_program::id(),
+            rent: sysvar::rent::ID,
+            metadata: *collection_metadata_pubkey,
+            mint: *collection_mint_pubkey,
+            edition: *collection_edition_pubkey,
+            collection_authority_record,
+            token_metadata_program: mpl_token_metadata::ID,
+        })
+        .args(nft_instruction::SetCollection);
+
+    Ok(builder.instructions()?)
+}
+
+/// Checks that the collection can be set on the tars by the payer.
+pub fn check_set_collection(
+    payer: &Pubkey,
+    tars_state: &Tars,
+    collection_metadata_info: &PdaInfo<Metadata>,
+    collection_edition_info: &PdaInfo<MasterEditionV2>,
+) -> Result<()> {
+    let (_, collection_metadata) = collection_metadata_info;
+    let (_, collection_edition) = collection_edition_info;
+
     if !tars_state.data.retain_authority {
         return Err(anyhow!(TarsError::TarsCollectionRequiresRetainAuthority));
     }
 
-    if collection_metadata.update_authority != payer {
+    if collection_metadata.update_authority != *payer {
         return Err(anyhow!(CustomTarsError::AuthorityMismatch(
             collection_metadata.update_authority.to_string(),
             payer.to_string()
@@ -159,24 +259,5 @@ pub fn set_collection(
         ));
     }
 
-    let builder = program
-        .request()
-        .accounts(nft_accounts::SetCollection {
-            tars: *tars_pubkey,
-            authority: payer,
-            collection_pda: collection_pda_pubkey,
-            payer,
-            system_program: system_program::id(),
-            rent: sysvar::rent::ID,
-            metadata: *collection_metadata_pubkey,
-            mint: *collection_mint_pubkey,
-            edition: *collection_edition_pubkey,
-            collection_authority_record,
-            token_metadata_program: mpl_token_metadata::ID,
-        })
-        .args(nft_instruction::SetCollection);
-
-    let sig = builder.send()?;
-
-    Ok(sig)
+    Ok(())
 }
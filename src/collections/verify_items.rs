@@ -0,0 +1,431 @@
+use std::{collections::BTreeMap, thread, time::Duration};
+
+use anchor_client::solana_sdk::instruction::Instruction;
+use console::style;
+use mpl_token_metadata::{
+    instruction::{set_and_verify_collection, unverify_collection},
+    state::Metadata,
+};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    cache::load_cluster_cache,
+    common::*,
+    config::{get_config_data, CaseConfig},
+    pdas::*,
+    snapshot::find_tars_metadata,
+    tars::TARS_ID,
+    tx::send_and_confirm,
+    utils::{check_token_metadata_program, progress_bar_with_style, spinner_with_style},
+};
+
+pub struct VerifyItemsArgs {
+    pub config: String,
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub tars: Option<String>,
+    /// Collection to verify [default: the collection of the tars].
+    pub collection_mint: Option<String>,
+    pub dry_run: bool,
+}
+
+/// NFT whose collection was set and verified.
+#[derive(Debug, Serialize)]
+pub struct VerifiedItem {
+    pub mint: String,
+    pub signature: String,
+}
+
+/// NFT whose collection could not be verified.
+#[derive(Debug, Serialize)]
+pub struct FailedItem {
+    pub mint: String,
+    pub error: String,
+}
+
+/// Result of the collection verify-items command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyItemsOutcome {
+    pub tars: String,
+    /// Empty when only the item groups have a collection.
+    pub collection_mint: String,
+    pub minted: usize,
+    pub already_verified: usize,
+    /// NFTs verified in another collection, which are left unchanged.
+    pub other_collection: Vec<String>,
+    /// NFTs missing the collection of their item group, by group.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, usize>,
+    /// NFTs that are not in an item group, when the tars has no collection.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ungrouped: Vec<String>,
+    /// NFTs missing the collection (--dry-run).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pending: Vec<String>,
+    pub verified: Vec<VerifiedItem>,
+    pub failed: Vec<FailedItem>,
+}
+
+/// NFT to verify: its metadata, the collection to verify and the collection it is
+/// verified in, which is unverified first.
+type PendingItem = (Metadata, Pubkey, Option<Pubkey>);
+
+pub fn process_verify_items(args: VerifyItemsArgs) -> Result<VerifyItemsOutcome> {
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+    check_token_metadata_program(&program, "verify the collection of the NFTs", None)?;
+    let payer = program.payer();
+
+    // the item groups are ignored when verifying a single collection
+    let item_groups = match args.collection_mint {
+        Some(_) => Vec::new(),
+        None if Path::new(&args.config).exists() => {
+            get_config_data(&args.config, &args.global.address_book)?.item_groups
+        }
+        None => Vec::new(),
+    };
+
+    // the cache also maps the NFTs to the index of their item
+    let cache = if args.tars.is_none() || !item_groups.is_empty() {
+        Some(load_cluster_cache(&case_config, &args.cache, false)?)
+    } else {
+        None
+    };
+
+    // the tars id specified takes precedence over the one from the cache
+    let (tars_id, cache_collection) = match (args.tars, &cache) {
+        (Some(tars_id), _) => (tars_id, None),
+        (None, Some(cache)) => (
+            cache.program.tars.clone(),
+            Some(cache.program.collection_mint.clone()),
+        ),
+        (None, None) => unreachable!("the cache is loaded without a tars id"),
+    };
+    let tars_pubkey = resolve_pubkey(&tars_id, &args.global.address_book)?;
+
+    case_println!(
+        "{} {}Loading tars",
+        style("[1/3]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+    case_println!("{} {}", style("Tars ID:").bold(), tars_id);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    // the collection set on the tars takes precedence over the one of the cache
+    let collection_mint = match args.collection_mint {
+        Some(collection_mint) => Some(resolve_pubkey(&collection_mint, &args.global.address_book)?),
+        None => match (get_collection_pda(&tars_pubkey, &program), cache_collection) {
+            (Ok((_, collection_pda)), _) => Some(collection_pda.mint),
+            (Err(_), Some(collection_mint)) if !collection_mint.is_empty() => {
+                Some(Pubkey::from_str(&collection_mint)?)
+            }
+            (Err(_), _) if !item_groups.is_empty() => None,
+            (Err(_), _) => {
+                return Err(anyhow!(
+                    "Tars {} has no collection, set it with 'collection set' or use \
+                     --collection-mint",
+                    tars_pubkey
+                ))
+            }
+        },
+    };
+
+    // metadata and master edition of each collection to verify
+    let mut collections = HashMap::new();
+
+    for mint in collection_mint
+        .iter()
+        .chain(item_groups.iter().map(|group| &group.collection_mint))
+    {
+        let (collection_metadata, collection) = get_metadata_pda(mint, &program)?;
+
+        // the collection authority also needs to be the update authority of the NFTs
+        if collection.update_authority != payer {
+            return Err(CustomTarsError::AuthorityMismatch(
+                collection.update_authority.to_string(),
+                payer.to_string(),
+            )
+            .into());
+        }
+
+        collections.insert(*mint, (collection_metadata, find_master_edition_pda(mint)));
+    }
+
+    pb.finish_with_message("Done");
+
+    if let Some(collection_mint) = &collection_mint {
+        case_println!("{} {}", style("Collection mint:").bold(), collection_mint);
+    }
+    for group in &item_groups {
+        case_println!(
+            "{} {} (items {} to {})",
+            style(format!("Collection mint of group '{}':", group.name)).bold(),
+            group.collection_mint,
+            group.start,
+            group.end
+        );
+    }
+
+    case_println!(
+        "\n{} {}Looking up minted NFTs",
+        style("[2/3]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+    let minted = find_tars_metadata(&program, &tars_pubkey)?;
+    pb.finish_with_message(format!("Found {} NFT(s)", minted.len()));
+
+    // the index of a minted NFT is the one of the cache item with the same uri
+    let indices: HashMap<&str, u64> = cache
+        .iter()
+        .flat_map(|cache| cache.items.iter())
+        .filter_map(|(index, item)| Some((item.metadata_link.as_str(), index.parse().ok()?)))
+        .collect();
+
+    let mut outcome = VerifyItemsOutcome {
+        tars: tars_pubkey.to_string(),
+        collection_mint: collection_mint
+            .map(|collection_mint| collection_mint.to_string())
+            .unwrap_or_default(),
+        minted: minted.len(),
+        ..Default::default()
+    };
+    let mut pending: Vec<PendingItem> = Vec::new();
+
+    for metadata in minted {
+        let group = indices
+            .get(metadata.data.uri.trim_matches(char::from(0)))
+            .and_then(|index| item_groups.iter().find(|group| group.contains(*index)));
+        let expected = match group.map(|group| group.collection_mint).or(collection_mint) {
+            Some(expected) => expected,
+            None => {
+                outcome.ungrouped.push(metadata.mint.to_string());
+                continue;
+            }
+        };
+
+        match &metadata.collection {
+            Some(item) if item.verified && item.key == expected => outcome.already_verified += 1,
+            // minted into the collection of the tars instead of the one of its group
+            Some(item) if item.verified && Some(item.key) == collection_mint => {
+                let current = item.key;
+                if let Some(group) = group {
+                    *outcome.groups.entry(group.name.clone()).or_default() += 1;
+                }
+                pending.push((metadata, expected, Some(current)))
+            }
+            Some(item) if item.verified => outcome.other_collection.push(metadata.mint.to_string()),
+            _ => {
+                if let Some(group) = group {
+                    *outcome.groups.entry(group.name.clone()).or_default() += 1;
+                }
+                pending.push((metadata, expected, None))
+            }
+        }
+    }
+
+    case_println!(
+        "{} NFT(s) already verified, {} missing the collection",
+        outcome.already_verified,
+        pending.len()
+    );
+    for (name, count) in &outcome.groups {
+        case_println!("{} {}: {} NFT(s)", style(" :..").dim(), name, count);
+    }
+
+    if !outcome.other_collection.is_empty() {
+        case_println!(
+            "{}{}",
+            WARNING_EMOJI,
+            style(format!(
+                "{} NFT(s) are verified in another collection and are left unchanged",
+                outcome.other_collection.len()
+            ))
+            .yellow()
+        );
+    }
+
+    if !outcome.ungrouped.is_empty() {
+        case_println!(
+            "{}{}",
+            WARNING_EMOJI,
+            style(format!(
+                "{} NFT(s) are not in an item group and the tars has no collection, they are \
+                 left unchanged",
+                outcome.ungrouped.len()
+            ))
+            .yellow()
+        );
+    }
+
+    case_println!(
+        "\n{} {}Verifying collection",
+        style("[3/3]").bold().dim(),
+        COLLECTION_EMOJI
+    );
+
+    if pending.is_empty() {
+        case_println!("Every NFT is in the collection, nothing to verify.");
+        return Ok(outcome);
+    }
+
+    if args.dry_run {
+        outcome.pending = pending
+            .iter()
+            .map(|(metadata, _, _)| metadata.mint.to_string())
+            .collect();
+        case_println!(
+            "{} NFT(s) would be verified (dry run, nothing sent).",
+            pending.len()
+        );
+        return Ok(outcome);
+    }
+
+    // an NFT in the collection of the tars is unverified in the same transaction, so that
+    // it is never left without a verified collection
+    let transactions = pending
+        .iter()
+        .map(|(metadata, expected, current)| {
+            let instructions = if metadata.update_authority != payer {
+                Err(anyhow!(
+                    "update authority {} is not the collection authority",
+                    metadata.update_authority
+                ))
+            } else {
+                let metadata_pda = find_metadata_pda(&metadata.mint);
+                let mut instructions = Vec::new();
+
+                if let Some(current) = current {
+                    let (current_metadata, current_edition) = collections[current];
+                    instructions.push(unverify_collection(
+                        mpl_token_metadata::ID,
+                        metadata_pda,
+                        payer,
+                        *current,
+                        current_metadata,
+                        current_edition,
+                        None,
+                    ));
+                }
+
+                let (collection_metadata, collection_edition) = collections[expected];
+                instructions.push(set_and_verify_collection(
+                    mpl_token_metadata::ID,
+                    metadata_pda,
+                    payer,
+                    payer,
+                    payer,
+                    *expected,
+                    collection_metadata,
+                    collection_edition,
+                    None,
+                ));
+
+                Ok(instructions)
+            };
+            (metadata.mint, instructions)
+        })
+        .collect();
+
+    let (verified, failed) = send_item_transactions(&case_config, transactions)?;
+    outcome.verified = verified;
+    outcome.failed = failed;
+
+    case_println!(
+        "\n{} NFT(s) added to the collection, {} failed",
+        outcome.verified.len(),
+        outcome.failed.len()
+    );
+
+    Ok(outcome)
+}
+
+/// Sends the transaction of each NFT in parallel, retrying after an error, and returns
+/// the NFTs updated and the ones that failed. NFTs without a transaction (e.g. with a
+/// different update authority) are reported as failed.
+pub fn send_item_transactions(
+    case_config: &CaseConfig,
+    transactions: Vec<(Pubkey, Result<Vec<Instruction>>)>,
+) -> Result<(Vec<VerifiedItem>, Vec<FailedItem>)> {
+    let pb = progress_bar_with_style(transactions.len() as u64);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(PARALLEL_LIMIT)
+        .build()?;
+
+    let results: Vec<(Pubkey, Result<Signature>)> = pool.install(|| {
+        transactions
+            .into_par_iter()
+            .map(|(mint, instructions)| {
+                let result = instructions
+                    .and_then(|instructions| send_with_retries(case_config, &mint, &instructions));
+                pb.inc(1);
+                (mint, result)
+            })
+            .collect()
+    });
+
+    let mut verified = Vec::new();
+    let mut failed = Vec::new();
+
+    for (mint, result) in results {
+        match result {
+            Ok(signature) => verified.push(VerifiedItem {
+                mint: mint.to_string(),
+                signature: signature.to_string(),
+            }),
+            Err(err) => {
+                error!("Failed to update the collection of {}: {:?}", mint, err);
+                failed.push(FailedItem {
+                    mint: mint.to_string(),
+                    error: err.to_string(),
+                });
+            }
+        }
+    }
+
+    if failed.is_empty() {
+        pb.finish();
+    } else {
+        pb.abandon_with_message(format!("{}", style("Update failed ").red().bold()));
+
+        for item in &failed {
+            case_println!("- {}: {}", item.mint, item.error);
+        }
+        case_println!("\nRe-run the command to retry the failed NFTs.");
+    }
+
+    Ok((verified, failed))
+}
+
+/// Sends the instructions, retrying after an error with an increasing delay.
+fn send_with_retries(
+    case_config: &CaseConfig,
+    mint: &Pubkey,
+    instructions: &[Instruction],
+) -> Result<Signature> {
+    let mut attempt = 0;
+
+    loop {
+        match send_and_confirm(case_config, instructions, &[]) {
+            Ok(signature) => return Ok(signature),
+            Err(err) if attempt < COLLECTION_VERIFY_RETRIES => {
+                attempt += 1;
+                warn!(
+                    "Failed to update the collection of {}, retrying (attempt {}): {:?}",
+                    mint, attempt, err
+                );
+                thread::sleep(Duration::from_secs(attempt as u64));
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
@@ -29,9 +29,13 @@ pub use serde_json::{json, Value};
 pub use tracing::{debug, error, info, warn};
 
 pub use crate::{
+    address_book::resolve_pubkey,
     cache::{Cache, CacheItem},
+    case_print, case_println,
+    config::GlobalOptions,
     constants::*,
     errors::*,
+    output::is_json_output,
     parse::path_to_string,
     setup::{setup_client, case_setup},
 };
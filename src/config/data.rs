@@ -1,10 +1,12 @@
 use std::{
     fmt::{self, Display},
     str::FromStr,
+    sync::{Arc, Mutex},
 };
 
 use anchor_client::solana_sdk::{
-    native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Keypair,
+    commitment_config::CommitmentConfig, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey,
+    signature::Keypair,
 };
 pub use anyhow::{anyhow, Result};
 use chrono::DateTime;
@@ -16,17 +18,109 @@ use tars::{
 };
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::config::errors::*;
+use crate::{
+    config::errors::*,
+    constants::{
+        DEFAULT_ADDRESS_BOOK, DEFAULT_DENYLIST, DEFAULT_DEPLOYMENTS, DEFAULT_LAYERS, DEFAULT_PLAN,
+        DEFAULT_PROCESSED_ASSETS,
+    },
+};
 
 pub struct CaseConfig {
     pub keypair: Keypair,
     pub rpc_url: String,
+    /// Websocket url of the RPC url (--ws-url or the Solana config), derived from the
+    /// RPC url when not specified.
+    pub ws_url: Option<String>,
+    pub commitment: CommitmentConfig,
+    /// Endpoints of the command when more than one RPC url is specified, shared by the
+    /// configs of the same endpoints.
+    pub(crate) rpc_endpoints: Arc<Mutex<RpcEndpoints>>,
+    /// Genesis hash of the RPC, once requested (`None` if the RPC could not be reached).
+    pub(crate) genesis_hash: Arc<Mutex<Option<Option<String>>>>,
+    /// Uses a cache file on a different cluster than the one it was created on
+    /// (--force-cluster).
+    pub force_cluster: bool,
+}
+
+impl CaseConfig {
+    /// Returns the config of the same endpoints with a different keypair.
+    pub fn with_keypair(&self, keypair: Keypair) -> CaseConfig {
+        CaseConfig {
+            keypair,
+            rpc_url: self.rpc_url.clone(),
+            ws_url: self.ws_url.clone(),
+            commitment: self.commitment,
+            rpc_endpoints: self.rpc_endpoints.clone(),
+            genesis_hash: self.genesis_hash.clone(),
+            force_cluster: self.force_cluster,
+        }
+    }
+}
+
+/// RPC endpoints specified for the command and the healthy ones, in order of preference.
+#[derive(Debug, Default)]
+pub(crate) struct RpcEndpoints {
+    /// Endpoints that passed the health check.
+    pub healthy: Vec<String>,
+    /// Index of the endpoint in use in the list of healthy endpoints.
+    pub current: usize,
+}
+
+/// Options of the command line that apply to every command, passed to the commands in
+/// their arguments.
+#[derive(Clone, Debug)]
+pub struct GlobalOptions {
+    /// Derivation path of keypairs read from a seed phrase (e.g. "m/44'/501'/0'/0'").
+    pub derivation_path: Option<String>,
+    /// Path of the address book file used to resolve `@label` values.
+    pub address_book: String,
+    /// Websocket url (--ws-url) used with the RPC url of the command, instead of the one
+    /// derived from the RPC url.
+    pub ws_url: Option<String>,
+    /// RPC endpoints listed in the config file (`rpcUrl`), used when --rpc-url is not
+    /// specified.
+    pub config_rpc_urls: Vec<String>,
+    /// Uses a cache file on a different cluster than the one it was created on, or when
+    /// the cluster of the RPC cannot be checked.
+    pub force_cluster: bool,
+    /// Profile namespacing the default cache and config files (e.g. "devnet").
+    pub profile: Option<String>,
+    /// Accepts keypair files readable by other users on mainnet.
+    pub insecure_keypair: bool,
+    /// Path of the deny list file of sanctioned or flagged addresses.
+    pub denylist: String,
+    /// Path of the plan file written by dry runs.
+    pub plan_file: String,
+    /// Path of the registry of the tarss created by the project.
+    pub deployments: String,
+}
+
+impl Default for GlobalOptions {
+    fn default() -> Self {
+        GlobalOptions {
+            derivation_path: None,
+            address_book: DEFAULT_ADDRESS_BOOK.to_string(),
+            ws_url: None,
+            config_rpc_urls: Vec::new(),
+            force_cluster: false,
+            profile: None,
+            insecure_keypair: false,
+            denylist: DEFAULT_DENYLIST.to_string(),
+            plan_file: DEFAULT_PLAN.to_string(),
+            deployments: DEFAULT_DEPLOYMENTS.to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SolanaConfig {
     pub json_rpc_url: String,
+    /// Empty when the websocket url is derived from the RPC url.
+    #[serde(default)]
+    pub websocket_url: String,
     pub keypair_path: String,
+    #[serde(default)]
     pub commitment: String,
 }
 
@@ -38,15 +132,15 @@ pub struct ConfigData {
     pub gatekeeper: Option<GatekeeperConfig>,
     pub creators: Vec<Creator>,
 
-    #[serde(deserialize_with = "to_option_pubkey")]
+    #[serde(default, deserialize_with = "to_option_pubkey")]
     #[serde(serialize_with = "to_option_string")]
     pub sol_treasury_account: Option<Pubkey>,
 
-    #[serde(deserialize_with = "to_option_pubkey")]
+    #[serde(default, deserialize_with = "to_option_pubkey")]
     #[serde(serialize_with = "to_option_string")]
     pub spl_token_account: Option<Pubkey>,
 
-    #[serde(deserialize_with = "to_option_pubkey")]
+    #[serde(default, deserialize_with = "to_option_pubkey")]
     #[serde(serialize_with = "to_option_string")]
     pub spl_token: Option<Pubkey>,
 
@@ -68,14 +162,262 @@ pub struct ConfigData {
 
     pub seller_fee_basis_points: u16,
 
+    #[serde(serialize_with = "to_option_string")]
+    pub bundlr_node: Option<String>,
+
     #[serde(serialize_with = "to_option_string")]
     pub aws_s3_bucket: Option<String>,
 
+    #[serde(serialize_with = "to_option_string")]
+    pub aws_profile: Option<String>,
+
     #[serde(serialize_with = "to_option_string")]
     pub nft_storage_auth_token: Option<String>,
 
     #[serde(serialize_with = "to_option_string")]
     pub shdw_storage_account: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+
+    /// RPC endpoints (a url or a list), tried in order when an endpoint is unhealthy.
+    #[serde(default, deserialize_with = "to_rpc_urls")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rpc_url: Vec<String>,
+
+    /// Compute unit limits of the transactions of each command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compute_units: Option<ComputeUnits>,
+
+    /// Marketplaces whose requirements are checked by `verify --metadata-standards`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub marketplaces: Vec<Marketplace>,
+
+    /// Descending price applied by `schedule price`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_schedule: Option<PriceSchedule>,
+
+    /// Processing of the images applied by `process-images` before the upload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_processing: Option<ImageProcessing>,
+
+    /// Layers and rules of the assets generated by `generate`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generation: Option<Generation>,
+
+    /// Ranges of items verified in a different collection than the one of the tars.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub item_groups: Vec<ItemGroup>,
+}
+
+/// Range of items (by config line index) that belong to their own collection. The tars
+/// mints every item into its collection, so the items of a group are moved to the
+/// collection of the group by `collection verify-items`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemGroup {
+    pub name: String,
+
+    /// Index of the first item of the group.
+    pub start: u64,
+
+    /// Index of the last item of the group (inclusive).
+    pub end: u64,
+
+    #[serde(deserialize_with = "to_pubkey")]
+    #[serde(serialize_with = "to_string")]
+    pub collection_mint: Pubkey,
+}
+
+impl ItemGroup {
+    pub fn contains(&self, index: u64) -> bool {
+        (self.start..=self.end).contains(&index)
+    }
+}
+
+/// Generation of the assets from layers of trait images: the images of a layer are the
+/// files of its folder in `layers_dir`, named after the trait value with an optional
+/// weight (e.g. "Red#20.png", the weight defaults to 1).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Generation {
+    /// Directory with a folder of trait images for each layer.
+    #[serde(default = "default_layers")]
+    pub layers_dir: String,
+
+    /// Name of the items, followed by their number (e.g. "Tars #1").
+    pub name: String,
+
+    #[serde(default)]
+    pub description: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_url: Option<String>,
+
+    /// Layers in the order they are drawn, from the background to the top.
+    pub layers: Vec<Layer>,
+
+    /// Traits that are never combined in an item.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub incompatibilities: Vec<Incompatibility>,
+}
+
+fn default_layers() -> String {
+    DEFAULT_LAYERS.to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Layer {
+    /// Name of the folder of the layer.
+    pub name: String,
+
+    /// Trait type of the attribute [default: the name of the layer].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trait_type: Option<String>,
+
+    /// Weights of the trait values, overriding the weights of the file names.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weights: Vec<TraitWeight>,
+}
+
+/// Weight of a trait value: a value is picked with a probability proportional to its
+/// weight, a value with a weight of 0 is never picked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraitWeight {
+    pub value: String,
+    pub weight: u32,
+}
+
+/// Trait `value` of `layer` is never combined with trait `with_value` of `with_layer`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Incompatibility {
+    pub layer: String,
+    pub value: String,
+    pub with_layer: String,
+    pub with_value: String,
+}
+
+/// Processing of the images before the upload: images larger than `max_dimension` are
+/// downscaled (keeping their aspect ratio), re-encoded to reduce their size and
+/// optionally converted to another format.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageProcessing {
+    /// Directory the processed assets are written to.
+    #[serde(default = "default_processed_assets")]
+    pub output_dir: String,
+
+    /// Maximum width and height (in pixels).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_dimension: Option<u32>,
+
+    /// Format the images are converted to; an image is kept in its original format when
+    /// the converted image is larger.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<ImageFormat>,
+
+    /// Quality (1-100) of JPEG images.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<u8>,
+
+    /// Maximum width and height (in pixels) of the thumbnails, which are written to a
+    /// folder of the output directory and not uploaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail_size: Option<u32>,
+}
+
+fn default_processed_assets() -> String {
+    DEFAULT_PROCESSED_ASSETS.to_string()
+}
+
+/// Format of the processed images (WebP images are lossless).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Webp,
+}
+
+impl ImageFormat {
+    /// Extension of the image files.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Webp => "webp",
+        }
+    }
+
+    /// Returns the format of an image file extension, if it can be processed.
+    pub fn from_extension(extension: &str) -> Option<ImageFormat> {
+        match extension.to_lowercase().as_str() {
+            "png" => Some(ImageFormat::Png),
+            "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+            "webp" => Some(ImageFormat::Webp),
+            _ => None,
+        }
+    }
+}
+
+/// Price schedule of a descending (dutch) auction: the price starts at `start_price` on
+/// the go live date and drops by `step` every `interval` seconds down to `floor_price`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceSchedule {
+    pub start_price: f64,
+    pub floor_price: f64,
+    pub step: f64,
+    /// Seconds between two price drops.
+    pub interval: u64,
+}
+
+impl PriceSchedule {
+    /// Returns the number of price drops at `now` for a schedule starting at `start`.
+    fn drops(&self, start: i64, now: i64) -> u64 {
+        if now < start || self.interval == 0 {
+            0
+        } else {
+            (now - start) as u64 / self.interval
+        }
+    }
+
+    /// Returns the price at `now` for a schedule starting at `start`.
+    pub fn price_at(&self, start: i64, now: i64) -> f64 {
+        let price = self.start_price - self.step * self.drops(start, now) as f64;
+        // rounded to lamports, to avoid carrying the float error of the steps
+        (price.max(self.floor_price) * LAMPORTS_PER_SOL as f64).round() / LAMPORTS_PER_SOL as f64
+    }
+
+    /// Returns the timestamp of the next price drop after `now`, or `None` once the price
+    /// reached the floor.
+    pub fn next_drop(&self, start: i64, now: i64) -> Option<i64> {
+        if self.price_at(start, now) <= self.floor_price {
+            return None;
+        }
+
+        if now < start {
+            Some(start + self.interval as i64)
+        } else {
+            Some(start + ((self.drops(start, now) + 1) * self.interval) as i64)
+        }
+    }
+}
+
+/// Compute unit limits requested by `mint` and `deploy` transactions. Without a limit,
+/// mint transactions use the runtime default and config line transactions request
+/// `CONFIG_LINES_COMPUTE_UNITS` when sent with a priority fee.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComputeUnits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mint: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deploy: Option<u32>,
 }
 
 pub fn to_string<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
@@ -144,6 +486,24 @@ where
     Ok(Some(pubkey))
 }
 
+fn to_rpc_urls<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RpcUrls {
+        One(String),
+        List(Vec<String>),
+    }
+
+    Ok(match Option::<RpcUrls>::deserialize(deserializer)? {
+        Some(RpcUrls::One(url)) => vec![url],
+        Some(RpcUrls::List(urls)) => urls,
+        None => Vec::new(),
+    })
+}
+
 fn discount_price_to_lamports(discount_price: Option<f64>) -> Option<u64> {
     discount_price.map(|price| (price * LAMPORTS_PER_SOL as f64) as u64)
 }
@@ -232,6 +592,19 @@ impl WhitelistMintSettings {
             discount_price,
         }
     }
+
+    pub fn mint(&self) -> Pubkey {
+        self.mint
+    }
+
+    pub fn discount_price(&self) -> Option<f64> {
+        self.discount_price
+    }
+
+    pub fn set_discount_price(&mut self, discount_price: Option<f64>) {
+        self.discount_price = discount_price;
+    }
+
     pub fn to_tars_format(&self) -> TarsWhitelistMintSettings {
         TarsWhitelistMintSettings {
             mode: self.mode.to_tars_format(),
@@ -272,25 +645,26 @@ impl FromStr for WhitelistMintMode {
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct HiddenSettings {
-    name: String,
-    uri: String,
-    hash: String,
+    pub name: String,
+    pub uri: String,
+    pub hash: String,
 }
 
 impl HiddenSettings {
     pub fn new(name: String, uri: String, hash: String) -> HiddenSettings {
         HiddenSettings { name, uri, hash }
     }
-    pub fn to_tars_format(&self) -> TarsHiddenSettings {
-        TarsHiddenSettings {
+    pub fn to_tars_format(&self) -> Result<TarsHiddenSettings> {
+        Ok(TarsHiddenSettings {
             name: self.name.clone(),
             uri: self.uri.clone(),
-            hash: self
-                .hash
-                .as_bytes()
-                .try_into()
-                .expect("Hidden settings hash has to be 32 characters long!"),
-        }
+            hash: self.hash.as_bytes().try_into().map_err(|_| {
+                anyhow!(
+                    "Hidden settings hash has to be 32 characters long, found '{}'",
+                    self.hash
+                )
+            })?,
+        })
     }
 }
 
@@ -317,6 +691,56 @@ impl Default for UploadMethod {
     }
 }
 
+/// Role of the keypair used with a config file, from the least to the most privileged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Role {
+    /// Commands that only read on-chain state or local files.
+    ReadOnly,
+    /// Commands that write the assets, upload, deploy and mint.
+    Operator,
+    /// Every command, including the ones that change or close the tars.
+    Authority,
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Role::ReadOnly => write!(f, "read-only"),
+            Role::Operator => write!(f, "operator"),
+            Role::Authority => write!(f, "authority"),
+        }
+    }
+}
+
+/// Marketplace with listing requirements on the metadata and images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Marketplace {
+    MagicEden,
+    #[serde(rename = "opensea")]
+    OpenSea,
+    Tensor,
+}
+
+impl Marketplace {
+    pub const ALL: [Marketplace; 3] = [
+        Marketplace::MagicEden,
+        Marketplace::OpenSea,
+        Marketplace::Tensor,
+    ];
+}
+
+impl Display for Marketplace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Marketplace::MagicEden => write!(f, "Magic Eden"),
+            Marketplace::OpenSea => write!(f, "OpenSea"),
+            Marketplace::Tensor => write!(f, "Tensor"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Default, Serialize)]
 pub struct Creator {
     #[serde(deserialize_with = "to_pubkey")]
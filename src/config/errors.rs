@@ -5,6 +5,15 @@ pub enum ConfigError {
     #[error("Could not parse the config file ({0})")]
     ParseError(String),
 
+    #[error("Invalid config file:\n  {}", .0.join("\n  "))]
+    SchemaError(Vec<String>),
+
+    #[error(
+        "Missing environment variables referenced in the config file:\n  {}",
+        .0.join("\n  ")
+    )]
+    MissingEnvVars(Vec<String>),
+
     #[error("Missing configuration file '{0}'")]
     MissingFileError(String),
 
@@ -1,6 +1,7 @@
 pub mod data;
 pub mod errors;
 pub mod parser;
+pub mod schema;
 
 pub use data::*;
 pub use errors::*;
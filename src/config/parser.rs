@@ -1,16 +1,117 @@
 use std::{
-    fs::{metadata, OpenOptions},
-    io::ErrorKind,
+    env,
+    fs::{self, metadata, OpenOptions},
+    io::{ErrorKind, Read},
+    path::Path,
 };
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::{Map, Value};
 use tracing::error;
 
-use crate::config::{data::*, errors::ConfigError};
+use crate::config::{
+    data::*,
+    errors::ConfigError,
+    schema::{resolve_address_labels, validate_schema},
+};
+
+/// Format of the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConfigFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// Detects the format from the file extension, defaulting to JSON.
+    pub fn from_path(path: &str) -> ConfigFormat {
+        match Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase())
+            .as_deref()
+        {
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            Some("toml") => ConfigFormat::Toml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Yaml => "yaml",
+            ConfigFormat::Toml => "toml",
+        }
+    }
+}
+
+/// Serializes the config data (or the raw content of a config file) in the specified
+/// format.
+pub fn config_to_string<T: Serialize>(config_data: &T, format: ConfigFormat) -> Result<String> {
+    Ok(match format {
+        ConfigFormat::Json => serde_json::to_string_pretty(config_data)?,
+        ConfigFormat::Yaml => serde_yaml::to_string(config_data)?,
+        // converted to a TOML value first so that plain values are written before tables
+        ConfigFormat::Toml => toml::to_string_pretty(&toml::Value::try_from(config_data)?)?,
+    })
+}
+
+/// Reads the config file as it is written, without resolving `${ENV_VAR}` placeholders or
+/// address labels, so that it can be updated without writing the resolved values back.
+pub fn read_raw_config(config_path: &str) -> Result<Value> {
+    let content = fs::read_to_string(config_path)?;
+
+    Ok(match ConfigFormat::from_path(config_path) {
+        ConfigFormat::Json => serde_json::from_str(&content)?,
+        ConfigFormat::Yaml => serde_yaml::from_str(&content)?,
+        ConfigFormat::Toml => toml::from_str(&content)?,
+    })
+}
 
-pub fn get_config_data(config_path: &str) -> Result<ConfigData, ConfigError> {
+/// Sets fields of the config file (by path, e.g. `hiddenSettings.hash`), removing the
+/// ones set to null. Only these fields are replaced in the content of the file, so that
+/// env var placeholders, address labels and the fields of the profile are not written
+/// back resolved.
+pub fn update_config_fields(config_path: &str, fields: &[(&str, Value)]) -> Result<()> {
+    let mut content = read_raw_config(config_path)?;
+    let invalid = || anyhow!("Invalid config file '{}'", config_path);
+
+    for (path, value) in fields {
+        let (parents, field) = match path.rsplit_once('.') {
+            Some((parents, field)) => (parents.split('.').collect(), field),
+            None => (Vec::new(), *path),
+        };
+
+        let mut object = &mut content;
+        for parent in parents {
+            object = object
+                .as_object_mut()
+                .ok_or_else(invalid)?
+                .entry(parent)
+                .or_insert_with(|| Value::Object(Map::new()));
+        }
+        let object = object.as_object_mut().ok_or_else(invalid)?;
+
+        if value.is_null() {
+            object.remove(field);
+        } else {
+            object.insert(field.to_string(), value.clone());
+        }
+    }
+
+    let format = ConfigFormat::from_path(config_path);
+    fs::write(config_path, config_to_string(&content, format)?)?;
+
+    Ok(())
+}
+
+pub fn get_config_data(config_path: &str, address_book: &str) -> Result<ConfigData, ConfigError> {
     // checks that the config file exists and it is readable
-    let f = match OpenOptions::new().read(true).open(config_path) {
+    let mut f = match OpenOptions::new().read(true).open(config_path) {
         Ok(f) => f,
         Err(err) => {
             let error = match err.kind() {
@@ -29,7 +130,48 @@ pub fn get_config_data(config_path: &str) -> Result<ConfigData, ConfigError> {
         return Err(error);
     }
 
-    let config_data: ConfigData = match serde_json::from_reader(f) {
+    let parsed = match ConfigFormat::from_path(config_path) {
+        ConfigFormat::Json => serde_json::from_reader(f).map_err(|err| err.to_string()),
+        ConfigFormat::Yaml => serde_yaml::from_reader(f).map_err(|err| err.to_string()),
+        ConfigFormat::Toml => {
+            let mut content = String::new();
+            f.read_to_string(&mut content)
+                .map_err(|err| err.to_string())
+                .and_then(|_| toml::from_str(&content).map_err(|err| err.to_string()))
+        }
+    };
+
+    let mut value: Value = match parsed {
+        Ok(value) => value,
+        Err(err) => {
+            let error = ConfigError::ParseError(err);
+            error!("{:?}", error);
+            return Err(error);
+        }
+    };
+
+    // resolves ${ENV_VAR} placeholders so secrets do not need to be stored in the file
+    let mut missing = Vec::new();
+    interpolate_env_vars("$", &mut value, &mut missing);
+
+    if !missing.is_empty() {
+        let error = ConfigError::MissingEnvVars(missing);
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    // reports all problems at once instead of the first serde error
+    let mut errors = resolve_address_labels(&mut value, address_book);
+    errors.extend(validate_schema(&value));
+
+    if !errors.is_empty() {
+        let error =
+            ConfigError::SchemaError(errors.iter().map(|error| error.to_string()).collect());
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    let config_data: ConfigData = match serde_json::from_value(value) {
         Ok(config_data) => config_data,
         Err(err) => {
             let error = ConfigError::ParseError(err.to_string());
@@ -39,3 +181,53 @@ pub fn get_config_data(config_path: &str) -> Result<ConfigData, ConfigError> {
     };
     Ok(config_data)
 }
+
+/// Replaces `${ENV_VAR}` placeholders in all string values, collecting the
+/// variables that are not set (with the path of the value).
+fn interpolate_env_vars(path: &str, value: &mut Value, missing: &mut Vec<String>) {
+    match value {
+        Value::String(string) if string.contains("${") => {
+            *string = interpolate_string(path, string, missing);
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                interpolate_env_vars(&format!("{}[{}]", path, index), item, missing);
+            }
+        }
+        Value::Object(object) => {
+            for (key, item) in object.iter_mut() {
+                interpolate_env_vars(&format!("{}.{}", path, key), item, missing);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn interpolate_string(path: &str, input: &str, missing: &mut Vec<String>) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find("${") {
+        output.push_str(&rest[..start]);
+        let placeholder = &rest[start + 2..];
+
+        match placeholder.find('}') {
+            Some(end) => {
+                let name = &placeholder[..end];
+                match env::var(name) {
+                    Ok(variable) => output.push_str(&variable),
+                    Err(_) => missing.push(format!("{} (at {})", name, path)),
+                }
+                rest = &placeholder[end + 1..];
+            }
+            // not a placeholder, kept as it is
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
@@ -0,0 +1,412 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use serde_json::{Map, Value};
+
+use crate::{
+    address_book::{resolve_pubkey, LABEL_PREFIX},
+    constants::MAX_COMPUTE_UNITS,
+};
+
+/// Expected type of a config value.
+pub enum FieldType {
+    Number,
+    Integer(u64),
+    Bool,
+    String,
+    Pubkey,
+    Enum(&'static [&'static str]),
+    Object(&'static [Field]),
+    Array(&'static FieldType),
+    /// A single value or an array of values.
+    OneOrMany(&'static FieldType),
+}
+
+/// Field of a config object.
+pub struct Field {
+    pub name: &'static str,
+    pub field_type: FieldType,
+    /// Whether the field must be present (even if `null` is accepted).
+    pub required: bool,
+    pub nullable: bool,
+}
+
+const fn field(name: &'static str, field_type: FieldType) -> Field {
+    Field {
+        name,
+        field_type,
+        required: true,
+        nullable: false,
+    }
+}
+
+const fn optional(name: &'static str, field_type: FieldType) -> Field {
+    Field {
+        name,
+        field_type,
+        required: false,
+        nullable: true,
+    }
+}
+
+const GATEKEEPER_FIELDS: &[Field] = &[
+    field("gatekeeperNetwork", FieldType::Pubkey),
+    field("expireOnUse", FieldType::Bool),
+];
+
+const CREATOR_FIELDS: &[Field] = &[
+    field("address", FieldType::Pubkey),
+    field("share", FieldType::Integer(u8::MAX as u64)),
+];
+
+const CREATOR_TYPE: FieldType = FieldType::Object(CREATOR_FIELDS);
+
+const END_SETTINGS_FIELDS: &[Field] = &[
+    field("endSettingType", FieldType::Enum(&["Date", "Amount"])),
+    field("number", FieldType::Integer(u64::MAX)),
+];
+
+const WHITELIST_MINT_SETTINGS_FIELDS: &[Field] = &[
+    field("mode", FieldType::Enum(&["burnEveryTime", "neverBurn"])),
+    field("mint", FieldType::Pubkey),
+    field("presale", FieldType::Bool),
+    optional("discountPrice", FieldType::Number),
+];
+
+const HIDDEN_SETTINGS_FIELDS: &[Field] = &[
+    field("name", FieldType::String),
+    field("uri", FieldType::String),
+    field("hash", FieldType::String),
+];
+
+const COMPUTE_UNITS_FIELDS: &[Field] = &[
+    optional("mint", FieldType::Integer(MAX_COMPUTE_UNITS as u64)),
+    optional("deploy", FieldType::Integer(MAX_COMPUTE_UNITS as u64)),
+];
+
+const PRICE_SCHEDULE_FIELDS: &[Field] = &[
+    field("startPrice", FieldType::Number),
+    field("floorPrice", FieldType::Number),
+    field("step", FieldType::Number),
+    field("interval", FieldType::Integer(u64::MAX)),
+];
+
+const IMAGE_PROCESSING_FIELDS: &[Field] = &[
+    optional("outputDir", FieldType::String),
+    optional("maxDimension", FieldType::Integer(u32::MAX as u64)),
+    optional("format", FieldType::Enum(&["png", "jpeg", "webp"])),
+    optional("quality", FieldType::Integer(100)),
+    optional("thumbnailSize", FieldType::Integer(u32::MAX as u64)),
+];
+
+const TRAIT_WEIGHT_FIELDS: &[Field] = &[
+    field("value", FieldType::String),
+    field("weight", FieldType::Integer(u32::MAX as u64)),
+];
+
+const TRAIT_WEIGHT_TYPE: FieldType = FieldType::Object(TRAIT_WEIGHT_FIELDS);
+
+const LAYER_FIELDS: &[Field] = &[
+    field("name", FieldType::String),
+    optional("traitType", FieldType::String),
+    optional("weights", FieldType::Array(&TRAIT_WEIGHT_TYPE)),
+];
+
+const LAYER_TYPE: FieldType = FieldType::Object(LAYER_FIELDS);
+
+const INCOMPATIBILITY_FIELDS: &[Field] = &[
+    field("layer", FieldType::String),
+    field("value", FieldType::String),
+    field("withLayer", FieldType::String),
+    field("withValue", FieldType::String),
+];
+
+const INCOMPATIBILITY_TYPE: FieldType = FieldType::Object(INCOMPATIBILITY_FIELDS);
+
+const GENERATION_FIELDS: &[Field] = &[
+    optional("layersDir", FieldType::String),
+    field("name", FieldType::String),
+    optional("description", FieldType::String),
+    optional("externalUrl", FieldType::String),
+    field("layers", FieldType::Array(&LAYER_TYPE)),
+    optional("incompatibilities", FieldType::Array(&INCOMPATIBILITY_TYPE)),
+];
+
+const ITEM_GROUP_FIELDS: &[Field] = &[
+    field("name", FieldType::String),
+    field("start", FieldType::Integer(u64::MAX)),
+    field("end", FieldType::Integer(u64::MAX)),
+    field("collectionMint", FieldType::Pubkey),
+];
+
+const ITEM_GROUP_TYPE: FieldType = FieldType::Object(ITEM_GROUP_FIELDS);
+
+/// Fields of the config file (`ConfigData`).
+pub const CONFIG_FIELDS: &[Field] = &[
+    field("price", FieldType::Number),
+    field("number", FieldType::Integer(u64::MAX)),
+    optional("gatekeeper", FieldType::Object(GATEKEEPER_FIELDS)),
+    field("creators", FieldType::Array(&CREATOR_TYPE)),
+    optional("solTreasuryAccount", FieldType::Pubkey),
+    optional("splTokenAccount", FieldType::Pubkey),
+    optional("splToken", FieldType::Pubkey),
+    optional("goLiveDate", FieldType::String),
+    optional("endSettings", FieldType::Object(END_SETTINGS_FIELDS)),
+    optional(
+        "whitelistMintSettings",
+        FieldType::Object(WHITELIST_MINT_SETTINGS_FIELDS),
+    ),
+    optional("hiddenSettings", FieldType::Object(HIDDEN_SETTINGS_FIELDS)),
+    field(
+        "uploadMethod",
+        FieldType::Enum(&["bundlr", "aws", "nft_storage", "shdw"]),
+    ),
+    field("retainAuthority", FieldType::Bool),
+    field("isMutable", FieldType::Bool),
+    field("symbol", FieldType::String),
+    field("sellerFeeBasisPoints", FieldType::Integer(u16::MAX as u64)),
+    optional("bundlrNode", FieldType::String),
+    optional("awsS3Bucket", FieldType::String),
+    optional("awsProfile", FieldType::String),
+    optional("nftStorageAuthToken", FieldType::String),
+    optional("shdwStorageAccount", FieldType::String),
+    optional(
+        "role",
+        FieldType::Enum(&["read-only", "operator", "authority"]),
+    ),
+    optional("rpcUrl", FieldType::OneOrMany(&FieldType::String)),
+    optional("computeUnits", FieldType::Object(COMPUTE_UNITS_FIELDS)),
+    optional(
+        "marketplaces",
+        FieldType::Array(&FieldType::Enum(&["magic-eden", "opensea", "tensor"])),
+    ),
+    optional("priceSchedule", FieldType::Object(PRICE_SCHEDULE_FIELDS)),
+    optional(
+        "imageProcessing",
+        FieldType::Object(IMAGE_PROCESSING_FIELDS),
+    ),
+    optional("generation", FieldType::Object(GENERATION_FIELDS)),
+    optional("itemGroups", FieldType::Array(&ITEM_GROUP_TYPE)),
+];
+
+/// Problem found in the config file.
+#[derive(Debug)]
+pub struct SchemaError {
+    /// JSON path of the value, e.g. `$.creators[0].share`.
+    pub path: String,
+    pub message: String,
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldType::Number => write!(f, "number"),
+            FieldType::Integer(u64::MAX) => write!(f, "non-negative integer"),
+            FieldType::Integer(max) => write!(f, "integer between 0 and {}", max),
+            FieldType::Bool => write!(f, "boolean"),
+            FieldType::String => write!(f, "string"),
+            FieldType::Pubkey => write!(f, "public key (base58 string)"),
+            FieldType::Enum(values) => write!(
+                f,
+                "one of {}",
+                values
+                    .iter()
+                    .map(|value| format!("\"{}\"", value))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            FieldType::Object(_) => write!(f, "object"),
+            FieldType::Array(item) => write!(f, "array of {}", item),
+            FieldType::OneOrMany(item) => write!(f, "{} or array of {}", item, item),
+        }
+    }
+}
+
+/// Validates the config file against the expected fields and types, returning
+/// all problems found.
+pub fn validate_schema(value: &Value) -> Vec<SchemaError> {
+    let mut errors = Vec::new();
+    check_value("$", value, &FieldType::Object(CONFIG_FIELDS), &mut errors);
+    errors
+}
+
+/// Replaces `@label` values of pubkey fields with the addresses from the address
+/// book, returning the labels that could not be resolved.
+pub fn resolve_address_labels(value: &mut Value, address_book: &str) -> Vec<SchemaError> {
+    let mut errors = Vec::new();
+    resolve_value(
+        "$",
+        value,
+        &FieldType::Object(CONFIG_FIELDS),
+        address_book,
+        &mut errors,
+    );
+    errors
+}
+
+fn resolve_value(
+    path: &str,
+    value: &mut Value,
+    field_type: &FieldType,
+    address_book: &str,
+    errors: &mut Vec<SchemaError>,
+) {
+    match (field_type, value) {
+        (FieldType::Pubkey, Value::String(address)) if address.starts_with(LABEL_PREFIX) => {
+            match resolve_pubkey(address, address_book) {
+                Ok(pubkey) => *address = pubkey.to_string(),
+                Err(err) => errors.push(SchemaError {
+                    path: path.to_string(),
+                    message: err.to_string(),
+                }),
+            }
+        }
+        (FieldType::Object(fields), Value::Object(object)) => {
+            for field in fields.iter() {
+                if let Some(item) = object.get_mut(field.name) {
+                    let item_path = format!("{}.{}", path, field.name);
+                    resolve_value(&item_path, item, &field.field_type, address_book, errors);
+                }
+            }
+        }
+        (FieldType::Array(item_type), Value::Array(items)) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                let item_path = format!("{}[{}]", path, index);
+                resolve_value(&item_path, item, item_type, address_book, errors);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn check_value(path: &str, value: &Value, field_type: &FieldType, errors: &mut Vec<SchemaError>) {
+    let valid = match (field_type, value) {
+        (FieldType::Number, Value::Number(_)) => true,
+        (FieldType::Integer(max), Value::Number(number)) => {
+            matches!(number.as_u64(), Some(number) if number <= *max)
+        }
+        (FieldType::Bool, Value::Bool(_)) => true,
+        (FieldType::String, Value::String(_)) => true,
+        // unresolved labels are reported by resolve_address_labels
+        (FieldType::Pubkey, Value::String(pubkey)) => {
+            pubkey.starts_with(LABEL_PREFIX) || Pubkey::from_str(pubkey).is_ok()
+        }
+        (FieldType::Enum(values), Value::String(value)) => values.contains(&value.as_str()),
+        (FieldType::Object(fields), Value::Object(object)) => {
+            check_object(path, object, fields, errors);
+            true
+        }
+        (FieldType::Array(item_type), Value::Array(items)) => {
+            for (index, item) in items.iter().enumerate() {
+                check_value(&format!("{}[{}]", path, index), item, item_type, errors);
+            }
+            true
+        }
+        (FieldType::OneOrMany(item_type), Value::Array(items)) => {
+            for (index, item) in items.iter().enumerate() {
+                check_value(&format!("{}[{}]", path, index), item, item_type, errors);
+            }
+            true
+        }
+        (FieldType::OneOrMany(item_type), value) => {
+            let mut item_errors = Vec::new();
+            check_value(path, value, item_type, &mut item_errors);
+            item_errors.is_empty()
+        }
+        _ => false,
+    };
+
+    if !valid {
+        errors.push(SchemaError {
+            path: path.to_string(),
+            message: format!("expected {}, found {}", field_type, describe(value)),
+        });
+    }
+}
+
+fn check_object(
+    path: &str,
+    object: &Map<String, Value>,
+    fields: &[Field],
+    errors: &mut Vec<SchemaError>,
+) {
+    for field in fields {
+        let field_path = format!("{}.{}", path, field.name);
+
+        match object.get(field.name) {
+            Some(Value::Null) if field.nullable => (),
+            Some(value) => check_value(&field_path, value, &field.field_type, errors),
+            None if field.required => errors.push(SchemaError {
+                path: field_path,
+                message: format!("missing required field (expected {})", field.field_type),
+            }),
+            None => (),
+        }
+    }
+
+    for key in object.keys() {
+        if !fields.iter().any(|field| field.name == key) {
+            let message = match closest_field(key, fields) {
+                Some(name) => format!("unknown field, did you mean '{}'?", name),
+                None => "unknown field".to_string(),
+            };
+
+            errors.push(SchemaError {
+                path: format!("{}.{}", path, key),
+                message,
+            });
+        }
+    }
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(_) => "boolean".to_string(),
+        Value::Number(number) => format!("number {}", number),
+        Value::String(string) => format!("string \"{}\"", string),
+        Value::Array(_) => "array".to_string(),
+        Value::Object(_) => "object".to_string(),
+    }
+}
+
+/// Returns the known field closest to a misspelled name.
+fn closest_field(key: &str, fields: &[Field]) -> Option<&'static str> {
+    let key = key.to_lowercase();
+
+    fields
+        .iter()
+        .map(|field| (field.name, edit_distance(&key, &field.name.to_lowercase())))
+        .filter(|(name, distance)| *distance <= (name.len() / 3).max(2))
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(name, _)| name)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
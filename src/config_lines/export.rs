@@ -0,0 +1,155 @@
+use std::fs;
+
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    cache::load_cluster_cache,
+    common::*,
+    tars::{config_line, unminted_indices, TARS_ID},
+    utils::spinner_with_style,
+};
+
+pub struct ExportConfigLinesArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub tars: Option<String>,
+    pub output: String,
+}
+
+/// Config line of the tars account. The name and uri are none for lines that were not
+/// written yet.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedConfigLine {
+    pub index: u32,
+    pub name: Option<String>,
+    pub uri: Option<String>,
+    pub minted: bool,
+}
+
+/// Result of the export config-lines command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportConfigLinesOutcome {
+    pub tars: String,
+    pub file: String,
+    pub lines: usize,
+    pub minted: usize,
+    /// Number of config lines not written yet.
+    pub missing: usize,
+}
+
+pub fn process_export_config_lines(
+    args: ExportConfigLinesArgs,
+) -> Result<ExportConfigLinesOutcome> {
+    case_println!(
+        "{} {}Reading tars",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+
+    // the tars id specified takes precedence over the one from the cache
+    let tars_id = match args.tars {
+        Some(tars_id) => tars_id,
+        None => {
+            load_cluster_cache(&case_config, &args.cache, false)?
+                .program
+                .tars
+        }
+    };
+    let tars_pubkey = resolve_pubkey(&tars_id, &args.global.address_book)?;
+
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    let data = program
+        .rpc()
+        .get_account_data(&tars_pubkey)
+        .map_err(|err| {
+            anyhow!(
+                "Failed to get the account data of tars {}: {}",
+                tars_pubkey,
+                err
+            )
+        })?;
+    let tars = Tars::try_deserialize(&mut data.as_slice())?;
+
+    pb.finish_and_clear();
+
+    if tars.data.hidden_settings.is_some() {
+        return Err(anyhow!(
+            "Tars {} uses hidden settings and has no config lines",
+            tars_pubkey
+        ));
+    }
+
+    case_println!(
+        "\n{} {}Exporting config lines",
+        style("[2/2]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    let unminted = unminted_indices(&data, &tars.data, tars.items_redeemed);
+
+    let lines: Vec<ExportedConfigLine> = (0..tars.data.items_available as u32)
+        .map(|index| {
+            let (name, uri) = match config_line(&data, index as usize) {
+                Some((name, uri)) => (Some(name), Some(uri)),
+                None => (None, None),
+            };
+
+            ExportedConfigLine {
+                index,
+                name,
+                uri,
+                minted: unminted.binary_search(&index).is_err(),
+            }
+        })
+        .collect();
+
+    fs::write(&args.output, serde_json::to_string_pretty(&lines)?).map_err(|err| {
+        anyhow!(
+            "Failed to write config lines file '{}': {}",
+            args.output,
+            err
+        )
+    })?;
+
+    let outcome = ExportConfigLinesOutcome {
+        tars: tars_pubkey.to_string(),
+        file: args.output,
+        lines: lines.len(),
+        minted: lines.iter().filter(|line| line.minted).count(),
+        missing: lines.iter().filter(|line| line.name.is_none()).count(),
+    };
+
+    case_println!("{} {}", style("Config lines:").bold(), outcome.lines);
+    case_println!("{} {}", style("Minted:").bold(), outcome.minted);
+
+    if outcome.missing > 0 {
+        case_println!(
+            "{}",
+            style(format!(
+                "{} config line(s) not written yet.",
+                outcome.missing
+            ))
+            .yellow()
+        );
+    }
+
+    case_println!(
+        "{} Config lines written to '{}'",
+        COMPLETE_EMOJI,
+        outcome.file
+    );
+
+    Ok(outcome)
+}
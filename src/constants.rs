@@ -46,6 +46,25 @@ pub const STRING_LEN_SIZE: usize = 4;
 
 pub const CONFIG_CHUNK_SIZE: usize = 10;
 
+/// Default maximum number of config lines per transaction.
+pub const CONFIG_LINES_BATCH_SIZE: usize = 17;
+
+/// Number of times config lines are retried after a transient error.
+pub const CONFIG_LINES_RETRIES: u32 = 3;
+
+/// Number of times the collection of a minted NFT is retried after an error.
+pub const COLLECTION_VERIFY_RETRIES: u32 = 3;
+
+/// Priority fee (in lamports) added on every retry of config lines.
+pub const CONFIG_LINES_PRIORITY_FEE_INCREMENT: u32 = 10_000;
+
+/// Compute units requested by `add_config_lines` transactions with a priority fee, unless
+/// a limit is set in the config file (`computeUnits.deploy`) or with `--compute-units`.
+pub const CONFIG_LINES_COMPUTE_UNITS: u32 = 200_000;
+
+/// Maximum number of compute units a transaction can request.
+pub const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+
 pub const CONFIG_NAME_OFFSET: usize = STRING_LEN_SIZE;
 
 pub const CONFIG_URI_OFFSET: usize = STRING_LEN_SIZE + CONFIG_NAME_OFFSET + MAX_NAME_LENGTH;
@@ -58,6 +77,53 @@ pub const DEFAULT_UUID: &str = "000000";
 /// and network connections).
 pub const PARALLEL_LIMIT: usize = 45;
 
+/// Number of items whose stored files are compared with the local files by
+/// `upload --verify-storage`, unless `--full` is used.
+pub const STORAGE_CHECK_SAMPLE: usize = 20;
+
+/// Default maximum number of files open at the same time during an upload.
+pub const DEFAULT_MAX_OPEN_FILES: usize = 64;
+
+/// Default cap (in MB) of the media files held in memory during an upload.
+pub const DEFAULT_MAX_BUFFER_MB: u32 = 512;
+
+/// File with the name of the profile set with `profile switch`.
+pub const PROFILE_FILE: &str = ".case/profile";
+
+/// Directory of the log files, one per run.
+pub const LOGS_DIR: &str = ".case/logs";
+
+/// Number of run log files kept in the logs directory.
+pub const MAX_RUN_LOGS: usize = 100;
+
+/// Default forecast cost (in SOL) of a bulk mint above which it must be confirmed.
+pub const DEFAULT_MINT_CONFIRM_SOL: f64 = 1.0;
+
+/// Default number of mints simulated by `rehearse-golive`.
+pub const DEFAULT_REHEARSAL_BURST: u64 = 20;
+
+/// Default number of mints simulated at the same time by `rehearse-golive`.
+pub const DEFAULT_REHEARSAL_CONCURRENCY: usize = 10;
+
+/// Delay (in milliseconds) after the scheduled time above which `rehearse-golive` warns
+/// that the burst started late.
+pub const REHEARSAL_MAX_START_DELAY_MS: u64 = 1000;
+
+/// Time to wait for the confirmation of a transaction before giving up.
+pub const TX_CONFIRMATION_TIMEOUT_SECS: u64 = 90;
+
+/// Number of times a transaction is rebuilt and sent again after its blockhash expired.
+pub const TX_RESENDS: u32 = 3;
+
+/// Time to wait for an RPC endpoint to respond to the health check.
+pub const RPC_PROBE_TIMEOUT_SECS: u64 = 10;
+
+/// Maximum number of slots an RPC endpoint can be behind the others to be healthy.
+pub const MAX_RPC_SLOT_LAG: u64 = 150;
+
+/// Fee charged for each signature of a transaction.
+pub const SIGNATURE_FEE: u64 = 5000;
+
 /// Default path for assets folder.
 pub const DEFAULT_ASSETS: &str = "assets";
 
@@ -67,9 +133,89 @@ pub const DEFAULT_CACHE: &str = "cache.json";
 /// Default path for config file.
 pub const DEFAULT_CONFIG: &str = "config.json";
 
+/// Default file of the receipts of the items reserved for the team.
+pub const DEFAULT_RESERVE_RECEIPTS: &str = "reserve-receipts.json";
+
+/// Default file of the progress of a whitelist token airdrop.
+pub const DEFAULT_WHITELIST_PROGRESS: &str = "whitelist-airdrop.json";
+
+/// Default file of the config lines exported from a tars.
+pub const DEFAULT_CONFIG_LINES: &str = "config-lines.json";
+
+/// Default path for the address book file (label → pubkey).
+pub const DEFAULT_ADDRESS_BOOK: &str = "addresses.json";
+
+/// Default path for the plan file written by dry runs.
+pub const DEFAULT_PLAN: &str = "plan.json";
+
+/// Default path for the registry of the tarss created by the project.
+pub const DEFAULT_DEPLOYMENTS: &str = "deployments.json";
+
+/// Default path for the deny list file (sanctioned or flagged addresses).
+pub const DEFAULT_DENYLIST: &str = "denylist.json";
+
+/// Directory where the config and cache files of a sandbox are written.
+pub const DEFAULT_SANDBOX_DIR: &str = "sandbox";
+
+/// Directory where the files of the self-test are written.
+pub const DEFAULT_SELFTEST_DIR: &str = ".case-selftest";
+
+/// Directory where the metadata files of a remote staging source or an assets archive
+/// are staged.
+pub const DEFAULT_STAGING_DIR: &str = ".case-staging";
+
+/// Directory where `collection create` stages the collection files for upload.
+pub const COLLECTION_STAGING_DIR: &str = ".case-staging-collection";
+
+/// Default directory where `process-images` writes the processed assets.
+pub const DEFAULT_PROCESSED_ASSETS: &str = "processed-assets";
+
+/// Folder of the output directory of `process-images` with the thumbnails, which are not
+/// uploaded.
+pub const THUMBNAILS_DIR: &str = "thumbnails";
+
+/// Default quality (1-100) of the JPEG images written by `process-images`.
+pub const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+/// Default directory with the layers of `generate`, a folder of trait images per layer.
+pub const DEFAULT_LAYERS: &str = "layers";
+
+/// Attempts of `generate` to find a new combination of traits for each item, before
+/// giving up when the layers have too few unique combinations.
+pub const GENERATION_ATTEMPTS: usize = 1000;
+
+/// Directory where the transactions of `--sign-offline` are written.
+pub const OFFLINE_TX_DIR: &str = "transactions";
+
+/// Path of the proof file of a hidden drop shuffle.
+pub const DEFAULT_SHUFFLE_PROOF: &str = "shuffle.json";
+
+/// Directory (inside the assets directory) where `validate --fix` saves the original files.
+pub const FIX_BACKUP_DIR: &str = ".case-backup";
+
 /// Default path for keypair file.
 pub const DEFAULT_KEYPATH: &str = "~/.config/solana/id.json";
 
+/// Environment variable with the keypair (or the path of a keypair file) used when `--keypair`
+/// is not specified.
+pub const KEYPAIR_ENV: &str = "CASE_KEYPAIR";
+
+/// Environment variable with the IPFS gateways (comma-separated) used when
+/// `--ipfs-gateway` is not specified.
+pub const IPFS_GATEWAYS_ENV: &str = "CASE_IPFS_GATEWAYS";
+
+/// IPFS gateways used to download `ipfs://` links, in order of preference.
+pub const DEFAULT_IPFS_GATEWAYS: &[&str] = &[
+    "https://nftstorage.link",
+    "https://ipfs.io",
+    "https://cloudflare-ipfs.com",
+    "https://dweb.link",
+    "https://gateway.pinata.cloud",
+];
+
+/// Number of words accepted in a seed phrase.
+pub const SEED_PHRASE_WORDS: [usize; 5] = [12, 15, 18, 21, 24];
+
 /// Bundlr devnet endpoint.
 pub const BUNDLR_DEVNET: &str = "https://devnet.bundlr.network";
 
@@ -110,3 +256,11 @@ pub const COLLECTION_EMOJI: Emoji<'_, '_> = Emoji("📦 ", "");
 pub const ERROR_EMOJI: Emoji<'_, '_> = Emoji("🛑 ", "");
 
 pub const WARNING_EMOJI: Emoji<'_, '_> = Emoji("⚠️ ", "");
+
+pub const SIGNING_EMOJI: Emoji<'_, '_> = Emoji("✍️  ", "");
+
+pub const TICKET_EMOJI: Emoji<'_, '_> = Emoji("🎫 ", "");
+
+pub const CLOCK_EMOJI: Emoji<'_, '_> = Emoji("⏰ ", "");
+
+pub const IMAGE_EMOJI: Emoji<'_, '_> = Emoji("🖼  ", "");
@@ -0,0 +1,169 @@
+use std::{env, fs, panic};
+
+use chrono::Utc;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{
+    cli::Commands,
+    common::*,
+    config::ConfigFormat,
+    role::{command_name, config_path},
+};
+
+/// Placeholder of the values removed from a report.
+const REDACTED: &str = "<redacted>";
+
+lazy_static! {
+    /// Values that could identify the user: URLs (which may contain API keys), addresses,
+    /// signatures and long hex or base64 strings.
+    static ref SENSITIVE: Regex = Regex::new(
+        r"[a-zA-Z][a-zA-Z0-9+.-]*://\S+|\b[1-9A-HJ-NP-Za-km-z]{32,88}\b|\b[0-9a-fA-F]{32,}\b|[A-Za-z0-9+/]{40,}={0,2}"
+    )
+    .expect("Failed to compile sensitive values regex.");
+}
+
+/// Context of the running command, only collected when the user opted in to error
+/// reports.
+#[derive(Clone, Debug)]
+pub struct ReportContext {
+    command: String,
+    flags: Vec<String>,
+    config: String,
+}
+
+/// Diagnostic report written on a fatal error when `--report-errors` is specified.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorReport {
+    pub created_at: String,
+    pub case_version: String,
+    pub os: String,
+    pub arch: String,
+    pub command: String,
+    /// Flags of the command line, without their values.
+    pub flags: Vec<String>,
+    /// Structure of the config file with every value replaced by its type.
+    pub config_shape: Option<Value>,
+    /// Error followed by its causes.
+    pub error_chain: Vec<String>,
+}
+
+/// Enables error reports for the command, returning the context of the reports. This
+/// must only be called when the user gave consent with `--report-errors`: nothing is
+/// collected otherwise. Reports are written to a local file and never sent anywhere.
+pub fn enable_error_reports(command: &Commands, global: &GlobalOptions) -> ReportContext {
+    let flags = env::args()
+        .skip(1)
+        .filter(|arg| arg.starts_with('-'))
+        .map(|arg| arg.split('=').next().unwrap_or_default().to_string())
+        .collect();
+
+    let context = ReportContext {
+        command: command_name(command).to_string(),
+        flags,
+        config: config_path(command, global.profile.as_deref()),
+    };
+
+    let panic_context = context.clone();
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+
+        let location = info
+            .location()
+            .map(|location| format!(" at {}:{}", location.file(), location.line()))
+            .unwrap_or_default();
+        let message = match info.payload().downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => info
+                .payload()
+                .downcast_ref::<String>()
+                .cloned()
+                .unwrap_or_else(|| "unknown panic".to_string()),
+        };
+
+        let error_chain = vec![format!("panic{}: {}", location, message)];
+        if let Some(path) = write_report(&panic_context, error_chain) {
+            eprintln!("Diagnostic report written to {}", path);
+        }
+    }));
+
+    context
+}
+
+/// Writes the report of a fatal error, returning the path of the file.
+pub fn write_error_report(context: &ReportContext, error: &anyhow::Error) -> Option<String> {
+    write_report(
+        context,
+        error.chain().map(|cause| cause.to_string()).collect(),
+    )
+}
+
+fn write_report(context: &ReportContext, error_chain: Vec<String>) -> Option<String> {
+    let report = ErrorReport {
+        created_at: Utc::now().to_rfc3339(),
+        case_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: env::consts::OS.to_string(),
+        arch: env::consts::ARCH.to_string(),
+        command: context.command.clone(),
+        flags: context.flags.clone(),
+        config_shape: config_shape(&context.config),
+        error_chain: error_chain.iter().map(|error| redact(error)).collect(),
+    };
+
+    let path = format!("case-error-{}.json", Utc::now().format("%Y%m%d%H%M%S"));
+
+    match serde_json::to_string_pretty(&report)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| Ok(fs::write(&path, content)?))
+    {
+        Ok(()) => Some(path),
+        Err(err) => {
+            warn!("Failed to write error report: {}", err);
+            None
+        }
+    }
+}
+
+/// Removes values that could identify the user from a message.
+pub fn redact(message: &str) -> String {
+    let message = SENSITIVE.replace_all(message, REDACTED).to_string();
+
+    match dirs::home_dir().and_then(|home| home.to_str().map(|home| home.to_string())) {
+        Some(home) if !home.is_empty() => message.replace(&home, "~"),
+        _ => message,
+    }
+}
+
+/// Returns the structure of the config file, with every value replaced by its type.
+fn config_shape(path: &str) -> Option<Value> {
+    let config = fs::read_to_string(path).ok()?;
+    let value: Value = match ConfigFormat::from_path(path) {
+        ConfigFormat::Json => serde_json::from_str(&config).ok()?,
+        ConfigFormat::Yaml => serde_yaml::from_str(&config).ok()?,
+        ConfigFormat::Toml => toml::from_str(&config).ok()?,
+    };
+
+    Some(shape(&value))
+}
+
+fn shape(value: &Value) -> Value {
+    match value {
+        Value::Null => json!("null"),
+        Value::Bool(_) => json!("bool"),
+        Value::Number(_) => json!("number"),
+        Value::String(_) => json!("string"),
+        Value::Array(values) => json!({
+            "length": values.len(),
+            "items": values.first().map(shape),
+        }),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), shape(value)))
+                .collect(),
+        ),
+    }
+}
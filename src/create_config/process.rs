@@ -1,6 +1,7 @@
 use std::{
     default::Default,
     fs::{File, OpenOptions},
+    io::Write,
     path::{Path, PathBuf},
     str::FromStr,
     sync::Arc,
@@ -14,16 +15,20 @@ use dialoguer::{Confirm, Input, MultiSelect, Select};
 use url::Url;
 
 use crate::{
+    address_book::resolve_pubkey,
+    case_println,
     tars::TARS_ID,
     config::{
-        parse_string_as_date, ConfigData, Creator, EndSettingType, EndSettings, GatekeeperConfig,
-        HiddenSettings, UploadMethod, WhitelistMintMode, WhitelistMintSettings,
+        config_to_string, parse_string_as_date, CaseConfig, ConfigData, ConfigFormat, Creator,
+        EndSettingType, EndSettings, GatekeeperConfig, HiddenSettings, UploadMethod,
+        WhitelistMintMode, WhitelistMintSettings,
     },
     constants::*,
+    profile::default_config,
     setup::{setup_client, case_setup},
-    upload::list_files,
-    utils::{check_spl_token, check_spl_token_account, get_dialoguer_theme},
-    validate::Metadata,
+    upload::{initialize, list_files, AWSMethod, ResourceLimits},
+    utils::{check_spl_token, check_spl_token_account, get_dialoguer_theme, spinner_with_style},
+    validate::{check_hidden_settings_name, Metadata},
 };
 
 /// Default name of the first metadata file.
@@ -38,18 +43,51 @@ const DATE_MASK: &str = "%Y-%m-%d %H:%M:%S %z";
 pub struct CreateConfigArgs {
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
     pub config: Option<String>,
     pub assets_dir: String,
+    pub format: Option<ConfigFormat>,
 }
 
-pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
+pub async fn process_create_config(args: CreateConfigArgs) -> Result<()> {
     let mut config_data: ConfigData = ConfigData::default();
     let theme = get_dialoguer_theme();
 
+    // the format of a config file is detected from its extension when it is read, so a
+    // format that does not match the extension is rejected before asking any question
+    let (file_path, format) = match (args.config, args.format) {
+        (Some(config), Some(format)) => {
+            if ConfigFormat::from_path(&config) != format {
+                return Err(anyhow!(
+                    "The config file \"{}\" does not have the extension of the {:?} format (.{})",
+                    config,
+                    format,
+                    format.extension()
+                ));
+            }
+            (config, format)
+        }
+        (Some(config), None) => {
+            let format = ConfigFormat::from_path(&config);
+            (config, format)
+        }
+        (None, Some(format)) => (
+            Path::new(&default_config(args.global.profile.as_deref()))
+                .with_extension(format.extension())
+                .to_string_lossy()
+                .to_string(),
+            format,
+        ),
+        (None, None) => (
+            default_config(args.global.profile.as_deref()),
+            ConfigFormat::Json,
+        ),
+    };
+
     // validators
 
     let pubkey_validator = |input: &String| -> Result<(), String> {
-        if Pubkey::from_str(input).is_err() {
+        if resolve_pubkey(input, &args.global.address_book).is_err() {
             Err(format!("Couldn't parse input of '{}' to a pubkey.", input))
         } else {
             Ok(())
@@ -119,7 +157,7 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
         }
     };
 
-    println!(
+    case_println!(
         "{} {}Case interactive config maker",
         style("[1/2]").bold().dim(),
         TARS_EMOJI
@@ -136,8 +174,8 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
     let mut seller_fee = INVALID_SELLER_FEE;
 
     if num_files > 0 {
-        println!("\nFound metadata file(s) in folder '{}':", args.assets_dir);
-        println!("  -> Loading values from file '{}'", DEFAULT_METADATA);
+        case_println!("\nFound metadata file(s) in folder '{}':", args.assets_dir);
+        case_println!("  -> Loading values from file '{}'", DEFAULT_METADATA);
 
         // loads the default values from the first metadata file
         let metadata_file = PathBuf::from(&args.assets_dir)
@@ -159,8 +197,8 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
         }
     }
 
-    println!("\nCheck out our Tars config docs to learn about the options:");
-    println!(
+    case_println!("\nCheck out our Tars config docs to learn about the options:");
+    case_println!(
         "  -> {}\n",
         style("https://docs.metaplex.com/tools/case/configuration")
             .bold()
@@ -292,12 +330,13 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
     let mut total_share = 0;
 
     (0..num_creators).into_iter().for_each(|i| {
-        let address = Pubkey::from_str(
+        let address = resolve_pubkey(
             &Input::with_theme(&theme)
                 .with_prompt(format!("Enter creator wallet address #{}", i + 1))
                 .validate_with(pubkey_validator)
                 .interact()
                 .unwrap(),
+            &args.global.address_book,
         )
             .expect("Failed to parse string into pubkey that should have already been validated.");
 
@@ -349,36 +388,40 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
 
     // SPL token mint
 
-    let case_config = case_setup(args.keypair, args.rpc_url)?;
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
     let client = Arc::new(setup_client(&case_config)?);
     let program = client.program(TARS_ID);
 
     if choices.contains(&SPL_INDEX) {
         config_data.sol_treasury_account = None;
         config_data.spl_token = Some(
-            Pubkey::from_str(
+            resolve_pubkey(
                 &Input::with_theme(&theme)
                     .with_prompt("What is your SPL token mint address?")
                     .validate_with(pubkey_validator)
                     .validate_with(|input: &String| -> Result<()> {
-                        check_spl_token(&program, input)?;
+                        let mint = resolve_pubkey(input, &args.global.address_book)?;
+                        check_spl_token(&program, &mint.to_string())?;
                         Ok(())
                     })
                     .interact()
                     .unwrap(),
+                &args.global.address_book,
             )
             .expect("Failed to parse string into pubkey that should have already been validated."),
         );
         config_data.spl_token_account = Some(
-                Pubkey::from_str(
+                resolve_pubkey(
                     &Input::with_theme(&theme)
                         .with_prompt("What is your SPL token account address (the account that will hold the SPL token mints)?")
                         .validate_with(pubkey_validator)
                         .validate_with(|input: &String| -> Result<()> {
-                            check_spl_token_account(&program, input)
+                            let token_account = resolve_pubkey(input, &args.global.address_book)?;
+                            check_spl_token_account(&program, &token_account.to_string())
                         })
                         .interact()
                         .unwrap(),
+                    &args.global.address_book,
                 )
                     .expect("Failed to parse string into pubkey that should have already been validated."),
             )
@@ -386,12 +429,13 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
         config_data.spl_token = None;
         config_data.spl_token_account = None;
         config_data.sol_treasury_account = Some(
-            Pubkey::from_str(
+            resolve_pubkey(
                 &Input::with_theme(&theme)
                     .with_prompt("What is your SOL treasury address?")
                     .validate_with(pubkey_validator)
                     .interact()
                     .unwrap(),
+                &args.global.address_book,
             )
             .expect("Failed to parse string into pubkey that should have already been validated."),
         );
@@ -424,12 +468,13 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
     // whitelist mint settings
 
     config_data.whitelist_mint_settings = if choices.contains(&WL_INDEX) {
-        let mint = Pubkey::from_str(
+        let mint = resolve_pubkey(
             &Input::with_theme(&theme)
                 .with_prompt("What is your WL token mint address?")
                 .validate_with(pubkey_validator)
                 .interact()
                 .unwrap(),
+            &args.global.address_book,
         )
         .expect("Failed to parse string into pubkey that should have already been validated.");
 
@@ -529,11 +574,7 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
         let name = Input::with_theme(&theme)
             .with_prompt("What is the prefix name for your hidden settings mints? The mint index will be appended at the end of the name.")
             .validate_with(|name: &String| {
-                if name.len() > (MAX_NAME_LENGTH - 7) {
-                    Err("Your hidden settings name probably cannot be longer than 25 characters.")
-                } else {
-                    Ok(())
-                }
+                check_hidden_settings_name(name, config_data.number).map_err(|e| e.to_string())
             })
             .interact()
             .unwrap();
@@ -549,10 +590,15 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
             .validate_with(url_validator)
             .interact()
             .unwrap();
+        // an empty hash stands for the hash of the cache (see verify-config-hash)
         let hash = Input::with_theme(&theme)
-            .with_prompt("What is the hash value for your hidden settings?")
+            .with_prompt(
+                "What is the hash value for your hidden settings? Leave it empty to use the hash \
+                 of the cache.",
+            )
+            .allow_empty(true)
             .validate_with(|hash: &String| {
-                if hash.len() != 32 {
+                if !hash.is_empty() && hash.len() != 32 {
                     Err("Your hidden settings hash has to be 32 characters long.")
                 } else {
                     Ok(())
@@ -568,46 +614,116 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
     // upload method
 
     let upload_options = vec!["Bundlr", "AWS", "NFT Storage", "SHDW"];
-    config_data.upload_method = match Select::with_theme(&theme)
-        .with_prompt("What upload method do you want to use?")
-        .items(&upload_options)
-        .default(0)
-        .interact()
-        .unwrap()
-    {
-        0 => UploadMethod::Bundlr,
-        1 => UploadMethod::AWS,
-        2 => UploadMethod::NftStorage,
-        3 => UploadMethod::SHDW,
-        _ => UploadMethod::Bundlr,
-    };
 
-    if config_data.upload_method == UploadMethod::AWS {
-        config_data.aws_s3_bucket = Some(
-            Input::with_theme(&theme)
-                .with_prompt("What is the AWS S3 bucket name?")
-                .interact()
-                .unwrap(),
-        );
-    }
+    loop {
+        config_data.upload_method = match Select::with_theme(&theme)
+            .with_prompt("What upload method do you want to use?")
+            .items(&upload_options)
+            .default(0)
+            .interact()
+            .unwrap()
+        {
+            0 => UploadMethod::Bundlr,
+            1 => UploadMethod::AWS,
+            2 => UploadMethod::NftStorage,
+            3 => UploadMethod::SHDW,
+            _ => UploadMethod::Bundlr,
+        };
 
-    if config_data.upload_method == UploadMethod::NftStorage {
-        config_data.nft_storage_auth_token = Some(
-            Input::with_theme(&theme)
-                .with_prompt("What is the NFT Storage authentication token?")
-                .interact()
-                .unwrap(),
-        );
-    }
+        // clears the settings of a previously selected method
+        config_data.bundlr_node = None;
+        config_data.aws_s3_bucket = None;
+        config_data.aws_profile = None;
+        config_data.nft_storage_auth_token = None;
+        config_data.shdw_storage_account = None;
+
+        match config_data.upload_method {
+            UploadMethod::Bundlr => {
+                let node: String = Input::with_theme(&theme)
+                    .with_prompt("What Bundlr node do you want to use? (leave blank to use the default node for the cluster)")
+                    .allow_empty(true)
+                    .validate_with(|input: &String| -> Result<(), String> {
+                        if input.is_empty() {
+                            Ok(())
+                        } else {
+                            url_validator(input)
+                        }
+                    })
+                    .interact()
+                    .unwrap();
 
-    if config_data.upload_method == UploadMethod::SHDW {
-        config_data.shdw_storage_account = Some(
-            Input::with_theme(&theme)
-                .with_prompt("What is the SHDW storage address?")
-                .validate_with(pubkey_validator)
-                .interact()
-                .unwrap(),
-        );
+                if !node.is_empty() {
+                    config_data.bundlr_node = Some(node.trim_end_matches('/').to_string());
+                }
+            }
+            UploadMethod::AWS => {
+                config_data.aws_s3_bucket = Some(
+                    Input::with_theme(&theme)
+                        .with_prompt("What is the AWS S3 bucket name?")
+                        .interact()
+                        .unwrap(),
+                );
+
+                let profile: String = Input::with_theme(&theme)
+                    .with_prompt("Which AWS credentials profile do you want to use?")
+                    .default("default".to_string())
+                    .interact()
+                    .unwrap();
+
+                if profile != "default" {
+                    config_data.aws_profile = Some(profile);
+                }
+            }
+            UploadMethod::NftStorage => {
+                config_data.nft_storage_auth_token = Some(
+                    Input::with_theme(&theme)
+                        .with_prompt("What is the NFT Storage authentication token?")
+                        .interact()
+                        .unwrap(),
+                );
+            }
+            UploadMethod::SHDW => {
+                config_data.shdw_storage_account = Some(
+                    Input::with_theme(&theme)
+                        .with_prompt("What is the SHDW storage address?")
+                        .validate_with(pubkey_validator)
+                        .interact()
+                        .unwrap(),
+                );
+            }
+        }
+
+        let pb = spinner_with_style();
+        pb.enable_steady_tick(120);
+        pb.set_message("Validating upload settings...");
+        let validation = validate_upload_settings(&case_config, &config_data).await;
+        pb.finish_and_clear();
+
+        match validation {
+            Ok(()) => {
+                case_println!(
+                    "{} {}",
+                    style("Upload settings validated:").green(),
+                    config_data.upload_method
+                );
+                break;
+            }
+            Err(err) => {
+                case_println!(
+                    "{} {}",
+                    style("Could not validate the upload settings:").red(),
+                    err
+                );
+
+                if !Confirm::with_theme(&theme)
+                    .with_prompt("Do you want to re-enter the upload settings?")
+                    .default(true)
+                    .interact()?
+                {
+                    break;
+                }
+            }
+        }
     }
 
     // retain authority
@@ -624,17 +740,14 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
 
     // saving configuration file
 
-    println!(
+    case_println!(
         "\n{} {}Saving config file\n",
         style("[2/2]").bold().dim(),
         PAPER_EMOJI
     );
 
     let mut save_file = true;
-    let file_path = match args.config {
-        Some(config) => config,
-        None => DEFAULT_CONFIG.to_string(),
-    };
+    let content = config_to_string(&config_data, format)?;
 
     if Path::new(&file_path).is_file() {
         save_file = Select::with_theme(&theme)
@@ -643,7 +756,7 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
             .default(0)
             .interact()
             .unwrap() == 0;
-        println!();
+        case_println!();
     }
 
     if save_file {
@@ -654,15 +767,14 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
             .open(Path::new(&file_path));
 
         match file {
-            Ok(f) => {
-                println!(
+            Ok(mut f) => {
+                case_println!(
                     "{}",
                     style(format!("Saving config to file: \"{}\"\n", file_path))
                 );
-                serde_json::to_writer_pretty(f, &config_data)
-                    .expect("Unable to convert config to JSON!");
+                f.write_all(content.as_bytes())?;
 
-                println!(
+                case_println!(
                     "{} {}",
                     style("Successfully generated the config file.")
                         .magenta()
@@ -672,29 +784,36 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
             }
 
             Err(_) => {
-                println!(
+                case_println!(
                     "{}\n",
                     style("Error creating config file - logging config to console.")
                         .bold()
                         .red()
                 );
-                println!(
-                    "{}",
-                    style(
-                        serde_json::to_string_pretty(&config_data)
-                            .expect("Unable to convert config to JSON.")
-                    )
-                    .red()
-                );
+                case_println!("{}", style(&content).red());
             }
         }
     } else {
-        println!("{}\n", style("Logging config to console:").dim());
-        println!(
-            "{}",
-            serde_json::to_string_pretty(&config_data).expect("Unable to convert config to JSON.")
-        );
+        case_println!("{}\n", style("Logging config to console:").dim());
+        case_println!("{}", content);
     }
 
     Ok(())
 }
+
+/// Checks that the upload method can be initialized with the settings provided,
+/// authenticating with the storage backend.
+async fn validate_upload_settings(case_config: &CaseConfig, config_data: &ConfigData) -> Result<()> {
+    match config_data.upload_method {
+        // creating the bucket handle does not require a request
+        UploadMethod::AWS => {
+            AWSMethod::new(config_data, &ResourceLimits::default())
+                .await?
+                .check_access()
+                .await
+        }
+        _ => initialize(case_config, config_data, false, &ResourceLimits::default())
+            .await
+            .map(|_| ()),
+    }
+}
@@ -0,0 +1,30 @@
+use std::{collections::HashSet, fs};
+
+use crate::common::*;
+
+/// Loads the addresses of the deny list file of sanctioned or flagged addresses (a JSON
+/// array of addresses). Returns an empty deny list when the file does not exist.
+pub fn load_denylist(path: &str) -> Result<HashSet<Pubkey>> {
+    if !Path::new(path).exists() {
+        return Ok(HashSet::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|err| DenylistError::FailedToOpen(path.to_string(), err.to_string()))?;
+    let addresses: Vec<String> = serde_json::from_str(&content)
+        .map_err(|err| DenylistError::WrongFormat(path.to_string(), err.to_string()))?;
+
+    let mut denylist = HashSet::with_capacity(addresses.len());
+
+    for address in addresses {
+        let pubkey = Pubkey::from_str(&address)
+            .map_err(|_| DenylistError::InvalidAddress(path.to_string(), address.clone()))?;
+        denylist.insert(pubkey);
+    }
+
+    if !denylist.is_empty() {
+        info!("Loaded {} denied address(es) from {}", denylist.len(), path);
+    }
+
+    Ok(denylist)
+}
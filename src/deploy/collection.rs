@@ -1,4 +1,7 @@
-use anchor_client::{solana_sdk::pubkey::Pubkey, Client};
+use anchor_client::{
+    solana_sdk::{instruction::Instruction, pubkey::Pubkey},
+    Client,
+};
 use anyhow::Result;
 use mpl_token_metadata::{
     instruction::{create_master_edition_v3, create_metadata_accounts_v2},
@@ -14,20 +17,24 @@ use spl_token::{
 use crate::{
     tars::TARS_ID,
     common::*,
-    config::ConfigData,
+    config::{CaseConfig, ConfigData},
     pdas::{find_collection_pda, find_master_edition_pda, find_metadata_pda},
+    rng::Rng,
+    tx::send_and_confirm,
 };
 
 pub fn create_and_set_collection(
+    case_config: &CaseConfig,
     client: Client,
     tars_pubkey: Pubkey,
     cache: &mut Cache,
     config_data: ConfigData,
+    rng: &mut dyn Rng,
 ) -> Result<(Signature, Pubkey)> {
     let program = client.program(TARS_ID);
     let payer = program.payer();
 
-    let collection_mint = Keypair::new();
+    let collection_mint = rng.new_keypair();
     let collection_item: &mut CacheItem = match cache.items.get_mut("-1") {
         Some(item) => item,
         None => {
@@ -35,6 +42,62 @@ pub fn create_and_set_collection(
         }
     };
 
+    let collection_metadata_pubkey = find_metadata_pda(&collection_mint.pubkey());
+    let collection_edition_pubkey = find_master_edition_pda(&collection_mint.pubkey());
+    let collection_instructions = create_collection_instructions(
+        &program,
+        &collection_mint.pubkey(),
+        collection_item.name.clone(),
+        config_data.symbol,
+        collection_item.metadata_link.clone(),
+    )?;
+
+    let collection_pda_pubkey = find_collection_pda(&tars_pubkey).0;
+    let collection_authority_record =
+        find_collection_authority_account(&collection_mint.pubkey(), &collection_pda_pubkey).0;
+
+    let mut builder = program.request().signer(&collection_mint);
+
+    for instruction in collection_instructions {
+        builder = builder.instruction(instruction);
+    }
+
+    let builder = builder
+        .accounts(nft_accounts::SetCollection {
+            tars: tars_pubkey,
+            authority: payer,
+            collection_pda: collection_pda_pubkey,
+            payer,
+            system_program: system_program::id(),
+            rent: sysvar::rent::ID,
+            metadata: collection_metadata_pubkey,
+            mint: collection_mint.pubkey(),
+            edition: collection_edition_pubkey,
+            collection_authority_record,
+            token_metadata_program: mpl_token_metadata::ID,
+        })
+        .args(nft_instruction::SetCollection);
+
+    let sig = send_and_confirm(case_config, &builder.instructions()?, &[&collection_mint])?;
+    collection_item.on_chain = true;
+    cache.program.collection_mint = collection_mint.pubkey().to_string();
+    cache.sync_file()?;
+
+    Ok((sig, collection_mint.pubkey()))
+}
+
+/// Returns the instructions that mint a collection NFT (a master edition with a max supply
+/// of 0) to the payer, which is its update authority and only creator. The collection mint
+/// keypair also signs the transaction.
+pub fn create_collection_instructions(
+    program: &Program,
+    collection_mint: &Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Result<Vec<Instruction>> {
+    let payer = program.payer();
+
     // Allocate memory for the account
     let min_rent = program
         .rpc()
@@ -43,31 +106,25 @@ pub fn create_and_set_collection(
     // Create mint account
     let create_mint_account_ix = system_instruction::create_account(
         &payer,
-        &collection_mint.pubkey(),
+        collection_mint,
         min_rent,
         MINT_LAYOUT,
         &TOKEN_PROGRAM_ID,
     );
 
     // Initialize mint ix
-    let init_mint_ix = initialize_mint(
-        &TOKEN_PROGRAM_ID,
-        &collection_mint.pubkey(),
-        &payer,
-        Some(&payer),
-        0,
-    )?;
+    let init_mint_ix =
+        initialize_mint(&TOKEN_PROGRAM_ID, collection_mint, &payer, Some(&payer), 0)?;
 
-    let ata_pubkey = get_associated_token_address(&payer, &collection_mint.pubkey());
+    let ata_pubkey = get_associated_token_address(&payer, collection_mint);
 
     // Create associated account instruction
-    let create_assoc_account_ix =
-        create_associated_token_account(&payer, &payer, &collection_mint.pubkey());
+    let create_assoc_account_ix = create_associated_token_account(&payer, &payer, collection_mint);
 
     // Mint to instruction
     let mint_to_ix = mint_to(
         &TOKEN_PROGRAM_ID,
-        &collection_mint.pubkey(),
+        collection_mint,
         &ata_pubkey,
         &payer,
         &[],
@@ -79,18 +136,18 @@ pub fn create_and_set_collection(
         verified: true,
         share: 100,
     };
-    let collection_metadata_pubkey = find_metadata_pda(&collection_mint.pubkey());
+    let collection_metadata_pubkey = find_metadata_pda(collection_mint);
 
     let create_metadata_account_ix = create_metadata_accounts_v2(
         mpl_token_metadata::ID,
         collection_metadata_pubkey,
-        collection_mint.pubkey(),
+        *collection_mint,
         payer,
         payer,
         payer,
-        collection_item.name.clone(),
-        config_data.symbol,
-        collection_item.metadata_link.clone(),
+        name,
+        symbol,
+        uri,
         Some(vec![creator]),
         0,
         true,
@@ -99,12 +156,12 @@ pub fn create_and_set_collection(
         None,
     );
 
-    let collection_edition_pubkey = find_master_edition_pda(&collection_mint.pubkey());
+    let collection_edition_pubkey = find_master_edition_pda(collection_mint);
 
     let create_master_edition_ix = create_master_edition_v3(
         mpl_token_metadata::ID,
         collection_edition_pubkey,
-        collection_mint.pubkey(),
+        *collection_mint,
         payer,
         payer,
         collection_metadata_pubkey,
@@ -112,38 +169,12 @@ pub fn create_and_set_collection(
         Some(0),
     );
 
-    let collection_pda_pubkey = find_collection_pda(&tars_pubkey).0;
-    let collection_authority_record =
-        find_collection_authority_account(&collection_mint.pubkey(), &collection_pda_pubkey).0;
-
-    let builder = program
-        .request()
-        .instruction(create_mint_account_ix)
-        .instruction(init_mint_ix)
-        .instruction(create_assoc_account_ix)
-        .instruction(mint_to_ix)
-        .signer(&collection_mint)
-        .instruction(create_metadata_account_ix)
-        .instruction(create_master_edition_ix)
-        .accounts(nft_accounts::SetCollection {
-            tars: tars_pubkey,
-            authority: payer,
-            collection_pda: collection_pda_pubkey,
-            payer,
-            system_program: system_program::id(),
-            rent: sysvar::rent::ID,
-            metadata: collection_metadata_pubkey,
-            mint: collection_mint.pubkey(),
-            edition: collection_edition_pubkey,
-            collection_authority_record,
-            token_metadata_program: mpl_token_metadata::ID,
-        })
-        .args(nft_instruction::SetCollection);
-
-    let sig = builder.send()?;
-    collection_item.on_chain = true;
-    cache.program.collection_mint = collection_mint.pubkey().to_string();
-    cache.sync_file()?;
-
-    Ok((sig, collection_mint.pubkey()))
+    Ok(vec![
+        create_mint_account_ix,
+        init_mint_ix,
+        create_assoc_account_ix,
+        mint_to_ix,
+        create_metadata_account_ix,
+        create_master_edition_ix,
+    ])
 }
@@ -6,7 +6,10 @@ use std::{
     },
 };
 
-use anchor_client::solana_sdk::{pubkey::Pubkey, signature::Keypair};
+use anchor_client::solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, instruction::Instruction, pubkey::Pubkey,
+    signature::Keypair,
+};
 use anyhow::Result;
 use console::style;
 use futures::future::select_all;
@@ -15,27 +18,63 @@ pub use mpl_token_metadata::state::{
     MAX_CREATOR_LIMIT, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH,
 };
 
+use tokio::task::JoinHandle;
+
 use crate::{
-    cache::*, tars::TARS_ID, common::*, config::data::*, deploy::errors::*,
-    setup::setup_client, utils::*,
+    budget::FeeBudget,
+    cache::*,
+    common::*,
+    config::data::*,
+    deploy::errors::*,
+    setup::{is_transient_error, rpc_endpoint, setup_client},
+    tars::TARS_ID,
+    tx::send_and_confirm,
+    utils::*,
 };
 
 /// The maximum config line bytes per transaction.
 const MAX_TRANSACTION_BYTES: usize = 1000;
 
-/// The maximum number of config lines per transaction.
-const MAX_TRANSACTION_LINES: usize = 17;
-
 pub struct TxInfo {
     tars_pubkey: Pubkey,
     payer: Keypair,
     chunk: Vec<(u32, ConfigLine)>,
+    /// Number of times the config lines were retried after a transient error.
+    attempt: u32,
+    /// Compute unit limit set for the deploy.
+    compute_units: Option<u32>,
 }
 
-/// Determine the config lines that need to be uploaded.
+impl TxInfo {
+    /// Priority fee (in lamports) of the transaction, which is increased on every retry.
+    fn priority_fee(&self) -> u32 {
+        self.attempt * CONFIG_LINES_PRIORITY_FEE_INCREMENT
+    }
+
+    /// Compute units requested by the transaction: the limit set for the deploy or, when
+    /// sent with a priority fee, `CONFIG_LINES_COMPUTE_UNITS`.
+    fn compute_units(&self) -> Option<u32> {
+        match self.compute_units {
+            Some(compute_units) => Some(compute_units),
+            None if self.priority_fee() > 0 => Some(CONFIG_LINES_COMPUTE_UNITS),
+            None => None,
+        }
+    }
+}
+
+/// Result of an `add_config_lines` transaction.
+pub struct TxResult {
+    indices: Vec<u32>,
+    attempt: u32,
+    result: Result<()>,
+}
+
+/// Determine the config lines that need to be uploaded, with at most `batch_size`
+/// config lines per transaction.
 pub fn generate_config_lines(
     num_items: u64,
     cache_items: &CacheItems,
+    batch_size: usize,
 ) -> Result<Vec<Vec<(u32, ConfigLine)>>> {
     let mut config_lines: Vec<Vec<(u32, ConfigLine)>> = Vec::new();
     let mut current: Vec<(u32, ConfigLine)> = Vec::new();
@@ -67,7 +106,7 @@ pub fn generate_config_lines(
 
             let size = (2 * STRING_LEN_SIZE) + config_line.name.len() + config_line.uri.len();
 
-            if (tx_size + size) > MAX_TRANSACTION_BYTES || current.len() == MAX_TRANSACTION_LINES {
+            if (tx_size + size) > MAX_TRANSACTION_BYTES || current.len() == batch_size {
                 // we need a separate tx to not break the size limit
                 config_lines.push(current);
                 current = Vec::new();
@@ -87,19 +126,29 @@ pub fn generate_config_lines(
 }
 
 /// Send the config lines to the tars program.
+///
+/// The cache is saved after every confirmed transaction, so an interrupted deploy
+/// resumes from the first config line that was not written. When a transaction
+/// exceeds the size limit, the batch size is halved for the remaining transactions.
+/// Transactions that fail with a transient error (e.g. an expired blockhash) are
+/// retried with their config lines split in smaller batches and a higher priority fee.
 pub async fn upload_config_lines(
     case_config: Arc<CaseConfig>,
     tars_pubkey: Pubkey,
     cache: &mut Cache,
     config_lines: Vec<Vec<(u32, ConfigLine)>>,
+    batch_size: usize,
+    compute_units: Option<u32>,
     interrupted: Arc<AtomicBool>,
+    budget: Arc<FeeBudget>,
 ) -> Result<Vec<DeployError>> {
-    println!(
+    case_println!(
         "Sending config line(s) in {} transaction(s): (Ctrl+C to abort)",
         config_lines.len()
     );
 
-    let pb = progress_bar_with_style(config_lines.len() as u64);
+    let total = config_lines.iter().map(|chunk| chunk.len()).sum::<usize>();
+    let pb = progress_bar_with_style(total as u64);
 
     debug!("Num of config line chunks: {:?}", config_lines.len());
     info!("Uploading config lines in chunks...");
@@ -107,49 +156,106 @@ pub async fn upload_config_lines(
     let mut transactions = Vec::new();
 
     for chunk in config_lines {
-        let keypair = bs58::encode(case_config.keypair.to_bytes()).into_string();
-        let payer = Keypair::from_base58_string(&keypair);
-
         transactions.push(TxInfo {
             tars_pubkey,
-            payer,
+            payer: payer_keypair(&case_config),
             chunk,
+            attempt: 0,
+            compute_units,
         });
     }
 
-    let mut handles = Vec::new();
+    // reversed so that transactions are sent in order when popped
+    transactions.reverse();
 
-    for tx in transactions.drain(0..cmp::min(transactions.len(), PARALLEL_LIMIT)) {
-        let config = case_config.clone();
-        handles.push(tokio::spawn(
-            async move { add_config_lines(config, tx).await },
-        ));
-    }
+    let mut batch_size = batch_size;
+    let mut handles = Vec::new();
+    let mut budget_error = spawn_transactions(
+        &case_config,
+        &mut transactions,
+        &mut handles,
+        PARALLEL_LIMIT,
+        &budget,
+    )
+    .err();
 
     let mut errors = Vec::new();
 
-    while !interrupted.load(Ordering::SeqCst) && !handles.is_empty() {
+    // once interrupted, no new transactions are sent but the ones in flight are
+    // awaited, so the cache reflects every config line written
+    while !handles.is_empty() {
         match select_all(handles).await {
-            (Ok(res), _index, remaining) => {
+            (Ok(tx_result), _index, remaining) => {
                 // independently if the upload was successful or not
                 // we continue to try the remaining ones
                 handles = remaining;
 
-                if res.is_ok() {
-                    let indices = res?;
-
-                    for index in indices {
-                        let item = cache.items.get_mut(&index.to_string()).unwrap();
-                        item.on_chain = true;
+                let TxResult {
+                    indices,
+                    attempt,
+                    result,
+                } = tx_result;
+
+                match result {
+                    Ok(()) => {
+                        for index in &indices {
+                            let item = cache.items.get_mut(&index.to_string()).unwrap();
+                            item.on_chain = true;
+                        }
+                        // saves the progress to the cache file
+                        cache.sync_file()?;
+                        // updates the progress bar
+                        pb.inc(indices.len() as u64);
+                    }
+                    Err(err) if indices.len() > 1 && is_transaction_too_large(&err) => {
+                        batch_size = cmp::min(batch_size, indices.len() / 2);
+                        warn!(
+                            "Transaction with {} config lines is too large, reducing the batch size to {}",
+                            indices.len(),
+                            batch_size
+                        );
+
+                        // the failed transaction is retried first, followed by the
+                        // remaining ones split to the new batch size
+                        transactions.push(TxInfo {
+                            tars_pubkey,
+                            payer: payer_keypair(&case_config),
+                            chunk: rebuild_chunk(cache, &indices),
+                            attempt,
+                            compute_units,
+                        });
+                        transactions = split_transactions(&case_config, transactions, batch_size);
+                    }
+                    Err(err) if attempt < CONFIG_LINES_RETRIES && is_transient_error(&err) => {
+                        let attempt = attempt + 1;
+                        warn!(
+                            "Transient error writing config lines {:?}, retrying (attempt {}): {:?}",
+                            indices, attempt, err
+                        );
+
+                        // the config lines are retried first, in smaller batches so that
+                        // a single failure does not hold back every line of the batch
+                        let retry = TxInfo {
+                            tars_pubkey,
+                            payer: payer_keypair(&case_config),
+                            chunk: rebuild_chunk(cache, &indices),
+                            attempt,
+                            compute_units,
+                        };
+                        let retry_size = cmp::max(1, (indices.len() + 1) / 2);
+                        transactions.extend(split_transactions(
+                            &case_config,
+                            vec![retry],
+                            retry_size,
+                        ));
+                    }
+                    Err(err) => {
+                        // user will need to retry the upload
+                        errors.push(DeployError::AddConfigLineFailed(format!(
+                            "Transaction error: {:?}",
+                            err
+                        )));
                     }
-                    // updates the progress bar
-                    pb.inc(1);
-                } else {
-                    // user will need to retry the upload
-                    errors.push(DeployError::AddConfigLineFailed(format!(
-                        "Transaction error: {:?}",
-                        res.err().unwrap()
-                    )));
                 }
             }
             (Err(err), _index, remaining) => {
@@ -163,26 +269,34 @@ pub async fn upload_config_lines(
             }
         }
 
-        if !transactions.is_empty() {
+        if !interrupted.load(Ordering::SeqCst) && !transactions.is_empty() && budget_error.is_none()
+        {
             // if we are half way through, let spawn more transactions
             if (PARALLEL_LIMIT - handles.len()) > (PARALLEL_LIMIT / 2) {
-                // saves the progress to the cache file
-                cache.sync_file()?;
-
-                for tx in transactions.drain(0..cmp::min(transactions.len(), PARALLEL_LIMIT / 2)) {
-                    let config = case_config.clone();
-                    handles.push(tokio::spawn(
-                        async move { add_config_lines(config, tx).await },
-                    ));
-                }
+                budget_error = spawn_transactions(
+                    &case_config,
+                    &mut transactions,
+                    &mut handles,
+                    PARALLEL_LIMIT / 2,
+                    &budget,
+                )
+                .err();
             }
         }
     }
 
+    if let Some(err) = budget_error {
+        // in-flight transactions have completed, so the cache reflects what was written
+        pb.abandon_with_message(format!("{}", style("Upload stopped ").yellow().bold()));
+        cache.sync_file()?;
+        return Err(err);
+    }
+
     if !errors.is_empty() {
         pb.abandon_with_message(format!("{}", style("Deploy failed ").red().bold()));
     } else if !transactions.is_empty() {
         pb.abandon_with_message(format!("{}", style("Upload aborted ").red().bold()));
+        cache.sync_file()?;
         return Err(DeployError::AddConfigLineFailed(
             "Not all config lines were deployed.".to_string(),
         )
@@ -200,35 +314,153 @@ pub async fn upload_config_lines(
     Ok(errors)
 }
 
-/// Send the `add_config_lines` instruction to the tars program.
-pub async fn add_config_lines(config: Arc<CaseConfig>, tx_info: TxInfo) -> Result<Vec<u32>> {
+/// Spawns up to `count` transactions, stopping early when the fee budget is reached.
+fn spawn_transactions(
+    case_config: &Arc<CaseConfig>,
+    transactions: &mut Vec<TxInfo>,
+    handles: &mut Vec<JoinHandle<TxResult>>,
+    count: usize,
+    budget: &FeeBudget,
+) -> Result<()> {
+    for _ in 0..cmp::min(transactions.len(), count) {
+        let priority_fee = transactions.last().map_or(0, TxInfo::priority_fee);
+        budget.reserve(SIGNATURE_FEE + priority_fee as u64)?;
+
+        if let Some(tx) = transactions.pop() {
+            let config = case_config.clone();
+            handles.push(tokio::spawn(
+                async move { add_config_lines(config, tx).await },
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Splits the pending transactions so that none has more than `batch_size` config
+/// lines. Transactions are stored in reverse order, and so are their parts.
+fn split_transactions(
+    case_config: &CaseConfig,
+    transactions: Vec<TxInfo>,
+    batch_size: usize,
+) -> Vec<TxInfo> {
+    let mut split = Vec::new();
+
+    for tx in transactions {
+        let mut parts = Vec::new();
+        let mut chunk = tx.chunk.into_iter().peekable();
+
+        while chunk.peek().is_some() {
+            parts.push(TxInfo {
+                tars_pubkey: tx.tars_pubkey,
+                payer: payer_keypair(case_config),
+                chunk: chunk.by_ref().take(batch_size).collect(),
+                attempt: tx.attempt,
+                compute_units: tx.compute_units,
+            });
+        }
+
+        parts.reverse();
+        split.extend(parts);
+    }
+
+    split
+}
+
+/// Returns the config lines of the cache items at the given indices.
+fn rebuild_chunk(cache: &Cache, indices: &[u32]) -> Vec<(u32, ConfigLine)> {
+    indices
+        .iter()
+        .map(|index| {
+            let item = cache.items.get(&index.to_string()).unwrap();
+            let config_line = item
+                .to_config_line()
+                .expect("Could not convert item to config line");
+            (*index, config_line)
+        })
+        .collect()
+}
+
+fn payer_keypair(case_config: &CaseConfig) -> Keypair {
+    let keypair = bs58::encode(case_config.keypair.to_bytes()).into_string();
+    Keypair::from_base58_string(&keypair)
+}
+
+/// Returns `true` if the error was caused by a transaction exceeding the packet size.
+fn is_transaction_too_large(err: &anyhow::Error) -> bool {
+    format!("{:?}", err).to_lowercase().contains("too large")
+}
+
+/// Send the `add_config_lines` instruction to the tars program, returning the indices
+/// of the config lines together with the result.
+pub async fn add_config_lines(config: Arc<CaseConfig>, tx_info: TxInfo) -> TxResult {
+    // this will be used to update the cache
+    let indices: Vec<u32> = tx_info.chunk.iter().map(|(index, _)| *index).collect();
+    let attempt = tx_info.attempt;
+    let result = send_config_lines(config, tx_info);
+
+    TxResult {
+        indices,
+        attempt,
+        result,
+    }
+}
+
+fn send_config_lines(config: Arc<CaseConfig>, tx_info: TxInfo) -> Result<()> {
+    let indices: Vec<u32> = tx_info.chunk.iter().map(|(index, _)| *index).collect();
     let client = setup_client(&config)?;
     let program = client.program(TARS_ID);
 
-    // this will be used to update the cache
-    let mut indices: Vec<u32> = Vec::new();
-    // configLine does not implement clone, so we have to do this
-    let mut config_lines: Vec<ConfigLine> = Vec::new();
-    // start index
-    let start_index = tx_info.chunk[0].0;
+    let priority_fee = tx_info.priority_fee();
+    let mut instructions = Vec::new();
 
-    for (index, line) in tx_info.chunk {
-        indices.push(index);
-        config_lines.push(line);
+    if let Some(compute_units) = tx_info.compute_units() {
+        instructions.push(ComputeBudgetInstruction::request_units(
+            compute_units,
+            priority_fee,
+        ));
     }
 
-    let _sig = program
+    instructions.extend(add_config_lines_instructions(
+        &program,
+        &tx_info.tars_pubkey,
+        tx_info.chunk,
+    )?);
+
+    let sig = send_and_confirm(&config, &instructions, &[&tx_info.payer])?;
+    info!(
+        "Config lines {:?} written through {}: {}",
+        indices,
+        rpc_endpoint(&config),
+        sig
+    );
+
+    Ok(())
+}
+
+/// Returns the `add_config_lines` instruction that writes a chunk of consecutive config
+/// lines.
+pub fn add_config_lines_instructions(
+    program: &Program,
+    tars_pubkey: &Pubkey,
+    chunk: Vec<(u32, ConfigLine)>,
+) -> Result<Vec<Instruction>> {
+    // start index
+    let start_index = chunk[0].0;
+    // configLine does not implement clone, so we have to do this
+    let config_lines: Vec<ConfigLine> = chunk.into_iter().map(|(_, line)| line).collect();
+
+    let instructions = program
         .request()
         .accounts(nft_accounts::AddConfigLines {
-            tars: tx_info.tars_pubkey,
+            tars: *tars_pubkey,
             authority: program.payer(),
         })
         .args(nft_instruction::AddConfigLines {
             index: start_index,
             config_lines,
         })
-        .signer(&tx_info.payer)
-        .send()?;
+        .instructions()?;
 
-    Ok(indices)
+    Ok(instructions)
 }
@@ -1,4 +1,5 @@
 use anchor_client::solana_sdk::{
+    instruction::Instruction,
     pubkey::Pubkey,
     signature::{Keypair, Signature, Signer},
     system_instruction, system_program, sysvar,
@@ -14,7 +15,10 @@ pub use mpl_token_metadata::state::{
 };
 use solana_program::native_token::LAMPORTS_PER_SOL;
 
-use crate::{tars::parse_config_price, common::*, config::data::*, deploy::errors::*};
+use crate::{
+    tars::parse_config_price, common::*, config::data::*, deploy::errors::*,
+    tx::send_and_confirm,
+};
 
 /// Create the tars data struct.
 pub fn create_tars_data(
@@ -31,7 +35,11 @@ pub fn create_tars_data(
         .as_ref()
         .map(|s| s.to_tars_format());
 
-    let hidden_settings = config.hidden_settings.as_ref().map(|s| s.to_tars_format());
+    let hidden_settings = config
+        .hidden_settings
+        .as_ref()
+        .map(|s| s.to_tars_format())
+        .transpose()?;
 
     let gatekeeper = config
         .gatekeeper
@@ -84,31 +92,59 @@ pub fn create_tars_data(
     Ok(data)
 }
 
+/// Returns the size of the tars account, which stores the config lines unless
+/// hidden settings are used.
+pub fn tars_account_size(items_available: u64, hidden: bool) -> usize {
+    if hidden {
+        CONFIG_ARRAY_START
+    } else {
+        CONFIG_ARRAY_START
+            + 4
+            + items_available as usize * CONFIG_LINE_SIZE
+            + 8
+            + 2 * (items_available as usize / 8 + 1)
+    }
+}
+
 /// Send the `initialize_tars` instruction to the tars program.
 pub fn initialize_tars(
+    case_config: &CaseConfig,
     config_data: &ConfigData,
     tars_account: &Keypair,
     tars_data: TarsData,
     treasury_wallet: Pubkey,
     program: Program,
 ) -> Result<Signature> {
+    let instructions = initialize_tars_instructions(
+        config_data,
+        &tars_account.pubkey(),
+        tars_data,
+        treasury_wallet,
+        &program,
+    )?;
+
+    send_and_confirm(case_config, &instructions, &[tars_account])
+}
+
+/// Returns the instructions that create the tars account and initialize it. The
+/// transaction must be signed by the tars keypair.
+pub fn initialize_tars_instructions(
+    config_data: &ConfigData,
+    tars_pubkey: &Pubkey,
+    tars_data: TarsData,
+    treasury_wallet: Pubkey,
+    program: &Program,
+) -> Result<Vec<Instruction>> {
     let payer = program.payer();
     let items_available = tars_data.items_available;
 
-    let tars_account_size = if tars_data.hidden_settings.is_some() {
-        CONFIG_ARRAY_START
-    } else {
-        CONFIG_ARRAY_START
-            + 4
-            + items_available as usize * CONFIG_LINE_SIZE
-            + 8
-            + 2 * (items_available as usize / 8 + 1)
-    };
+    let tars_account_size =
+        tars_account_size(items_available, tars_data.hidden_settings.is_some());
 
     info!(
         "Initializing tars with account size of: {} and address of: {}",
         tars_account_size,
-        tars_account.pubkey().to_string()
+        tars_pubkey.to_string()
     );
 
     let lamports = program
@@ -129,14 +165,13 @@ pub fn initialize_tars(
         .request()
         .instruction(system_instruction::create_account(
             &payer,
-            &tars_account.pubkey(),
+            tars_pubkey,
             lamports,
             tars_account_size as u64,
             &program.id(),
         ))
-        .signer(tars_account)
         .accounts(nft_accounts::InitializeTars {
-            tars: tars_account.pubkey(),
+            tars: *tars_pubkey,
             wallet: treasury_wallet,
             authority: payer,
             payer,
@@ -155,7 +190,5 @@ pub fn initialize_tars(
         });
     }
 
-    let sig = tx.send()?;
-
-    Ok(sig)
+    Ok(tx.instructions()?)
 }
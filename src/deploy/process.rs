@@ -14,20 +14,33 @@ use anchor_client::solana_sdk::{
 };
 use anyhow::Result;
 use console::style;
+use serde::Serialize;
 use spl_associated_token_account::get_associated_token_address;
 
 use crate::{
+    budget::{nft_rent, FeeBudget},
     cache::*,
     tars::{get_tars_state, TARS_ID},
+    collections::set_collection,
     common::*,
-    config::parser::get_config_data,
+    config::{parser::get_config_data, CaseConfig, ConfigData},
+    deployments::record_deployment,
     deploy::{
-        create_and_set_collection, create_tars_data, errors::*, generate_config_lines,
-        initialize_tars, upload_config_lines,
+        add_config_lines_instructions, create_and_set_collection, create_tars_data, errors::*,
+        generate_config_lines, initialize_tars, initialize_tars_instructions, tars_account_size,
+        upload_config_lines,
     },
-    setup::{setup_client, case_setup},
+    diff::{config_line_changes, diff_tars_data, print_diff, TarsDiff},
+    pdas::*,
+    rng::{Rng, SystemRng},
+    setup::{case_setup, check_treasury_wallet, genesis_hash, setup_client},
+    tx::{resolve_nonces, setup_offline_client, OfflineWriter, TxPlan},
     utils::*,
-    validate::parser::{check_name, check_seller_fee_basis_points, check_symbol, check_url},
+    validate::parser::{
+        check_compute_units, check_hidden_settings_name, check_item_groups, check_name,
+        check_seller_fee_basis_points, check_symbol, check_url,
+    },
+    verify_config_hash::{fill_hidden_settings_hash, hidden_settings_hash, HASH_LENGTH},
 };
 
 pub struct DeployArgs {
@@ -35,16 +48,44 @@ pub struct DeployArgs {
     pub cache: String,
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
     pub interrupted: Arc<AtomicBool>,
+    pub dry_run: bool,
+    pub max_fee: Option<f64>,
+    pub batch_size: usize,
+    /// Authority that signs the transactions offline.
+    pub sign_offline: Option<String>,
+    /// Durable nonce accounts of the transactions signed offline.
+    pub nonces: Vec<String>,
+    /// Leave the collection NFT to a later deploy.
+    pub skip_collection: bool,
+    /// Compute unit limit of config line transactions, overriding the config file.
+    pub compute_units: Option<u32>,
+}
+
+/// Result of the deploy command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployOutcome {
+    pub tars: String,
+    pub signatures: Vec<String>,
+    pub config_lines_written: usize,
+    pub collection_mint: Option<String>,
+    pub cache: CacheStats,
+    /// Transaction files written for offline signing.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub transactions: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<TarsDiff>,
 }
 
-pub async fn process_deploy(args: DeployArgs) -> Result<()> {
+pub async fn process_deploy(args: DeployArgs) -> Result<DeployOutcome> {
     // loads the cache file (this needs to have been created by
     // the upload command)
     let mut cache = load_cache(&args.cache, false)?;
 
     if cache.items.is_empty() {
-        println!(
+        case_println!(
             "{}",
             style("No cache items found - run 'upload' to create the cache file first.")
                 .red()
@@ -56,25 +97,51 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
     }
 
     // checks that all metadata information are present and have the
-    // correct length
+    // correct length (all items exceeding the limits are reported)
+
+    let mut too_long = Vec::new();
 
     for (index, item) in &cache.items.0 {
         if item.name.is_empty() {
             return Err(DeployError::MissingName(index.to_string()).into());
-        } else {
-            check_name(&item.name)?;
+        } else if let Err(err) = check_name(&item.name) {
+            too_long.push(format!("Item {}: {}", index, err));
         }
 
         if item.metadata_link.is_empty() {
             return Err(DeployError::MissingMetadataLink(index.to_string()).into());
-        } else {
-            check_url(&item.metadata_link)?;
+        } else if let Err(err) = check_url(&item.metadata_link) {
+            too_long.push(format!("Item {}: {}", index, err));
         }
     }
 
-    let case_config = Arc::new(case_setup(args.keypair, args.rpc_url)?);
-    let client = setup_client(&case_config)?;
-    let config_data = get_config_data(&args.config)?;
+    if !too_long.is_empty() {
+        let mut message = format!(
+            "{} config line value(s) exceed the on-chain limits:",
+            too_long.len()
+        );
+
+        for error in too_long {
+            message.push_str(&style("\n=> ").dim().to_string());
+            message.push_str(&error);
+        }
+
+        return Err(DeployError::ConfigLineTooLong(message).into());
+    }
+
+    let case_config = Arc::new(case_setup(args.keypair, args.rpc_url, &args.global)?);
+    check_cache_cluster(&case_config, &mut cache)?;
+    cache.set_signer(&case_config.keypair)?;
+    let offline_authority = args
+        .sign_offline
+        .as_deref()
+        .map(|authority| resolve_pubkey(authority, &args.global.address_book))
+        .transpose()?;
+    let client = match &offline_authority {
+        Some(authority) => setup_offline_client(&case_config, authority),
+        None => setup_client(&case_config)?,
+    };
+    let mut config_data = get_config_data(&args.config, &args.global.address_book)?;
 
     let tars_address = &cache.program.tars;
 
@@ -99,10 +166,149 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
         check_seller_fee_basis_points(config_data.seller_fee_basis_points)?;
     }
 
-    let total_steps = 2 + (collection_in_cache as u8) - (hidden as u8);
+    // an empty hash stands for the hash of the cache, which update and
+    // verify-config-hash compute the same way, so the config file is left as it is
+    if let Some(hash) = fill_hidden_settings_hash(&mut config_data, &cache) {
+        case_println!(
+            "{} {} (computed from the cache)",
+            style("Hidden settings hash:").bold(),
+            hash
+        );
+    }
+
+    if let Some(hidden_settings) = &config_data.hidden_settings {
+        check_hidden_settings_name(&hidden_settings.name, num_items)?;
+        check_url(&hidden_settings.uri)?;
+
+        if hidden_settings.hash.len() != HASH_LENGTH {
+            return Err(anyhow!(
+                "The hidden settings hash has to be {} characters long, leave it empty to use \
+                 the hash of the cache",
+                HASH_LENGTH
+            ));
+        }
+
+        if hidden_settings.hash != hidden_settings_hash(&cache.items) {
+            warn!("Hidden settings hash does not match the cache");
+            case_println!(
+                "{}{}",
+                WARNING_EMOJI,
+                style(
+                    "The hidden settings hash does not match the cache, 'verify-config-hash' \
+                     will report a mismatch"
+                )
+                .yellow()
+            );
+        }
+    }
+
+    if args.batch_size == 0 {
+        return Err(anyhow!("The batch size must be at least 1."));
+    }
+
+    // --compute-units takes precedence over the limit of the config file
+    let compute_units = args.compute_units.or_else(|| {
+        config_data
+            .compute_units
+            .as_ref()
+            .and_then(|compute_units| compute_units.deploy)
+    });
+    if let Some(compute_units) = compute_units {
+        check_compute_units(compute_units)?;
+    }
+
+    // the collection NFT is not created with --sign-offline
+    if collection_in_cache && !args.skip_collection && offline_authority.is_none() {
+        check_token_metadata_program(
+            &client.program(TARS_ID),
+            "create and set the collection NFT",
+            Some("--skip-collection"),
+        )?;
+    }
+
+    // the items of a group are moved to its collection after the mint, which needs the
+    // update authority of the collection
+    let item_groups = config_data.item_groups.clone();
+    check_item_groups(&item_groups, num_items)?;
+
+    if !item_groups.is_empty() {
+        let program = client.program(TARS_ID);
+        let authority = offline_authority.unwrap_or_else(|| case_config.keypair.pubkey());
+        check_token_metadata_program(&program, "verify the collections of the item groups", None)?;
+
+        for group in &item_groups {
+            let (_, collection) =
+                get_metadata_pda(&group.collection_mint, &program).map_err(|_| {
+                    anyhow!(
+                        "Collection {} of item group '{}' not found",
+                        group.collection_mint,
+                        group.name
+                    )
+                })?;
+
+            if collection.update_authority != authority {
+                return Err(CustomTarsError::AuthorityMismatch(
+                    collection.update_authority.to_string(),
+                    authority.to_string(),
+                )
+                .into());
+            }
+        }
+    }
+
+    if args.dry_run {
+        let (mut diff, plan) = dry_run_diff(
+            &case_config,
+            &client,
+            &config_data,
+            &cache,
+            num_items,
+            args.batch_size,
+        )?;
+        diff.plan_file = Some(args.global.plan_file.clone());
+        print_diff(&diff);
+        plan.save(&args.global.plan_file)?;
+
+        return Ok(DeployOutcome {
+            tars: diff.tars.clone().unwrap_or_default(),
+            cache: cache.stats(),
+            dry_run: Some(diff),
+            ..Default::default()
+        });
+    }
+
+    let budget = Arc::new(FeeBudget::new(args.max_fee));
+    let costs = if budget.is_limited() {
+        let costs = deploy_costs(
+            &client,
+            &cache,
+            tars_address.is_empty(),
+            num_items,
+            hidden,
+            args.batch_size,
+        )?;
+        budget.check_estimate(costs.total())?;
+        costs
+    } else {
+        DeployCosts::default()
+    };
+
+    let total_steps = 2 + ((collection_in_cache && !args.skip_collection) as u8) - (hidden as u8);
+    let mut outcome = DeployOutcome::default();
+    let mut offline_writer = match offline_authority {
+        Some(authority) => Some(OfflineWriter::new(
+            &client.program(TARS_ID).rpc(),
+            authority,
+            &resolve_nonces(&args.nonces, &args.global.address_book)?,
+        )?),
+        None => None,
+    };
+
+    // keypairs of the new tars and collection mint
+    let mut rng = SystemRng::default();
 
     let tars_pubkey = if tars_address.is_empty() {
-        println!(
+        case_println!(
             "{} {}Creating tars",
             style(format!("[1/{}]", total_steps)).bold().dim(),
             TARS_EMOJI
@@ -112,67 +318,69 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
         let spinner = spinner_with_style();
         spinner.set_message("Creating tars...");
 
-        let tars_keypair = Keypair::new();
+        let tars_keypair = rng.new_keypair();
         let tars_pubkey = tars_keypair.pubkey();
 
         let uuid = DEFAULT_UUID.to_string();
         let tars_data = create_tars_data(&client, &config_data, uuid)?;
         let program = client.program(TARS_ID);
 
-        let treasury_wallet = match config_data.spl_token {
-            Some(spl_token) => {
-                let spl_token_account_figured = if config_data.spl_token_account.is_some() {
-                    config_data.spl_token_account
-                } else {
-                    Some(get_associated_token_address(&program.payer(), &spl_token))
-                };
-
-                if config_data.sol_treasury_account.is_some() {
-                    return Err(anyhow!("If spl-token-account or spl-token is set then sol-treasury-account cannot be set"));
-                }
+        let treasury_wallet = get_treasury_wallet(&program, &config_data)?;
 
-                // validates the mint address of the token accepted as payment
-                check_spl_token(&program, &spl_token.to_string())?;
-
-                if let Some(token_account) = spl_token_account_figured {
-                    // validates the spl token wallet to receive proceedings from SPL token payments
-                    check_spl_token_account(&program, &token_account.to_string())?;
-                    token_account
-                } else {
-                    return Err(anyhow!(
-                        "If spl-token is set, spl-token-account must also be set"
-                    ));
-                }
-            }
-            None => match config_data.sol_treasury_account {
-                Some(sol_treasury_account) => sol_treasury_account,
-                None => case_config.keypair.pubkey(),
-            },
-        };
+        check_treasury_wallet(&program.payer(), &treasury_wallet);
+        budget.reserve(costs.tars)?;
 
         // all good, let's create the tars
 
-        let sig = initialize_tars(
-            &config_data,
-            &tars_keypair,
-            tars_data,
-            treasury_wallet,
-            program,
-        )?;
-        info!("Tars initialized with sig: {}", sig);
+        if let Some(writer) = &mut offline_writer {
+            let instructions = initialize_tars_instructions(
+                &config_data,
+                &tars_pubkey,
+                tars_data,
+                treasury_wallet,
+                &program,
+            )?;
+            // the tars keypair is only needed for this transaction, so it signs it now
+            writer.write("initialize tars", &instructions, &[&tars_keypair])?;
+        } else {
+            let sig = initialize_tars(
+                &case_config,
+                &config_data,
+                &tars_keypair,
+                tars_data,
+                treasury_wallet,
+                program,
+            )?;
+            info!("Tars initialized with sig: {}", sig);
+            outcome.signatures.push(sig.to_string());
+        }
         info!(
             "Tars created with address: {}",
             &tars_pubkey.to_string()
         );
 
         cache.program = CacheProgram::new_from_cm(&tars_pubkey);
+        cache.program.cluster = genesis_hash(&case_config);
         cache.sync_file()?;
 
+        // the tars address is in the cache, so a registry that cannot be written is not
+        // an error
+        let cluster = cache.program.cluster.clone();
+        if let Err(err) = record_deployment(
+            &args.global.deployments,
+            &tars_pubkey,
+            &config_data,
+            &args.cache,
+            cluster,
+        ) {
+            warn!("Failed to add the tars to the deployments file: {}", err);
+        }
+
         spinner.finish_and_clear();
 
         tars_pubkey
     } else {
-        println!(
+        case_println!(
             "{} {}Loading tars",
             style(format!("[1/{}]", total_steps)).bold().dim(),
             TARS_EMOJI
@@ -206,19 +414,33 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
         tars_pubkey
     };
 
-    println!("{} {}", style("Tars ID:").bold(), tars_pubkey);
+    case_println!("{} {}", style("Tars ID:").bold(), tars_pubkey);
 
     if !hidden {
-        println!(
+        case_println!(
             "\n{} {}Writing config lines",
             style(format!("[2/{}]", total_steps)).bold().dim(),
             PAPER_EMOJI
         );
 
-        let config_lines = generate_config_lines(num_items, &cache.items)?;
+        let config_lines = generate_config_lines(num_items, &cache.items, args.batch_size)?;
+        let written = config_lines.iter().map(|lines| lines.len()).sum::<usize>();
 
         if config_lines.is_empty() {
-            println!("\nAll config lines deployed.");
+            case_println!("\nAll config lines deployed.");
+        } else if let Some(writer) = &mut offline_writer {
+            // the cache is updated by 'verify' once the transactions are sent
+            let program = client.program(TARS_ID);
+
+            for chunk in config_lines {
+                let description = format!(
+                    "add config lines {}-{}",
+                    chunk[0].0,
+                    chunk[chunk.len() - 1].0
+                );
+                let instructions = add_config_lines_instructions(&program, &tars_pubkey, chunk)?;
+                writer.write(&description, &instructions, &[])?;
+            }
         } else {
             // clear the interruption handler value ahead of the upload
             args.interrupted.store(false, Ordering::SeqCst);
@@ -228,7 +450,10 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
                 tars_pubkey,
                 &mut cache,
                 config_lines,
+                args.batch_size,
+                compute_units,
                 args.interrupted,
+                Arc::clone(&budget),
             )
             .await?;
 
@@ -253,31 +478,78 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
 
                 return Err(DeployError::AddConfigLineFailed(message).into());
             }
+
+            outcome.config_lines_written = written;
         }
     } else {
-        println!("\nTars with hidden settings deployed.");
+        case_println!("\nTars with hidden settings deployed.");
     }
 
-    if let Some(collection_item) = cache.items.get_mut("-1") {
-        println!(
+    if let Some(collection_item) = cache
+        .items
+        .get_mut("-1")
+        .filter(|_| !args.skip_collection)
+    {
+        case_println!(
             "\n{} {}Creating and setting the collection NFT for tars",
             style(format!("[3/{}]", total_steps)).bold().dim(),
             COLLECTION_EMOJI
         );
 
         if item_redeemed {
-            println!("\nAn item has already been minted and thus cannot modify the tars collection. Skipping...");
+            case_println!("\nAn item has already been minted and thus cannot modify the tars collection. Skipping...");
+        } else if offline_writer.is_some() {
+            case_println!("\nThe collection NFT is not created with --sign-offline, use 'collection set' once it exists.");
         } else if collection_item.on_chain {
-            println!("\nCollection mint already deployed.");
+            let program = client.program(TARS_ID);
+
+            // a collection created with 'collection create' is set on the new tars
+            if get_collection_pda(&tars_pubkey, &program).is_err()
+                && !cache.program.collection_mint.is_empty()
+            {
+                let pb = spinner_with_style();
+                pb.set_message("Sending set collection transaction...");
+
+                let collection_mint = Pubkey::from_str(&cache.program.collection_mint)?;
+                let tars_state = get_tars_state(&case_config, &tars_pubkey)?;
+                let sig = set_collection(
+                    &case_config,
+                    &program,
+                    &tars_pubkey,
+                    &tars_state,
+                    &collection_mint,
+                    &get_metadata_pda(&collection_mint, &program)?,
+                    &get_master_edition_pda(&collection_mint, &program)?,
+                )?;
+                outcome.signatures.push(sig.to_string());
+
+                pb.finish_and_clear();
+                case_println!(
+                    "{} {}",
+                    style("Collection mint ID:").bold(),
+                    collection_mint
+                );
+            } else {
+                case_println!("\nCollection mint already deployed.");
+            }
         } else {
+            budget.reserve(costs.collection)?;
+
             let pb = spinner_with_style();
             pb.set_message("Sending create and set collection NFT transaction...");
 
-            let (_, collection_mint) =
-                create_and_set_collection(client, tars_pubkey, &mut cache, config_data)?;
+            let (sig, collection_mint) = create_and_set_collection(
+                &case_config,
+                client,
+                tars_pubkey,
+                &mut cache,
+                config_data,
+                &mut rng,
+            )?;
+            outcome.signatures.push(sig.to_string());
 
             pb.finish_and_clear();
-            println!(
+            case_println!(
                 "{} {}",
                 style("Collection mint ID:").bold(),
                 collection_mint
@@ -285,5 +557,214 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
         }
     }
 
-    Ok(())
+    if !item_groups.is_empty() {
+        case_println!(
+            "\n{} item group(s) with their own collection:",
+            item_groups.len()
+        );
+        for group in &item_groups {
+            case_println!(
+                "{} {} (items {} to {}): {}",
+                style(" :..").dim(),
+                group.name,
+                group.start,
+                group.end,
+                group.collection_mint
+            );
+        }
+        case_println!(
+            "Move the minted items of the groups to their collection with 'case collection \
+             verify-items'."
+        );
+    }
+
+    outcome.tars = tars_pubkey.to_string();
+    if !cache.program.collection_mint.is_empty() {
+        outcome.collection_mint = Some(cache.program.collection_mint.clone());
+    }
+    outcome.cache = cache.stats();
+
+    if let Some(writer) = offline_writer {
+        outcome.transactions = writer.finish();
+    }
+
+    Ok(outcome)
+}
+
+/// Returns the wallet that receives the proceeds of the mint.
+fn get_treasury_wallet(program: &Program, config_data: &ConfigData) -> Result<Pubkey> {
+    match config_data.spl_token {
+        Some(spl_token) => {
+            let spl_token_account_figured = if config_data.spl_token_account.is_some() {
+                config_data.spl_token_account
+            } else {
+                Some(get_associated_token_address(&program.payer(), &spl_token))
+            };
+
+            if config_data.sol_treasury_account.is_some() {
+                return Err(anyhow!("If spl-token-account or spl-token is set then sol-treasury-account cannot be set"));
+            }
+
+            // validates the mint address of the token accepted as payment
+            check_spl_token(program, &spl_token.to_string())?;
+
+            if let Some(token_account) = spl_token_account_figured {
+                // validates the spl token wallet to receive proceedings from SPL token payments
+                check_spl_token_account(program, &token_account.to_string())?;
+                Ok(token_account)
+            } else {
+                Err(anyhow!(
+                    "If spl-token is set, spl-token-account must also be set"
+                ))
+            }
+        }
+        None => match config_data.sol_treasury_account {
+            Some(sol_treasury_account) => Ok(sol_treasury_account),
+            None => Ok(program.payer()),
+        },
+    }
+}
+
+/// Estimated lamports spent on each step of the deploy.
+#[derive(Debug, Default)]
+struct DeployCosts {
+    tars: u64,
+    config_lines: u64,
+    collection: u64,
+}
+
+impl DeployCosts {
+    fn total(&self) -> u64 {
+        self.tars + self.config_lines + self.collection
+    }
+}
+
+/// Estimates the rent and fees of the steps that the deploy still has to run.
+fn deploy_costs(
+    client: &Client,
+    cache: &Cache,
+    create_tars: bool,
+    num_items: u64,
+    hidden: bool,
+    batch_size: usize,
+) -> Result<DeployCosts> {
+    let program = client.program(TARS_ID);
+    let rpc_client = program.rpc();
+    let mut costs = DeployCosts::default();
+
+    if create_tars {
+        // signed by the payer and the tars keypair
+        costs.tars = rpc_client
+            .get_minimum_balance_for_rent_exemption(tars_account_size(num_items, hidden))?
+            + 2 * SIGNATURE_FEE;
+    }
+
+    if !hidden {
+        let transactions = generate_config_lines(num_items, &cache.items, batch_size)?.len() as u64;
+        costs.config_lines = transactions * SIGNATURE_FEE;
+    }
+
+    if let Some(collection_item) = cache.items.get("-1") {
+        if !collection_item.on_chain {
+            // signed by the payer and the collection mint keypair
+            costs.collection = nft_rent(&rpc_client)? + 2 * SIGNATURE_FEE;
+        }
+    }
+
+    Ok(costs)
+}
+
+/// Builds the changes that the deploy would apply, and the plan of the transactions that
+/// it would send, without sending any transaction.
+fn dry_run_diff(
+    case_config: &CaseConfig,
+    client: &Client,
+    config_data: &ConfigData,
+    cache: &Cache,
+    num_items: u64,
+    batch_size: usize,
+) -> Result<(TarsDiff, TxPlan)> {
+    let program = client.program(TARS_ID);
+    let treasury_wallet = get_treasury_wallet(&program, config_data)?;
+    let mut diff = TarsDiff::default();
+    let mut plan = TxPlan::new("deploy", &program.payer());
+
+    let (tars_pubkey, current) = if cache.program.tars.is_empty() {
+        diff.notes.push("A new tars account would be created.".to_string());
+        // the address is only a placeholder, the plan names the new tars instead
+        (Keypair::new().pubkey(), None)
+    } else {
+        let tars_pubkey = Pubkey::from_str(&cache.program.tars)
+            .map_err(|_| CacheError::InvalidTarsAddress(cache.program.tars.clone()))?;
+        diff.tars = Some(tars_pubkey.to_string());
+        (
+            tars_pubkey,
+            Some(get_tars_state(case_config, &tars_pubkey)?),
+        )
+    };
+    let placeholders = if current.is_none() {
+        vec![(tars_pubkey, "new tars")]
+    } else {
+        Vec::new()
+    };
+
+    let uuid = match &current {
+        Some(tars_state) => tars_state.data.uuid.clone(),
+        None => DEFAULT_UUID.to_string(),
+    };
+    let tars_data = create_tars_data(client, config_data, uuid)?;
+
+    diff.fields = diff_tars_data(
+        current.as_ref(),
+        &tars_data,
+        &treasury_wallet,
+        &config_data.spl_token,
+    );
+
+    if current.is_some() && !diff.fields.is_empty() {
+        diff.notes.push(
+            "Deploy does not modify the settings of an existing tars - run 'update' to apply the field changes."
+                .to_string(),
+        );
+    }
+
+    if current.is_none() {
+        let instructions = initialize_tars_instructions(
+            config_data,
+            &tars_pubkey,
+            tars_data,
+            treasury_wallet,
+            &program,
+        )?;
+        plan.add("initialize tars", None, &instructions, &placeholders);
+    }
+
+    if config_data.hidden_settings.is_none() {
+        let config_lines = generate_config_lines(num_items, &cache.items, batch_size)?;
+        diff.config_lines = config_line_changes(&config_lines);
+
+        for chunk in config_lines {
+            let args = json!(chunk
+                .iter()
+                .map(|(index, line)| json!({ "index": index, "name": line.name, "uri": line.uri }))
+                .collect::<Vec<Value>>());
+            let description = format!(
+                "add config lines {}-{}",
+                chunk[0].0,
+                chunk[chunk.len() - 1].0
+            );
+            let instructions = add_config_lines_instructions(&program, &tars_pubkey, chunk)?;
+            plan.add(&description, Some(args), &instructions, &placeholders);
+        }
+    }
+
+    if let Some(collection_item) = cache.items.get("-1") {
+        if !collection_item.on_chain {
+            diff.notes.push("The collection NFT would be created and set.".to_string());
+        }
+    }
+
+    plan.notes = diff.notes.clone();
+
+    Ok((diff, plan))
 }
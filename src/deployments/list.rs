@@ -0,0 +1,147 @@
+use chrono::Utc;
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    cache::cluster_name,
+    common::*,
+    deployments::{load_deployments, save_deployments, Deployment, DeploymentState},
+    setup::genesis_hash,
+    tars::TARS_ID,
+    utils::spinner_with_style,
+};
+
+pub struct DeploymentsListArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    /// Look up the on-chain state of the deployments of the RPC cluster.
+    pub refresh: bool,
+}
+
+/// Result of the deployments list command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentsListOutcome {
+    pub file: String,
+    pub deployments: Vec<Deployment>,
+    /// Number of deployments whose state changed (--refresh).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refreshed: Option<usize>,
+}
+
+pub fn process_deployments_list(args: DeploymentsListArgs) -> Result<DeploymentsListOutcome> {
+    let mut outcome = DeploymentsListOutcome {
+        file: args.global.deployments.clone(),
+        deployments: load_deployments(&args.global.deployments)?,
+        ..Default::default()
+    };
+
+    if outcome.deployments.is_empty() {
+        case_println!(
+            "No deployments recorded in '{}' - tarss are added by 'deploy'.",
+            outcome.file
+        );
+        return Ok(outcome);
+    }
+
+    if args.refresh {
+        outcome.refreshed = Some(refresh_states(
+            args.keypair,
+            args.rpc_url,
+            &args.global,
+            &mut outcome.deployments,
+        )?);
+        save_deployments(&args.global.deployments, &outcome.deployments)?;
+    }
+
+    case_println!("\n{:44} {:10} {:20} State", "Tars ID", "Cluster", "Created");
+    case_println!("{:-<85}", "-");
+
+    for deployment in &outcome.deployments {
+        let cluster = deployment
+            .cluster
+            .as_deref()
+            .map_or_else(|| "unknown".to_string(), cluster_name);
+        let state = match deployment.state {
+            DeploymentState::Active => style(deployment.state.name()).green(),
+            DeploymentState::SoldOut => style(deployment.state.name()).yellow(),
+            DeploymentState::Closed => style(deployment.state.name()).dim(),
+        };
+
+        // the date and time, without the fractions of seconds and the offset
+        let created_at = deployment
+            .created_at
+            .get(..19)
+            .unwrap_or(&deployment.created_at);
+
+        case_println!(
+            "{:44} {:10} {:20} {}",
+            deployment.tars,
+            cluster,
+            created_at,
+            state
+        );
+    }
+
+    if let Some(refreshed) = outcome.refreshed {
+        case_println!("\n{} deployment state(s) changed", refreshed);
+    }
+
+    Ok(outcome)
+}
+
+/// Updates the state of the open deployments of the RPC cluster, returning the number
+/// of deployments whose state changed. Closed tarss cannot be reopened, so they are
+/// not looked up.
+fn refresh_states(
+    keypair: Option<String>,
+    rpc_url: Option<String>,
+    global: &GlobalOptions,
+    deployments: &mut [Deployment],
+) -> Result<usize> {
+    let case_config = case_setup(keypair, rpc_url, global)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+    let cluster = genesis_hash(&case_config);
+
+    let pb = spinner_with_style();
+    pb.set_message("Refreshing deployment states...");
+
+    let mut changed = 0;
+
+    for deployment in deployments.iter_mut().filter(|deployment| {
+        deployment.state != DeploymentState::Closed
+            && (deployment.cluster.is_none() || deployment.cluster == cluster)
+    }) {
+        let tars = Pubkey::from_str(&deployment.tars)
+            .map_err(|_| anyhow!("Invalid tars address in deployments: {}", deployment.tars))?;
+        let account = program
+            .rpc()
+            .get_account_with_commitment(&tars, CommitmentConfig::confirmed())?
+            .value;
+
+        let state = match account {
+            Some(account) => {
+                let tars_state = Tars::try_deserialize(&mut account.data.as_slice())?;
+
+                if tars_state.items_redeemed >= tars_state.data.items_available {
+                    DeploymentState::SoldOut
+                } else {
+                    DeploymentState::Active
+                }
+            }
+            None => DeploymentState::Closed,
+        };
+
+        if state != deployment.state {
+            deployment.state = state;
+            deployment.updated_at = Some(Utc::now().to_rfc3339());
+            changed += 1;
+        }
+    }
+
+    pb.finish_and_clear();
+
+    Ok(changed)
+}
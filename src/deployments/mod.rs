@@ -0,0 +1,5 @@
+pub mod list;
+pub mod registry;
+
+pub use list::*;
+pub use registry::*;
@@ -0,0 +1,118 @@
+use std::fs;
+
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::{
+    canonical_json::to_canonical_json, common::*, config::ConfigData, shuffle::sha256_hex,
+};
+
+/// State of a deployment, as last seen on-chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DeploymentState {
+    Active,
+    SoldOut,
+    Closed,
+}
+
+impl DeploymentState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeploymentState::Active => "active",
+            DeploymentState::SoldOut => "sold out",
+            DeploymentState::Closed => "closed",
+        }
+    }
+}
+
+/// Tars created by the project. Entries are never removed, so that old tarss can be
+/// found after their cache file is overwritten.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deployment {
+    pub tars: String,
+    /// Genesis hash of the cluster the tars was created on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster: Option<String>,
+    pub created_at: String,
+    /// SHA-256 of the canonical JSON of the config the tars was created with.
+    pub config_hash: String,
+    pub cache: String,
+    pub state: DeploymentState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+/// Loads the deployments of the registry at `path`, oldest first. Returns no deployments
+/// when the file does not exist.
+pub fn load_deployments(path: &str) -> Result<Vec<Deployment>> {
+    if !Path::new(path).exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|err| anyhow!("Failed to open deployments file '{}': {}", path, err))?;
+
+    serde_json::from_str(&content)
+        .map_err(|err| anyhow!("Failed to parse deployments file '{}': {}", path, err))
+}
+
+pub fn save_deployments(path: &str, deployments: &[Deployment]) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(deployments)?)
+        .map_err(|err| anyhow!("Failed to write deployments file '{}': {}", path, err))
+}
+
+/// Adds a tars created from the config on the cluster with the genesis hash to the
+/// registry at `path`.
+pub fn record_deployment(
+    path: &str,
+    tars: &Pubkey,
+    config_data: &ConfigData,
+    cache: &str,
+    cluster: Option<String>,
+) -> Result<()> {
+    let mut deployments = load_deployments(path)?;
+
+    deployments.push(Deployment {
+        tars: tars.to_string(),
+        cluster,
+        created_at: Utc::now().to_rfc3339(),
+        config_hash: config_hash(config_data)?,
+        cache: cache.to_string(),
+        state: DeploymentState::Active,
+        updated_at: None,
+    });
+
+    save_deployments(path, &deployments)?;
+    info!("Recorded tars {} in {}", tars, path);
+
+    Ok(())
+}
+
+/// Sets the state of a tars of the registry at `path`; tarss created by other projects
+/// are ignored.
+pub fn set_deployment_state(path: &str, tars: &Pubkey, state: DeploymentState) -> Result<()> {
+    let mut deployments = load_deployments(path)?;
+    let tars = tars.to_string();
+    let mut changed = false;
+
+    for deployment in deployments.iter_mut() {
+        if deployment.tars == tars && deployment.state != state {
+            deployment.state = state;
+            deployment.updated_at = Some(Utc::now().to_rfc3339());
+            changed = true;
+        }
+    }
+
+    if changed {
+        save_deployments(path, &deployments)?;
+    }
+
+    Ok(())
+}
+
+/// Hash of a config, to tell which version of the config a tars was created with.
+pub fn config_hash(config_data: &ConfigData) -> Result<String> {
+    Ok(sha256_hex(to_canonical_json(config_data)?.as_bytes()))
+}
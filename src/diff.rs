@@ -0,0 +1,247 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use console::style;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tars::{ConfigLine, EndSettingType, Tars, TarsData, WhitelistMintMode};
+
+use crate::case_println;
+
+/// Change of a single tars field.
+#[derive(Debug, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub current: Value,
+    pub new: Value,
+}
+
+/// Config line that would be written to the tars.
+#[derive(Debug, Serialize)]
+pub struct ConfigLineChange {
+    pub index: u32,
+    pub name: String,
+    pub uri: String,
+}
+
+/// Changes that a command would apply on-chain.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TarsDiff {
+    pub tars: Option<String>,
+    pub fields: Vec<FieldChange>,
+    pub config_lines: Vec<ConfigLineChange>,
+    pub notes: Vec<String>,
+    /// Plan file of the transactions that would be sent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_file: Option<String>,
+}
+
+impl TarsDiff {
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.config_lines.is_empty()
+    }
+}
+
+/// Returns the (comparable) values of the tars account fields.
+fn tars_fields(
+    data: &TarsData,
+    wallet: &Pubkey,
+    token_mint: &Option<Pubkey>,
+) -> Vec<(&'static str, Value)> {
+    vec![
+        ("wallet", json!(wallet.to_string())),
+        ("tokenMint", json!(token_mint.map(|mint| mint.to_string()))),
+        ("uuid", json!(data.uuid)),
+        ("price", json!(data.price)),
+        ("symbol", json!(data.symbol)),
+        ("sellerFeeBasisPoints", json!(data.seller_fee_basis_points)),
+        ("maxSupply", json!(data.max_supply)),
+        ("isMutable", json!(data.is_mutable)),
+        ("retainAuthority", json!(data.retain_authority)),
+        ("goLiveDate", json!(data.go_live_date)),
+        (
+            "endSettings",
+            match &data.end_settings {
+                Some(end_settings) => json!({
+                    "endSettingType": match end_settings.end_setting_type {
+                        EndSettingType::Date => "date",
+                        EndSettingType::Amount => "amount",
+                    },
+                    "number": end_settings.number,
+                }),
+                None => Value::Null,
+            },
+        ),
+        (
+            "creators",
+            Value::Array(
+                data.creators
+                    .iter()
+                    .map(|creator| {
+                        json!({
+                            "address": creator.address.to_string(),
+                            "verified": creator.verified,
+                            "share": creator.share,
+                        })
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "whitelistMintSettings",
+            match &data.whitelist_mint_settings {
+                Some(settings) => json!({
+                    "mode": if settings.mode == WhitelistMintMode::BurnEveryTime {
+                        "burnEveryTime"
+                    } else {
+                        "neverBurn"
+                    },
+                    "mint": settings.mint.to_string(),
+                    "presale": settings.presale,
+                    "discountPrice": settings.discount_price,
+                }),
+                None => Value::Null,
+            },
+        ),
+        (
+            "hiddenSettings",
+            match &data.hidden_settings {
+                Some(settings) => json!({
+                    "name": settings.name,
+                    "uri": settings.uri,
+                    "hash": String::from_utf8_lossy(&settings.hash),
+                }),
+                None => Value::Null,
+            },
+        ),
+        ("itemsAvailable", json!(data.items_available)),
+        (
+            "gatekeeper",
+            match &data.gatekeeper {
+                Some(gatekeeper) => json!({
+                    "gatekeeperNetwork": gatekeeper.gatekeeper_network.to_string(),
+                    "expireOnUse": gatekeeper.expire_on_use,
+                }),
+                None => Value::Null,
+            },
+        ),
+    ]
+}
+
+/// Compares the tars data that would be written against the current on-chain state
+/// (`None` when the tars does not exist yet).
+pub fn diff_tars_data(
+    current: Option<&Tars>,
+    data: &TarsData,
+    wallet: &Pubkey,
+    token_mint: &Option<Pubkey>,
+) -> Vec<FieldChange> {
+    let new_fields = tars_fields(data, wallet, token_mint);
+
+    match current {
+        Some(current) => {
+            let current_fields = tars_fields(&current.data, &current.wallet, &current.token_mint);
+
+            current_fields
+                .into_iter()
+                .zip(new_fields)
+                .filter(|((_, current), (_, new))| current != new)
+                .map(|((field, current), (_, new))| FieldChange {
+                    field: field.to_string(),
+                    current,
+                    new,
+                })
+                .collect()
+        }
+        None => new_fields
+            .into_iter()
+            .map(|(field, new)| FieldChange {
+                field: field.to_string(),
+                current: Value::Null,
+                new,
+            })
+            .collect(),
+    }
+}
+
+pub fn config_line_changes(config_lines: &[Vec<(u32, ConfigLine)>]) -> Vec<ConfigLineChange> {
+    config_lines
+        .iter()
+        .flatten()
+        .map(|(index, line)| ConfigLineChange {
+            index: *index,
+            name: line.name.clone(),
+            uri: line.uri.clone(),
+        })
+        .collect()
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Null => "none".to_string(),
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Prints each field change as `current -> new`.
+pub fn print_field_changes(fields: &[FieldChange]) {
+    for change in fields {
+        case_println!(
+            " {} {} {} {}",
+            style(format!(":.. {}:", change.field)).dim(),
+            style(format_value(&change.current)).red(),
+            style("->").dim(),
+            style(format_value(&change.new)).green()
+        );
+    }
+}
+
+/// Prints the diff in a human-readable format followed by its JSON representation.
+pub fn print_diff(diff: &TarsDiff) {
+    case_println!(
+        "\n{}",
+        style("Dry run - no transactions sent").bold().yellow()
+    );
+
+    if let Some(tars) = &diff.tars {
+        case_println!("{} {}", style("Tars ID:").bold(), tars);
+    }
+
+    for note in &diff.notes {
+        case_println!("{} {}", style("=>").dim(), note);
+    }
+
+    if diff.is_empty() {
+        case_println!("\nNo changes.");
+    }
+
+    if !diff.fields.is_empty() {
+        case_println!("\n{}", style("Fields:").dim());
+        print_field_changes(&diff.fields);
+    }
+
+    if !diff.config_lines.is_empty() {
+        case_println!(
+            "\n{}",
+            style(format!(
+                "Config lines ({} to write):",
+                diff.config_lines.len()
+            ))
+            .dim()
+        );
+        for line in &diff.config_lines {
+            case_println!(
+                " {} {} {}",
+                style(format!(":.. {}:", line.index)).dim(),
+                line.name,
+                style(&line.uri).dim()
+            );
+        }
+    }
+
+    case_println!(
+        "\n{}\n{}",
+        style("JSON diff:").dim(),
+        serde_json::to_string_pretty(diff).unwrap_or_default()
+    );
+}
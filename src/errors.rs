@@ -6,12 +6,21 @@ use std::{
 use serde::Serialize;
 use thiserror::Error;
 
-use crate::common::*;
+use crate::{common::*, validate::ValidateParserError};
 
 #[derive(Debug, Error)]
 pub enum SetupError {
     #[error("Error setting up case: {0}")]
     CaseSetupError(String),
+
+    #[error("Keypair file '{0}' is readable by other users. Restrict its permissions (chmod 600) or use --insecure-keypair to proceed on mainnet.")]
+    InsecureKeypair(String),
+
+    #[error("Invalid keypair from {0}: {1}")]
+    InvalidKeypair(String, String),
+
+    #[error("Invalid derivation path '{0}': {1}")]
+    InvalidDerivationPath(String, String),
 }
 
 #[derive(Debug, Error)]
@@ -30,6 +39,81 @@ pub enum CacheError {
 
     #[error("Invalid cache state found.")]
     InvalidState,
+
+    #[error("Cache file '{0}' was modified outside of case (invalid signature). Restore it, or remove its \"signature\" field to accept the changes.")]
+    InvalidSignature(String),
+
+    #[error("Cache file was signed by {0}, which is not the current keypair ({1}). Check that the cache belongs to this project.")]
+    SignerMismatch(String, String),
+
+    #[error("Cache item {0} not found.")]
+    ItemNotFound(u32),
+
+    #[error("No removed cache item to restore{}.", .0.map(|index| format!(" at index {}", index)).unwrap_or_default())]
+    RemovedItemNotFound(Option<u32>),
+
+    #[error("Cannot shift the items from index {0}: item {1} is already on-chain.")]
+    ItemOnChain(u32, String),
+
+    #[error("Cache file '{0}' was created on {1}, but the RPC is connected to {2}. Check the RPC url, or use --force-cluster to proceed anyway.")]
+    ClusterMismatch(String, String, String),
+
+    #[error("Could not check that cache file '{0}' was created on the cluster of the RPC: the genesis hash of the RPC is unavailable. Check the RPC url, or use --force-cluster to proceed anyway.")]
+    ClusterUnknown(String),
+}
+
+#[derive(Debug, Error)]
+pub enum AddressBookError {
+    #[error("Failed to open address book file: {0} with error: {1}")]
+    FailedToOpen(String, String),
+
+    #[error("Failed to parse address book file: {0} with error: {1}")]
+    WrongFormat(String, String),
+
+    #[error("Invalid address for label '@{0}' in the address book: {1}")]
+    InvalidAddress(String, String),
+
+    #[error("Label '@{0}' not found in the address book")]
+    UnknownLabel(String),
+}
+
+#[derive(Debug, Error)]
+pub enum DenylistError {
+    #[error("Failed to open deny list file: {0} with error: {1}")]
+    FailedToOpen(String, String),
+
+    #[error("Failed to parse deny list file: {0} with error: {1}")]
+    WrongFormat(String, String),
+
+    #[error("Invalid address in the deny list file {0}: {1}")]
+    InvalidAddress(String, String),
+
+    #[error("Address {0} is in the deny list")]
+    Denied(String),
+}
+
+#[derive(Debug, Error)]
+pub enum FeeBudgetError {
+    #[error("Estimated cost of ◎ {0} exceeds the --max-fee budget of ◎ {1}")]
+    EstimateExceeded(String, String),
+
+    #[error("Stopped to stay within the --max-fee budget of ◎ {1} (◎ {0} spent)")]
+    LimitReached(String, String),
+}
+
+#[derive(Debug, Error)]
+pub enum RoleError {
+    #[error("The '{1}' role set in the config file '{2}' does not allow running 'case {0}' (requires '{3}')")]
+    NotPermitted(String, String, String, String),
+
+    #[error("Missing config file '{1}' to check the role required by 'case {0}' ('{2}')")]
+    MissingConfig(String, String, String),
+}
+
+#[derive(Debug, Error)]
+pub enum ClusterError {
+    #[error("The token metadata program {0} is not deployed on the cluster at {1}, it is needed to {2}. Start the local validator with the program (e.g. 'solana-test-validator --clone {0} --url mainnet-beta'){3}.")]
+    TokenMetadataNotDeployed(String, String, String, String),
 }
 
 #[derive(Debug, Error)]
@@ -41,9 +125,20 @@ pub enum CustomTarsError {
 #[derive(Debug, Serialize)]
 pub struct ValidateError<'a> {
     pub path: &'a PathBuf,
+    pub code: &'static str,
     pub error: String,
 }
 
+impl<'a> ValidateError<'a> {
+    pub fn new(path: &'a PathBuf, error: &ValidateParserError) -> Self {
+        ValidateError {
+            path,
+            code: error.code(),
+            error: error.to_string(),
+        }
+    }
+}
+
 pub fn log_errors<T: std::fmt::Debug + Serialize>(
     error_type: &str,
     errors: Arc<Mutex<Vec<T>>>,
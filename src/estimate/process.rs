@@ -0,0 +1,186 @@
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    budget::{nft_rent, to_sol},
+    cache::*,
+    common::*,
+    config::{get_config_data, UploadMethod},
+    deploy::{generate_config_lines, tars_account_size},
+    tars::TARS_ID,
+    upload::{
+        assets::{get_asset_pairs, DataType},
+        methods::{bundlr_node, upload_size, BundlrMethod},
+        uploader::MOCK_URI_SIZE,
+    },
+    utils::*,
+};
+
+pub struct EstimateArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub assets_dir: String,
+    pub config: String,
+}
+
+/// Result of the estimate command (values in lamports).
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimateOutcome {
+    pub items: usize,
+    pub upload_method: String,
+    /// Bytes charged by the storage (Bundlr only).
+    pub upload_size: u64,
+    pub storage: u64,
+    pub tars_rent: u64,
+    pub config_line_fees: u64,
+    pub collection: u64,
+    pub total: u64,
+    pub balance: u64,
+}
+
+pub async fn process_estimate(args: EstimateArgs) -> Result<EstimateOutcome> {
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let config_data = get_config_data(&args.config, &args.global.address_book)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+    let rpc_client = program.rpc();
+
+    case_println!(
+        "{} {}Loading assets",
+        style("[1/2]").bold().dim(),
+        ASSETS_EMOJI
+    );
+
+    let asset_pairs = get_asset_pairs(&args.assets_dir)?;
+    let hidden = config_data.hidden_settings.is_some();
+    let num_items = asset_pairs.keys().filter(|index| **index >= 0).count();
+
+    if !hidden && num_items as u64 != config_data.number {
+        return Err(anyhow!(
+            "The number of items in the config file ({}) does not match the number of assets ({})",
+            config_data.number,
+            num_items
+        ));
+    }
+
+    case_println!(
+        "\n{} {}Estimating costs",
+        style("[2/2]").bold().dim(),
+        PAYMENT_EMOJI
+    );
+
+    let pb = spinner_with_style();
+    pb.enable_steady_tick(120);
+    pb.set_message("Fetching prices...");
+
+    let mut outcome = EstimateOutcome {
+        items: num_items,
+        upload_method: config_data.upload_method.to_string(),
+        ..Default::default()
+    };
+
+    // storage cost, charged in SOL only by Bundlr
+    let storage_note = match config_data.upload_method {
+        UploadMethod::Bundlr => {
+            let indices: Vec<isize> = asset_pairs.keys().copied().collect();
+            outcome.upload_size = upload_size(
+                &asset_pairs,
+                vec![
+                    (DataType::Image, indices.as_slice()),
+                    (DataType::Metadata, indices.as_slice()),
+                    (DataType::Animation, indices.as_slice()),
+                ],
+            )
+            .await?;
+
+            let node = bundlr_node(&config_data, get_cluster(program.rpc())?);
+            // the upload funds the Bundlr account in a single transaction
+            outcome.storage =
+                BundlrMethod::get_bundlr_fee(&HttpClient::new(), node, outcome.upload_size).await?
+                    + SIGNATURE_FEE;
+            format!("{} bytes", outcome.upload_size)
+        }
+        UploadMethod::AWS => "billed by AWS".to_string(),
+        UploadMethod::NftStorage => "free".to_string(),
+        UploadMethod::SHDW => "paid from the storage account".to_string(),
+    };
+
+    // signed by the payer and the tars keypair
+    outcome.tars_rent = rpc_client
+        .get_minimum_balance_for_rent_exemption(tars_account_size(config_data.number, hidden))?
+        + 2 * SIGNATURE_FEE;
+
+    if !hidden {
+        // config lines with URIs of the same size as the ones written after upload
+        let mut cache_items = CacheItems::new();
+
+        for (index, pair) in &asset_pairs {
+            cache_items.insert(
+                index.to_string(),
+                CacheItem {
+                    name: pair.name.clone(),
+                    image_hash: String::new(),
+                    image_link: String::new(),
+                    metadata_hash: String::new(),
+                    metadata_link: "x".repeat(MOCK_URI_SIZE),
+                    on_chain: false,
+                    animation_hash: None,
+                    animation_link: None,
+                    storage_check: None,
+                },
+            );
+        }
+
+        let transactions =
+            generate_config_lines(config_data.number, &cache_items, CONFIG_LINES_BATCH_SIZE)?;
+        outcome.config_line_fees = transactions.len() as u64 * SIGNATURE_FEE;
+    }
+
+    if asset_pairs.contains_key(&-1) {
+        // signed by the payer and the collection mint keypair
+        outcome.collection = nft_rent(&rpc_client)? + 2 * SIGNATURE_FEE;
+    }
+
+    outcome.total =
+        outcome.storage + outcome.tars_rent + outcome.config_line_fees + outcome.collection;
+    outcome.balance = rpc_client.get_balance(&case_config.keypair.pubkey())?;
+
+    pb.finish_and_clear();
+
+    let rows = [
+        (format!("Storage ({})", storage_note), outcome.storage),
+        ("Tars account rent".to_string(), outcome.tars_rent),
+        ("Config line fees".to_string(), outcome.config_line_fees),
+        ("Collection NFT".to_string(), outcome.collection),
+    ];
+
+    case_println!();
+    for (label, lamports) in rows {
+        case_println!("{:<40} ◎ {}", label, to_sol(lamports));
+    }
+    case_println!(
+        "{}",
+        style(format!("{:<40} ◎ {}", "Total", to_sol(outcome.total))).bold()
+    );
+    case_println!(
+        "{:<40} ◎ {}",
+        format!("Balance of {}", case_config.keypair.pubkey()),
+        to_sol(outcome.balance)
+    );
+
+    if outcome.balance < outcome.total {
+        case_println!(
+            "\n{}",
+            style(format!(
+                "Fund the wallet with at least ◎ {} before running 'launch'.",
+                to_sol(outcome.total - outcome.balance)
+            ))
+            .yellow()
+            .bold()
+        );
+    }
+
+    Ok(outcome)
+}
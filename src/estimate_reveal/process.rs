@@ -0,0 +1,231 @@
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    budget::to_sol,
+    cache::{check_cache_cluster, load_cache},
+    common::*,
+    rng::{Rng, SystemRng},
+    snapshot::find_tars_metadata,
+    tars::*,
+    utils::*,
+};
+
+/// Average time (in seconds) to send and confirm a metadata update transaction.
+const SECONDS_PER_TRANSACTION: f64 = 2.0;
+
+/// Concurrency levels estimated when none is specified.
+const DEFAULT_CONCURRENCY: [usize; 5] = [1, 5, 10, 25, PARALLEL_LIMIT];
+
+pub struct EstimateRevealArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub tars: Option<String>,
+    pub concurrency: Vec<usize>,
+    pub sample: usize,
+}
+
+/// Estimated duration of the reveal with a number of concurrent transactions.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevealTiming {
+    pub concurrency: usize,
+    pub seconds: u64,
+}
+
+/// Result of the estimate-reveal command (fees in lamports).
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimateRevealOutcome {
+    pub tars: String,
+    pub minted: u64,
+    pub cache_items: usize,
+    pub transactions: u64,
+    pub fees: u64,
+    pub balance: u64,
+    pub timings: Vec<RevealTiming>,
+    /// Number of minted NFTs checked for update authority.
+    pub sampled: usize,
+    /// Sampled mints whose metadata cannot be updated by the keypair.
+    pub not_updatable: Vec<String>,
+}
+
+pub fn process_estimate_reveal(args: EstimateRevealArgs) -> Result<EstimateRevealOutcome> {
+    case_println!(
+        "{} {}Looking up tars",
+        style("[1/3]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let mut cache = load_cache(&args.cache, false)?;
+
+    // the tars id specified takes precedence over the one from the cache
+    let tars_id = match args.tars {
+        Some(tars_id) => tars_id,
+        None => cache.program.tars.clone(),
+    };
+    let tars_pubkey = resolve_pubkey(&tars_id, &args.global.address_book)?;
+
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    check_cache_cluster(&case_config, &mut cache)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+    let pubkey = case_config.keypair.pubkey();
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+    let tars_state = get_tars_state(&case_config, &tars_pubkey)?;
+    pb.finish_and_clear();
+
+    if tars_state.data.hidden_settings.is_none() {
+        return Err(anyhow!(
+            "Tars {} does not use hidden settings, there is nothing to reveal",
+            tars_pubkey
+        ));
+    }
+
+    let mut outcome = EstimateRevealOutcome {
+        tars: tars_pubkey.to_string(),
+        minted: tars_state.items_redeemed,
+        cache_items: cache.items.keys().filter(|key| *key != "-1").count(),
+        ..Default::default()
+    };
+
+    case_println!(
+        "{} {} of {}",
+        style("Minted:").bold(),
+        outcome.minted,
+        tars_state.data.items_available
+    );
+
+    if (outcome.cache_items as u64) < outcome.minted {
+        case_println!(
+            "{}",
+            style(format!(
+                "The cache has {} item(s), fewer than the {} minted NFT(s) to reveal.",
+                outcome.cache_items, outcome.minted
+            ))
+            .yellow()
+        );
+    }
+
+    case_println!(
+        "\n{} {}Estimating the reveal",
+        style("[2/3]").bold().dim(),
+        PAYMENT_EMOJI
+    );
+
+    // one metadata update (signed by the update authority) per minted NFT
+    outcome.transactions = outcome.minted;
+    outcome.fees = outcome.transactions * SIGNATURE_FEE;
+    outcome.balance = program.rpc().get_balance(&pubkey)?;
+
+    let mut levels = if args.concurrency.is_empty() {
+        DEFAULT_CONCURRENCY.to_vec()
+    } else {
+        args.concurrency
+    };
+    levels.sort_unstable();
+    levels.dedup();
+
+    for concurrency in levels {
+        if concurrency == 0 {
+            return Err(anyhow!("Concurrency must be greater than zero"));
+        }
+
+        let rounds = (outcome.transactions as f64 / concurrency as f64).ceil();
+        outcome.timings.push(RevealTiming {
+            concurrency,
+            seconds: (rounds * SECONDS_PER_TRANSACTION) as u64,
+        });
+    }
+
+    case_println!("{:<40} {}", "Transactions", outcome.transactions);
+    case_println!("{:<40} ◎ {}", "Fees", to_sol(outcome.fees));
+    case_println!(
+        "{:<40} ◎ {}",
+        format!("Balance of {}", pubkey),
+        to_sol(outcome.balance)
+    );
+    case_println!();
+    for timing in &outcome.timings {
+        case_println!(
+            "{:<40} {}",
+            format!("Time with concurrency {}", timing.concurrency),
+            format_duration(timing.seconds)
+        );
+    }
+
+    if outcome.balance < outcome.fees {
+        case_println!(
+            "\n{}",
+            style(format!(
+                "Fund the wallet with at least ◎ {} before the reveal.",
+                to_sol(outcome.fees - outcome.balance)
+            ))
+            .yellow()
+            .bold()
+        );
+    }
+
+    case_println!(
+        "\n{} {}Checking update authority",
+        style("[3/3]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let pb = spinner_with_style();
+    pb.set_message("Looking up minted NFTs...");
+    let metadata = find_tars_metadata(&program, &tars_pubkey)?;
+    pb.finish_and_clear();
+
+    let sample: Vec<_> = SystemRng::default()
+        .sample_indices(metadata.len(), args.sample)
+        .into_iter()
+        .map(|i| &metadata[i])
+        .collect();
+    outcome.sampled = sample.len();
+    outcome.not_updatable = sample
+        .iter()
+        .filter(|metadata| !metadata.is_mutable || metadata.update_authority != pubkey)
+        .map(|metadata| metadata.mint.to_string())
+        .collect();
+
+    if outcome.not_updatable.is_empty() {
+        case_println!(
+            "{} {} sampled NFT(s) out of {} can be updated by {}",
+            style("Update authority:").bold(),
+            outcome.sampled,
+            metadata.len(),
+            pubkey
+        );
+    } else {
+        for mint in &outcome.not_updatable {
+            case_println!("{} {}", WARNING_EMOJI, mint);
+        }
+        case_println!(
+            "\n{}",
+            style(format!(
+                "{} of {} sampled NFT(s) are immutable or have a different update authority than {}.",
+                outcome.not_updatable.len(),
+                outcome.sampled,
+                pubkey
+            ))
+            .red()
+            .bold()
+        );
+    }
+
+    Ok(outcome)
+}
+
+fn format_duration(seconds: u64) -> String {
+    format!(
+        "{}h {:02}m {:02}s",
+        seconds / 3600,
+        (seconds % 3600) / 60,
+        seconds % 60
+    )
+}
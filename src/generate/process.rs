@@ -0,0 +1,450 @@
+use std::{collections::HashSet, ffi::OsStr, fs, sync::Mutex};
+
+use console::style;
+use image::{imageops, RgbaImage};
+use rand::Rng as _;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    canonical_json::to_canonical_json,
+    common::*,
+    config::{get_config_data, ConfigData, Generation},
+    rng::{Rng, SeededRng, SystemRng},
+    upload::list_files,
+    utils::progress_bar_with_style,
+    validate::{
+        validate_metadata_file, Attribute, Creator as MetadataCreator, FileAttr, Metadata, Property,
+    },
+};
+
+/// Extensions of the trait images, which need to support transparency.
+const LAYER_EXTENSIONS: [&str; 2] = ["png", "webp"];
+
+pub struct GenerateArgs {
+    pub assets_dir: String,
+    pub config: String,
+    pub global: GlobalOptions,
+    /// Number of items [default: the number of the config].
+    pub number: Option<u64>,
+    /// Seed of the random choice of the traits, to generate the same items again.
+    pub seed: Option<u64>,
+    /// Replaces the asset files of the assets directory.
+    pub force: bool,
+}
+
+/// Result of the generate command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateOutcome {
+    pub assets_dir: String,
+    pub items: usize,
+    pub layers: usize,
+    /// Combinations of the traits, including the incompatible ones.
+    pub combinations: u128,
+}
+
+/// Trait image of a layer.
+struct TraitImage {
+    value: String,
+    weight: u32,
+    path: PathBuf,
+}
+
+struct LoadedLayer {
+    trait_type: String,
+    traits: Vec<TraitImage>,
+}
+
+/// Incompatibility between two traits, as (layer, trait) indices.
+type Incompatibility = ((usize, usize), (usize, usize));
+
+pub fn process_generate(args: GenerateArgs) -> Result<GenerateOutcome> {
+    let config_data = get_config_data(&args.config, &args.global.address_book)?;
+    let generation = config_data.generation.clone().ok_or_else(|| {
+        anyhow!(
+            "Missing \"generation\" settings in the config file '{}'",
+            args.config
+        )
+    })?;
+    let number = args.number.unwrap_or(config_data.number) as usize;
+
+    if number == 0 {
+        return Err(anyhow!(
+            "The number of items to generate must be at least 1"
+        ));
+    }
+
+    case_println!(
+        "{} {}Loading layers",
+        style("[1/3]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let layers = load_layers(&generation)?;
+    let incompatibilities = resolve_incompatibilities(&generation, &layers)?;
+    let combinations = layers
+        .iter()
+        .map(|layer| layer.traits.len() as u128)
+        .fold(1u128, |total, count| total.saturating_mul(count));
+
+    for layer in &layers {
+        case_println!("{}: {} trait(s)", layer.trait_type, layer.traits.len());
+    }
+
+    case_println!(
+        "\n{} {}Choosing traits",
+        style("[2/3]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    let mut rng: Box<dyn Rng> = match args.seed {
+        Some(seed) => Box::new(SeededRng::new(seed)),
+        None => Box::new(SystemRng::default()),
+    };
+    let items = choose_traits(&layers, &incompatibilities, number, rng.as_mut())?;
+    case_println!(
+        "{} unique combination(s) of {} possible",
+        items.len(),
+        combinations
+    );
+
+    let assets_dir = Path::new(&args.assets_dir);
+    if assets_dir.is_dir() {
+        let existing = list_files(&args.assets_dir, true)?;
+
+        if !existing.is_empty() && !args.force {
+            return Err(anyhow!(
+                "The assets directory '{}' already has {} asset file(s), use --force to \
+                 replace them",
+                args.assets_dir,
+                existing.len()
+            ));
+        }
+
+        for entry in existing {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    fs::create_dir_all(assets_dir)?;
+
+    case_println!(
+        "\n{} {}Composing images",
+        style("[3/3]").bold().dim(),
+        IMAGE_EMOJI
+    );
+
+    let pb = progress_bar_with_style(items.len() as u64);
+    let errors = Mutex::new(Vec::new());
+
+    items.par_iter().enumerate().for_each(|(index, traits)| {
+        let result = write_item(
+            assets_dir,
+            index,
+            traits,
+            &layers,
+            &generation,
+            &config_data,
+        );
+        pb.inc(1);
+
+        if let Err(err) = result {
+            error!("Failed to generate item {}: {:?}", index, err);
+            errors
+                .lock()
+                .unwrap()
+                .push(format!("Item {}: {}", index, err));
+        }
+    });
+
+    let mut errors = errors.into_inner().unwrap();
+
+    if !errors.is_empty() {
+        pb.abandon_with_message(format!("{}", style("Generation failed ").red().bold()));
+        errors.sort();
+
+        let mut message = format!("Failed to generate {} item(s):", errors.len());
+        for error in errors {
+            message.push_str("\n=> ");
+            message.push_str(&error);
+        }
+
+        return Err(anyhow!(message));
+    }
+
+    pb.finish_with_message(format!("{}", style("Generation successful").green().bold()));
+
+    case_println!(
+        "\n{} item(s) written to '{}', check the trait distribution with 'case rarity {}'.",
+        items.len(),
+        args.assets_dir,
+        args.assets_dir
+    );
+
+    Ok(GenerateOutcome {
+        assets_dir: args.assets_dir,
+        items: items.len(),
+        layers: layers.len(),
+        combinations,
+    })
+}
+
+/// Loads the trait images of each layer, sorted by value so that a seed always
+/// generates the same items.
+fn load_layers(generation: &Generation) -> Result<Vec<LoadedLayer>> {
+    if generation.layers.is_empty() {
+        return Err(anyhow!("The generation settings have no layers"));
+    }
+
+    let mut layers = Vec::new();
+
+    for layer in &generation.layers {
+        let dir = Path::new(&generation.layers_dir).join(&layer.name);
+        let entries = fs::read_dir(&dir)
+            .map_err(|err| anyhow!("Failed to read layer folder '{}': {}", dir.display(), err))?;
+        let mut traits = Vec::new();
+
+        for entry in entries {
+            let path = entry?.path();
+            let extension = path
+                .extension()
+                .and_then(OsStr::to_str)
+                .map(str::to_lowercase)
+                .unwrap_or_default();
+
+            if !path.is_file() || !LAYER_EXTENSIONS.contains(&extension.as_str()) {
+                continue;
+            }
+
+            let stem = path
+                .file_stem()
+                .and_then(OsStr::to_str)
+                .ok_or_else(|| anyhow!("Invalid trait file name '{}'", path.display()))?;
+            let (value, weight) = match stem.rsplit_once('#') {
+                Some((value, weight)) => (
+                    value.to_string(),
+                    weight.parse::<u32>().map_err(|_| {
+                        anyhow!("Invalid weight of trait file '{}'", path.display())
+                    })?,
+                ),
+                None => (stem.to_string(), 1),
+            };
+
+            traits.push(TraitImage {
+                value,
+                weight,
+                path,
+            });
+        }
+
+        for weight in &layer.weights {
+            let value = traits
+                .iter_mut()
+                .find(|image| image.value == weight.value)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Unknown trait '{}' in the weights of layer '{}'",
+                        weight.value,
+                        layer.name
+                    )
+                })?;
+            value.weight = weight.weight;
+        }
+
+        traits.retain(|image| image.weight > 0);
+        traits.sort_by(|a, b| a.value.cmp(&b.value));
+
+        if traits.is_empty() {
+            return Err(anyhow!(
+                "Layer '{}' has no trait images ({}) in '{}'",
+                layer.name,
+                LAYER_EXTENSIONS.join(", "),
+                dir.display()
+            ));
+        }
+
+        layers.push(LoadedLayer {
+            trait_type: layer
+                .trait_type
+                .clone()
+                .unwrap_or_else(|| layer.name.clone()),
+            traits,
+        });
+    }
+
+    Ok(layers)
+}
+
+fn resolve_incompatibilities(
+    generation: &Generation,
+    layers: &[LoadedLayer],
+) -> Result<Vec<Incompatibility>> {
+    let find = |layer_name: &str, value: &str| -> Result<(usize, usize)> {
+        let layer = generation
+            .layers
+            .iter()
+            .position(|layer| layer.name == layer_name)
+            .ok_or_else(|| anyhow!("Unknown layer '{}' in the incompatibilities", layer_name))?;
+        let image = layers[layer]
+            .traits
+            .iter()
+            .position(|image| image.value == value)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown trait '{}' of layer '{}' in the incompatibilities",
+                    value,
+                    layer_name
+                )
+            })?;
+
+        Ok((layer, image))
+    };
+
+    generation
+        .incompatibilities
+        .iter()
+        .map(|incompatibility| {
+            Ok((
+                find(&incompatibility.layer, &incompatibility.value)?,
+                find(&incompatibility.with_layer, &incompatibility.with_value)?,
+            ))
+        })
+        .collect()
+}
+
+/// Chooses a unique combination of traits (one trait index per layer) for each item,
+/// picking the traits of a layer in proportion to their weights.
+fn choose_traits(
+    layers: &[LoadedLayer],
+    incompatibilities: &[Incompatibility],
+    number: usize,
+    rng: &mut dyn Rng,
+) -> Result<Vec<Vec<usize>>> {
+    let mut items = Vec::with_capacity(number);
+    let mut seen = HashSet::new();
+    let mut attempts = 0;
+
+    while items.len() < number && attempts < number * GENERATION_ATTEMPTS {
+        attempts += 1;
+
+        let traits: Vec<usize> = layers
+            .iter()
+            .map(|layer| {
+                let total: u64 = layer.traits.iter().map(|image| image.weight as u64).sum();
+                let mut pick = rng.source().gen_range(0..total);
+
+                layer
+                    .traits
+                    .iter()
+                    .position(|image| {
+                        let found = pick < image.weight as u64;
+                        pick = pick.saturating_sub(image.weight as u64);
+                        found
+                    })
+                    .expect("Failed to pick a trait")
+            })
+            .collect();
+
+        let incompatible = incompatibilities
+            .iter()
+            .any(|((a, a_trait), (b, b_trait))| traits[*a] == *a_trait && traits[*b] == *b_trait);
+
+        if !incompatible && seen.insert(traits.clone()) {
+            items.push(traits);
+        }
+    }
+
+    if items.len() < number {
+        return Err(anyhow!(
+            "Only {} unique combination(s) of compatible traits were found for {} item(s), \
+             add traits or generate fewer items",
+            items.len(),
+            number
+        ));
+    }
+
+    Ok(items)
+}
+
+/// Composes the image of an item and writes it with its metadata, which is validated
+/// with the rules of the validate command.
+fn write_item(
+    assets_dir: &Path,
+    index: usize,
+    traits: &[usize],
+    layers: &[LoadedLayer],
+    generation: &Generation,
+    config_data: &ConfigData,
+) -> Result<()> {
+    let mut canvas: Option<RgbaImage> = None;
+
+    for (layer, trait_index) in layers.iter().zip(traits) {
+        let path = &layer.traits[*trait_index].path;
+        let image = image::open(path)
+            .map_err(|err| anyhow!("Failed to read trait image '{}': {}", path.display(), err))?
+            .to_rgba8();
+
+        match canvas.as_mut() {
+            Some(canvas) if canvas.dimensions() != image.dimensions() => {
+                return Err(anyhow!(
+                    "Trait image '{}' is {}x{}, expected {}x{} like the other layers",
+                    path.display(),
+                    image.width(),
+                    image.height(),
+                    canvas.width(),
+                    canvas.height()
+                ));
+            }
+            Some(canvas) => imageops::overlay(canvas, &image, 0, 0),
+            None => canvas = Some(image),
+        }
+    }
+
+    let image_name = format!("{}.png", index);
+    canvas
+        .expect("Failed to compose the image")
+        .save_with_format(assets_dir.join(&image_name), image::ImageFormat::Png)?;
+
+    let metadata = Metadata {
+        name: format!("{} #{}", generation.name, index + 1),
+        symbol: config_data.symbol.clone(),
+        description: generation.description.clone(),
+        seller_fee_basis_points: Some(config_data.seller_fee_basis_points),
+        image: image_name.clone(),
+        animation_url: None,
+        external_url: generation.external_url.clone(),
+        attributes: layers
+            .iter()
+            .zip(traits)
+            .map(|(layer, trait_index)| Attribute {
+                trait_type: layer.trait_type.clone(),
+                value: layer.traits[*trait_index].value.clone(),
+            })
+            .collect(),
+        properties: Property {
+            files: vec![FileAttr {
+                uri: image_name,
+                file_type: "image/png".to_string(),
+            }],
+            category: Some("image".to_string()),
+            creators: Some(
+                config_data
+                    .creators
+                    .iter()
+                    .map(|creator| MetadataCreator {
+                        address: creator.address.to_string(),
+                        share: creator.share as u16,
+                    })
+                    .collect(),
+            ),
+        },
+    };
+
+    let metadata_path = assets_dir.join(format!("{}.json", index));
+    fs::write(&metadata_path, to_canonical_json(&metadata)?)?;
+
+    validate_metadata_file(&metadata_path).map_err(|errors| {
+        let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        anyhow!("invalid metadata: {}", errors.join(" "))
+    })
+}
@@ -0,0 +1,119 @@
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use chrono::Utc;
+use console::style;
+use serde::Serialize;
+
+use crate::common::*;
+
+/// Time to wait for the request of a health check.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Status of a long-running command reported by the health endpoint.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthStatus {
+    pub command: String,
+    pub started_at: String,
+    /// Time of the last successful action.
+    pub last_action: Option<String>,
+    pub actions: u64,
+    pub errors: u64,
+    /// Errors since the last successful action; the endpoint responds with 503 while
+    /// this is not zero.
+    pub consecutive_errors: u64,
+    pub last_error: Option<String>,
+}
+
+/// Health endpoint of a long-running command, served over HTTP on a background thread
+/// so that it can be supervised with standard tooling.
+#[derive(Clone)]
+pub struct Health {
+    status: Arc<Mutex<HealthStatus>>,
+}
+
+impl Health {
+    /// Serves the status of the command on every interface at `port`.
+    pub fn serve(command: &str, port: u16) -> Result<Health> {
+        let listener = TcpListener::bind(("0.0.0.0", port)).map_err(|err| {
+            anyhow!(
+                "Failed to start the health endpoint on port {}: {}",
+                port,
+                err
+            )
+        })?;
+
+        let health = Health {
+            status: Arc::new(Mutex::new(HealthStatus {
+                command: command.to_string(),
+                started_at: Utc::now().to_rfc3339(),
+                ..Default::default()
+            })),
+        };
+
+        let status = Arc::clone(&health.status);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let status = status.lock().unwrap().clone();
+
+                if let Err(err) = respond(stream, &status) {
+                    debug!("Failed to respond to a health check: {}", err);
+                }
+            }
+        });
+
+        info!("Health endpoint listening on port {}", port);
+        case_println!(
+            "{} http://0.0.0.0:{}/health",
+            style("Health endpoint:").bold(),
+            port
+        );
+
+        Ok(health)
+    }
+
+    pub fn record_action(&self) {
+        let mut status = self.status.lock().unwrap();
+        status.last_action = Some(Utc::now().to_rfc3339());
+        status.actions += 1;
+        status.consecutive_errors = 0;
+    }
+
+    pub fn record_error(&self, error: &anyhow::Error) {
+        let mut status = self.status.lock().unwrap();
+        status.errors += 1;
+        status.consecutive_errors += 1;
+        status.last_error = Some(error.to_string());
+    }
+}
+
+/// Writes the status as JSON, whatever the path of the request.
+fn respond(mut stream: TcpStream, status: &HealthStatus) -> Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    // only the start of the request is read, the response is the same for every path
+    let mut request = [0u8; 1024];
+    let _ = stream.read(&mut request)?;
+
+    let body = serde_json::to_string(status)?;
+    let code = if status.consecutive_errors == 0 {
+        "200 OK"
+    } else {
+        "503 Service Unavailable"
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        code,
+        body.len(),
+        body
+    )?;
+
+    Ok(stream.flush()?)
+}
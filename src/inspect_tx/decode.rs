@@ -0,0 +1,281 @@
+use anchor_client::solana_sdk::{
+    hash::hash, program_utils::limited_deserialize, pubkey::Pubkey,
+    system_instruction::SystemInstruction, system_program,
+};
+use serde::Serialize;
+use spl_token::instruction::TokenInstruction;
+
+use crate::{inspect_tx::errors::InspectTxError, tars::TARS_ID};
+
+/// Tars instructions: (instruction name, account names in the order expected
+/// by the program).
+const TARS_INSTRUCTIONS: &[(&str, &[&str])] = &[
+    (
+        "initialize_tars",
+        &[
+            "tars",
+            "wallet",
+            "authority",
+            "payer",
+            "system_program",
+            "rent",
+        ],
+    ),
+    ("update_tars", &["tars", "authority", "wallet"]),
+    ("update_authority", &["tars", "authority", "wallet"]),
+    ("add_config_lines", &["tars", "authority"]),
+    (
+        "mint_nft",
+        &[
+            "tars",
+            "tars_creator",
+            "payer",
+            "wallet",
+            "metadata",
+            "mint",
+            "mint_authority",
+            "update_authority",
+            "master_edition",
+            "token_metadata_program",
+            "token_program",
+            "system_program",
+            "rent",
+            "clock",
+            "recent_blockhashes",
+            "instruction_sysvar_account",
+        ],
+    ),
+    ("withdraw_funds", &["tars", "authority"]),
+    (
+        "set_collection",
+        &[
+            "tars",
+            "authority",
+            "collection_pda",
+            "payer",
+            "system_program",
+            "rent",
+            "metadata",
+            "mint",
+            "edition",
+            "collection_authority_record",
+            "token_metadata_program",
+        ],
+    ),
+    (
+        "remove_collection",
+        &[
+            "tars",
+            "authority",
+            "collection_pda",
+            "metadata",
+            "mint",
+            "collection_authority_record",
+            "token_metadata_program",
+        ],
+    ),
+    (
+        "set_collection_during_mint",
+        &[
+            "tars",
+            "metadata",
+            "payer",
+            "collection_pda",
+            "token_metadata_program",
+            "instructions",
+            "collection_mint",
+            "collection_metadata",
+            "collection_master_edition",
+            "authority",
+            "collection_authority_record",
+        ],
+    ),
+];
+
+/// Token Metadata instructions, indexed by their (borsh) enum variant.
+const METADATA_INSTRUCTIONS: &[&str] = &[
+    "CreateMetadataAccount",
+    "UpdateMetadataAccount",
+    "DeprecatedCreateMasterEdition",
+    "DeprecatedMintNewEditionFromMasterEditionViaPrintingToken",
+    "UpdatePrimarySaleHappenedViaToken",
+    "DeprecatedSetReservationList",
+    "DeprecatedCreateReservationList",
+    "SignMetadata",
+    "DeprecatedMintPrintingTokensViaToken",
+    "DeprecatedMintPrintingTokens",
+    "CreateMasterEdition",
+    "MintNewEditionFromMasterEditionViaToken",
+    "ConvertMasterEditionV1ToV2",
+    "MintNewEditionFromMasterEditionViaVaultProxy",
+    "PuffMetadata",
+    "UpdateMetadataAccountV2",
+    "CreateMetadataAccountV2",
+    "CreateMasterEditionV3",
+    "VerifyCollection",
+    "Utilize",
+    "ApproveUseAuthority",
+    "RevokeUseAuthority",
+    "UnverifyCollection",
+    "ApproveCollectionAuthority",
+    "RevokeCollectionAuthority",
+    "SetAndVerifyCollection",
+    "FreezeDelegatedAccount",
+    "ThawDelegatedAccount",
+    "RemoveCreatorVerification",
+];
+
+#[derive(Debug, Serialize)]
+pub struct DecodedAccount {
+    pub name: String,
+    pub address: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedInstruction {
+    pub program: String,
+    pub program_id: String,
+    pub name: String,
+    /// Arguments of the instruction, for the programs whose arguments are decoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub args: Option<String>,
+    pub accounts: Vec<DecodedAccount>,
+}
+
+/// Returns the anchor discriminator of an instruction.
+fn sighash(name: &str) -> [u8; 8] {
+    let mut sighash = [0u8; 8];
+    sighash.copy_from_slice(&hash(format!("global:{}", name).as_bytes()).to_bytes()[..8]);
+    sighash
+}
+
+/// Converts a snake case name into camel case.
+fn to_camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Extracts the variant name from the debug representation of an enum value.
+fn variant_name<T: std::fmt::Debug>(value: &T) -> String {
+    format!("{:?}", value)
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn positional_accounts(accounts: &[Pubkey], names: &[&str]) -> Vec<DecodedAccount> {
+    accounts
+        .iter()
+        .enumerate()
+        .map(|(index, address)| DecodedAccount {
+            name: match names.get(index) {
+                Some(name) => name.to_string(),
+                None if names.is_empty() => format!("account #{}", index + 1),
+                None => format!("remaining #{}", index - names.len() + 1),
+            },
+            address: address.to_string(),
+        })
+        .collect()
+}
+
+/// Decodes an instruction of a known program (tars, token metadata, token,
+/// associated token and system programs).
+pub fn decode_instruction(
+    program_id: &Pubkey,
+    accounts: &[Pubkey],
+    data: &[u8],
+) -> DecodedInstruction {
+    let mut args = None;
+    let (program, name, names): (&str, String, &[&str]) = if *program_id == TARS_ID {
+        let instruction = if data.len() >= 8 {
+            TARS_INSTRUCTIONS
+                .iter()
+                .find(|(name, _)| sighash(name) == data[..8])
+        } else {
+            None
+        };
+
+        match instruction {
+            Some((name, names)) => ("Tars Program", to_camel_case(name), *names),
+            None => ("Tars Program", "Unknown".to_string(), &[]),
+        }
+    } else if *program_id == mpl_token_metadata::ID {
+        let name = data
+            .first()
+            .and_then(|index| METADATA_INSTRUCTIONS.get(*index as usize))
+            .unwrap_or(&"Unknown");
+        ("Token Metadata Program", name.to_string(), &[])
+    } else if *program_id == spl_token::id() {
+        let name = match TokenInstruction::unpack(data) {
+            Ok(instruction) => {
+                args = Some(format!("{:?}", instruction));
+                variant_name(&instruction)
+            }
+            Err(_) => "Unknown".to_string(),
+        };
+        ("Token Program", name, &[])
+    } else if *program_id == spl_associated_token_account::id() {
+        (
+            "Associated Token Account Program",
+            "Create".to_string(),
+            &[],
+        )
+    } else if *program_id == system_program::id() {
+        let name = match limited_deserialize::<SystemInstruction>(data) {
+            Ok(instruction) => {
+                args = Some(format!("{:?}", instruction));
+                variant_name(&instruction)
+            }
+            Err(_) => "Unknown".to_string(),
+        };
+        ("System Program", name, &[])
+    } else {
+        ("Unknown Program", "Unknown".to_string(), &[])
+    };
+
+    DecodedInstruction {
+        program: program.to_string(),
+        program_id: program_id.to_string(),
+        name,
+        args,
+        accounts: positional_accounts(accounts, names),
+    }
+}
+
+/// Decodes an instruction compiled in a transaction message, whose program and accounts
+/// are indices into the accounts of the message.
+pub fn decode_compiled_instruction(
+    account_keys: &[Pubkey],
+    program_id_index: u8,
+    accounts: &[u8],
+    data: &[u8],
+) -> Result<DecodedInstruction, InspectTxError> {
+    let account_key = |index: u8| {
+        account_keys
+            .get(index as usize)
+            .copied()
+            .ok_or(InspectTxError::InvalidAccountIndex(
+                index,
+                account_keys.len(),
+            ))
+    };
+    let accounts = accounts
+        .iter()
+        .map(|index| account_key(*index))
+        .collect::<Result<Vec<Pubkey>, _>>()?;
+
+    Ok(decode_instruction(
+        &account_key(program_id_index)?,
+        &accounts,
+        data,
+    ))
+}
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum InspectTxError {
+    #[error("Invalid transaction signature: {0}")]
+    InvalidSignature(String),
+    #[error("Failed to fetch transaction {0}: {1}")]
+    FetchFailed(String, String),
+    #[error("Failed to decode transaction {0}")]
+    DecodeFailed(String),
+    #[error("Invalid account index {0}: the transaction has {1} account(s)")]
+    InvalidAccountIndex(u8, usize),
+}
@@ -0,0 +1,7 @@
+pub mod decode;
+pub mod errors;
+pub mod process;
+
+pub use decode::*;
+pub use errors::*;
+pub use process::*;
@@ -0,0 +1,243 @@
+use anchor_client::solana_sdk::{
+    instruction::InstructionError, native_token::LAMPORTS_PER_SOL, transaction::TransactionError,
+};
+use chrono::NaiveDateTime;
+use console::style;
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_transaction_status::{UiInstruction, UiTransactionEncoding};
+
+use crate::{
+    common::*,
+    inspect_tx::{decode_compiled_instruction, errors::InspectTxError, DecodedInstruction},
+    parse::find_external_program_error,
+    utils::*,
+};
+
+pub struct InspectTxArgs {
+    pub signature: String,
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InspectedInstruction {
+    #[serde(flatten)]
+    pub instruction: DecodedInstruction,
+    pub inner: Vec<DecodedInstruction>,
+}
+
+/// Result of the inspect-tx command.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectTxOutcome {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub fee: u64,
+    pub error: Option<String>,
+    pub instructions: Vec<InspectedInstruction>,
+    pub logs: Vec<String>,
+}
+
+pub fn process_inspect_tx(args: InspectTxArgs) -> Result<InspectTxOutcome> {
+    let signature = Signature::from_str(&args.signature)
+        .map_err(|_| InspectTxError::InvalidSignature(args.signature.clone()))?;
+
+    case_println!(
+        "{} {}Fetching transaction",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let rpc_client =
+        RpcClient::new_with_commitment(case_config.rpc_url.clone(), CommitmentConfig::confirmed());
+
+    let confirmed = rpc_client
+        .get_transaction(&signature, UiTransactionEncoding::Base64)
+        .map_err(|err| InspectTxError::FetchFailed(args.signature.clone(), err.to_string()))?;
+
+    pb.finish_and_clear();
+
+    case_println!(
+        "\n{} {}Decoding instructions",
+        style("[2/2]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    let transaction = confirmed
+        .transaction
+        .transaction
+        .decode()
+        .ok_or_else(|| InspectTxError::DecodeFailed(args.signature.clone()))?;
+    let meta = confirmed.transaction.meta;
+    let account_keys = &transaction.message.account_keys;
+
+    // instructions are reported as indices into the transaction accounts
+    let resolve = |program_id_index: u8, accounts: &[u8], data: &[u8]| {
+        decode_compiled_instruction(account_keys, program_id_index, accounts, data)
+    };
+
+    let mut instructions: Vec<InspectedInstruction> = transaction
+        .message
+        .instructions
+        .iter()
+        .map(|instruction| {
+            Ok(InspectedInstruction {
+                instruction: resolve(
+                    instruction.program_id_index,
+                    &instruction.accounts,
+                    &instruction.data,
+                )?,
+                inner: Vec::new(),
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    let mut outcome = InspectTxOutcome {
+        signature: args.signature,
+        slot: confirmed.slot,
+        block_time: confirmed.block_time,
+        fee: 0,
+        error: None,
+        instructions: Vec::new(),
+        logs: Vec::new(),
+    };
+
+    if let Some(meta) = meta {
+        outcome.fee = meta.fee;
+        outcome.error = meta.err.as_ref().map(describe_error);
+        outcome.logs = meta.log_messages.unwrap_or_default();
+
+        for inner in meta.inner_instructions.unwrap_or_default() {
+            if let Some(parent) = instructions.get_mut(inner.index as usize) {
+                for instruction in inner.instructions {
+                    if let UiInstruction::Compiled(compiled) = instruction {
+                        let data = bs58::decode(&compiled.data).into_vec()?;
+                        parent.inner.push(resolve(
+                            compiled.program_id_index,
+                            &compiled.accounts,
+                            &data,
+                        )?);
+                    }
+                }
+            }
+        }
+    }
+
+    outcome.instructions = instructions;
+
+    print_outcome(&outcome);
+
+    Ok(outcome)
+}
+
+fn describe_error(error: &TransactionError) -> String {
+    match error {
+        TransactionError::InstructionError(index, InstructionError::Custom(code)) => format!(
+            "Instruction #{} failed: {}",
+            index + 1,
+            find_external_program_error(format!("{:#x}", code))
+        ),
+        TransactionError::InstructionError(index, error) => {
+            format!("Instruction #{} failed: {}", index + 1, error)
+        }
+        _ => error.to_string(),
+    }
+}
+
+fn print_outcome(outcome: &InspectTxOutcome) {
+    case_println!(
+        "\n{}{} {}",
+        PAPER_EMOJI,
+        style("Signature:").dim(),
+        outcome.signature
+    );
+    case_println!(" {}", style(":").dim());
+    print_with_style("", "slot", outcome.slot.to_string());
+    print_with_style(
+        "",
+        "block time",
+        match outcome.block_time {
+            Some(time) => NaiveDateTime::from_timestamp(time, 0)
+                .format("%a %B %e %Y %H:%M:%S UTC")
+                .to_string(),
+            None => "unknown".to_string(),
+        },
+    );
+    print_with_style(
+        "",
+        "fee",
+        format!(
+            "◎ {} ({})",
+            outcome.fee as f64 / LAMPORTS_PER_SOL as f64,
+            outcome.fee
+        ),
+    );
+    print_with_style(
+        "",
+        "status",
+        match &outcome.error {
+            Some(error) => style(format!("failed ({})", error)).red().to_string(),
+            None => style("success").green().to_string(),
+        },
+    );
+
+    for (index, instruction) in outcome.instructions.iter().enumerate() {
+        print_instruction(&format!("#{}", index + 1), "", &instruction.instruction);
+
+        for (inner_index, inner) in instruction.inner.iter().enumerate() {
+            print_instruction(
+                &format!("#{}.{}", index + 1, inner_index + 1),
+                ":   ",
+                inner,
+            );
+        }
+    }
+
+    if !outcome.logs.is_empty() {
+        case_println!("\n{}", style("Program logs:").dim());
+        for log in &outcome.logs {
+            case_println!(" {} {}", style(":..").dim(), log);
+        }
+    }
+}
+
+/// Prints a decoded instruction: its program, name, arguments (when decoded) and
+/// accounts.
+pub fn print_instruction(label: &str, indent: &str, instruction: &DecodedInstruction) {
+    case_println!(" {}{}", style(indent).dim(), style(":").dim());
+    print_with_style(
+        indent,
+        &format!("instruction {}", label),
+        format!(
+            "{} {} {}",
+            style(&instruction.program).bold(),
+            style("::").dim(),
+            instruction.name
+        ),
+    );
+
+    let indent = format!("{}:   ", indent);
+
+    if let Some(args) = &instruction.args {
+        print_with_style(&indent, "args", args.clone());
+    }
+
+    for account in &instruction.accounts {
+        print_with_style(&indent, &account.name, account.address.clone());
+    }
+}
+
+fn print_with_style(indent: &str, key: &str, value: String) {
+    case_println!(
+        " {} {}",
+        style(format!("{}:.. {}:", indent, key)).dim(),
+        value
+    );
+}
@@ -0,0 +1,212 @@
+use std::{
+    env,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use reqwest::StatusCode;
+use serde::Serialize;
+use tokio::time::{sleep, Duration};
+
+use crate::common::*;
+
+/// Scheme of IPFS links.
+const IPFS_SCHEME: &str = "ipfs://";
+
+/// Number of times the list of gateways is tried before a download fails.
+const MAX_ROUNDS: u32 = 3;
+
+/// Delay between rounds; doubled after each round.
+const ROUND_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Time to wait for a gateway before trying the next one.
+const GATEWAY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Consecutive failures after which a gateway is considered unhealthy.
+const UNHEALTHY_FAILURES: u32 = 3;
+
+/// Returns `true` if the link is an `ipfs://` link.
+pub fn is_ipfs(uri: &str) -> bool {
+    uri.starts_with(IPFS_SCHEME)
+}
+
+/// Returns the path of an `ipfs://` link (`<cid>/<path>`); links in the
+/// `ipfs://ipfs/<cid>` form are also accepted.
+pub fn ipfs_path(uri: &str) -> Option<&str> {
+    let path = uri.strip_prefix(IPFS_SCHEME)?;
+    let path = path.strip_prefix("ipfs/").unwrap_or(path);
+
+    if path.is_empty() {
+        None
+    } else {
+        Some(path)
+    }
+}
+
+/// Health of a gateway.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayHealth {
+    pub url: String,
+    pub successes: u32,
+    pub failures: u32,
+    pub healthy: bool,
+}
+
+struct Gateway {
+    url: String,
+    successes: AtomicU32,
+    /// Consecutive failures, reset by a successful download.
+    failures: AtomicU32,
+    total_failures: AtomicU32,
+}
+
+impl Gateway {
+    fn is_healthy(&self) -> bool {
+        self.failures.load(Ordering::SeqCst) < UNHEALTHY_FAILURES
+    }
+}
+
+/// List of IPFS gateways used to download `ipfs://` links. Downloads start with the
+/// healthiest gateway and move to the next one when a gateway fails, so a single flaky
+/// gateway only slows down the checks.
+pub struct IpfsGateways {
+    gateways: Vec<Gateway>,
+}
+
+impl IpfsGateways {
+    /// Uses the gateways specified, the ones of the `CASE_IPFS_GATEWAYS` environment
+    /// variable (comma-separated) or the default list, in this order of precedence.
+    pub fn from_options(gateways: &[String]) -> Result<Self> {
+        if !gateways.is_empty() {
+            return IpfsGateways::new(gateways);
+        }
+
+        match env::var(IPFS_GATEWAYS_ENV) {
+            Ok(value) if !value.trim().is_empty() => {
+                let gateways: Vec<String> = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|gateway| !gateway.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                IpfsGateways::new(&gateways)
+                    .map_err(|err| anyhow!("Invalid {}: {}", IPFS_GATEWAYS_ENV, err))
+            }
+            _ => IpfsGateways::new(
+                &DEFAULT_IPFS_GATEWAYS
+                    .iter()
+                    .map(|gateway| gateway.to_string())
+                    .collect::<Vec<String>>(),
+            ),
+        }
+    }
+
+    /// Gateways are base URLs (e.g. "https://ipfs.io"); a trailing "/ipfs" is ignored.
+    pub fn new(gateways: &[String]) -> Result<Self> {
+        if gateways.is_empty() {
+            return Err(anyhow!("No IPFS gateway specified"));
+        }
+
+        let mut list: Vec<Gateway> = Vec::new();
+
+        for gateway in gateways {
+            if !(gateway.starts_with("https://") || gateway.starts_with("http://")) {
+                return Err(anyhow!("Invalid IPFS gateway URL: {}", gateway));
+            }
+
+            let url = gateway.trim_end_matches('/');
+            let url = url.strip_suffix("/ipfs").unwrap_or(url).to_string();
+
+            if !list.iter().any(|existing| existing.url == url) {
+                list.push(Gateway {
+                    url,
+                    successes: AtomicU32::new(0),
+                    failures: AtomicU32::new(0),
+                    total_failures: AtomicU32::new(0),
+                });
+            }
+        }
+
+        Ok(IpfsGateways { gateways: list })
+    }
+
+    /// Returns the gateways ordered by their consecutive failures; gateways with the
+    /// same number keep the order of the list.
+    fn ordered(&self) -> Vec<&Gateway> {
+        let mut gateways: Vec<&Gateway> = self.gateways.iter().collect();
+        gateways.sort_by_key(|gateway| gateway.failures.load(Ordering::SeqCst));
+        gateways
+    }
+
+    /// Returns the HTTP URL of an `ipfs://` link through the healthiest gateway.
+    pub fn gateway_url(&self, uri: &str) -> Option<String> {
+        let path = ipfs_path(uri)?;
+        let gateway = self.ordered()[0];
+        Some(format!("{}/ipfs/{}", gateway.url, path))
+    }
+
+    /// Downloads an `ipfs://` link, trying the gateways from the healthiest one.
+    pub async fn download(&self, client: &HttpClient, uri: &str) -> Result<Vec<u8>> {
+        let path = ipfs_path(uri).ok_or_else(|| anyhow!("Invalid IPFS link: {}", uri))?;
+        let mut delay = ROUND_BACKOFF;
+        let mut last_error = String::new();
+
+        for round in 1..=MAX_ROUNDS {
+            for gateway in self.ordered() {
+                let url = format!("{}/ipfs/{}", gateway.url, path);
+
+                let error = match client.get(&url).timeout(GATEWAY_TIMEOUT).send().await {
+                    Ok(response) if response.status().is_success() => {
+                        match response.bytes().await {
+                            Ok(content) => {
+                                gateway.successes.fetch_add(1, Ordering::SeqCst);
+                                gateway.failures.store(0, Ordering::SeqCst);
+                                return Ok(content.to_vec());
+                            }
+                            Err(err) => Some(err.to_string()),
+                        }
+                    }
+                    // the content was not found, which does not make the gateway unhealthy
+                    Ok(response) if response.status() == StatusCode::NOT_FOUND => {
+                        last_error = format!("{}: {}", gateway.url, response.status());
+                        None
+                    }
+                    Ok(response) => Some(response.status().to_string()),
+                    Err(err) => Some(err.to_string()),
+                };
+
+                if let Some(error) = error {
+                    gateway.failures.fetch_add(1, Ordering::SeqCst);
+                    gateway.total_failures.fetch_add(1, Ordering::SeqCst);
+                    warn!("IPFS gateway {} failed for {}: {}", gateway.url, uri, error);
+                    last_error = format!("{}: {}", gateway.url, error);
+                }
+            }
+
+            if round < MAX_ROUNDS {
+                sleep(delay).await;
+                delay *= 2;
+            }
+        }
+
+        Err(anyhow!(
+            "Failed to download {} from {} IPFS gateway(s) (last error: {})",
+            uri,
+            self.gateways.len(),
+            last_error
+        ))
+    }
+
+    /// Returns the health of each gateway.
+    pub fn health(&self) -> Vec<GatewayHealth> {
+        self.gateways
+            .iter()
+            .map(|gateway| GatewayHealth {
+                url: gateway.url.clone(),
+                successes: gateway.successes.load(Ordering::SeqCst),
+                failures: gateway.total_failures.load(Ordering::SeqCst),
+                healthy: gateway.is_healthy(),
+            })
+            .collect()
+    }
+}
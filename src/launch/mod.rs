@@ -1,3 +1,5 @@
 pub mod process;
+pub mod state;
 
 pub use process::*;
+pub use state::*;
@@ -1,17 +1,30 @@
-use std::sync::{atomic::AtomicBool, Arc};
+use std::{
+    path::Path,
+    sync::{atomic::AtomicBool, Arc},
+};
 
 use anyhow::Result;
+use chrono::Utc;
 use console::{style, Style};
 use dialoguer::{theme::ColorfulTheme, Confirm};
+use serde::Serialize;
 
 use crate::{
-    common::LAUNCH_EMOJI,
-    config::parser::get_config_data,
+    cache::{load_cache, load_cluster_cache},
+    case_println,
+    common::{
+        case_setup, setup_client, CONFIG_LINES_BATCH_SIZE, DEFAULT_MAX_BUFFER_MB,
+        DEFAULT_MAX_OPEN_FILES, LAUNCH_EMOJI, PARALLEL_LIMIT,
+    },
+    config::{data::CaseConfig, parser::get_config_data},
     create_config::{process_create_config, CreateConfigArgs},
-    deploy::{process_deploy, DeployArgs},
-    upload::{process_upload, UploadArgs},
+    deploy::{process_deploy, DeployArgs, DeployOutcome},
+    launch::{LaunchCheckpoint, LaunchState, LaunchStep},
+    tars::TARS_ID,
+    upload::{process_upload, UploadArgs, UploadOutcome},
+    utils::check_token_metadata_program,
     validate::{process_validate, ValidateArgs},
-    verify::{process_verify, VerifyArgs},
+    verify::{process_verify, VerifyArgs, VerifyOutcome},
 };
 
 pub struct LaunchArgs {
@@ -19,86 +32,231 @@ pub struct LaunchArgs {
     pub config: String,
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
     pub cache: String,
     pub strict: bool,
     pub skip_collection_prompt: bool,
+    /// Leave the collection NFT to a later deploy.
+    pub skip_collection: bool,
     pub interrupted: Arc<AtomicBool>,
+    /// Step to restart the launch from, running it again even if it was completed.
+    pub from_step: Option<LaunchStep>,
+}
+
+/// Result of the launch command (steps skipped have no outcome).
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchOutcome {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upload: Option<UploadOutcome>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deploy: Option<DeployOutcome>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<DeployOutcome>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verify: Option<VerifyOutcome>,
+    /// Steps completed by a previous launch.
+    pub skipped: Vec<LaunchStep>,
 }
 
-pub async fn process_launch(args: LaunchArgs) -> Result<()> {
-    println!("Starting Case launch... {}", LAUNCH_EMOJI);
+pub async fn process_launch(args: LaunchArgs) -> Result<LaunchOutcome> {
+    case_println!("Starting Case launch... {}", LAUNCH_EMOJI);
 
     let theme = ColorfulTheme {
         prompt_style: Style::new(),
         ..Default::default()
     };
 
-    if let Err(err) = get_config_data(&args.config) {
+    if let Err(err) = get_config_data(&args.config, &args.global.address_book) {
         // padding
-        println!();
+        case_println!();
         if Confirm::with_theme(&theme)
             .with_prompt("Could not load config file. Would you like to create a new config file?")
             .interact()?
         {
-            println!("\n{} case create-config\n", style(">>>").magenta());
+            case_println!("\n{} case create-config\n", style(">>>").magenta());
 
             let create_config_args = CreateConfigArgs {
                 config: Some(args.config.clone()),
                 keypair: args.keypair.clone(),
                 rpc_url: args.rpc_url.clone(),
+                global: args.global.clone(),
                 assets_dir: args.assets_dir.clone(),
+                format: None,
             };
 
-            process_create_config(create_config_args)?;
+            process_create_config(create_config_args).await?;
         } else {
             return Err(err.into());
         }
     }
 
-    println!("\n{} case validate\n", style(">>>").magenta());
+    case_println!("\n{} case validate\n", style(">>>").magenta());
 
     let validate_args = ValidateArgs {
         assets_dir: args.assets_dir.clone(),
+        config: Some(args.config.clone()),
+        global: args.global.clone(),
         strict: args.strict,
         skip_collection_prompt: args.skip_collection_prompt,
+        watch: false,
+        fix: false,
+        allow_duplicates: false,
     };
 
     process_validate(validate_args)?;
 
-    println!("\n{} case upload\n", style(">>>").magenta());
+    let case_config = case_setup(args.keypair.clone(), args.rpc_url.clone(), &args.global)?;
 
-    let upload_args = UploadArgs {
-        assets_dir: args.assets_dir.clone(),
-        config: args.config.clone(),
-        keypair: args.keypair.clone(),
-        rpc_url: args.rpc_url.clone(),
-        cache: args.cache.clone(),
-        interrupted: args.interrupted.clone(),
+    // steps completed by a previous launch are skipped, unless restarting from a step
+    let mut state = if Path::new(&args.cache).exists() {
+        load_cluster_cache(&case_config, &args.cache, false)?
+            .launch
+            .unwrap_or_default()
+    } else {
+        LaunchState::default()
     };
 
-    process_upload(upload_args).await?;
+    if let Some(step) = args.from_step {
+        state.reset_from(step);
+    }
+
+    let mut outcome = LaunchOutcome::default();
 
-    println!("\n{} case deploy\n", style(">>>").magenta());
+    for step in LaunchStep::ALL {
+        if state.is_completed(step) {
+            case_println!(
+                "\n{} case {} {}",
+                style(">>>").magenta(),
+                step.name(),
+                style("(completed by a previous launch, skipping)").dim()
+            );
+            outcome.skipped.push(step);
+            continue;
+        }
 
-    let deploy_args = DeployArgs {
-        config: args.config.clone(),
-        keypair: args.keypair.clone(),
-        rpc_url: args.rpc_url.clone(),
-        cache: args.cache.clone(),
-        interrupted: args.interrupted.clone(),
-    };
+        // not recorded as completed, so that a later launch creates the collection
+        if step == LaunchStep::Collection && args.skip_collection {
+            case_println!(
+                "\n{} case deploy {}",
+                style(">>>").magenta(),
+                style("(collection skipped with --skip-collection)").dim()
+            );
+            continue;
+        }
 
-    process_deploy(deploy_args).await?;
+        let signatures = match step {
+            LaunchStep::Upload => {
+                case_println!("\n{} case upload\n", style(">>>").magenta());
 
-    println!("\n{} case verify\n", style(">>>").magenta());
+                let upload_args = UploadArgs {
+                    assets_dir: args.assets_dir.clone(),
+                    config: args.config.clone(),
+                    keypair: args.keypair.clone(),
+                    rpc_url: args.rpc_url.clone(),
+                    global: args.global.clone(),
+                    cache: args.cache.clone(),
+                    interrupted: args.interrupted.clone(),
+                    inject_metadata: false,
+                    concurrency: PARALLEL_LIMIT,
+                    assets_url: None,
+                    auto_fund: true,
+                    max_open_files: DEFAULT_MAX_OPEN_FILES,
+                    max_buffer_mb: DEFAULT_MAX_BUFFER_MB,
+                    verify_storage: false,
+                    verify_full: false,
+                    // already reported by the validation
+                    allow_duplicates: true,
+                };
 
-    let verify_args = VerifyArgs {
-        keypair: args.keypair.clone(),
-        rpc_url: args.rpc_url.clone(),
-        cache: args.cache.clone(),
-    };
+                outcome.upload = Some(process_upload(upload_args).await?);
+                Vec::new()
+            }
+            LaunchStep::Deploy | LaunchStep::Collection => {
+                case_println!("\n{} case deploy\n", style(">>>").magenta());
 
-    process_verify(verify_args)?;
+                // the collection NFT needs the token metadata program, checked before the
+                // tars is created so that the launch does not stop half-way
+                if step == LaunchStep::Deploy
+                    && !args.skip_collection
+                    && !state.is_completed(LaunchStep::Collection)
+                    && load_cache(&args.cache, false)?.items.contains_key("-1")
+                {
+                    check_token_metadata_program(
+                        &setup_client(&case_config)?.program(TARS_ID),
+                        "create and set the collection NFT",
+                        Some("--skip-collection"),
+                    )?;
+                }
+
+                // the collection NFT is created by a second deploy, once the config
+                // lines are on-chain
+                let deploy_args = DeployArgs {
+                    config: args.config.clone(),
+                    keypair: args.keypair.clone(),
+                    rpc_url: args.rpc_url.clone(),
+                    global: args.global.clone(),
+                    cache: args.cache.clone(),
+                    interrupted: args.interrupted.clone(),
+                    dry_run: false,
+                    max_fee: None,
+                    sign_offline: None,
+                    nonces: Vec::new(),
+                    batch_size: CONFIG_LINES_BATCH_SIZE,
+                    skip_collection: step == LaunchStep::Deploy,
+                    compute_units: None,
+                };
+
+                let deploy = process_deploy(deploy_args).await?;
+                let signatures = deploy.signatures.clone();
+
+                if step == LaunchStep::Deploy {
+                    outcome.deploy = Some(deploy);
+                } else {
+                    outcome.collection = Some(deploy);
+                }
+                signatures
+            }
+            LaunchStep::Verify => {
+                case_println!("\n{} case verify\n", style(">>>").magenta());
+
+                let verify_args = VerifyArgs {
+                    config: args.config.clone(),
+                    keypair: args.keypair.clone(),
+                    rpc_url: args.rpc_url.clone(),
+                    global: args.global.clone(),
+                    cache: args.cache.clone(),
+                    check_metadata: false,
+                    check_links: false,
+                    ipfs_gateways: Vec::new(),
+                    repair: false,
+                    metadata_standards: false,
+                };
+
+                outcome.verify = Some(process_verify(verify_args).await?);
+                Vec::new()
+            }
+        };
+
+        state.completed.push(LaunchCheckpoint {
+            step,
+            completed_at: Utc::now().to_rfc3339(),
+            signatures,
+        });
+        save_launch_state(&args.cache, &case_config, &state)?;
+    }
+
+    Ok(outcome)
+}
 
-    Ok(())
+/// Stores the launch state in the cache file, which the steps update in between.
+fn save_launch_state(
+    cache_path: &str,
+    case_config: &CaseConfig,
+    state: &LaunchState,
+) -> Result<()> {
+    let mut cache = load_cache(cache_path, false)?;
+    cache.set_signer(&case_config.keypair)?;
+    cache.launch = Some(state.clone());
+    cache.sync_file()
 }
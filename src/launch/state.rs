@@ -0,0 +1,64 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Step of the launch, in the order they are run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LaunchStep {
+    /// Upload the assets and metadata.
+    Upload,
+    /// Create the tars and write its config lines.
+    Deploy,
+    /// Create and set the collection NFT.
+    Collection,
+    /// Verify the config lines on-chain.
+    Verify,
+}
+
+impl LaunchStep {
+    pub const ALL: [LaunchStep; 4] = [
+        LaunchStep::Upload,
+        LaunchStep::Deploy,
+        LaunchStep::Collection,
+        LaunchStep::Verify,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LaunchStep::Upload => "upload",
+            LaunchStep::Deploy => "deploy",
+            LaunchStep::Collection => "collection",
+            LaunchStep::Verify => "verify",
+        }
+    }
+}
+
+/// Step completed by a previous launch.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchCheckpoint {
+    pub step: LaunchStep,
+    pub completed_at: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signatures: Vec<String>,
+}
+
+/// Progress of the launch, stored in the cache so that a launch that failed can be
+/// resumed from the step that failed.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct LaunchState {
+    pub completed: Vec<LaunchCheckpoint>,
+}
+
+impl LaunchState {
+    pub fn is_completed(&self, step: LaunchStep) -> bool {
+        self.completed
+            .iter()
+            .any(|checkpoint| checkpoint.step == step)
+    }
+
+    /// Forgets the checkpoints of `step` and the steps after it, so that they run again.
+    pub fn reset_from(&mut self, step: LaunchStep) {
+        self.completed.retain(|checkpoint| checkpoint.step < step);
+    }
+}
@@ -1,24 +1,63 @@
+pub mod address_book;
+pub mod api;
+pub mod backup;
+pub mod budget;
 pub mod bundlr;
 pub mod cache;
+pub mod canonical_json;
 pub mod tars;
 pub mod cli;
+pub mod clock;
 pub mod collections;
 pub mod common;
 pub mod config;
+pub mod config_lines;
 pub mod constants;
+pub mod crash_report;
 pub mod create_config;
+pub mod denylist;
+pub mod deployments;
 pub mod deploy;
+pub mod diff;
 pub mod errors;
+pub mod health;
+pub mod estimate;
+pub mod estimate_reveal;
+pub mod generate;
+pub mod inspect_tx;
+pub mod ipfs;
 pub mod launch;
+pub mod logs;
+pub mod metadata;
+pub mod metadata_cache;
+pub mod migrate_payment;
 pub mod mint;
+pub mod output;
 pub mod parse;
 pub mod pdas;
+pub mod process_images;
+pub mod profile;
 pub mod program_errors;
+pub mod rarity;
+pub mod rehearse;
+pub mod report;
+pub mod reserve;
+pub mod rng;
+pub mod role;
+pub mod sandbox;
+pub mod schedule;
+pub mod selftest;
 pub mod setup;
 pub mod show;
+pub mod shuffle;
+pub mod snapshot;
+pub mod tx;
 pub mod update;
 pub mod upload;
 pub mod utils;
 pub mod validate;
 pub mod verify;
+pub mod verify_config_hash;
+pub mod whitelist;
+pub mod whoami;
 pub mod withdraw;
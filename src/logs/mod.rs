@@ -0,0 +1,5 @@
+pub mod process;
+pub mod run;
+
+pub use process::*;
+pub use run::*;
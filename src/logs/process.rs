@@ -0,0 +1,123 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use console::style;
+use serde::Serialize;
+
+use crate::{common::*, logs::run_logs};
+
+pub struct LogsArgs {
+    pub lines: usize,
+    pub list: bool,
+    pub open: bool,
+    /// Log file of the current run, which is not listed.
+    pub run_log: Option<PathBuf>,
+}
+
+/// Result of the logs command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogsOutcome {
+    /// Log file of the latest run.
+    pub file: Option<String>,
+    /// Log files of previous runs (with --list).
+    pub files: Vec<String>,
+    /// Last lines of the log file of the latest run.
+    pub lines: Vec<String>,
+}
+
+pub fn process_logs(args: LogsArgs) -> Result<LogsOutcome> {
+    let logs = run_logs(args.run_log.as_deref())?;
+    let mut outcome = LogsOutcome::default();
+
+    if args.list {
+        for path in &logs {
+            let path = path_to_string(path)?;
+            case_println!("{}", path);
+            outcome.files.push(path);
+        }
+
+        return Ok(outcome);
+    }
+
+    let latest = match logs.last() {
+        Some(latest) => latest,
+        None => return Err(anyhow!("No log file found in {}", LOGS_DIR)),
+    };
+    outcome.file = Some(path_to_string(latest)?);
+
+    if args.open {
+        open_file(latest)?;
+        case_println!("{} {}", style("Opened:").bold(), latest.display());
+        return Ok(outcome);
+    }
+
+    let content = fs::read_to_string(latest)
+        .map_err(|err| anyhow!("Failed to read log file {}: {}", latest.display(), err))?;
+    let lines: Vec<&str> = content.lines().collect();
+
+    case_println!("{} {}\n", style("Log file:").bold(), latest.display());
+
+    for line in &lines[lines.len().saturating_sub(args.lines)..] {
+        let line = format_line(line);
+        case_println!("{}", line);
+        outcome.lines.push(line);
+    }
+
+    Ok(outcome)
+}
+
+/// Formats a (Bunyan) log line as "<time> <level> <message>", leaving lines that are
+/// not JSON as they are.
+fn format_line(line: &str) -> String {
+    let record: Value = match serde_json::from_str(line) {
+        Ok(record) => record,
+        Err(_) => return line.to_string(),
+    };
+
+    let level = match record["level"].as_u64() {
+        Some(level) if level >= 50 => "ERROR",
+        Some(level) if level >= 40 => "WARN",
+        Some(level) if level >= 30 => "INFO",
+        Some(level) if level >= 20 => "DEBUG",
+        _ => "TRACE",
+    };
+
+    format!(
+        "{} {:<5} {}",
+        record["time"].as_str().unwrap_or_default(),
+        level,
+        record["msg"].as_str().unwrap_or_default()
+    )
+}
+
+/// Opens the file with the default application of the OS.
+fn open_file(path: &Path) -> Result<()> {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+
+    let status = command
+        .arg(path)
+        .status()
+        .map_err(|err| anyhow!("Failed to open log file {}: {}", path.display(), err))?;
+
+    if !status.success() {
+        return Err(anyhow!(
+            "Failed to open log file {} ({})",
+            path.display(),
+            status
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    path::{Path, PathBuf},
+    process,
+};
+
+use chrono::Utc;
+
+use crate::common::*;
+
+/// Creates the log file of the current run in the logs directory, removing the oldest
+/// log files over the limit. Returns the file and its path.
+pub fn create_run_log() -> Result<(File, PathBuf)> {
+    fs::create_dir_all(LOGS_DIR)
+        .map_err(|err| anyhow!("Failed to create logs directory {}: {}", LOGS_DIR, err))?;
+
+    // timestamped (and unique per process) so that file names sort in run order
+    let name = format!(
+        "case-{}-{}.log",
+        Utc::now().format("%Y%m%dT%H%M%S%.3fZ"),
+        process::id()
+    );
+    let path = Path::new(LOGS_DIR).join(name);
+
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .map_err(|err| anyhow!("Failed to create log file {}: {}", path.display(), err))?;
+
+    prune_run_logs(&path);
+
+    Ok((file, path))
+}
+
+/// Returns the log files of previous runs, from the oldest to the latest. The log
+/// file of the current run is not included.
+pub fn run_logs(current: Option<&Path>) -> Result<Vec<PathBuf>> {
+    let mut logs: Vec<PathBuf> = match fs::read_dir(LOGS_DIR) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("log"))
+            .filter(|path| Some(path.as_path()) != current)
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    logs.sort();
+
+    Ok(logs)
+}
+
+fn prune_run_logs(current: &Path) {
+    if let Ok(logs) = run_logs(Some(current)) {
+        // the current run is not listed, so one less than the limit is kept
+        let excess = (logs.len() + 1).saturating_sub(MAX_RUN_LOGS);
+
+        for path in logs.iter().take(excess) {
+            if let Err(err) = fs::remove_file(path) {
+                warn!("Failed to remove log file {}: {}", path.display(), err);
+            }
+        }
+    }
+}
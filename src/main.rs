@@ -1,6 +1,5 @@
 use std::{
-    fs::OpenOptions,
-    path::PathBuf,
+    path::{Path, PathBuf},
     str::FromStr,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -9,41 +8,102 @@ use std::{
 };
 
 use anyhow::{anyhow, Result};
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use console::style;
+use serde_json::Value;
 use case_cli::{
+    backup::{process_export_backup, process_import_backup, ExportBackupArgs, ImportBackupArgs},
     bundlr::{process_bundlr, BundlrArgs},
-    cli::{Cli, CollectionSubcommands, Commands},
+    cache::{
+        process_check_cache, process_rebuild_cache, process_remove_cache_item,
+        process_repair_cache, process_restore_cache_item, process_sync_cache, CheckCacheArgs,
+        RebuildCacheArgs, RemoveCacheItemArgs, RepairCacheArgs, RestoreCacheItemArgs,
+        SyncCacheArgs,
+    },
+    cli::{
+        CacheSubcommands, Cli, CollectionSubcommands, Commands, DeploymentsSubcommands,
+        ExportSubcommands, ImportSubcommands, MetadataSubcommands, ProfileSubcommands,
+        ReportSubcommands, ScheduleSubcommands, ShuffleSubcommands, SnapshotSubcommands,
+        TxSubcommands, WhitelistSubcommands,
+    },
     collections::{
-        process_remove_collection, process_set_collection, RemoveCollectionArgs, SetCollectionArgs,
+        process_create_collection, process_migrate_collection, process_remove_collection,
+        process_set_collection, process_set_collection_batch, process_verify_items,
+        CreateCollectionArgs, MigrateCollectionArgs, RemoveCollectionArgs, SetCollectionArgs,
+        SetCollectionBatchArgs, VerifyItemsArgs,
     },
+    config::{get_config_data, GlobalOptions},
+    config_lines::{process_export_config_lines, ExportConfigLinesArgs},
     constants::{COMPLETE_EMOJI, ERROR_EMOJI},
+    crash_report::{enable_error_reports, write_error_report, ReportContext},
     create_config::{process_create_config, CreateConfigArgs},
+    deployments::{process_deployments_list, DeploymentsListArgs},
     deploy::{process_deploy, DeployArgs},
+    estimate::{process_estimate, EstimateArgs},
+    estimate_reveal::{process_estimate_reveal, EstimateRevealArgs},
+    generate::{process_generate, GenerateArgs},
+    inspect_tx::{process_inspect_tx, InspectTxArgs},
     launch::{process_launch, LaunchArgs},
+    logs::{create_run_log, process_logs, LogsArgs},
+    metadata::{process_mutate_metadata, MutateMetadataArgs},
+    migrate_payment::{process_migrate_payment, MigratePaymentArgs},
     mint::{process_mint, MintArgs},
+    output::{is_json_output, set_output_format, JsonOutput, OutputFormat},
     parse::parse_case_errors,
+    process_images::{process_process_images, ProcessImagesArgs},
+    profile::{
+        check_profile_name, find_active_profile, process_list_profiles, process_switch_profile,
+        with_profile_defaults, ListProfilesArgs, SwitchProfileArgs,
+    },
+    rarity::{process_rarity, RarityArgs},
+    rehearse::{process_rehearse_golive, RehearseGoliveArgs},
+    report::{process_report_progress, ReportProgressArgs},
+    reserve::{process_reserve, ReserveArgs},
+    role::{check_role, command_name, config_path},
+    sandbox::{process_sandbox, SandboxArgs},
+    schedule::{process_schedule_price, SchedulePriceArgs},
+    selftest::{process_selftest, SelftestArgs},
     show::{process_show, ShowArgs},
+    shuffle::{
+        process_shuffle_commit, process_shuffle_reveal, process_shuffle_verify, ShuffleCommitArgs,
+        ShuffleRevealArgs, ShuffleVerifyArgs,
+    },
+    snapshot::{
+        process_snapshot_holders, process_snapshot_mints, SnapshotHoldersArgs, SnapshotMintsArgs,
+    },
+    tx::{process_tx_send, process_tx_sign, TxSendArgs, TxSignArgs},
     update::{process_update, UpdateArgs},
     upload::{process_upload, UploadArgs},
     validate::{process_validate, ValidateArgs},
     verify::{process_verify, VerifyArgs},
+    verify_config_hash::{process_verify_config_hash, VerifyConfigHashArgs},
+    whoami::{process_whoami, WhoamiArgs},
+    whitelist::{
+        process_whitelist_airdrop, process_whitelist_create, process_whitelist_mint,
+        WhitelistAirdropArgs, WhitelistCreateArgs, WhitelistMintArgs,
+    },
     withdraw::{process_withdraw, WithdrawArgs},
 };
 use tracing::subscriber::set_global_default;
 use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
 use tracing_subscriber::{self, filter::LevelFilter, prelude::*, EnvFilter};
 
-fn setup_logging(level: Option<EnvFilter>) -> Result<()> {
-    // Log path; change this to be dynamic for multiple OSes.
-    // Log in current directory for now.
-    let log_path = PathBuf::from("case.log");
+/// Module path of the library crate, which the `case` log targets resolve to.
+const LOG_TARGET: &str = "case_cli";
+
+/// State of the run used to report a failed command.
+#[derive(Default)]
+struct RunContext {
+    /// Context of the error reports, when the user opted in to them.
+    report: Option<ReportContext>,
+    /// Log file of the run, once logging is set up.
+    log: Option<PathBuf>,
+}
 
-    let file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .open(&log_path)
-        .unwrap();
+/// Sets up the logging of the run to its own log file, returning the path of the file.
+fn setup_logging(level: Option<EnvFilter>) -> Result<PathBuf> {
+    // one log file per run, so that the log of a failed run can be found later
+    let (file, path) = create_run_log()?;
 
     // Prioritize user-provided level, otherwise read from RUST_LOG env var for log level, fall back to "tracing" if not set.
     let env_filter = if let Some(filter) = level {
@@ -53,63 +113,173 @@ fn setup_logging(level: Option<EnvFilter>) -> Result<()> {
     };
 
     let formatting_layer = BunyanFormattingLayer::new("case".into(), file);
-    let level_filter = LevelFilter::from_str(&env_filter.to_string())?;
 
     let subscriber = tracing_subscriber::registry()
-        .with(formatting_layer.with_filter(level_filter))
+        .with(env_filter)
+        .with(formatting_layer)
         .with(JsonStorageLayer);
 
     set_global_default(subscriber).expect("Failed to set global default subscriber");
 
-    Ok(())
+    Ok(path)
+}
+
+/// Parses the `--log-level` value, either a single level or a list of tracing
+/// filter directives (e.g. "case::upload=debug,solana_client=warn").
+///
+/// Directives for the `case` module are associated with the library crate, and
+/// when only module directives are given, everything else is logged at `warn`.
+fn log_filter(spec: &str) -> Result<EnvFilter> {
+    let mut directives: Vec<String> = spec
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .map(|directive| {
+            if directive == "case" || directive.starts_with("case=") {
+                format!("{}{}", LOG_TARGET, &directive[4..])
+            } else if let Some(module) = directive.strip_prefix("case::") {
+                format!("{}::{}", LOG_TARGET, module)
+            } else {
+                directive.to_string()
+            }
+        })
+        .collect();
+
+    if directives.is_empty() {
+        return Err(anyhow!("Log level cannot be empty"));
+    }
+
+    // a directive without a target (e.g. "debug") sets the default level
+    let has_default = directives
+        .iter()
+        .any(|directive| !directive.contains('=') && LevelFilter::from_str(directive).is_ok());
+
+    if !has_default {
+        directives.insert(0, "warn".to_string());
+    }
+
+    EnvFilter::try_new(directives.join(",")).map_err(|err| anyhow!(err))
 }
 
 #[tokio::main(worker_threads = 4)]
 async fn main() {
-    match run().await {
-        Ok(()) => {
-            println!(
-                "\n{}{}",
-                COMPLETE_EMOJI,
-                style("Command successful.").green().bold().dim()
-            );
+    let mut context = RunContext::default();
+
+    match run(&mut context).await {
+        Ok(result) => {
+            // the result holds the signatures of the transactions sent by the command
+            if let Some(result) = &result {
+                tracing::info!("Result: {}", result);
+            }
+
+            if is_json_output() {
+                JsonOutput::success(result).print();
+            } else {
+                println!(
+                    "\n{}{}",
+                    COMPLETE_EMOJI,
+                    style("Command successful.").green().bold().dim()
+                );
+            }
         }
         Err(err) => {
+            tracing::error!("{:?}", err);
             let parsed_err = parse_case_errors(&err.to_string());
+            let report = context
+                .report
+                .as_ref()
+                .and_then(|report| write_error_report(report, &err));
+
+            if is_json_output() {
+                JsonOutput::error(parsed_err).print();
+            } else {
+                println!(
+                    "\n{}{} {}",
+                    ERROR_EMOJI,
+                    style("Error running command (re-run needed):").red(),
+                    parsed_err,
+                );
 
-            println!(
-                "\n{}{} {}",
-                ERROR_EMOJI,
-                style("Error running command (re-run needed):").red(),
-                parsed_err,
-            );
+                if let Some(report) = report {
+                    println!(
+                        "\nDiagnostic report written to '{}'. Review it before attaching it to an issue.",
+                        report
+                    );
+                }
+
+                if let Some(log) = &context.log {
+                    println!("\nLog of this run written to '{}'.", log.display());
+                }
+            }
             // finished the program with an error code to the OS
             std::process::exit(1);
         }
     }
 }
 
-async fn run() -> Result<()> {
+async fn run(context: &mut RunContext) -> Result<Option<Value>> {
     solana_logger::setup_with_default("solana=off");
 
-    let cli = Cli::parse();
-
-    let log_level_error: Result<()> = Err(anyhow!(
-        "Invalid log level: {:?}.\n Valid levels are: trace, debug, info, warn, error.",
-        cli.log_level
-    ));
+    // the profile namespaces the default cache and config files of the commands, so it is
+    // read before the arguments are parsed
+    let profile = find_active_profile();
+    let command = match &profile {
+        Some(profile) => with_profile_defaults(Cli::command(), profile),
+        None => Cli::command(),
+    };
+    let cli = Cli::from_arg_matches(&command.get_matches()).unwrap_or_else(|err| err.exit());
 
     if let Some(user_filter) = cli.log_level {
-        let filter = match EnvFilter::from_str(&user_filter) {
-            Ok(filter) => filter,
-            Err(_) => return log_level_error,
-        };
-        setup_logging(Some(filter))?;
+        let filter = log_filter(&user_filter).map_err(|err| {
+            anyhow!(
+                "Invalid log level: {:?} ({}).\n Valid levels are: trace, debug, info, warn, error, \
+                 optionally per module (e.g. \"case::upload=debug,solana_client=warn\").",
+                user_filter,
+                err
+            )
+        })?;
+        context.log = Some(setup_logging(Some(filter))?);
     } else {
-        setup_logging(None)?;
+        context.log = Some(setup_logging(None)?);
     }
 
     tracing::info!("Lend me some case, I am your neighbor.");
+    tracing::info!("Running command: {}", command_name(&cli.command));
+
+    if let Some(profile) = &profile {
+        check_profile_name(profile)?;
+        tracing::info!("Using profile: {}", profile);
+    }
+
+    set_output_format(cli.output);
+
+    let mut global = GlobalOptions {
+        derivation_path: cli.derivation_path,
+        address_book: cli.address_book,
+        ws_url: cli.ws_url,
+        config_rpc_urls: Vec::new(),
+        force_cluster: cli.force_cluster,
+        profile,
+        insecure_keypair: cli.insecure_keypair,
+        denylist: cli.denylist,
+        plan_file: cli.plan_file,
+        deployments: cli.deployments,
+    };
+
+    // the RPC endpoints of the config file are used when --rpc-url is not specified
+    let config = config_path(&cli.command, global.profile.as_deref());
+    if Path::new(&config).exists() {
+        if let Ok(config_data) = get_config_data(&config, &global.address_book) {
+            global.config_rpc_urls = config_data.rpc_url;
+        }
+    }
+
+    // reports are only collected with the explicit consent of the user
+    if cli.report_errors {
+        context.report = Some(enable_error_reports(&cli.command, &global));
+    }
+
+    check_role(&cli.command, &global)?;
 
     let interrupted = Arc::new(AtomicBool::new(true));
     let ctrl_handler = interrupted.clone();
@@ -117,11 +287,15 @@ async fn run() -> Result<()> {
     ctrlc::set_handler(move || {
         if ctrl_handler.load(Ordering::SeqCst) {
             // we really need to exit
-            println!(
-                "\n\n{}{} Operation aborted.",
-                ERROR_EMOJI,
-                style("Error running command (re-run needed):").red(),
-            );
+            if is_json_output() {
+                JsonOutput::error("Operation aborted.".to_string()).print();
+            } else {
+                println!(
+                    "\n\n{}{} Operation aborted.",
+                    ERROR_EMOJI,
+                    style("Error running command (re-run needed):").red(),
+                );
+            }
             // finished the program with an error code to the OS
             std::process::exit(1);
         }
@@ -130,18 +304,60 @@ async fn run() -> Result<()> {
     })
     .expect("Error setting Ctrl-C handler");
 
-    match cli.command {
+    let result = match cli.command {
         Commands::CreateConfig {
             config,
             keypair,
             rpc_url,
             assets_dir,
-        } => process_create_config(CreateConfigArgs {
-            config,
+            format,
+        } => {
+            process_create_config(CreateConfigArgs {
+                config,
+                keypair,
+                rpc_url,
+                global,
+                assets_dir,
+                format,
+            })
+            .await?;
+            None
+        }
+        Commands::Estimate {
             keypair,
             rpc_url,
             assets_dir,
-        })?,
+            config,
+        } => {
+            let outcome = process_estimate(EstimateArgs {
+                keypair,
+                rpc_url,
+                global,
+                assets_dir,
+                config,
+            })
+            .await?;
+            Some(serde_json::to_value(outcome)?)
+        }
+        Commands::EstimateReveal {
+            keypair,
+            rpc_url,
+            cache,
+            tars,
+            concurrency,
+            sample,
+        } => {
+            let outcome = process_estimate_reveal(EstimateRevealArgs {
+                keypair,
+                rpc_url,
+                global,
+                cache,
+                tars,
+                concurrency,
+                sample,
+            })?;
+            Some(serde_json::to_value(outcome)?)
+        }
         Commands::Launch {
             assets_dir,
             config,
@@ -150,32 +366,106 @@ async fn run() -> Result<()> {
             cache,
             strict,
             skip_collection_prompt,
+            skip_collection,
+            from_step,
         } => {
-            process_launch(LaunchArgs {
+            let outcome = process_launch(LaunchArgs {
                 assets_dir,
                 config,
                 keypair,
                 rpc_url,
+                global,
                 cache,
                 strict,
                 skip_collection_prompt,
+                skip_collection,
                 interrupted: interrupted.clone(),
+                from_step,
             })
-            .await?
+            .await?;
+            Some(serde_json::to_value(outcome)?)
         }
         Commands::Mint {
+            config,
             keypair,
             rpc_url,
             cache,
             number,
             tars,
-        } => process_mint(MintArgs {
+            keypairs,
+            max_fee,
+            confirm_above,
+            yes,
+            compute_units,
+            to_file,
+            gateway_token,
+        } => {
+            let outcome = process_mint(MintArgs {
+                config,
+                keypair,
+                rpc_url,
+                global,
+                cache,
+                number,
+                tars,
+                keypairs,
+                max_fee,
+                confirm_above,
+                skip_confirmation: yes,
+                compute_units,
+                to_file,
+                gateway_token,
+            })?;
+            Some(serde_json::to_value(outcome)?)
+        }
+        Commands::Reserve {
             keypair,
             rpc_url,
             cache,
+            tars,
             number,
+            wallet,
+            receipts,
+        } => {
+            let outcome = process_reserve(ReserveArgs {
+                keypair,
+                rpc_url,
+                global,
+                cache,
+                tars,
+                number,
+                wallet,
+                receipts,
+            })?;
+            Some(serde_json::to_value(outcome)?)
+        }
+        Commands::RehearseGolive {
+            config,
+            keypair,
+            rpc_url,
+            cache,
             tars,
-        })?,
+            at,
+            burst,
+            concurrency,
+            compute_units,
+            priority_fee,
+        } => {
+            let outcome = process_rehearse_golive(RehearseGoliveArgs {
+                config,
+                keypair,
+                rpc_url,
+                global,
+                cache,
+                tars,
+                at,
+                burst,
+                concurrency,
+                compute_units,
+                priority_fee,
+            })?;
+            Some(serde_json::to_value(outcome)?)
+        }
         Commands::Update {
             config,
             keypair,
@@ -183,28 +473,117 @@ async fn run() -> Result<()> {
             cache,
             new_authority,
             tars,
-        } => process_update(UpdateArgs {
+            dry_run,
+            yes,
+            sign_offline,
+            nonces,
+            new_tars,
+        } => {
+            let outcome = process_update(UpdateArgs {
+                config,
+                keypair,
+                rpc_url,
+                global,
+                cache,
+                new_authority,
+                tars,
+                dry_run,
+                skip_confirmation: yes,
+                sign_offline,
+                nonces,
+                new_tars,
+            })?;
+            Some(serde_json::to_value(outcome)?)
+        }
+        Commands::MigratePayment {
             config,
             keypair,
             rpc_url,
             cache,
-            new_authority,
             tars,
-        })?,
+            to,
+            price,
+            token_account,
+            treasury,
+            dry_run,
+            yes,
+        } => {
+            let outcome = process_migrate_payment(MigratePaymentArgs {
+                keypair,
+                rpc_url,
+                global,
+                cache,
+                config,
+                tars,
+                to,
+                price,
+                token_account,
+                treasury,
+                dry_run,
+                skip_confirmation: yes,
+            })?;
+            Some(serde_json::to_value(outcome)?)
+        }
         Commands::Deploy {
             config,
             keypair,
             rpc_url,
             cache,
+            dry_run,
+            max_fee,
+            batch_size,
+            sign_offline,
+            nonces,
+            compute_units,
+            skip_collection,
         } => {
-            process_deploy(DeployArgs {
+            let outcome = process_deploy(DeployArgs {
                 config,
                 keypair,
                 rpc_url,
+                global,
                 cache,
                 interrupted: interrupted.clone(),
+                dry_run,
+                max_fee,
+                batch_size,
+                sign_offline,
+                nonces,
+                skip_collection,
+                compute_units,
             })
-            .await?
+            .await?;
+            Some(serde_json::to_value(outcome)?)
+        }
+        Commands::Generate {
+            assets_dir,
+            config,
+            number,
+            seed,
+            force,
+        } => {
+            let outcome = process_generate(GenerateArgs {
+                assets_dir,
+                config,
+                global,
+                number,
+                seed,
+                force,
+            })?;
+            Some(serde_json::to_value(outcome)?)
+        }
+        Commands::ProcessImages {
+            assets_dir,
+            config,
+            output_dir,
+        } => {
+            let outcome = process_process_images(ProcessImagesArgs {
+                assets_dir,
+                config,
+                global,
+                output_dir,
+            })?;
+            Some(serde_json::to_value(outcome)?)
         }
         Commands::Upload {
             assets_dir,
@@ -212,85 +591,795 @@ async fn run() -> Result<()> {
             keypair,
             rpc_url,
             cache,
+            inject_metadata,
+            concurrency,
+            assets_url,
+            no_auto_fund,
+            max_open_files,
+            max_buffer_mb,
+            verify_storage,
+            full,
+            allow_duplicates,
         } => {
-            process_upload(UploadArgs {
+            let outcome = process_upload(UploadArgs {
                 assets_dir,
                 config,
                 keypair,
                 rpc_url,
+                global,
                 cache,
                 interrupted: interrupted.clone(),
+                inject_metadata,
+                concurrency,
+                assets_url,
+                auto_fund: !no_auto_fund,
+                max_open_files,
+                max_buffer_mb,
+                verify_storage,
+                verify_full: full,
+                allow_duplicates,
             })
-            .await?
+            .await?;
+            Some(serde_json::to_value(outcome)?)
         }
         Commands::Validate {
             assets_dir,
+            config,
             strict,
             skip_collection_prompt,
-        } => process_validate(ValidateArgs {
-            assets_dir,
-            strict,
-            skip_collection_prompt,
-        })?,
+            watch,
+            fix,
+            allow_duplicates,
+        } => {
+            process_validate(ValidateArgs {
+                assets_dir,
+                config,
+                global,
+                strict,
+                skip_collection_prompt,
+                watch,
+                fix,
+                allow_duplicates,
+            })?;
+            None
+        }
         Commands::Withdraw {
             tars,
             keypair,
             rpc_url,
             list,
-        } => process_withdraw(WithdrawArgs {
-            tars,
-            keypair,
-            rpc_url,
-            list,
-        })?,
+            authority,
+            exclude,
+            yes,
+        } => {
+            let outcome = process_withdraw(WithdrawArgs {
+                tars,
+                keypair,
+                rpc_url,
+                global,
+                list,
+                authority,
+                exclude,
+                skip_confirmation: yes,
+            })?;
+            Some(serde_json::to_value(outcome)?)
+        }
+        Commands::Rarity {
+            assets_dir,
+            from_cache,
+            cache,
+            ipfs_gateways,
+            output_file,
+        } => {
+            let outcome = process_rarity(RarityArgs {
+                assets_dir,
+                cache,
+                from_cache,
+                ipfs_gateways,
+                output_file,
+            })
+            .await?;
+            Some(serde_json::to_value(outcome)?)
+        }
         Commands::Verify {
+            config,
             keypair,
             rpc_url,
             cache,
-        } => process_verify(VerifyArgs {
+            check_metadata,
+            check_links,
+            ipfs_gateways,
+            repair,
+            metadata_standards,
+        } => {
+            let outcome = process_verify(VerifyArgs {
+                config,
+                keypair,
+                rpc_url,
+                global,
+                cache,
+                check_metadata,
+                check_links,
+                ipfs_gateways,
+                repair,
+                metadata_standards,
+            })
+            .await?;
+            Some(serde_json::to_value(outcome)?)
+        }
+        Commands::Sandbox {
+            keypair,
+            rpc_url,
+            items,
+            output_dir,
+            batch_size,
+        } => {
+            let outcome = process_sandbox(SandboxArgs {
+                keypair,
+                rpc_url,
+                global,
+                items,
+                output_dir,
+                batch_size,
+                interrupted: interrupted.clone(),
+            })
+            .await?;
+            Some(serde_json::to_value(outcome)?)
+        }
+        Commands::Selftest {
+            keypair,
+            rpc_url,
+            output_dir,
+            keep,
+        } => {
+            let outcome = process_selftest(SelftestArgs {
+                keypair,
+                rpc_url,
+                global,
+                output_dir,
+                keep,
+                interrupted: interrupted.clone(),
+            })
+            .await?;
+            Some(serde_json::to_value(outcome)?)
+        }
+        Commands::VerifyConfigHash {
             keypair,
             rpc_url,
             cache,
-        })?,
+            assets_dir,
+            tars,
+            offline,
+        } => {
+            let outcome = process_verify_config_hash(VerifyConfigHashArgs {
+                keypair,
+                rpc_url,
+                global,
+                cache,
+                assets_dir,
+                tars,
+                offline,
+            })?;
+            Some(serde_json::to_value(outcome)?)
+        }
         Commands::Show {
             keypair,
             rpc_url,
             cache,
             tars,
             unminted,
-        } => process_show(ShowArgs {
+            json,
+            output_file,
+        } => {
+            if json {
+                set_output_format(OutputFormat::Json);
+            }
+
+            let outcome = process_show(ShowArgs {
+                keypair,
+                rpc_url,
+                global,
+                cache,
+                tars,
+                unminted,
+                json,
+                output_file,
+            })?;
+            Some(serde_json::to_value(outcome)?)
+        }
+        Commands::Whoami {
             keypair,
             rpc_url,
+            config,
             cache,
             tars,
-            unminted,
-        })?,
+        } => {
+            let outcome = process_whoami(WhoamiArgs {
+                keypair,
+                rpc_url,
+                global,
+                config,
+                cache,
+                tars,
+            })
+            .await?;
+            Some(serde_json::to_value(outcome)?)
+        }
+        Commands::Profile { command } => match command {
+            ProfileSubcommands::List => {
+                let outcome = process_list_profiles(ListProfilesArgs { global })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            ProfileSubcommands::Switch { name } => {
+                let outcome = process_switch_profile(SwitchProfileArgs { name })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+        },
+        Commands::Logs { lines, list, open } => {
+            let outcome = process_logs(LogsArgs {
+                lines,
+                list,
+                open,
+                run_log: context.log.clone(),
+            })?;
+            Some(serde_json::to_value(outcome)?)
+        }
+        Commands::InspectTx {
+            signature,
+            keypair,
+            rpc_url,
+        } => {
+            let outcome = process_inspect_tx(InspectTxArgs {
+                signature,
+                keypair,
+                rpc_url,
+                global,
+            })?;
+            Some(serde_json::to_value(outcome)?)
+        }
         Commands::Collection { command } => match command {
+            CollectionSubcommands::Create {
+                config,
+                keypair,
+                rpc_url,
+                cache,
+                image,
+                metadata,
+                set,
+                tars,
+            } => {
+                let outcome = process_create_collection(CreateCollectionArgs {
+                    config,
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                    image,
+                    metadata,
+                    set,
+                    tars,
+                    interrupted: interrupted.clone(),
+                })
+                .await?;
+                Some(serde_json::to_value(outcome)?)
+            }
             CollectionSubcommands::Set {
                 keypair,
                 rpc_url,
                 cache,
                 tars,
+                machines: Some(machines),
                 collection_mint,
-            } => process_set_collection(SetCollectionArgs {
-                collection_mint,
+                ..
+            } => {
+                let outcome = process_set_collection_batch(SetCollectionBatchArgs {
+                    collection_mint,
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                    machines,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            CollectionSubcommands::Set {
                 keypair,
                 rpc_url,
                 cache,
                 tars,
-            })?,
+                machines: None,
+                collection_mint,
+                sign_offline,
+                nonces,
+            } => {
+                let outcome = process_set_collection(SetCollectionArgs {
+                    collection_mint,
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                    tars,
+                    sign_offline,
+                    nonces,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
             CollectionSubcommands::Remove {
                 keypair,
                 rpc_url,
                 cache,
                 tars,
-            } => process_remove_collection(RemoveCollectionArgs {
+            } => {
+                let outcome = process_remove_collection(RemoveCollectionArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                    tars,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            CollectionSubcommands::VerifyItems {
+                config,
                 keypair,
                 rpc_url,
                 cache,
                 tars,
-            })?,
+                collection_mint,
+                dry_run,
+            } => {
+                let outcome = process_verify_items(VerifyItemsArgs {
+                    config,
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                    tars,
+                    collection_mint,
+                    dry_run,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            CollectionSubcommands::Migrate {
+                keypair,
+                rpc_url,
+                cache,
+                tars,
+                old_collection_mint,
+                dry_run,
+                yes,
+                new_collection_mint,
+            } => {
+                let outcome = process_migrate_collection(MigrateCollectionArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                    tars,
+                    old_collection_mint,
+                    new_collection_mint,
+                    dry_run,
+                    skip_confirmation: yes,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+        },
+        Commands::Metadata { command } => match command {
+            MetadataSubcommands::Mutate {
+                assets_dir,
+                patch,
+                set,
+                remove,
+                set_attribute,
+                remove_attribute,
+                dry_run,
+                yes,
+            } => {
+                let outcome = process_mutate_metadata(MutateMetadataArgs {
+                    assets_dir,
+                    patch,
+                    set,
+                    remove,
+                    set_attribute,
+                    remove_attribute,
+                    dry_run,
+                    skip_confirmation: yes,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
         },
+        Commands::Export { command } => match command {
+            ExportSubcommands::Backup {
+                keypair,
+                rpc_url,
+                config,
+                cache,
+                collection_keys,
+                output_dir,
+            } => {
+                let outcome = process_export_backup(ExportBackupArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    config,
+                    cache,
+                    collection_keys,
+                    output_dir,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            ExportSubcommands::ConfigLines {
+                keypair,
+                rpc_url,
+                cache,
+                tars,
+                output,
+            } => {
+                let outcome = process_export_config_lines(ExportConfigLinesArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                    tars,
+                    output,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+        },
+        Commands::Import { command } => match command {
+            ImportSubcommands::Backup {
+                archive,
+                output_dir,
+                force,
+            } => {
+                let outcome = process_import_backup(ImportBackupArgs {
+                    archive,
+                    output_dir,
+                    force,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+        },
+        Commands::Snapshot { command } => match command {
+            SnapshotSubcommands::Holders {
+                keypair,
+                rpc_url,
+                cache,
+                tars,
+                collection,
+                output_file,
+            } => {
+                let outcome = process_snapshot_holders(SnapshotHoldersArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                    tars,
+                    collection,
+                    output_file,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            SnapshotSubcommands::Mints {
+                keypair,
+                rpc_url,
+                cache,
+                tars,
+                collection,
+                include_metadata,
+                output_file,
+            } => {
+                let outcome = process_snapshot_mints(SnapshotMintsArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                    tars,
+                    collection,
+                    include_metadata,
+                    output_file,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+        },
+        Commands::Report { command } => match command {
+            ReportSubcommands::Progress {
+                keypair,
+                rpc_url,
+                cache,
+                tars,
+                interval,
+                stall_after,
+                samples,
+                output_file,
+            } => {
+                let outcome = process_report_progress(ReportProgressArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                    tars,
+                    interval,
+                    stall_after,
+                    samples,
+                    output_file,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+        },
+        Commands::Tx { command } => match command {
+            TxSubcommands::Sign {
+                keypair,
+                yes,
+                files,
+            } => {
+                let outcome = process_tx_sign(TxSignArgs {
+                    keypair,
+                    global,
+                    files,
+                    skip_confirmation: yes,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            TxSubcommands::Send {
+                keypair,
+                rpc_url,
+                files,
+            } => {
+                let outcome = process_tx_send(TxSendArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    files,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+        },
+        Commands::Cache { command } => match command {
+            CacheSubcommands::Check {
+                keypair,
+                rpc_url,
+                cache,
+            } => {
+                let outcome = process_check_cache(CheckCacheArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            CacheSubcommands::Repair {
+                keypair,
+                rpc_url,
+                cache,
+            } => {
+                let outcome = process_repair_cache(RepairCacheArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            CacheSubcommands::Rebuild {
+                keypair,
+                rpc_url,
+                cache,
+                tars,
+                force,
+                ipfs_gateways,
+            } => {
+                let outcome = process_rebuild_cache(RebuildCacheArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                    tars,
+                    force,
+                    ipfs_gateways,
+                })
+                .await?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            CacheSubcommands::Sync {
+                keypair,
+                rpc_url,
+                cache,
+                tars,
+                force,
+            } => {
+                let outcome = process_sync_cache(SyncCacheArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                    tars,
+                    force,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            CacheSubcommands::Remove {
+                keypair,
+                cache,
+                config,
+                index,
+            } => {
+                let outcome = process_remove_cache_item(RemoveCacheItemArgs {
+                    keypair,
+                    global,
+                    cache,
+                    config,
+                    index,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            CacheSubcommands::Restore {
+                keypair,
+                cache,
+                config,
+                index,
+            } => {
+                let outcome = process_restore_cache_item(RestoreCacheItemArgs {
+                    keypair,
+                    global,
+                    cache,
+                    config,
+                    index,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+        },
+        Commands::Shuffle { command } => match command {
+            ShuffleSubcommands::Commit {
+                keypair,
+                rpc_url,
+                cache,
+                tars,
+                proof,
+                slot_offset,
+                secret_hash,
+            } => {
+                let outcome = process_shuffle_commit(ShuffleCommitArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                    tars,
+                    proof,
+                    slot_offset,
+                    secret_hash,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            ShuffleSubcommands::Reveal {
+                keypair,
+                rpc_url,
+                cache,
+                proof,
+                secret,
+            } => {
+                let outcome = process_shuffle_reveal(ShuffleRevealArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    cache,
+                    proof,
+                    secret,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            ShuffleSubcommands::Verify {
+                keypair,
+                rpc_url,
+                proof,
+            } => {
+                let outcome = process_shuffle_verify(ShuffleVerifyArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    proof,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+        },
+        Commands::Schedule {
+            command:
+                ScheduleSubcommands::Price {
+                    config,
+                    keypair,
+                    rpc_url,
+                    cache,
+                    tars,
+                    once,
+                    dry_run,
+                    health_port,
+                },
+        } => {
+            let outcome = process_schedule_price(SchedulePriceArgs {
+                config,
+                keypair,
+                rpc_url,
+                global,
+                cache,
+                tars,
+                once,
+                dry_run,
+                health_port,
+            })?;
+            Some(serde_json::to_value(outcome)?)
+        }
+        Commands::Whitelist { command } => match command {
+            WhitelistSubcommands::Create {
+                keypair,
+                rpc_url,
+                amount,
+            } => {
+                let outcome = process_whitelist_create(WhitelistCreateArgs {
+                    keypair,
+                    rpc_url,
+                    global,
+                    amount,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            WhitelistSubcommands::Mint {
+                amount,
+                config,
+                keypair,
+                rpc_url,
+                mint,
+                to,
+            } => {
+                let outcome = process_whitelist_mint(WhitelistMintArgs {
+                    config,
+                    keypair,
+                    rpc_url,
+                    global,
+                    mint,
+                    amount,
+                    to,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+            WhitelistSubcommands::Airdrop {
+                list,
+                config,
+                keypair,
+                rpc_url,
+                mint,
+                amount,
+                progress,
+            } => {
+                let outcome = process_whitelist_airdrop(WhitelistAirdropArgs {
+                    config,
+                    keypair,
+                    rpc_url,
+                    global,
+                    mint,
+                    list,
+                    amount,
+                    progress,
+                })?;
+                Some(serde_json::to_value(outcome)?)
+            }
+        },
+        Commands::Deployments {
+            command:
+                DeploymentsSubcommands::List {
+                    keypair,
+                    rpc_url,
+                    refresh,
+                },
+        } => {
+            let outcome = process_deployments_list(DeploymentsListArgs {
+                keypair,
+                rpc_url,
+                global,
+                refresh,
+            })?;
+            Some(serde_json::to_value(outcome)?)
+        }
         Commands::Bundlr {
             keypair,
             rpc_url,
@@ -299,11 +1388,13 @@ async fn run() -> Result<()> {
             process_bundlr(BundlrArgs {
                 keypair,
                 rpc_url,
+                global,
                 action,
             })
-            .await?
+            .await?;
+            None
         }
-    }
+    };
 
-    Ok(())
+    Ok(result)
 }
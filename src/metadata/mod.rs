@@ -0,0 +1,3 @@
+pub mod mutate;
+
+pub use mutate::*;
@@ -0,0 +1,533 @@
+use std::{ffi::OsStr, fs};
+
+use chrono::Utc;
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    canonical_json::to_canonical_json, common::*, upload::list_files, utils::confirm_action,
+    validate::Metadata,
+};
+
+/// Number of files whose changes are listed in the preview.
+const PREVIEW_FILES: usize = 5;
+
+pub struct MutateMetadataArgs {
+    pub assets_dir: String,
+    /// Path to a JSON file with a list of operations, applied first.
+    pub patch: Option<String>,
+    /// `PATH=TEMPLATE` values to set.
+    pub set: Vec<String>,
+    /// Paths of the values to remove.
+    pub remove: Vec<String>,
+    /// `TRAIT=TEMPLATE` attributes to add or replace.
+    pub set_attribute: Vec<String>,
+    /// Trait types of the attributes to remove.
+    pub remove_attribute: Vec<String>,
+    pub dry_run: bool,
+    pub skip_confirmation: bool,
+}
+
+/// Change of a metadata file. Strings of the values are templates: `{index}` and
+/// `{number}` (the index plus one) are replaced by the index of the file, `{trait:TYPE}`
+/// by the value of an attribute and any other `{path}` by the value at that path of the
+/// original metadata (e.g. `{name}`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum Operation {
+    /// Sets the value at a path (e.g. `$.properties.category`), creating the missing
+    /// objects.
+    Set {
+        path: String,
+        value: Value,
+    },
+    Remove {
+        path: String,
+    },
+    /// Sets the value of an attribute, appending it when the metadata does not have it.
+    #[serde(rename_all = "camelCase")]
+    SetAttribute {
+        trait_type: String,
+        value: Value,
+    },
+    #[serde(rename_all = "camelCase")]
+    RemoveAttribute {
+        trait_type: String,
+    },
+}
+
+/// Changes of a metadata file.
+#[derive(Debug, Serialize)]
+pub struct FileChanges {
+    pub file: String,
+    pub changes: Vec<String>,
+}
+
+/// Result of the metadata mutate command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MutateMetadataOutcome {
+    pub files: usize,
+    pub changed: Vec<FileChanges>,
+    pub dry_run: bool,
+    /// Directory with the original files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_dir: Option<String>,
+}
+
+/// Segment of a metadata path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+pub fn process_mutate_metadata(args: MutateMetadataArgs) -> Result<MutateMetadataOutcome> {
+    let operations = parse_operations(&args)?;
+
+    if operations.is_empty() {
+        return Err(anyhow!(
+            "No changes to apply, use --patch, --set, --remove, --set-attribute or \
+             --remove-attribute"
+        ));
+    }
+
+    case_println!(
+        "{} {}Applying {} operation(s)",
+        style("[1/2]").bold().dim(),
+        PAPER_EMOJI,
+        operations.len()
+    );
+
+    let mut paths: Vec<(u64, PathBuf)> = list_files(&args.assets_dir, false)?
+        .into_iter()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(OsStr::to_str) == Some("json"))
+        .filter_map(|path| {
+            let index = path.file_stem()?.to_str()?.parse().ok()?;
+            Some((index, path))
+        })
+        .collect();
+    paths.sort();
+
+    if paths.is_empty() {
+        return Err(anyhow!("No metadata files found in '{}'", args.assets_dir));
+    }
+
+    let mut outcome = MutateMetadataOutcome {
+        files: paths.len(),
+        dry_run: args.dry_run,
+        ..Default::default()
+    };
+    let mut mutated = Vec::new();
+    let mut invalid = Vec::new();
+
+    for (index, path) in &paths {
+        let file = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let original: Value = serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|err| anyhow!("Failed to parse metadata file '{}': {}", file, err))?;
+
+        let mut metadata = original.clone();
+        for operation in &operations {
+            apply(&mut metadata, &original, *index, operation)
+                .map_err(|err| anyhow!("{}: {}", file, err))?;
+        }
+
+        let mut changes = Vec::new();
+        diff_values("$", &original, &metadata, &mut changes);
+
+        if changes.is_empty() {
+            continue;
+        }
+
+        // the files are only written when they all pass the validation
+        let errors: Vec<String> = match serde_json::from_value::<Metadata>(metadata.clone()) {
+            Ok(parsed) => parsed
+                .validate()
+                .iter()
+                .map(|error| error.to_string())
+                .collect(),
+            Err(err) => vec![err.to_string()],
+        };
+        if !errors.is_empty() {
+            invalid.push(format!("{}: {}", file, errors.join(" ")));
+        }
+
+        outcome.changed.push(FileChanges { file, changes });
+        mutated.push((path, metadata));
+    }
+
+    for file in outcome.changed.iter().take(PREVIEW_FILES) {
+        case_println!("{}", style(&file.file).bold());
+        for change in &file.changes {
+            case_println!("{} {}", style(" :..").dim(), change);
+        }
+    }
+    if outcome.changed.len() > PREVIEW_FILES {
+        case_println!(
+            "... and {} more file(s)",
+            outcome.changed.len() - PREVIEW_FILES
+        );
+    }
+
+    case_println!(
+        "\n{} of {} metadata file(s) changed",
+        outcome.changed.len(),
+        outcome.files
+    );
+
+    if !invalid.is_empty() {
+        let mut message = format!(
+            "{} metadata file(s) would fail the validation, no file was changed:",
+            invalid.len()
+        );
+        for error in invalid {
+            message.push_str("\n=> ");
+            message.push_str(&error);
+        }
+
+        return Err(anyhow!(message));
+    }
+
+    case_println!(
+        "\n{} {}Writing metadata files",
+        style("[2/2]").bold().dim(),
+        ASSETS_EMOJI
+    );
+
+    if mutated.is_empty() {
+        case_println!("Every file is up to date, nothing to write.");
+        return Ok(outcome);
+    }
+
+    if args.dry_run {
+        case_println!(
+            "{} file(s) would be rewritten (dry run, nothing written).",
+            mutated.len()
+        );
+        return Ok(outcome);
+    }
+
+    let prompt = format!("Rewrite {} metadata file(s)?", mutated.len());
+
+    if !confirm_action(&prompt, args.skip_confirmation)? {
+        return Err(anyhow!("Metadata changes cancelled"));
+    }
+
+    let backup_dir = Path::new(&args.assets_dir)
+        .join(FIX_BACKUP_DIR)
+        .join(Utc::now().format("%Y%m%d%H%M%S").to_string());
+    fs::create_dir_all(&backup_dir)?;
+
+    for (path, _) in &mutated {
+        if let Some(file_name) = path.file_name() {
+            fs::copy(path, backup_dir.join(file_name))?;
+        }
+    }
+
+    for (path, metadata) in &mutated {
+        fs::write(path, to_canonical_json(metadata)?)?;
+        info!("Mutated metadata file {}", path.display());
+    }
+
+    case_println!(
+        "Rewrote {} metadata file(s), originals saved to '{}'",
+        mutated.len(),
+        backup_dir.display()
+    );
+    case_println!("Run 'case upload' to upload the changed metadata.");
+
+    outcome.backup_dir = Some(path_to_string(&backup_dir)?);
+
+    Ok(outcome)
+}
+
+/// Returns the operations of the patch file followed by the ones of the options.
+fn parse_operations(args: &MutateMetadataArgs) -> Result<Vec<Operation>> {
+    let mut operations: Vec<Operation> = match &args.patch {
+        Some(patch) => {
+            let content = fs::read_to_string(patch)
+                .map_err(|err| anyhow!("Failed to read patch file '{}': {}", patch, err))?;
+            serde_json::from_str(&content)
+                .map_err(|err| anyhow!("Failed to parse patch file '{}': {}", patch, err))?
+        }
+        None => Vec::new(),
+    };
+
+    let split = |option: &str, value: &str| -> Result<(String, String)> {
+        value
+            .split_once('=')
+            .map(|(key, template)| (key.trim().to_string(), template.to_string()))
+            .ok_or_else(|| anyhow!("Invalid {} '{}', expected KEY=TEMPLATE", option, value))
+    };
+
+    for value in &args.set {
+        let (path, template) = split("--set", value)?;
+        operations.push(Operation::Set {
+            path,
+            value: Value::String(template),
+        });
+    }
+    for path in &args.remove {
+        operations.push(Operation::Remove { path: path.clone() });
+    }
+    for value in &args.set_attribute {
+        let (trait_type, template) = split("--set-attribute", value)?;
+        operations.push(Operation::SetAttribute {
+            trait_type,
+            value: Value::String(template),
+        });
+    }
+    for trait_type in &args.remove_attribute {
+        operations.push(Operation::RemoveAttribute {
+            trait_type: trait_type.clone(),
+        });
+    }
+
+    // paths are checked before any file is read
+    for operation in &operations {
+        if let Operation::Set { path, .. } | Operation::Remove { path } = operation {
+            parse_path(path)?;
+        }
+    }
+
+    Ok(operations)
+}
+
+fn apply(metadata: &mut Value, original: &Value, index: u64, operation: &Operation) -> Result<()> {
+    match operation {
+        Operation::Set { path, value } => {
+            let value = render_value(value, original, index)?;
+            set_path(metadata, &parse_path(path)?, value)
+        }
+        Operation::Remove { path } => {
+            remove_path(metadata, &parse_path(path)?);
+            Ok(())
+        }
+        Operation::SetAttribute { trait_type, value } => {
+            let value = render_value(value, original, index)?;
+            let attributes = metadata
+                .get_mut("attributes")
+                .and_then(Value::as_array_mut)
+                .ok_or_else(|| anyhow!("missing attributes"))?;
+
+            match attributes
+                .iter_mut()
+                .find(|attribute| attribute["trait_type"].as_str() == Some(trait_type))
+            {
+                Some(attribute) => attribute["value"] = value,
+                None => attributes.push(json!({ "trait_type": trait_type, "value": value })),
+            }
+            Ok(())
+        }
+        Operation::RemoveAttribute { trait_type } => {
+            if let Some(attributes) = metadata.get_mut("attributes").and_then(Value::as_array_mut) {
+                attributes.retain(|attribute| attribute["trait_type"].as_str() != Some(trait_type));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Parses a path of keys and array indices, e.g. `$.properties.files[0].uri` (the `$.`
+/// prefix is optional).
+fn parse_path(path: &str) -> Result<Vec<Segment>> {
+    let invalid = || anyhow!("Invalid metadata path '{}'", path);
+    let trimmed = path.trim();
+    let trimmed = trimmed
+        .strip_prefix("$.")
+        .or_else(|| trimmed.strip_prefix('$'))
+        .unwrap_or(trimmed);
+    let mut segments = Vec::new();
+
+    for part in trimmed.split('.') {
+        let (key, indices) = part.split_at(part.find('[').unwrap_or(part.len()));
+
+        if !key.is_empty() {
+            segments.push(Segment::Key(key.to_string()));
+        } else if indices.is_empty() {
+            return Err(invalid());
+        }
+
+        let mut rest = indices;
+        while !rest.is_empty() {
+            let (index, next) = rest
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once(']'))
+                .ok_or_else(invalid)?;
+            segments.push(Segment::Index(index.parse().map_err(|_| invalid())?));
+            rest = next;
+        }
+    }
+
+    if segments.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(segments)
+}
+
+fn get_path<'a>(value: &'a Value, segments: &[Segment]) -> Option<&'a Value> {
+    segments
+        .iter()
+        .try_fold(value, |value, segment| match segment {
+            Segment::Key(key) => value.get(key),
+            Segment::Index(index) => value.get(index),
+        })
+}
+
+fn set_path(value: &mut Value, segments: &[Segment], new: Value) -> Result<()> {
+    let (last, parents) = segments.split_last().expect("Empty metadata path");
+    let mut current = value;
+
+    for segment in parents {
+        current = match segment {
+            Segment::Key(key) => {
+                let object = current
+                    .as_object_mut()
+                    .ok_or_else(|| anyhow!("'{}' is not in an object", key))?;
+                object.entry(key.clone()).or_insert_with(|| json!({}))
+            }
+            Segment::Index(index) => current
+                .get_mut(index)
+                .ok_or_else(|| anyhow!("missing array item {}", index))?,
+        };
+    }
+
+    match last {
+        Segment::Key(key) => {
+            current
+                .as_object_mut()
+                .ok_or_else(|| anyhow!("'{}' is not in an object", key))?
+                .insert(key.clone(), new);
+        }
+        Segment::Index(index) => {
+            *current
+                .get_mut(index)
+                .ok_or_else(|| anyhow!("missing array item {}", index))? = new;
+        }
+    }
+
+    Ok(())
+}
+
+fn remove_path(value: &mut Value, segments: &[Segment]) {
+    let (last, parents) = segments.split_last().expect("Empty metadata path");
+    let mut current = value;
+
+    for segment in parents {
+        let next = match segment {
+            Segment::Key(key) => current.get_mut(key),
+            Segment::Index(index) => current.get_mut(index),
+        };
+        current = match next {
+            Some(next) => next,
+            None => return,
+        };
+    }
+
+    match (last, current) {
+        (Segment::Key(key), Value::Object(object)) => {
+            object.remove(key);
+        }
+        (Segment::Index(index), Value::Array(array)) if *index < array.len() => {
+            array.remove(*index);
+        }
+        _ => (),
+    }
+}
+
+/// Renders the templates of the strings of a value.
+fn render_value(value: &Value, original: &Value, index: u64) -> Result<Value> {
+    Ok(match value {
+        Value::String(template) => Value::String(render(template, original, index)?),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render_value(item, original, index))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .map(|(key, item)| Ok((key.clone(), render_value(item, original, index)?)))
+                .collect::<Result<_>>()?,
+        ),
+        _ => value.clone(),
+    })
+}
+
+fn render(template: &str, original: &Value, index: u64) -> Result<String> {
+    let mut rendered = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .map(|end| start + end)
+            .ok_or_else(|| anyhow!("unclosed placeholder in template '{}'", template))?;
+        rendered.push_str(&rest[..start]);
+
+        let placeholder = &rest[start + 1..end];
+        let value = match placeholder {
+            "index" => index.to_string(),
+            "number" => (index + 1).to_string(),
+            _ => {
+                let value = match placeholder.strip_prefix("trait:") {
+                    Some(trait_type) => original["attributes"]
+                        .as_array()
+                        .and_then(|attributes| {
+                            attributes.iter().find(|attribute| {
+                                attribute["trait_type"].as_str() == Some(trait_type)
+                            })
+                        })
+                        .map(|attribute| &attribute["value"]),
+                    None => get_path(original, &parse_path(placeholder)?),
+                };
+
+                match value {
+                    Some(Value::String(string)) => string.clone(),
+                    Some(value) if !value.is_null() => value.to_string(),
+                    _ => {
+                        return Err(anyhow!(
+                            "missing value of placeholder '{{{}}}'",
+                            placeholder
+                        ))
+                    }
+                }
+            }
+        };
+
+        rendered.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Describes the differences between two values, by path.
+fn diff_values(path: &str, old: &Value, new: &Value, changes: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                let key_path = format!("{}.{}", path, key);
+                match new.get(key) {
+                    Some(new_value) => diff_values(&key_path, old_value, new_value, changes),
+                    None => changes.push(format!("{}: removed {}", key_path, old_value)),
+                }
+            }
+            for (key, new_value) in new {
+                if !old.contains_key(key) {
+                    changes.push(format!("{}.{}: added {}", path, key, new_value));
+                }
+            }
+        }
+        _ if old != new => changes.push(format!("{}: {} -> {}", path, old, new)),
+        _ => (),
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    fs,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use data_encoding::HEXLOWER;
+use reqwest::StatusCode;
+use ring::digest::{digest, SHA256};
+use tokio::time::{sleep, Duration};
+
+use crate::{
+    common::*,
+    ipfs::{is_ipfs, GatewayHealth, IpfsGateways},
+};
+
+/// Maximum number of times a download is retried.
+const MAX_RETRIES: u32 = 4;
+
+/// Delay before the first retry; doubled after each attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Local cache of metadata JSON files keyed by the hash of their URI, so repeated
+/// checks do not download the same files from the storage gateways again.
+pub struct MetadataCache {
+    dir: PathBuf,
+    client: HttpClient,
+    gateways: IpfsGateways,
+    hits: AtomicUsize,
+    downloads: AtomicUsize,
+}
+
+impl MetadataCache {
+    /// Uses the user cache directory (e.g. `~/.cache/case/metadata`).
+    pub fn new() -> Result<Self> {
+        let base = dirs::cache_dir()
+            .or_else(|| dirs::home_dir().map(|home| home.join(".cache")))
+            .ok_or_else(|| anyhow!("Could not find a cache directory"))?;
+
+        MetadataCache::with_dir(base.join("case").join("metadata"))
+    }
+
+    pub fn with_dir(dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir)?;
+
+        Ok(MetadataCache {
+            dir,
+            client: HttpClient::new(),
+            gateways: IpfsGateways::from_options(&[])?,
+            hits: AtomicUsize::new(0),
+            downloads: AtomicUsize::new(0),
+        })
+    }
+
+    /// Sets the gateways used to download `ipfs://` links.
+    pub fn with_gateways(mut self, gateways: IpfsGateways) -> Self {
+        self.gateways = gateways;
+        self
+    }
+
+    /// Returns the path of the cache entry for the URI.
+    pub fn path(&self, uri: &str) -> PathBuf {
+        let hash = HEXLOWER.encode(digest(&SHA256, uri.as_bytes()).as_ref());
+        self.dir.join(format!("{}.json", hash))
+    }
+
+    /// Returns the metadata JSON for the URI, downloading it only when it is not
+    /// in the cache yet.
+    pub async fn fetch(&self, uri: &str) -> Result<Value> {
+        let path = self.path(uri);
+
+        if let Ok(content) = fs::read(&path) {
+            // a corrupted entry is downloaded again
+            if let Ok(value) = serde_json::from_slice(&content) {
+                self.hits.fetch_add(1, Ordering::SeqCst);
+                return Ok(value);
+            }
+        }
+
+        let content = self.download(uri).await?;
+        let value = serde_json::from_slice(&content)
+            .map_err(|err| anyhow!("Invalid metadata JSON at {}: {}", uri, err))?;
+
+        // written to a temporary file first so an interrupted run does not leave
+        // a truncated entry behind
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, &content)?;
+        fs::rename(&temp, &path)?;
+        self.downloads.fetch_add(1, Ordering::SeqCst);
+
+        Ok(value)
+    }
+
+    /// Downloads the URI, retrying server errors and timeouts with exponential backoff.
+    /// `ipfs://` links are downloaded through the IPFS gateways instead.
+    async fn download(&self, uri: &str) -> Result<Vec<u8>> {
+        if is_ipfs(uri) {
+            return self.gateways.download(&self.client, uri).await;
+        }
+
+        let mut delay = INITIAL_BACKOFF;
+        let mut attempt = 0;
+
+        loop {
+            let retryable = match self.client.get(uri).send().await {
+                Ok(response) if response.status().is_success() => {
+                    return Ok(response.bytes().await?.to_vec());
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    if !(status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS)
+                        || attempt == MAX_RETRIES
+                    {
+                        return Err(anyhow!("Failed to download {}: {}", uri, status));
+                    }
+                    status.to_string()
+                }
+                Err(err) if attempt == MAX_RETRIES => {
+                    return Err(anyhow!("Failed to download {}: {}", uri, err));
+                }
+                Err(err) => err.to_string(),
+            };
+
+            attempt += 1;
+            warn!(
+                "Download of {} failed ({}), retrying in {:?} ({}/{})",
+                uri, retryable, delay, attempt, MAX_RETRIES
+            );
+            sleep(delay).await;
+            delay *= 2;
+        }
+    }
+
+    /// Number of entries served from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::SeqCst)
+    }
+
+    /// Number of entries downloaded.
+    pub fn downloads(&self) -> usize {
+        self.downloads.load(Ordering::SeqCst)
+    }
+
+    /// Health of the IPFS gateways used by the downloads.
+    pub fn gateway_health(&self) -> Vec<GatewayHealth> {
+        self.gateways.health()
+    }
+
+    /// Removes all cached entries.
+    pub fn clear(&self) -> Result<()> {
+        fs::remove_dir_all(&self.dir)?;
+        fs::create_dir_all(&self.dir)?;
+        Ok(())
+    }
+}
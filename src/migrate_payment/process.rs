@@ -0,0 +1,314 @@
+use anchor_client::solana_sdk::program_pack::Pack;
+use anchor_lang::prelude::AccountMeta;
+use console::style;
+use serde::Serialize;
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::Account;
+use tars::{accounts as nft_accounts, instruction as nft_instruction};
+
+use crate::{
+    cache::load_cluster_cache,
+    common::*,
+    config::{get_config_data, update_config_fields, ConfigData},
+    diff::{diff_tars_data, print_diff, print_field_changes, FieldChange, TarsDiff},
+    setup::check_treasury_wallet,
+    tars::{get_tars_state, price_in_base_units, TARS_ID},
+    tx::{send_and_confirm, TxPlan},
+    update::create_tars_data,
+    utils::{assert_correct_authority, check_spl_token, confirm_action, spinner_with_style},
+    verify_config_hash::fill_hidden_settings_hash_from_cache,
+};
+
+/// Decimals of SOL prices (lamports).
+const SOL_DECIMALS: u8 = 9;
+
+pub struct MigratePaymentArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub config: String,
+    pub tars: Option<String>,
+    /// Mint of the new payment token, or "sol".
+    pub to: String,
+    pub price: Option<f64>,
+    pub token_account: Option<String>,
+    pub treasury: Option<String>,
+    pub dry_run: bool,
+    pub skip_confirmation: bool,
+}
+
+/// Result of the migrate-payment command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigratePaymentOutcome {
+    pub tars: String,
+    pub signature: Option<String>,
+    /// Changes to the config file.
+    pub config_changes: Vec<FieldChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<TarsDiff>,
+}
+
+pub fn process_migrate_payment(args: MigratePaymentArgs) -> Result<MigratePaymentOutcome> {
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+    let mut config_data = get_config_data(&args.config, &args.global.address_book)?;
+    // an empty hidden settings hash is the hash of the cache, as used by deploy
+    fill_hidden_settings_hash_from_cache(&mut config_data, &args.cache)?;
+
+    // the tars id specified takes precedence over the one from the cache
+    let tars_id = match args.tars {
+        Some(tars_id) => tars_id,
+        None => {
+            load_cluster_cache(&case_config, &args.cache, false)?
+                .program
+                .tars
+        }
+    };
+    let tars_pubkey = resolve_pubkey(&tars_id, &args.global.address_book)?;
+
+    case_println!(
+        "{} {}Loading tars",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+    case_println!("{} {}", style("Tars ID:").bold(), tars_id);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+    let tars_state = get_tars_state(&case_config, &tars_pubkey)?;
+    pb.finish_with_message("Done");
+
+    assert_correct_authority(&case_config.keypair.pubkey(), &tars_state.authority)?;
+
+    case_println!(
+        "\n{} {}Validating the new payment settings",
+        style("[2/2]").bold().dim(),
+        PAYMENT_EMOJI
+    );
+
+    let mut new_config = config_data.clone();
+    new_config.price = args.price.unwrap_or(config_data.price);
+
+    let (decimals, wallet) = if args.to.eq_ignore_ascii_case("sol") {
+        if args.token_account.is_some() {
+            return Err(anyhow!(
+                "--token-account can only be used with a token mint"
+            ));
+        }
+
+        let treasury = match &args.treasury {
+            Some(treasury) => resolve_pubkey(treasury, &args.global.address_book)?,
+            None => case_config.keypair.pubkey(),
+        };
+
+        new_config.spl_token = None;
+        new_config.spl_token_account = None;
+        new_config.sol_treasury_account = Some(treasury);
+
+        (SOL_DECIMALS, treasury)
+    } else {
+        if args.treasury.is_some() {
+            return Err(anyhow!("--treasury can only be used when migrating to SOL"));
+        }
+
+        let mint = resolve_pubkey(&args.to, &args.global.address_book)?;
+        let token_mint = check_spl_token(&program, &mint.to_string())?;
+
+        let token_account = match &args.token_account {
+            Some(token_account) => resolve_pubkey(token_account, &args.global.address_book)?,
+            None => get_associated_token_address(&program.payer(), &mint),
+        };
+        check_token_account(&program.rpc().get_account_data(&token_account)?, &mint)
+            .map_err(|err| anyhow!("Invalid token account {}: {}", token_account, err))?;
+
+        new_config.spl_token = Some(mint);
+        new_config.spl_token_account = Some(token_account);
+        new_config.sol_treasury_account = None;
+
+        (token_mint.decimals, token_account)
+    };
+
+    // the price must be representable in the base units of the new payment
+    price_in_base_units(new_config.price, decimals)?;
+
+    // the whitelist discount keeps the same ratio to the price
+    if let Some(settings) = &mut new_config.whitelist_mint_settings {
+        if let Some(discount_price) = settings.discount_price() {
+            if config_data.price > 0.0 && new_config.price != config_data.price {
+                let scale = 10f64.powi(decimals.into());
+                let discount = discount_price * new_config.price / config_data.price;
+                settings.set_discount_price(Some((discount * scale).round() / scale));
+            }
+        }
+    }
+
+    check_treasury_wallet(&case_config.keypair.pubkey(), &wallet);
+
+    let tars_data = create_tars_data(&client, &new_config, &tars_state.data)?;
+    let changes = diff_tars_data(
+        Some(&tars_state),
+        &tars_data,
+        &wallet,
+        &new_config.spl_token,
+    );
+    let config_changes = diff_config(&config_data, &new_config)?;
+
+    let mut outcome = MigratePaymentOutcome {
+        tars: tars_pubkey.to_string(),
+        ..Default::default()
+    };
+
+    if args.dry_run {
+        let mut plan = TxPlan::new("migrate-payment", &program.payer());
+
+        if !changes.is_empty() {
+            let mut builder = program
+                .request()
+                .accounts(nft_accounts::UpdateTars {
+                    tars: tars_pubkey,
+                    authority: program.payer(),
+                    wallet,
+                })
+                .args(nft_instruction::UpdateTars { data: tars_data });
+
+            if let Some(token) = new_config.spl_token {
+                builder = builder.accounts(AccountMeta {
+                    pubkey: token,
+                    is_signer: false,
+                    is_writable: false,
+                });
+            }
+
+            plan.add(
+                "update tars",
+                Some(json!(changes)),
+                &builder.instructions()?,
+                &[],
+            );
+        }
+
+        let diff = TarsDiff {
+            tars: Some(tars_pubkey.to_string()),
+            fields: changes,
+            notes: vec![format!("Config file '{}' would be updated.", args.config)],
+            plan_file: Some(args.global.plan_file.clone()),
+            ..Default::default()
+        };
+        plan.notes = diff.notes.clone();
+
+        print_diff(&diff);
+        print_config_changes(&config_changes);
+        plan.save(&args.global.plan_file)?;
+
+        outcome.config_changes = config_changes;
+        outcome.dry_run = Some(diff);
+        return Ok(outcome);
+    }
+
+    if changes.is_empty() && config_changes.is_empty() {
+        case_println!("\nThe tars already accepts this payment, nothing to migrate.");
+        return Ok(outcome);
+    }
+
+    case_println!("\n{}", style("Changed fields:").dim());
+    print_field_changes(&changes);
+    print_config_changes(&config_changes);
+
+    if !confirm_action("Migrate the payment settings?", args.skip_confirmation)? {
+        return Err(anyhow!("Migration cancelled"));
+    }
+
+    if !changes.is_empty() {
+        let mut builder = program
+            .request()
+            .accounts(nft_accounts::UpdateTars {
+                tars: tars_pubkey,
+                authority: program.payer(),
+                wallet,
+            })
+            .args(nft_instruction::UpdateTars { data: tars_data });
+
+        if let Some(token) = new_config.spl_token {
+            builder = builder.accounts(AccountMeta {
+                pubkey: token,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+
+        let pb = spinner_with_style();
+        pb.set_message("Sending update transaction...");
+
+        let signature = send_and_confirm(&case_config, &builder.instructions()?, &[])?;
+        outcome.signature = Some(signature.to_string());
+
+        pb.finish_with_message(format!(
+            "{} {}",
+            style("Update signature:").bold(),
+            signature
+        ));
+    }
+
+    // the config file is only updated once the tars matches it
+    let fields: Vec<(&str, Value)> = config_changes
+        .iter()
+        .map(|change| (change.field.as_str(), change.new.clone()))
+        .collect();
+    update_config_fields(&args.config, &fields)?;
+    info!("Updated payment settings in {}", args.config);
+    case_println!("Config file '{}' updated.", args.config);
+
+    outcome.config_changes = config_changes;
+
+    Ok(outcome)
+}
+
+/// Checks that the account is an initialized token account of the mint.
+fn check_token_account(data: &[u8], mint: &Pubkey) -> Result<()> {
+    let account = Account::unpack(data)?;
+
+    if account.mint != *mint {
+        return Err(anyhow!("account holds tokens of mint {}", account.mint));
+    }
+
+    Ok(())
+}
+
+/// Returns the payment fields that differ between the current and new config.
+fn diff_config(current: &ConfigData, new: &ConfigData) -> Result<Vec<FieldChange>> {
+    let current = serde_json::to_value(current)?;
+    let new = serde_json::to_value(new)?;
+    let discount = |config: &Value| config["whitelistMintSettings"]["discountPrice"].clone();
+
+    let mut changes = Vec::new();
+
+    for field in ["price", "splToken", "splTokenAccount", "solTreasuryAccount"] {
+        if current[field] != new[field] {
+            changes.push(FieldChange {
+                field: field.to_string(),
+                current: current[field].clone(),
+                new: new[field].clone(),
+            });
+        }
+    }
+
+    if discount(&current) != discount(&new) {
+        changes.push(FieldChange {
+            field: "whitelistMintSettings.discountPrice".to_string(),
+            current: discount(&current),
+            new: discount(&new),
+        });
+    }
+
+    Ok(changes)
+}
+
+fn print_config_changes(changes: &[FieldChange]) {
+    if !changes.is_empty() {
+        case_println!("\n{}", style("Config file:").dim());
+        print_field_changes(changes);
+    }
+}
@@ -1,3 +1,9 @@
 pub mod process;
+pub mod receipts;
+pub mod rotation;
+pub mod scheduler;
 
 pub use process::*;
+pub use receipts::*;
+pub use rotation::*;
+pub use scheduler::*;
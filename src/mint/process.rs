@@ -1,7 +1,10 @@
-use std::{str::FromStr, sync::Arc};
+use std::{path::Path, str::FromStr, sync::Arc};
 
 use anchor_client::{
     solana_sdk::{
+        compute_budget::ComputeBudgetInstruction,
+        instruction::Instruction,
+        native_token::LAMPORTS_PER_SOL,
         program_pack::Pack,
         pubkey::Pubkey,
         signature::{Keypair, Signature, Signer},
@@ -11,14 +14,15 @@ use anchor_client::{
 };
 use anchor_lang::prelude::AccountMeta;
 use anyhow::Result;
-use chrono::Utc;
 use console::style;
+use dialoguer::Confirm;
+use serde::Serialize;
 use tars::{
     accounts as nft_accounts, instruction as nft_instruction, TarsError, Tars,
     CollectionPDA, EndSettingType, WhitelistMintMode,
 };
 use mpl_token_metadata::pda::find_collection_authority_account;
-use solana_client::rpc_response::Response;
+use solana_client::{rpc_client::RpcClient, rpc_response::Response};
 use spl_associated_token_account::{create_associated_token_account, get_associated_token_address};
 use spl_token::{
     instruction::{initialize_mint, mint_to},
@@ -27,24 +31,66 @@ use spl_token::{
 };
 
 use crate::{
-    cache::load_cache,
+    budget::{to_sol, FeeBudget},
+    cache::load_cluster_cache,
     tars::{TARS_ID, *},
+    clock::{Clock, SystemClock},
     common::*,
-    config::Cluster,
+    config::{get_config_data, CaseConfig, Cluster},
+    mint::rotation::*,
     pdas::*,
+    rng::{Rng, SystemRng},
+    tx::send_and_confirm,
     utils::*,
+    validate::parser::check_compute_units,
 };
 
 pub struct MintArgs {
+    pub config: String,
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
     pub cache: String,
     pub number: Option<u64>,
     pub tars: Option<String>,
+    pub keypairs: Option<String>,
+    pub max_fee: Option<f64>,
+    pub confirm_above: f64,
+    pub skip_confirmation: bool,
+    pub compute_units: Option<u32>,
+    /// File that a receipt of every successful mint is appended to.
+    pub to_file: Option<String>,
+    /// Gateway token account of the payer, for tars with gatekeeper settings.
+    pub gateway_token: Option<String>,
 }
 
-pub fn process_mint(args: MintArgs) -> Result<()> {
-    let case_config = case_setup(args.keypair, args.rpc_url)?;
+/// Result of the mint command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintOutcome {
+    pub tars: String,
+    pub signatures: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipts_file: Option<String>,
+}
+
+pub fn process_mint(args: MintArgs) -> Result<MintOutcome> {
+    let compute_units =
+        mint_compute_units(&args.config, args.compute_units, &args.global.address_book)?;
+
+    // a gateway token is issued to a single wallet
+    if args.gateway_token.is_some() && args.keypairs.is_some() {
+        return Err(anyhow!(
+            "--gateway-token cannot be used with --keypairs, the gateway token of each payer \
+             is derived instead"
+        ));
+    }
+    let gateway_token = args
+        .gateway_token
+        .as_deref()
+        .map(|authority| resolve_pubkey(authority, &args.global.address_book))
+        .transpose()?;
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
     let client = Arc::new(setup_client(&case_config)?);
 
     // the tars id specified takes precedence over the one from the cache
@@ -52,38 +98,32 @@ pub fn process_mint(args: MintArgs) -> Result<()> {
     let tars_id = match args.tars {
         Some(tars_id) => tars_id,
         None => {
-            let cache = load_cache(&args.cache, false)?;
+            let cache = load_cluster_cache(&case_config, &args.cache, false)?;
             cache.program.tars
         }
     };
 
-    let tars_pubkey = match Pubkey::from_str(&tars_id) {
-        Ok(tars_pubkey) => tars_pubkey,
-        Err(_) => {
-            let error = anyhow!("Failed to parse tars id: {}", tars_id);
-            error!("{:?}", error);
-            return Err(error);
-        }
-    };
+    let tars_pubkey = resolve_pubkey(&tars_id, &args.global.address_book)?;
 
-    println!(
+    case_println!(
         "{} {}Loading tars",
         style("[1/2]").bold().dim(),
         LOOKING_GLASS_EMOJI
     );
-    println!("{} {}", style("Tars ID:").bold(), tars_id);
+    case_println!("{} {}", style("Tars ID:").bold(), tars_id);
 
     let pb = spinner_with_style();
     pb.set_message("Connecting...");
 
     let tars_state = Arc::new(get_tars_state(&case_config, &tars_pubkey)?);
+    check_token_metadata_program(&client.program(TARS_ID), "mint NFTs", None)?;
 
     let collection_pda_info =
         Arc::new(get_collection_pda(&tars_pubkey, &client.program(TARS_ID)).ok());
 
     pb.finish_with_message("Done");
 
-    println!(
+    case_println!(
         "\n{} {}Minting from tars",
         style("[2/2]").bold().dim(),
         TARS_EMOJI
@@ -101,20 +141,107 @@ pub fn process_mint(args: MintArgs) -> Result<()> {
     info!("Minting NFT from tars: {}", &tars_id);
     info!("Tars program id: {:?}", TARS_ID);
 
-    if number == 1 {
+    let mut outcome = MintOutcome {
+        tars: tars_id.clone(),
+        receipts_file: args.to_file.clone(),
+        ..Default::default()
+    };
+
+    // receipts of previous runs are kept
+    let receipts = match &args.to_file {
+        Some(path) => Some(Arc::new(MintReceipts::load(path)?)),
+        None => None,
+    };
+
+    let rpc_client = RpcClient::new(case_config.rpc_url.clone());
+    let budget = Arc::new(FeeBudget::new(args.max_fee));
+
+    let cost_per_mint = if budget.is_limited() || args.keypairs.is_some() || number > 1 {
+        mint_cost_estimate(&rpc_client, &tars_state)?
+    } else {
+        0
+    };
+    budget.check_estimate(cost_per_mint * number)?;
+
+    if let Some(keypairs_dir) = args.keypairs {
+        let keypairs = load_keypairs(
+            &keypairs_dir,
+            &case_config.rpc_url,
+            args.global.insecure_keypair,
+        )?;
+
+        let payers: Vec<(Pubkey, u64)> = keypairs
+            .iter()
+            .map(|keypair| keypair.pubkey())
+            .zip(distribute_mints(keypairs.len(), number))
+            .collect();
+        let balance = check_funding(&rpc_client, &payers, cost_per_mint)?;
+
+        if number > 1 {
+            confirm_bulk_mint(&args, &tars_state, number, cost_per_mint, balance)?;
+        }
+
+        case_println!(
+            "Rotating {} payer keypair(s) across {} mint(s)",
+            keypairs.len(),
+            number
+        );
+
+        let pb = progress_bar_with_style(number);
+
+        match mint_with_rotation(
+            keypairs,
+            &case_config,
+            tars_pubkey,
+            Arc::clone(&tars_state),
+            Arc::clone(&collection_pda_info),
+            compute_units,
+            receipts.clone(),
+            number,
+            Arc::clone(&budget),
+            cost_per_mint,
+            pb.clone(),
+        ) {
+            Ok(signatures) => {
+                outcome
+                    .signatures
+                    .extend(signatures.iter().map(|signature| signature.to_string()));
+                pb.finish();
+            }
+            Err(err) => {
+                pb.abandon_with_message(format!("{}", style("Mint failed ").red().bold()));
+                error!("{:?}", err);
+                return Err(err);
+            }
+        }
+    } else if number == 1 {
         let pb = spinner_with_style();
         pb.set_message(format!(
             "{} item(s) remaining",
             tars_state.data.items_available - tars_state.items_redeemed
         ));
 
-        let result = match mint(
+        let result = match mint_nft(
             Arc::clone(&client),
+            &case_config,
             tars_pubkey,
             Arc::clone(&tars_state),
             Arc::clone(&collection_pda_info),
+            compute_units,
+            gateway_token,
         ) {
-            Ok(signature) => format!("{} {}", style("Signature:").bold(), signature),
+            Ok((signature, mint)) => {
+                if let Some(receipts) = &receipts {
+                    receipts.append(
+                        &tars_pubkey,
+                        &mint,
+                        &case_config.keypair.pubkey(),
+                        &signature,
+                    )?;
+                }
+                outcome.signatures.push(signature.to_string());
+                format!("{} {}", style("Signature:").bold(), signature)
+            }
             Err(err) => {
                 pb.abandon_with_message(format!("{}", style("Mint failed ").red().bold()));
                 error!("{:?}", err);
@@ -124,18 +251,44 @@ pub fn process_mint(args: MintArgs) -> Result<()> {
 
         pb.finish_with_message(result);
     } else {
+        let balance = rpc_client.get_balance(&case_config.keypair.pubkey())?;
+        confirm_bulk_mint(&args, &tars_state, number, cost_per_mint, balance)?;
+
         let pb = progress_bar_with_style(number);
 
         for _i in 0..number {
-            if let Err(err) = mint(
+            // stops before the next mint would go over the budget
+            if let Err(err) = budget.reserve(cost_per_mint) {
+                pb.abandon_with_message(format!("{}", style("Mint stopped ").yellow().bold()));
+                case_println!("{} item(s) minted", outcome.signatures.len());
+                return Err(err);
+            }
+
+            match mint_nft(
                 Arc::clone(&client),
+                &case_config,
                 tars_pubkey,
                 Arc::clone(&tars_state),
                 Arc::clone(&collection_pda_info),
+                compute_units,
+                gateway_token,
             ) {
-                pb.abandon_with_message(format!("{}", style("Mint failed ").red().bold()));
-                error!("{:?}", err);
-                return Err(err);
+                Ok((signature, mint)) => {
+                    if let Some(receipts) = &receipts {
+                        receipts.append(
+                            &tars_pubkey,
+                            &mint,
+                            &case_config.keypair.pubkey(),
+                            &signature,
+                        )?;
+                    }
+                    outcome.signatures.push(signature.to_string());
+                }
+                Err(err) => {
+                    pb.abandon_with_message(format!("{}", style("Mint failed ").red().bold()));
+                    error!("{:?}", err);
+                    return Err(err);
+                }
             }
 
             pb.inc(1);
@@ -144,36 +297,272 @@ pub fn process_mint(args: MintArgs) -> Result<()> {
         pb.finish();
     }
 
+    if let Some(receipts) = &receipts {
+        case_println!(
+            "{} {}",
+            style("Receipts written to:").bold(),
+            receipts.path()
+        );
+    }
+
+    Ok(outcome)
+}
+
+/// Returns the compute unit limit of mint transactions: the one specified or, when the
+/// config file exists, `computeUnits.mint`. Without a limit, mint transactions do not
+/// request compute units and use the runtime default.
+pub fn mint_compute_units(
+    config: &str,
+    compute_units: Option<u32>,
+    address_book: &str,
+) -> Result<Option<u32>> {
+    let compute_units = match compute_units {
+        Some(compute_units) => Some(compute_units),
+        None if Path::new(config).exists() => get_config_data(config, address_book)?
+            .compute_units
+            .and_then(|compute_units| compute_units.mint),
+        None => None,
+    };
+
+    if let Some(compute_units) = compute_units {
+        check_compute_units(compute_units)?;
+    }
+
+    Ok(compute_units)
+}
+
+/// Prints the forecast cost of a bulk mint and asks for confirmation when it is
+/// above the threshold or more than the balance of the payer(s).
+fn confirm_bulk_mint(
+    args: &MintArgs,
+    tars_state: &Tars,
+    number: u64,
+    cost_per_mint: u64,
+    balance: u64,
+) -> Result<()> {
+    let total = cost_per_mint * number;
+    let threshold = (args.confirm_above * LAMPORTS_PER_SOL as f64) as u64;
+
+    case_println!(
+        "{} ◎ {} for {} mint(s) (◎ {} each), balance ◎ {}",
+        style("Forecast:").bold(),
+        to_sol(total),
+        number,
+        to_sol(cost_per_mint),
+        to_sol(balance)
+    );
+
+    if tars_state.token_mint.is_some() {
+        case_println!(
+            "{}",
+            style("The price of the mints is paid in SPL tokens and not included.").dim()
+        );
+    }
+
+    if total > balance {
+        case_println!(
+            "{}",
+            style(format!(
+                "The balance only covers {} of the {} mint(s).",
+                balance / cost_per_mint.max(1),
+                number
+            ))
+            .red()
+            .bold()
+        );
+    } else if total <= threshold {
+        return Ok(());
+    }
+
+    if !args.skip_confirmation
+        && !Confirm::with_theme(&get_dialoguer_theme())
+            .with_prompt(format!("Mint {} item(s) for ◎ {}?", number, to_sol(total)))
+            .default(false)
+            .interact()?
+    {
+        return Err(anyhow!("Mint cancelled"));
+    }
+
+    Ok(())
+}
+
+/// Checks that the payer holds enough tokens to pay the price, since the mint otherwise
+/// fails deep in the program. Offers to create the token account of the payer when it is
+/// missing.
+fn check_token_payment(
+    program: &Program,
+    case_config: &CaseConfig,
+    tars_state: &Tars,
+    token_mint: &Pubkey,
+) -> Result<()> {
+    let payer = program.payer();
+    let mint = check_spl_token(program, &token_mint.to_string())?;
+    let token_account = get_associated_token_address(&payer, token_mint);
+
+    // the whitelist discount may lower the price, the lowest price is required
+    let price = tars_state
+        .data
+        .whitelist_mint_settings
+        .as_ref()
+        .and_then(|settings| settings.discount_price)
+        .map_or(tars_state.data.price, |discount_price| {
+            discount_price.min(tars_state.data.price)
+        });
+
+    let balance = match program.rpc().get_account_data(&token_account) {
+        Ok(data) if !data.is_empty() => Account::unpack_unchecked(&data)?.amount,
+        _ => {
+            case_println!(
+                "{}{}",
+                WARNING_EMOJI,
+                style(format!(
+                    "The payer has no token account for the payment token {}",
+                    token_mint
+                ))
+                .yellow()
+            );
+
+            if is_json_output()
+                || !Confirm::with_theme(&get_dialoguer_theme())
+                    .with_prompt(format!("Create the token account {}?", token_account))
+                    .default(false)
+                    .interact()?
+            {
+                return Err(anyhow!(
+                    "Missing token account {} of the payer for the payment token {}",
+                    token_account,
+                    token_mint
+                ));
+            }
+
+            let instruction = create_associated_token_account(&payer, &payer, token_mint);
+            let signature = send_and_confirm(case_config, &[instruction], &[])?;
+            case_println!("{} {}", style("Token account created:").bold(), signature);
+
+            0
+        }
+    };
+
+    if balance < price {
+        return Err(anyhow!(
+            "Insufficient payment tokens in {}: the price is {} but the payer holds {} ({} short)",
+            token_account,
+            format_token_amount(price, mint.decimals),
+            format_token_amount(balance, mint.decimals),
+            format_token_amount(price - balance, mint.decimals)
+        ));
+    }
+
     Ok(())
 }
 
+/// Formats an amount of base units in the units of a token with `decimals` decimals.
+fn format_token_amount(amount: u64, decimals: u8) -> String {
+    format!(
+        "{:.*}",
+        decimals as usize,
+        amount as f64 / 10f64.powi(decimals.into())
+    )
+}
+
 pub fn mint(
     client: Arc<Client>,
+    case_config: &CaseConfig,
     tars_id: Pubkey,
     tars_state: Arc<Tars>,
     collection_pda_info: Arc<Option<PdaInfo<CollectionPDA>>>,
+    compute_units: Option<u32>,
+    gateway_token: Option<Pubkey>,
 ) -> Result<Signature> {
+    mint_nft(
+        client,
+        case_config,
+        tars_id,
+        tars_state,
+        collection_pda_info,
+        compute_units,
+        gateway_token,
+    )
+    .map(|(signature, _)| signature)
+}
+
+/// Mints an NFT to the payer, returning the signature and the address of the NFT mint.
+/// The client must use the keypair of the config as payer.
+///
+/// When the tars has gatekeeper settings, the gateway token account defaults to the one
+/// derived for the payer and the gatekeeper network.
+pub fn mint_nft(
+    client: Arc<Client>,
+    case_config: &CaseConfig,
+    tars_id: Pubkey,
+    tars_state: Arc<Tars>,
+    collection_pda_info: Arc<Option<PdaInfo<CollectionPDA>>>,
+    compute_units: Option<u32>,
+    gateway_token: Option<Pubkey>,
+) -> Result<(Signature, Pubkey)> {
     let program = client.program(TARS_ID);
     let payer = program.payer();
-    let wallet = tars_state.wallet;
-    let authority = tars_state.authority;
-
     let tars_data = &tars_state.data;
 
-    if let Some(_gatekeeper) = &tars_data.gatekeeper {
+    if tars_state.items_redeemed >= tars_data.items_available {
+        return Err(anyhow!(TarsError::TarsEmpty));
+    }
+
+    check_mint_live(&tars_state, &payer, &SystemClock)?;
+
+    let mut instructions = Vec::new();
+
+    // only requested when a limit is set, the runtime default is enough for most mints
+    if let Some(compute_units) = compute_units {
+        instructions.push(ComputeBudgetInstruction::request_units(compute_units, 0));
+    }
+
+    let (nft_instructions, nft_mint) = mint_instructions(
+        &program,
+        case_config,
+        tars_id,
+        &tars_state,
+        &collection_pda_info,
+        gateway_token,
+        &mut SystemRng::default(),
+    )?;
+    instructions.extend(nft_instructions);
+
+    let metadata_pda = find_metadata_pda(&nft_mint.pubkey());
+    let sig = send_and_confirm(case_config, &instructions, &[&nft_mint])?;
+
+    if let Err(_) | Ok(Response { value: None, .. }) = program
+        .rpc()
+        .get_account_with_commitment(&metadata_pda, CommitmentConfig::processed())
+    {
+        let cluster_param = match get_cluster(program.rpc()).unwrap_or(Cluster::Mainnet) {
+            Cluster::Devnet => "?devnet",
+            Cluster::Mainnet => "",
+        };
         return Err(anyhow!(
-            "Command-line mint disabled (gatekeeper settings in use)"
+            "Minting most likely failed with a bot tax. Check the transaction link for more details: https://explorer.solana.com/tx/{}{}",
+            sig.to_string(),
+            cluster_param,
         ));
-    } else if tars_state.items_redeemed >= tars_data.items_available {
-        return Err(anyhow!(TarsError::TarsEmpty));
     }
 
-    if tars_state.authority != payer {
+    info!("Minted! TxId: {} (through {})", sig, program.rpc().url());
+
+    Ok((sig, nft_mint.pubkey()))
+}
+
+/// Checks that the mint is live for `payer` at the time of `clock`: the go-live date has
+/// passed (or a whitelist presale is active) and the end settings are not reached. The
+/// authority of the tars can always mint.
+pub fn check_mint_live(tars_state: &Tars, payer: &Pubkey, clock: &dyn Clock) -> Result<()> {
+    let tars_data = &tars_state.data;
+
+    if tars_state.authority != *payer {
         // we are not authority, we need to follow the rules
         // 1. go_live_date
         // 2. whitelist mint settings
         // 3. end settings
-        let mint_date = Utc::now().timestamp();
+        let mint_date = clock.timestamp();
         let mut mint_enabled = if let Some(date) = tars_data.go_live_date {
             // mint will be enabled only if the go live date is earlier
             // than the current date
@@ -208,16 +597,34 @@ pub fn mint(
                 }
                 EndSettingType::Amount => {
                     if tars_state.items_redeemed >= end_settings.number {
-                        return Err(anyhow!(
-                            "Tars is not live (end settings amount reached)"
-                        ));
+                        return Err(anyhow!("Tars is not live (end settings amount reached)"));
                     }
                 }
             }
         }
     }
 
-    let nft_mint = Keypair::new();
+    Ok(())
+}
+
+/// Returns the instructions that mint an NFT to the payer of the program, without a
+/// compute budget, and the keypair of the new NFT mint, which also signs the transaction.
+/// Unlike `mint_nft`, it does not check that the mint is live for the payer.
+pub fn mint_instructions(
+    program: &Program,
+    case_config: &CaseConfig,
+    tars_id: Pubkey,
+    tars_state: &Tars,
+    collection_pda_info: &Option<PdaInfo<CollectionPDA>>,
+    gateway_token: Option<Pubkey>,
+    rng: &mut dyn Rng,
+) -> Result<(Vec<Instruction>, Keypair)> {
+    let payer = program.payer();
+    let wallet = tars_state.wallet;
+    let authority = tars_state.authority;
+    let tars_data = &tars_state.data;
+
+    let nft_mint = rng.new_keypair();
     let metaplex_program_id = Pubkey::from_str(METAPLEX_PROGRAM_ID)?;
 
     // Allocate memory for the account
@@ -262,6 +669,41 @@ pub fn mint(
 
     let mut additional_accounts: Vec<AccountMeta> = Vec::new();
 
+    // Check gatekeeper settings (the gateway accounts come first)
+    if let Some(gatekeeper) = &tars_data.gatekeeper {
+        let gateway_token = gateway_token
+            .unwrap_or_else(|| find_gateway_token_pda(&payer, &gatekeeper.gatekeeper_network).0);
+
+        if program.rpc().get_account(&gateway_token).is_err() {
+            return Err(anyhow!(
+                "Gateway token account {} not found: the payer needs a gateway token issued by \
+                 the gatekeeper network {}",
+                gateway_token,
+                gatekeeper.gatekeeper_network
+            ));
+        }
+
+        additional_accounts.push(AccountMeta {
+            pubkey: gateway_token,
+            is_signer: false,
+            is_writable: true,
+        });
+
+        if gatekeeper.expire_on_use {
+            additional_accounts.push(AccountMeta {
+                pubkey: Pubkey::from_str(CIVIC)?,
+                is_signer: false,
+                is_writable: false,
+            });
+
+            additional_accounts.push(AccountMeta {
+                pubkey: find_network_expire_pda(&gatekeeper.gatekeeper_network).0,
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+    }
+
     // Check whitelist mint settings
     if let Some(wl_mint_settings) = &tars_data.whitelist_mint_settings {
         let whitelist_token_account = get_associated_token_address(&payer, &wl_mint_settings.mint);
@@ -313,6 +755,8 @@ pub fn mint(
     }
 
     if let Some(token_mint) = tars_state.token_mint {
+        check_token_payment(program, case_config, tars_state, &token_mint)?;
+
         let user_token_account_info = get_associated_token_address(&payer, &token_mint);
 
         additional_accounts.push(AccountMeta {
@@ -367,8 +811,7 @@ pub fn mint(
         .instruction(init_mint_ix)
         .instruction(create_assoc_account_ix)
         .instruction(mint_to_ix)
-        .instruction(mint_ix[0].clone())
-        .signer(&nft_mint);
+        .instruction(mint_ix[0].clone());
 
     if let Some((collection_pda_pubkey, collection_pda)) = collection_pda_info.as_ref() {
         let collection_authority_record =
@@ -390,24 +833,128 @@ pub fn mint(
             .args(nft_instruction::SetCollectionDuringMint {});
     }
 
-    let sig = builder.send()?;
+    Ok((builder.instructions()?, nft_mint))
+}
 
-    if let Err(_) | Ok(Response { value: None, .. }) = program
-        .rpc()
-        .get_account_with_commitment(&metadata_pda, CommitmentConfig::processed())
-    {
-        let cluster_param = match get_cluster(program.rpc()).unwrap_or(Cluster::Mainnet) {
-            Cluster::Devnet => "?devnet",
-            Cluster::Mainnet => "",
-        };
-        return Err(anyhow!(
-            "Minting most likely failed with a bot tax. Check the transaction link for more details: https://explorer.solana.com/tx/{}{}",
-            sig.to_string(),
-            cluster_param,
-        ));
+#[cfg(test)]
+pub(crate) mod tests {
+    use tars::{EndSettings, TarsData, WhitelistMintSettings};
+
+    use super::*;
+    use crate::clock::FixedClock;
+
+    pub(crate) const GO_LIVE: i64 = 1_700_000_000;
+
+    /// Tars with 10 items available and no other settings than the go-live date.
+    pub(crate) fn tars_state(go_live_date: Option<i64>) -> Tars {
+        Tars {
+            authority: Pubkey::new_unique(),
+            wallet: Pubkey::new_unique(),
+            token_mint: None,
+            items_redeemed: 0,
+            data: TarsData {
+                uuid: String::from("000000"),
+                price: 1,
+                symbol: String::new(),
+                seller_fee_basis_points: 0,
+                max_supply: 0,
+                is_mutable: true,
+                retain_authority: true,
+                go_live_date,
+                end_settings: None,
+                creators: Vec::new(),
+                whitelist_mint_settings: None,
+                hidden_settings: None,
+                items_available: 10,
+                gatekeeper: None,
+            },
+        }
+    }
+
+    fn whitelist(presale: bool) -> Option<WhitelistMintSettings> {
+        Some(WhitelistMintSettings {
+            mode: WhitelistMintMode::BurnEveryTime,
+            mint: Pubkey::new_unique(),
+            presale,
+            discount_price: None,
+        })
     }
 
-    info!("Minted! TxId: {}", sig);
+    fn is_live(tars_state: &Tars, timestamp: i64) -> bool {
+        let payer = Pubkey::new_unique();
+
+        check_mint_live(tars_state, &payer, &FixedClock::at(timestamp)).is_ok()
+    }
+
+    #[test]
+    fn live_only_after_go_live_date() {
+        let tars_state = tars_state(Some(GO_LIVE));
+
+        assert!(!is_live(&tars_state, GO_LIVE - 1));
+        assert!(!is_live(&tars_state, GO_LIVE));
+        assert!(is_live(&tars_state, GO_LIVE + 1));
+    }
 
-    Ok(sig)
+    #[test]
+    fn not_live_without_go_live_date() {
+        assert!(!is_live(&tars_state(None), GO_LIVE));
+    }
+
+    #[test]
+    fn authority_can_always_mint() {
+        let tars_state = tars_state(None);
+        let clock = FixedClock::at(GO_LIVE);
+
+        assert!(check_mint_live(&tars_state, &tars_state.authority, &clock).is_ok());
+    }
+
+    #[test]
+    fn presale_is_live_before_go_live_date() {
+        let mut tars_state = tars_state(Some(GO_LIVE));
+        tars_state.data.whitelist_mint_settings = whitelist(true);
+
+        assert!(is_live(&tars_state, GO_LIVE - 1));
+
+        tars_state.data.go_live_date = None;
+
+        assert!(is_live(&tars_state, GO_LIVE - 1));
+    }
+
+    #[test]
+    fn whitelist_without_presale_waits_for_go_live_date() {
+        let mut tars_state = tars_state(Some(GO_LIVE));
+        tars_state.data.whitelist_mint_settings = whitelist(false);
+
+        assert!(!is_live(&tars_state, GO_LIVE - 1));
+        assert!(is_live(&tars_state, GO_LIVE + 1));
+    }
+
+    #[test]
+    fn not_live_after_end_date() {
+        let end_date = GO_LIVE + 3600;
+        let mut tars_state = tars_state(Some(GO_LIVE));
+        tars_state.data.end_settings = Some(EndSettings {
+            end_setting_type: EndSettingType::Date,
+            number: end_date as u64,
+        });
+
+        assert!(is_live(&tars_state, end_date));
+        assert!(!is_live(&tars_state, end_date + 1));
+    }
+
+    #[test]
+    fn not_live_after_end_amount() {
+        let mut tars_state = tars_state(Some(GO_LIVE));
+        tars_state.data.end_settings = Some(EndSettings {
+            end_setting_type: EndSettingType::Amount,
+            number: 5,
+        });
+        tars_state.items_redeemed = 4;
+
+        assert!(is_live(&tars_state, GO_LIVE + 1));
+
+        tars_state.items_redeemed = 5;
+
+        assert!(!is_live(&tars_state, GO_LIVE + 1));
+    }
 }
@@ -0,0 +1,71 @@
+use std::{fs, sync::Mutex};
+
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::common::*;
+
+/// Receipt of a successful mint, written by `mint --to-file`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintReceipt {
+    /// Position of the receipt in the file, across runs.
+    pub index: usize,
+    pub tars: String,
+    pub mint: String,
+    pub owner: String,
+    pub signature: String,
+    pub timestamp: String,
+}
+
+/// Receipts file that mints are appended to, shared by concurrent mints.
+pub struct MintReceipts {
+    path: String,
+    receipts: Mutex<Vec<MintReceipt>>,
+}
+
+impl MintReceipts {
+    /// Loads the receipts of previous runs, if the file exists.
+    pub fn load(path: &str) -> Result<MintReceipts> {
+        let receipts = if Path::new(path).exists() {
+            serde_json::from_str(&fs::read_to_string(path)?)
+                .map_err(|err| anyhow!("Failed to parse receipts file '{}': {}", path, err))?
+        } else {
+            Vec::new()
+        };
+
+        Ok(MintReceipts {
+            path: path.to_string(),
+            receipts: Mutex::new(receipts),
+        })
+    }
+
+    /// Appends the receipt of a mint and writes the file, so that a failure later on does
+    /// not lose the receipts.
+    pub fn append(
+        &self,
+        tars: &Pubkey,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        signature: &Signature,
+    ) -> Result<()> {
+        let mut receipts = self.receipts.lock().unwrap();
+
+        let index = receipts.len();
+        receipts.push(MintReceipt {
+            index,
+            tars: tars.to_string(),
+            mint: mint.to_string(),
+            owner: owner.to_string(),
+            signature: signature.to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+        });
+
+        fs::write(&self.path, serde_json::to_string_pretty(&*receipts)?)
+            .map_err(|err| anyhow!("Failed to write receipts file '{}': {}", self.path, err))
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
@@ -0,0 +1,236 @@
+use std::{
+    fs,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use anchor_client::solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::read_keypair_file};
+use solana_client::rpc_client::RpcClient;
+use tars::{CollectionPDA, Tars};
+
+use crate::{
+    budget::{nft_rent, FeeBudget},
+    common::*,
+    config::CaseConfig,
+    mint::{is_lock_error, mint_nft, MintReceipts, MintScheduler},
+    pdas::PdaInfo,
+    setup::audit_keypair_permissions,
+    utils::ProgressBar,
+};
+
+/// Number of times a mint rejected due to a write-locked tars account is retried.
+const MAX_LOCK_RETRIES: u32 = 5;
+
+/// Loads all keypair files (`*.json`) from the specified directory. Files readable by
+/// other users are only accepted on mainnet with `allow_insecure`.
+pub fn load_keypairs(dir: &str, rpc_url: &str, allow_insecure: bool) -> Result<Vec<Keypair>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|err| anyhow!("Failed to read keypairs directory {}: {}", dir, err))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .collect();
+    // sorted so the rotation order is stable across runs
+    paths.sort();
+
+    let mut keypairs = Vec::new();
+
+    for path in paths {
+        let path = path_to_string(&path)?;
+        let keypair = read_keypair_file(&path)
+            .map_err(|err| anyhow!("Failed to read keypair file: {}, {}", path, err))?;
+        audit_keypair_permissions(&path, Some(rpc_url), allow_insecure)?;
+        keypairs.push(keypair);
+    }
+
+    if keypairs.is_empty() {
+        return Err(anyhow!("No keypair files found in directory {}", dir));
+    }
+
+    Ok(keypairs)
+}
+
+/// Returns the number of mints assigned to each payer when `number` mints are
+/// distributed round-robin.
+pub fn distribute_mints(payers: usize, number: u64) -> Vec<u64> {
+    (0..payers as u64)
+        .map(|index| number / payers as u64 + u64::from(index < number % payers as u64))
+        .collect()
+}
+
+/// Estimates the lamports spent by a payer on a single mint: the tars price (for
+/// SOL payments), the rent of the new accounts and the transaction fees.
+pub fn mint_cost_estimate(rpc_client: &RpcClient, tars_state: &Tars) -> Result<u64> {
+    let rent = nft_rent(rpc_client)?;
+
+    let price = if tars_state.token_mint.is_none() {
+        tars_state.data.price
+    } else {
+        0
+    };
+
+    // signed by the payer and the mint keypair
+    Ok(price + rent + 2 * SIGNATURE_FEE)
+}
+
+/// Checks that every payer can cover the mints assigned to it. Returns the total
+/// balance of the payers.
+pub fn check_funding(
+    rpc_client: &RpcClient,
+    payers: &[(Pubkey, u64)],
+    cost_per_mint: u64,
+) -> Result<u64> {
+    let mut underfunded = Vec::new();
+    let mut total = 0;
+
+    for (payer, mints) in payers {
+        let required = cost_per_mint * mints;
+        let balance = rpc_client.get_balance(payer)?;
+        total += balance;
+
+        if balance < required {
+            underfunded.push(format!(
+                "{} (balance ◎ {}, required ◎ {})",
+                payer,
+                balance as f64 / LAMPORTS_PER_SOL as f64,
+                required as f64 / LAMPORTS_PER_SOL as f64
+            ));
+        }
+    }
+
+    if !underfunded.is_empty() {
+        let mut message = format!(
+            "{} payer keypair(s) cannot fund their share of the mints:",
+            underfunded.len()
+        );
+
+        for payer in underfunded {
+            message.push_str("\n=> ");
+            message.push_str(&payer);
+        }
+
+        return Err(anyhow!(message));
+    }
+
+    Ok(total)
+}
+
+/// Mints using several payers concurrently; each payer mints its share of the
+/// items on its own thread, with submissions paced by a shared scheduler.
+pub fn mint_with_rotation(
+    keypairs: Vec<Keypair>,
+    case_config: &CaseConfig,
+    tars_pubkey: Pubkey,
+    tars_state: Arc<Tars>,
+    collection_pda_info: Arc<Option<PdaInfo<CollectionPDA>>>,
+    compute_units: Option<u32>,
+    receipts: Option<Arc<MintReceipts>>,
+    number: u64,
+    budget: Arc<FeeBudget>,
+    cost_per_mint: u64,
+    pb: ProgressBar,
+) -> Result<Vec<Signature>> {
+    let shares = distribute_mints(keypairs.len(), number);
+    let signatures = Arc::new(Mutex::new(Vec::new()));
+    let scheduler = Arc::new(MintScheduler::new(keypairs.len()));
+    let mut handles = Vec::new();
+
+    for (keypair, share) in keypairs.into_iter().zip(shares) {
+        if share == 0 {
+            continue;
+        }
+
+        let case_config = case_config.with_keypair(keypair);
+        let tars_state = Arc::clone(&tars_state);
+        let collection_pda_info = Arc::clone(&collection_pda_info);
+        let signatures = Arc::clone(&signatures);
+        let receipts = receipts.clone();
+        let scheduler = Arc::clone(&scheduler);
+        let budget = Arc::clone(&budget);
+        let pb = pb.clone();
+
+        handles.push(thread::spawn(move || -> Result<()> {
+            // the client is not thread-safe, so each payer uses its own
+            let client = Arc::new(setup_client(&case_config)?);
+
+            for _i in 0..share {
+                budget.reserve(cost_per_mint)?;
+                // the retries are counted for each mint
+                let mut retries = 0;
+
+                loop {
+                    let permit = scheduler.acquire();
+
+                    match mint_nft(
+                        Arc::clone(&client),
+                        &case_config,
+                        tars_pubkey,
+                        Arc::clone(&tars_state),
+                        Arc::clone(&collection_pda_info),
+                        compute_units,
+                        None,
+                    ) {
+                        Ok((signature, mint)) => {
+                            permit.finish(None);
+                            signatures.lock().unwrap().push(signature);
+                            if let Some(receipts) = &receipts {
+                                receipts.append(
+                                    &tars_pubkey,
+                                    &mint,
+                                    &case_config.keypair.pubkey(),
+                                    &signature,
+                                )?;
+                            }
+                            break;
+                        }
+                        Err(err) => {
+                            let message = err.to_string();
+                            permit.finish(Some(&message));
+
+                            // the transaction was not processed, so it is safe to retry
+                            if is_lock_error(&message) && retries < MAX_LOCK_RETRIES {
+                                retries += 1;
+                                continue;
+                            }
+
+                            return Err(anyhow!(
+                                "Mint failed for payer {}: {}",
+                                case_config.keypair.pubkey(),
+                                message
+                            ));
+                        }
+                    }
+                }
+
+                pb.set_message(format!("(up to {} in flight) ", scheduler.limit()));
+                pb.inc(1);
+            }
+
+            Ok(())
+        }));
+    }
+
+    let mut errors = Vec::new();
+
+    for handle in handles {
+        match handle.join() {
+            Ok(Ok(())) => (),
+            Ok(Err(err)) => {
+                error!("{:?}", err);
+                errors.push(err.to_string());
+            }
+            Err(_) => errors.push("Mint thread panicked".to_string()),
+        }
+    }
+
+    let signatures = signatures.lock().unwrap().clone();
+
+    if !errors.is_empty() {
+        return Err(anyhow!(
+            "{} ({} mint(s) succeeded)",
+            errors.join("\n"),
+            signatures.len()
+        ));
+    }
+
+    Ok(signatures)
+}
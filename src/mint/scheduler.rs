@@ -0,0 +1,160 @@
+use std::{
+    sync::{Condvar, Mutex},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+/// Weight of the latest observation in the latency moving average.
+const EWMA_WEIGHT: f64 = 0.3;
+
+/// Confirmation latency (relative to the baseline) above which the tars account
+/// is considered contended.
+const CONTENTION_FACTOR: f64 = 1.5;
+
+/// Pause added between submissions for each contended observation.
+const DELAY_STEP: Duration = Duration::from_millis(250);
+
+/// Upper limit for the pause between submissions.
+const MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Errors indicating that the transaction was rejected because the tars account
+/// was write-locked by another transaction; these are safe to retry.
+const LOCK_ERRORS: &[&str] = &["AccountInUse", "Account in use"];
+
+/// Errors indicating slow or dropped transactions under load.
+const CONGESTION_ERRORS: &[&str] = &[
+    "Blockhash not found",
+    "unable to confirm transaction",
+    "timed out",
+];
+
+struct SchedulerState {
+    limit: usize,
+    in_flight: usize,
+    baseline: Option<f64>,
+    average: Option<f64>,
+    delay: Duration,
+    last_submission: Option<Instant>,
+}
+
+/// Paces concurrent mints that write-lock the same tars account. The number of
+/// transactions in flight grows while confirmations stay close to the baseline
+/// latency and is halved (with an increasing pause between submissions) when
+/// latency rises or lock conflicts are observed.
+pub struct MintScheduler {
+    max_concurrency: usize,
+    state: Mutex<SchedulerState>,
+    available: Condvar,
+}
+
+/// Slot for an in-flight mint; released when dropped.
+pub struct MintPermit<'a> {
+    scheduler: &'a MintScheduler,
+    started: Instant,
+}
+
+impl MintScheduler {
+    pub fn new(max_concurrency: usize) -> Self {
+        MintScheduler {
+            max_concurrency: max_concurrency.max(1),
+            state: Mutex::new(SchedulerState {
+                limit: 1,
+                in_flight: 0,
+                baseline: None,
+                average: None,
+                delay: Duration::ZERO,
+                last_submission: None,
+            }),
+            available: Condvar::new(),
+        }
+    }
+
+    /// Blocks until a new mint can be submitted.
+    pub fn acquire(&self) -> MintPermit {
+        let mut state = self.state.lock().unwrap();
+
+        loop {
+            while state.in_flight >= state.limit {
+                state = self.available.wait(state).unwrap();
+            }
+
+            // keeps submissions apart while the account is contended
+            let wait = match state.last_submission {
+                Some(last) => state.delay.saturating_sub(last.elapsed()),
+                None => Duration::ZERO,
+            };
+
+            if wait.is_zero() {
+                break;
+            }
+
+            drop(state);
+            sleep(wait);
+            state = self.state.lock().unwrap();
+        }
+
+        state.in_flight += 1;
+        state.last_submission = Some(Instant::now());
+
+        MintPermit {
+            scheduler: self,
+            started: Instant::now(),
+        }
+    }
+
+    /// Current number of mints allowed in flight.
+    pub fn limit(&self) -> usize {
+        self.state.lock().unwrap().limit
+    }
+
+    fn record(&self, latency: Duration, contended: bool) {
+        let mut state = self.state.lock().unwrap();
+        let latency = latency.as_secs_f64();
+
+        let average = match state.average {
+            Some(average) => EWMA_WEIGHT * latency + (1.0 - EWMA_WEIGHT) * average,
+            None => latency,
+        };
+        state.average = Some(average);
+
+        let baseline = match state.baseline {
+            Some(baseline) if baseline <= latency => baseline,
+            _ => latency,
+        };
+        state.baseline = Some(baseline);
+
+        if contended || average > baseline * CONTENTION_FACTOR {
+            // multiplicative decrease
+            state.limit = (state.limit / 2).max(1);
+            state.delay = (state.delay + DELAY_STEP).min(MAX_DELAY);
+        } else {
+            // additive increase
+            state.limit = (state.limit + 1).min(self.max_concurrency);
+            state.delay = state.delay.saturating_sub(DELAY_STEP);
+        }
+    }
+}
+
+impl MintPermit<'_> {
+    /// Records the outcome of the mint and releases the slot.
+    pub fn finish(self, error: Option<&str>) {
+        let contended = error.map_or(false, |error| {
+            is_lock_error(error) || CONGESTION_ERRORS.iter().any(|e| error.contains(e))
+        });
+        self.scheduler.record(self.started.elapsed(), contended);
+    }
+}
+
+impl Drop for MintPermit<'_> {
+    fn drop(&mut self) {
+        let mut state = self.scheduler.state.lock().unwrap();
+        state.in_flight -= 1;
+        self.scheduler.available.notify_all();
+    }
+}
+
+/// Returns whether the error means that the transaction was not processed because
+/// the tars account was locked.
+pub fn is_lock_error(error: &str) -> bool {
+    LOCK_ERRORS.iter().any(|e| error.contains(e))
+}
@@ -0,0 +1,85 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Format of the console output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable output with progress bars.
+    Text,
+    /// A single JSON document with the result of the command.
+    Json,
+}
+
+/// Flag indicating whether the JSON output mode is active.
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+pub fn set_output_format(format: OutputFormat) {
+    JSON_OUTPUT.store(format == OutputFormat::Json, Ordering::SeqCst);
+}
+
+pub fn is_json_output() -> bool {
+    JSON_OUTPUT.load(Ordering::SeqCst)
+}
+
+/// Prints to the standard output (with a newline) unless the JSON output mode is active.
+#[macro_export]
+macro_rules! case_println {
+    () => {
+        if !$crate::output::is_json_output() {
+            println!();
+        }
+    };
+    ($($arg:tt)*) => {
+        if !$crate::output::is_json_output() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Prints to the standard output unless the JSON output mode is active.
+#[macro_export]
+macro_rules! case_print {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_json_output() {
+            print!($($arg)*);
+        }
+    };
+}
+
+/// Document printed at the end of a command in JSON output mode.
+#[derive(Debug, Serialize)]
+pub struct JsonOutput {
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl JsonOutput {
+    pub fn success(result: Option<Value>) -> Self {
+        JsonOutput {
+            status: "success",
+            result,
+            error: None,
+        }
+    }
+
+    pub fn error(error: String) -> Self {
+        JsonOutput {
+            status: "error",
+            result: None,
+            error: Some(error),
+        }
+    }
+
+    pub fn print(&self) {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(self).expect("Unable to convert output to JSON.")
+        );
+    }
+}
@@ -56,7 +56,7 @@ pub fn parse_case_errors(msg: &str) -> String {
     }
 }
 
-fn find_external_program_error(code: String) -> String {
+pub fn find_external_program_error(code: String) -> String {
     let code = code.to_uppercase();
 
     let parsed_code = if code.contains("0X") {
@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 use anchor_client::{solana_sdk::pubkey::Pubkey, ClientError, Program};
 use anyhow::{anyhow, Result};
 use tars::CollectionPDA;
@@ -8,7 +10,7 @@ use mpl_token_metadata::{
     utils::try_from_slice_checked,
 };
 
-use crate::tars::TARS_ID;
+use crate::{constants::CIVIC, tars::TARS_ID};
 
 pub type PdaInfo<T> = (Pubkey, T);
 
@@ -85,6 +87,27 @@ pub fn find_collection_pda(tars_id: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(collection_seeds, &TARS_ID)
 }
 
+/// Returns the default gateway token account issued to a wallet by a gatekeeper network.
+pub fn find_gateway_token_pda(owner: &Pubkey, gatekeeper_network: &Pubkey) -> (Pubkey, u8) {
+    // default tokens are derived with an empty (zeroed) seed
+    let gateway_seeds = &[
+        owner.as_ref(),
+        "gateway".as_bytes(),
+        &[0; 8],
+        gatekeeper_network.as_ref(),
+    ];
+
+    Pubkey::find_program_address(gateway_seeds, &Pubkey::from_str(CIVIC).unwrap())
+}
+
+/// Returns the account holding the expire feature of a gatekeeper network, checked by the
+/// tars when gateway tokens expire on use.
+pub fn find_network_expire_pda(gatekeeper_network: &Pubkey) -> (Pubkey, u8) {
+    let expire_seeds = &[gatekeeper_network.as_ref(), "expire".as_bytes()];
+
+    Pubkey::find_program_address(expire_seeds, &Pubkey::from_str(CIVIC).unwrap())
+}
+
 pub fn get_collection_pda(
     tars: &Pubkey,
     program: &Program,
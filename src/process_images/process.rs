@@ -0,0 +1,392 @@
+use std::{fs, io::Cursor, sync::Mutex};
+
+use console::style;
+use image::{
+    codecs::{
+        jpeg::JpegEncoder,
+        png::{CompressionType, FilterType as PngFilter, PngEncoder},
+        webp::WebPEncoder,
+    },
+    imageops::FilterType,
+    ColorType, DynamicImage, ImageEncoder,
+};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    canonical_json::to_canonical_json,
+    common::*,
+    config::{get_config_data, ImageFormat, ImageProcessing},
+    upload::{asset_file_name, get_asset_pairs, is_archive, list_files, media_content_type},
+    utils::progress_bar_with_style,
+};
+
+pub struct ProcessImagesArgs {
+    pub assets_dir: String,
+    pub config: String,
+    pub global: GlobalOptions,
+    /// Directory the processed assets are written to [default: the one of the config].
+    pub output_dir: Option<String>,
+}
+
+/// Result of the process-images command (sizes in bytes).
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessImagesOutcome {
+    pub output_dir: String,
+    pub images: usize,
+    pub resized: usize,
+    pub converted: usize,
+    /// Images copied unchanged, since processing did not make them smaller (or their
+    /// format cannot be processed).
+    pub unchanged: usize,
+    pub original_size: u64,
+    pub processed_size: u64,
+    pub thumbnails: usize,
+}
+
+/// Result of the processing of an image.
+struct ProcessedImage {
+    file_name: String,
+    original_size: u64,
+    size: u64,
+    resized: bool,
+    converted: bool,
+    unchanged: bool,
+    thumbnail: bool,
+}
+
+pub fn process_process_images(args: ProcessImagesArgs) -> Result<ProcessImagesOutcome> {
+    let config_data = get_config_data(&args.config, &args.global.address_book)?;
+    let settings = config_data.image_processing.ok_or_else(|| {
+        anyhow!(
+            "Missing \"imageProcessing\" settings in the config file '{}'",
+            args.config
+        )
+    })?;
+    let output_dir = args
+        .output_dir
+        .unwrap_or_else(|| settings.output_dir.clone());
+
+    if let Some(quality) = settings.quality {
+        if !(1..=100).contains(&quality) {
+            return Err(anyhow!(
+                "Invalid image quality {}, expected a value between 1 and 100",
+                quality
+            ));
+        }
+    }
+
+    case_println!(
+        "{} {}Loading assets",
+        style("[1/2]").bold().dim(),
+        ASSETS_EMOJI
+    );
+
+    if is_archive(&args.assets_dir) {
+        return Err(anyhow!(
+            "The images of an archive can't be processed, extract the archive first"
+        ));
+    }
+
+    let asset_pairs = get_asset_pairs(&args.assets_dir)?;
+    let output_path = Path::new(&output_dir);
+
+    if output_path.exists() && fs::canonicalize(output_path)? == fs::canonicalize(&args.assets_dir)?
+    {
+        return Err(anyhow!(
+            "The output directory must be different from the assets directory"
+        ));
+    }
+
+    // asset files of a previous run are removed, so that an image converted to a
+    // different format is not left next to the new one
+    if output_path.is_dir() {
+        for entry in list_files(&output_dir, true)? {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    fs::create_dir_all(output_path)?;
+    if settings.thumbnail_size.is_some() {
+        fs::create_dir_all(output_path.join(THUMBNAILS_DIR))?;
+    }
+
+    case_println!("Found {} asset pair(s)", asset_pairs.len());
+
+    case_println!(
+        "\n{} {}Processing images",
+        style("[2/2]").bold().dim(),
+        IMAGE_EMOJI
+    );
+
+    let pb = progress_bar_with_style(asset_pairs.len() as u64);
+    let errors = Mutex::new(Vec::new());
+
+    let results: Vec<ProcessedImage> = asset_pairs
+        .par_iter()
+        .filter_map(|(index, pair)| {
+            let result = process_asset(
+                &pair.image,
+                &pair.metadata,
+                &pair.animation,
+                output_path,
+                &settings,
+            );
+            pb.inc(1);
+
+            match result {
+                Ok(image) => Some(image),
+                Err(err) => {
+                    error!("Failed to process the image of item {}: {:?}", index, err);
+                    errors
+                        .lock()
+                        .unwrap()
+                        .push(format!("Item {}: {}", index, err));
+                    None
+                }
+            }
+        })
+        .collect();
+
+    let mut errors = errors.into_inner().unwrap();
+
+    if !errors.is_empty() {
+        pb.abandon_with_message(format!("{}", style("Processing failed ").red().bold()));
+        errors.sort();
+
+        let mut message = format!("Failed to process {} image(s):", errors.len());
+        for error in errors {
+            message.push_str("\n=> ");
+            message.push_str(&error);
+        }
+
+        return Err(anyhow!(message));
+    }
+
+    pb.finish_with_message(format!("{}", style("Processing successful").green().bold()));
+
+    let mut outcome = ProcessImagesOutcome {
+        output_dir: output_dir.clone(),
+        images: results.len(),
+        ..Default::default()
+    };
+
+    for image in &results {
+        debug!(
+            "Processed {}: {} -> {} byte(s)",
+            image.file_name, image.original_size, image.size
+        );
+        outcome.resized += image.resized as usize;
+        outcome.converted += image.converted as usize;
+        outcome.unchanged += image.unchanged as usize;
+        outcome.thumbnails += image.thumbnail as usize;
+        outcome.original_size += image.original_size;
+        outcome.processed_size += image.size;
+    }
+
+    let reduction = if outcome.original_size > 0 {
+        100.0 - (outcome.processed_size as f64 * 100.0 / outcome.original_size as f64)
+    } else {
+        0.0
+    };
+
+    case_println!(
+        "\n{} {:.2} MB -> {:.2} MB ({:.1}% smaller)",
+        style("Image size:").bold(),
+        outcome.original_size as f64 / 1_000_000.0,
+        outcome.processed_size as f64 / 1_000_000.0,
+        reduction
+    );
+    case_println!(
+        "{} resized, {} converted, {} unchanged",
+        outcome.resized,
+        outcome.converted,
+        outcome.unchanged
+    );
+    if outcome.thumbnails > 0 {
+        case_println!(
+            "{} thumbnail(s) written to '{}'",
+            outcome.thumbnails,
+            output_path.join(THUMBNAILS_DIR).display()
+        );
+    }
+    case_println!(
+        "\nUpload the processed assets with 'case upload {}'.",
+        output_dir
+    );
+
+    Ok(outcome)
+}
+
+/// Processes the image of an asset, writing it to the output directory together with a
+/// copy of the animation and the metadata, whose references to the image are updated
+/// when its format changes.
+fn process_asset(
+    image_path: &str,
+    metadata_path: &str,
+    animation: &Option<String>,
+    output_dir: &Path,
+    settings: &ImageProcessing,
+) -> Result<ProcessedImage> {
+    let original = fs::read(image_path)?;
+    let file_name = asset_file_name(image_path);
+    let (stem, extension) = file_name
+        .rsplit_once('.')
+        .ok_or_else(|| anyhow!("Missing extension of image file '{}'", file_name))?;
+
+    let mut processed = ProcessedImage {
+        file_name: file_name.clone(),
+        original_size: original.len() as u64,
+        size: original.len() as u64,
+        resized: false,
+        converted: false,
+        unchanged: true,
+        thumbnail: false,
+    };
+
+    // formats that can't be processed (e.g. animated gifs) are copied unchanged
+    let (new_name, data) = match ImageFormat::from_extension(extension) {
+        Some(format) => {
+            let image = image::load_from_memory(&original)
+                .map_err(|err| anyhow!("Failed to decode image '{}': {}", file_name, err))?;
+            let quality = settings.quality.unwrap_or(DEFAULT_JPEG_QUALITY);
+
+            let image = match settings.max_dimension {
+                Some(max) if image.width() > max || image.height() > max => {
+                    processed.resized = true;
+                    image.resize(max, max, FilterType::Lanczos3)
+                }
+                _ => image,
+            };
+
+            if let Some(size) = settings.thumbnail_size {
+                let thumbnail = image.thumbnail(size, size);
+                fs::write(
+                    output_dir.join(THUMBNAILS_DIR).join(&file_name),
+                    encode(&thumbnail, format, quality)?,
+                )?;
+                processed.thumbnail = true;
+            }
+
+            // the image is re-encoded in its format, so that the converted image can fall
+            // back to it when it is larger; the original is kept if it is even smaller
+            let mut candidates = vec![(format, encode(&image, format, quality)?)];
+            if let Some(target) = settings.format.filter(|target| *target != format) {
+                candidates.push((target, encode(&image, target, quality)?));
+            }
+            if !processed.resized {
+                candidates.push((format, original));
+            }
+
+            let (chosen, data) = candidates
+                .into_iter()
+                .min_by_key(|(_, data)| data.len())
+                .expect("Failed to select the processed image");
+
+            processed.converted = chosen != format;
+            processed.unchanged = !processed.resized && data.len() as u64 == processed.size;
+            if processed.converted {
+                (format!("{}.{}", stem, chosen.extension()), data)
+            } else {
+                (file_name.clone(), data)
+            }
+        }
+        None => (file_name.clone(), original),
+    };
+
+    processed.size = data.len() as u64;
+    fs::write(output_dir.join(&new_name), data)?;
+
+    if let Some(animation) = animation {
+        fs::copy(animation, output_dir.join(asset_file_name(animation)))?;
+    }
+
+    let metadata_name = asset_file_name(metadata_path);
+    let mut metadata: Value = serde_json::from_reader(File::open(metadata_path)?)
+        .map_err(|err| anyhow!("Failed to read metadata file '{}': {}", metadata_name, err))?;
+
+    if new_name != file_name {
+        update_image_references(&mut metadata, &file_name, &new_name)?;
+    }
+    fs::write(
+        output_dir.join(&metadata_name),
+        to_canonical_json(&metadata)?,
+    )?;
+
+    Ok(processed)
+}
+
+/// Replaces the references to the image file in the `image` and `properties.files` of
+/// the metadata, including the content type of the files.
+fn update_image_references(metadata: &mut Value, old_name: &str, new_name: &str) -> Result<()> {
+    let content_type = media_content_type(new_name)?;
+
+    if let Some(image) = metadata.get_mut("image") {
+        if image.as_str().map(asset_file_name).as_deref() == Some(old_name) {
+            *image = Value::String(new_name.to_string());
+        }
+    }
+
+    let files = metadata
+        .get_mut("properties")
+        .and_then(|properties| properties.get_mut("files"))
+        .and_then(Value::as_array_mut);
+
+    for file in files.into_iter().flatten() {
+        let matches = file
+            .get("uri")
+            .and_then(Value::as_str)
+            .map(asset_file_name)
+            .as_deref()
+            == Some(old_name);
+
+        if let (true, Some(file)) = (matches, file.as_object_mut()) {
+            file.insert("uri".to_string(), Value::String(new_name.to_string()));
+            file.insert("type".to_string(), Value::String(content_type.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Encodes an image: PNG with the best compression, JPEG with the quality and lossless
+/// WebP. JPEG images have no alpha channel.
+fn encode(image: &DynamicImage, format: ImageFormat, quality: u8) -> Result<Vec<u8>> {
+    let mut data = Cursor::new(Vec::new());
+
+    match format {
+        ImageFormat::Jpeg => {
+            let rgb = image.to_rgb8();
+            JpegEncoder::new_with_quality(&mut data, quality).write_image(
+                rgb.as_raw(),
+                rgb.width(),
+                rgb.height(),
+                ColorType::Rgb8,
+            )?;
+        }
+        ImageFormat::Png | ImageFormat::Webp => {
+            // the encoders only accept 8-bit samples
+            let image = match image.color() {
+                ColorType::L8 | ColorType::La8 | ColorType::Rgb8 | ColorType::Rgba8 => {
+                    image.clone()
+                }
+                _ => DynamicImage::ImageRgba8(image.to_rgba8()),
+            };
+            let (width, height, color) = (image.width(), image.height(), image.color());
+
+            if format == ImageFormat::Png {
+                PngEncoder::new_with_quality(&mut data, CompressionType::Best, PngFilter::Adaptive)
+                    .write_image(image.as_bytes(), width, height, color)?;
+            } else {
+                WebPEncoder::new_lossless(&mut data).write_image(
+                    image.as_bytes(),
+                    width,
+                    height,
+                    color,
+                )?;
+            }
+        }
+    }
+
+    Ok(data.into_inner())
+}
@@ -0,0 +1,105 @@
+use std::{env, ffi::OsStr, fs, mem};
+
+use clap::Command;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::common::*;
+
+lazy_static! {
+    /// Files of a profile: "cache.<profile>.<ext>" or "config.<profile>.<ext>".
+    pub static ref PROFILE_FILE_NAME: Regex =
+        Regex::new(r"^(?:cache|config)\.([A-Za-z0-9_-]+)\.(?:json|yaml|yml|toml)$")
+            .expect("Failed to compile profile file regex.");
+}
+
+/// Returns the default cache file, namespaced by the profile.
+pub fn default_cache(profile: Option<&str>) -> String {
+    profile_file(DEFAULT_CACHE, profile)
+}
+
+/// Returns the default config file, namespaced by the profile.
+pub fn default_config(profile: Option<&str>) -> String {
+    profile_file(DEFAULT_CONFIG, profile)
+}
+
+/// Returns the file of a profile, inserting its name before the extension (e.g.
+/// "cache.json" becomes "cache.devnet.json").
+pub fn profile_file(file: &str, profile: Option<&str>) -> String {
+    match (profile, file.rsplit_once('.')) {
+        (Some(profile), Some((stem, extension))) => {
+            format!("{}.{}.{}", stem, profile, extension)
+        }
+        (Some(profile), None) => format!("{}.{}", file, profile),
+        (None, _) => file.to_string(),
+    }
+}
+
+/// Checks that a profile name can be part of a file name.
+pub fn check_profile_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(anyhow!(
+            "Invalid profile name '{}': only letters, digits, '-' and '_' are allowed",
+            name
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns the profile given with `--profile`, or else the one set with `profile switch`.
+/// The profile is needed to build the default values of the command line, so the
+/// `--profile` flag is read before the arguments are parsed.
+pub fn find_active_profile() -> Option<String> {
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            return args.next();
+        } else if let Some(profile) = arg.strip_prefix("--profile=") {
+            return Some(profile.to_string());
+        }
+    }
+
+    fs::read_to_string(PROFILE_FILE)
+        .ok()
+        .map(|profile| profile.trim().to_string())
+        .filter(|profile| !profile.is_empty())
+}
+
+/// Returns the command line with the default cache and config files of its commands
+/// namespaced by the profile.
+pub fn with_profile_defaults(command: Command<'static>, profile: &str) -> Command<'static> {
+    // the default values of the command line are borrowed for the whole run
+    let cache: &'static str = Box::leak(default_cache(Some(profile)).into_boxed_str());
+    let config: &'static str = Box::leak(default_config(Some(profile)).into_boxed_str());
+
+    set_profile_defaults(command, &[(DEFAULT_CACHE, cache), (DEFAULT_CONFIG, config)])
+}
+
+fn set_profile_defaults(
+    mut command: Command<'static>,
+    defaults: &[(&str, &'static str)],
+) -> Command<'static> {
+    for subcommand in command.get_subcommands_mut() {
+        *subcommand = set_profile_defaults(mem::take(subcommand), defaults);
+    }
+
+    for (default, profile_default) in defaults {
+        let ids: Vec<&'static str> = command
+            .get_arguments()
+            .filter(|arg| arg.get_default_values() == [OsStr::new(default)])
+            .map(|arg| arg.get_id())
+            .collect();
+
+        for id in ids {
+            command = command.mut_arg(id, |arg| arg.default_value(profile_default));
+        }
+    }
+
+    command
+}
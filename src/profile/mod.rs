@@ -0,0 +1,5 @@
+pub mod active;
+pub mod process;
+
+pub use active::*;
+pub use process::*;
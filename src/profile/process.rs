@@ -0,0 +1,121 @@
+use std::{collections::BTreeSet, fs};
+
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    common::*,
+    profile::{check_profile_name, profile_file, PROFILE_FILE_NAME},
+};
+
+pub struct ListProfilesArgs {
+    pub global: GlobalOptions,
+}
+
+pub struct SwitchProfileArgs {
+    pub name: Option<String>,
+}
+
+/// Result of the profile commands.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileOutcome {
+    pub active: Option<String>,
+    pub profiles: Vec<String>,
+}
+
+pub fn process_list_profiles(args: ListProfilesArgs) -> Result<ProfileOutcome> {
+    let outcome = ProfileOutcome {
+        active: args.global.profile,
+        profiles: find_profiles()?,
+    };
+
+    if outcome.profiles.is_empty() {
+        case_println!("No profile found (files named like 'cache.<profile>.json').");
+    }
+
+    for profile in &outcome.profiles {
+        if Some(profile) == outcome.active.as_ref() {
+            case_println!("{} {}", style("*").green().bold(), style(profile).bold());
+        } else {
+            case_println!("  {}", profile);
+        }
+    }
+
+    if let Some(active) = &outcome.active {
+        if !outcome.profiles.contains(active) {
+            case_println!(
+                "\n{} {}",
+                style("Active profile (no files yet):").dim(),
+                active
+            );
+        }
+    }
+
+    Ok(outcome)
+}
+
+pub fn process_switch_profile(args: SwitchProfileArgs) -> Result<ProfileOutcome> {
+    let profiles = find_profiles()?;
+
+    match &args.name {
+        Some(name) => {
+            check_profile_name(name)?;
+
+            if let Some(dir) = Path::new(PROFILE_FILE).parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(PROFILE_FILE, name)
+                .map_err(|err| anyhow!("Failed to write {}: {}", PROFILE_FILE, err))?;
+
+            case_println!(
+                "{} {} ({}, {})",
+                style("Active profile:").bold(),
+                name,
+                profile_file(DEFAULT_CONFIG, Some(name)),
+                profile_file(DEFAULT_CACHE, Some(name))
+            );
+
+            if !profiles.contains(name) {
+                case_println!(
+                    "{}",
+                    style("The profile has no files yet, they are created by the next commands.")
+                        .dim()
+                );
+            }
+        }
+        None => {
+            if Path::new(PROFILE_FILE).exists() {
+                fs::remove_file(PROFILE_FILE)
+                    .map_err(|err| anyhow!("Failed to remove {}: {}", PROFILE_FILE, err))?;
+            }
+
+            case_println!(
+                "{} none ({}, {})",
+                style("Active profile:").bold(),
+                DEFAULT_CONFIG,
+                DEFAULT_CACHE
+            );
+        }
+    }
+
+    Ok(ProfileOutcome {
+        active: args.name,
+        profiles,
+    })
+}
+
+/// Returns the profiles with a config or cache file in the current directory.
+fn find_profiles() -> Result<Vec<String>> {
+    let mut profiles = BTreeSet::new();
+
+    for entry in fs::read_dir(".")? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+
+        if let Some(captures) = PROFILE_FILE_NAME.captures(&name) {
+            profiles.insert(captures[1].to_string());
+        }
+    }
+
+    Ok(profiles.into_iter().collect())
+}
@@ -0,0 +1,428 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    fs,
+};
+
+use console::style;
+use futures::{stream, StreamExt};
+use serde::Serialize;
+
+use crate::{
+    cache::load_cache,
+    common::*,
+    ipfs::IpfsGateways,
+    metadata_cache::MetadataCache,
+    upload::{get_archive_asset_pairs, get_asset_pairs, is_archive},
+    utils::{progress_bar_with_style, spinner_with_style},
+};
+
+/// Value counted for the items that do not have a trait type of the collection.
+const MISSING_TRAIT: &str = "None";
+
+/// Number of rarest items printed.
+const RAREST_ITEMS: usize = 5;
+
+pub struct RarityArgs {
+    pub assets_dir: String,
+    pub cache: String,
+    /// Reads the metadata from the links of the cache instead of the assets.
+    pub from_cache: bool,
+    pub ipfs_gateways: Vec<String>,
+    pub output_file: String,
+}
+
+/// Number of items with a trait value.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraitCount {
+    pub trait_type: String,
+    pub value: String,
+    pub count: usize,
+    /// Fraction of the items with the value.
+    pub frequency: f64,
+}
+
+/// Rarity of an item, where the score is the sum of the inverse frequencies of its trait
+/// values (rank 1 is the rarest item).
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemRarity {
+    pub index: String,
+    pub name: String,
+    pub rank: usize,
+    pub score: f64,
+    pub attributes: BTreeMap<String, String>,
+}
+
+/// Report written to the output file.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RarityReport {
+    pub items: usize,
+    pub trait_types: Vec<String>,
+    pub traits: Vec<TraitCount>,
+    pub ranking: Vec<ItemRarity>,
+    /// Indices of the items with the same attributes.
+    pub duplicates: Vec<Vec<String>>,
+}
+
+/// Result of the rarity command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RarityOutcome {
+    pub items: usize,
+    pub trait_types: usize,
+    pub duplicates: Vec<Vec<String>>,
+    pub output_file: String,
+}
+
+/// Name and attributes of the metadata of an item.
+struct ItemMetadata {
+    index: String,
+    name: String,
+    attributes: BTreeMap<String, String>,
+}
+
+pub async fn process_rarity(args: RarityArgs) -> Result<RarityOutcome> {
+    case_println!(
+        "{} {}Loading metadata",
+        style("[1/3]").bold().dim(),
+        ASSETS_EMOJI
+    );
+
+    let mut items = if args.from_cache {
+        load_cache_metadata(&args.cache, &args.ipfs_gateways).await?
+    } else {
+        load_assets_metadata(&args.assets_dir)?
+    };
+
+    if items.is_empty() {
+        return Err(anyhow!("No metadata found to compute the rarity"));
+    }
+    items.sort_by_key(|item| item.index.parse::<isize>().unwrap_or(isize::MAX));
+
+    case_println!(
+        "\n{} {}Computing rarity",
+        style("[2/3]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let report = rarity_report(items);
+
+    case_println!(
+        "{} item(s) with {} trait type(s) and {} trait value(s)",
+        report.items,
+        report.trait_types.len(),
+        report.traits.len()
+    );
+
+    if report.trait_types.is_empty() {
+        case_println!(
+            "{}",
+            style("The metadata has no attributes, every item has the same rarity.").yellow()
+        );
+    }
+
+    for item in report.ranking.iter().take(RAREST_ITEMS) {
+        case_println!(
+            "{} {} (item {}, score {:.2})",
+            style(format!("#{}", item.rank)).bold(),
+            item.name,
+            item.index,
+            item.score
+        );
+    }
+
+    if !report.duplicates.is_empty() {
+        case_println!(
+            "\n{}{}",
+            WARNING_EMOJI,
+            style(format!(
+                "{} group(s) of items have the same attributes:",
+                report.duplicates.len()
+            ))
+            .yellow()
+            .bold()
+        );
+
+        for indices in &report.duplicates {
+            case_println!("{} items {}", style("=>").dim(), indices.join(", "));
+        }
+    }
+
+    case_println!(
+        "\n{} {}Writing report",
+        style("[3/3]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    write_report(&args.output_file, &report)?;
+    case_println!("Rarity report written to '{}'.", args.output_file);
+
+    Ok(RarityOutcome {
+        items: report.items,
+        trait_types: report.trait_types.len(),
+        duplicates: report.duplicates,
+        output_file: args.output_file,
+    })
+}
+
+/// Reads the metadata files of the assets directory (or archive).
+fn load_assets_metadata(assets_dir: &str) -> Result<Vec<ItemMetadata>> {
+    let pb = spinner_with_style();
+    pb.set_message("Reading metadata files...");
+
+    let asset_pairs = if is_archive(assets_dir) {
+        get_archive_asset_pairs(assets_dir, Path::new(DEFAULT_STAGING_DIR))?
+    } else {
+        get_asset_pairs(assets_dir)?
+    };
+
+    let mut items = Vec::new();
+
+    for (index, pair) in asset_pairs.iter().filter(|(index, _)| **index != -1) {
+        let metadata: Value = serde_json::from_reader(File::open(&pair.metadata)?)
+            .map_err(|err| anyhow!("Failed to read metadata of item {}: {}", index, err))?;
+        items.push(item_metadata(index.to_string(), &pair.name, &metadata));
+    }
+
+    pb.finish_with_message(format!("Read {} metadata file(s)", items.len()));
+
+    Ok(items)
+}
+
+/// Downloads the metadata of the cache items (through the local metadata cache).
+async fn load_cache_metadata(cache: &str, ipfs_gateways: &[String]) -> Result<Vec<ItemMetadata>> {
+    let cache = load_cache(cache, false)?;
+    let metadata_cache =
+        MetadataCache::new()?.with_gateways(IpfsGateways::from_options(ipfs_gateways)?);
+
+    let items: Vec<(&String, &CacheItem)> = cache
+        .items
+        .0
+        .iter()
+        .filter(|(index, _)| *index != "-1")
+        .collect();
+
+    if let Some((index, _)) = items.iter().find(|(_, item)| item.metadata_link.is_empty()) {
+        return Err(anyhow!(
+            "Item {} has no metadata link, upload the assets or read them from the assets \
+             directory",
+            index
+        ));
+    }
+
+    let pb = progress_bar_with_style(items.len() as u64);
+    let metadata_cache = &metadata_cache;
+    let progress = &pb;
+
+    let results: Vec<Result<ItemMetadata>> = stream::iter(items)
+        .map(|(index, item)| async move {
+            let result = metadata_cache
+                .fetch(&item.metadata_link)
+                .await
+                .map(|metadata| item_metadata(index.clone(), &item.name, &metadata))
+                .map_err(|err| anyhow!("Item {}: {}", index, err));
+            progress.inc(1);
+            result
+        })
+        .buffer_unordered(PARALLEL_LIMIT)
+        .collect()
+        .await;
+
+    let (items, errors): (Vec<_>, Vec<_>) = results.into_iter().partition(Result::is_ok);
+
+    if !errors.is_empty() {
+        pb.abandon_with_message(format!("{}", style("Download failed ").red().bold()));
+
+        let mut message = format!("Failed to download {} metadata file(s):", errors.len());
+        for error in errors.into_iter().filter_map(Result::err) {
+            message.push_str("\n=> ");
+            message.push_str(&error.to_string());
+        }
+
+        return Err(anyhow!(message));
+    }
+
+    pb.finish_with_message(format!(
+        "Downloaded {} metadata file(s) ({} from local cache)",
+        items.len(),
+        metadata_cache.hits()
+    ));
+
+    Ok(items.into_iter().filter_map(Result::ok).collect())
+}
+
+/// Returns the attributes of the metadata, where non-string values (e.g. numbers) are
+/// counted by their JSON representation.
+fn item_metadata(index: String, name: &str, metadata: &Value) -> ItemMetadata {
+    let mut attributes = BTreeMap::new();
+
+    for attribute in metadata
+        .get("attributes")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let trait_type = attribute.get("trait_type").and_then(Value::as_str);
+        let value = attribute.get("value").map(|value| match value {
+            Value::String(value) => value.clone(),
+            value => value.to_string(),
+        });
+
+        if let (Some(trait_type), Some(value)) = (trait_type, value) {
+            attributes.insert(trait_type.to_string(), value);
+        }
+    }
+
+    ItemMetadata {
+        index,
+        name: metadata
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or(name)
+            .to_string(),
+        attributes,
+    }
+}
+
+/// Counts the trait values, scores and ranks the items and finds the items with the same
+/// attributes. Items without a trait type are counted with the value "None", so that
+/// missing a trait is also rare when most items have it.
+fn rarity_report(items: Vec<ItemMetadata>) -> RarityReport {
+    let total = items.len();
+    let trait_types: BTreeSet<String> = items
+        .iter()
+        .flat_map(|item| item.attributes.keys().cloned())
+        .collect();
+
+    let value_of = |item: &ItemMetadata, trait_type: &str| -> String {
+        item.attributes
+            .get(trait_type)
+            .cloned()
+            .unwrap_or_else(|| MISSING_TRAIT.to_string())
+    };
+
+    let mut counts: BTreeMap<(String, String), usize> = BTreeMap::new();
+    for item in &items {
+        for trait_type in &trait_types {
+            *counts
+                .entry((trait_type.clone(), value_of(item, trait_type)))
+                .or_default() += 1;
+        }
+    }
+
+    let mut ranking: Vec<ItemRarity> = items
+        .iter()
+        .map(|item| ItemRarity {
+            index: item.index.clone(),
+            name: item.name.clone(),
+            rank: 0,
+            score: trait_types
+                .iter()
+                .map(|trait_type| {
+                    let count = counts[&(trait_type.clone(), value_of(item, trait_type))];
+                    total as f64 / count as f64
+                })
+                .sum(),
+            attributes: item.attributes.clone(),
+        })
+        .collect();
+
+    // the sort is stable, items with the same score stay in index order
+    ranking.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    for position in 0..ranking.len() {
+        ranking[position].rank =
+            if position > 0 && ranking[position].score == ranking[position - 1].score {
+                ranking[position - 1].rank
+            } else {
+                position + 1
+            };
+    }
+
+    let mut combinations: BTreeMap<&BTreeMap<String, String>, Vec<String>> = BTreeMap::new();
+    for item in items.iter().filter(|item| !item.attributes.is_empty()) {
+        combinations
+            .entry(&item.attributes)
+            .or_default()
+            .push(item.index.clone());
+    }
+    let mut duplicates: Vec<Vec<String>> = combinations
+        .into_values()
+        .filter(|indices| indices.len() > 1)
+        .collect();
+    duplicates.sort_by_key(|indices| indices[0].parse::<isize>().unwrap_or(isize::MAX));
+
+    let mut traits: Vec<TraitCount> = counts
+        .into_iter()
+        .map(|((trait_type, value), count)| TraitCount {
+            trait_type,
+            value,
+            count,
+            frequency: count as f64 / total as f64,
+        })
+        .collect();
+    traits.sort_by(|a, b| a.trait_type.cmp(&b.trait_type).then(a.count.cmp(&b.count)));
+
+    RarityReport {
+        items: total,
+        trait_types: trait_types.into_iter().collect(),
+        traits,
+        ranking,
+        duplicates,
+    }
+}
+
+/// Writes the ranking as CSV (one column per trait type) when the file has a `.csv`
+/// extension, otherwise the whole report as JSON.
+fn write_report(path: &str, report: &RarityReport) -> Result<()> {
+    let is_csv = Path::new(path)
+        .extension()
+        .map(|extension| extension.eq_ignore_ascii_case("csv"))
+        .unwrap_or(false);
+
+    let content = if is_csv {
+        let mut csv = String::from("rank,index,name,score");
+        for trait_type in &report.trait_types {
+            csv.push(',');
+            csv.push_str(&csv_field(trait_type));
+        }
+        csv.push('\n');
+
+        for item in &report.ranking {
+            csv.push_str(&format!(
+                "{},{},{},{:.4}",
+                item.rank,
+                item.index,
+                csv_field(&item.name),
+                item.score
+            ));
+            for trait_type in &report.trait_types {
+                csv.push(',');
+                csv.push_str(&csv_field(
+                    item.attributes
+                        .get(trait_type)
+                        .map(String::as_str)
+                        .unwrap_or(MISSING_TRAIT),
+                ));
+            }
+            csv.push('\n');
+        }
+        csv
+    } else {
+        serde_json::to_string_pretty(report)?
+    };
+
+    fs::write(path, content)
+        .map_err(|err| anyhow!("Failed to write rarity report '{}': {}", path, err))
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains(&[',', '"', '\n'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
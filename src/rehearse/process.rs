@@ -0,0 +1,492 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anchor_client::solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{Instruction, InstructionError},
+    transaction::TransactionError,
+};
+use chrono::{TimeZone, Utc};
+use console::style;
+use serde::Serialize;
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcSimulateTransactionConfig};
+use tars::{CollectionPDA, Tars};
+
+use crate::{
+    cache::load_cluster_cache,
+    clock::{Clock, SystemClock},
+    common::*,
+    config::{go_live_date_as_timestamp, CaseConfig},
+    mint::{distribute_mints, mint_compute_units, mint_instructions},
+    parse::find_external_program_error,
+    pdas::{get_collection_pda, PdaInfo},
+    rng::SystemRng,
+    tars::{get_tars_state, TARS_ID},
+    utils::{progress_bar_with_style, spinner_with_style},
+};
+
+pub struct RehearseGoliveArgs {
+    pub config: String,
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub tars: Option<String>,
+    /// Time of the rehearsal [default: the start of the next minute].
+    pub at: Option<String>,
+    /// Number of mints simulated.
+    pub burst: u64,
+    /// Number of mints simulated at the same time.
+    pub concurrency: usize,
+    pub compute_units: Option<u32>,
+    /// Priority fee (in lamports) of the mint transactions.
+    pub priority_fee: u32,
+}
+
+/// Time (in milliseconds) to build and simulate a mint.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyStats {
+    pub min_ms: u64,
+    pub median_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+}
+
+/// Result of the rehearse-golive command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RehearseGoliveOutcome {
+    pub tars: String,
+    pub rpc_url: String,
+    pub scheduled_at: String,
+    /// Delay (in milliseconds) between the scheduled time and the start of the slowest
+    /// worker, which shows how busy the machine is.
+    pub start_delay_ms: u64,
+    pub burst: u64,
+    pub concurrency: usize,
+    pub succeeded: u64,
+    pub failed: u64,
+    /// Number of failed simulations by error.
+    pub errors: BTreeMap<String, u64>,
+    pub latency: Option<LatencyStats>,
+    pub duration_ms: u64,
+    pub compute_unit_limit: Option<u32>,
+    pub max_units_consumed: Option<u64>,
+    pub priority_fee: u32,
+    /// Transaction fees (in lamports) of a mint.
+    pub fee_per_mint: u64,
+}
+
+/// Simulated mint: the time to build and simulate it and the compute units consumed.
+struct Simulation {
+    latency: Duration,
+    result: Result<Option<u64>>,
+}
+
+pub fn process_rehearse_golive(args: RehearseGoliveArgs) -> Result<RehearseGoliveOutcome> {
+    let compute_units =
+        mint_compute_units(&args.config, args.compute_units, &args.global.address_book)?;
+
+    // the priority fee is paid on the compute units requested
+    if args.priority_fee > 0 && compute_units.is_none() {
+        return Err(anyhow!(
+            "--priority-fee needs a compute unit limit, set \"computeUnits.mint\" in the config \
+             file or use --compute-units"
+        ));
+    }
+
+    if args.burst == 0 || args.concurrency == 0 {
+        return Err(anyhow!(
+            "--burst and --concurrency must be greater than zero"
+        ));
+    }
+
+    let scheduled = rehearsal_time(&args.at, &SystemClock)?;
+
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = setup_client(&case_config)?;
+
+    // the tars id specified takes precedence over the one from the cache
+    let tars_id = match args.tars {
+        Some(tars_id) => tars_id,
+        None => {
+            let cache = load_cluster_cache(&case_config, &args.cache, false)?;
+            cache.program.tars
+        }
+    };
+    let tars_pubkey = resolve_pubkey(&tars_id, &args.global.address_book)?;
+
+    case_println!(
+        "{} {}Loading tars",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+    case_println!("{} {}", style("Tars ID:").bold(), tars_id);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    let tars_state = Arc::new(get_tars_state(&case_config, &tars_pubkey)?);
+    let collection_pda_info =
+        Arc::new(get_collection_pda(&tars_pubkey, &client.program(TARS_ID)).ok());
+
+    pb.finish_with_message("Done");
+
+    let mut outcome = RehearseGoliveOutcome {
+        tars: tars_pubkey.to_string(),
+        rpc_url: case_config.rpc_url.clone(),
+        scheduled_at: Utc.timestamp(scheduled, 0).to_rfc3339(),
+        burst: args.burst,
+        concurrency: args.concurrency,
+        compute_unit_limit: compute_units,
+        priority_fee: args.priority_fee,
+        // signed by the payer and the mint keypair
+        fee_per_mint: 2 * SIGNATURE_FEE + args.priority_fee as u64,
+        ..Default::default()
+    };
+
+    case_println!(
+        "\n{} {}Rehearsing go-live",
+        style("[2/2]").bold().dim(),
+        CLOCK_EMOJI
+    );
+    case_println!(
+        "{} {} simulated mint(s) at {}, {} at a time (nothing is sent)",
+        style("Burst:").bold(),
+        args.burst,
+        outcome.scheduled_at,
+        args.concurrency
+    );
+
+    // the program only lets the authority mint before go-live
+    let live = tars_state
+        .data
+        .go_live_date
+        .map_or(false, |date| date <= scheduled);
+    if tars_state.authority != case_config.keypair.pubkey() && !live {
+        case_println!(
+            "{}{}",
+            WARNING_EMOJI,
+            style(
+                "The tars is not live at the rehearsal time, the simulations of a payer other \
+                 than the authority will fail"
+            )
+            .yellow()
+        );
+    }
+
+    let delay = (scheduled * 1000 - Utc::now().timestamp_millis()).max(0) as u64;
+    let start = Instant::now() + Duration::from_millis(delay);
+
+    // the workers are set up before the scheduled time, so that only the mints are timed
+    let simulations = Arc::new(Mutex::new(Vec::new()));
+    let start_delays = Arc::new(Mutex::new(Vec::new()));
+    let pb = progress_bar_with_style(args.burst);
+    let mut handles = Vec::new();
+
+    for share in distribute_mints(args.concurrency, args.burst) {
+        if share == 0 {
+            continue;
+        }
+
+        let case_config =
+            case_config.with_keypair(Keypair::from_bytes(&case_config.keypair.to_bytes())?);
+        let tars_state = Arc::clone(&tars_state);
+        let collection_pda_info = Arc::clone(&collection_pda_info);
+        let simulations = Arc::clone(&simulations);
+        let start_delays = Arc::clone(&start_delays);
+        let priority_fee = args.priority_fee;
+        let pb = pb.clone();
+
+        handles.push(thread::spawn(move || -> Result<()> {
+            // the client is not thread-safe, so each worker uses its own
+            let client = setup_client(&case_config)?;
+            let program = client.program(TARS_ID);
+            let rpc_client = RpcClient::new_with_commitment(
+                case_config.rpc_url.clone(),
+                CommitmentConfig::confirmed(),
+            );
+
+            thread::sleep(start.saturating_duration_since(Instant::now()));
+            start_delays.lock().unwrap().push(start.elapsed());
+
+            for _i in 0..share {
+                let started = Instant::now();
+                let result = simulate_mint(
+                    &program,
+                    &rpc_client,
+                    &case_config,
+                    tars_pubkey,
+                    &tars_state,
+                    &collection_pda_info,
+                    compute_units
+                        .map(|units| ComputeBudgetInstruction::request_units(units, priority_fee)),
+                );
+
+                simulations.lock().unwrap().push(Simulation {
+                    latency: started.elapsed(),
+                    result,
+                });
+                pb.inc(1);
+            }
+
+            Ok(())
+        }));
+    }
+
+    // the progress bar counts down until the burst
+    while Instant::now() < start {
+        pb.set_message(format!(
+            "Firing in {} second(s)... ",
+            start.saturating_duration_since(Instant::now()).as_secs() + 1
+        ));
+        thread::sleep(
+            Duration::from_millis(250).min(start.saturating_duration_since(Instant::now())),
+        );
+    }
+    pb.set_message("");
+
+    for handle in handles {
+        match handle.join() {
+            Ok(Ok(())) => (),
+            Ok(Err(err)) => return Err(err),
+            Err(_) => return Err(anyhow!("Rehearsal thread panicked")),
+        }
+    }
+    outcome.duration_ms = start.elapsed().as_millis() as u64;
+    pb.finish();
+
+    outcome.start_delay_ms = start_delays
+        .lock()
+        .unwrap()
+        .iter()
+        .max()
+        .map_or(0, |delay| delay.as_millis() as u64);
+
+    let simulations = std::mem::take(&mut *simulations.lock().unwrap());
+    let mut latencies = Vec::new();
+
+    for simulation in simulations {
+        latencies.push(simulation.latency);
+
+        match simulation.result {
+            Ok(units_consumed) => {
+                outcome.succeeded += 1;
+                outcome.max_units_consumed = outcome.max_units_consumed.max(units_consumed);
+            }
+            Err(err) => {
+                outcome.failed += 1;
+                *outcome.errors.entry(err.to_string()).or_default() += 1;
+            }
+        }
+    }
+
+    outcome.latency = latency_stats(latencies);
+    print_report(&outcome);
+
+    Ok(outcome)
+}
+
+/// Returns the time (unix timestamp) of the rehearsal: the date specified or the start of
+/// the next minute, which must not be in the past.
+fn rehearsal_time(at: &Option<String>, clock: &dyn Clock) -> Result<i64> {
+    let scheduled = match go_live_date_as_timestamp(at)? {
+        Some(scheduled) => scheduled,
+        None => {
+            let now = clock.timestamp();
+            now - now % 60 + 60
+        }
+    };
+
+    if scheduled * 1000 < clock.now().timestamp_millis() {
+        return Err(anyhow!(
+            "The rehearsal time {} is in the past",
+            Utc.timestamp(scheduled, 0).to_rfc3339()
+        ));
+    }
+
+    Ok(scheduled)
+}
+
+/// Builds a mint and simulates it with preflight checks, returning the compute units
+/// consumed.
+fn simulate_mint(
+    program: &Program,
+    rpc_client: &RpcClient,
+    case_config: &CaseConfig,
+    tars_pubkey: Pubkey,
+    tars_state: &Tars,
+    collection_pda_info: &Option<PdaInfo<CollectionPDA>>,
+    compute_budget: Option<Instruction>,
+) -> Result<Option<u64>> {
+    let mut instructions: Vec<Instruction> = compute_budget.into_iter().collect();
+
+    let (nft_instructions, nft_mint) = mint_instructions(
+        program,
+        case_config,
+        tars_pubkey,
+        tars_state,
+        collection_pda_info,
+        None,
+        &mut SystemRng::default(),
+    )?;
+    instructions.extend(nft_instructions);
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&case_config.keypair.pubkey()),
+        &[&case_config.keypair, &nft_mint],
+        blockhash,
+    );
+
+    let response = rpc_client.simulate_transaction_with_config(
+        &transaction,
+        RpcSimulateTransactionConfig {
+            sig_verify: true,
+            commitment: Some(CommitmentConfig::confirmed()),
+            ..Default::default()
+        },
+    )?;
+
+    match response.value.err {
+        Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+            Err(anyhow!(find_external_program_error(code.to_string())))
+        }
+        Some(err) => Err(anyhow!("Simulation failed: {}", err)),
+        None => Ok(response.value.units_consumed),
+    }
+}
+
+fn latency_stats(mut latencies: Vec<Duration>) -> Option<LatencyStats> {
+    if latencies.is_empty() {
+        return None;
+    }
+    latencies.sort_unstable();
+
+    let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100].as_millis() as u64;
+
+    Some(LatencyStats {
+        min_ms: percentile(0),
+        median_ms: percentile(50),
+        p95_ms: percentile(95),
+        max_ms: percentile(100),
+    })
+}
+
+fn print_report(outcome: &RehearseGoliveOutcome) {
+    case_println!(
+        "\n{} {}/{} simulated mint(s) succeeded in {} ms",
+        style("Result:").bold(),
+        outcome.succeeded,
+        outcome.burst,
+        outcome.duration_ms
+    );
+
+    if let Some(latency) = &outcome.latency {
+        case_println!(
+            "{} min {} ms, median {} ms, p95 {} ms, max {} ms",
+            style("Latency:").bold(),
+            latency.min_ms,
+            latency.median_ms,
+            latency.p95_ms,
+            latency.max_ms
+        );
+    }
+
+    case_println!(
+        "{} {} ms after the scheduled time",
+        style("Start delay:").bold(),
+        outcome.start_delay_ms
+    );
+
+    let units_consumed = outcome
+        .max_units_consumed
+        .map_or("unknown".to_string(), |units| units.to_string());
+    match outcome.compute_unit_limit {
+        Some(limit) => case_println!(
+            "{} up to {} consumed of {} requested",
+            style("Compute units:").bold(),
+            units_consumed,
+            limit
+        ),
+        None => case_println!(
+            "{} up to {} consumed (no limit requested)",
+            style("Compute units:").bold(),
+            units_consumed
+        ),
+    }
+
+    case_println!(
+        "{} {} lamport(s) per mint (priority fee {})",
+        style("Fees:").bold(),
+        outcome.fee_per_mint,
+        outcome.priority_fee
+    );
+
+    if outcome.start_delay_ms > REHEARSAL_MAX_START_DELAY_MS {
+        case_println!(
+            "{}{}",
+            WARNING_EMOJI,
+            style(format!(
+                "The burst started {} ms late, the machine may be too busy at go-live",
+                outcome.start_delay_ms
+            ))
+            .yellow()
+        );
+    }
+
+    if !outcome.errors.is_empty() {
+        case_println!(
+            "\n{}",
+            style(format!("{} simulated mint(s) failed:", outcome.failed))
+                .red()
+                .bold()
+        );
+
+        for (error, count) in &outcome.errors {
+            case_println!("{} {} ({}x)", style("=>").dim(), error, count);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+
+    const NOW: i64 = 1_700_000_030;
+
+    #[test]
+    fn rehearsal_defaults_to_next_minute() {
+        let scheduled = rehearsal_time(&None, &FixedClock::at(NOW)).unwrap();
+
+        assert_eq!(scheduled, 1_700_000_040);
+    }
+
+    #[test]
+    fn rehearsal_at_given_time() {
+        let at = Some((NOW + 3600).to_string());
+
+        assert_eq!(
+            rehearsal_time(&at, &FixedClock::at(NOW)).unwrap(),
+            NOW + 3600
+        );
+        assert_eq!(
+            rehearsal_time(&at, &FixedClock::at(NOW + 3600)).unwrap(),
+            NOW + 3600
+        );
+    }
+
+    #[test]
+    fn rehearsal_in_the_past_is_rejected() {
+        let at = Some((NOW - 1).to_string());
+
+        assert!(rehearsal_time(&at, &FixedClock::at(NOW)).is_err());
+    }
+}
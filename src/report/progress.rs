@@ -0,0 +1,221 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use console::style;
+use serde::Serialize;
+
+use crate::{cache::load_cluster_cache, common::*, tars::get_tars_state};
+
+/// Header of the progress CSV file.
+const CSV_HEADER: &str = "timestamp,elapsed_secs,items_redeemed,items_available,minted,\
+                          velocity_per_min,average_per_min,projected_sell_out,stalled";
+
+pub struct ReportProgressArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub tars: Option<String>,
+    pub interval: u64,
+    pub stall_after: u64,
+    pub samples: Option<u64>,
+    pub output_file: String,
+}
+
+/// Result of the report progress command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportProgressOutcome {
+    pub tars: String,
+    pub output_file: String,
+    pub samples: u64,
+    pub items_redeemed: u64,
+    pub items_available: u64,
+    pub sold_out: bool,
+    pub stalls: u64,
+}
+
+/// Sample of the redeemed counter.
+#[derive(Clone)]
+struct Sample {
+    time: DateTime<Utc>,
+    elapsed: Duration,
+    items_redeemed: u64,
+}
+
+pub fn process_report_progress(args: ReportProgressArgs) -> Result<ReportProgressOutcome> {
+    if args.interval == 0 {
+        return Err(anyhow!("The sampling interval must be greater than zero"));
+    }
+
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+
+    // the tars id specified takes precedence over the one from the cache
+    let tars_id = match args.tars {
+        Some(tars_id) => tars_id,
+        None => {
+            load_cluster_cache(&case_config, &args.cache, false)?
+                .program
+                .tars
+        }
+    };
+
+    let tars_pubkey = resolve_pubkey(&tars_id, &args.global.address_book)?;
+
+    case_println!(
+        "{} {}Sampling tars every {} second(s)",
+        style("[1/1]").bold().dim(),
+        LOOKING_GLASS_EMOJI,
+        args.interval
+    );
+    case_println!("{} {}", style("Tars ID:").bold(), tars_id);
+    case_println!("{} {}\n", style("Writing to:").bold(), args.output_file);
+
+    // rows are appended (and flushed) as they are sampled so the file can be
+    // followed by a dashboard while the sale is running
+    let new_file = !Path::new(&args.output_file).exists();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&args.output_file)
+        .map_err(|err| anyhow!("Failed to open '{}': {}", args.output_file, err))?;
+
+    if new_file {
+        writeln!(file, "{}", CSV_HEADER)?;
+    }
+
+    let start = Instant::now();
+    let stall_after = Duration::from_secs(args.stall_after);
+    let mut first: Option<Sample> = None;
+    let mut previous: Option<Sample> = None;
+    let mut last_mint = start;
+    let mut outcome = ReportProgressOutcome {
+        tars: tars_id,
+        output_file: args.output_file.clone(),
+        ..Default::default()
+    };
+
+    loop {
+        let state = get_tars_state(&case_config, &tars_pubkey)?;
+        let sample = Sample {
+            time: Utc::now(),
+            elapsed: start.elapsed(),
+            items_redeemed: state.items_redeemed,
+        };
+        let items_available = state.data.items_available;
+
+        let minted = previous
+            .as_ref()
+            .map(|previous| {
+                sample
+                    .items_redeemed
+                    .saturating_sub(previous.items_redeemed)
+            })
+            .unwrap_or(0);
+
+        if minted > 0 {
+            last_mint = Instant::now();
+        }
+
+        let velocity = previous
+            .as_ref()
+            .map(|previous| per_minute(minted, sample.elapsed - previous.elapsed))
+            .unwrap_or(0.0);
+        let average = first
+            .as_ref()
+            .map(|first| {
+                per_minute(
+                    sample.items_redeemed.saturating_sub(first.items_redeemed),
+                    sample.elapsed - first.elapsed,
+                )
+            })
+            .unwrap_or(0.0);
+
+        let sold_out = sample.items_redeemed >= items_available;
+        let stalled = !sold_out && last_mint.elapsed() >= stall_after;
+
+        // projected from the average velocity since the first sample
+        let projected = if sold_out {
+            Some(sample.time)
+        } else if average > 0.0 {
+            let remaining = (items_available - sample.items_redeemed) as f64;
+            let seconds = (remaining / average * 60.0).ceil() as i64;
+            Some(sample.time + chrono::Duration::seconds(seconds))
+        } else {
+            None
+        };
+
+        writeln!(
+            file,
+            "{},{},{},{},{},{:.2},{:.2},{},{}",
+            sample.time.to_rfc3339(),
+            sample.elapsed.as_secs(),
+            sample.items_redeemed,
+            items_available,
+            minted,
+            velocity,
+            average,
+            projected.map(|time| time.to_rfc3339()).unwrap_or_default(),
+            stalled
+        )?;
+        file.flush()?;
+
+        case_println!(
+            "{} {}/{} redeemed, {:.2}/min{}{}",
+            style(sample.time.format("%H:%M:%S")).dim(),
+            sample.items_redeemed,
+            items_available,
+            velocity,
+            projected
+                .filter(|_| !sold_out)
+                .map(|time| format!(", sell-out at {}", time.format("%Y-%m-%d %H:%M:%S UTC")))
+                .unwrap_or_default(),
+            if stalled {
+                style(" (stalled)").yellow().to_string()
+            } else {
+                String::new()
+            }
+        );
+
+        if stalled {
+            outcome.stalls += 1;
+        }
+        outcome.samples += 1;
+        outcome.items_redeemed = sample.items_redeemed;
+        outcome.items_available = items_available;
+        outcome.sold_out = sold_out;
+
+        if sold_out {
+            case_println!("\n{}", style("Sold out.").green().bold());
+            break;
+        }
+
+        if matches!(args.samples, Some(samples) if outcome.samples >= samples) {
+            break;
+        }
+
+        if first.is_none() {
+            first = Some(sample.clone());
+        }
+        previous = Some(sample);
+
+        sleep(Duration::from_secs(args.interval));
+    }
+
+    Ok(outcome)
+}
+
+fn per_minute(items: u64, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+
+    if seconds > 0.0 {
+        items as f64 / seconds * 60.0
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,280 @@
+use std::{fs, sync::Arc};
+
+use chrono::Utc;
+use console::style;
+use serde::{Deserialize, Serialize};
+use spl_associated_token_account::{create_associated_token_account, get_associated_token_address};
+use spl_token::{instruction::transfer, ID as TOKEN_PROGRAM_ID};
+
+use crate::{
+    cache::load_cluster_cache,
+    clock::{Clock, SystemClock},
+    common::*,
+    config::CaseConfig,
+    denylist::load_denylist,
+    mint::{check_mint_live, mint_compute_units, mint_nft},
+    pdas::{get_collection_pda, get_metadata_pda},
+    profile::default_config,
+    tars::{get_tars_state, TARS_ID},
+    tx::send_and_confirm,
+    utils::*,
+};
+
+pub struct ReserveArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub tars: Option<String>,
+    pub number: u64,
+    pub wallet: String,
+    pub receipts: String,
+}
+
+/// Receipt of an item minted for the team and transferred to its wallet.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReserveReceipt {
+    pub tars: String,
+    pub mint: String,
+    pub name: Option<String>,
+    pub recipient: String,
+    pub mint_signature: String,
+    pub transfer_signature: String,
+    pub created_at: String,
+}
+
+/// Result of the reserve command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReserveOutcome {
+    pub tars: String,
+    pub recipient: String,
+    pub receipts_file: String,
+    pub receipts: Vec<ReserveReceipt>,
+}
+
+pub fn process_reserve(args: ReserveArgs) -> Result<ReserveOutcome> {
+    // reserved items are minted with the same compute unit limit as 'mint'
+    let compute_units = mint_compute_units(
+        &default_config(args.global.profile.as_deref()),
+        None,
+        &args.global.address_book,
+    )?;
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = Arc::new(setup_client(&case_config)?);
+    let program = client.program(TARS_ID);
+    let payer = program.payer();
+
+    // the tars id specified takes precedence over the one from the cache
+    let tars_id = match args.tars {
+        Some(tars_id) => tars_id,
+        None => {
+            load_cluster_cache(&case_config, &args.cache, false)?
+                .program
+                .tars
+        }
+    };
+    let tars_pubkey = resolve_pubkey(&tars_id, &args.global.address_book)?;
+    let recipient = resolve_pubkey(&args.wallet, &args.global.address_book)?;
+
+    if load_denylist(&args.global.denylist)?.contains(&recipient) {
+        return Err(DenylistError::Denied(recipient.to_string()).into());
+    }
+
+    case_println!(
+        "{} {}Loading tars",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+    let tars_state = Arc::new(get_tars_state(&case_config, &tars_pubkey)?);
+    let collection_pda_info = Arc::new(get_collection_pda(&tars_pubkey, &program).ok());
+    pb.finish_and_clear();
+
+    // only the authority can mint before go-live
+    if tars_state.authority != payer {
+        return Err(anyhow!(
+            "The keypair ({}) is not the authority of tars {}, only the authority can reserve items",
+            payer,
+            tars_pubkey
+        ));
+    }
+
+    let available = tars_state.data.items_available - tars_state.items_redeemed;
+
+    if args.number == 0 || args.number > available {
+        return Err(anyhow!(
+            "{} item(s) available, requested {}",
+            available,
+            args.number
+        ));
+    }
+
+    if is_live(&tars_state, &SystemClock) {
+        case_println!(
+            "{}{}",
+            WARNING_EMOJI,
+            style("The tars is already live: the public sale may mint the items first.").yellow()
+        );
+    }
+
+    case_println!(
+        "\n{} {}Minting {} item(s) to {}",
+        style("[2/2]").bold().dim(),
+        TARS_EMOJI,
+        args.number,
+        recipient
+    );
+
+    // receipts of previous runs are kept
+    let mut receipts: Vec<ReserveReceipt> = if Path::new(&args.receipts).exists() {
+        serde_json::from_str(&fs::read_to_string(&args.receipts)?)
+            .map_err(|err| anyhow!("Failed to parse receipts file '{}': {}", args.receipts, err))?
+    } else {
+        Vec::new()
+    };
+
+    let mut outcome = ReserveOutcome {
+        tars: tars_pubkey.to_string(),
+        recipient: recipient.to_string(),
+        receipts_file: args.receipts.clone(),
+        ..Default::default()
+    };
+
+    let pb = progress_bar_with_style(args.number);
+
+    for _ in 0..args.number {
+        let result = mint_nft(
+            Arc::clone(&client),
+            &case_config,
+            tars_pubkey,
+            Arc::clone(&tars_state),
+            Arc::clone(&collection_pda_info),
+            compute_units,
+            None,
+        )
+        .and_then(|(mint_signature, mint)| {
+            let transfer_signature = transfer_nft(&case_config, &mint, &recipient)?;
+            let name = get_metadata_pda(&mint, &program)
+                .ok()
+                .map(|(_, metadata)| metadata.data.name.trim_matches(char::from(0)).to_string());
+
+            Ok(ReserveReceipt {
+                tars: tars_pubkey.to_string(),
+                mint: mint.to_string(),
+                name,
+                recipient: recipient.to_string(),
+                mint_signature: mint_signature.to_string(),
+                transfer_signature: transfer_signature.to_string(),
+                created_at: Utc::now().to_rfc3339(),
+            })
+        });
+
+        match result {
+            Ok(receipt) => {
+                receipts.push(receipt.clone());
+                outcome.receipts.push(receipt);
+                // written after every item, so that a failure does not lose the receipts
+                write_receipts(&args.receipts, &receipts)?;
+            }
+            Err(err) => {
+                pb.abandon_with_message(format!("{}", style("Reserve failed ").red().bold()));
+                case_println!(
+                    "{} item(s) reserved, receipts written to '{}'",
+                    outcome.receipts.len(),
+                    args.receipts
+                );
+                error!("{:?}", err);
+                return Err(err);
+            }
+        }
+
+        pb.inc(1);
+    }
+
+    pb.finish();
+
+    for receipt in &outcome.receipts {
+        case_println!(
+            "{} {} {}",
+            style(" :..").dim(),
+            receipt.mint,
+            receipt.name.as_deref().unwrap_or_default()
+        );
+    }
+    case_println!(
+        "\n{} {} item(s) reserved, receipts written to '{}'",
+        COMPLETE_EMOJI,
+        outcome.receipts.len(),
+        args.receipts
+    );
+
+    Ok(outcome)
+}
+
+/// Returns whether the tars is live for other wallets than the authority at the time of
+/// `clock`.
+fn is_live(tars_state: &Tars, clock: &dyn Clock) -> bool {
+    // the authority can always mint, so the check is made for any other wallet
+    check_mint_live(tars_state, &Pubkey::default(), clock).is_ok()
+}
+
+/// Transfers the NFT from the token account of the payer to the recipient.
+fn transfer_nft(case_config: &CaseConfig, mint: &Pubkey, recipient: &Pubkey) -> Result<Signature> {
+    let payer = case_config.keypair.pubkey();
+    let source = get_associated_token_address(&payer, mint);
+    let destination = get_associated_token_address(recipient, mint);
+
+    let instructions = [
+        create_associated_token_account(&payer, recipient, mint),
+        transfer(&TOKEN_PROGRAM_ID, &source, &destination, &payer, &[], 1)?,
+    ];
+
+    send_and_confirm(case_config, &instructions, &[])
+}
+
+fn write_receipts(path: &str, receipts: &[ReserveReceipt]) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(receipts)?)
+        .map_err(|err| anyhow!("Failed to write receipts file '{}': {}", path, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use tars::{EndSettingType, EndSettings};
+
+    use super::*;
+    use crate::{
+        clock::FixedClock,
+        mint::process::tests::{tars_state, GO_LIVE},
+    };
+
+    #[test]
+    fn live_only_after_go_live_date() {
+        let tars_state = tars_state(Some(GO_LIVE));
+
+        assert!(!is_live(&tars_state, &FixedClock::at(GO_LIVE - 1)));
+        assert!(!is_live(&tars_state, &FixedClock::at(GO_LIVE)));
+        assert!(is_live(&tars_state, &FixedClock::at(GO_LIVE + 1)));
+    }
+
+    #[test]
+    fn not_live_without_go_live_date() {
+        assert!(!is_live(&tars_state(None), &FixedClock::at(GO_LIVE)));
+    }
+
+    #[test]
+    fn not_live_after_end_date() {
+        let end_date = GO_LIVE + 3600;
+        let mut tars_state = tars_state(Some(GO_LIVE));
+        tars_state.data.end_settings = Some(EndSettings {
+            end_setting_type: EndSettingType::Date,
+            number: end_date as u64,
+        });
+
+        assert!(is_live(&tars_state, &FixedClock::at(end_date)));
+        assert!(!is_live(&tars_state, &FixedClock::at(end_date + 1)));
+    }
+}
@@ -0,0 +1,52 @@
+use anchor_client::solana_sdk::signature::{keypair_from_seed, Keypair};
+use rand::{
+    rngs::{OsRng, StdRng},
+    seq::index,
+    RngCore, SeedableRng,
+};
+
+/// Source of the randomness of the commands: the keypairs of new accounts and the random
+/// samples of items, so that they can be reproduced from a seed.
+pub trait Rng: Send {
+    /// Returns the random number generator.
+    fn source(&mut self) -> &mut dyn RngCore;
+
+    /// Returns a new keypair.
+    fn new_keypair(&mut self) -> Keypair {
+        let mut seed = [0u8; 32];
+        self.source().fill_bytes(&mut seed);
+        keypair_from_seed(&seed).expect("Failed to create keypair from seed")
+    }
+
+    /// Returns `amount` distinct indices in `0..len` in random order, or every index when
+    /// `amount` is larger than `len`.
+    fn sample_indices(&mut self, len: usize, amount: usize) -> Vec<usize> {
+        index::sample(self.source(), len, amount.min(len)).into_vec()
+    }
+}
+
+/// Randomness of the operating system, used by the commands.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemRng(OsRng);
+
+impl Rng for SystemRng {
+    fn source(&mut self) -> &mut dyn RngCore {
+        &mut self.0
+    }
+}
+
+/// Deterministic randomness derived from a seed.
+#[derive(Clone, Debug)]
+pub struct SeededRng(StdRng);
+
+impl SeededRng {
+    pub fn new(seed: u64) -> SeededRng {
+        SeededRng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl Rng for SeededRng {
+    fn source(&mut self) -> &mut dyn RngCore {
+        &mut self.0
+    }
+}
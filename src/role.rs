@@ -0,0 +1,215 @@
+use crate::{
+    cli::{
+        BundlrAction, CacheSubcommands, CollectionSubcommands, Commands, DeploymentsSubcommands,
+        ExportSubcommands, ImportSubcommands, MetadataSubcommands, ProfileSubcommands,
+        ReportSubcommands, ScheduleSubcommands, ShuffleSubcommands, SnapshotSubcommands,
+        TxSubcommands, WhitelistSubcommands,
+    },
+    common::*,
+    config::{get_config_data, Role},
+    profile::default_config,
+};
+
+/// Returns the name of a command and the role required to run it.
+fn requirement(command: &Commands) -> (&'static str, Role) {
+    match command {
+        Commands::CreateConfig { .. } => ("create-config", Role::ReadOnly),
+        Commands::Estimate { .. } => ("estimate", Role::ReadOnly),
+        Commands::EstimateReveal { .. } => ("estimate-reveal", Role::ReadOnly),
+        Commands::Validate { fix: true, .. } => ("validate --fix", Role::Operator),
+        Commands::Validate { .. } => ("validate", Role::ReadOnly),
+        Commands::Rarity { .. } => ("rarity", Role::ReadOnly),
+        Commands::Verify { repair: true, .. } => ("verify --repair", Role::Operator),
+        Commands::Verify { .. } => ("verify", Role::ReadOnly),
+        Commands::VerifyConfigHash { .. } => ("verify-config-hash", Role::ReadOnly),
+        Commands::Show { .. } => ("show", Role::ReadOnly),
+        Commands::Whoami { .. } => ("whoami", Role::ReadOnly),
+        Commands::InspectTx { .. } => ("inspect-tx", Role::ReadOnly),
+        Commands::Logs { .. } => ("logs", Role::ReadOnly),
+        Commands::Profile { command } => match command {
+            ProfileSubcommands::List => ("profile list", Role::ReadOnly),
+            ProfileSubcommands::Switch { .. } => ("profile switch", Role::ReadOnly),
+        },
+        Commands::Export {
+            command: ExportSubcommands::Backup { .. },
+        } => ("export backup", Role::ReadOnly),
+        Commands::Export {
+            command: ExportSubcommands::ConfigLines { .. },
+        } => ("export config-lines", Role::ReadOnly),
+        Commands::Snapshot { command } => match command {
+            SnapshotSubcommands::Holders { .. } => ("snapshot holders", Role::ReadOnly),
+            SnapshotSubcommands::Mints { .. } => ("snapshot mints", Role::ReadOnly),
+        },
+        Commands::Report {
+            command: ReportSubcommands::Progress { .. },
+        } => ("report progress", Role::ReadOnly),
+        Commands::Deployments {
+            command: DeploymentsSubcommands::List { .. },
+        } => ("deployments list", Role::ReadOnly),
+        Commands::Shuffle {
+            command: ShuffleSubcommands::Verify { .. },
+        } => ("shuffle verify", Role::ReadOnly),
+        Commands::Bundlr {
+            action: BundlrAction::Balance,
+            ..
+        } => ("bundlr balance", Role::ReadOnly),
+        Commands::Bundlr {
+            action: BundlrAction::Fund { .. },
+            ..
+        } => ("bundlr fund", Role::Operator),
+        Commands::Launch { .. } => ("launch", Role::Operator),
+        Commands::Generate { .. } => ("generate", Role::Operator),
+        Commands::ProcessImages { .. } => ("process-images", Role::Operator),
+        Commands::Upload { .. } => ("upload", Role::Operator),
+        Commands::Deploy { .. } => ("deploy", Role::Operator),
+        Commands::Mint { .. } => ("mint", Role::Operator),
+        Commands::Reserve { .. } => ("reserve", Role::Authority),
+        Commands::RehearseGolive { .. } => ("rehearse-golive", Role::ReadOnly),
+        Commands::Sandbox { .. } => ("sandbox", Role::Operator),
+        Commands::Selftest { .. } => ("selftest", Role::Operator),
+        Commands::Whitelist { command } => match command {
+            WhitelistSubcommands::Create { .. } => ("whitelist create", Role::Operator),
+            WhitelistSubcommands::Mint { .. } => ("whitelist mint", Role::Operator),
+            WhitelistSubcommands::Airdrop { .. } => ("whitelist airdrop", Role::Operator),
+        },
+        Commands::Tx {
+            command: TxSubcommands::Send { .. },
+        } => ("tx send", Role::Operator),
+        Commands::Shuffle {
+            command: ShuffleSubcommands::Commit { .. },
+        } => ("shuffle commit", Role::Operator),
+        Commands::Shuffle {
+            command: ShuffleSubcommands::Reveal { .. },
+        } => ("shuffle reveal", Role::Operator),
+        Commands::Import {
+            command: ImportSubcommands::Backup { .. },
+        } => ("import backup", Role::Operator),
+        Commands::Cache { command } => match command {
+            CacheSubcommands::Check { .. } => ("cache check", Role::ReadOnly),
+            CacheSubcommands::Repair { .. } => ("cache repair", Role::Operator),
+            CacheSubcommands::Rebuild { .. } => ("cache rebuild", Role::Operator),
+            CacheSubcommands::Sync { .. } => ("cache sync", Role::Operator),
+            CacheSubcommands::Remove { .. } => ("cache remove", Role::Operator),
+            CacheSubcommands::Restore { .. } => ("cache restore", Role::Operator),
+        },
+        Commands::Update { .. } => ("update", Role::Authority),
+        Commands::MigratePayment { .. } => ("migrate-payment", Role::Authority),
+        Commands::Withdraw { .. } => ("withdraw", Role::Authority),
+        Commands::Schedule {
+            command: ScheduleSubcommands::Price { dry_run: true, .. },
+        } => ("schedule price --dry-run", Role::ReadOnly),
+        Commands::Schedule {
+            command: ScheduleSubcommands::Price { .. },
+        } => ("schedule price", Role::Authority),
+        Commands::Tx {
+            command: TxSubcommands::Sign { .. },
+        } => ("tx sign", Role::Authority),
+        Commands::Collection { command } => match command {
+            CollectionSubcommands::Create { set: true, .. } => {
+                ("collection create --set", Role::Authority)
+            }
+            CollectionSubcommands::Create { .. } => ("collection create", Role::Operator),
+            CollectionSubcommands::Set { .. } => ("collection set", Role::Authority),
+            CollectionSubcommands::Remove { .. } => ("collection remove", Role::Authority),
+            CollectionSubcommands::VerifyItems { .. } => {
+                ("collection verify-items", Role::Authority)
+            }
+            CollectionSubcommands::Migrate { .. } => ("collection migrate", Role::Authority),
+        },
+        Commands::Metadata { command } => match command {
+            MetadataSubcommands::Mutate { dry_run: true, .. } => {
+                ("metadata mutate --dry-run", Role::ReadOnly)
+            }
+            MetadataSubcommands::Mutate { .. } => ("metadata mutate", Role::Operator),
+        },
+        Commands::Bundlr {
+            action: BundlrAction::Withdraw,
+            ..
+        } => ("bundlr withdraw", Role::Authority),
+    }
+}
+
+/// Returns the name of a command (e.g. "collection set").
+pub fn command_name(command: &Commands) -> &'static str {
+    requirement(command).0
+}
+
+/// Returns the config file used by a command, or the default config file of the profile
+/// for commands that do not take one.
+pub fn config_path(command: &Commands, profile: Option<&str>) -> String {
+    match command {
+        Commands::Launch { config, .. }
+        | Commands::Estimate { config, .. }
+        | Commands::Update { config, .. }
+        | Commands::MigratePayment { config, .. }
+        | Commands::Deploy { config, .. }
+        | Commands::Mint { config, .. }
+        | Commands::Verify { config, .. }
+        | Commands::Whoami { config, .. }
+        | Commands::Schedule {
+            command: ScheduleSubcommands::Price { config, .. },
+        }
+        | Commands::Whitelist {
+            command:
+                WhitelistSubcommands::Mint { config, .. } | WhitelistSubcommands::Airdrop { config, .. },
+        }
+        | Commands::Collection {
+            command: CollectionSubcommands::VerifyItems { config, .. },
+        }
+        | Commands::Generate { config, .. }
+        | Commands::ProcessImages { config, .. }
+        | Commands::Upload { config, .. }
+        | Commands::Export {
+            command: ExportSubcommands::Backup { config, .. },
+        }
+        | Commands::Cache {
+            command:
+                CacheSubcommands::Remove { config, .. } | CacheSubcommands::Restore { config, .. },
+        } => config.to_string(),
+        Commands::CreateConfig {
+            config: Some(config),
+            ..
+        }
+        | Commands::Validate {
+            config: Some(config),
+            ..
+        } => config.to_string(),
+        _ => default_config(profile),
+    }
+}
+
+/// Checks that the role set in the config file allows running the command. A config
+/// without a role allows every command, while a command that requires a role fails when
+/// there is no config file to read it from.
+pub fn check_role(command: &Commands, global: &GlobalOptions) -> Result<()> {
+    let (name, required) = requirement(command);
+
+    if required == Role::ReadOnly {
+        return Ok(());
+    }
+
+    let path = config_path(command, global.profile.as_deref());
+
+    if !Path::new(&path).exists() {
+        let error = RoleError::MissingConfig(name.to_string(), path, required.to_string());
+        error!("{:?}", error);
+        return Err(error.into());
+    }
+
+    let role = get_config_data(&path, &global.address_book)?
+        .role
+        .unwrap_or(Role::Authority);
+
+    if role < required {
+        let error = RoleError::NotPermitted(
+            name.to_string(),
+            role.to_string(),
+            path,
+            required.to_string(),
+        );
+        error!("{:?}", error);
+        return Err(error.into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use std::{
+    fs,
+    sync::{atomic::AtomicBool, Arc},
+    time::Instant,
+};
+
+use chrono::Utc;
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    cache::Cache,
+    common::*,
+    config::{config_to_string, Cluster, ConfigData, ConfigFormat, Creator},
+    deploy::{process_deploy, DeployArgs},
+    tars::TARS_ID,
+    utils::get_cluster,
+};
+
+/// Base URI of the synthetic assets (config lines only need a valid URI, nothing is
+/// uploaded).
+const SANDBOX_URI: &str = "https://example.com/sandbox";
+
+/// Symbol of the sandbox items.
+const SANDBOX_SYMBOL: &str = "SBX";
+
+/// Mint price (in SOL) of the sandbox items.
+const SANDBOX_PRICE: f64 = 0.01;
+
+pub struct SandboxArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub items: u64,
+    pub output_dir: String,
+    pub batch_size: usize,
+    pub interrupted: Arc<AtomicBool>,
+}
+
+/// Result of the sandbox command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxOutcome {
+    pub tars: String,
+    pub config: String,
+    pub cache: String,
+    pub items: u64,
+    pub config_lines_written: usize,
+    pub deploy_secs: f64,
+}
+
+pub async fn process_sandbox(args: SandboxArgs) -> Result<SandboxOutcome> {
+    if args.items == 0 {
+        return Err(anyhow!("The number of items must be greater than zero."));
+    }
+
+    let case_config = case_setup(args.keypair.clone(), args.rpc_url.clone(), &args.global)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+
+    // sandboxes are throwaway, so they are never created with real funds
+    if !matches!(get_cluster(program.rpc())?, Cluster::Devnet) {
+        return Err(anyhow!("The sandbox can only be created on devnet."));
+    }
+
+    case_println!(
+        "{} {}Generating {} synthetic item(s)",
+        style("[1/2]").bold().dim(),
+        PAPER_EMOJI,
+        args.items
+    );
+
+    let output_dir = Path::new(&args.output_dir);
+    fs::create_dir_all(output_dir)?;
+
+    let config_path = path_to_string(&output_dir.join(DEFAULT_CONFIG))?;
+    let cache_path = path_to_string(&output_dir.join(DEFAULT_CACHE))?;
+
+    let config_data = sandbox_config(case_config.keypair.pubkey(), args.items);
+    fs::write(
+        &config_path,
+        config_to_string(&config_data, ConfigFormat::Json)?,
+    )?;
+
+    let mut cache = Cache::new();
+    cache.file_path = cache_path.clone();
+    cache.set_signer(&case_config.keypair)?;
+
+    for index in 0..args.items {
+        cache.items.insert(
+            index.to_string(),
+            CacheItem {
+                name: format!("Sandbox #{}", index + 1),
+                image_hash: String::new(),
+                image_link: format!("{}/{}.png", SANDBOX_URI, index),
+                metadata_hash: String::new(),
+                metadata_link: format!("{}/{}.json", SANDBOX_URI, index),
+                on_chain: false,
+                animation_hash: None,
+                animation_link: None,
+                storage_check: None,
+            },
+        );
+    }
+
+    cache.sync_file()?;
+
+    case_println!("{} {}", style("Config:").bold(), config_path);
+    case_println!("{} {}", style("Cache:").bold(), cache_path);
+
+    case_println!(
+        "\n{} {}Deploying sandbox",
+        style("[2/2]").bold().dim(),
+        TARS_EMOJI
+    );
+    case_println!("\n{} case deploy\n", style(">>>").magenta());
+
+    let start = Instant::now();
+
+    let deploy = process_deploy(DeployArgs {
+        config: config_path.clone(),
+        cache: cache_path.clone(),
+        keypair: args.keypair,
+        rpc_url: args.rpc_url,
+        global: args.global,
+        interrupted: args.interrupted,
+        dry_run: false,
+        max_fee: None,
+        sign_offline: None,
+        nonces: Vec::new(),
+        batch_size: args.batch_size,
+        skip_collection: false,
+        compute_units: None,
+    })
+    .await?;
+
+    let deploy_secs = start.elapsed().as_secs_f64();
+
+    case_println!(
+        "\n{} config line(s) written in {:.1}s ({:.1} lines/s)",
+        deploy.config_lines_written,
+        deploy_secs,
+        deploy.config_lines_written as f64 / deploy_secs
+    );
+    case_println!(
+        "Use --config {} --cache {} to run other commands against the sandbox.",
+        config_path,
+        cache_path
+    );
+
+    Ok(SandboxOutcome {
+        tars: deploy.tars,
+        config: config_path,
+        cache: cache_path,
+        items: args.items,
+        config_lines_written: deploy.config_lines_written,
+        deploy_secs,
+    })
+}
+
+/// Returns the config of a sandbox tars, live immediately and with the payer as the
+/// only creator.
+fn sandbox_config(payer: Pubkey, items: u64) -> ConfigData {
+    ConfigData {
+        price: SANDBOX_PRICE,
+        number: items,
+        creators: vec![Creator {
+            address: payer,
+            share: 100,
+        }],
+        go_live_date: Some(Utc::now().to_rfc2822()),
+        retain_authority: true,
+        is_mutable: true,
+        symbol: SANDBOX_SYMBOL.to_string(),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,3 @@
+pub mod price;
+
+pub use price::*;
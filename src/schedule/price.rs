@@ -0,0 +1,283 @@
+use std::{thread, time::Duration};
+
+use anchor_lang::prelude::AccountMeta;
+use chrono::{NaiveDateTime, Utc};
+use console::style;
+use serde::Serialize;
+use tars::{accounts as nft_accounts, instruction as nft_instruction, Tars, TarsData};
+
+use crate::{
+    cache::load_cluster_cache,
+    common::*,
+    config::{get_config_data, go_live_date_as_timestamp, CaseConfig, ConfigData, PriceSchedule},
+    health::Health,
+    tars::{get_tars_state, parse_config_price, TARS_ID},
+    tx::send_and_confirm,
+    utils::*,
+    validate::check_price_schedule,
+};
+
+/// Time to wait before retrying a failed price update.
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+pub struct SchedulePriceArgs {
+    pub config: String,
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub tars: Option<String>,
+    /// Applies the current price and exits instead of following the schedule.
+    pub once: bool,
+    /// Prints the schedule without updating the tars.
+    pub dry_run: bool,
+    /// Port of the health endpoint, served while following the schedule.
+    pub health_port: Option<u16>,
+}
+
+/// Price set on the tars by the schedule.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceUpdate {
+    pub price: f64,
+    pub signature: String,
+    pub applied_at: String,
+}
+
+/// Result of the schedule price command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchedulePriceOutcome {
+    pub tars: String,
+    pub updates: Vec<PriceUpdate>,
+    /// Price at the time the command stopped.
+    pub price: f64,
+}
+
+pub fn process_schedule_price(args: SchedulePriceArgs) -> Result<SchedulePriceOutcome> {
+    let config_data = get_config_data(&args.config, &args.global.address_book)?;
+    let schedule = config_data
+        .price_schedule
+        .clone()
+        .ok_or_else(|| anyhow!("No price schedule in the config file '{}'", args.config))?;
+    check_price_schedule(&schedule, &config_data.go_live_date)?;
+    // checked above, the schedule starts on the go live date
+    let start = go_live_date_as_timestamp(&config_data.go_live_date)?.unwrap();
+
+    if args.dry_run {
+        print_schedule(&schedule, start);
+
+        return Ok(SchedulePriceOutcome {
+            price: schedule.price_at(start, Utc::now().timestamp()),
+            ..Default::default()
+        });
+    }
+
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+
+    // the tars id specified takes precedence over the one from the cache
+    let tars_id = match args.tars {
+        Some(tars_id) => tars_id,
+        None => {
+            load_cluster_cache(&case_config, &args.cache, false)?
+                .program
+                .tars
+        }
+    };
+    let tars_pubkey = resolve_pubkey(&tars_id, &args.global.address_book)?;
+
+    case_println!(
+        "{} {}Loading tars",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+    case_println!("{} {}", style("Tars ID:").bold(), tars_pubkey);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+    let tars_state = get_tars_state(&case_config, &tars_pubkey)?;
+    pb.finish_with_message("Done");
+
+    assert_correct_authority(&program.payer(), &tars_state.authority)?;
+
+    case_println!(
+        "\n{} {}Following the price schedule",
+        style("[2/2]").bold().dim(),
+        PAYMENT_EMOJI
+    );
+
+    let mut outcome = SchedulePriceOutcome {
+        tars: tars_pubkey.to_string(),
+        ..Default::default()
+    };
+    let health = match args.health_port {
+        Some(port) => Some(Health::serve("schedule price", port)?),
+        None => None,
+    };
+
+    loop {
+        let now = Utc::now().timestamp();
+        let price = schedule.price_at(start, now);
+        outcome.price = price;
+
+        if let Err(err) = apply_price(
+            &client,
+            &case_config,
+            &config_data,
+            &tars_pubkey,
+            price,
+            &mut outcome,
+        ) {
+            if args.once {
+                return Err(err);
+            }
+            if let Some(health) = &health {
+                health.record_error(&err);
+            }
+            // the daemon keeps following the schedule through RPC failures
+            error!("Failed to update the price: {:?}", err);
+            case_println!(
+                "{}{} {}",
+                WARNING_EMOJI,
+                style("Failed to update the price, retrying:").yellow(),
+                err
+            );
+            thread::sleep(RETRY_INTERVAL);
+            continue;
+        }
+
+        if let Some(health) = &health {
+            health.record_action();
+        }
+
+        if args.once {
+            break;
+        }
+
+        let next = match schedule.next_drop(start, now) {
+            Some(next) => next,
+            None => {
+                case_println!(
+                    "\n{} The price reached the floor of ◎ {}",
+                    COMPLETE_EMOJI,
+                    schedule.floor_price
+                );
+                break;
+            }
+        };
+
+        case_println!(
+            "{} ◎ {} on {}",
+            style("Next price:").bold(),
+            schedule.price_at(start, next),
+            format_timestamp(next)
+        );
+        thread::sleep(Duration::from_secs(
+            (next - Utc::now().timestamp()).max(1) as u64
+        ));
+    }
+
+    Ok(outcome)
+}
+
+/// Updates the price of the tars, if it differs from the on-chain price.
+fn apply_price(
+    client: &Client,
+    case_config: &CaseConfig,
+    config_data: &ConfigData,
+    tars_pubkey: &Pubkey,
+    price: f64,
+    outcome: &mut SchedulePriceOutcome,
+) -> Result<()> {
+    // the price is converted with the decimals of the payment token
+    let price_units = parse_config_price(
+        client,
+        &ConfigData {
+            price,
+            ..config_data.clone()
+        },
+    )?;
+    let tars_state = get_tars_state(case_config, tars_pubkey)?;
+
+    if tars_state.data.price == price_units {
+        case_println!("{} ◎ {} (up to date)", style("Price:").bold(), price);
+        return Ok(());
+    }
+
+    let signature = update_price(client, case_config, tars_pubkey, &tars_state, price_units)?;
+
+    case_println!(
+        "{} ◎ {} {}",
+        style("Price:").bold(),
+        price,
+        style(format!("(signature {})", signature)).dim()
+    );
+    outcome.updates.push(PriceUpdate {
+        price,
+        signature: signature.to_string(),
+        applied_at: Utc::now().to_rfc3339(),
+    });
+
+    Ok(())
+}
+
+/// Sends an update of the tars that only changes its price.
+fn update_price(
+    client: &Client,
+    case_config: &CaseConfig,
+    tars_pubkey: &Pubkey,
+    tars_state: &Tars,
+    price: u64,
+) -> Result<Signature> {
+    let program = client.program(TARS_ID);
+    let data = TarsData {
+        price,
+        ..tars_state.data.clone()
+    };
+
+    let mut builder = program
+        .request()
+        .accounts(nft_accounts::UpdateTars {
+            tars: *tars_pubkey,
+            authority: program.payer(),
+            wallet: tars_state.wallet,
+        })
+        .args(nft_instruction::UpdateTars { data });
+
+    // the token mint is cleared when it is not passed to the update
+    if let Some(token_mint) = tars_state.token_mint {
+        builder = builder.accounts(AccountMeta {
+            pubkey: token_mint,
+            is_signer: false,
+            is_writable: false,
+        });
+    }
+
+    send_and_confirm(case_config, &builder.instructions()?, &[])
+}
+
+/// Prints the price steps of the schedule, from the start price down to the floor.
+fn print_schedule(schedule: &PriceSchedule, start: i64) {
+    let mut time = start;
+
+    loop {
+        case_println!(
+            "{:<40} ◎ {}",
+            format_timestamp(time),
+            schedule.price_at(start, time)
+        );
+
+        match schedule.next_drop(start, time) {
+            Some(next) => time = next,
+            None => break,
+        }
+    }
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    NaiveDateTime::from_timestamp(timestamp, 0)
+        .format("%a %B %e %Y %H:%M:%S UTC")
+        .to_string()
+}
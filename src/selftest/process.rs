@@ -0,0 +1,465 @@
+use std::{
+    fs,
+    sync::{atomic::AtomicBool, Arc},
+    time::Instant,
+};
+
+use chrono::Utc;
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    cache::{load_cache, Cache},
+    common::*,
+    config::{config_to_string, Cluster, ConfigData, ConfigFormat, Creator},
+    deploy::{process_deploy, DeployArgs},
+    mint::{process_mint, MintArgs},
+    tars::{get_tars_state, TARS_ID},
+    utils::get_cluster,
+    validate::{process_validate, ValidateArgs},
+    verify::{process_verify, VerifyArgs},
+    withdraw::{process_withdraw, WithdrawArgs},
+};
+
+/// Assets bundled with the binary: (file name, content).
+const FIXTURES: [(&str, &[u8]); 6] = [
+    ("0.png", include_bytes!("fixtures/0.png")),
+    ("0.json", include_bytes!("fixtures/0.json")),
+    ("1.png", include_bytes!("fixtures/1.png")),
+    ("1.json", include_bytes!("fixtures/1.json")),
+    ("2.png", include_bytes!("fixtures/2.png")),
+    ("2.json", include_bytes!("fixtures/2.json")),
+];
+
+/// Number of items of the self-test tars.
+const SELFTEST_ITEMS: u64 = 3;
+
+/// Base URI of the items (config lines only need a valid URI, nothing is uploaded).
+const SELFTEST_URI: &str = "https://example.com/selftest";
+
+/// Symbol of the self-test items, the same as the one of the fixtures.
+const SELFTEST_SYMBOL: &str = "CST";
+
+/// File that marks a directory created by the self-test, which can be removed.
+const SELFTEST_MARKER: &str = ".selftest";
+
+/// Mint price (in SOL) of the self-test items.
+const SELFTEST_PRICE: f64 = 0.01;
+
+pub struct SelftestArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub output_dir: String,
+    /// Keeps the files of the self-test when it passes.
+    pub keep: bool,
+    pub interrupted: Arc<AtomicBool>,
+}
+
+/// Stage of the self-test, in the order they are run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SelftestStage {
+    /// Validate the bundled assets.
+    Validate,
+    /// Create the tars and write its config lines.
+    Deploy,
+    /// Mint one item.
+    Mint,
+    /// Verify the config lines on-chain.
+    Verify,
+    /// Withdraw the rent of the tars.
+    Withdraw,
+}
+
+impl SelftestStage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SelftestStage::Validate => "validate",
+            SelftestStage::Deploy => "deploy",
+            SelftestStage::Mint => "mint",
+            SelftestStage::Verify => "verify",
+            SelftestStage::Withdraw => "withdraw",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StageStatus {
+    Passed,
+    Failed,
+    /// Not run because a previous stage failed.
+    Skipped,
+}
+
+/// Result of a stage of the self-test.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageResult {
+    pub stage: SelftestStage,
+    pub status: StageStatus,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of the selftest command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelftestOutcome {
+    pub rpc_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tars: Option<String>,
+    pub passed: bool,
+    pub stages: Vec<StageResult>,
+}
+
+impl SelftestOutcome {
+    /// Records the result of a stage, returning its value when it passed.
+    fn record<T>(
+        &mut self,
+        stage: SelftestStage,
+        started: Instant,
+        result: Result<T>,
+    ) -> Option<T> {
+        let (status, error, value) = match result {
+            Ok(value) => (StageStatus::Passed, None, Some(value)),
+            Err(err) => {
+                error!("Self-test stage {} failed: {}", stage.name(), err);
+                (StageStatus::Failed, Some(err.to_string()), None)
+            }
+        };
+
+        self.stages.push(StageResult {
+            stage,
+            status,
+            duration_ms: started.elapsed().as_millis() as u64,
+            error,
+        });
+
+        value
+    }
+
+    fn skip(&mut self, stage: SelftestStage) {
+        self.stages.push(StageResult {
+            stage,
+            status: StageStatus::Skipped,
+            duration_ms: 0,
+            error: None,
+        });
+    }
+}
+
+/// Runs a miniature launch on devnet with the bundled assets (validate, deploy, mint one
+/// item, verify and withdraw), reporting the result of each stage. A stage is skipped when
+/// a previous one failed, except the withdraw, which recovers the rent of any tars
+/// deployed.
+pub async fn process_selftest(args: SelftestArgs) -> Result<SelftestOutcome> {
+    let case_config = case_setup(args.keypair.clone(), args.rpc_url.clone(), &args.global)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+
+    // the self-test spends real transactions, so it is never run with real funds
+    if !matches!(get_cluster(program.rpc())?, Cluster::Devnet) {
+        return Err(anyhow!("The self-test can only be run on devnet."));
+    }
+
+    // only a directory created by a previous self-test is replaced
+    let output_dir = Path::new(&args.output_dir);
+    if output_dir.join(SELFTEST_MARKER).is_file() {
+        fs::remove_dir_all(output_dir)?;
+    } else if output_dir.exists() && fs::read_dir(output_dir)?.next().is_some() {
+        return Err(anyhow!(
+            "The directory '{}' is not empty, use an empty directory for the self-test files",
+            args.output_dir
+        ));
+    }
+    let assets_dir = output_dir.join(DEFAULT_ASSETS);
+    fs::create_dir_all(&assets_dir)?;
+    fs::write(output_dir.join(SELFTEST_MARKER), "")?;
+
+    let assets_path = path_to_string(&assets_dir)?;
+    let config_path = path_to_string(&output_dir.join(DEFAULT_CONFIG))?;
+    let cache_path = path_to_string(&output_dir.join(DEFAULT_CACHE))?;
+
+    for (file_name, content) in FIXTURES {
+        fs::write(assets_dir.join(file_name), content)?;
+    }
+
+    let config_data = selftest_config(case_config.keypair.pubkey());
+    fs::write(
+        &config_path,
+        config_to_string(&config_data, ConfigFormat::Json)?,
+    )?;
+
+    // the items are recorded as uploaded, so that the self-test does not need a storage
+    // provider
+    let mut cache = Cache::new();
+    cache.file_path = cache_path.clone();
+    cache.set_signer(&case_config.keypair)?;
+
+    for index in 0..SELFTEST_ITEMS {
+        cache.items.insert(
+            index.to_string(),
+            CacheItem {
+                name: format!("Selftest #{}", index + 1),
+                image_hash: String::new(),
+                image_link: format!("{}/{}.png", SELFTEST_URI, index),
+                metadata_hash: String::new(),
+                metadata_link: format!("{}/{}.json", SELFTEST_URI, index),
+                on_chain: false,
+                animation_hash: None,
+                animation_link: None,
+                storage_check: None,
+            },
+        );
+    }
+    cache.sync_file()?;
+
+    let mut outcome = SelftestOutcome {
+        rpc_url: case_config.rpc_url.clone(),
+        ..Default::default()
+    };
+
+    print_stage(SelftestStage::Validate);
+    let started = Instant::now();
+    let result = process_validate(ValidateArgs {
+        assets_dir: assets_path,
+        config: Some(config_path.clone()),
+        global: args.global.clone(),
+        strict: false,
+        skip_collection_prompt: true,
+        watch: false,
+        fix: false,
+        allow_duplicates: false,
+    });
+    let validated = outcome
+        .record(SelftestStage::Validate, started, result)
+        .is_some();
+
+    let deployed = if validated {
+        print_stage(SelftestStage::Deploy);
+        let started = Instant::now();
+        let result = process_deploy(DeployArgs {
+            config: config_path.clone(),
+            cache: cache_path.clone(),
+            keypair: args.keypair.clone(),
+            rpc_url: args.rpc_url.clone(),
+            global: args.global.clone(),
+            interrupted: args.interrupted.clone(),
+            dry_run: false,
+            max_fee: None,
+            sign_offline: None,
+            nonces: Vec::new(),
+            batch_size: CONFIG_LINES_BATCH_SIZE,
+            skip_collection: true,
+            compute_units: None,
+        })
+        .await
+        .and_then(|deploy| {
+            if deploy.config_lines_written == SELFTEST_ITEMS as usize {
+                Ok(())
+            } else {
+                Err(anyhow!(
+                    "{} of {} config line(s) written",
+                    deploy.config_lines_written,
+                    SELFTEST_ITEMS
+                ))
+            }
+        });
+        outcome
+            .record(SelftestStage::Deploy, started, result)
+            .is_some()
+    } else {
+        outcome.skip(SelftestStage::Deploy);
+        false
+    };
+
+    // a deploy that failed part-way may have created the tars, which is read from the
+    // cache so that its rent is always withdrawn
+    let tars = load_cache(&cache_path, false)
+        .ok()
+        .map(|cache| cache.program.tars)
+        .filter(|tars| !tars.is_empty());
+    outcome.tars = tars.clone();
+
+    let minted = match tars.as_ref().filter(|_| deployed) {
+        Some(tars) => {
+            print_stage(SelftestStage::Mint);
+            let started = Instant::now();
+            let result = process_mint(MintArgs {
+                config: config_path.clone(),
+                keypair: args.keypair.clone(),
+                rpc_url: args.rpc_url.clone(),
+                global: args.global.clone(),
+                cache: cache_path.clone(),
+                number: None,
+                tars: Some(tars.clone()),
+                keypairs: None,
+                max_fee: None,
+                confirm_above: f64::MAX,
+                skip_confirmation: true,
+                compute_units: None,
+                to_file: None,
+                gateway_token: None,
+            })
+            .and_then(|_| {
+                // the mint is only counted once the tars records it
+                let state = get_tars_state(
+                    &case_config,
+                    &resolve_pubkey(tars, &args.global.address_book)?,
+                )?;
+                if state.items_redeemed == 1 {
+                    Ok(())
+                } else {
+                    Err(anyhow!(
+                        "The tars has {} item(s) redeemed, expected 1",
+                        state.items_redeemed
+                    ))
+                }
+            });
+            outcome
+                .record(SelftestStage::Mint, started, result)
+                .is_some()
+        }
+        None => {
+            outcome.skip(SelftestStage::Mint);
+            false
+        }
+    };
+
+    if minted {
+        print_stage(SelftestStage::Verify);
+        let started = Instant::now();
+        let result = process_verify(VerifyArgs {
+            config: config_path.clone(),
+            keypair: args.keypair.clone(),
+            rpc_url: args.rpc_url.clone(),
+            global: args.global.clone(),
+            cache: cache_path.clone(),
+            check_metadata: false,
+            check_links: false,
+            ipfs_gateways: Vec::new(),
+            repair: false,
+            metadata_standards: false,
+        })
+        .await;
+        outcome.record(SelftestStage::Verify, started, result);
+    } else {
+        outcome.skip(SelftestStage::Verify);
+    }
+
+    match &tars {
+        Some(tars) => {
+            print_stage(SelftestStage::Withdraw);
+            let started = Instant::now();
+            let result = process_withdraw(WithdrawArgs {
+                tars: Some(tars.clone()),
+                keypair: args.keypair.clone(),
+                rpc_url: args.rpc_url.clone(),
+                global: args.global.clone(),
+                list: false,
+                authority: None,
+                exclude: Vec::new(),
+                skip_confirmation: true,
+            })
+            .and_then(|withdraw| {
+                if withdraw.signatures.is_empty() {
+                    Err(anyhow!("The tars was not drained"))
+                } else {
+                    Ok(())
+                }
+            });
+            outcome.record(SelftestStage::Withdraw, started, result);
+        }
+        None => outcome.skip(SelftestStage::Withdraw),
+    }
+
+    outcome.passed = outcome
+        .stages
+        .iter()
+        .all(|stage| stage.status == StageStatus::Passed);
+    print_report(&outcome);
+
+    if !outcome.passed {
+        let failed: Vec<&str> = outcome
+            .stages
+            .iter()
+            .filter(|stage| stage.status == StageStatus::Failed)
+            .map(|stage| stage.stage.name())
+            .collect();
+
+        return Err(anyhow!(
+            "Self-test failed ({}), the files are kept in '{}' for inspection",
+            failed.join(", "),
+            args.output_dir
+        ));
+    }
+
+    // the files are only removed on success, so that a failure can be inspected
+    if !args.keep {
+        fs::remove_dir_all(output_dir)?;
+    }
+
+    Ok(outcome)
+}
+
+/// Returns the config of the self-test tars, live immediately and with the payer as the
+/// only creator.
+fn selftest_config(payer: Pubkey) -> ConfigData {
+    ConfigData {
+        price: SELFTEST_PRICE,
+        number: SELFTEST_ITEMS,
+        creators: vec![Creator {
+            address: payer,
+            share: 100,
+        }],
+        go_live_date: Some(Utc::now().to_rfc2822()),
+        retain_authority: true,
+        is_mutable: true,
+        symbol: SELFTEST_SYMBOL.to_string(),
+        ..Default::default()
+    }
+}
+
+fn print_stage(stage: SelftestStage) {
+    case_println!("\n{} case {}\n", style(">>>").magenta(), stage.name());
+}
+
+fn print_report(outcome: &SelftestOutcome) {
+    case_println!("\n{}", style("Self-test report:").bold());
+
+    for stage in &outcome.stages {
+        let status = match stage.status {
+            StageStatus::Passed => style("PASS").green().bold(),
+            StageStatus::Failed => style("FAIL").red().bold(),
+            StageStatus::Skipped => style("SKIP").dim(),
+        };
+
+        match &stage.error {
+            Some(error) => case_println!("{} {:<9} {}", status, stage.stage.name(), error),
+            None if stage.status == StageStatus::Skipped => {
+                case_println!("{} {}", status, stage.stage.name())
+            }
+            None => case_println!(
+                "{} {:<9} {:.1}s",
+                status,
+                stage.stage.name(),
+                stage.duration_ms as f64 / 1000.0
+            ),
+        }
+    }
+
+    if outcome.passed {
+        case_println!(
+            "\n{}",
+            style(format!(
+                "{}Every stage passed against {}",
+                COMPLETE_EMOJI, outcome.rpc_url
+            ))
+            .green()
+            .bold()
+        );
+    }
+}
@@ -1,88 +1,539 @@
-use std::rc::Rc;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::{
+    env, fs,
+    path::Path,
+    rc::Rc,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use anchor_client::{
     solana_sdk::{
         commitment_config::CommitmentConfig,
-        signature::{keypair::Keypair, read_keypair_file},
+        derivation_path::DerivationPath,
+        pubkey::Pubkey,
+        signature::{
+            generate_seed_from_seed_phrase_and_passphrase, keypair::Keypair,
+            keypair_from_seed_and_derivation_path, keypair_from_seed_phrase_and_passphrase,
+        },
     },
-    Client, Cluster,
+    Client, Cluster as ClientCluster,
 };
 use anyhow::{anyhow, Result};
-use tracing::error;
+use bip39::{Language, Mnemonic};
+use console::style;
+use solana_client::rpc_client::RpcClient;
+use tracing::{error, info, warn};
+use url::Url;
 
+#[cfg(unix)]
+use crate::{config::data::Cluster, utils::get_cluster};
 use crate::{
-    config::data::CaseConfig,
-    constants::{DEFAULT_KEYPATH, DEFAULT_RPC_DEVNET},
+    case_println,
+    config::data::{CaseConfig, GlobalOptions, RpcEndpoints, SolanaConfig},
+    constants::{
+        DEFAULT_KEYPATH, DEFAULT_RPC_DEVNET, KEYPAIR_ENV, MAX_RPC_SLOT_LAG, RPC_PROBE_TIMEOUT_SECS,
+        SEED_PHRASE_WORDS, WARNING_EMOJI,
+    },
+    errors::SetupError,
     parse::*,
 };
 
 pub fn setup_client(case_config: &CaseConfig) -> Result<Client> {
-    let rpc_url = case_config.rpc_url.clone();
-    let ws_url = rpc_url.replace("http", "ws");
-    let cluster = Cluster::Custom(rpc_url, ws_url);
+    let rpc_url = rpc_endpoint(case_config);
+    let ws_url = websocket_url(case_config, &rpc_url);
+    let cluster = ClientCluster::Custom(rpc_url, ws_url);
 
     let key_bytes = case_config.keypair.to_bytes();
     let signer = Rc::new(Keypair::from_bytes(&key_bytes)?);
 
-    let opts = CommitmentConfig::confirmed();
-    Ok(Client::new_with_options(cluster, signer, opts))
+    Ok(Client::new_with_options(cluster, signer, case_config.commitment))
 }
 
 pub fn case_setup(
     keypair_opt: Option<String>,
     rpc_url_opt: Option<String>,
+    global: &GlobalOptions,
 ) -> Result<CaseConfig> {
     let sol_config_option = parse_solana_config();
 
-    let rpc_url = match rpc_url_opt {
-        Some(rpc_url) => rpc_url,
+    // --rpc-url takes precedence over the endpoints of the config file, followed by the
+    // Solana config
+    let config_rpc_urls: Vec<String> = global
+        .config_rpc_urls
+        .iter()
+        .flat_map(|rpc_url| split_rpc_urls(rpc_url))
+        .collect();
+    let rpc_urls = match rpc_url_opt {
+        Some(rpc_url) => split_rpc_urls(&rpc_url),
+        None if !config_rpc_urls.is_empty() => config_rpc_urls,
         None => match sol_config_option {
-            Some(ref sol_config) => sol_config.json_rpc_url.clone(),
-            None => String::from(DEFAULT_RPC_DEVNET),
+            Some(ref sol_config) => vec![sol_config.json_rpc_url.clone()],
+            None => vec![String::from(DEFAULT_RPC_DEVNET)],
         },
     };
+    let rpc_endpoints = select_rpc_endpoints(rpc_urls)?;
+    let rpc_url = rpc_endpoints.healthy[0].clone();
+    let keypair = resolve_keypair(keypair_opt, global, &sol_config_option, Some(&rpc_url))?;
+
+    // --ws-url takes precedence over the websocket url of the Solana config, which is only
+    // used together with its RPC url
+    let ws_url = match &global.ws_url {
+        Some(ws_url) => Some(ws_url.clone()),
+        None => sol_config_option
+            .as_ref()
+            .filter(|sol_config| {
+                sol_config.json_rpc_url == rpc_url && !sol_config.websocket_url.is_empty()
+            })
+            .map(|sol_config| sol_config.websocket_url.clone()),
+    };
+
+    let commitment = match &sol_config_option {
+        Some(sol_config) if !sol_config.commitment.is_empty() => {
+            CommitmentConfig::from_str(&sol_config.commitment).map_err(|_| {
+                anyhow!(
+                    "Invalid commitment '{}' in the Solana config file",
+                    sol_config.commitment
+                )
+            })?
+        }
+        _ => CommitmentConfig::confirmed(),
+    };
+
+    info!("RPC endpoint: {}", rpc_url);
+
+    Ok(CaseConfig {
+        rpc_url,
+        keypair,
+        ws_url,
+        commitment,
+        rpc_endpoints: Arc::new(Mutex::new(rpc_endpoints)),
+        genesis_hash: Arc::new(Mutex::new(None)),
+        force_cluster: global.force_cluster,
+    })
+}
+
+/// Reads the keypair of a command without connecting to a cluster, for commands that only
+/// sign local files.
+pub fn local_keypair(keypair_opt: Option<String>, global: &GlobalOptions) -> Result<Keypair> {
+    resolve_keypair(keypair_opt, global, &parse_solana_config(), None)
+}
 
-    let keypair = match keypair_opt {
-        Some(keypair_path) => match read_keypair_file(&keypair_path) {
-            Ok(keypair) => keypair,
-            Err(e) => {
-                error!("Failed to read keypair file: {}", e);
-                return Err(anyhow!(
-                    "Failed to read keypair file: {}, {}",
-                    keypair_path,
-                    e
-                ));
+/// Returns the keypair of `--keypair`, the `CASE_KEYPAIR` environment variable or the
+/// Solana config, in this order.
+fn resolve_keypair(
+    keypair_opt: Option<String>,
+    global: &GlobalOptions,
+    sol_config_option: &Option<SolanaConfig>,
+    rpc_url: Option<&str>,
+) -> Result<Keypair> {
+    // the environment variable is only used when --keypair is not specified, so that CI
+    // can provide a keypair without a file; --keypair only accepts a path, since a secret
+    // in the arguments would show in the process list and the shell history
+    match keypair_opt {
+        Some(keypair_path) => read_keypair(&keypair_path, rpc_url, global),
+        None => match env::var(KEYPAIR_ENV) {
+            Ok(value) if !value.trim().is_empty() => {
+                info!("Using keypair from {}", KEYPAIR_ENV);
+                read_keypair_env(&value, rpc_url, global)
+                    .map_err(|err| anyhow!("Failed to read keypair from {}: {}", KEYPAIR_ENV, err))
+            }
+            _ => {
+                let keypair_path = match sol_config_option {
+                    Some(sol_config) => sol_config.keypair_path.clone(),
+                    None => shellexpand::tilde(DEFAULT_KEYPATH).to_string(),
+                };
+                read_keypair(&keypair_path, rpc_url, global)
             }
         },
+    }
+}
 
-        None => match sol_config_option {
-            Some(ref sol_config) => match read_keypair_file(&sol_config.keypair_path) {
-                Ok(keypair) => keypair,
-                Err(e) => {
-                    error!(
-                        "Failed to read keypair file: {}, {}",
-                        &sol_config.keypair_path, e
-                    );
-                    return Err(anyhow!(
-                        "Failed to read keypair file: {}, {}",
-                        &sol_config.keypair_path,
-                        e
-                    ));
+/// Returns the websocket url of an RPC endpoint: the one specified for the RPC url of the
+/// config, otherwise the url derived from the endpoint.
+pub fn websocket_url(case_config: &CaseConfig, rpc_url: &str) -> String {
+    match &case_config.ws_url {
+        Some(ws_url) if rpc_url == case_config.rpc_url => ws_url.clone(),
+        _ => compute_websocket_url(rpc_url),
+    }
+}
+
+/// Derives the websocket url of an RPC url the same way as the Solana CLI: the scheme is
+/// replaced by ws (or wss) and an explicit port is incremented by one.
+pub fn compute_websocket_url(rpc_url: &str) -> String {
+    let mut url = match Url::parse(rpc_url) {
+        Ok(url) => url,
+        Err(_) => return rpc_url.replacen("http", "ws", 1),
+    };
+
+    let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+    // http(s) and ws(s) are all special schemes, so the scheme can be replaced
+    let _ = url.set_scheme(scheme);
+
+    if let Some(port) = url.port() {
+        let _ = url.set_port(Some(port.saturating_add(1)));
+    }
+
+    url.to_string()
+}
+
+/// Splits a comma-separated list of RPC urls.
+fn split_rpc_urls(rpc_urls: &str) -> Vec<String> {
+    rpc_urls
+        .split(',')
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .collect()
+}
+
+/// Returns the endpoints to use out of a list of RPC urls, the first one being used
+/// first. When there is more than one, only the healthy endpoints are kept.
+fn select_rpc_endpoints(rpc_urls: Vec<String>) -> Result<RpcEndpoints> {
+    if rpc_urls.is_empty() {
+        return Err(anyhow!("No RPC endpoint specified"));
+    }
+
+    let healthy = if rpc_urls.len() == 1 {
+        rpc_urls
+    } else {
+        let healthy = probe_rpc_endpoints(&rpc_urls);
+
+        if healthy.is_empty() {
+            return Err(anyhow!(
+                "None of the RPC endpoints is healthy: {}",
+                rpc_urls.join(", ")
+            ));
+        }
+
+        healthy
+    };
+
+    Ok(RpcEndpoints {
+        healthy,
+        current: 0,
+    })
+}
+
+/// Returns the endpoints that respond to the health check and are at most
+/// `MAX_RPC_SLOT_LAG` slots behind the most recent one, in the order specified.
+fn probe_rpc_endpoints(rpc_urls: &[String]) -> Vec<String> {
+    let timeout = Duration::from_secs(RPC_PROBE_TIMEOUT_SECS);
+
+    let slots: Vec<(String, u64)> = rpc_urls
+        .iter()
+        .filter_map(|rpc_url| {
+            let client = RpcClient::new_with_timeout(rpc_url.clone(), timeout);
+
+            // not every RPC provider implements getHealth
+            if let Err(err) = client.get_health() {
+                if !err.to_string().to_lowercase().contains("method not found") {
+                    warn!("RPC endpoint {} is unhealthy: {}", rpc_url, err);
+                    return None;
                 }
-            },
-            None => match read_keypair_file(&*shellexpand::tilde(DEFAULT_KEYPATH)) {
-                Ok(keypair) => keypair,
-                Err(e) => {
-                    error!("Failed to read keypair file: {}, {}", DEFAULT_KEYPATH, e);
-                    return Err(anyhow!(
-                        "Failed to read keypair file: {}, {}",
-                        DEFAULT_KEYPATH,
-                        e
-                    ));
+            }
+
+            match client.get_slot() {
+                Ok(slot) => Some((rpc_url.clone(), slot)),
+                Err(err) => {
+                    warn!("RPC endpoint {} is unreachable: {}", rpc_url, err);
+                    None
                 }
-            },
-        },
+            }
+        })
+        .collect();
+
+    let max_slot = slots
+        .iter()
+        .map(|(_, slot)| *slot)
+        .max()
+        .unwrap_or_default();
+
+    slots
+        .into_iter()
+        .filter_map(|(rpc_url, slot)| {
+            let lag = max_slot - slot;
+            if lag > MAX_RPC_SLOT_LAG {
+                warn!(
+                    "RPC endpoint {} is {} slots behind, skipping it",
+                    rpc_url, lag
+                );
+                None
+            } else {
+                info!("RPC endpoint {} is healthy (slot {})", rpc_url, slot);
+                Some(rpc_url)
+            }
+        })
+        .collect()
+}
+
+/// Returns the endpoint in use for the RPC url of the config: once an endpoint has
+/// failed, the next healthy endpoint of the list replaces it.
+pub fn rpc_endpoint(case_config: &CaseConfig) -> String {
+    let endpoints = case_config.rpc_endpoints.lock().unwrap();
+
+    match endpoints.healthy.get(endpoints.current) {
+        Some(rpc_url) => rpc_url.clone(),
+        None => case_config.rpc_url.clone(),
+    }
+}
+
+/// Moves on to the next healthy endpoint after a rate limit or a timeout of `rpc_url`.
+/// Returns `true` if a different endpoint is used from now on.
+pub fn rotate_rpc_endpoint(case_config: &CaseConfig, rpc_url: &str) -> bool {
+    let mut endpoints = case_config.rpc_endpoints.lock().unwrap();
+
+    // the endpoint might have been rotated already by another transaction
+    if endpoints.healthy.len() <= 1 || endpoints.healthy[endpoints.current] != rpc_url {
+        return false;
+    }
+
+    endpoints.current = (endpoints.current + 1) % endpoints.healthy.len();
+    warn!(
+        "RPC endpoint {} failed, switching to {}",
+        rpc_url, endpoints.healthy[endpoints.current]
+    );
+
+    true
+}
+
+/// Returns `true` if the error is likely to succeed when the request is sent again
+/// (expired blockhash, dropped or unconfirmed transaction, rate limit, timeout).
+pub fn is_transient_error(err: &anyhow::Error) -> bool {
+    let message = format!("{:?}", err).to_lowercase();
+
+    [
+        "blockhash not found",
+        "blockhashnotfound",
+        "block height exceeded",
+        "unable to confirm",
+        "timed out",
+        "timeout",
+        "node is behind",
+        "429",
+        "too many requests",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+/// Runs an RPC request through the endpoint in use for the RPC url of the config. When
+/// it fails with a transient error, the request is run again through the next healthy
+/// endpoint, until every endpoint has been tried.
+pub fn with_rpc_failover<T>(
+    case_config: &CaseConfig,
+    mut request: impl FnMut(&RpcClient) -> Result<T>,
+) -> Result<T> {
+    let first = rpc_endpoint(case_config);
+
+    loop {
+        let rpc_url = rpc_endpoint(case_config);
+        let rpc_client = RpcClient::new_with_commitment(rpc_url.clone(), case_config.commitment);
+
+        match request(&rpc_client) {
+            Err(err)
+                if is_transient_error(&err)
+                    && rotate_rpc_endpoint(case_config, &rpc_url)
+                    && rpc_endpoint(case_config) != first => {}
+            result => return result,
+        }
+    }
+}
+
+/// Returns the genesis hash of the RPC of the config, requested once. Returns `None` if
+/// the RPC cannot be reached.
+pub fn genesis_hash(case_config: &CaseConfig) -> Option<String> {
+    let mut genesis_hash = case_config.genesis_hash.lock().unwrap();
+
+    if genesis_hash.is_none() {
+        let rpc_client = RpcClient::new(case_config.rpc_url.clone());
+        *genesis_hash = Some(match rpc_client.get_genesis_hash() {
+            Ok(hash) => Some(hash.to_string()),
+            Err(err) => {
+                warn!("Failed to get the genesis hash of the RPC: {}", err);
+                None
+            }
+        });
+    }
+
+    genesis_hash.clone().flatten()
+}
+
+/// Reads a keypair from a file. The keypair can be a JSON byte array (solana-keygen
+/// format), a BIP39 seed phrase or a base58-encoded secret key. A seed phrase is derived
+/// with the derivation path (e.g. "m/44'/501'/0'/0'") or, without one, from the seed
+/// directly, the same as `solana-keygen recover`.
+pub fn read_keypair(path: &str, rpc_url: Option<&str>, global: &GlobalOptions) -> Result<Keypair> {
+    let path = shellexpand::tilde(path.trim()).to_string();
+
+    if !Path::new(&path).is_file() {
+        error!("Failed to read keypair file: {}, file not found", path);
+        return Err(anyhow!(
+            "Failed to read keypair file: {}, file not found",
+            path
+        ));
+    }
+
+    let content = fs::read_to_string(&path).map_err(|err| {
+        error!("Failed to read keypair file: {}, {}", path, err);
+        anyhow!("Failed to read keypair file: {}, {}", path, err)
+    })?;
+    let keypair = parse_keypair(&content, global.derivation_path.as_deref()).map_err(|err| {
+        let error = SetupError::InvalidKeypair(format!("file '{}'", path), err.to_string());
+        error!("{:?}", error);
+        error
+    })?;
+
+    audit_keypair_permissions(&path, rpc_url, global.insecure_keypair)?;
+
+    Ok(keypair)
+}
+
+/// Reads the keypair of the `CASE_KEYPAIR` environment variable, which is either the path
+/// of a keypair file or the keypair itself.
+fn read_keypair_env(value: &str, rpc_url: Option<&str>, global: &GlobalOptions) -> Result<Keypair> {
+    let path = shellexpand::tilde(value.trim()).to_string();
+
+    // a value that looks like a path is reported as a missing file rather than as an
+    // invalid key
+    if Path::new(&path).is_file()
+        || value.ends_with(".json")
+        || value.contains(std::path::MAIN_SEPARATOR)
+    {
+        return read_keypair(value, rpc_url, global);
+    }
+
+    parse_keypair(value, global.derivation_path.as_deref()).map_err(|err| {
+        let error = SetupError::InvalidKeypair("value".to_string(), err.to_string());
+        error!("{:?}", error);
+        error.into()
+    })
+}
+
+/// Parses the content of a keypair: a JSON byte array, a seed phrase or a base58
+/// secret key.
+fn parse_keypair(content: &str, derivation_path: Option<&str>) -> Result<Keypair> {
+    let content = content.trim();
+
+    if content.starts_with('[') {
+        let bytes: Vec<u8> = serde_json::from_str(content)
+            .map_err(|err| anyhow!("invalid JSON byte array ({})", err))?;
+        return Keypair::from_bytes(&bytes).map_err(|err| anyhow!(err.to_string()));
+    }
+
+    let words: Vec<&str> = content.split_whitespace().collect();
+
+    if words.len() > 1 {
+        if !SEED_PHRASE_WORDS.contains(&words.len()) {
+            return Err(anyhow!(
+                "a seed phrase must have 12, 15, 18, 21 or 24 words, found {}",
+                words.len()
+            ));
+        }
+
+        return keypair_from_seed_phrase(&words.join(" "), derivation_path);
+    }
+
+    let bytes = bs58::decode(content)
+        .into_vec()
+        .map_err(|_| anyhow!("not a JSON byte array, seed phrase or base58 secret key"))?;
+
+    Keypair::from_bytes(&bytes).map_err(|_| {
+        anyhow!(
+            "a base58 secret key must be 64 bytes, found {}",
+            bytes.len()
+        )
+    })
+}
+
+fn keypair_from_seed_phrase(seed_phrase: &str, derivation_path: Option<&str>) -> Result<Keypair> {
+    // the words and checksum are checked, so that a mistyped phrase does not derive a
+    // different (empty) wallet
+    Mnemonic::from_phrase(seed_phrase, Language::English)
+        .map_err(|err| anyhow!("invalid seed phrase ({})", err))?;
+
+    match derivation_path {
+        Some(derivation_path) => {
+            let path = DerivationPath::from_absolute_path_str(derivation_path).map_err(|err| {
+                SetupError::InvalidDerivationPath(derivation_path.to_string(), err.to_string())
+            })?;
+            let seed = generate_seed_from_seed_phrase_and_passphrase(seed_phrase, "");
+
+            keypair_from_seed_and_derivation_path(&seed, Some(path))
+                .map_err(|err| anyhow!(err.to_string()))
+        }
+        None => keypair_from_seed_phrase_and_passphrase(seed_phrase, "")
+            .map_err(|err| anyhow!(err.to_string())),
+    }
+}
+
+/// Check that the keypair file is not readable by other users. On mainnet this is
+/// an error unless `allow_insecure` is set (`--insecure-keypair`); on other clusters (or
+/// without an RPC url) only a warning is printed.
+#[cfg(unix)]
+pub fn audit_keypair_permissions(
+    keypair_path: &str,
+    rpc_url: Option<&str>,
+    allow_insecure: bool,
+) -> Result<()> {
+    let mode = fs::metadata(keypair_path)?.permissions().mode();
+
+    if mode & 0o004 == 0 {
+        return Ok(());
+    }
+
+    // clusters that cannot be identified (e.g. localnet) are not treated as mainnet
+    let mainnet = match rpc_url {
+        Some(rpc_url) => matches!(
+            get_cluster(RpcClient::new(rpc_url.to_string())),
+            Ok(Cluster::Mainnet)
+        ),
+        None => false,
     };
 
-    Ok(CaseConfig { rpc_url, keypair })
+    if mainnet && !allow_insecure {
+        let error = SetupError::InsecureKeypair(keypair_path.to_string());
+        error!("{:?}", error);
+        return Err(error.into());
+    }
+
+    warn!("Keypair file {} is world-readable", keypair_path);
+    case_println!(
+        "{}{}",
+        WARNING_EMOJI,
+        style(format!(
+            "Keypair file '{}' is readable by other users (chmod 600 to fix).",
+            keypair_path
+        ))
+        .yellow()
+    );
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn audit_keypair_permissions(
+    _keypair_path: &str,
+    _rpc_url: Option<&str>,
+    _allow_insecure: bool,
+) -> Result<()> {
+    Ok(())
+}
+
+/// Warn when the treasury wallet is the keypair used to sign the transactions, since
+/// the proceeds of the mint would then accumulate in a hot wallet.
+pub fn check_treasury_wallet(keypair: &Pubkey, treasury_wallet: &Pubkey) {
+    if keypair == treasury_wallet {
+        warn!("Treasury wallet {} is the signing keypair", treasury_wallet);
+        case_println!(
+            "{}{}",
+            WARNING_EMOJI,
+            style(
+                "The treasury wallet is the keypair used by case: mint proceeds will be \
+                 held in this (hot) wallet. Consider setting 'solTreasuryAccount'."
+            )
+            .yellow()
+        );
+    }
 }
@@ -1,29 +1,62 @@
-use std::str::FromStr;
+use std::{fs, str::FromStr};
 
 use anchor_client::solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
 use anyhow::Result;
 use chrono::NaiveDateTime;
 use console::style;
-use tars::{utils::is_feature_active, EndSettingType, WhitelistMintMode};
+use serde::Serialize;
+use tars::{EndSettingType, WhitelistMintMode};
 
-use crate::{cache::load_cache, tars::*, common::*, pdas::get_collection_pda, utils::*};
+use crate::{cache::load_cluster_cache, tars::*, common::*, pdas::get_collection_pda, utils::*};
 
 pub struct ShowArgs {
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
     pub cache: String,
     pub tars: Option<String>,
     pub unminted: bool,
+    pub json: bool,
+    /// File to write the unminted items to (JSON, or CSV with a ".csv" extension).
+    pub output_file: Option<String>,
 }
 
-// TODO: change the value '1' for the corresponding constant once the
-// new version of the tars crate is published
-const SWAP_REMOVE_FEATURE_INDEX: usize = 1;
-// number of indices per line
-const PER_LINE: usize = 11;
+/// Result of the show command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShowOutcome {
+    pub tars: String,
+    pub authority: String,
+    pub wallet: String,
+    pub collection_mint: Option<String>,
+    pub token_mint: Option<String>,
+    pub items_available: u64,
+    pub items_redeemed: u64,
+    pub price: u64,
+    pub symbol: String,
+    pub go_live_date: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unminted: Option<Vec<u32>>,
+    /// Config line of each unminted index.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unminted_items: Option<Vec<UnmintedItem>>,
+    /// Complete deserialized state of the tars (--json).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<Value>,
+}
+
+/// Config line of an unminted index. The name and uri are read from the tars account
+/// and, for tars with hidden settings (no config lines), from the cache.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmintedItem {
+    pub index: u32,
+    pub name: Option<String>,
+    pub uri: Option<String>,
+}
 
-pub fn process_show(args: ShowArgs) -> Result<()> {
-    println!(
+pub fn process_show(args: ShowArgs) -> Result<ShowOutcome> {
+    case_println!(
         "{} {}Looking up tars",
         if args.unminted {
             style("[1/2]").bold().dim()
@@ -36,27 +69,28 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
     let pb = spinner_with_style();
     pb.set_message("Connecting...");
 
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+
     // the tars id specified takes precedence over the one from the cache
 
-    let tars_id = if let Some(tars) = args.tars {
-        tars
-    } else {
-        let cache = load_cache(&args.cache, false)?;
-        cache.program.tars
+    // the cache is only required when it is the source of the tars id
+    let cache = match &args.tars {
+        Some(_) => load_cluster_cache(&case_config, &args.cache, false).ok(),
+        None => Some(load_cluster_cache(&case_config, &args.cache, false)?),
+    };
+
+    let tars_id = match args.tars {
+        Some(tars) => tars,
+        None => cache
+            .as_ref()
+            .map(|cache| cache.program.tars.clone())
+            .unwrap_or_default(),
     };
 
-    let case_config = case_setup(args.keypair, args.rpc_url)?;
     let client = setup_client(&case_config)?;
     let program = client.program(TARS_ID);
 
-    let tars_id = match Pubkey::from_str(&tars_id) {
-        Ok(tars_id) => tars_id,
-        Err(_) => {
-            let error = anyhow!("Failed to parse tars id: {}", tars_id);
-            error!("{:?}", error);
-            return Err(error);
-        }
-    };
+    let tars_id = resolve_pubkey(&tars_id, &args.global.address_book)?;
 
     let collection_mint =
         if let Ok((_, collection_pda)) = get_collection_pda(&tars_id, &program) {
@@ -66,11 +100,32 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
         };
 
     let cndy_state = get_tars_state(&case_config, &tars_id)?;
+    let state = if args.json {
+        Some(tars_state_json(&cndy_state))
+    } else {
+        None
+    };
     let cndy_data = cndy_state.data;
 
     pb.finish_and_clear();
 
-    println!(
+    let mut outcome = ShowOutcome {
+        tars: tars_id.to_string(),
+        authority: cndy_state.authority.to_string(),
+        wallet: cndy_state.wallet.to_string(),
+        collection_mint: collection_mint.map(|mint| mint.to_string()),
+        token_mint: cndy_state.token_mint.map(|mint| mint.to_string()),
+        items_available: cndy_data.items_available,
+        items_redeemed: cndy_state.items_redeemed,
+        price: cndy_data.price,
+        symbol: cndy_data.symbol.clone(),
+        go_live_date: cndy_data.go_live_date,
+        unminted: None,
+        unminted_items: None,
+        state,
+    };
+
+    case_println!(
         "\n{}{} {}",
         TARS_EMOJI,
         style("Tars ID:").dim(),
@@ -79,7 +134,7 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
 
     // tars state and data
 
-    println!(" {}", style(":").dim());
+    case_println!(" {}", style(":").dim());
     print_with_style("", "authority", cndy_state.authority.to_string());
     print_with_style("", "wallet", cndy_state.wallet.to_string());
     match collection_mint {
@@ -170,10 +225,10 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
     }
 
     // hidden settings
-    if let Some(hidden_settings) = cndy_data.hidden_settings {
+    if let Some(hidden_settings) = &cndy_data.hidden_settings {
         print_with_style("", "hidden settings", "".to_string());
-        print_with_style(":   ", "name", hidden_settings.name);
-        print_with_style(":   ", "uri", hidden_settings.uri);
+        print_with_style(":   ", "name", hidden_settings.name.clone());
+        print_with_style(":   ", "uri", hidden_settings.uri.clone());
         print_with_style(
             ":   ",
             "hash",
@@ -230,105 +285,130 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
     // unminted indices
 
     if args.unminted {
-        println!(
+        case_println!(
             "\n{} {}Retrieving unminted indices",
             style("[2/2]").bold().dim(),
             LOOKING_GLASS_EMOJI
         );
 
-        let mut start = CONFIG_ARRAY_START
-            + STRING_LEN_SIZE
-            + CONFIG_LINE_SIZE * cndy_data.items_available as usize
-            + STRING_LEN_SIZE
-            + cndy_data
-                .items_available
-                .checked_div(8)
-                .expect("Numerical overflow error") as usize
-            + STRING_LEN_SIZE;
-
         let pb = spinner_with_style();
         pb.set_message("Connecting...");
         // retrieve the (raw) tars data
         let data = program.rpc().get_account_data(&tars_id)?;
 
         pb.finish_and_clear();
-        let mut index = 0;
-        let mut indices = vec![];
-
-        if is_feature_active(&cndy_data.uuid, SWAP_REMOVE_FEATURE_INDEX) {
-            start += 1; // needed to get around rounding precision
-            let remaining = cndy_data.items_available - cndy_state.items_redeemed;
-            for i in 0..remaining {
-                let slice = start + (i * 4) as usize;
-                indices.push(u32::from_le_bytes(
-                    data[slice..slice + 4].try_into().unwrap(),
-                ));
-            }
-        } else {
-            while start < data.len() {
-                let mask = 1u8 << 7;
-
-                for i in 0..8 {
-                    if index < cndy_data.items_available {
-                        // unused mint indices have the 'flag' set to 0
-                        if (data[start] & (mask >> i)) == 0 {
-                            indices.push(index as u32);
-                        }
-                        index += 1;
-                    }
-                }
-
-                start += 1;
-            }
-        }
+        let indices = unminted_indices(&data, &cndy_data, cndy_state.items_redeemed);
 
         if indices.is_empty() {
-            println!(
+            case_println!(
                 "\n{}{}",
                 PAPER_EMOJI,
                 style("All items of the tars have been minted.").dim()
             );
         } else {
-            // makes sure all items are in order
-            indices.sort_unstable();
             // logs all indices
             info!("unminted list: {:?}", indices);
 
-            println!(
+            // the cache only has the items of its own tars
+            let cache_items = cache
+                .as_ref()
+                .filter(|cache| cache.program.tars == tars_id.to_string())
+                .map(|cache| &cache.items);
+
+            let items: Vec<UnmintedItem> = indices
+                .iter()
+                .map(|index| {
+                    let (name, uri) = match config_line(&data, *index as usize) {
+                        Some((name, uri)) => (Some(name), Some(uri)),
+                        None => match cache_items.and_then(|items| items.get(&index.to_string())) {
+                            Some(item) => {
+                                (Some(item.name.clone()), Some(item.metadata_link.clone()))
+                            }
+                            None => (None, None),
+                        },
+                    };
+
+                    UnmintedItem {
+                        index: *index,
+                        name,
+                        uri,
+                    }
+                })
+                .collect();
+
+            case_println!(
                 "\n{}{}",
                 PAPER_EMOJI,
-                style(format!("Unminted list ({} total):", indices.len())).dim()
+                style(format!("Unminted list ({} total):", items.len())).dim()
             );
-            let mut current = 0;
-
-            for i in indices {
-                if current == 0 {
-                    println!("{}", style(" :").dim());
-                    print!("{}", style(" :.. ").dim());
-                }
-                current += 1;
-
-                print!(
-                    "{:<5}{}",
-                    i,
-                    if current == PER_LINE {
-                        current = 0;
-                        "\n"
-                    } else {
-                        " "
-                    }
+            case_println!("{}", style(" :").dim());
+
+            for item in &items {
+                case_println!(
+                    "{}{:<5} {} {}",
+                    style(" :.. ").dim(),
+                    item.index,
+                    item.name.as_deref().unwrap_or("-"),
+                    style(item.uri.as_deref().unwrap_or("")).dim()
                 );
             }
-            // just adds a new line break
-            println!();
+
+            outcome.unminted = Some(indices);
+            outcome.unminted_items = Some(items);
+        }
+
+        if outcome.unminted.is_none() {
+            outcome.unminted = Some(Vec::new());
+            outcome.unminted_items = Some(Vec::new());
+        }
+
+        if let Some(path) = &args.output_file {
+            write_unminted(path, outcome.unminted_items.as_deref().unwrap_or_default())?;
+            case_println!("\nUnminted items written to '{}'.", path);
         }
     }
 
-    Ok(())
+    Ok(outcome)
+}
+
+/// Writes the unminted items as CSV when the file has a `.csv` extension, otherwise as
+/// JSON.
+fn write_unminted(path: &str, items: &[UnmintedItem]) -> Result<()> {
+    let is_csv = Path::new(path)
+        .extension()
+        .map(|extension| extension.eq_ignore_ascii_case("csv"))
+        .unwrap_or(false);
+
+    let content = if is_csv {
+        let mut csv = String::from("index,name,uri\n");
+        for item in items {
+            csv.push_str(&format!(
+                "{},{},{}\n",
+                item.index,
+                csv_field(item.name.as_deref().unwrap_or_default()),
+                csv_field(item.uri.as_deref().unwrap_or_default())
+            ));
+        }
+        csv
+    } else {
+        serde_json::to_string_pretty(items)?
+    };
+
+    fs::write(path, content)
+        .map_err(|err| anyhow!("Failed to write unminted file '{}': {}", path, err))
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains(&[',', '"', '\n'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
 }
 
 fn print_with_style(indent: &str, key: &str, value: String) {
-    println!(
+    case_println!(
         " {} {}",
         style(format!("{}:.. {}:", indent, key)).dim(),
         value
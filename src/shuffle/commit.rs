@@ -0,0 +1,131 @@
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    cache::{check_cache_cluster, load_cache},
+    common::*,
+    shuffle::{Commitment, ShuffleProof},
+    tars::{get_tars_state, TARS_ID},
+    verify_config_hash::{hidden_settings_hash, VerifyConfigHashError},
+};
+
+pub struct ShuffleCommitArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub tars: Option<String>,
+    pub proof: String,
+    pub slot_offset: u64,
+    pub secret_hash: Option<String>,
+}
+
+/// Result of the shuffle commit command.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShuffleCommitOutcome {
+    pub proof: String,
+    pub tars: String,
+    pub cache_hash: String,
+    pub commitment: Commitment,
+}
+
+pub fn process_shuffle_commit(args: ShuffleCommitArgs) -> Result<ShuffleCommitOutcome> {
+    if Path::new(&args.proof).exists() {
+        return Err(anyhow!(
+            "Shuffle proof file '{}' already exists, a shuffle can only be committed once.",
+            args.proof
+        ));
+    }
+
+    let mut cache = load_cache(&args.cache, false)?;
+
+    // the tars id specified takes precedence over the one from the cache
+    let tars_id = args.tars.unwrap_or_else(|| cache.program.tars.clone());
+    let tars_pubkey = resolve_pubkey(&tars_id, &args.global.address_book)?;
+
+    case_println!(
+        "{} {}Checking the hidden settings hash",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    check_cache_cluster(&case_config, &mut cache)?;
+    let client = setup_client(&case_config)?;
+    let rpc_client = client.program(TARS_ID).rpc();
+    let tars_state = get_tars_state(&case_config, &tars_pubkey)?;
+
+    let hidden_settings = tars_state
+        .data
+        .hidden_settings
+        .ok_or_else(|| VerifyConfigHashError::NoHiddenSettings(tars_pubkey.to_string()))?;
+
+    // the items are committed by the hash set at deploy, so they cannot change once the
+    // ordering is known
+    let on_chain_hash = String::from_utf8_lossy(&hidden_settings.hash).to_string();
+    let cache_hash = hidden_settings_hash(&cache.items);
+
+    if on_chain_hash != cache_hash {
+        return Err(VerifyConfigHashError::HashMismatch(cache_hash, on_chain_hash).into());
+    }
+
+    case_println!(
+        "\n{} {}Committing the randomness source",
+        style("[2/2]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    let committed_slot = rpc_client.get_slot()?;
+    let commitment = match args.secret_hash {
+        Some(secret_hash) => Commitment::Secret {
+            secret_hash: secret_hash.to_lowercase(),
+        },
+        None => Commitment::SlotHash {
+            target_slot: committed_slot + args.slot_offset,
+        },
+    };
+
+    let proof = ShuffleProof {
+        tars: tars_pubkey.to_string(),
+        cache_hash: cache_hash.clone(),
+        items: cache
+            .items
+            .keys()
+            .filter(|index| !index.starts_with('-'))
+            .count() as u32,
+        committed_slot,
+        commitment: commitment.clone(),
+        randomness: None,
+        seed: None,
+        ordering: None,
+    };
+    proof.save(&args.proof)?;
+
+    match &commitment {
+        Commitment::SlotHash { target_slot } => case_println!(
+            "Ordering will be derived from the blockhash of slot {} (current slot {}).",
+            target_slot,
+            committed_slot
+        ),
+        Commitment::Secret { secret_hash } => case_println!(
+            "Ordering will be derived from the secret with SHA-256 hash {}.",
+            secret_hash
+        ),
+    }
+    case_println!(
+        "{}",
+        style(format!(
+            "Publish '{}' now, before the randomness is known.",
+            args.proof
+        ))
+        .bold()
+    );
+
+    Ok(ShuffleCommitOutcome {
+        proof: args.proof,
+        tars: tars_pubkey.to_string(),
+        cache_hash,
+        commitment,
+    })
+}
@@ -0,0 +1,31 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ShuffleError {
+    #[error("Shuffle proof file '{0}' not found, run 'case shuffle commit' first.")]
+    ProofNotFound(String),
+
+    #[error("Shuffle proof file '{0}' already has a revealed ordering.")]
+    AlreadyRevealed(String),
+
+    #[error("The shuffle has not been revealed yet, run 'case shuffle reveal' first.")]
+    NotRevealed,
+
+    #[error("Target slot {0} is not finalized yet (finalized slot {1}), try again later.")]
+    SlotNotFinalized(u64, u64),
+
+    #[error("No block found between slot {0} and {1}.")]
+    NoBlock(u64, u64),
+
+    #[error("A secret is required to reveal a secret commitment.")]
+    MissingSecret,
+
+    #[error("The secret does not match the committed hash.")]
+    SecretMismatch,
+
+    #[error("The cache hash '{0}' does not match the committed hash '{1}'.")]
+    CacheChanged(String, String),
+
+    #[error("Verification failed: {0}")]
+    VerificationFailed(String),
+}
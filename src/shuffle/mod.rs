@@ -0,0 +1,13 @@
+pub mod commit;
+pub mod errors;
+pub mod proof;
+pub mod reveal;
+pub mod source;
+pub mod verify;
+
+pub use commit::*;
+pub use errors::*;
+pub use proof::*;
+pub use reveal::*;
+pub use source::*;
+pub use verify::*;
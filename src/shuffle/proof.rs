@@ -0,0 +1,46 @@
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::*,
+    shuffle::{Commitment, Randomness, ShuffleError},
+};
+
+/// Proof of a shuffle of the items of a hidden drop, published so that anyone can check
+/// that the ordering was derived from randomness committed before it was known.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShuffleProof {
+    pub tars: String,
+    /// Hidden settings hash of the items (see `verify-config-hash`).
+    pub cache_hash: String,
+    pub items: u32,
+    /// Slot at which the commitment was made.
+    pub committed_slot: u64,
+    pub commitment: Commitment,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub randomness: Option<Randomness>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<String>,
+    /// Item index assigned to each mint number (mint #1 is the first entry).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ordering: Option<Vec<u32>>,
+}
+
+impl ShuffleProof {
+    pub fn load(path: &str) -> Result<ShuffleProof> {
+        if !Path::new(path).is_file() {
+            return Err(ShuffleError::ProofNotFound(path.to_string()).into());
+        }
+
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|err| anyhow!("Failed to parse shuffle proof '{}': {}", path, err))
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
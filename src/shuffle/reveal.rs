@@ -0,0 +1,82 @@
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    cache::load_cache,
+    common::*,
+    shuffle::{shuffle_items, shuffle_seed, ShuffleError, ShuffleProof},
+    tars::TARS_ID,
+    verify_config_hash::hidden_settings_hash,
+};
+
+pub struct ShuffleRevealArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub proof: String,
+    pub secret: Option<String>,
+}
+
+/// Result of the shuffle reveal command.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShuffleRevealOutcome {
+    pub proof: String,
+    pub seed: String,
+    pub ordering: Vec<u32>,
+}
+
+pub fn process_shuffle_reveal(args: ShuffleRevealArgs) -> Result<ShuffleRevealOutcome> {
+    let mut proof = ShuffleProof::load(&args.proof)?;
+
+    if proof.ordering.is_some() {
+        return Err(ShuffleError::AlreadyRevealed(args.proof).into());
+    }
+
+    let cache = load_cache(&args.cache, false)?;
+    let cache_hash = hidden_settings_hash(&cache.items);
+
+    if cache_hash != proof.cache_hash {
+        return Err(ShuffleError::CacheChanged(cache_hash, proof.cache_hash).into());
+    }
+
+    case_println!(
+        "{} {}Revealing the randomness",
+        style("[1/1]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = setup_client(&case_config)?;
+    let rpc_client = client.program(TARS_ID).rpc();
+
+    let randomness = proof
+        .commitment
+        .reveal(&rpc_client, args.secret.as_deref())?;
+    let seed = shuffle_seed(&randomness, &proof.cache_hash);
+    let ordering = shuffle_items(proof.items, &seed);
+
+    if let Some(slot) = randomness.slot {
+        case_println!("{} {}", style("Slot:").bold(), slot);
+    }
+    case_println!("{} {}", style("Randomness:").bold(), randomness.value);
+    case_println!("{} {}", style("Seed:").bold(), seed);
+
+    proof.randomness = Some(randomness);
+    proof.seed = Some(seed.clone());
+    proof.ordering = Some(ordering.clone());
+    proof.save(&args.proof)?;
+
+    case_println!(
+        "\nOrdering of {} item(s) saved to '{}'.",
+        ordering.len(),
+        args.proof
+    );
+
+    Ok(ShuffleRevealOutcome {
+        proof: args.proof,
+        seed,
+        ordering,
+    })
+}
@@ -0,0 +1,144 @@
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use data_encoding::HEXLOWER;
+use ring::digest::{digest, Context, SHA256};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+
+use crate::{common::*, shuffle::ShuffleError};
+
+/// Maximum number of slots after the target slot searched for a block, since slots can
+/// be skipped by their leader.
+const MAX_SKIPPED_SLOTS: u64 = 100;
+
+/// Source of the randomness of a shuffle, committed before the ordering is known.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "source", rename_all = "camelCase")]
+pub enum Commitment {
+    /// Blockhash of the first block at or after a future slot.
+    #[serde(rename_all = "camelCase")]
+    SlotHash { target_slot: u64 },
+    /// Secret value (e.g. from an external beacon) published at reveal, committed by
+    /// its SHA-256 hash.
+    #[serde(rename_all = "camelCase")]
+    Secret { secret_hash: String },
+}
+
+/// Random value of a commitment, together with the data needed to verify it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Randomness {
+    /// Slot of the block (slot hash commitments).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+    /// Blockhash or secret.
+    pub value: String,
+}
+
+impl Commitment {
+    /// Returns the random value of the commitment, once it is available.
+    pub fn reveal(&self, rpc_client: &RpcClient, secret: Option<&str>) -> Result<Randomness> {
+        match self {
+            Commitment::SlotHash { target_slot } => {
+                let finalized =
+                    rpc_client.get_slot_with_commitment(CommitmentConfig::finalized())?;
+
+                if finalized < *target_slot {
+                    return Err(ShuffleError::SlotNotFinalized(*target_slot, finalized).into());
+                }
+
+                let (slot, blockhash) = first_block(rpc_client, *target_slot)?;
+
+                Ok(Randomness {
+                    slot: Some(slot),
+                    value: blockhash,
+                })
+            }
+            Commitment::Secret { secret_hash } => {
+                let secret = secret.ok_or(ShuffleError::MissingSecret)?;
+
+                if sha256_hex(secret.as_bytes()) != *secret_hash {
+                    return Err(ShuffleError::SecretMismatch.into());
+                }
+
+                Ok(Randomness {
+                    slot: None,
+                    value: secret.to_string(),
+                })
+            }
+        }
+    }
+
+    /// Checks that the random value is the one of the commitment.
+    pub fn verify(&self, rpc_client: &RpcClient, randomness: &Randomness) -> Result<()> {
+        let expected = self.reveal(rpc_client, Some(&randomness.value))?;
+
+        if expected != *randomness {
+            return Err(ShuffleError::VerificationFailed(format!(
+                "expected randomness {:?}, found {:?}",
+                expected, randomness
+            ))
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the slot and blockhash of the first block at or after `slot`.
+fn first_block(rpc_client: &RpcClient, slot: u64) -> Result<(u64, String)> {
+    let last_slot = slot + MAX_SKIPPED_SLOTS;
+    let slot = rpc_client
+        .get_blocks(slot, Some(last_slot))?
+        .first()
+        .copied()
+        .ok_or(ShuffleError::NoBlock(slot, last_slot))?;
+
+    Ok((slot, rpc_client.get_block(slot)?.blockhash))
+}
+
+/// Returns the seed of the shuffle: the SHA-256 of the random value and the hash of the
+/// items, so that the ordering also depends on the committed items.
+pub fn shuffle_seed(randomness: &Randomness, cache_hash: &str) -> String {
+    sha256_hex(format!("{}:{}", randomness.value, cache_hash).as_bytes())
+}
+
+/// Shuffles the item indices `0..items` with a Fisher-Yates shuffle, where the random
+/// numbers are derived from SHA-256 hashes of the seed so that the ordering can be
+/// recomputed with any SHA-256 implementation.
+pub fn shuffle_items(items: u32, seed: &str) -> Vec<u32> {
+    let mut ordering: Vec<u32> = (0..items).collect();
+
+    for i in (1..ordering.len()).rev() {
+        let j = random_below(seed, i as u64, i as u64 + 1);
+        ordering.swap(i, j as usize);
+    }
+
+    ordering
+}
+
+/// Returns a uniform random number in `0..bound` for position `i`: the first 8 bytes
+/// (little-endian) of SHA-256("<seed>:<i>:<counter>"), rejecting values that would bias
+/// the modulo.
+fn random_below(seed: &str, i: u64, bound: u64) -> u64 {
+    let zone = u64::MAX - (u64::MAX % bound);
+
+    for counter in 0u64.. {
+        let mut context = Context::new(&SHA256);
+        context.update(format!("{}:{}:{}", seed, i, counter).as_bytes());
+        let hash = context.finish();
+
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&hash.as_ref()[..8]);
+        let value = u64::from_le_bytes(bytes);
+
+        if value < zone {
+            return value % bound;
+        }
+    }
+
+    unreachable!()
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    HEXLOWER.encode(digest(&SHA256, data).as_ref())
+}
@@ -0,0 +1,100 @@
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    common::*,
+    shuffle::{shuffle_items, shuffle_seed, ShuffleError, ShuffleProof},
+    tars::{get_tars_state, TARS_ID},
+};
+
+pub struct ShuffleVerifyArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub proof: String,
+}
+
+/// Result of the shuffle verify command.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShuffleVerifyOutcome {
+    pub proof: String,
+    pub tars: String,
+    pub seed: String,
+}
+
+/// Verifies a shuffle proof using only on-chain data: the committed items against the
+/// hidden settings hash, the randomness against its commitment and the ordering
+/// against the seed.
+pub fn process_shuffle_verify(args: ShuffleVerifyArgs) -> Result<ShuffleVerifyOutcome> {
+    let proof = ShuffleProof::load(&args.proof)?;
+    let (randomness, seed, ordering) = match (&proof.randomness, &proof.seed, &proof.ordering) {
+        (Some(randomness), Some(seed), Some(ordering)) => (randomness, seed, ordering),
+        _ => return Err(ShuffleError::NotRevealed.into()),
+    };
+
+    case_println!(
+        "{} {}Verifying shuffle proof",
+        style("[1/1]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = setup_client(&case_config)?;
+    let rpc_client = client.program(TARS_ID).rpc();
+    let tars_pubkey = Pubkey::from_str(&proof.tars)?;
+    let tars_state = get_tars_state(&case_config, &tars_pubkey)?;
+
+    let check = |name: &str, result: Result<()>| -> Result<()> {
+        match result {
+            Ok(()) => {
+                case_println!("{} {}", style("✔").green(), name);
+                Ok(())
+            }
+            Err(err) => {
+                case_println!("{} {}", style("✘").red(), name);
+                Err(err)
+            }
+        }
+    };
+
+    check(
+        "items match the hidden settings hash",
+        match &tars_state.data.hidden_settings {
+            Some(hidden_settings)
+                if String::from_utf8_lossy(&hidden_settings.hash) == proof.cache_hash =>
+            {
+                Ok(())
+            }
+            _ => Err(ShuffleError::VerificationFailed(
+                "the cache hash does not match the tars hidden settings".to_string(),
+            )
+            .into()),
+        },
+    )?;
+
+    check(
+        "randomness matches the commitment",
+        proof.commitment.verify(&rpc_client, randomness),
+    )?;
+
+    let expected_seed = shuffle_seed(randomness, &proof.cache_hash);
+    check(
+        "ordering matches the seed",
+        if expected_seed != *seed {
+            Err(ShuffleError::VerificationFailed(format!("expected seed {}", expected_seed)).into())
+        } else if shuffle_items(proof.items, seed) != *ordering {
+            Err(ShuffleError::VerificationFailed("ordering does not match".to_string()).into())
+        } else {
+            Ok(())
+        },
+    )?;
+
+    case_println!("\n{}", style("Shuffle proof is valid.").green().bold());
+
+    Ok(ShuffleVerifyOutcome {
+        proof: args.proof,
+        tars: proof.tars.clone(),
+        seed: seed.clone(),
+    })
+}
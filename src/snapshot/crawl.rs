@@ -0,0 +1,156 @@
+use std::fmt::{self, Display};
+
+use anchor_client::solana_sdk::commitment_config::CommitmentLevel;
+use mpl_token_metadata::{deser::meta_deser, state::Metadata};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+};
+
+use crate::{cache::load_cluster_cache, common::*, config::CaseConfig, pdas::*};
+
+/// Offset of the update authority in a metadata account.
+const UPDATE_AUTHORITY_OFFSET: usize = 1; // key
+
+/// Offset of the first creator address in a metadata account.
+const FIRST_CREATOR_OFFSET: usize = 1 + // key
+    32 +                                // update authority
+    32 +                                // mint
+    4 + MAX_NAME_LENGTH +               // name
+    4 + MAX_SYMBOL_LENGTH +             // symbol
+    4 + MAX_URI_LENGTH +                // uri
+    2 +                                 // seller fee basis points
+    1 +                                 // option for creators
+    4; // u32 len
+
+/// Source of the NFTs of a snapshot.
+pub enum SnapshotSource {
+    /// NFTs minted by a tars.
+    Tars(Pubkey),
+    /// NFTs that are verified members of a collection.
+    Collection(Pubkey),
+}
+
+impl SnapshotSource {
+    /// Parses the source from the command arguments: the collection takes precedence;
+    /// otherwise the tars id (or the one from the cache file of the cluster) is used.
+    pub fn from_args(
+        tars: Option<String>,
+        collection: Option<String>,
+        case_config: &CaseConfig,
+        cache: &str,
+        address_book: &str,
+    ) -> Result<Self> {
+        if let Some(collection) = collection {
+            return Ok(SnapshotSource::Collection(resolve_pubkey(
+                &collection,
+                address_book,
+            )?));
+        }
+
+        let tars_id = match tars {
+            Some(tars_id) => tars_id,
+            None => load_cluster_cache(case_config, cache, false)?.program.tars,
+        };
+
+        Ok(SnapshotSource::Tars(resolve_pubkey(
+            &tars_id,
+            address_book,
+        )?))
+    }
+}
+
+impl Display for SnapshotSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotSource::Tars(tars) => write!(f, "tars {}", tars),
+            SnapshotSource::Collection(collection) => write!(f, "collection {}", collection),
+        }
+    }
+}
+
+/// Returns the metadata of all NFTs of the source, sorted by mint address.
+pub fn find_nft_metadata(program: &Program, source: &SnapshotSource) -> Result<Vec<Metadata>> {
+    let mut metadata = match source {
+        SnapshotSource::Tars(tars) => find_tars_metadata(program, tars)?,
+        SnapshotSource::Collection(collection) => find_collection_metadata(program, collection)?,
+    };
+
+    metadata.sort_by_key(|metadata| metadata.mint.to_string());
+    Ok(metadata)
+}
+
+/// Returns the metadata of the NFTs whose first verified creator is the tars creator PDA.
+pub fn find_tars_metadata(program: &Program, tars: &Pubkey) -> Result<Vec<Metadata>> {
+    let (creator, _bump) = find_tars_creator_pda(tars);
+    let accounts = get_metadata_accounts(program, FIRST_CREATOR_OFFSET, &creator)?;
+
+    Ok(accounts
+        .into_iter()
+        .filter(|metadata| {
+            metadata
+                .data
+                .creators
+                .as_ref()
+                .and_then(|creators| creators.iter().find(|creator| creator.verified))
+                .map(|first| first.address == creator)
+                .unwrap_or(false)
+        })
+        .collect())
+}
+
+/// Returns the metadata of the NFTs that are verified members of the collection.
+pub fn find_collection_metadata(program: &Program, collection: &Pubkey) -> Result<Vec<Metadata>> {
+    // items of a collection share the update authority of the collection NFT
+    let (_, collection_metadata) = get_metadata_pda(collection, program)?;
+    let accounts = get_metadata_accounts(
+        program,
+        UPDATE_AUTHORITY_OFFSET,
+        &collection_metadata.update_authority,
+    )?;
+
+    Ok(accounts
+        .into_iter()
+        .filter(|metadata| {
+            matches!(
+                &metadata.collection,
+                Some(item) if item.verified && item.key == *collection
+            )
+        })
+        .collect())
+}
+
+fn get_metadata_accounts(program: &Program, offset: usize, key: &Pubkey) -> Result<Vec<Metadata>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp {
+            offset,
+            bytes: MemcmpEncodedBytes::Base58(key.to_string()),
+            encoding: None,
+        })]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: None,
+            commitment: Some(CommitmentConfig {
+                commitment: CommitmentLevel::Confirmed,
+            }),
+        },
+        with_context: None,
+    };
+
+    let metadata_program = Pubkey::from_str(METAPLEX_PROGRAM_ID)?;
+    let accounts = program
+        .rpc()
+        .get_program_accounts_with_config(&metadata_program, config)?;
+
+    let mut metadata = Vec::with_capacity(accounts.len());
+
+    for (pubkey, account) in accounts {
+        match meta_deser(&mut account.data.as_slice()) {
+            Ok(m) => metadata.push(m),
+            Err(_) => warn!("Failed to deserialize metadata account: {}", pubkey),
+        }
+    }
+
+    Ok(metadata)
+}
@@ -0,0 +1,202 @@
+use std::{collections::HashSet, fs};
+
+use anchor_client::solana_sdk::program_pack::Pack;
+use console::style;
+use serde::Serialize;
+use spl_token::state::Account;
+
+use crate::{
+    common::*,
+    denylist::load_denylist,
+    snapshot::{find_nft_metadata, SnapshotSource},
+    utils::*,
+};
+
+/// Maximum number of accounts per `getMultipleAccounts` request.
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+pub struct SnapshotHoldersArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub tars: Option<String>,
+    pub collection: Option<String>,
+    pub output_file: String,
+}
+
+/// Current holder of an NFT.
+#[derive(Debug, Serialize)]
+pub struct Holder {
+    pub owner: String,
+    pub mint: String,
+    pub amount: u64,
+}
+
+/// Result of the snapshot holders command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotHoldersOutcome {
+    pub output_file: String,
+    pub mints: usize,
+    pub holders: usize,
+    pub unique_owners: usize,
+    /// Holdings of deny list addresses, left out of the snapshot.
+    pub denied: Vec<Holder>,
+}
+
+pub fn process_snapshot_holders(args: SnapshotHoldersArgs) -> Result<SnapshotHoldersOutcome> {
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let source = SnapshotSource::from_args(
+        args.tars,
+        args.collection,
+        &case_config,
+        &args.cache,
+        &args.global.address_book,
+    )?;
+
+    case_println!(
+        "{} {}Looking up NFTs of {}",
+        style("[1/3]").bold().dim(),
+        LOOKING_GLASS_EMOJI,
+        source
+    );
+
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_PROGRAM_ID);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+    let metadata = find_nft_metadata(&program, &source)?;
+    pb.finish_with_message(format!("Found {} NFT(s)", metadata.len()));
+
+    case_println!(
+        "\n{} {}Resolving token account owners",
+        style("[2/3]").bold().dim(),
+        COLLECTION_EMOJI
+    );
+
+    let denylist = load_denylist(&args.global.denylist)?;
+    let mints: Vec<Pubkey> = metadata.iter().map(|m| m.mint).collect();
+    let (denied, holders): (Vec<Holder>, Vec<Holder>) = find_holders(&program, &mints)?
+        .into_iter()
+        .partition(|holder| {
+            Pubkey::from_str(&holder.owner)
+                .map(|owner| denylist.contains(&owner))
+                .unwrap_or(false)
+        });
+
+    if !denied.is_empty() {
+        case_println!(
+            "{}{}",
+            WARNING_EMOJI,
+            style(format!(
+                "Skipped {} NFT(s) held by deny list addresses:",
+                denied.len()
+            ))
+            .yellow()
+        );
+        for holder in &denied {
+            case_println!("{} {} ({})", style(" :..").dim(), holder.owner, holder.mint);
+        }
+    }
+
+    case_println!(
+        "\n{} {}Writing snapshot",
+        style("[3/3]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    write_holders(&args.output_file, &holders)?;
+
+    let unique_owners = holders
+        .iter()
+        .map(|holder| holder.owner.as_str())
+        .collect::<HashSet<&str>>()
+        .len();
+
+    case_println!(
+        "{} {} holder(s) of {} NFT(s), {} unique owner(s)",
+        style("Snapshot saved to:").bold(),
+        holders.len(),
+        mints.len(),
+        unique_owners
+    );
+    case_println!("{}", style(&args.output_file).green());
+
+    Ok(SnapshotHoldersOutcome {
+        output_file: args.output_file,
+        mints: mints.len(),
+        holders: holders.len(),
+        unique_owners,
+        denied,
+    })
+}
+
+/// Returns the owner of the token account holding each mint; burned NFTs are skipped.
+fn find_holders(program: &Program, mints: &[Pubkey]) -> Result<Vec<Holder>> {
+    let pb = progress_bar_with_style(mints.len() as u64);
+    let mut token_accounts = Vec::with_capacity(mints.len());
+
+    for mint in mints {
+        let largest = program.rpc().get_token_largest_accounts(mint)?;
+
+        match largest.iter().find(|balance| balance.amount.amount != "0") {
+            Some(balance) => token_accounts.push((*mint, Pubkey::from_str(&balance.address)?)),
+            None => warn!("No token account holds mint {} (burned?)", mint),
+        }
+
+        pb.inc(1);
+    }
+
+    pb.finish_and_clear();
+
+    let mut holders = Vec::with_capacity(token_accounts.len());
+
+    for chunk in token_accounts.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let addresses: Vec<Pubkey> = chunk.iter().map(|(_, address)| *address).collect();
+        let accounts = program.rpc().get_multiple_accounts(&addresses)?;
+
+        for ((mint, address), account) in chunk.iter().zip(accounts) {
+            let account = match account {
+                Some(account) => Account::unpack(&account.data)?,
+                None => {
+                    warn!("Token account {} of mint {} not found", address, mint);
+                    continue;
+                }
+            };
+
+            holders.push(Holder {
+                owner: account.owner.to_string(),
+                mint: mint.to_string(),
+                amount: account.amount,
+            });
+        }
+    }
+
+    Ok(holders)
+}
+
+/// Writes the holders as CSV when the file has a `.csv` extension, otherwise as JSON.
+fn write_holders(path: &str, holders: &[Holder]) -> Result<()> {
+    let is_csv = Path::new(path)
+        .extension()
+        .map(|extension| extension.eq_ignore_ascii_case("csv"))
+        .unwrap_or(false);
+
+    let content = if is_csv {
+        let mut csv = String::from("owner,mint,amount\n");
+        for holder in holders {
+            csv.push_str(&format!(
+                "{},{},{}\n",
+                holder.owner, holder.mint, holder.amount
+            ));
+        }
+        csv
+    } else {
+        serde_json::to_string_pretty(holders)?
+    };
+
+    fs::write(path, content)
+        .map_err(|err| anyhow!("Failed to write snapshot file '{}': {}", path, err))
+}
@@ -0,0 +1,105 @@
+use std::fs;
+
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    common::*,
+    snapshot::{find_nft_metadata, SnapshotSource},
+    utils::*,
+};
+
+pub struct SnapshotMintsArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub tars: Option<String>,
+    pub collection: Option<String>,
+    pub include_metadata: bool,
+    pub output_file: String,
+}
+
+/// Mint address with its on-chain name and URI.
+#[derive(Debug, Serialize)]
+pub struct MintInfo {
+    pub mint: String,
+    pub name: String,
+    pub uri: String,
+}
+
+/// Result of the snapshot mints command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotMintsOutcome {
+    pub output_file: String,
+    pub mints: usize,
+}
+
+pub fn process_snapshot_mints(args: SnapshotMintsArgs) -> Result<SnapshotMintsOutcome> {
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let source = SnapshotSource::from_args(
+        args.tars,
+        args.collection,
+        &case_config,
+        &args.cache,
+        &args.global.address_book,
+    )?;
+
+    case_println!(
+        "{} {}Looking up NFTs of {}",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI,
+        source
+    );
+
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_PROGRAM_ID);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+    let metadata = find_nft_metadata(&program, &source)?;
+    pb.finish_with_message(format!("Found {} NFT(s)", metadata.len()));
+
+    case_println!(
+        "\n{} {}Writing snapshot",
+        style("[2/2]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    // on-chain strings are padded with null characters
+    let content = if args.include_metadata {
+        let mints: Vec<MintInfo> = metadata
+            .iter()
+            .map(|m| MintInfo {
+                mint: m.mint.to_string(),
+                name: m.data.name.trim_matches(char::from(0)).to_string(),
+                uri: m.data.uri.trim_matches(char::from(0)).to_string(),
+            })
+            .collect();
+        serde_json::to_string_pretty(&mints)?
+    } else {
+        let mints: Vec<String> = metadata.iter().map(|m| m.mint.to_string()).collect();
+        serde_json::to_string_pretty(&mints)?
+    };
+
+    fs::write(&args.output_file, content).map_err(|err| {
+        anyhow!(
+            "Failed to write snapshot file '{}': {}",
+            args.output_file,
+            err
+        )
+    })?;
+
+    case_println!(
+        "{} {} mint(s)",
+        style("Snapshot saved to:").bold(),
+        metadata.len()
+    );
+    case_println!("{}", style(&args.output_file).green());
+
+    Ok(SnapshotMintsOutcome {
+        output_file: args.output_file,
+        mints: metadata.len(),
+    })
+}
@@ -0,0 +1,7 @@
+pub mod crawl;
+pub mod holders;
+pub mod mints;
+
+pub use crawl::*;
+pub use holders::*;
+pub use mints::*;
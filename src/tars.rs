@@ -1,15 +1,28 @@
 use anchor_client::{solana_sdk::pubkey::Pubkey, Client, ClientError};
 use anyhow::{anyhow, Result};
 pub use tars::ID as TARS_ID;
-use tars::{Tars, TarsData, WhitelistMintMode, WhitelistMintSettings};
+use serde_json::{json, Value};
 use spl_token::id as token_program_id;
+use tars::{
+    utils::is_feature_active, EndSettingType, Tars, TarsData, WhitelistMintMode,
+    WhitelistMintSettings,
+};
 
 use crate::{
+    case_println,
     config::{data::CaseConfig, price_as_lamports, ConfigData},
+    constants::{
+        CONFIG_ARRAY_START, CONFIG_LINE_SIZE, CONFIG_NAME_OFFSET, CONFIG_URI_OFFSET,
+        MAX_NAME_LENGTH, MAX_URI_LENGTH, STRING_LEN_SIZE,
+    },
     setup::setup_client,
     utils::check_spl_token,
 };
 
+// TODO: change the value '1' for the corresponding constant once the
+// new version of the tars crate is published
+const SWAP_REMOVE_FEATURE_INDEX: usize = 1;
+
 // To test a custom tars program, comment the tars::ID line
 // above and use the following lines to declare the id to use:
 //
@@ -27,11 +40,7 @@ pub fn parse_config_price(client: &Client, config: &ConfigData) -> Result<u64> {
     let parsed_price = if let Some(spl_token) = config.spl_token {
         let token_program = client.program(token_program_id());
         let token_mint = check_spl_token(&token_program, &spl_token.to_string())?;
-
-        match (config.price as u64).checked_mul(10u64.pow(token_mint.decimals.into())) {
-            Some(price) => price,
-            None => return Err(anyhow!("Price math overflow")),
-        }
+        price_in_base_units(config.price, token_mint.decimals)?
     } else {
         price_as_lamports(config.price)
     };
@@ -39,6 +48,28 @@ pub fn parse_config_price(client: &Client, config: &ConfigData) -> Result<u64> {
     Ok(parsed_price)
 }
 
+/// Converts a price to the base units of a token with `decimals` decimals, failing if
+/// the price has more decimal places than the token supports.
+pub fn price_in_base_units(price: f64, decimals: u8) -> Result<u64> {
+    let units = price * 10f64.powi(decimals.into());
+
+    if !units.is_finite() || units < 0.0 || units > u64::MAX as f64 {
+        return Err(anyhow!("Price math overflow"));
+    }
+
+    let rounded = units.round();
+    // tolerates the representation error of the float value
+    if (units - rounded).abs() > 1e-6 * rounded.max(1.0) {
+        return Err(anyhow!(
+            "Price {} has more decimal places than the token supports ({})",
+            price,
+            decimals
+        ));
+    }
+
+    Ok(rounded as u64)
+}
+
 pub fn get_tars_state(
     case_config: &CaseConfig,
     tars_id: &Pubkey,
@@ -65,40 +96,204 @@ pub fn get_tars_data(
 }
 
 pub fn print_tars_state(state: Tars) {
-    println!("Authority {:?}", state.authority);
-    println!("Wallet {:?}", state.wallet);
-    println!("Token mint: {:?}", state.token_mint);
-    println!("Items redeemed: {:?}", state.items_redeemed);
+    case_println!("Authority {:?}", state.authority);
+    case_println!("Wallet {:?}", state.wallet);
+    case_println!("Token mint: {:?}", state.token_mint);
+    case_println!("Items redeemed: {:?}", state.items_redeemed);
     print_tars_data(&state.data);
 }
 
 pub fn print_tars_data(data: &TarsData) {
-    println!("Uuid: {:?}", data.uuid);
-    println!("Price: {:?}", data.price);
-    println!("Symbol: {:?}", data.symbol);
-    println!(
+    case_println!("Uuid: {:?}", data.uuid);
+    case_println!("Price: {:?}", data.price);
+    case_println!("Symbol: {:?}", data.symbol);
+    case_println!(
         "Seller fee basis points: {:?}",
         data.seller_fee_basis_points
     );
-    println!("Max supply: {:?}", data.max_supply);
-    println!("Is mutable: {:?}", data.is_mutable);
-    println!("Retain Authority: {:?}", data.retain_authority);
-    println!("Go live date: {:?}", data.go_live_date);
-    println!("Items available: {:?}", data.items_available);
+    case_println!("Max supply: {:?}", data.max_supply);
+    case_println!("Is mutable: {:?}", data.is_mutable);
+    case_println!("Retain Authority: {:?}", data.retain_authority);
+    case_println!("Go live date: {:?}", data.go_live_date);
+    case_println!("Items available: {:?}", data.items_available);
+
+    for creator in &data.creators {
+        case_println!(
+            "Creator: {} ({}%, verified: {})",
+            creator.address,
+            creator.share,
+            creator.verified
+        );
+    }
+
+    match &data.end_settings {
+        Some(settings) => match settings.end_setting_type {
+            EndSettingType::Date => case_println!("End settings: date {}", settings.number),
+            EndSettingType::Amount => case_println!("End settings: amount {}", settings.number),
+        },
+        None => case_println!("No end settings"),
+    }
+
+    match &data.hidden_settings {
+        Some(settings) => {
+            case_println!("Hidden settings name: {:?}", settings.name);
+            case_println!("Hidden settings uri: {:?}", settings.uri);
+            case_println!(
+                "Hidden settings hash: {:?}",
+                String::from_utf8_lossy(&settings.hash)
+            );
+        }
+        None => case_println!("No hidden settings"),
+    }
+
+    match &data.gatekeeper {
+        Some(gatekeeper) => {
+            case_println!("Gatekeeper network: {:?}", gatekeeper.gatekeeper_network);
+            case_println!("Expire on use: {:?}", gatekeeper.expire_on_use);
+        }
+        None => case_println!("No gatekeeper"),
+    }
 
     print_whitelist_mint_settings(&data.whitelist_mint_settings);
 }
 
+/// Returns the complete (deserialized) state of a tars as JSON.
+pub fn tars_state_json(state: &Tars) -> Value {
+    let data = &state.data;
+
+    json!({
+        "authority": state.authority.to_string(),
+        "wallet": state.wallet.to_string(),
+        "tokenMint": state.token_mint.map(|mint| mint.to_string()),
+        "itemsRedeemed": state.items_redeemed,
+        "data": {
+            "uuid": data.uuid,
+            "price": data.price,
+            "symbol": data.symbol,
+            "sellerFeeBasisPoints": data.seller_fee_basis_points,
+            "maxSupply": data.max_supply,
+            "isMutable": data.is_mutable,
+            "retainAuthority": data.retain_authority,
+            "goLiveDate": data.go_live_date,
+            "endSettings": data.end_settings.as_ref().map(|settings| json!({
+                "endSettingType": match settings.end_setting_type {
+                    EndSettingType::Date => "date",
+                    EndSettingType::Amount => "amount",
+                },
+                "number": settings.number,
+            })),
+            "creators": data.creators.iter().map(|creator| json!({
+                "address": creator.address.to_string(),
+                "verified": creator.verified,
+                "share": creator.share,
+            })).collect::<Vec<Value>>(),
+            "hiddenSettings": data.hidden_settings.as_ref().map(|settings| json!({
+                "name": settings.name,
+                "uri": settings.uri,
+                "hash": String::from_utf8_lossy(&settings.hash),
+            })),
+            "whitelistMintSettings": data.whitelist_mint_settings.as_ref().map(|settings| json!({
+                "mode": match settings.mode {
+                    WhitelistMintMode::BurnEveryTime => "burnEveryTime",
+                    WhitelistMintMode::NeverBurn => "neverBurn",
+                },
+                "mint": settings.mint.to_string(),
+                "presale": settings.presale,
+                "discountPrice": settings.discount_price,
+            })),
+            "itemsAvailable": data.items_available,
+            "gatekeeper": data.gatekeeper.as_ref().map(|gatekeeper| json!({
+                "gatekeeperNetwork": gatekeeper.gatekeeper_network.to_string(),
+                "expireOnUse": gatekeeper.expire_on_use,
+            })),
+        },
+    })
+}
+
 fn print_whitelist_mint_settings(settings: &Option<WhitelistMintSettings>) {
     if let Some(settings) = settings {
         match settings.mode {
-            WhitelistMintMode::BurnEveryTime => println!("Mode: Burn every time"),
-            WhitelistMintMode::NeverBurn => println!("Mode: Never burn"),
+            WhitelistMintMode::BurnEveryTime => case_println!("Mode: Burn every time"),
+            WhitelistMintMode::NeverBurn => case_println!("Mode: Never burn"),
+        }
+        case_println!("Mint: {:?}", settings.mint);
+        case_println!("Presale: {:?}", settings.presale);
+        case_println!("Discount price: {:?}", settings.discount_price);
+    } else {
+        case_println!("No whitelist mint settings");
+    }
+}
+
+/// Returns the name and uri of the config line at `index`, or none if the line is
+/// empty (tars with hidden settings).
+pub fn config_line(data: &[u8], index: usize) -> Option<(String, String)> {
+    let line_start = CONFIG_ARRAY_START + STRING_LEN_SIZE + CONFIG_LINE_SIZE * index;
+    let name_start = line_start + CONFIG_NAME_OFFSET;
+    let uri_start = line_start + CONFIG_URI_OFFSET;
+
+    let read = |start: usize, length: usize| {
+        data.get(start..start + length).map(|bytes| {
+            String::from_utf8_lossy(bytes)
+                .trim_matches(char::from(0))
+                .to_string()
+        })
+    };
+
+    let name = read(name_start, MAX_NAME_LENGTH)?;
+    let uri = read(uri_start, MAX_URI_LENGTH)?;
+
+    if name.is_empty() && uri.is_empty() {
+        None
+    } else {
+        Some((name, uri))
+    }
+}
+
+/// Returns the (sorted) indices not minted yet, read from the mint bitmask of the tars
+/// or, for tars with the swap-remove feature, from the list of remaining indices.
+pub fn unminted_indices(data: &[u8], tars_data: &TarsData, items_redeemed: u64) -> Vec<u32> {
+    let items_available = tars_data.items_available;
+    let mut start = CONFIG_ARRAY_START
+        + STRING_LEN_SIZE
+        + CONFIG_LINE_SIZE * items_available as usize
+        + STRING_LEN_SIZE
+        + items_available
+            .checked_div(8)
+            .expect("Numerical overflow error") as usize
+        + STRING_LEN_SIZE;
+
+    let mut indices = vec![];
+
+    if is_feature_active(&tars_data.uuid, SWAP_REMOVE_FEATURE_INDEX) {
+        start += 1; // needed to get around rounding precision
+        let remaining = items_available - items_redeemed;
+        for i in 0..remaining {
+            let slice = start + (i * 4) as usize;
+            indices.push(u32::from_le_bytes(
+                data[slice..slice + 4].try_into().unwrap(),
+            ));
         }
-        println!("Mint: {:?}", settings.mint);
-        println!("Presale: {:?}", settings.presale);
-        println!("Discount price: {:?}", settings.discount_price);
     } else {
-        println!("No whitelist mint settings");
+        let mut index = 0;
+
+        while start < data.len() {
+            let mask = 1u8 << 7;
+
+            for i in 0..8 {
+                if index < items_available {
+                    // unused mint indices have the 'flag' set to 0
+                    if (data[start] & (mask >> i)) == 0 {
+                        indices.push(index as u32);
+                    }
+                    index += 1;
+                }
+            }
+
+            start += 1;
+        }
     }
+
+    // makes sure all items are in order
+    indices.sort_unstable();
+    indices
 }
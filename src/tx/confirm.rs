@@ -0,0 +1,208 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use anchor_client::solana_sdk::instruction::Instruction;
+use solana_client::{
+    pubsub_client::{PubsubClient, SignatureSubscription},
+    rpc_client::RpcClient,
+    rpc_config::RpcSignatureSubscribeConfig,
+    rpc_response::RpcSignatureResult,
+};
+
+use crate::{
+    common::*,
+    config::CaseConfig,
+    setup::{rpc_endpoint, websocket_url, with_rpc_failover},
+    tx::TxError,
+};
+
+/// Interval between checks of the block height while waiting for a confirmation.
+const CONFIRMATION_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Outcome of waiting for the confirmation of a transaction.
+enum Confirmation {
+    Confirmed,
+    /// The blockhash expired before the transaction was confirmed.
+    Expired,
+}
+
+/// Sends a transaction paid by the keypair of the config and waits for its confirmation.
+///
+/// The confirmation is received through a signature subscription over websockets (the
+/// status is polled instead when the RPC does not accept the subscription). When the
+/// blockhash expires before the transaction is confirmed, the transaction is rebuilt
+/// with a new blockhash and sent again, up to `TX_RESENDS` times. Requests that fail
+/// with a rate limit or a timeout go through the next healthy RPC endpoint.
+pub fn send_and_confirm(
+    case_config: &CaseConfig,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<Signature> {
+    let payer = case_config.keypair.pubkey();
+    let mut keypairs = vec![&case_config.keypair];
+    keypairs.extend(
+        signers
+            .iter()
+            .copied()
+            .filter(|signer| signer.pubkey() != payer),
+    );
+
+    for attempt in 0..=TX_RESENDS {
+        let (blockhash, last_valid_block_height) = with_rpc_failover(case_config, |rpc_client| {
+            Ok(rpc_client.get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())?)
+        })?;
+        let transaction =
+            Transaction::new_signed_with_payer(instructions, Some(&payer), &keypairs, blockhash);
+        let signature = transaction.signatures[0];
+
+        // subscribes before sending so that the notification cannot be missed
+        let mut subscription = subscribe(
+            &websocket_url(case_config, &rpc_endpoint(case_config)),
+            &signature,
+            case_config.commitment,
+        );
+        // the same signed transaction is sent through the next endpoint, so it cannot be
+        // processed twice
+        with_rpc_failover(case_config, |rpc_client| {
+            rpc_client.send_transaction(&transaction)?;
+            debug!(
+                "Transaction {} sent through {}",
+                signature,
+                rpc_client.url()
+            );
+            Ok(())
+        })?;
+
+        let confirmation = with_rpc_failover(case_config, |rpc_client| {
+            wait_for_confirmation(
+                rpc_client,
+                &signature,
+                subscription.take(),
+                last_valid_block_height,
+            )
+        })?;
+
+        match confirmation {
+            Confirmation::Confirmed => return Ok(signature),
+            Confirmation::Expired if attempt < TX_RESENDS => {
+                warn!(
+                    "Blockhash of transaction {} expired, sending it again (attempt {})",
+                    signature,
+                    attempt + 1
+                );
+            }
+            Confirmation::Expired => (),
+        }
+    }
+
+    Err(TxError::ConfirmationExpired(TX_RESENDS + 1).into())
+}
+
+/// Subscribes to the notification of a signature, returning `None` if the websocket
+/// endpoint of the RPC cannot be reached.
+fn subscribe(
+    ws_url: &str,
+    signature: &Signature,
+    commitment: CommitmentConfig,
+) -> Option<SignatureSubscription> {
+    let config = RpcSignatureSubscribeConfig {
+        commitment: Some(commitment),
+        enable_received_notification: Some(false),
+    };
+
+    match PubsubClient::signature_subscribe(ws_url, signature, Some(config)) {
+        Ok(subscription) => Some(subscription),
+        Err(err) => {
+            warn!(
+                "Failed to subscribe to signature notifications on {}, polling instead: {}",
+                ws_url, err
+            );
+            None
+        }
+    }
+}
+
+/// Waits for a transaction to be confirmed, for its blockhash to expire or for
+/// `TX_CONFIRMATION_TIMEOUT_SECS`, whichever comes first.
+fn wait_for_confirmation(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+    mut subscription: Option<SignatureSubscription>,
+    last_valid_block_height: u64,
+) -> Result<Confirmation> {
+    let start = Instant::now();
+    let timeout = Duration::from_secs(TX_CONFIRMATION_TIMEOUT_SECS);
+
+    let result = loop {
+        let notification = match &subscription {
+            Some((_, receiver)) => match receiver.recv_timeout(CONFIRMATION_CHECK_INTERVAL) {
+                Ok(response) => match response.value {
+                    RpcSignatureResult::ProcessedSignature(result) => Some(result.err),
+                    RpcSignatureResult::ReceivedSignature(_) => None,
+                },
+                Err(err) if err.is_disconnected() => {
+                    warn!("Signature subscription closed, polling instead");
+                    subscription = None;
+                    None
+                }
+                Err(_) => None,
+            },
+            None => {
+                thread::sleep(CONFIRMATION_CHECK_INTERVAL);
+                None
+            }
+        };
+
+        if let Some(err) = notification {
+            break match err {
+                Some(err) => Err(TxError::TransactionFailed(
+                    signature.to_string(),
+                    err.to_string(),
+                )),
+                None => Ok(Confirmation::Confirmed),
+            };
+        }
+
+        let expired = rpc_client.get_block_height()? > last_valid_block_height;
+
+        // the status is checked without a subscription, and before giving up on the
+        // transaction in case the notification was missed
+        if subscription.is_none() || expired {
+            if let Some(status) = rpc_client
+                .get_signature_status_with_commitment(signature, rpc_client.commitment())?
+            {
+                break match status {
+                    Ok(()) => Ok(Confirmation::Confirmed),
+                    Err(err) => Err(TxError::TransactionFailed(
+                        signature.to_string(),
+                        err.to_string(),
+                    )),
+                };
+            }
+        }
+
+        if expired {
+            break Ok(Confirmation::Expired);
+        }
+
+        if start.elapsed() > timeout {
+            break Err(TxError::ConfirmationTimeout(
+                signature.to_string(),
+                TX_CONFIRMATION_TIMEOUT_SECS,
+            ));
+        }
+    };
+
+    if let Some((subscription, _)) = subscription {
+        if subscription.shutdown().is_err() {
+            debug!(
+                "Failed to close the signature subscription of {}",
+                signature
+            );
+        }
+    }
+
+    result.map_err(anyhow::Error::from)
+}
@@ -0,0 +1,40 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TxError {
+    #[error("Transaction file '{0}' not found.")]
+    FileNotFound(String),
+
+    #[error("Failed to parse transaction file '{0}': {1}")]
+    InvalidFile(String, String),
+
+    #[error("{0} is not a signer of the transaction in '{1}'.")]
+    NotASigner(String, String),
+
+    #[error("The fee payer of the transaction in '{0}' is {1}, expected the signer {2}.")]
+    UnexpectedFeePayer(String, String, String),
+
+    #[error("The transaction in '{0}' does not match its file: {1}.")]
+    UnexpectedTransaction(String, String),
+
+    #[error("Transaction in '{0}' is missing the signature(s) of: {1}")]
+    MissingSignatures(String, String),
+
+    #[error("Transaction in '{0}' has an invalid signature.")]
+    InvalidSignature(String),
+
+    #[error("The blockhash of the transaction in '{0}' expired, run the command with --sign-offline again to create a new transaction (use --nonce for transactions that do not expire).")]
+    BlockhashExpired(String),
+
+    #[error("The nonce account {1} of the transaction in '{0}' was advanced, run the command with --sign-offline again to create a new transaction.")]
+    NonceAdvanced(String, String),
+
+    #[error("Transaction {0} failed: {1}")]
+    TransactionFailed(String, String),
+
+    #[error("Transaction {0} was not confirmed: timed out after {1} seconds.")]
+    ConfirmationTimeout(String, u64),
+
+    #[error("Transaction was not confirmed after {0} attempt(s): block height exceeded.")]
+    ConfirmationExpired(u32),
+}
@@ -0,0 +1,13 @@
+pub mod confirm;
+pub mod errors;
+pub mod offline;
+pub mod plan;
+pub mod send;
+pub mod sign;
+
+pub use confirm::*;
+pub use errors::*;
+pub use offline::*;
+pub use plan::*;
+pub use send::*;
+pub use sign::*;
@@ -0,0 +1,301 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    rc::Rc,
+};
+
+use anchor_client::{
+    solana_sdk::{
+        hash::Hash, instruction::Instruction, signer::null_signer::NullSigner,
+        system_instruction::SystemInstruction,
+    },
+    Cluster as ClientCluster,
+};
+use console::style;
+use data_encoding::BASE64;
+use serde::Serialize;
+use solana_client::{nonce_utils, rpc_client::RpcClient};
+
+use crate::{common::*, config::CaseConfig, setup::websocket_url, tx::TxError};
+
+/// Transaction written by the `--sign-offline` mode of a command, to be signed with
+/// `case tx sign` and sent with `case tx send`.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfflineTransaction {
+    pub description: String,
+    /// Fee payer of the transaction, the authority of the command.
+    pub fee_payer: String,
+    /// Blockhash (or durable nonce) of the transaction.
+    pub blockhash: String,
+    /// Nonce account advanced by the transaction, if it uses a durable nonce.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    /// Base64-encoded (bincode) transaction.
+    pub transaction: String,
+    /// Signature of the transaction, set once it was sent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl OfflineTransaction {
+    pub fn new(description: &str, transaction: &Transaction) -> Result<OfflineTransaction> {
+        let fee_payer = transaction
+            .message
+            .account_keys
+            .first()
+            .ok_or_else(|| anyhow!("The transaction has no fee payer"))?;
+
+        Ok(OfflineTransaction {
+            description: description.to_string(),
+            fee_payer: fee_payer.to_string(),
+            blockhash: transaction.message.recent_blockhash.to_string(),
+            nonce: nonce_account(transaction).map(|nonce| nonce.to_string()),
+            transaction: BASE64.encode(&bincode::serialize(transaction)?),
+            signature: None,
+        })
+    }
+
+    pub fn load(path: &str) -> Result<OfflineTransaction> {
+        if !Path::new(path).is_file() {
+            return Err(TxError::FileNotFound(path.to_string()).into());
+        }
+
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|err| TxError::InvalidFile(path.to_string(), err.to_string()).into())
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Saves the transaction to a new file, failing if the file already exists.
+    pub fn save_new(&self, path: &str) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .map_err(|err| anyhow!("Failed to create transaction file {}: {}", path, err))?;
+        file.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn transaction(&self) -> Result<Transaction> {
+        let bytes = BASE64
+            .decode(self.transaction.as_bytes())
+            .map_err(|err| anyhow!("invalid base64 transaction: {}", err))?;
+        Ok(bincode::deserialize(&bytes)?)
+    }
+
+    pub fn set_transaction(&mut self, transaction: &Transaction) -> Result<()> {
+        self.transaction = BASE64.encode(&bincode::serialize(transaction)?);
+        Ok(())
+    }
+}
+
+/// Returns the signers of the transaction that have not signed it yet.
+pub fn missing_signers(transaction: &Transaction) -> Vec<Pubkey> {
+    let required = transaction.message.header.num_required_signatures as usize;
+
+    transaction
+        .message
+        .account_keys
+        .iter()
+        .take(required)
+        .zip(transaction.signatures.iter())
+        .filter(|(_, signature)| **signature == Signature::default())
+        .map(|(pubkey, _)| *pubkey)
+        .collect()
+}
+
+/// Returns the nonce account of a transaction that uses a durable nonce, which is advanced
+/// by its first instruction.
+pub fn nonce_account(transaction: &Transaction) -> Option<Pubkey> {
+    let message = &transaction.message;
+    let instruction = message.instructions.first()?;
+    let program = message
+        .account_keys
+        .get(instruction.program_id_index as usize)?;
+
+    match bincode::deserialize(&instruction.data) {
+        Ok(SystemInstruction::AdvanceNonceAccount) if *program == system_program::ID => message
+            .account_keys
+            .get(*instruction.accounts.first()? as usize)
+            .copied(),
+        _ => None,
+    }
+}
+
+/// Parses the nonce accounts of the `--nonce` option.
+pub fn resolve_nonces(nonces: &[String], address_book: &str) -> Result<Vec<Pubkey>> {
+    nonces
+        .iter()
+        .map(|nonce| resolve_pubkey(nonce, address_book))
+        .collect()
+}
+
+/// Returns the nonce stored in a nonce account and the authority that advances it.
+pub fn get_nonce(rpc_client: &RpcClient, nonce: &Pubkey) -> Result<(Hash, Pubkey)> {
+    let account =
+        nonce_utils::get_account_with_commitment(rpc_client, nonce, rpc_client.commitment())
+            .map_err(|err| anyhow!("Failed to get nonce account {}: {}", nonce, err))?;
+    let data = nonce_utils::data_from_account(&account)
+        .map_err(|err| anyhow!("Invalid nonce account {}: {}", nonce, err))?;
+
+    Ok((data.blockhash, data.authority))
+}
+
+/// Returns a client whose payer is the offline authority, so that the instructions of a
+/// command are built for the authority without its keypair. Requests of this client
+/// cannot be sent.
+pub fn setup_offline_client(case_config: &CaseConfig, authority: &Pubkey) -> Client {
+    let rpc_url = case_config.rpc_url.clone();
+    let ws_url = websocket_url(case_config, &rpc_url);
+    let cluster = ClientCluster::Custom(rpc_url, ws_url);
+
+    Client::new_with_options(
+        cluster,
+        Rc::new(NullSigner::new(authority)),
+        case_config.commitment,
+    )
+}
+
+/// Writes the transactions of a command to numbered files in the transactions
+/// directory, in the order in which they must be sent.
+///
+/// With nonce accounts, each transaction uses the durable nonce of the next account
+/// instead of a recent blockhash, so that it does not expire while it is signed.
+pub struct OfflineWriter {
+    dir: PathBuf,
+    authority: Pubkey,
+    blockhash: Hash,
+    /// Nonce accounts (with their nonce) that are not used by a transaction yet.
+    nonces: Vec<(Pubkey, Hash)>,
+    durable: bool,
+    next: usize,
+    files: Vec<String>,
+}
+
+impl OfflineWriter {
+    pub fn new(
+        rpc_client: &RpcClient,
+        authority: Pubkey,
+        nonces: &[Pubkey],
+    ) -> Result<OfflineWriter> {
+        let mut nonce_accounts = Vec::new();
+        for nonce in nonces {
+            if nonce_accounts.iter().any(|(account, _)| account == nonce) {
+                return Err(anyhow!("Nonce account {} is used more than once", nonce));
+            }
+
+            let (blockhash, nonce_authority) = get_nonce(rpc_client, nonce)?;
+            if nonce_authority != authority {
+                return Err(anyhow!(
+                    "The authority of nonce account {} is {}, expected {}",
+                    nonce,
+                    nonce_authority,
+                    authority
+                ));
+            }
+            nonce_accounts.push((*nonce, blockhash));
+        }
+        // the accounts are used in order
+        nonce_accounts.reverse();
+
+        let dir = PathBuf::from(OFFLINE_TX_DIR);
+        fs::create_dir_all(&dir)?;
+
+        // files of previous commands are kept so that they are sent first, and new files
+        // are numbered after the last one even when earlier files were removed
+        let next = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+            .filter_map(|path| path.file_stem()?.to_str()?.parse::<usize>().ok())
+            .max()
+            .map_or(0, |last| last + 1);
+
+        Ok(OfflineWriter {
+            dir,
+            authority,
+            blockhash: rpc_client.get_latest_blockhash()?,
+            durable: !nonce_accounts.is_empty(),
+            nonces: nonce_accounts,
+            next,
+            files: Vec::new(),
+        })
+    }
+
+    /// Writes a transaction with the authority as fee payer, partially signed by the
+    /// local signers (e.g. the keypair of a new account).
+    pub fn write(
+        &mut self,
+        description: &str,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<String> {
+        let (instructions, blockhash) = if self.durable {
+            // a nonce can only be used by one transaction
+            let (nonce, blockhash) = self.nonces.pop().ok_or_else(|| {
+                anyhow!(
+                    "The command needs more transactions than nonce accounts, use a --nonce \
+                     for each transaction (more than {})",
+                    self.files.len()
+                )
+            })?;
+            let mut nonce_instructions = vec![system_instruction::advance_nonce_account(
+                &nonce,
+                &self.authority,
+            )];
+            nonce_instructions.extend_from_slice(instructions);
+
+            (nonce_instructions, blockhash)
+        } else {
+            (instructions.to_vec(), self.blockhash)
+        };
+
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(&self.authority));
+
+        if signers.is_empty() {
+            transaction.message.recent_blockhash = blockhash;
+        } else {
+            transaction.try_partial_sign(&signers.to_vec(), blockhash)?;
+        }
+
+        let path = path_to_string(&self.dir.join(format!("{:03}.json", self.next)))?;
+        OfflineTransaction::new(description, &transaction)?.save_new(&path)?;
+        info!("Wrote offline transaction '{}' to {}", description, path);
+
+        self.next += 1;
+        self.files.push(path.clone());
+
+        Ok(path)
+    }
+
+    /// Prints the files written and returns their paths.
+    pub fn finish(self) -> Vec<String> {
+        case_println!(
+            "\n{} transaction(s) written to '{}' for authority {}:",
+            self.files.len(),
+            self.dir.display(),
+            self.authority
+        );
+        for file in &self.files {
+            case_println!("  {}", file);
+        }
+
+        let hint = if self.durable {
+            "Sign them with 'case tx sign' and send them with 'case tx send', they use durable \
+             nonces and do not expire until the nonce accounts are advanced."
+        } else {
+            "Sign them with 'case tx sign' and send them with 'case tx send' before the \
+             blockhash expires (about a minute), or use --nonce for transactions that do \
+             not expire."
+        };
+        case_println!("{}", style(hint).bold());
+
+        self.files
+    }
+}
@@ -0,0 +1,110 @@
+use std::fs;
+
+use anchor_client::solana_sdk::instruction::Instruction;
+use data_encoding::BASE64;
+use serde::Serialize;
+
+use crate::{canonical_json::to_canonical_json, common::*};
+
+/// Transactions that a dry run would send, written as canonical JSON so that the plans
+/// of two runs (or two operators) can be diffed before approving a live run. Blockhashes
+/// and signatures are left out, since they change on every run.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxPlan {
+    pub command: String,
+    pub payer: String,
+    pub transactions: Vec<PlannedTransaction>,
+    /// Changes that the plan does not describe as transactions.
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedTransaction {
+    pub description: String,
+    /// Arguments of the transaction, in a readable form.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub args: Option<Value>,
+    pub instructions: Vec<PlannedInstruction>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedInstruction {
+    pub program_id: String,
+    pub accounts: Vec<PlannedAccount>,
+    /// Base64-encoded instruction data.
+    pub data: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedAccount {
+    pub pubkey: String,
+    pub signer: bool,
+    pub writable: bool,
+}
+
+impl TxPlan {
+    pub fn new(command: &str, payer: &Pubkey) -> TxPlan {
+        TxPlan {
+            command: command.to_string(),
+            payer: payer.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a transaction to the plan. Accounts created by the run (e.g. a new tars)
+    /// have a new address every time, so they are replaced by a placeholder name.
+    pub fn add(
+        &mut self,
+        description: &str,
+        args: Option<Value>,
+        instructions: &[Instruction],
+        placeholders: &[(Pubkey, &str)],
+    ) {
+        let name = |pubkey: &Pubkey| {
+            placeholders
+                .iter()
+                .find(|(placeholder, _)| placeholder == pubkey)
+                .map_or_else(|| pubkey.to_string(), |(_, name)| format!("<{}>", name))
+        };
+
+        self.transactions.push(PlannedTransaction {
+            description: description.to_string(),
+            args,
+            instructions: instructions
+                .iter()
+                .map(|instruction| PlannedInstruction {
+                    program_id: name(&instruction.program_id),
+                    accounts: instruction
+                        .accounts
+                        .iter()
+                        .map(|account| PlannedAccount {
+                            pubkey: name(&account.pubkey),
+                            signer: account.is_signer,
+                            writable: account.is_writable,
+                        })
+                        .collect(),
+                    data: BASE64.encode(&instruction.data),
+                })
+                .collect(),
+        });
+    }
+
+    /// Writes the plan to the plan file at `path`.
+    pub fn save(&self, path: &str) -> Result<()> {
+        fs::write(path, to_canonical_json(self)?)
+            .map_err(|err| anyhow!("Failed to write plan file '{}': {}", path, err))?;
+        info!("Wrote plan of '{}' to {}", self.command, path);
+
+        case_println!(
+            "\n{} transaction(s) planned, written to '{}'",
+            self.transactions.len(),
+            path
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    common::*,
+    tars::TARS_ID,
+    tx::{
+        get_nonce, missing_signers, nonce_account, transaction_files, OfflineTransaction, TxError,
+    },
+    utils::spinner_with_style,
+};
+
+pub struct TxSendArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub files: Vec<String>,
+}
+
+/// Result of the tx send command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxSendOutcome {
+    pub signatures: Vec<String>,
+    /// Files that were sent by a previous run.
+    pub skipped: Vec<String>,
+}
+
+/// Sends signed transaction files in order, stopping at the first failure. The signature
+/// is saved in the file, so files sent by a previous run are skipped.
+pub fn process_tx_send(args: TxSendArgs) -> Result<TxSendOutcome> {
+    let files = transaction_files(&args.files)?;
+
+    // every transaction is checked before the first one is sent
+    let mut transactions = Vec::new();
+
+    for file in files {
+        let offline = OfflineTransaction::load(&file)?;
+        let transaction = offline.transaction()?;
+
+        if offline.signature.is_none() {
+            let missing = missing_signers(&transaction);
+
+            if !missing.is_empty() {
+                let missing: Vec<String> =
+                    missing.iter().map(|pubkey| pubkey.to_string()).collect();
+                return Err(TxError::MissingSignatures(file, missing.join(", ")).into());
+            }
+
+            if transaction.verify().is_err() {
+                return Err(TxError::InvalidSignature(file).into());
+            }
+        }
+
+        transactions.push((file, offline, transaction));
+    }
+
+    case_println!(
+        "{} {}Sending {} transaction(s)",
+        style("[1/1]").bold().dim(),
+        PAPER_EMOJI,
+        transactions.len()
+    );
+
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = setup_client(&case_config)?;
+    let rpc_client = client.program(TARS_ID).rpc();
+    let mut outcome = TxSendOutcome::default();
+
+    for (file, mut offline, transaction) in transactions {
+        if offline.signature.is_some() {
+            case_println!("{} {} (already sent)", style("-").dim(), file);
+            outcome.skipped.push(file);
+            continue;
+        }
+
+        // an expired transaction is reported before it is sent, since it would never land
+        let blockhash = transaction.message.recent_blockhash;
+        match nonce_account(&transaction) {
+            Some(nonce) => {
+                if get_nonce(&rpc_client, &nonce)?.0 != blockhash {
+                    return Err(TxError::NonceAdvanced(file, nonce.to_string()).into());
+                }
+            }
+            None => {
+                if !rpc_client.is_blockhash_valid(&blockhash, rpc_client.commitment())? {
+                    return Err(TxError::BlockhashExpired(file).into());
+                }
+            }
+        }
+
+        let pb = spinner_with_style();
+        pb.set_message(format!("Sending {}...", offline.description));
+
+        let signature = rpc_client
+            .send_and_confirm_transaction(&transaction)
+            .map_err(|err| {
+                pb.finish_and_clear();
+                let message = err.to_string().to_lowercase();
+
+                if message.contains("blockhash not found") {
+                    TxError::BlockhashExpired(file.clone()).into()
+                } else {
+                    anyhow!("Failed to send '{}': {}", file, err)
+                }
+            })?;
+
+        offline.signature = Some(signature.to_string());
+        offline.save(&file)?;
+
+        pb.finish_with_message(format!(
+            "{} {} {}",
+            style(&offline.description).bold(),
+            style("signature:").dim(),
+            signature
+        ));
+        outcome.signatures.push(signature.to_string());
+    }
+
+    Ok(outcome)
+}
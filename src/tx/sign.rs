@@ -0,0 +1,222 @@
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    common::*,
+    inspect_tx::{decode_compiled_instruction, print_instruction},
+    setup::local_keypair,
+    tx::{missing_signers, nonce_account, OfflineTransaction, TxError},
+    utils::confirm_action,
+};
+
+pub struct TxSignArgs {
+    pub keypair: Option<String>,
+    pub global: GlobalOptions,
+    pub files: Vec<String>,
+    pub skip_confirmation: bool,
+}
+
+/// Result of the tx sign command.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxSignOutcome {
+    pub signer: String,
+    pub signed: Vec<String>,
+}
+
+/// Adds the signature of the keypair to transaction files. No connection is made to the
+/// cluster, so the command can run on an offline machine. The instructions of each
+/// transaction are printed before it is signed.
+pub fn process_tx_sign(args: TxSignArgs) -> Result<TxSignOutcome> {
+    let keypair = local_keypair(args.keypair, &args.global)?;
+    let signer = keypair.pubkey();
+    let files = transaction_files(&args.files)?;
+
+    case_println!(
+        "{} {}Signing {} transaction(s) with {}",
+        style("[1/1]").bold().dim(),
+        SIGNING_EMOJI,
+        files.len(),
+        signer
+    );
+
+    let mut outcome = TxSignOutcome {
+        signer: signer.to_string(),
+        signed: Vec::new(),
+    };
+
+    for file in files {
+        let mut offline = OfflineTransaction::load(&file)?;
+        let mut transaction = offline.transaction()?;
+
+        if !missing_signers(&transaction).contains(&signer) {
+            if transaction.message.account_keys.contains(&signer) {
+                case_println!("{} {} (already signed)", style("-").dim(), file);
+                continue;
+            }
+            return Err(TxError::NotASigner(signer.to_string(), file).into());
+        }
+
+        check_transaction(&file, &offline, &transaction, &signer)?;
+        print_transaction(&file, &offline, &transaction)?;
+
+        if !confirm_action(
+            &format!("Sign '{}'?", offline.description),
+            args.skip_confirmation,
+        )? {
+            return Err(anyhow!("Signing cancelled"));
+        }
+
+        let blockhash = transaction.message.recent_blockhash;
+        transaction.try_partial_sign(&[&keypair], blockhash)?;
+        offline.set_transaction(&transaction)?;
+        offline.save(&file)?;
+
+        let missing = missing_signers(&transaction);
+        if missing.is_empty() {
+            case_println!("{} {} {}", style("✔").green(), file, offline.description);
+        } else {
+            case_println!(
+                "{} {} {} {}",
+                style("✔").green(),
+                file,
+                offline.description,
+                style(format!("(waiting for {} signature(s))", missing.len())).dim()
+            );
+        }
+
+        outcome.signed.push(file);
+    }
+
+    Ok(outcome)
+}
+
+/// Checks that the transaction is the one described by its file: paid by the signer,
+/// with the blockhash it was created with, or the durable nonce of the nonce account
+/// advanced by the signer.
+fn check_transaction(
+    file: &str,
+    offline: &OfflineTransaction,
+    transaction: &Transaction,
+    signer: &Pubkey,
+) -> Result<()> {
+    let message = &transaction.message;
+    let mismatch = |reason: String| TxError::UnexpectedTransaction(file.to_string(), reason);
+
+    let fee_payer = message
+        .account_keys
+        .first()
+        .map(|fee_payer| fee_payer.to_string())
+        .unwrap_or_default();
+    if fee_payer != signer.to_string() {
+        return Err(
+            TxError::UnexpectedFeePayer(file.to_string(), fee_payer, signer.to_string()).into(),
+        );
+    }
+    if fee_payer != offline.fee_payer {
+        return Err(mismatch(format!(
+            "fee payer {} instead of {}",
+            fee_payer, offline.fee_payer
+        ))
+        .into());
+    }
+
+    let blockhash = message.recent_blockhash.to_string();
+    if blockhash != offline.blockhash {
+        return Err(mismatch(format!(
+            "blockhash {} instead of {}",
+            blockhash, offline.blockhash
+        ))
+        .into());
+    }
+
+    let nonce = nonce_account(transaction);
+    if nonce.map(|nonce| nonce.to_string()) != offline.nonce {
+        return Err(mismatch(format!(
+            "nonce account {} instead of {}",
+            nonce.map_or("none".to_string(), |nonce| nonce.to_string()),
+            offline.nonce.as_deref().unwrap_or("none")
+        ))
+        .into());
+    }
+
+    // the accounts of AdvanceNonceAccount are the nonce, the recent blockhashes sysvar and
+    // the nonce authority
+    if nonce.is_some() {
+        let authority = message.instructions[0]
+            .accounts
+            .get(2)
+            .and_then(|index| message.account_keys.get(*index as usize));
+        if authority != Some(signer) {
+            return Err(mismatch(format!("the nonce authority is not {}", signer)).into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the fee payer, the blockhash (or nonce) and the decoded instructions of a
+/// transaction.
+fn print_transaction(
+    file: &str,
+    offline: &OfflineTransaction,
+    transaction: &Transaction,
+) -> Result<()> {
+    let message = &transaction.message;
+
+    case_println!(
+        "\n{} {} ({})",
+        style("Transaction:").bold(),
+        offline.description,
+        file
+    );
+    case_println!("{} {}", style("Fee payer:").dim(), offline.fee_payer);
+    match &offline.nonce {
+        Some(nonce) => case_println!(
+            "{} {} (nonce account {})",
+            style("Durable nonce:").dim(),
+            offline.blockhash,
+            nonce
+        ),
+        None => case_println!("{} {}", style("Blockhash:").dim(), offline.blockhash),
+    }
+
+    for (index, instruction) in message.instructions.iter().enumerate() {
+        let decoded = decode_compiled_instruction(
+            &message.account_keys,
+            instruction.program_id_index,
+            &instruction.accounts,
+            &instruction.data,
+        )?;
+        print_instruction(&format!("#{}", index + 1), "", &decoded);
+    }
+
+    Ok(())
+}
+
+/// Expands the arguments of the tx commands: a directory stands for the JSON files it
+/// contains, sorted by name.
+pub fn transaction_files(paths: &[String]) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+
+    for path in paths {
+        if Path::new(path).is_dir() {
+            let mut entries: Vec<String> = std::fs::read_dir(path)?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+                .map(|path| path_to_string(&path))
+                .collect::<Result<Vec<String>>>()?;
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    if files.is_empty() {
+        return Err(anyhow!("No transaction files found"));
+    }
+
+    Ok(files)
+}
@@ -0,0 +1,17 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum UpdateError {
+    #[error(
+        "The number of items cannot be changed from {0} to {1}: the tars account was sized for {0} config lines when it was created, and the program cannot resize it. Set \"number\" back to {0} in the config file, or use --new-tars to move the cache to a new tars."
+    )]
+    ItemsAvailableChanged(u64, u64),
+    #[error("The number of items ({0}) cannot be lower than the number of items minted ({1})")]
+    ItemsBelowRedeemed(u64, u64),
+    #[error(
+        "--new-tars is only needed when the number of items of a tars with config lines changes"
+    )]
+    NewTarsNotNeeded,
+    #[error("Cache file '{0}' belongs to tars {1}, not {2}")]
+    CacheTarsMismatch(String, String, String),
+}
@@ -1,65 +1,102 @@
-use std::str::FromStr;
+use std::{fs, str::FromStr};
 
 use anchor_client::solana_sdk::pubkey::Pubkey;
 use anchor_lang::prelude::AccountMeta;
 use anyhow::Result;
 use console::style;
+use serde::Serialize;
 use tars::{
     accounts as nft_accounts, instruction as nft_instruction, TarsData,
 };
 use spl_associated_token_account::get_associated_token_address;
 
 use crate::{
-    cache::load_cache,
+    cache::{load_cache, load_cluster_cache, CacheProgram},
     tars::{get_tars_state, parse_config_price, TARS_ID},
     common::*,
     config::{
         data::{ConfigData, *},
         parser::get_config_data,
     },
+    diff::{diff_tars_data, print_diff, print_field_changes, FieldChange, TarsDiff},
+    setup::check_treasury_wallet,
+    tx::{resolve_nonces, send_and_confirm, setup_offline_client, OfflineWriter, TxPlan},
+    update::UpdateError,
     utils::{
-        assert_correct_authority, check_spl_token, check_spl_token_account, spinner_with_style,
+        assert_correct_authority, check_spl_token, check_spl_token_account, confirm_action,
+        spinner_with_style,
     },
+    verify_config_hash::fill_hidden_settings_hash_from_cache,
 };
 
 pub struct UpdateArgs {
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
     pub cache: String,
     pub new_authority: Option<String>,
     pub config: String,
     pub tars: Option<String>,
+    pub dry_run: bool,
+    pub skip_confirmation: bool,
+    /// Authority that signs the transactions offline.
+    pub sign_offline: Option<String>,
+    /// Durable nonce accounts of the transactions signed offline.
+    pub nonces: Vec<String>,
+    /// Move the cache to a new tars when the number of items changes.
+    pub new_tars: bool,
 }
 
-pub fn process_update(args: UpdateArgs) -> Result<()> {
-    let case_config = case_setup(args.keypair, args.rpc_url)?;
-    let client = setup_client(&case_config)?;
-    let config_data = get_config_data(&args.config)?;
+/// Result of the update command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateOutcome {
+    pub tars: String,
+    pub signatures: Vec<String>,
+    pub new_authority: Option<String>,
+    /// Transaction files written for offline signing.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub transactions: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<TarsDiff>,
+    /// Backup of the cache moved to a new tars (--new-tars).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_backup: Option<String>,
+}
+
+pub fn process_update(args: UpdateArgs) -> Result<UpdateOutcome> {
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let offline_authority = args
+        .sign_offline
+        .as_deref()
+        .map(|authority| resolve_pubkey(authority, &args.global.address_book))
+        .transpose()?;
+    let client = match &offline_authority {
+        Some(authority) => setup_offline_client(&case_config, authority),
+        None => setup_client(&case_config)?,
+    };
+    let mut config_data = get_config_data(&args.config, &args.global.address_book)?;
+
+    // an empty hidden settings hash is the hash of the cache, as used by deploy
+    fill_hidden_settings_hash_from_cache(&mut config_data, &args.cache)?;
 
     // the tars id specified takes precedence over the one from the cache
     let tars_id = match args.tars {
         Some(tars_id) => tars_id,
         None => {
-            let cache = load_cache(&args.cache, false)?;
+            let cache = load_cluster_cache(&case_config, &args.cache, false)?;
             cache.program.tars
         }
     };
 
-    let tars_pubkey = match Pubkey::from_str(&tars_id) {
-        Ok(tars_pubkey) => tars_pubkey,
-        Err(_) => {
-            let error = anyhow!("Failed to parse tars id: {}", tars_id);
-            error!("{:?}", error);
-            return Err(error);
-        }
-    };
+    let tars_pubkey = resolve_pubkey(&tars_id, &args.global.address_book)?;
 
-    println!(
+    case_println!(
         "{} {}Loading tars",
         style("[1/2]").bold().dim(),
         LOOKING_GLASS_EMOJI
     );
-    println!("{} {}", style("Tars ID:").bold(), tars_id);
+    case_println!("{} {}", style("Tars ID:").bold(), tars_id);
 
     let pb = spinner_with_style();
     pb.set_message("Connecting...");
@@ -70,12 +107,73 @@ pub fn process_update(args: UpdateArgs) -> Result<()> {
 
     pb.finish_with_message("Done");
 
-    assert_correct_authority(
-        &case_config.keypair.pubkey(),
-        &tars_state.authority,
-    )?;
+    let program = client.program(TARS_ID);
+    assert_correct_authority(&program.payer(), &tars_state.authority)?;
+
+    let current_items = tars_state.data.items_available;
+    let new_items = tars_data.items_available;
+
+    if new_items < tars_state.items_redeemed {
+        return Err(UpdateError::ItemsBelowRedeemed(new_items, tars_state.items_redeemed).into());
+    }
+
+    // the account of a tars with config lines is sized for the number of items when it
+    // is created, only tarss with hidden settings (no config lines) can change it
+    let resize = new_items != current_items && tars_state.data.hidden_settings.is_none();
+
+    if args.new_tars {
+        if !resize {
+            return Err(UpdateError::NewTarsNotNeeded.into());
+        }
+
+        case_println!(
+            "\n{} {}Moving the cache to a new tars",
+            style("[2/2]").bold().dim(),
+            TARS_EMOJI
+        );
+
+        let mut outcome = UpdateOutcome {
+            tars: tars_pubkey.to_string(),
+            ..Default::default()
+        };
+
+        if args.dry_run {
+            case_println!(
+                "The cache file '{}' would be moved to a new tars of {} items.",
+                args.cache,
+                new_items
+            );
+        } else {
+            let (backup, collection_mint) =
+                migrate_cache(&args.cache, &case_config.keypair, &tars_pubkey)?;
+            case_println!(
+                "Cache file '{}' backed up to '{}' and moved to a new tars.\n\n\
+                 Run 'case deploy' to create the new tars with {} items, then 'case withdraw \
+                 {}' to close the current one once it is no longer needed.",
+                args.cache,
+                backup,
+                new_items,
+                tars_pubkey
+            );
+            if let Some(collection_mint) = collection_mint {
+                case_println!(
+                    "Set the collection on the new tars with 'case collection set {}'.",
+                    collection_mint
+                );
+            }
+            outcome.cache_backup = Some(backup);
+        }
+
+        return Ok(outcome);
+    }
+
+    if resize {
+        let error = UpdateError::ItemsAvailableChanged(current_items, new_items);
+        error!("{:?}", error);
+        return Err(error.into());
+    }
 
-    println!(
+    case_println!(
         "\n{} {}Updating configuration",
         style("[2/2]").bold().dim(),
         COMPUTER_EMOJI
@@ -93,8 +191,6 @@ pub fn process_update(args: UpdateArgs) -> Result<()> {
         }
     }
 
-    let program = client.program(TARS_ID);
-
     let treasury_account = match config_data.spl_token {
         Some(spl_token) => {
             let spl_token_account_figured = if config_data.spl_token_account.is_some() {
@@ -122,43 +218,148 @@ pub fn process_update(args: UpdateArgs) -> Result<()> {
         }
         None => match config_data.sol_treasury_account {
             Some(sol_treasury_account) => sol_treasury_account,
-            None => case_config.keypair.pubkey(),
+            None => program.payer(),
         },
     };
 
-    let mut builder = program
-        .request()
-        .accounts(nft_accounts::UpdateTars {
-            tars: tars_pubkey,
-            authority: program.payer(),
-            wallet: treasury_account,
-        })
-        .args(nft_instruction::UpdateTars {
-            data: tars_data,
-        });
+    check_treasury_wallet(&program.payer(), &treasury_account);
+
+    let changes = diff_tars_data(
+        Some(&tars_state),
+        &tars_data,
+        &treasury_account,
+        &config_data.spl_token,
+    );
+
+    if args.dry_run {
+        let mut plan = TxPlan::new("update", &program.payer());
+
+        if !changes.is_empty() {
+            let mut builder = program
+                .request()
+                .accounts(nft_accounts::UpdateTars {
+                    tars: tars_pubkey,
+                    authority: program.payer(),
+                    wallet: treasury_account,
+                })
+                .args(nft_instruction::UpdateTars { data: tars_data });
+
+            for account in remaining_accounts {
+                builder = builder.accounts(account);
+            }
+
+            plan.add(
+                "update tars",
+                Some(json!(changes)),
+                &builder.instructions()?,
+                &[],
+            );
+        }
+
+        let mut diff = TarsDiff {
+            tars: Some(tars_pubkey.to_string()),
+            fields: changes,
+            plan_file: Some(args.global.plan_file.clone()),
+            ..Default::default()
+        };
+
+        if let Some(new_authority) = &args.new_authority {
+            let new_authority_pubkey = resolve_pubkey(new_authority, &args.global.address_book)?;
+            let instructions = program
+                .request()
+                .accounts(nft_accounts::UpdateTars {
+                    tars: tars_pubkey,
+                    authority: program.payer(),
+                    wallet: treasury_account,
+                })
+                .args(nft_instruction::UpdateAuthority {
+                    new_authority: Some(new_authority_pubkey),
+                })
+                .instructions()?;
+            let change = FieldChange {
+                field: "authority".to_string(),
+                current: json!(tars_state.authority.to_string()),
+                new: json!(new_authority_pubkey.to_string()),
+            };
 
-    if !remaining_accounts.is_empty() {
-        for account in remaining_accounts {
-            builder = builder.accounts(account);
+            plan.add(
+                "update tars authority",
+                Some(json!([change])),
+                &instructions,
+                &[],
+            );
+            diff.fields.push(change);
         }
+
+        print_diff(&diff);
+        plan.save(&args.global.plan_file)?;
+
+        return Ok(UpdateOutcome {
+            tars: tars_pubkey.to_string(),
+            dry_run: Some(diff),
+            ..Default::default()
+        });
     }
 
-    let pb = spinner_with_style();
-    pb.set_message("Sending update transaction...");
+    let mut outcome = UpdateOutcome {
+        tars: tars_pubkey.to_string(),
+        ..Default::default()
+    };
+    let mut offline_writer = match offline_authority {
+        Some(authority) => Some(OfflineWriter::new(
+            &program.rpc(),
+            authority,
+            &resolve_nonces(&args.nonces, &args.global.address_book)?,
+        )?),
+        None => None,
+    };
 
-    let update_signature = builder.send()?;
+    if changes.is_empty() {
+        case_println!("\nTars data is up to date with the config file, nothing to update.");
+    } else {
+        case_println!("\n{}", style("Changed fields:").dim());
+        print_field_changes(&changes);
 
-    pb.finish_with_message(format!(
-        "{} {}",
-        style("Update signature:").bold(),
-        update_signature
-    ));
+        let prompt = format!("Update {} field(s)?", changes.len());
 
-    if let Some(new_authority) = args.new_authority {
-        let pb = spinner_with_style();
-        pb.set_message("Sending update authority transaction...");
+        if !confirm_action(&prompt, args.skip_confirmation)? {
+            return Err(anyhow!("Update cancelled"));
+        }
+
+        let mut builder = program
+            .request()
+            .accounts(nft_accounts::UpdateTars {
+                tars: tars_pubkey,
+                authority: program.payer(),
+                wallet: treasury_account,
+            })
+            .args(nft_instruction::UpdateTars { data: tars_data });
+
+        if !remaining_accounts.is_empty() {
+            for account in remaining_accounts {
+                builder = builder.accounts(account);
+            }
+        }
 
-        let new_authority_pubkey = Pubkey::from_str(&new_authority)?;
+        if let Some(writer) = &mut offline_writer {
+            writer.write("update tars", &builder.instructions()?, &[])?;
+        } else {
+            let pb = spinner_with_style();
+            pb.set_message("Sending update transaction...");
+
+            let update_signature = send_and_confirm(&case_config, &builder.instructions()?, &[])?;
+            outcome.signatures.push(update_signature.to_string());
+
+            pb.finish_with_message(format!(
+                "{} {}",
+                style("Update signature:").bold(),
+                update_signature
+            ));
+        }
+    }
+
+    if let Some(new_authority) = args.new_authority {
+        let new_authority_pubkey = resolve_pubkey(&new_authority, &args.global.address_book)?;
         let builder = program
             .request()
             .accounts(nft_accounts::UpdateTars {
@@ -170,18 +371,76 @@ pub fn process_update(args: UpdateArgs) -> Result<()> {
                 new_authority: Some(new_authority_pubkey),
             });
 
-        let authority_signature = builder.send()?;
-        pb.finish_with_message(format!(
-            "{} {}",
-            style("Authority signature:").bold(),
-            authority_signature
-        ));
+        if let Some(writer) = &mut offline_writer {
+            writer.write("update tars authority", &builder.instructions()?, &[])?;
+        } else {
+            let pb = spinner_with_style();
+            pb.set_message("Sending update authority transaction...");
+
+            let authority_signature =
+                send_and_confirm(&case_config, &builder.instructions()?, &[])?;
+            outcome.signatures.push(authority_signature.to_string());
+            pb.finish_with_message(format!(
+                "{} {}",
+                style("Authority signature:").bold(),
+                authority_signature
+            ));
+        }
+        outcome.new_authority = Some(new_authority_pubkey.to_string());
+    }
+
+    if let Some(writer) = offline_writer {
+        outcome.transactions = writer.finish();
     }
 
-    Ok(())
+    Ok(outcome)
 }
 
-fn create_tars_data(
+/// Moves the cache to a new tars: the cache file is backed up and its items are marked
+/// as not on-chain, so that 'deploy' creates a new tars and writes every config line.
+/// The collection NFT is kept and its mint returned, to be set on the new tars with
+/// 'collection set'.
+fn migrate_cache(
+    cache_path: &str,
+    keypair: &Keypair,
+    tars: &Pubkey,
+) -> Result<(String, Option<String>)> {
+    let mut cache = load_cache(cache_path, false)?;
+    cache.set_signer(keypair)?;
+
+    if cache.program.tars != tars.to_string() {
+        return Err(UpdateError::CacheTarsMismatch(
+            cache_path.to_string(),
+            cache.program.tars.clone(),
+            tars.to_string(),
+        )
+        .into());
+    }
+
+    let backup = format!("{}.{}.bak", cache_path, tars);
+    fs::copy(cache_path, &backup)
+        .map_err(|err| anyhow!("Failed to back up cache file '{}': {}", cache_path, err))?;
+
+    let collection_mint = Some(cache.program.collection_mint.clone())
+        .filter(|collection_mint| !collection_mint.is_empty());
+    cache.program = CacheProgram::new();
+
+    for (index, item) in cache.items.iter_mut() {
+        if index != "-1" {
+            item.on_chain = false;
+        }
+    }
+
+    cache.sync_file()?;
+    info!(
+        "Moved cache {} from tars {} (backup: {})",
+        cache_path, tars, backup
+    );
+
+    Ok((backup, collection_mint))
+}
+
+pub(crate) fn create_tars_data(
     client: &Client,
     config: &ConfigData,
     tars: &TarsData,
@@ -196,7 +455,11 @@ fn create_tars_data(
         .as_ref()
         .map(|s| s.to_tars_format());
 
-    let hidden_settings = config.hidden_settings.as_ref().map(|s| s.to_tars_format());
+    let hidden_settings = config
+        .hidden_settings
+        .as_ref()
+        .map(|s| s.to_tars_format())
+        .transpose()?;
 
     let gatekeeper = config.gatekeeper.as_ref().map(|g| g.to_tars_format());
 
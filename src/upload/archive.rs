@@ -0,0 +1,371 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, Read, Write},
+    sync::{Arc, Mutex},
+};
+
+use data_encoding::HEXLOWER;
+use flate2::read::GzDecoder;
+use lazy_static::lazy_static;
+use ring::digest::{Context, SHA256};
+use zip::{write::FileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    common::*,
+    upload::{
+        assets::{ensure_asset_files, AssetPair, ANIMATION_EXTENSIONS, IMAGE_EXTENSIONS},
+        remote::{find_media, is_remote},
+    },
+    validate::format::Metadata,
+};
+
+/// Separator between the path of an archive and the name of an entry in the content of
+/// an asset (e.g. `assets.zip!/0.png`).
+pub const ARCHIVE_ENTRY_SEPARATOR: &str = "!/";
+
+/// Uncompressed zip in the staging directory with the media files of a tar.gz archive.
+const MEDIA_PACK: &str = "media.zip";
+
+lazy_static! {
+    /// Zip archives read by the upload, so that the central directory of an archive with
+    /// many entries is only read once.
+    static ref ARCHIVES: Mutex<HashMap<String, Arc<Mutex<ZipArchive<BufReader<File>>>>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Format of an assets archive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Returns the format of an archive from its extension, or `None` for other paths
+    /// (e.g. a directory).
+    pub fn from_path(path: &str) -> Option<ArchiveFormat> {
+        let path = path.to_lowercase();
+
+        if path.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns `true` if the assets path is a zip or tar.gz archive.
+pub fn is_archive(path: &str) -> bool {
+    ArchiveFormat::from_path(path).is_some()
+}
+
+/// Returns `true` if the content of an asset is an entry of a zip archive.
+pub fn is_archive_entry(content: &str) -> bool {
+    !is_remote(content) && content.contains(ARCHIVE_ENTRY_SEPARATOR)
+}
+
+/// Asset file of an archive.
+pub struct ArchiveFile {
+    /// File name of the asset (e.g. `0.png`).
+    pub name: String,
+    /// Name of the entry in the archive, which can be in a folder.
+    pub entry: String,
+    /// Path of the staged copy of a metadata file, or the zip entry of a media file.
+    pub content: String,
+    /// SHA-256 of the file, the same as the hash of the extracted file.
+    pub hash: String,
+}
+
+/// Reads the asset files of an archive in a single pass, without extracting it. Metadata
+/// files are written to the staging directory (they are small and need to be updated
+/// before upload), while media files are only hashed and read from the archive on
+/// upload. Since a tar.gz archive can only be read sequentially, its media files are
+/// copied to an uncompressed zip in the staging directory.
+pub fn stage_archive(archive: &str, staging_dir: &Path) -> Result<Vec<ArchiveFile>> {
+    let format = ArchiveFormat::from_path(archive).ok_or_else(|| {
+        anyhow!(
+            "Unsupported assets archive '{}', expected a .zip or .tar.gz file",
+            archive
+        )
+    })?;
+    let file = File::open(archive)
+        .map_err(|err| anyhow!("Failed to open assets archive '{}': {}", archive, err))?;
+
+    // files of a previous run are removed, so that they are not mistaken for assets
+    if staging_dir.exists() {
+        fs::remove_dir_all(staging_dir)?;
+    }
+    fs::create_dir_all(staging_dir)?;
+
+    let mut files = Vec::new();
+
+    match format {
+        ArchiveFormat::Zip => {
+            let mut zip = ZipArchive::new(BufReader::new(file))
+                .map_err(|err| anyhow!("Failed to read assets archive '{}': {}", archive, err))?;
+
+            for i in 0..zip.len() {
+                let mut entry = zip.by_index(i)?;
+                let entry_name = entry.name().to_string();
+
+                let name = match asset_name(&entry_name) {
+                    Some(name) if entry.is_file() => name,
+                    _ => continue,
+                };
+
+                let (content, hash) = if is_metadata(&name) {
+                    stage_metadata(staging_dir, &name, &mut entry)?
+                } else {
+                    (
+                        entry_content(archive, &entry_name),
+                        hash_data(&mut entry, &mut io::sink())?,
+                    )
+                };
+
+                files.push(ArchiveFile {
+                    name,
+                    entry: entry_name,
+                    content,
+                    hash,
+                });
+            }
+        }
+        ArchiveFormat::TarGz => {
+            let pack_path = path_to_string(&staging_dir.join(MEDIA_PACK))?;
+            let mut pack = ZipWriter::new(File::create(&pack_path)?);
+            let options = FileOptions::default()
+                .compression_method(CompressionMethod::Stored)
+                .large_file(true);
+            let mut tar = tar::Archive::new(GzDecoder::new(BufReader::new(file)));
+
+            for entry in tar.entries()? {
+                let mut entry = entry?;
+                let entry_name = entry.path()?.to_string_lossy().to_string();
+
+                let name = match asset_name(&entry_name) {
+                    Some(name) if entry.header().entry_type().is_file() => name,
+                    _ => continue,
+                };
+
+                let (content, hash) = if is_metadata(&name) {
+                    stage_metadata(staging_dir, &name, &mut entry)?
+                } else {
+                    pack.start_file(entry_name.as_str(), options)?;
+                    (
+                        entry_content(&pack_path, &entry_name),
+                        hash_data(&mut entry, &mut pack)?,
+                    )
+                };
+
+                files.push(ArchiveFile {
+                    name,
+                    entry: entry_name,
+                    content,
+                    hash,
+                });
+            }
+
+            pack.finish()?;
+        }
+    }
+
+    info!(
+        "Staged {} asset file(s) of archive '{}'",
+        files.len(),
+        archive
+    );
+
+    Ok(files)
+}
+
+/// Returns the asset pairs of an archive (see `stage_archive`).
+pub fn get_archive_asset_pairs(
+    archive: &str,
+    staging_dir: &Path,
+) -> Result<HashMap<isize, AssetPair>> {
+    archive_asset_pairs(&stage_archive(archive, staging_dir)?)
+}
+
+/// Returns the asset pairs of the staged files of an archive.
+pub fn archive_asset_pairs(files: &[ArchiveFile]) -> Result<HashMap<isize, AssetPair>> {
+    ensure_asset_files(
+        &files
+            .iter()
+            .map(|file| file.name.clone())
+            .collect::<Vec<String>>(),
+    )?;
+
+    // file name -> content
+    let contents: HashMap<String, String> = files
+        .iter()
+        .map(|file| (file.name.clone(), file.content.clone()))
+        .collect();
+    // content -> hash
+    let hashes: HashMap<String, String> = files
+        .iter()
+        .map(|file| (file.content.clone(), file.hash.clone()))
+        .collect();
+
+    let mut asset_pairs = HashMap::new();
+
+    for file in files.iter().filter(|file| is_metadata(&file.name)) {
+        let stem = file.name.split('.').next().unwrap();
+
+        let index: isize = if stem == "collection" {
+            -1
+        } else {
+            stem.parse::<isize>().map_err(|_| {
+                anyhow!(
+                    "Couldn't parse filename '{}' to a valid index number.",
+                    file.name
+                )
+            })?
+        };
+
+        let image = find_media(&contents, stem, IMAGE_EXTENSIONS).ok_or_else(|| {
+            if index == -1 {
+                anyhow!("Couldn't find the collection image file.")
+            } else {
+                anyhow!("Couldn't find an image file at index {}.", index)
+            }
+        })?;
+        let animation = find_media(&contents, stem, ANIMATION_EXTENSIONS);
+
+        let metadata: Metadata =
+            serde_json::from_reader(File::open(&file.content)?).map_err(|e| {
+                anyhow!(
+                    "Failed to read metadata file '{}' with error: {e}",
+                    file.entry
+                )
+            })?;
+
+        asset_pairs.insert(
+            index,
+            AssetPair {
+                name: metadata.name,
+                metadata: file.content.clone(),
+                metadata_hash: file.hash.clone(),
+                image_hash: hashes[&image].clone(),
+                image,
+                animation_hash: animation
+                    .as_ref()
+                    .map(|animation| hashes[animation].clone()),
+                animation,
+            },
+        );
+    }
+
+    Ok(asset_pairs)
+}
+
+/// Returns the size of a media file of an archive.
+pub fn archive_entry_size(content: &str) -> Result<u64> {
+    let (path, name) = split_entry(content)?;
+    let archive = open_archive(path)?;
+    let mut archive = archive.lock().unwrap();
+    let size = archive
+        .by_name(name)
+        .map_err(|err| anyhow!("Failed to read '{}' from archive '{}': {}", name, path, err))?
+        .size();
+
+    Ok(size)
+}
+
+/// Returns the data of a media file of an archive.
+pub fn read_archive_entry(content: &str) -> Result<Vec<u8>> {
+    let (path, name) = split_entry(content)?;
+    let archive = open_archive(path)?;
+    let mut archive = archive.lock().unwrap();
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|err| anyhow!("Failed to read '{}' from archive '{}': {}", name, path, err))?;
+
+    let mut data = Vec::with_capacity(entry.size() as usize);
+    entry.read_to_end(&mut data)?;
+
+    Ok(data)
+}
+
+fn open_archive(path: &str) -> Result<Arc<Mutex<ZipArchive<BufReader<File>>>>> {
+    let mut archives = ARCHIVES.lock().unwrap();
+
+    if let Some(archive) = archives.get(path) {
+        return Ok(archive.clone());
+    }
+
+    let file = File::open(path)
+        .map_err(|err| anyhow!("Failed to open assets archive '{}': {}", path, err))?;
+    let archive = Arc::new(Mutex::new(ZipArchive::new(BufReader::new(file))?));
+    archives.insert(path.to_string(), archive.clone());
+
+    Ok(archive)
+}
+
+fn split_entry(content: &str) -> Result<(&str, &str)> {
+    content
+        .split_once(ARCHIVE_ENTRY_SEPARATOR)
+        .ok_or_else(|| anyhow!("Invalid archive entry: {}", content))
+}
+
+fn entry_content(archive: &str, entry: &str) -> String {
+    format!("{}{}{}", archive, ARCHIVE_ENTRY_SEPARATOR, entry)
+}
+
+/// Returns the file name of an entry if it can be an asset file, skipping folders and
+/// hidden files (e.g. `__MACOSX/._0.png`), the same as `list_files` for a directory.
+fn asset_name(entry: &str) -> Option<String> {
+    let name = entry.rsplit(['/', '\\']).next().unwrap_or_default();
+    let stem = Path::new(name).file_stem()?.to_str()?;
+
+    if !name.starts_with('.')
+        && (stem == "collection" || (!stem.is_empty() && stem.chars().all(|c| c.is_ascii_digit())))
+    {
+        Some(name.to_string())
+    } else {
+        None
+    }
+}
+
+fn is_metadata(name: &str) -> bool {
+    name.to_lowercase().ends_with(".json")
+}
+
+/// Writes a metadata file to the staging directory, returning its path and hash.
+fn stage_metadata(
+    staging_dir: &Path,
+    name: &str,
+    reader: &mut dyn Read,
+) -> Result<(String, String)> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+
+    let path = staging_dir.join(name);
+    fs::write(&path, &data)?;
+
+    let mut context = Context::new(&SHA256);
+    context.update(&data);
+
+    Ok((
+        path_to_string(&path)?,
+        HEXLOWER.encode(context.finish().as_ref()),
+    ))
+}
+
+/// Copies the data of a reader to a writer, returning its hash.
+fn hash_data(reader: &mut dyn Read, writer: &mut dyn Write) -> Result<String> {
+    let mut context = Context::new(&SHA256);
+    let mut buffer = [0; 64 * 1024];
+
+    loop {
+        let count = reader.read(&mut buffer)?;
+        if count == 0 {
+            break;
+        }
+        context.update(&buffer[..count]);
+        writer.write_all(&buffer[..count])?;
+    }
+
+    Ok(HEXLOWER.encode(context.finish().as_ref()))
+}
@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     ffi::OsStr,
     fs::{self, DirEntry, File, OpenOptions},
     io::{BufReader, Read},
@@ -6,6 +7,7 @@ use std::{
 };
 
 use bundlr_sdk::{tags::Tag, Bundlr, Ed25519Signer as SolanaSigner};
+use console::style;
 use data_encoding::HEXLOWER;
 use glob::glob;
 use regex::{Regex, RegexBuilder};
@@ -13,7 +15,22 @@ use ring::digest::{Context, SHA256};
 use serde::Serialize;
 use serde_json;
 
-use crate::{common::*, validate::format::Metadata};
+use crate::{
+    canonical_json::to_canonical_json,
+    common::*,
+    config::Creator,
+    upload::{asset_file_name, is_remote, UploadError},
+    validate::format::{FileAttr, Metadata},
+};
+
+/// Extensions of image files.
+pub const IMAGE_EXTENSIONS: &[&str] = &["jpg", "gif", "png", "webp"];
+
+/// Extensions of animation files (video, audio, 3D models and HTML), which are
+/// uploaded as the `animation_url` of the metadata.
+pub const ANIMATION_EXTENSIONS: &[&str] = &[
+    "mp4", "mov", "webm", "mp3", "wav", "ogg", "flac", "glb", "gltf", "html",
+];
 
 pub struct UploadDataArgs<'a> {
     pub bundlr_client: Arc<Bundlr<SolanaSigner>>,
@@ -52,6 +69,7 @@ impl AssetPair {
             on_chain: false,
             animation_hash: self.animation_hash,
             animation_link: self.animation,
+            storage_check: None,
         }
     }
 }
@@ -124,6 +142,63 @@ pub fn list_files(assets_dir: &str, include_collection: bool) -> Result<Vec<DirE
     Ok(files.collect())
 }
 
+/// Overwrites the `symbol` and `properties.creators` of every metadata file in the
+/// assets directory with the values from the config file. Returns the number of
+/// files changed.
+pub fn inject_metadata(assets_dir: &str, symbol: &str, creators: &[Creator]) -> Result<usize> {
+    let creators: Vec<Value> = creators
+        .iter()
+        .map(|creator| {
+            json!({
+                "address": creator.address.to_string(),
+                "share": creator.share,
+            })
+        })
+        .collect();
+    let mut updated = 0;
+
+    for entry in list_files(assets_dir, true)? {
+        let path = entry.path();
+
+        if path.extension().and_then(OsStr::to_str) != Some("json") {
+            continue;
+        }
+
+        let file = File::open(&path)?;
+        let mut metadata: Value = serde_json::from_reader(BufReader::new(file)).map_err(|e| {
+            anyhow!("Failed to parse metadata file '{}': {}", path.display(), e)
+        })?;
+        let original = metadata.clone();
+
+        let object = metadata
+            .as_object_mut()
+            .ok_or_else(|| anyhow!("Invalid metadata file '{}'", path.display()))?;
+        object.insert("symbol".to_string(), json!(symbol));
+
+        let properties = object
+            .entry("properties")
+            .or_insert_with(|| json!({ "files": [] }));
+        match properties.as_object_mut() {
+            Some(properties) => {
+                properties.insert("creators".to_string(), Value::Array(creators.clone()));
+            }
+            None => {
+                return Err(anyhow!(
+                    "Invalid 'properties' value in metadata file '{}'",
+                    path.display()
+                ))
+            }
+        }
+
+        if metadata != original {
+            fs::write(&path, to_canonical_json(&metadata)?)?;
+            updated += 1;
+        }
+    }
+
+    Ok(updated)
+}
+
 pub fn get_asset_pairs(assets_dir: &str) -> Result<HashMap<isize, AssetPair>> {
     // filters out directories and hidden files
     let filtered_files = list_files(assets_dir, true)?;
@@ -137,12 +212,15 @@ pub fn get_asset_pairs(assets_dir: &str) -> Result<HashMap<isize, AssetPair>> {
         })
         .collect::<Vec<String>>();
 
+    ensure_asset_files(&paths)?;
+
     let mut asset_pairs: HashMap<isize, AssetPair> = HashMap::new();
 
     let paths_ref = &paths;
 
     let animation_exists_regex =
-        Regex::new("^(.+)\\.((mp4)|(mov)|(webm))$").expect("Failed to create regex.");
+        Regex::new(&format!("^(.+)\\.({})$", ANIMATION_EXTENSIONS.join("|")))
+            .expect("Failed to create regex.");
 
     // since there doesn't have to be video for each image/json pair, need to get rid of
     // invalid file names before entering metadata filename loop
@@ -162,8 +240,6 @@ pub fn get_asset_pairs(assets_dir: &str) -> Result<HashMap<isize, AssetPair>> {
         .filter(|p| p.to_lowercase().ends_with(".json"))
         .collect::<Vec<String>>();
 
-    ensure_sequential_files(metadata_filenames.clone())?;
-
     for metadata_filename in metadata_filenames {
         let i = metadata_filename.split('.').next().unwrap();
         let is_collection_index = i == "collection";
@@ -181,7 +257,7 @@ pub fn get_asset_pairs(assets_dir: &str) -> Result<HashMap<isize, AssetPair>> {
             return Err(error);
         };
 
-        let img_pattern = format!("^{}\\.((jpg)|(gif)|(png))$", i);
+        let img_pattern = format!("^{}\\.({})$", i, IMAGE_EXTENSIONS.join("|"));
 
         let img_regex = RegexBuilder::new(&img_pattern)
             .case_insensitive(true)
@@ -212,7 +288,7 @@ pub fn get_asset_pairs(assets_dir: &str) -> Result<HashMap<isize, AssetPair>> {
         // need a similar check for animation as above, this one checking if there is animation
         // on specific index
 
-        let animation_pattern = format!("^{}\\.((mp4)|(mov)|(webm))$", i);
+        let animation_pattern = format!("^{}\\.({})$", i, ANIMATION_EXTENSIONS.join("|"));
         let animation_regex = RegexBuilder::new(&animation_pattern)
             .case_insensitive(true)
             .build()
@@ -224,6 +300,16 @@ pub fn get_asset_pairs(assets_dir: &str) -> Result<HashMap<isize, AssetPair>> {
             .filter(|p| animation_regex.is_match(p))
             .collect::<Vec<String>>();
 
+        if animation_filenames.len() > 1 {
+            let error = anyhow!(
+                "Found more than one animation file for '{}': {}",
+                metadata_filename,
+                animation_filenames.join(", ")
+            );
+            error!("{:?}", error);
+            return Err(error);
+        }
+
         let metadata_filepath = Path::new(assets_dir)
             .join(&metadata_filename)
             .to_str()
@@ -294,43 +380,184 @@ pub fn encode(file: &str) -> Result<String> {
     Ok(HEXLOWER.encode(context.finish().as_ref()))
 }
 
-fn ensure_sequential_files(metadata_filenames: Vec<String>) -> Result<()> {
-    let mut metadata_indices = metadata_filenames
-        .into_iter()
-        .filter(|f| !f.starts_with("collection"))
-        .map(|f| {
-            f.split('.')
-                .next()
-                .unwrap()
-                .to_string()
-                .parse::<usize>()
-                .map_err(|_| {
-                    anyhow!(
-                        "Couldn't parse metadata filename '{}' to a valid index number.",
-                        f
-                    )
-                })
-        })
-        .collect::<Result<Vec<usize>>>()?;
-    metadata_indices.sort_unstable();
+/// Files of an asset index, grouped by type.
+#[derive(Default)]
+struct IndexFiles<'a> {
+    metadata: Vec<&'a str>,
+    image: Vec<&'a str>,
+    animation: Vec<&'a str>,
+}
 
-    metadata_indices
-        .into_iter()
-        .enumerate()
-        .try_for_each(|(i, file_index)| {
-            if i != file_index {
-                Err(anyhow!("Missing metadata file '{}.json'", i))
-            } else {
-                Ok(())
+/// Checks the file names of the assets: indices must be continuous from 0, every
+/// metadata file needs a single image (animations are optional) and every media file
+/// needs a metadata file. Returns all problems found.
+pub fn check_asset_files(file_names: &[String]) -> Vec<String> {
+    let mut problems = Vec::new();
+    // the collection uses index -1
+    let mut indices: BTreeMap<isize, IndexFiles> = BTreeMap::new();
+
+    for file_name in file_names {
+        let (stem, extension) = match file_name.rsplit_once('.') {
+            Some((stem, extension)) => (stem, extension.to_lowercase()),
+            None => {
+                problems.push(format!("'{}': missing file extension", file_name));
+                continue;
             }
-        })
+        };
+
+        let index = if stem == "collection" {
+            -1
+        } else if let Ok(index) = stem.parse::<usize>() {
+            index as isize
+        } else {
+            problems.push(format!(
+                "'{}': couldn't parse the file name to a valid index number",
+                file_name
+            ));
+            continue;
+        };
+
+        let files = indices.entry(index).or_default();
+
+        if extension == "json" {
+            files.metadata.push(file_name);
+        } else if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+            files.image.push(file_name);
+        } else if ANIMATION_EXTENSIONS.contains(&extension.as_str()) {
+            files.animation.push(file_name);
+        } else {
+            problems.push(format!("'{}': unsupported file type", file_name));
+        }
+    }
+
+    for (index, files) in &indices {
+        let label = if *index == -1 {
+            "collection".to_string()
+        } else {
+            index.to_string()
+        };
+
+        for (kind, names) in [
+            ("metadata", &files.metadata),
+            ("image", &files.image),
+            ("animation", &files.animation),
+        ] {
+            if names.len() > 1 {
+                problems.push(format!(
+                    "Duplicate {} files for index {}: {}",
+                    kind,
+                    label,
+                    names.join(", ")
+                ));
+            }
+        }
+
+        if files.metadata.is_empty() {
+            let media: Vec<&str> = files
+                .image
+                .iter()
+                .chain(&files.animation)
+                .copied()
+                .collect();
+            problems.push(format!(
+                "Missing metadata file '{}.json' for {}",
+                label,
+                media.join(", ")
+            ));
+        } else if files.image.is_empty() {
+            problems.push(format!(
+                "Missing image file for '{}'",
+                files.metadata.join(", ")
+            ));
+        }
+    }
+
+    // gaps in the indices, reported as ranges (the last index is always present)
+    if let Some(last) = indices.keys().next_back().filter(|last| **last >= 0) {
+        let mut gap_start = None;
+
+        for index in 0..=*last {
+            match (indices.contains_key(&index), gap_start) {
+                (false, None) => gap_start = Some(index),
+                (true, Some(start)) => {
+                    problems.push(if start == index - 1 {
+                        format!("Missing files for index {} (e.g. '{}.json')", start, start)
+                    } else {
+                        format!(
+                            "Missing files for indices {} to {} (e.g. '{}.json')",
+                            start,
+                            index - 1,
+                            start
+                        )
+                    });
+                    gap_start = None;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    problems
+}
+
+/// Fails with every problem found in the asset file names (see `check_asset_files`).
+pub fn ensure_asset_files(file_names: &[String]) -> Result<()> {
+    let problems = check_asset_files(file_names);
+
+    if problems.is_empty() {
+        return Ok(());
+    }
+
+    let mut message = format!("{} problem(s) found in the asset files:", problems.len());
+
+    for problem in problems {
+        message.push_str(&style("\n=> ").dim().to_string());
+        message.push_str(&problem);
+    }
+
+    let error = UploadError::InvalidAssetFiles(message);
+    error!("{:?}", error);
+    Err(error.into())
 }
 
+/// Returns the content type of a media file based on its extension.
+pub fn media_content_type(file_name: &str) -> Result<String> {
+    let extension = Path::new(file_name)
+        .extension()
+        .and_then(OsStr::to_str)
+        .ok_or(UploadError::NoExtension)?
+        .to_lowercase();
+
+    let content_type = match extension.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "png" => "image/png",
+        "webp" => "image/webp",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        "webm" => "video/webm",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "flac" => "audio/flac",
+        "glb" => "model/gltf-binary",
+        "gltf" => "model/gltf+json",
+        "html" => "text/html",
+        _ => return Err(anyhow!("Unsupported file type: {}", file_name)),
+    };
+
+    Ok(content_type.to_string())
+}
+
+/// Returns the metadata of an asset with the links of the uploaded media. The `image`,
+/// `animation_url` and matching `properties.files` entries (by their original value or
+/// file name) are replaced, and entries for the media are added if missing.
 pub fn get_updated_metadata(
-    metadata_file: &str,
+    asset_pair: &AssetPair,
     image_link: &str,
     animation_link: &Option<String>,
 ) -> Result<String> {
+    let metadata_file = &asset_pair.metadata;
     let mut metadata: Metadata = {
         let m = OpenOptions::new()
             .read(true)
@@ -341,21 +568,53 @@ pub fn get_updated_metadata(
         serde_json::from_reader(&m)?
     };
 
+    let image_name = asset_file_name(&asset_pair.image);
+    let animation = match (&asset_pair.animation, animation_link) {
+        (Some(animation), Some(animation_link)) => {
+            Some((asset_file_name(animation), animation_link))
+        }
+        _ => None,
+    };
+
+    let mut image_found = false;
+    let mut animation_found = false;
+
     for file in &mut metadata.properties.files {
-        if file.uri.eq(&metadata.image) {
+        let file_name = asset_file_name(&file.uri);
+
+        if file.uri.eq(&metadata.image) || file_name == image_name {
             file.uri = image_link.to_string();
-        }
-        if let Some(ref animation_link) = animation_link {
-            if let Some(ref animation_url) = metadata.animation_url {
-                if file.uri.eq(animation_url) {
-                    file.uri = animation_link.to_string();
-                }
+            image_found = true;
+        } else if let Some((animation_name, animation_link)) = &animation {
+            if metadata.animation_url.as_ref() == Some(&file.uri) || file_name == *animation_name {
+                file.uri = animation_link.to_string();
+                animation_found = true;
             }
         }
     }
 
+    if !image_found {
+        metadata.properties.files.push(FileAttr {
+            uri: image_link.to_string(),
+            file_type: media_content_type(&image_name)?,
+        });
+    }
+
     metadata.image = image_link.to_string();
-    metadata.animation_url = animation_link.clone();
 
-    Ok(serde_json::to_string(&metadata).unwrap())
+    if let Some((animation_name, animation_link)) = animation {
+        if !animation_found {
+            metadata.properties.files.push(FileAttr {
+                uri: animation_link.to_string(),
+                file_type: media_content_type(&animation_name)?,
+            });
+        }
+
+        metadata.animation_url = Some(animation_link.to_string());
+    } else if !metadata.animation_url.as_deref().map_or(false, is_remote) {
+        // local animation references without a file cannot be resolved
+        metadata.animation_url = None;
+    }
+
+    to_canonical_json(&metadata)
 }
@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+
+use console::style;
+use serde::Serialize;
+
+use crate::{canonical_json::to_canonical_json, common::*, shuffle::sha256_hex, upload::AssetPair};
+
+/// Assets of different indices with the same content.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateAssets {
+    /// Type of the files: "image" or "metadata".
+    pub kind: String,
+    pub indices: Vec<isize>,
+}
+
+/// Returns the items with byte-identical images and the items with identical metadata.
+/// Metadata files are compared by their canonical JSON without the media references
+/// (`image`, `animation_url` and `properties.files`), which name the files of each index,
+/// so that formatting and key order do not matter. The collection is not compared.
+pub fn find_duplicate_assets(
+    asset_pairs: &HashMap<isize, AssetPair>,
+) -> Result<Vec<DuplicateAssets>> {
+    let mut images: BTreeMap<&str, Vec<isize>> = BTreeMap::new();
+    let mut metadata: BTreeMap<String, Vec<isize>> = BTreeMap::new();
+
+    for (index, pair) in asset_pairs.iter().filter(|(index, _)| **index != -1) {
+        images.entry(&pair.image_hash).or_default().push(*index);
+        metadata
+            .entry(metadata_content_hash(&pair.metadata)?)
+            .or_default()
+            .push(*index);
+    }
+
+    let groups = images
+        .into_values()
+        .map(|indices| ("image", indices))
+        .chain(metadata.into_values().map(|indices| ("metadata", indices)));
+
+    let mut duplicates: Vec<DuplicateAssets> = groups
+        .filter(|(_, indices)| indices.len() > 1)
+        .map(|(kind, mut indices)| {
+            indices.sort_unstable();
+            DuplicateAssets {
+                kind: kind.to_string(),
+                indices,
+            }
+        })
+        .collect();
+    duplicates.sort_by(|a, b| a.kind.cmp(&b.kind).then(a.indices.cmp(&b.indices)));
+
+    Ok(duplicates)
+}
+
+/// Prints a warning with the indices of the duplicate assets.
+pub fn warn_duplicate_assets(duplicates: &[DuplicateAssets]) {
+    if duplicates.is_empty() {
+        return;
+    }
+
+    case_println!(
+        "\n{}{}",
+        WARNING_EMOJI,
+        style(format!(
+            "{} group(s) of items have the same content (use --allow-duplicates if intended):",
+            duplicates.len()
+        ))
+        .yellow()
+        .bold()
+    );
+
+    for duplicate in duplicates {
+        let indices: Vec<String> = duplicate
+            .indices
+            .iter()
+            .map(|index| index.to_string())
+            .collect();
+        warn!(
+            "Duplicate {} in items {}",
+            duplicate.kind,
+            indices.join(", ")
+        );
+        case_println!(
+            "{} {} of items {}",
+            style("=>").dim(),
+            duplicate.kind,
+            indices.join(", ")
+        );
+    }
+}
+
+/// Returns the SHA-256 of the canonical JSON of a metadata file without its media
+/// references.
+fn metadata_content_hash(path: &str) -> Result<String> {
+    let mut metadata: Value = serde_json::from_reader(File::open(path)?)
+        .map_err(|err| anyhow!("Failed to read metadata file '{}': {}", path, err))?;
+
+    if let Some(object) = metadata.as_object_mut() {
+        object.remove("image");
+        object.remove("animation_url");
+
+        if let Some(properties) = object.get_mut("properties").and_then(Value::as_object_mut) {
+            properties.remove("files");
+        }
+    }
+
+    Ok(sha256_hex(to_canonical_json(&metadata)?.as_bytes()))
+}
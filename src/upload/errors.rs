@@ -25,6 +25,18 @@ pub enum UploadError {
     )]
     MismatchValue(String, String, String, String),
 
+    #[error("{0}")]
+    InvalidAssetFiles(String),
+
     #[error("Metadata file {0} is not formatted correctly for animations.")]
     AnimationFileError(String),
+
+    #[error("Storage check failed: {0} item(s) differ from the local files (re-run the upload to send them again) and {1} could not be downloaded")]
+    StorageCheckFailed(usize, usize),
+
+    #[error("Bundlr balance of {0} lamports is below the upload cost of {1} lamports; fund it with 'case bundlr fund' or upload without --no-auto-fund")]
+    InsufficientBundlrBalance(u64, u64),
+
+    #[error("Media file {0} has {1} bytes, above the {2} bytes of the in-memory buffer; this upload method holds each file in memory while it is sent, so raise --max-buffer-mb or use a storage that streams the files")]
+    MediaTooLarge(String, u64, u64),
 }
@@ -0,0 +1,235 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use console::style;
+use futures::{stream, StreamExt};
+use serde::Serialize;
+
+use crate::{
+    common::*,
+    ipfs::{is_ipfs, IpfsGateways},
+    rng::Rng,
+    upload::{
+        assets::{get_updated_metadata, AssetPair},
+        limits::ResourceLimits,
+        remote::read_media,
+    },
+    utils::progress_bar_with_style,
+};
+
+/// Time to wait for the download of a stored file.
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Result of the comparison of the stored files of a cache item with the local files.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageCheck {
+    pub status: StorageStatus,
+    pub checked_at: String,
+    /// Files that differ or could not be downloaded.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageStatus {
+    /// Every stored file is the same as the local file.
+    Verified,
+    /// A stored file differs from the local file.
+    Mismatch,
+    /// A stored file could not be downloaded.
+    Unavailable,
+}
+
+/// Result of the storage check of the upload command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageCheckOutcome {
+    pub checked: usize,
+    pub verified: usize,
+    /// Indices of the items with a stored file that differs from the local file.
+    pub mismatch: Vec<String>,
+    /// Indices of the items with a stored file that could not be downloaded.
+    pub unavailable: Vec<String>,
+}
+
+/// Downloads the stored files of a random sample of items (or of every item with `full`)
+/// and compares them byte by byte with the local files, recording the result in the
+/// cache items. The metadata is compared with the content that was uploaded, which has
+/// the links of the media files. The links of items that differ are cleared, so that the
+/// next upload sends their files again.
+pub async fn verify_storage(
+    asset_pairs: &HashMap<isize, AssetPair>,
+    cache: &mut Cache,
+    full: bool,
+    concurrency: usize,
+    limits: &ResourceLimits,
+    rng: &mut dyn Rng,
+) -> Result<StorageCheckOutcome> {
+    let mut indices: Vec<isize> = asset_pairs.keys().copied().collect();
+
+    if !full {
+        indices = rng
+            .sample_indices(indices.len(), STORAGE_CHECK_SAMPLE)
+            .into_iter()
+            .map(|i| indices[i])
+            .collect();
+    }
+    indices.sort_unstable();
+
+    let items: Vec<(isize, &AssetPair, CacheItem)> = indices
+        .iter()
+        .filter_map(|index| {
+            let item = cache.items.get(&index.to_string())?;
+            Some((*index, asset_pairs.get(index)?, item.clone()))
+        })
+        .collect();
+
+    case_println!(
+        "\n{}Comparing the stored files of {} of {} item(s):",
+        LOOKING_GLASS_EMOJI,
+        items.len(),
+        asset_pairs.len()
+    );
+    let pb = progress_bar_with_style(items.len() as u64);
+
+    let client = &HttpClient::new();
+    let gateways = &IpfsGateways::from_options(&[])?;
+    let progress = &pb;
+
+    let checks: Vec<(isize, StorageCheck)> = stream::iter(items)
+        .map(|(index, asset_pair, item)| async move {
+            let check = check_item(client, gateways, limits, asset_pair, &item).await;
+            progress.inc(1);
+            (index, check)
+        })
+        .buffer_unordered(concurrency)
+        .collect()
+        .await;
+
+    let mut outcome = StorageCheckOutcome {
+        checked: checks.len(),
+        ..Default::default()
+    };
+    let mut problems = Vec::new();
+
+    for (index, check) in checks {
+        let item = cache
+            .items
+            .get_mut(&index.to_string())
+            .expect("Failed to get cache item");
+
+        match check.status {
+            StorageStatus::Verified => outcome.verified += 1,
+            StorageStatus::Mismatch => {
+                outcome.mismatch.push(index.to_string());
+                item.image_link = String::new();
+                item.metadata_link = String::new();
+                item.animation_link = None;
+            }
+            StorageStatus::Unavailable => outcome.unavailable.push(index.to_string()),
+        }
+
+        for problem in &check.problems {
+            problems.push(format!("Item {}: {}", index, problem));
+        }
+        item.storage_check = Some(check);
+    }
+
+    cache.sync_file()?;
+
+    if problems.is_empty() {
+        pb.finish_with_message(format!(
+            "{}",
+            style("Storage check successful").green().bold()
+        ));
+    } else {
+        pb.abandon_with_message(format!("{}", style("Storage check failed ").red().bold()));
+        problems.sort();
+
+        for problem in problems {
+            error!("{}", problem);
+            case_println!("{} {}", style("=>").dim(), problem);
+        }
+    }
+
+    Ok(outcome)
+}
+
+async fn check_item(
+    client: &HttpClient,
+    gateways: &IpfsGateways,
+    limits: &ResourceLimits,
+    asset_pair: &AssetPair,
+    item: &CacheItem,
+) -> StorageCheck {
+    // (kind, link, media file); the metadata content is built from the links
+    let mut files = vec![("image", &item.image_link, Some(&asset_pair.image))];
+    if let (Some(animation), Some(link)) = (&asset_pair.animation, &item.animation_link) {
+        files.push(("animation", link, Some(animation)));
+    }
+    files.push(("metadata", &item.metadata_link, None));
+
+    let mut status = StorageStatus::Verified;
+    let mut problems = Vec::new();
+
+    for (kind, link, media) in files {
+        let local = match media {
+            Some(media) => read_media(media, limits).await.map(|(data, _buffer)| data),
+            None => get_updated_metadata(asset_pair, &item.image_link, &item.animation_link)
+                .map(String::into_bytes),
+        };
+        let result = match local {
+            Ok(local) => download(client, gateways, link)
+                .await
+                .map(|stored| (stored, local)),
+            Err(err) => Err(anyhow!("failed to read the local file: {}", err)),
+        };
+
+        match result {
+            Ok((stored, local)) if stored == local => (),
+            Ok((stored, local)) => {
+                status = StorageStatus::Mismatch;
+                problems.push(format!(
+                    "{} {} differs from the local file ({} byte(s), expected {})",
+                    kind,
+                    link,
+                    stored.len(),
+                    local.len()
+                ));
+            }
+            Err(err) => {
+                if status == StorageStatus::Verified {
+                    status = StorageStatus::Unavailable;
+                }
+                problems.push(format!("{} {}: {}", kind, link, err));
+            }
+        }
+    }
+
+    StorageCheck {
+        status,
+        checked_at: Utc::now().to_rfc3339(),
+        problems,
+    }
+}
+
+async fn download(client: &HttpClient, gateways: &IpfsGateways, link: &str) -> Result<Vec<u8>> {
+    if link.is_empty() {
+        return Err(anyhow!("missing link"));
+    }
+
+    if is_ipfs(link) {
+        return gateways.download(client, link).await;
+    }
+
+    let response = client
+        .get(link)
+        .timeout(DOWNLOAD_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(response.bytes().await?.to_vec())
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::common::*;
+
+/// Bounds the number of files open at the same time and the size of the media files
+/// held in memory while they are sent, independently of the number of concurrent
+/// uploads. Clones share the same limits.
+#[derive(Clone)]
+pub struct ResourceLimits {
+    open_files: Arc<Semaphore>,
+    /// Buffer space in KiB (permits are `u32`).
+    buffer: Arc<Semaphore>,
+    buffer_kib: u32,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        ResourceLimits::new(DEFAULT_MAX_OPEN_FILES, DEFAULT_MAX_BUFFER_MB)
+    }
+}
+
+impl ResourceLimits {
+    /// Limits with the maximum number of open files and the in-memory buffer cap (in MB).
+    pub fn new(max_open_files: usize, max_buffer_mb: u32) -> Self {
+        let buffer_kib = max_buffer_mb.max(1).saturating_mul(1024);
+
+        ResourceLimits {
+            open_files: Arc::new(Semaphore::new(max_open_files.max(1))),
+            buffer: Arc::new(Semaphore::new(buffer_kib as usize)),
+            buffer_kib,
+        }
+    }
+
+    /// Waits for a file handle to be available. The file must only be open while the
+    /// permit is held.
+    pub async fn acquire_file(&self) -> Result<OwnedSemaphorePermit> {
+        self.open_files
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|err| anyhow!("Failed to acquire a file handle: {}", err))
+    }
+
+    /// Returns the cap (in bytes) of the in-memory buffer.
+    pub fn buffer_limit(&self) -> u64 {
+        self.buffer_kib as u64 * 1024
+    }
+
+    /// Waits until `size` bytes of the buffer are available. A file larger than the cap
+    /// reserves the complete buffer, so it is sent on its own.
+    pub async fn reserve_buffer(&self, size: u64) -> Result<BufferPermit> {
+        // rounded down, so that files whose total size is within the cap (e.g. a batch)
+        // can always be reserved together
+        let kib = (size / 1024).min(self.buffer_kib as u64) as u32;
+
+        if kib == 0 {
+            return Ok(BufferPermit::none());
+        }
+
+        let permit = self
+            .buffer
+            .clone()
+            .acquire_many_owned(kib)
+            .await
+            .map_err(|err| anyhow!("Failed to reserve upload buffer: {}", err))?;
+
+        Ok(BufferPermit(Some(permit)))
+    }
+}
+
+/// Space of the in-memory buffer reserved for a media file, released when dropped.
+pub struct BufferPermit(Option<OwnedSemaphorePermit>);
+
+impl BufferPermit {
+    /// Permit for data that is not buffered (e.g. streamed from a remote source).
+    pub fn none() -> Self {
+        BufferPermit(None)
+    }
+}
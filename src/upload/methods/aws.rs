@@ -1,4 +1,4 @@
-use std::{fs, sync::Arc};
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use bs58;
@@ -11,6 +11,8 @@ use crate::{
     config::*,
     upload::{
         assets::{AssetPair, DataType},
+        limits::{BufferPermit, ResourceLimits},
+        remote::{check_media_in_memory, read_media},
         uploader::{AssetInfo, ParallelUploader, Prepare},
     },
 };
@@ -18,43 +20,78 @@ use crate::{
 // Maximum number of times to retry each individual upload.
 const MAX_RETRY: u8 = 3;
 
+// Profile used when the config file does not specify one.
+const DEFAULT_PROFILE: &str = "default";
+
 pub struct AWSMethod {
     pub bucket: Arc<Bucket>,
+    limits: ResourceLimits,
 }
 
 impl AWSMethod {
-    pub async fn new(config_data: &ConfigData) -> Result<Self> {
-        let credentials = Credentials::default()?;
-        let region = AWSMethod::load_region()?;
+    pub async fn new(config_data: &ConfigData, limits: &ResourceLimits) -> Result<Self> {
+        let (credentials, profile) = match &config_data.aws_profile {
+            Some(profile) => (Credentials::from_profile(Some(profile))?, profile.as_str()),
+            None => (Credentials::default()?, DEFAULT_PROFILE),
+        };
+        let region = AWSMethod::load_region(profile)?;
 
         if let Some(aws_s3_bucket) = &config_data.aws_s3_bucket {
             Ok(Self {
                 bucket: Arc::new(Bucket::new(aws_s3_bucket, region, credentials)?),
+                limits: limits.clone(),
             })
         } else {
             Err(anyhow!("Missing 'awsS3Bucket' value in config file."))
         }
     }
 
-    fn load_region() -> Result<Region> {
+    /// Checks that the credentials give access to the bucket.
+    pub async fn check_access(&self) -> Result<()> {
+        let (_, code) = self
+            .bucket
+            .list_page(String::new(), None, None, None, Some(1))
+            .await?;
+
+        match code {
+            200 => Ok(()),
+            403 => Err(anyhow!(
+                "Access denied to bucket '{}' with the configured credentials.",
+                self.bucket.name()
+            )),
+            code => Err(anyhow!("Could not access bucket '{}': {code}", self.bucket.name())),
+        }
+    }
+
+    fn load_region(profile: &str) -> Result<Region> {
         let home_dir = dirs::home_dir().expect("Couldn't find home dir.");
         let credentials = home_dir.join(Path::new(".aws/credentials"));
         let configuration = ini!(credentials
             .to_str()
             .ok_or_else(|| anyhow!("Failed to load AWS credentials"))?);
 
-        if let Some(region) = &configuration["default"]["region"] {
-            Ok(region.parse()?)
-        } else {
-            Err(anyhow!("Region configuration not found"))
+        match configuration
+            .get(profile)
+            .and_then(|section| section.get("region"))
+        {
+            Some(Some(region)) => Ok(region.parse()?),
+            _ => Err(anyhow!(
+                "Region configuration not found for profile '{}'",
+                profile
+            )),
         }
     }
 
-    async fn send(bucket: Arc<Bucket>, asset_info: AssetInfo) -> Result<(String, String)> {
-        let data = match asset_info.data_type {
-            DataType::Image => fs::read(&asset_info.content)?,
-            DataType::Metadata => asset_info.content.into_bytes(),
-            DataType::Animation => fs::read(&asset_info.content)?,
+    async fn send(
+        bucket: Arc<Bucket>,
+        limits: ResourceLimits,
+        asset_info: AssetInfo,
+    ) -> Result<(String, String)> {
+        // the buffer is held until the data is sent
+        let (data, _buffer) = match asset_info.data_type {
+            DataType::Image => read_media(&asset_info.content, &limits).await?,
+            DataType::Metadata => (asset_info.content.into_bytes(), BufferPermit::none()),
+            DataType::Animation => read_media(&asset_info.content, &limits).await?,
         };
 
         let key = bs58::encode(&asset_info.name).into_string();
@@ -87,11 +124,11 @@ impl Prepare for AWSMethod {
     async fn prepare(
         &self,
         _case_config: &CaseConfig,
-        _asset_pairs: &HashMap<isize, AssetPair>,
-        _asset_indices: Vec<(DataType, &[isize])>,
+        asset_pairs: &HashMap<isize, AssetPair>,
+        asset_indices: Vec<(DataType, &[isize])>,
     ) -> Result<()> {
-        // nothing to do here
-        Ok(())
+        // the files are read to memory before they are sent
+        check_media_in_memory(asset_pairs, &asset_indices, &self.limits).await
     }
 }
 
@@ -99,6 +136,7 @@ impl Prepare for AWSMethod {
 impl ParallelUploader for AWSMethod {
     fn upload_asset(&self, asset_info: AssetInfo) -> JoinHandle<Result<(String, String)>> {
         let bucket = self.bucket.clone();
-        tokio::spawn(async move { AWSMethod::send(bucket, asset_info).await })
+        let limits = self.limits.clone();
+        tokio::spawn(async move { AWSMethod::send(bucket, limits, asset_info).await })
     }
 }
@@ -1,4 +1,4 @@
-use std::{cmp, fs, path::Path, sync::Arc};
+use std::{cmp, sync::Arc};
 
 use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
 use async_trait::async_trait;
@@ -17,7 +17,10 @@ use crate::{
     config::*,
     upload::{
         assets::{get_updated_metadata, AssetPair, DataType},
+        limits::{BufferPermit, ResourceLimits},
+        remote::{check_media_in_memory, media_size, read_media},
         uploader::{AssetInfo, ParallelUploader, Prepare, MOCK_URI_SIZE},
+        UploadError,
     },
     utils::*,
 };
@@ -34,24 +37,91 @@ const HEADER_SIZE: u64 = 2000;
 /// Minimum file size for cost calculation
 const MINIMUM_SIZE: u64 = 10000;
 
+/// Returns the Bundlr node of the config file, or the default node of the cluster.
+pub fn bundlr_node(config_data: &ConfigData, cluster: Cluster) -> &str {
+    match (&config_data.bundlr_node, cluster) {
+        (Some(node), _) => node.as_str(),
+        (None, Cluster::Devnet) => BUNDLR_DEVNET,
+        (None, Cluster::Mainnet) => BUNDLR_MAINNET,
+    }
+}
+
+/// Returns the size charged by Bundlr to upload the files of the assets, including
+/// the transaction header of each file.
+pub async fn upload_size(
+    assets: &HashMap<isize, AssetPair>,
+    asset_indices: Vec<(DataType, &[isize])>,
+) -> Result<u64> {
+    let mut total_size = 0;
+
+    for (data_type, indices) in asset_indices {
+        match data_type {
+            DataType::Image => {
+                for index in indices {
+                    let item = assets.get(index).unwrap();
+                    total_size +=
+                        HEADER_SIZE + cmp::max(MINIMUM_SIZE, media_size(&item.image).await?);
+                }
+            }
+            DataType::Animation => {
+                for index in indices {
+                    let item = assets.get(index).unwrap();
+
+                    if let Some(animation) = &item.animation {
+                        total_size +=
+                            HEADER_SIZE + cmp::max(MINIMUM_SIZE, media_size(animation).await?);
+                    }
+                }
+            }
+            DataType::Metadata => {
+                let mock_uri = "x".repeat(MOCK_URI_SIZE);
+
+                for index in indices {
+                    let item = assets.get(index).unwrap();
+                    let animation = if item.animation.is_some() {
+                        Some(mock_uri.clone())
+                    } else {
+                        None
+                    };
+
+                    total_size += HEADER_SIZE
+                        + cmp::max(
+                            MINIMUM_SIZE,
+                            get_updated_metadata(item, &mock_uri, &animation)?
+                                .into_bytes()
+                                .len() as u64,
+                        );
+                }
+            }
+        }
+    }
+
+    Ok(total_size)
+}
+
 pub struct BundlrMethod {
     pub client: Arc<Bundlr<SolanaSigner>>,
     pub case_tag: Tag,
     pubkey: Pubkey,
     node: String,
+    /// Whether the Bundlr balance is funded when it is below the upload cost.
+    auto_fund: bool,
+    limits: ResourceLimits,
 }
 
 impl BundlrMethod {
-    pub async fn new(case_config: &CaseConfig, config_data: &ConfigData) -> Result<Self> {
+    pub async fn new(
+        case_config: &CaseConfig,
+        config_data: &ConfigData,
+        auto_fund: bool,
+        limits: &ResourceLimits,
+    ) -> Result<Self> {
         let client = setup_client(case_config)?;
         let program = client.program(TARS_ID);
         let solana_cluster: Cluster = get_cluster(program.rpc())?;
 
         let bundlr_node = match config_data.upload_method {
-            UploadMethod::Bundlr => match solana_cluster {
-                Cluster::Devnet => BUNDLR_DEVNET,
-                Cluster::Mainnet => BUNDLR_MAINNET,
-            },
+            UploadMethod::Bundlr => bundlr_node(config_data, solana_cluster),
             _ => {
                 return Err(anyhow!(format!(
                     "Upload method '{}' currently unsupported!",
@@ -83,11 +153,13 @@ impl BundlrMethod {
             pubkey: bundlr_pubkey,
             case_tag,
             node: bundlr_node.to_string(),
+            auto_fund,
+            limits: limits.clone(),
         })
     }
 
     /// Return the solana address for Bundlr.
-    async fn get_bundlr_solana_address(http_client: &HttpClient, node: &str) -> Result<String> {
+    pub async fn get_bundlr_solana_address(http_client: &HttpClient, node: &str) -> Result<String> {
         let url = format!("{}/info", node);
         let data = http_client.get(&url).send().await?.json::<Value>().await?;
         let addresses = data
@@ -104,7 +176,7 @@ impl BundlrMethod {
     }
 
     /// Add fund to the Bundlr address.
-    async fn fund_bundlr_address(
+    pub async fn fund_bundlr_address(
         rpc_client: RpcClient,
         http_client: &HttpClient,
         bundlr_address: &Pubkey,
@@ -123,9 +195,9 @@ impl BundlrMethod {
             recent_blockhash,
         );
 
-        println!("Funding address:");
-        println!("  -> pubkey: {}", payer_pubkey);
-        println!(
+        case_println!("Funding address:");
+        case_println!("  -> pubkey: {}", payer_pubkey);
+        case_println!(
             "  -> lamports: {} (◎ {})",
             amount,
             amount as f64 / LAMPORTS_PER_SOL as f64
@@ -136,7 +208,7 @@ impl BundlrMethod {
             CommitmentConfig::confirmed(),
         )?;
 
-        println!("{} {sig}", style("Signature:").bold());
+        case_println!("{} {sig}", style("Signature:").bold());
 
         let mut map = HashMap::new();
         map.insert("tx_id", sig.to_string());
@@ -167,7 +239,11 @@ impl BundlrMethod {
     }
 
     /// Return the Bundlr fee for upload based on the data size.
-    async fn get_bundlr_fee(http_client: &HttpClient, node: &str, data_size: u64) -> Result<u64> {
+    pub async fn get_bundlr_fee(
+        http_client: &HttpClient,
+        node: &str,
+        data_size: u64,
+    ) -> Result<u64> {
         let required_amount = http_client
             .get(format!("{node}/price/solana/{data_size}"))
             .send()
@@ -181,12 +257,14 @@ impl BundlrMethod {
     async fn send(
         client: Arc<Bundlr<SolanaSigner>>,
         tag: Tag,
+        limits: ResourceLimits,
         asset_info: AssetInfo,
     ) -> Result<(String, String)> {
-        let data = match asset_info.data_type {
-            DataType::Image => fs::read(&asset_info.content)?,
-            DataType::Metadata => asset_info.content.into_bytes(),
-            DataType::Animation => fs::read(&asset_info.content)?,
+        // the buffer is held until the data is sent
+        let (data, _buffer) = match asset_info.data_type {
+            DataType::Image => read_media(&asset_info.content, &limits).await?,
+            DataType::Metadata => (asset_info.content.into_bytes(), BufferPermit::none()),
+            DataType::Animation => read_media(&asset_info.content, &limits).await?,
         };
 
         let tags = vec![
@@ -216,52 +294,11 @@ impl Prepare for BundlrMethod {
         assets: &HashMap<isize, AssetPair>,
         asset_indices: Vec<(DataType, &[isize])>,
     ) -> Result<()> {
+        // the files are read to memory to be signed before they are sent
+        check_media_in_memory(assets, &asset_indices, &self.limits).await?;
+
         // calculates the size of the files to upload
-        let mut total_size = 0;
-
-        for (data_type, indices) in asset_indices {
-            match data_type {
-                DataType::Image => {
-                    for index in indices {
-                        let item = assets.get(index).unwrap();
-                        let path = Path::new(&item.image);
-                        total_size +=
-                            HEADER_SIZE + cmp::max(MINIMUM_SIZE, fs::metadata(path)?.len());
-                    }
-                }
-                DataType::Animation => {
-                    for index in indices {
-                        let item = assets.get(index).unwrap();
-
-                        if let Some(animation) = &item.animation {
-                            let path = Path::new(animation);
-                            total_size +=
-                                HEADER_SIZE + cmp::max(MINIMUM_SIZE, fs::metadata(path)?.len());
-                        }
-                    }
-                }
-                DataType::Metadata => {
-                    let mock_uri = "x".repeat(MOCK_URI_SIZE);
-
-                    for index in indices {
-                        let item = assets.get(index).unwrap();
-                        let animation = if item.animation.is_some() {
-                            Some(mock_uri.clone())
-                        } else {
-                            None
-                        };
-
-                        total_size += HEADER_SIZE
-                            + cmp::max(
-                                MINIMUM_SIZE,
-                                get_updated_metadata(&item.metadata, &mock_uri.clone(), &animation)?
-                                    .into_bytes()
-                                    .len() as u64,
-                            );
-                    }
-                }
-            }
-        }
+        let total_size = upload_size(assets, asset_indices).await?;
 
         info!("Total upload size: {}", total_size);
 
@@ -287,6 +324,12 @@ impl Prepare for BundlrMethod {
         };
 
         if lamports_fee > balance {
+            if !self.auto_fund {
+                let error = UploadError::InsufficientBundlrBalance(balance, lamports_fee).into();
+                error!("{:?}", error);
+                return Err(error);
+            }
+
             BundlrMethod::fund_bundlr_address(
                 rpc_client,
                 &http_client,
@@ -298,6 +341,9 @@ impl Prepare for BundlrMethod {
             .await?;
 
             let pb = ProgressBar::new(MAX_RETRY);
+            if is_json_output() {
+                pb.set_draw_target(ProgressDrawTarget::hidden());
+            }
             pb.set_style(ProgressStyle::default_bar().template("{spinner} {msg} {wide_bar}"));
             pb.enable_steady_tick(60);
             pb.set_message("Verifying balance:");
@@ -342,6 +388,7 @@ impl ParallelUploader for BundlrMethod {
     fn upload_asset(&self, asset_info: AssetInfo) -> JoinHandle<Result<(String, String)>> {
         let client = self.client.clone();
         let tag = self.case_tag.clone();
-        tokio::spawn(async move { BundlrMethod::send(client, tag, asset_info).await })
+        let limits = self.limits.clone();
+        tokio::spawn(async move { BundlrMethod::send(client, tag, limits, asset_info).await })
     }
 }
@@ -1,10 +1,6 @@
-use std::{
-    fs,
-    path::Path,
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
 };
 
 use async_trait::async_trait;
@@ -68,11 +64,12 @@ pub struct NftError {
 
 pub struct NftStorageMethod {
     client: Arc<Client>,
+    limits: ResourceLimits,
 }
 
 impl NftStorageMethod {
     /// Initialize a new NftStorageHandler.
-    pub async fn new(config_data: &ConfigData) -> Result<Self> {
+    pub async fn new(config_data: &ConfigData, limits: &ResourceLimits) -> Result<Self> {
         if let Some(auth_token) = &config_data.nft_storage_auth_token {
             let client_builder = Client::builder();
 
@@ -90,6 +87,7 @@ impl NftStorageMethod {
             match response.status() {
                 StatusCode::OK => Ok(Self {
                     client: Arc::new(client),
+                    limits: limits.clone(),
                 }),
                 StatusCode::UNAUTHORIZED => {
                     Err(anyhow!("Invalid nft.storage authentication token."))
@@ -118,14 +116,10 @@ impl Prepare for NftStorageMethod {
             for index in indices {
                 let item = asset_pairs.get(index).unwrap();
                 let size = match data_type {
-                    DataType::Image => {
-                        let path = Path::new(&item.image);
-                        fs::metadata(path)?.len()
-                    }
+                    DataType::Image => media_size(&item.image).await?,
                     DataType::Animation => {
                         if let Some(animation) = &item.animation {
-                            let path = Path::new(animation);
-                            fs::metadata(path)?.len()
+                            media_size(animation).await?
                         } else {
                             0
                         }
@@ -138,7 +132,7 @@ impl Prepare for NftStorageMethod {
                             None
                         };
 
-                        get_updated_metadata(&item.metadata, &mock_uri.clone(), &animation)?
+                        get_updated_metadata(item, &mock_uri, &animation)?
                             .into_bytes()
                             .len() as u64
                     }
@@ -167,25 +161,27 @@ impl Uploader for NftStorageMethod {
         assets: &mut Vec<AssetInfo>,
         progress: &ProgressBar,
         interrupted: Arc<AtomicBool>,
+        _concurrency: usize,
     ) -> Result<Vec<UploadError>> {
         let mut batches: Vec<Vec<&AssetInfo>> = Vec::new();
         let mut current: Vec<&AssetInfo> = Vec::new();
         let mut upload_size = 0;
         let mut upload_count = 0;
+        // a batch is held in memory until it is sent
+        let size_limit = FILE_SIZE_LIMIT.min(self.limits.buffer_limit());
 
         for asset_info in assets {
             let size = match data_type {
-                DataType::Image | DataType::Animation => {
-                    let path = Path::new(&asset_info.content);
-                    fs::metadata(path)?.len()
-                }
+                DataType::Image | DataType::Animation => media_size(&asset_info.content).await?,
                 DataType::Metadata => {
                     let content = String::from(&asset_info.content);
                     content.into_bytes().len() as u64
                 }
             };
 
-            if (upload_size + size) > FILE_SIZE_LIMIT || (upload_count + 1) > FILE_COUNT_LIMIT {
+            if !current.is_empty()
+                && ((upload_size + size) > size_limit || (upload_count + 1) > FILE_COUNT_LIMIT)
+            {
                 batches.push(current);
                 current = Vec::new();
                 upload_size = 0;
@@ -208,19 +204,30 @@ impl Uploader for NftStorageMethod {
         while !interrupted.load(Ordering::SeqCst) && !batches.is_empty() {
             let batch = batches.remove(0);
             let mut form = Form::new();
+            // buffers of the batch, held until the form is sent
+            let mut buffers = Vec::new();
 
             for asset_info in &batch {
-                let data = match asset_info.data_type {
-                    DataType::Image | DataType::Animation => fs::read(&asset_info.content)?,
+                // media files are streamed from the source
+                let file = match asset_info.data_type {
+                    DataType::Image | DataType::Animation => {
+                        let (part, buffer) = media_part(
+                            &asset_info.content,
+                            &asset_info.name,
+                            &asset_info.content_type,
+                            &self.limits,
+                        )
+                        .await?;
+                        buffers.push(buffer);
+                        part
+                    }
                     DataType::Metadata => {
                         let content = String::from(&asset_info.content);
-                        content.into_bytes()
+                        Part::bytes(content.into_bytes())
+                            .file_name(asset_info.name.clone())
+                            .mime_str(asset_info.content_type.as_str())?
                     }
                 };
-
-                let file = Part::bytes(data)
-                    .file_name(asset_info.name.clone())
-                    .mime_str(asset_info.content_type.as_str())?;
                 form = form.part("file", file);
             }
 
@@ -231,6 +238,7 @@ impl Uploader for NftStorageMethod {
                 .send()
                 .await?;
             let status = response.status();
+            drop(buffers);
 
             if status.is_success() {
                 let body = response.json::<Value>().await?;
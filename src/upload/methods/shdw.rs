@@ -1,4 +1,4 @@
-use std::{fs, ops::Deref, sync::Arc};
+use std::{ops::Deref, sync::Arc};
 
 use async_trait::async_trait;
 use data_encoding::HEXLOWER;
@@ -15,6 +15,8 @@ use crate::{
     config::*,
     upload::{
         assets::{get_updated_metadata, AssetPair, DataType},
+        limits::{BufferPermit, ResourceLimits},
+        remote::{media_part, media_size},
         uploader::{AssetInfo, ParallelUploader, Prepare, MOCK_URI_SIZE},
         UploadError,
     },
@@ -45,6 +47,7 @@ pub struct Config {
     keypair: Keypair,
     storage_account: Pubkey,
     storage_info: StorageInfo,
+    limits: ResourceLimits,
 }
 
 pub struct SHDWMethod(Arc<Config>);
@@ -57,7 +60,11 @@ impl Deref for SHDWMethod {
 }
 
 impl SHDWMethod {
-    pub async fn new(case_config: &CaseConfig, config_data: &ConfigData) -> Result<Self> {
+    pub async fn new(
+        case_config: &CaseConfig,
+        config_data: &ConfigData,
+        limits: &ResourceLimits,
+    ) -> Result<Self> {
         if let Some(pubkey) = &config_data.shdw_storage_account {
             let client = setup_client(case_config)?;
             let program = client.program(SHADOW_DRIVE_PROGRAM_ID);
@@ -91,6 +98,7 @@ impl SHDWMethod {
                         keypair,
                         storage_account: Pubkey::from_str(pubkey)?,
                         storage_info,
+                        limits: limits.clone(),
                     })))
                 }
                 code => Err(anyhow!("Could not initialize storage account: {code}")),
@@ -121,8 +129,7 @@ impl Prepare for SHDWMethod {
                 DataType::Image => {
                     for index in indices {
                         let item = assets.get(index).unwrap();
-                        let path = Path::new(&item.image);
-                        total_size += fs::metadata(path)?.len();
+                        total_size += media_size(&item.image).await?;
                     }
                 }
                 DataType::Animation => {
@@ -130,8 +137,7 @@ impl Prepare for SHDWMethod {
                         let item = assets.get(index).unwrap();
 
                         if let Some(animation) = &item.animation {
-                            let path = Path::new(animation);
-                            total_size += fs::metadata(path)?.len();
+                            total_size += media_size(animation).await?;
                         }
                     }
                 }
@@ -146,10 +152,9 @@ impl Prepare for SHDWMethod {
                             None
                         };
 
-                        total_size +=
-                            get_updated_metadata(&item.metadata, &mock_uri.clone(), &animation)?
-                                .into_bytes()
-                                .len() as u64;
+                        total_size += get_updated_metadata(item, &mock_uri, &animation)?
+                            .into_bytes()
+                            .len() as u64;
                     }
                 }
             }
@@ -176,12 +181,6 @@ impl ParallelUploader for SHDWMethod {
 
 impl Config {
     async fn send(&self, asset_info: AssetInfo) -> Result<(String, String)> {
-        let data = match asset_info.data_type {
-            DataType::Image => fs::read(&asset_info.content)?,
-            DataType::Metadata => asset_info.content.into_bytes(),
-            DataType::Animation => fs::read(&asset_info.content)?,
-        };
-
         let mut context = Context::new(&SHA256);
         context.update(asset_info.name.as_bytes());
         let hash = HEXLOWER.encode(context.finish().as_ref());
@@ -196,9 +195,25 @@ impl Config {
         let signature = self.keypair.sign_message(message.as_bytes()).to_string();
 
         let mut form = Form::new();
-        let file = Part::bytes(data)
-            .file_name(asset_info.name.clone())
-            .mime_str(asset_info.content_type.as_str())?;
+        // media files are streamed from the source; the buffer is held until the form
+        // is sent
+        let (file, _buffer) = match asset_info.data_type {
+            DataType::Image | DataType::Animation => {
+                media_part(
+                    &asset_info.content,
+                    &asset_info.name,
+                    &asset_info.content_type,
+                    &self.limits,
+                )
+                .await?
+            }
+            DataType::Metadata => (
+                Part::bytes(asset_info.content.clone().into_bytes())
+                    .file_name(asset_info.name.clone())
+                    .mime_str(asset_info.content_type.as_str())?,
+                BufferPermit::none(),
+            ),
+        };
         form = form
             .part("file", file)
             .text("message", signature)
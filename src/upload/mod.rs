@@ -1,11 +1,21 @@
+pub mod archive;
 pub mod assets;
+pub mod duplicates;
 pub mod errors;
+pub mod integrity;
+pub mod limits;
 pub mod methods;
 pub mod process;
+pub mod remote;
 pub mod uploader;
 
+pub use archive::*;
 pub use assets::*;
+pub use duplicates::*;
 pub use errors::*;
+pub use integrity::*;
+pub use limits::*;
 pub use methods::*;
 pub use process::*;
+pub use remote::*;
 pub use uploader::*;
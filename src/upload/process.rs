@@ -1,7 +1,6 @@
 use std::{
     borrow::Borrow,
     collections::HashSet,
-    ffi::OsStr,
     fmt::Write as _,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -10,11 +9,13 @@ use std::{
 };
 
 use console::style;
+use serde::Serialize;
 
 use crate::{
-    cache::{load_cache, Cache},
+    cache::{load_cluster_cache, Cache, CacheStats},
     common::*,
     config::{get_config_data, CaseConfig},
+    rng::SystemRng,
     upload::*,
     utils::*,
     validate::format::Metadata,
@@ -25,8 +26,23 @@ pub struct UploadArgs {
     pub config: String,
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
     pub cache: String,
     pub interrupted: Arc<AtomicBool>,
+    pub inject_metadata: bool,
+    pub concurrency: usize,
+    pub assets_url: Option<String>,
+    pub auto_fund: bool,
+    /// Maximum number of files open at the same time.
+    pub max_open_files: usize,
+    /// Cap (in MB) of the media files held in memory.
+    pub max_buffer_mb: u32,
+    /// Compares the stored files with the local files after the upload.
+    pub verify_storage: bool,
+    /// Compares the files of every item instead of a sample.
+    pub verify_full: bool,
+    /// Skips the check for items with the same image or metadata.
+    pub allow_duplicates: bool,
 }
 
 pub struct AssetType {
@@ -35,12 +51,29 @@ pub struct AssetType {
     pub animation: Vec<isize>,
 }
 
-pub async fn process_upload(args: UploadArgs) -> Result<()> {
-    let case_config = case_setup(args.keypair, args.rpc_url)?;
-    let config_data = get_config_data(&args.config)?;
+/// Result of the upload command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadOutcome {
+    pub asset_pairs: usize,
+    pub images_uploaded: usize,
+    pub animations_uploaded: usize,
+    pub metadata_uploaded: usize,
+    pub cache: CacheStats,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_check: Option<StorageCheckOutcome>,
+    /// Items with the same image or metadata (unless --allow-duplicates).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub duplicates: Vec<DuplicateAssets>,
+}
+
+pub async fn process_upload(args: UploadArgs) -> Result<UploadOutcome> {
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let config_data = get_config_data(&args.config, &args.global.address_book)?;
+    let limits = ResourceLimits::new(args.max_open_files, args.max_buffer_mb);
 
     // loading assets
-    println!(
+    case_println!(
         "{} {}Loading assets",
         style("[1/4]").bold().dim(),
         ASSETS_EMOJI
@@ -48,12 +81,38 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
 
     let pb = spinner_with_style();
     pb.enable_steady_tick(120);
+
     pb.set_message("Reading files...");
-    let asset_pairs = get_asset_pairs(&args.assets_dir)?;
+    // with a remote source or an archive, only the metadata files are staged locally and
+    // the media files are read from the source on upload
+    let (mut asset_pairs, metadata_dir) = match &args.assets_url {
+        Some(assets_url) => (
+            get_remote_asset_pairs(assets_url, Path::new(DEFAULT_STAGING_DIR)).await?,
+            DEFAULT_STAGING_DIR,
+        ),
+        None if is_archive(&args.assets_dir) => (
+            get_archive_asset_pairs(&args.assets_dir, Path::new(DEFAULT_STAGING_DIR))?,
+            DEFAULT_STAGING_DIR,
+        ),
+        None => (get_asset_pairs(&args.assets_dir)?, args.assets_dir.as_str()),
+    };
+
+    if args.inject_metadata {
+        pb.set_message("Updating metadata files...");
+        let updated = inject_metadata(metadata_dir, &config_data.symbol, &config_data.creators)?;
+        info!("Injected symbol and creators into {} metadata file(s)", updated);
+
+        for pair in asset_pairs.values_mut() {
+            pair.metadata_hash = encode(&pair.metadata)?;
+        }
+    }
 
     // creates/loads the cache
-    let mut cache = load_cache(&args.cache, true)?;
-    if asset_pairs.get(&-1).is_none() {
+    let mut cache = load_cluster_cache(&case_config, &args.cache, true)?;
+    cache.set_signer(&case_config.keypair)?;
+    // a collection created with 'collection create' is kept without collection files
+    if asset_pairs.get(&-1).is_none() && !cache.items.get("-1").map_or(false, |item| item.on_chain)
+    {
         cache.items.remove("-1");
     }
 
@@ -95,6 +154,7 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
                     item.metadata_hash = pair.metadata_hash.clone();
                     item.metadata_link = String::new();
                     item.on_chain = false;
+                    item.storage_check = None;
                     // we need to upload metadata only
                     indices.metadata.push(*index);
                 }
@@ -151,19 +211,36 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
 
     pb.finish_and_clear();
 
-    println!(
+    // intentional duplicates (e.g. editions of the same artwork) are not reported
+    let duplicates = if args.allow_duplicates {
+        Vec::new()
+    } else {
+        find_duplicate_assets(&asset_pairs)?
+    };
+    warn_duplicate_assets(&duplicates);
+
+    case_println!(
         "Found {} asset pair(s), uploading files:",
         asset_pairs.len()
     );
-    println!("+--------------------+");
-    println!("| images    | {:>6} |", indices.image.len());
-    println!("| metadata  | {:>6} |", indices.metadata.len());
+    case_println!("+--------------------+");
+    case_println!("| images    | {:>6} |", indices.image.len());
+    case_println!("| metadata  | {:>6} |", indices.metadata.len());
 
     if !indices.animation.is_empty() {
-        println!("| animation | {:>6} |", indices.animation.len());
+        case_println!("| animation | {:>6} |", indices.animation.len());
     }
 
-    println!("+--------------------+");
+    case_println!("+--------------------+");
+
+    let mut outcome = UploadOutcome {
+        asset_pairs: asset_pairs.len(),
+        images_uploaded: indices.image.len(),
+        animations_uploaded: indices.animation.len(),
+        metadata_uploaded: indices.metadata.len(),
+        duplicates,
+        ..Default::default()
+    };
 
     // this should never happen, since every time we update the image file we
     // need to update the metadata
@@ -183,7 +260,7 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
     let mut errors = Vec::new();
 
     if need_upload {
-        println!(
+        case_println!(
             "\n{} {}Initializing upload",
             style(if indices.animation.is_empty() {
                 "[2/4]"
@@ -198,7 +275,7 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
         let pb = spinner_with_style();
         pb.set_message("Connecting...");
 
-        let storage = initialize(&case_config, &config_data).await?;
+        let storage = initialize(&case_config, &config_data, args.auto_fund, &limits).await?;
 
         pb.finish_with_message("Connected");
 
@@ -217,7 +294,7 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
         // clear the interruption handler value ahead of the upload
         args.interrupted.store(false, Ordering::SeqCst);
 
-        println!(
+        case_println!(
             "\n{} {}Uploading image files {}",
             style(if indices.animation.is_empty() {
                 "[3/4]"
@@ -244,6 +321,7 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
                     DataType::Image,
                     storage.borrow(),
                     args.interrupted.clone(),
+                    args.concurrency,
                 )
                 .await?,
             );
@@ -263,7 +341,7 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
         }
 
         if !indices.animation.is_empty() {
-            println!(
+            case_println!(
                 "\n{} {}Uploading animation files {}",
                 style("[4/5]").bold().dim(),
                 UPLOAD_EMOJI,
@@ -285,6 +363,7 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
                     DataType::Animation,
                     storage.borrow(),
                     args.interrupted.clone(),
+                    args.concurrency,
                 )
                 .await?,
             );
@@ -303,7 +382,7 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
             }
         }
 
-        println!(
+        case_println!(
             "\n{} {}Uploading metadata files {}",
             style(if indices.animation.is_empty() {
                 "[4/4]"
@@ -330,23 +409,25 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
                     DataType::Metadata,
                     storage.borrow(),
                     args.interrupted.clone(),
+                    args.concurrency,
                 )
                 .await?,
             );
         }
     } else {
-        println!("\n....no files need uploading, skipping remaining steps.");
+        case_println!("\n....no files need uploading, skipping remaining steps.");
     }
 
     // sanity check
 
     cache.items.sort_keys();
     cache.sync_file()?;
+    outcome.cache = cache.stats();
 
     let mut count = 0;
 
-    for (_index, item) in cache.items.0 {
-        let has_animation = if let Some(animation_link) = item.animation_link {
+    for item in cache.items.0.values() {
+        let has_animation = if let Some(animation_link) = &item.animation_link {
             animation_link.is_empty()
         } else {
             false
@@ -357,7 +438,7 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
         }
     }
 
-    println!(
+    case_println!(
         "\n{}",
         style(format!(
             "{}/{} asset pair(s) uploaded.",
@@ -395,11 +476,32 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
         return Err(UploadError::Incomplete(message).into());
     }
 
-    Ok(())
+    if args.verify_storage {
+        let storage_check = verify_storage(
+            &asset_pairs,
+            &mut cache,
+            args.verify_full,
+            args.concurrency,
+            &limits,
+            &mut SystemRng::default(),
+        )
+        .await?;
+        let (mismatch, unavailable) = (
+            storage_check.mismatch.len(),
+            storage_check.unavailable.len(),
+        );
+        outcome.storage_check = Some(storage_check);
+
+        if mismatch > 0 || unavailable > 0 {
+            return Err(UploadError::StorageCheckFailed(mismatch, unavailable).into());
+        }
+    }
+
+    Ok(outcome)
 }
 
 /// Upload the data to the selected storage.
-async fn upload_data(
+pub async fn upload_data(
     case_config: &CaseConfig,
     asset_pairs: &HashMap<isize, AssetPair>,
     cache: &mut Cache,
@@ -407,8 +509,8 @@ async fn upload_data(
     data_type: DataType,
     uploader: &dyn Uploader,
     interrupted: Arc<AtomicBool>,
+    concurrency: usize,
 ) -> Result<Vec<UploadError>> {
-    let mut extension = HashSet::with_capacity(1);
     let mut paths = Vec::new();
 
     for index in indices {
@@ -432,52 +534,33 @@ async fn upload_data(
             }
         };
 
-        let path = Path::new(&file_path);
-        let ext = path
-            .extension()
-            .and_then(OsStr::to_str)
-            .expect("Failed to convert extension from unicode");
-        extension.insert(String::from(ext));
-
-        paths.push(file_path);
+        paths.push((item, file_path));
     }
 
-    // validates that all files have the same extension
-    let extension = if extension.len() == 1 {
-        extension.iter().next().unwrap()
-    } else {
-        return Err(anyhow!("Invalid file extension: {:?}", extension));
-    };
-
-    let content_type = match data_type {
-        DataType::Image => format!("image/{}", extension),
-        DataType::Metadata => "application/json".to_string(),
-        DataType::Animation => format!("video/{}", extension),
-    };
-
     // uploading data
 
-    println!("\nSending data: (Ctrl+C to abort)");
+    case_println!("\nSending data: (Ctrl+C to abort)");
 
     let pb = progress_bar_with_style(paths.len() as u64);
 
     let mut assets = Vec::new();
 
-    for file_path in paths {
-        // path to the media/metadata file
-        let path = Path::new(&file_path);
-        let file_name = String::from(
-            path.file_name()
-                .and_then(OsStr::to_str)
-                .expect("Filed to get file name."),
-        );
-        let (asset_id, cache_item) = get_cache_item(path, cache)?;
+    for (asset_pair, file_path) in paths {
+        // name of the media/metadata file (files can also be URLs of a remote source)
+        let file_name = asset_file_name(&file_path);
+        let (asset_id, cache_item) = get_cache_item(Path::new(&file_name), cache)?;
+
+        // media files can have different types (e.g. mp4 and glb animations)
+        let content_type = match data_type {
+            DataType::Metadata => "application/json".to_string(),
+            _ => media_content_type(&file_name)?,
+        };
 
         let content = match data_type {
             // replaces the media link without modifying the original file to avoid
             // changing the hash of the metadata file
             DataType::Metadata => get_updated_metadata(
-                &file_path,
+                asset_pair,
                 &cache_item.image_link,
                 &cache_item.animation_link,
             )?,
@@ -489,7 +572,7 @@ async fn upload_data(
             name: file_name,
             content,
             data_type: data_type.clone(),
-            content_type: content_type.clone(),
+            content_type,
         });
     }
 
@@ -501,6 +584,7 @@ async fn upload_data(
             &mut assets,
             &pb,
             interrupted,
+            concurrency,
         )
         .await?;
 
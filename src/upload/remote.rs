@@ -0,0 +1,344 @@
+use std::fs;
+
+use data_encoding::HEXLOWER;
+use reqwest::{
+    header::{CONTENT_RANGE, ETAG, LAST_MODIFIED, RANGE},
+    multipart::Part,
+    Body, StatusCode,
+};
+use ring::digest::{digest, SHA256};
+use url::Url;
+
+use crate::{
+    common::*,
+    upload::{
+        archive::{archive_entry_size, is_archive_entry, read_archive_entry},
+        assets::{
+            encode, ensure_asset_files, AssetPair, DataType, ANIMATION_EXTENSIONS, IMAGE_EXTENSIONS,
+        },
+        errors::UploadError,
+        limits::{BufferPermit, ResourceLimits},
+    },
+    validate::format::Metadata,
+};
+
+/// Returns `true` if the content of an asset is a URL of a remote staging source.
+pub fn is_remote(content: &str) -> bool {
+    content.starts_with("https://") || content.starts_with("http://")
+}
+
+/// Returns the file name of an asset path or URL. The query string is ignored, since
+/// presigned URLs carry their signature in it.
+pub fn asset_file_name(content: &str) -> String {
+    if is_remote(content) {
+        if let Some(name) = Url::parse(content)
+            .ok()
+            .and_then(|url| url.path_segments()?.last().map(|name| name.to_string()))
+        {
+            return name;
+        }
+    }
+
+    Path::new(content)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| content.to_string())
+}
+
+/// Lists the files of a remote staging source, which can be a JSON array of URLs (e.g.
+/// S3 presigned URLs) or a plain text listing with one URL per line.
+async fn list_remote_files(client: &HttpClient, source: &str) -> Result<Vec<String>> {
+    let response = client.get(source).send().await?;
+    let status = response.status();
+
+    if !status.is_success() {
+        return Err(anyhow!(
+            "Failed to read the staging listing {}: {}",
+            source,
+            status
+        ));
+    }
+
+    let body = response.text().await?;
+
+    let urls: Vec<String> = match serde_json::from_str(&body) {
+        Ok(urls) => urls,
+        Err(_) => body
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect(),
+    };
+
+    for url in &urls {
+        if !is_remote(url) {
+            return Err(anyhow!("Invalid URL in the staging listing: {}", url));
+        }
+    }
+
+    Ok(urls)
+}
+
+/// Returns the asset pairs of a remote staging source. Metadata files are downloaded to
+/// the staging directory (they are small and need to be updated before upload), while
+/// media files are only referenced by their URL and streamed to the storage on upload.
+pub async fn get_remote_asset_pairs(
+    source: &str,
+    staging_dir: &Path,
+) -> Result<HashMap<isize, AssetPair>> {
+    let client = HttpClient::new();
+    let urls = list_remote_files(&client, source).await?;
+
+    // file name -> URL
+    let files: HashMap<String, String> = urls
+        .into_iter()
+        .map(|url| (asset_file_name(&url), url))
+        .collect();
+
+    let metadata_filenames: Vec<String> = files
+        .keys()
+        .filter(|name| name.to_lowercase().ends_with(".json"))
+        .cloned()
+        .collect();
+
+    ensure_asset_files(&files.keys().cloned().collect::<Vec<String>>())?;
+    fs::create_dir_all(staging_dir)?;
+
+    let mut asset_pairs = HashMap::new();
+
+    for metadata_filename in metadata_filenames {
+        let stem = metadata_filename.split('.').next().unwrap();
+
+        let index: isize = if stem == "collection" {
+            -1
+        } else {
+            stem.parse::<isize>().map_err(|_| {
+                anyhow!(
+                    "Couldn't parse filename '{}' to a valid index number.",
+                    metadata_filename
+                )
+            })?
+        };
+
+        let image = find_media(&files, stem, IMAGE_EXTENSIONS).ok_or_else(|| {
+            if index == -1 {
+                anyhow!("Couldn't find the collection image URL.")
+            } else {
+                anyhow!("Couldn't find an image URL at index {}.", index)
+            }
+        })?;
+        let animation = find_media(&files, stem, ANIMATION_EXTENSIONS);
+
+        let metadata_path = staging_dir.join(&metadata_filename);
+        let content = client
+            .get(&files[&metadata_filename])
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        fs::write(&metadata_path, &content)?;
+
+        let metadata: Metadata = serde_json::from_slice(&content).map_err(|e| {
+            anyhow!("Failed to read metadata file '{metadata_filename}' with error: {e}")
+        })?;
+        let metadata_path = path_to_string(&metadata_path)?;
+
+        let animation_hash = match &animation {
+            Some(animation) => Some(remote_hash(&client, animation).await?),
+            None => None,
+        };
+
+        asset_pairs.insert(
+            index,
+            AssetPair {
+                name: metadata.name,
+                metadata_hash: encode(&metadata_path)?,
+                metadata: metadata_path,
+                image_hash: remote_hash(&client, &image).await?,
+                image,
+                animation_hash,
+                animation,
+            },
+        );
+    }
+
+    Ok(asset_pairs)
+}
+
+/// Returns the content of the media file of an index (`stem`) with one of the extensions.
+pub fn find_media(
+    files: &HashMap<String, String>,
+    stem: &str,
+    extensions: &[&str],
+) -> Option<String> {
+    files
+        .iter()
+        .find(|(name, _)| match name.rsplit_once('.') {
+            Some((name_stem, extension)) => {
+                name_stem == stem && extensions.iter().any(|e| e.eq_ignore_ascii_case(extension))
+            }
+            None => false,
+        })
+        .map(|(_, url)| url.clone())
+}
+
+/// Size and version information of a remote file.
+struct RemoteInfo {
+    size: Option<u64>,
+    version: String,
+}
+
+/// Requests the first byte of a remote file to read its size and version headers. A
+/// ranged `GET` is used instead of `HEAD` since presigned URLs are only valid for the
+/// method they were signed for.
+async fn probe(client: &HttpClient, url: &str) -> Result<RemoteInfo> {
+    let response = client
+        .get(url)
+        .header(RANGE, "bytes=0-0")
+        .send()
+        .await?
+        .error_for_status()?;
+    let headers = response.headers();
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+    };
+
+    // "bytes 0-0/<size>" for partial responses, otherwise the complete file was sent
+    let size = if response.status() == StatusCode::PARTIAL_CONTENT {
+        header(CONTENT_RANGE)
+            .rsplit_once('/')
+            .and_then(|(_, size)| size.parse().ok())
+    } else {
+        response.content_length()
+    };
+
+    // the query string is not part of the version, since presigned URLs change every
+    // time they are generated
+    let version = format!(
+        "{}|{}|{}|{}",
+        Url::parse(url)?.path(),
+        header(ETAG),
+        size.map(|size| size.to_string()).unwrap_or_default(),
+        header(LAST_MODIFIED)
+    );
+
+    Ok(RemoteInfo { size, version })
+}
+
+/// Returns a hash identifying the current version of a remote file without downloading it.
+async fn remote_hash(client: &HttpClient, url: &str) -> Result<String> {
+    let info = probe(client, url).await?;
+    Ok(HEXLOWER.encode(digest(&SHA256, info.version.as_bytes()).as_ref()))
+}
+
+/// Returns the size of a media file.
+pub async fn media_size(content: &str) -> Result<u64> {
+    if is_remote(content) {
+        probe(&HttpClient::new(), content)
+            .await?
+            .size
+            .ok_or_else(|| anyhow!("Missing content length of {}", asset_file_name(content)))
+    } else if is_archive_entry(content) {
+        archive_entry_size(content)
+    } else {
+        Ok(fs::metadata(content)?.len())
+    }
+}
+
+/// Returns the data of a media file with the buffer space it holds. Remote files and
+/// archive entries are read to memory, for upload methods that need the complete data
+/// (e.g. to sign it); `check_media_in_memory` makes sure they fit in the buffer.
+pub async fn read_media(content: &str, limits: &ResourceLimits) -> Result<(Vec<u8>, BufferPermit)> {
+    let buffer = limits.reserve_buffer(media_size(content).await?).await?;
+
+    let data = if is_remote(content) {
+        let response = HttpClient::new()
+            .get(content)
+            .send()
+            .await?
+            .error_for_status()?;
+        response.bytes().await?.to_vec()
+    } else if is_archive_entry(content) {
+        read_archive_entry(content)?
+    } else {
+        // the file is only open while it is read
+        let _file = limits.acquire_file().await?;
+        fs::read(content)?
+    };
+
+    Ok((data, buffer))
+}
+
+/// Checks that the media files of the indices fit in the in-memory buffer, for upload
+/// methods that hold the complete data of a file while it is sent (AWS and Bundlr).
+pub async fn check_media_in_memory(
+    assets: &HashMap<isize, AssetPair>,
+    asset_indices: &[(DataType, &[isize])],
+    limits: &ResourceLimits,
+) -> Result<()> {
+    let limit = limits.buffer_limit();
+
+    for (data_type, indices) in asset_indices {
+        for index in indices.iter() {
+            let item = assets.get(index).unwrap();
+            let media = match data_type {
+                DataType::Image => Some(&item.image),
+                DataType::Animation => item.animation.as_ref(),
+                DataType::Metadata => None,
+            };
+
+            if let Some(media) = media {
+                let size = media_size(media).await?;
+                if size > limit {
+                    return Err(
+                        UploadError::MediaTooLarge(asset_file_name(media), size, limit).into(),
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns a multipart part with the data of a media file and the buffer space it holds.
+/// Remote files are streamed from the staging source without being stored locally.
+pub async fn media_part(
+    content: &str,
+    name: &str,
+    content_type: &str,
+    limits: &ResourceLimits,
+) -> Result<(Part, BufferPermit)> {
+    let (part, buffer) = if is_remote(content) {
+        let response = HttpClient::new()
+            .get(content)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let part = match response.content_length() {
+            Some(length) => {
+                Part::stream_with_length(Body::wrap_stream(response.bytes_stream()), length)
+            }
+            None => Part::stream(Body::wrap_stream(response.bytes_stream())),
+        };
+        (part, BufferPermit::none())
+    } else if is_archive_entry(content) {
+        let buffer = limits.reserve_buffer(archive_entry_size(content)?).await?;
+        (Part::bytes(read_archive_entry(content)?), buffer)
+    } else {
+        let buffer = limits.reserve_buffer(fs::metadata(content)?.len()).await?;
+        let _file = limits.acquire_file().await?;
+        (Part::bytes(fs::read(content)?), buffer)
+    };
+
+    Ok((
+        part.file_name(name.to_string()).mime_str(content_type)?,
+        buffer,
+    ))
+}
@@ -1,6 +1,6 @@
 use std::{
     cmp,
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -17,9 +17,9 @@ use tokio::task::JoinHandle;
 use crate::{
     cache::Cache,
     config::{ConfigData, CaseConfig, UploadMethod},
-    constants::PARALLEL_LIMIT,
     upload::{
         assets::{AssetPair, DataType},
+        limits::ResourceLimits,
         methods::*,
         UploadError,
     },
@@ -103,6 +103,8 @@ pub trait Uploader: Prepare {
     ///                the console
     /// * `interrupted` - Reference to the shared interruption handler [`flag`](std::sync::atomic::AtomicBool)
     ///                   to receive notifications
+    /// * `concurrency` - Maximum number of concurrent uploads (methods that upload in batches
+    ///                   might not use it)
     ///
     /// # Examples
     ///
@@ -152,6 +154,7 @@ pub trait Uploader: Prepare {
         assets: &mut Vec<AssetInfo>,
         progress: &ProgressBar,
         interrupted: Arc<AtomicBool>,
+        concurrency: usize,
     ) -> Result<Vec<UploadError>>;
 }
 
@@ -184,9 +187,9 @@ pub trait ParallelUploader: Uploader + Send + Sync {
 ///
 #[async_trait]
 impl<T: ParallelUploader> Uploader for T {
-    /// Uploads assets in parallel. It creates `PARALLEL_LIMIT`[PARALLEL_LIMIT] tasks at a time to avoid
-    /// reaching the limit of concurrent files open and it syncs the cache file at every `PARALLEL_LIMIT / 2`
-    /// step.
+    /// Uploads assets in parallel. It keeps up to `concurrency` tasks running at a time to avoid
+    /// reaching the limit of concurrent files open and it syncs the cache file after every completed
+    /// upload, so an interrupted upload resumes without sending the same files again.
     ///
     async fn upload(
         &self,
@@ -196,62 +199,67 @@ impl<T: ParallelUploader> Uploader for T {
         assets: &mut Vec<AssetInfo>,
         progress: &ProgressBar,
         interrupted: Arc<AtomicBool>,
+        concurrency: usize,
     ) -> Result<Vec<UploadError>> {
+        let mut queue: VecDeque<AssetInfo> = assets.drain(..).collect();
         let mut handles = Vec::new();
 
-        for task in assets.drain(0..cmp::min(assets.len(), PARALLEL_LIMIT)) {
-            handles.push(self.upload_asset(task));
+        while handles.len() < cmp::max(concurrency, 1) {
+            match queue.pop_front() {
+                Some(task) => handles.push(self.upload_asset(task)),
+                None => break,
+            }
         }
 
         let mut errors = Vec::new();
 
-        while !interrupted.load(Ordering::SeqCst) && !handles.is_empty() {
-            match select_all(handles).await {
-                (Ok(res), _index, remaining) => {
-                    // independently if the upload was successful or not
-                    // we continue to try the remaining ones
-                    handles = remaining;
-                    if res.is_ok() {
-                        let val = res?;
-                        let link = val.clone().1;
-                        // cache item to update
-                        let item = cache.items.0.get_mut(&val.0).unwrap();
-                        match data_type {
-                            DataType::Image => item.image_link = link,
-                            DataType::Metadata => item.metadata_link = link,
-                            DataType::Animation => item.animation_link = Some(link),
-                        }
-                        // updates the progress bar
-                        progress.inc(1);
-                    } else {
-                        // user will need to retry the upload
-                        errors.push(UploadError::SendDataFailed(format!(
-                            "Upload error: {:?}",
-                            res.err().unwrap()
-                        )));
+        // once interrupted, no new uploads are started but the ones in progress are
+        // still recorded in the cache
+        while !handles.is_empty() {
+            let (result, _index, remaining) = select_all(handles).await;
+            handles = remaining;
+
+            match result {
+                Ok(Ok((asset_id, link))) => {
+                    // cache item to update
+                    let item = cache.items.0.get_mut(&asset_id).unwrap();
+                    match data_type {
+                        DataType::Image => item.image_link = link,
+                        DataType::Metadata => item.metadata_link = link,
+                        DataType::Animation => item.animation_link = Some(link),
                     }
+                    // syncs cache (checkpoint)
+                    cache.sync_file()?;
+                    // updates the progress bar
+                    progress.inc(1);
                 }
-                (Err(err), _index, remaining) => {
+                Ok(Err(err)) => {
+                    // user will need to retry the upload
+                    errors.push(UploadError::SendDataFailed(format!(
+                        "Upload error: {:?}",
+                        err
+                    )));
+                }
+                Err(err) => {
                     errors.push(UploadError::SendDataFailed(format!(
                         "Upload error: {:?}",
                         err
                     )));
-                    // ignoring all errors
-                    handles = remaining;
                 }
             }
-            if !assets.is_empty() {
-                // if we are half way through, let spawn more transactions
-                if (PARALLEL_LIMIT - handles.len()) > (PARALLEL_LIMIT / 2) {
-                    // syncs cache (checkpoint)
-                    cache.sync_file()?;
-                    for task in assets.drain(0..cmp::min(assets.len(), PARALLEL_LIMIT / 2)) {
-                        handles.push(self.upload_asset(task));
-                    }
+
+            if !interrupted.load(Ordering::SeqCst) {
+                // independently if the upload was successful or not
+                // we continue to try the remaining ones
+                if let Some(task) = queue.pop_front() {
+                    handles.push(self.upload_asset(task));
                 }
             }
         }
 
+        // assets not sent (interrupted)
+        assets.extend(queue);
+
         if errors.is_empty() && !assets.is_empty() {
             progress.abandon_with_message(format!("{}", style("Upload aborted ").red().bold()));
             return Err(
@@ -265,22 +273,29 @@ impl<T: ParallelUploader> Uploader for T {
 
 /// Returns a new uploader trait object based on the configuration `uploadMethod`.
 ///
-/// This function acts as a *factory* function for uploader objects.
+/// This function acts as a *factory* function for uploader objects. The `auto_fund`
+/// flag is only used by Bundlr, whose balance is funded before the upload. The `limits`
+/// bound the files open and held in memory by the uploads.
 ///
 pub async fn initialize(
     case_config: &CaseConfig,
     config_data: &ConfigData,
+    auto_fund: bool,
+    limits: &ResourceLimits,
 ) -> Result<Box<dyn Uploader>> {
     Ok(match config_data.upload_method {
-        UploadMethod::AWS => Box::new(AWSMethod::new(config_data).await?) as Box<dyn Uploader>,
+        UploadMethod::AWS => {
+            Box::new(AWSMethod::new(config_data, limits).await?) as Box<dyn Uploader>
+        }
         UploadMethod::Bundlr => {
-            Box::new(BundlrMethod::new(case_config, config_data).await?) as Box<dyn Uploader>
+            Box::new(BundlrMethod::new(case_config, config_data, auto_fund, limits).await?)
+                as Box<dyn Uploader>
         }
         UploadMethod::NftStorage => {
-            Box::new(NftStorageMethod::new(config_data).await?) as Box<dyn Uploader>
+            Box::new(NftStorageMethod::new(config_data, limits).await?) as Box<dyn Uploader>
         }
         UploadMethod::SHDW => {
-            Box::new(SHDWMethod::new(case_config, config_data).await?) as Box<dyn Uploader>
+            Box::new(SHDWMethod::new(case_config, config_data, limits).await?) as Box<dyn Uploader>
         }
     })
 }
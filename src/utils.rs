@@ -10,12 +10,12 @@ use anchor_client::{
 };
 pub use anyhow::{anyhow, Result};
 use console::{style, Style};
-use dialoguer::theme::ColorfulTheme;
-pub use indicatif::{ProgressBar, ProgressStyle};
+use dialoguer::{theme::ColorfulTheme, Confirm};
+pub use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use solana_client::rpc_client::RpcClient;
 use spl_token::state::{Account, Mint};
 
-use crate::config::data::Cluster;
+use crate::{config::data::Cluster, errors::ClusterError, output::is_json_output};
 
 /// Hash for devnet cluster
 pub const DEVNET_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
@@ -76,7 +76,43 @@ pub fn check_spl_token_account(program: &Program, input: &str) -> Result<()> {
     }
 }
 
+/// Check that the token metadata program is deployed on the cluster, which is not the case
+/// of a local validator started without it. `skip_option` is the option of the command
+/// that skips the collection features, suggested in the error.
+pub fn check_token_metadata_program(
+    program: &Program,
+    action: &str,
+    skip_option: Option<&str>,
+) -> Result<()> {
+    let rpc = program.rpc();
+    let deployed = rpc
+        .get_account_with_commitment(&mpl_token_metadata::ID, rpc.commitment())?
+        .value
+        .map(|account| account.executable)
+        .unwrap_or(false);
+
+    if deployed {
+        return Ok(());
+    }
+
+    let hint = skip_option
+        .map(|option| format!(", or use {} to continue without the collection", option))
+        .unwrap_or_default();
+
+    Err(ClusterError::TokenMetadataNotDeployed(
+        mpl_token_metadata::ID.to_string(),
+        rpc.url(),
+        action.to_string(),
+        hint,
+    )
+    .into())
+}
+
 pub fn spinner_with_style() -> ProgressBar {
+    if is_json_output() {
+        return ProgressBar::hidden();
+    }
+
     let pb = ProgressBar::new_spinner();
     pb.enable_steady_tick(120);
     pb.set_style(
@@ -110,6 +146,10 @@ pub fn wait_with_spinner_and_countdown(seconds: u64) {
 }
 
 pub fn progress_bar_with_style(len: u64) -> ProgressBar {
+    if is_json_output() {
+        return ProgressBar::with_draw_target(len, ProgressDrawTarget::hidden());
+    }
+
     let pb = ProgressBar::new(len);
     // forces the progress bar to show immediately
     pb.tick();
@@ -129,6 +169,26 @@ pub fn get_dialoguer_theme() -> ColorfulTheme {
     }
 }
 
+/// Asks to confirm an action, unless the confirmation is skipped (--yes). Nothing can be
+/// asked with --json, so the action then fails without --yes.
+pub fn confirm_action(prompt: &str, skip_confirmation: bool) -> Result<bool> {
+    if skip_confirmation {
+        return Ok(true);
+    }
+
+    if is_json_output() {
+        return Err(anyhow!(
+            "Confirmation required ({}), use --yes with --json",
+            prompt
+        ));
+    }
+
+    Ok(Confirm::with_theme(&get_dialoguer_theme())
+        .with_prompt(prompt)
+        .default(false)
+        .interact()?)
+}
+
 pub fn assert_correct_authority(user_keypair: &Pubkey, update_authority: &Pubkey) -> Result<()> {
     if user_keypair != update_authority {
         return Err(anyhow!(
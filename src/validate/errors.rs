@@ -9,14 +9,20 @@ pub enum ValidateParserError {
     #[error("Invalid assets directory")]
     InvalidAssetsDirectory,
 
-    #[error("Name exceeds 32 chars.")]
-    NameTooLong,
+    #[error("Assets archive '{0}' has no asset files")]
+    EmptyAssetsArchive(String),
 
-    #[error("Symbol exceeds 10 chars.")]
-    SymbolTooLong,
+    #[error("Name exceeds 32 bytes by {0} byte(s).")]
+    NameTooLong(usize),
 
-    #[error("Url exceeds 200 chars.")]
-    UrlTooLong,
+    #[error("Symbol exceeds 10 bytes by {0} byte(s).")]
+    SymbolTooLong(usize),
+
+    #[error("Url exceeds 200 bytes by {0} byte(s).")]
+    UrlTooLong(usize),
+
+    #[error("Hidden settings name '{0}' exceeds 32 bytes by {1} byte(s) once the mint number is appended.")]
+    HiddenSettingsNameTooLong(String, usize),
 
     #[error("Creator address: '{0}' is invalid.")]
     InvalidCreatorAddress(String),
@@ -41,4 +47,61 @@ pub enum ValidateParserError {
 
     #[error("Missing seller fee basis points field")]
     MissingSellerFeeBasisPoints,
+
+    #[error("Compute unit limit '{0}' is invalid: must be between 1 and 1,400,000.")]
+    InvalidComputeUnits(u32),
+
+    #[error("Invalid price schedule: {0}.")]
+    InvalidPriceSchedule(String),
+
+    #[error("Invalid item groups: {0}.")]
+    InvalidItemGroups(String),
+
+    #[error("Invalid category '{0}': must be one of image, video, audio, vr or html.")]
+    InvalidCategory(String),
+
+    #[error("Image '{0}' does not reference the image file of index '{1}'.")]
+    ImageMismatch(String, String),
+
+    #[error("File '{0}' referenced in the metadata does not exist in the assets directory.")]
+    MissingFile(String),
+
+    #[error("{0}")]
+    InvalidMetadataFile(String),
+
+    #[error("{0}")]
+    InvalidAssetFiles(String),
+}
+
+impl ValidateParserError {
+    /// Code identifying the error in the `validate_errors.json` file.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidateParserError::MissingOrEmptyAssetsDirectory => "MISSING_ASSETS_DIRECTORY",
+            ValidateParserError::InvalidAssetsDirectory => "INVALID_ASSETS_DIRECTORY",
+            ValidateParserError::EmptyAssetsArchive(_) => "EMPTY_ASSETS_ARCHIVE",
+            ValidateParserError::NameTooLong(_) => "NAME_TOO_LONG",
+            ValidateParserError::SymbolTooLong(_) => "SYMBOL_TOO_LONG",
+            ValidateParserError::UrlTooLong(_) => "URL_TOO_LONG",
+            ValidateParserError::HiddenSettingsNameTooLong(_, _) => "HIDDEN_SETTINGS_NAME_TOO_LONG",
+            ValidateParserError::InvalidCreatorAddress(_) => "INVALID_CREATOR_ADDRESS",
+            ValidateParserError::InvalidCreatorShare => "INVALID_CREATOR_SHARE",
+            ValidateParserError::InvalidSellerFeeBasisPoints(_) => {
+                "INVALID_SELLER_FEE_BASIS_POINTS"
+            }
+            ValidateParserError::MissingAnimationUrl => "MISSING_ANIMATION_URL",
+            ValidateParserError::MissingExternalUrl => "MISSING_EXTERNAL_URL",
+            ValidateParserError::MissingCollection => "MISSING_COLLECTION",
+            ValidateParserError::MissingCreators => "MISSING_CREATORS",
+            ValidateParserError::MissingSellerFeeBasisPoints => "MISSING_SELLER_FEE_BASIS_POINTS",
+            ValidateParserError::InvalidComputeUnits(_) => "INVALID_COMPUTE_UNITS",
+            ValidateParserError::InvalidPriceSchedule(_) => "INVALID_PRICE_SCHEDULE",
+            ValidateParserError::InvalidItemGroups(_) => "INVALID_ITEM_GROUPS",
+            ValidateParserError::InvalidCategory(_) => "INVALID_CATEGORY",
+            ValidateParserError::ImageMismatch(_, _) => "IMAGE_MISMATCH",
+            ValidateParserError::MissingFile(_) => "MISSING_FILE",
+            ValidateParserError::InvalidMetadataFile(_) => "INVALID_METADATA_FILE",
+            ValidateParserError::InvalidAssetFiles(_) => "INVALID_ASSET_FILES",
+        }
+    }
 }
@@ -0,0 +1,177 @@
+use std::fs;
+
+use chrono::Utc;
+use console::style;
+use dialoguer::Confirm;
+
+use crate::{
+    canonical_json::to_canonical_json,
+    common::*,
+    upload::{is_remote, media_content_type},
+    utils::get_dialoguer_theme,
+    validate::{MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH},
+};
+
+/// Applies safe fixes to the metadata files, rewriting them in place. The original
+/// files are copied to a backup directory inside the assets directory before any file
+/// is modified; returns the number of files fixed.
+pub fn fix_metadata_files(assets_dir: &Path, paths: &[PathBuf]) -> Result<usize> {
+    let mut fixed = Vec::new();
+
+    for path in paths {
+        let content = fs::read_to_string(path)?;
+        // files that cannot be parsed are reported by the validation
+        let mut metadata: Value = match serde_json::from_str(&content) {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+
+        let changes = fix_metadata(path, &mut metadata)?;
+
+        if !changes.is_empty() {
+            fixed.push((path, metadata, changes));
+        }
+    }
+
+    if fixed.is_empty() {
+        return Ok(0);
+    }
+
+    let backup_dir = assets_dir
+        .join(FIX_BACKUP_DIR)
+        .join(Utc::now().format("%Y%m%d%H%M%S").to_string());
+    fs::create_dir_all(&backup_dir)?;
+
+    for (path, _, _) in &fixed {
+        if let Some(file_name) = path.file_name() {
+            fs::copy(path, backup_dir.join(file_name))?;
+        }
+    }
+
+    for (path, metadata, changes) in &fixed {
+        fs::write(path, to_canonical_json(metadata)?)?;
+
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        info!("Fixed {}: {}", path.display(), changes.join(", "));
+        case_println!("{} {}: {}", style("✔").green(), name, changes.join(", "));
+    }
+
+    case_println!(
+        "\nFixed {} metadata file(s), originals saved to '{}'",
+        fixed.len(),
+        backup_dir.display()
+    );
+
+    Ok(fixed.len())
+}
+
+/// Fixes a metadata value, returning the description of the changes made.
+fn fix_metadata(path: &Path, metadata: &mut Value) -> Result<Vec<String>> {
+    let mut changes = Vec::new();
+    let object = match metadata.as_object_mut() {
+        Some(object) => object,
+        None => return Ok(changes),
+    };
+
+    // "500", 500.0 -> 500
+    if let Some(value) = object.get_mut("seller_fee_basis_points") {
+        let normalized = match value {
+            Value::String(string) => string.trim().parse::<f64>().ok(),
+            Value::Number(number) if !number.is_u64() => number.as_f64(),
+            _ => None,
+        };
+
+        if let Some(points) = normalized {
+            if points >= 0.0 && points.fract() == 0.0 && points <= u16::MAX as f64 {
+                *value = json!(points as u16);
+                changes.push("normalized seller_fee_basis_points".to_string());
+            }
+        }
+    }
+
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+
+    for (key, max_length) in [("name", MAX_NAME_LENGTH), ("symbol", MAX_SYMBOL_LENGTH)] {
+        if let Some(Value::String(value)) = object.get_mut(key) {
+            if value.len() > max_length {
+                let trimmed = truncate(value, max_length);
+                let prompt = format!("{}: trim {} '{}' to '{}'?", file_name, key, value, trimmed);
+
+                // values are only trimmed when confirmed, so they are kept under --json
+                if !is_json_output()
+                    && Confirm::with_theme(&get_dialoguer_theme())
+                        .with_prompt(prompt)
+                        .default(false)
+                        .interact()?
+                {
+                    *value = trimmed;
+                    changes.push(format!("trimmed {}", key));
+                }
+            }
+        }
+    }
+
+    if let Some(Value::Array(attributes)) = object.get_mut("attributes") {
+        let trait_type = |attribute: &Value| {
+            attribute
+                .get("trait_type")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+
+        if attributes
+            .windows(2)
+            .any(|pair| trait_type(&pair[0]) > trait_type(&pair[1]))
+        {
+            attributes.sort_by_key(trait_type);
+            changes.push("sorted attributes".to_string());
+        }
+    }
+
+    let files = media_files(object);
+    let properties = object.entry("properties").or_insert_with(|| json!({}));
+
+    if let Some(properties) = properties.as_object_mut() {
+        let missing = match properties.get("files") {
+            None | Some(Value::Null) => true,
+            Some(Value::Array(entries)) => entries.is_empty(),
+            _ => false,
+        };
+
+        if missing && !files.is_empty() {
+            properties.insert("files".to_string(), Value::Array(files));
+            changes.push("added properties.files".to_string());
+        }
+    }
+
+    Ok(changes)
+}
+
+/// Returns the `properties.files` entries of the image and animation of the metadata.
+fn media_files(object: &serde_json::Map<String, Value>) -> Vec<Value> {
+    ["image", "animation_url"]
+        .iter()
+        .filter_map(|key| object.get(*key).and_then(Value::as_str))
+        .filter(|uri| !uri.is_empty())
+        .filter_map(|uri| {
+            let name = if is_remote(uri) {
+                uri.split('?').next().unwrap_or(uri)
+            } else {
+                uri
+            };
+            // entries without a known content type are left for the user to add
+            media_content_type(name)
+                .ok()
+                .map(|file_type| json!({ "uri": uri, "type": file_type }))
+        })
+        .collect()
+}
+
+/// Truncates a string to at most `max_length` bytes on a char boundary.
+fn truncate(value: &str, max_length: usize) -> String {
+    let mut end = max_length;
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    value[..end].trim_end().to_string()
+}
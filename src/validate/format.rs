@@ -21,30 +21,37 @@ pub struct Metadata {
 }
 
 impl Metadata {
-    pub fn validate(&self) -> Result<(), ValidateParserError> {
-        parser::check_name(&self.name)?;
-        parser::check_symbol(&self.symbol)?;
-        parser::check_url(&self.image)?;
+    /// Validates the metadata values, returning all errors found.
+    pub fn validate(&self) -> Vec<ValidateParserError> {
+        let mut checks = vec![
+            parser::check_name(&self.name),
+            parser::check_symbol(&self.symbol),
+            parser::check_url(&self.image),
+        ];
 
         // If users are using the old format, we do validation on those values.
         if let Some(sfbp) = &self.seller_fee_basis_points {
-            parser::check_seller_fee_basis_points(*sfbp)?;
+            checks.push(parser::check_seller_fee_basis_points(*sfbp));
         }
 
         if let Some(creators) = &self.properties.creators {
-            parser::check_creators_shares(creators)?;
-            parser::check_creators_addresses(creators)?;
+            checks.push(parser::check_creators_shares(creators));
+            checks.push(parser::check_creators_addresses(creators));
+        }
+
+        if let Some(category) = &self.properties.category {
+            checks.push(parser::check_category(category));
         }
 
         if let Some(animation_url) = &self.animation_url {
-            parser::check_url(animation_url)?;
+            checks.push(parser::check_url(animation_url));
         }
 
         if let Some(external_url) = &self.external_url {
-            parser::check_url(external_url)?;
+            checks.push(parser::check_url(external_url));
         }
 
-        Ok(())
+        checks.into_iter().filter_map(Result::err).collect()
     }
 }
 
@@ -52,6 +59,8 @@ impl Metadata {
 pub struct Property {
     pub files: Vec<FileAttr>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub creators: Option<Vec<Creator>>,
 }
 
@@ -1,9 +1,13 @@
 pub mod errors;
+pub mod fix;
 pub mod format;
 pub mod parser;
 pub mod process;
+pub mod watch;
 
 pub use errors::*;
+pub use fix::*;
 pub use format::*;
 pub use parser::*;
 pub use process::*;
+pub use watch::*;
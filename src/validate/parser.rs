@@ -3,25 +3,62 @@ use std::str::FromStr;
 use anchor_lang::prelude::Pubkey;
 pub use mpl_token_metadata::state::{MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH};
 
-use crate::validate::{errors::ValidateParserError, Creator};
+use crate::{
+    config::{ItemGroup, PriceSchedule},
+    constants::MAX_COMPUTE_UNITS,
+    validate::{errors::ValidateParserError, Creator},
+};
+
+/// Categories of the metadata standard (`properties.category`).
+pub const METADATA_CATEGORIES: &[&str] = &["image", "video", "audio", "vr", "html"];
+
+// Lengths are checked in bytes (not chars) since this is how the on-chain
+// limits are enforced for UTF-8 strings.
 
 pub fn check_name(name: &str) -> Result<(), ValidateParserError> {
     if name.len() > MAX_NAME_LENGTH {
-        return Err(ValidateParserError::NameTooLong);
+        return Err(ValidateParserError::NameTooLong(
+            name.len() - MAX_NAME_LENGTH,
+        ));
     }
     Ok(())
 }
 
 pub fn check_symbol(symbol: &str) -> Result<(), ValidateParserError> {
     if symbol.len() > MAX_SYMBOL_LENGTH {
-        return Err(ValidateParserError::SymbolTooLong);
+        return Err(ValidateParserError::SymbolTooLong(
+            symbol.len() - MAX_SYMBOL_LENGTH,
+        ));
     }
     Ok(())
 }
 
 pub fn check_url(url: &str) -> Result<(), ValidateParserError> {
     if url.len() > MAX_URI_LENGTH {
-        return Err(ValidateParserError::UrlTooLong);
+        return Err(ValidateParserError::UrlTooLong(url.len() - MAX_URI_LENGTH));
+    }
+    Ok(())
+}
+
+/// Return the name assigned to a mint from a tars with hidden settings: the program
+/// appends the mint number (index + 1) to the hidden settings name.
+pub fn hidden_settings_name(name: &str, index: u64) -> String {
+    format!("{}#{}", name, index + 1)
+}
+
+/// Check that the hidden settings name fits the on-chain limit for every mint number;
+/// the longest name is the one of the last item.
+pub fn check_hidden_settings_name(
+    name: &str,
+    items_available: u64,
+) -> Result<(), ValidateParserError> {
+    let expanded = hidden_settings_name(name, items_available.saturating_sub(1));
+
+    if expanded.len() > MAX_NAME_LENGTH {
+        return Err(ValidateParserError::HiddenSettingsNameTooLong(
+            name.to_string(),
+            expanded.len() - MAX_NAME_LENGTH,
+        ));
     }
     Ok(())
 }
@@ -37,6 +74,71 @@ pub fn check_seller_fee_basis_points(
     Ok(())
 }
 
+pub fn check_compute_units(compute_units: u32) -> Result<(), ValidateParserError> {
+    if compute_units == 0 || compute_units > MAX_COMPUTE_UNITS {
+        return Err(ValidateParserError::InvalidComputeUnits(compute_units));
+    }
+    Ok(())
+}
+
+pub fn check_price_schedule(
+    schedule: &PriceSchedule,
+    go_live_date: &Option<String>,
+) -> Result<(), ValidateParserError> {
+    let problem = if go_live_date.is_none() {
+        Some("the schedule starts on the go live date, which is not set")
+    } else if schedule.floor_price < 0.0 || schedule.floor_price > schedule.start_price {
+        Some("the floor price must be between 0 and the start price")
+    } else if schedule.step <= 0.0 {
+        Some("the step must be greater than 0")
+    } else if schedule.interval == 0 {
+        Some("the interval must be greater than 0")
+    } else {
+        None
+    };
+
+    match problem {
+        Some(problem) => Err(ValidateParserError::InvalidPriceSchedule(
+            problem.to_string(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Checks that the item groups are ranges of the `number` items that do not overlap.
+pub fn check_item_groups(groups: &[ItemGroup], number: u64) -> Result<(), ValidateParserError> {
+    let mut sorted: Vec<&ItemGroup> = groups.iter().collect();
+    sorted.sort_by_key(|group| group.start);
+
+    for (i, group) in sorted.iter().enumerate() {
+        let problem = if group.start > group.end {
+            Some(format!("group '{}' ends before it starts", group.name))
+        } else if group.end >= number {
+            Some(format!(
+                "group '{}' ends after the last item (index {})",
+                group.name,
+                number.saturating_sub(1)
+            ))
+        } else if sorted[..i].iter().any(|other| other.name == group.name) {
+            Some(format!(
+                "group name '{}' is used more than once",
+                group.name
+            ))
+        } else {
+            sorted
+                .get(i + 1)
+                .filter(|next| next.start <= group.end)
+                .map(|next| format!("groups '{}' and '{}' overlap", group.name, next.name))
+        };
+
+        if let Some(problem) = problem {
+            return Err(ValidateParserError::InvalidItemGroups(problem));
+        }
+    }
+
+    Ok(())
+}
+
 pub fn check_creators_shares(creators: &Vec<Creator>) -> Result<(), ValidateParserError> {
     let mut shares = 0;
     for creator in creators {
@@ -49,6 +151,13 @@ pub fn check_creators_shares(creators: &Vec<Creator>) -> Result<(), ValidatePars
     Ok(())
 }
 
+pub fn check_category(category: &str) -> Result<(), ValidateParserError> {
+    if !METADATA_CATEGORIES.contains(&category) {
+        return Err(ValidateParserError::InvalidCategory(category.to_string()));
+    }
+    Ok(())
+}
+
 pub fn check_creators_addresses(creators: &Vec<Creator>) -> Result<(), ValidateParserError> {
     for creator in creators {
         Pubkey::from_str(&creator.address)
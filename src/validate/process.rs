@@ -1,4 +1,6 @@
 use std::{
+    collections::HashSet,
+    ffi::OsStr,
     fs::File,
     path::Path,
     sync::{Arc, Mutex},
@@ -10,17 +12,36 @@ use dialoguer::{theme::ColorfulTheme, Confirm};
 use glob::glob;
 use rayon::prelude::*;
 
-use crate::{common::*, utils::*, validate::*};
+use crate::{
+    common::*,
+    config::get_config_data,
+    upload::{
+        archive_asset_pairs, asset_file_name, check_asset_files, find_duplicate_assets,
+        get_asset_pairs, is_archive, is_remote, list_files, stage_archive, warn_duplicate_assets,
+        ARCHIVE_ENTRY_SEPARATOR, IMAGE_EXTENSIONS,
+    },
+    utils::*,
+    validate::*,
+};
+
+/// Checks whether a local file referenced by the metadata is one of the assets.
+type FileCheck<'a> = Box<dyn Fn(&str) -> bool + Sync + 'a>;
 
 pub struct ValidateArgs {
     pub assets_dir: String,
+    pub config: Option<String>,
+    pub global: GlobalOptions,
     pub strict: bool,
     pub skip_collection_prompt: bool,
+    pub watch: bool,
+    pub fix: bool,
+    /// Skips the check for items with the same image or metadata.
+    pub allow_duplicates: bool,
 }
 
 pub fn process_validate(args: ValidateArgs) -> Result<()> {
     // loading assets
-    println!(
+    case_println!(
         "{} {}Loading assets",
         style("[1/1]").bold().dim(),
         ASSETS_EMOJI
@@ -28,22 +49,52 @@ pub fn process_validate(args: ValidateArgs) -> Result<()> {
 
     let assets_dir = Path::new(&args.assets_dir);
 
-    // missing or empty assets directory
-    if !assets_dir.exists() || assets_dir.read_dir()?.next().is_none() {
-        info!("Assets directory is missing or empty.");
-        return Err(ValidateParserError::MissingOrEmptyAssetsDirectory.into());
+    // in watch mode the directory is usually still being filled by the generator
+    if args.watch {
+        if !assets_dir.is_dir() {
+            return Err(ValidateParserError::MissingOrEmptyAssetsDirectory.into());
+        }
+        return watch_assets(assets_dir);
     }
 
+    // the files of an archive are read without extracting it, only the metadata files
+    // are staged
+    let archive_files = if is_archive(&args.assets_dir) {
+        if args.fix {
+            return Err(anyhow!(
+                "--fix can't update the metadata files of an archive, fix the files before \
+                 creating the archive"
+            ));
+        }
+
+        let files = stage_archive(&args.assets_dir, Path::new(DEFAULT_STAGING_DIR))?;
+
+        if files.is_empty() {
+            return Err(ValidateParserError::EmptyAssetsArchive(args.assets_dir.clone()).into());
+        }
+        Some(files)
+    } else {
+        // missing or empty assets directory
+        if !assets_dir.exists() || assets_dir.read_dir()?.next().is_none() {
+            info!("Assets directory is missing or empty.");
+            return Err(ValidateParserError::MissingOrEmptyAssetsDirectory.into());
+        }
+        None
+    };
+
     if !args.skip_collection_prompt {
-        let collection_path = assets_dir.join("collection.json");
-        if !collection_path.is_file() {
+        let has_collection = match &archive_files {
+            Some(files) => files.iter().any(|file| file.name == "collection.json"),
+            None => assets_dir.join("collection.json").is_file(),
+        };
+        if !has_collection {
             let warning = format!(
                 "+----------------------------------------------+\n\
                  | {} MISSING COLLECTION FILES IN ASSETS FOLDER |\n\
                  +----------------------------------------------+",
                 WARNING_EMOJI
             );
-            println!(
+            case_println!(
                 "\n{}\n{}\n",
                 style(warning).bold().yellow(),
                 style(
@@ -63,82 +114,148 @@ pub fn process_validate(args: ValidateArgs) -> Result<()> {
             if !Confirm::with_theme(&theme).with_prompt("Do you want to continue without automatically setting the tars collection?").interact()? {
                 return Err(anyhow!("Operation aborted"));
             }
-            println!();
+            case_println!();
         }
     }
 
+    let assets_path = assets_dir.to_path_buf();
+    let config_path = args.config.as_ref().map(PathBuf::from);
     let errors = Arc::new(Mutex::new(Vec::new()));
 
-    let path = assets_dir.join("*.json");
-    let pattern = path
-        .to_str()
-        .ok_or(ValidateParserError::InvalidAssetsDirectory)?;
+    // gaps, duplicates and unpaired files are reported together with the metadata errors
+    let file_names: Vec<String> = match &archive_files {
+        Some(files) => files.iter().map(|file| file.name.clone()).collect(),
+        None => list_files(&args.assets_dir, true)?
+            .iter()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect(),
+    };
+
+    for problem in check_asset_files(&file_names) {
+        error!("{}: {}", assets_path.display(), problem);
+        errors.lock().unwrap().push(ValidateError::new(
+            &assets_path,
+            &ValidateParserError::InvalidAssetFiles(problem),
+        ));
+    }
+
+    // metadata files to validate (staged files of an archive are reported by their entry)
+    // and the check of the files they reference
+    let (paths, exists): (Vec<(PathBuf, PathBuf)>, FileCheck) = match &archive_files {
+        Some(files) => {
+            let paths = files
+                .iter()
+                .filter(|file| file.name.to_lowercase().ends_with(".json"))
+                .map(|file| {
+                    let entry = format!(
+                        "{}{}{}",
+                        args.assets_dir, ARCHIVE_ENTRY_SEPARATOR, file.entry
+                    );
+                    (PathBuf::from(&file.content), PathBuf::from(entry))
+                })
+                .collect();
+            let names: HashSet<String> = file_names.iter().cloned().collect();
+
+            (
+                paths,
+                Box::new(move |file: &str| names.contains(&asset_file_name(file))),
+            )
+        }
+        None => {
+            let path = assets_dir.join("*.json");
+            let pattern = path
+                .to_str()
+                .ok_or(ValidateParserError::InvalidAssetsDirectory)?;
+
+            // Unwrapping here because we know the pattern is valid and GlobErrors should
+            // be rare or impossible to produce.
+            let paths: Vec<PathBuf> = glob(pattern)
+                .unwrap()
+                .into_iter()
+                .map(Result::unwrap)
+                .collect();
+
+            if args.fix {
+                fix_metadata_files(assets_dir, &paths)?;
+            }
 
-    // Unwrapping here because we know the pattern is valid and GlobErrors should
-    // be rare or impossible to produce.
-    let paths: Vec<PathBuf> = glob(pattern)
-        .unwrap()
-        .into_iter()
-        .map(Result::unwrap)
-        .collect();
+            (
+                paths.into_iter().map(|path| (path.clone(), path)).collect(),
+                Box::new(move |file: &str| assets_dir.join(file).is_file()),
+            )
+        }
+    };
 
     let pb = spinner_with_style();
     pb.enable_steady_tick(120);
     pb.set_message(format!("Validating {} metadata file(s)...", paths.len()));
 
-    paths.par_iter().for_each(|path| {
-        let errors = errors.clone();
-        let f = match File::open(path) {
-            Ok(f) => f,
-            Err(error) => {
-                error!("{}: {}", path.display(), error);
-                errors.lock().unwrap().push(ValidateError {
-                    path,
-                    error: error.to_string(),
-                });
-                return;
+    paths.par_iter().for_each(|(path, display_path)| {
+        if let Err(file_errors) = validate_metadata(path, exists.as_ref()) {
+            let mut errors = errors.lock().unwrap();
+
+            for error in file_errors {
+                error!("{}: {}", display_path.display(), error);
+                errors.push(ValidateError::new(display_path, &error));
             }
-        };
+        }
+    });
+
+    pb.finish();
 
-        let metadata = match serde_json::from_reader::<File, Metadata>(f) {
-            Ok(metadata) => metadata,
-            Err(error) => {
+    // hidden settings are not part of the metadata files, but the name and uri
+    // also need to fit the on-chain limits
+    if let (Some(config), Some(path)) = (&args.config, &config_path) {
+        let config_data = get_config_data(config, &args.global.address_book)?;
+
+        if let Some(hidden_settings) = &config_data.hidden_settings {
+            let checks = [
+                check_hidden_settings_name(&hidden_settings.name, config_data.number),
+                check_url(&hidden_settings.uri),
+            ];
+
+            for error in checks.into_iter().filter_map(Result::err) {
                 error!("{}: {}", path.display(), error);
-                errors.lock().unwrap().push(ValidateError {
-                    path,
-                    error: error.to_string(),
-                });
-                return;
+                errors
+                    .lock()
+                    .unwrap()
+                    .push(ValidateError::new(path, &error));
             }
-        };
+        }
+
+        if let Some(compute_units) = &config_data.compute_units {
+            let checks = [compute_units.mint, compute_units.deploy]
+                .into_iter()
+                .flatten()
+                .map(check_compute_units);
 
-        // To be replaced with the strict validator once JSON standard is finalized.
-        if args.strict {
-            match metadata.validate() {
-                Ok(()) => {}
-                Err(e) => {
-                    error!("{}: {}", path.display(), e);
-                    errors.lock().unwrap().push(ValidateError {
-                        path,
-                        error: e.to_string(),
-                    });
-                }
+            for error in checks.filter_map(Result::err) {
+                error!("{}: {}", path.display(), error);
+                errors
+                    .lock()
+                    .unwrap()
+                    .push(ValidateError::new(path, &error));
             }
-        } else {
-            match metadata.validate() {
-                Ok(()) => {}
-                Err(e) => {
-                    error!("{}: {}", path.display(), e);
-                    errors.lock().unwrap().push(ValidateError {
-                        path,
-                        error: e.to_string(),
-                    });
-                }
+        }
+
+        if let Some(schedule) = &config_data.price_schedule {
+            if let Err(error) = check_price_schedule(schedule, &config_data.go_live_date) {
+                error!("{}: {}", path.display(), error);
+                errors
+                    .lock()
+                    .unwrap()
+                    .push(ValidateError::new(path, &error));
             }
         }
-    });
 
-    pb.finish();
+        if let Err(error) = check_item_groups(&config_data.item_groups, config_data.number) {
+            error!("{}: {}", path.display(), error);
+            errors
+                .lock()
+                .unwrap()
+                .push(ValidateError::new(path, &error));
+        }
+    }
 
     if !errors.lock().unwrap().is_empty() {
         log_errors("validate_errors", errors)?;
@@ -147,9 +264,88 @@ pub fn process_validate(args: ValidateArgs) -> Result<()> {
         ));
     }
 
+    // duplicates are only a warning, since they can be intentional
+    if !args.allow_duplicates {
+        let asset_pairs = match &archive_files {
+            Some(files) => archive_asset_pairs(files)?,
+            None => get_asset_pairs(&args.assets_dir)?,
+        };
+        warn_duplicate_assets(&find_duplicate_assets(&asset_pairs)?);
+    }
+
     let message = "Validation complete, your metadata file(s) look good.";
     info!("{message}");
-    println!("\n{message}");
+    case_println!("\n{message}");
 
     Ok(())
 }
+
+/// Validates a single metadata file, returning all errors found if it is invalid.
+pub fn validate_metadata_file(path: &Path) -> Result<(), Vec<ValidateParserError>> {
+    let assets_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    validate_metadata(path, &|file: &str| assets_dir.join(file).is_file())
+}
+
+/// Validates a metadata file, where `exists` checks whether a local file referenced by the
+/// metadata is one of the assets.
+pub fn validate_metadata(
+    path: &Path,
+    exists: &(dyn Fn(&str) -> bool + Sync),
+) -> Result<(), Vec<ValidateParserError>> {
+    let invalid_file = |error: String| vec![ValidateParserError::InvalidMetadataFile(error)];
+
+    let f = File::open(path).map_err(|error| invalid_file(error.to_string()))?;
+    let metadata = serde_json::from_reader::<File, Metadata>(f)
+        .map_err(|error| invalid_file(error.to_string()))?;
+
+    // To be replaced with the strict validator once JSON standard is finalized.
+    let mut errors = metadata.validate();
+    errors.extend(check_referenced_files(path, &metadata, exists));
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Checks that the image of the metadata is the image file of the same index and that
+/// the local files listed in `properties.files` exist. URLs are not checked, since they
+/// reference files that are already hosted.
+fn check_referenced_files(
+    path: &Path,
+    metadata: &Metadata,
+    exists: &(dyn Fn(&str) -> bool + Sync),
+) -> Vec<ValidateParserError> {
+    let mut errors = Vec::new();
+    let index = path.file_stem().and_then(OsStr::to_str).unwrap_or_default();
+
+    if !is_remote(&metadata.image) {
+        let image = Path::new(&metadata.image);
+        let is_index_image = image.file_stem().and_then(OsStr::to_str) == Some(index)
+            && image
+                .extension()
+                .and_then(OsStr::to_str)
+                .map_or(false, |extension| {
+                    IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+                });
+
+        if !is_index_image {
+            errors.push(ValidateParserError::ImageMismatch(
+                metadata.image.clone(),
+                index.to_string(),
+            ));
+        } else if !exists(&metadata.image) {
+            errors.push(ValidateParserError::MissingFile(metadata.image.clone()));
+        }
+    }
+
+    for file in &metadata.properties.files {
+        // the image was checked above
+        if file.uri != metadata.image && !is_remote(&file.uri) && !exists(&file.uri) {
+            errors.push(ValidateParserError::MissingFile(file.uri.clone()));
+        }
+    }
+
+    errors
+}
@@ -0,0 +1,113 @@
+use std::{
+    collections::HashMap,
+    sync::mpsc::{channel, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+use console::style;
+use glob::glob;
+use notify::{recommended_watcher, Event, EventKind, RecursiveMode, Watcher};
+
+use crate::{common::*, validate::*};
+
+/// Time without new events after which a file is considered completely written.
+const SETTLE_TIME: Duration = Duration::from_millis(500);
+
+/// Validates the metadata files of the assets directory and then keeps watching it,
+/// validating files as they are created or modified until interrupted.
+pub fn watch_assets(assets_dir: &Path) -> Result<()> {
+    let (sender, receiver) = channel::<notify::Result<Event>>();
+    let mut watcher = recommended_watcher(sender)?;
+    watcher.watch(assets_dir, RecursiveMode::NonRecursive)?;
+
+    let path = assets_dir.join("*.json");
+    let pattern = path
+        .to_str()
+        .ok_or(ValidateParserError::InvalidAssetsDirectory)?;
+
+    let mut invalid = 0;
+    for path in glob(pattern)?.flatten() {
+        if !report(&path, false) {
+            invalid += 1;
+        }
+    }
+
+    case_println!(
+        "\n{} {}Watching '{}' for changes (Ctrl-C to stop), {} invalid file(s) so far\n",
+        style("[watch]").bold().dim(),
+        LOOKING_GLASS_EMOJI,
+        assets_dir.display(),
+        invalid
+    );
+
+    // files are validated once no events were received for them during the settle
+    // time, so partially written files are not reported
+    let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+
+    loop {
+        match receiver.recv_timeout(SETTLE_TIME) {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    for path in event.paths {
+                        if is_metadata_file(&path) {
+                            pending.insert(path, Instant::now());
+                        }
+                    }
+                }
+            }
+            Ok(Err(err)) => warn!("Watch error: {}", err),
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        let settled: Vec<PathBuf> = pending
+            .iter()
+            .filter(|(_, time)| time.elapsed() >= SETTLE_TIME)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        for path in settled {
+            pending.remove(&path);
+            // removed or renamed before it settled
+            if path.is_file() {
+                report(&path, true);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Validates the file and prints the result (valid files only if `show_valid` is
+/// set); returns whether the file is valid.
+fn report(path: &Path, show_valid: bool) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+
+    match validate_metadata_file(path) {
+        Ok(()) => {
+            if show_valid {
+                case_println!("{} {}", style("✔").green(), name);
+            }
+            true
+        }
+        Err(errors) => {
+            let error = errors
+                .iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<String>>()
+                .join(" ");
+            error!("{}: {}", path.display(), error);
+            case_println!("{} {}: {}", style("✘").red(), name, style(error).red());
+            false
+        }
+    }
+}
+
+fn is_metadata_file(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| extension.eq_ignore_ascii_case("json"))
+        .unwrap_or(false)
+}
@@ -0,0 +1,142 @@
+use std::{
+    fmt::{self, Display},
+    time::Duration,
+};
+
+use console::style;
+use futures::{stream, StreamExt};
+use reqwest::{header::CONTENT_TYPE, StatusCode};
+
+use crate::{
+    common::*,
+    ipfs::{is_ipfs, IpfsGateways},
+    utils::progress_bar_with_style,
+};
+
+/// Time to wait for a response when checking a link.
+const LINK_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Copy, Debug)]
+enum LinkType {
+    Metadata,
+    Image,
+}
+
+impl Display for LinkType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinkType::Metadata => write!(f, "metadata"),
+            LinkType::Image => write!(f, "image"),
+        }
+    }
+}
+
+/// Requests the metadata and image links of every item, checking that they respond with
+/// a success status and the expected content type, and that the metadata is valid JSON.
+/// Returns the number of links checked.
+pub async fn check_links(cache: &Cache, gateways: &IpfsGateways) -> Result<usize> {
+    let mut links: Vec<(&String, LinkType, &String)> = Vec::new();
+
+    for (index, item) in cache.items.iter() {
+        if !item.metadata_link.is_empty() {
+            links.push((index, LinkType::Metadata, &item.metadata_link));
+        }
+        if !item.image_link.is_empty() {
+            links.push((index, LinkType::Image, &item.image_link));
+        }
+    }
+
+    case_println!("\nChecking {} link(s):", links.len());
+    let pb = progress_bar_with_style(links.len() as u64);
+
+    let client = &HttpClient::new();
+    let progress = &pb;
+
+    let errors: Vec<String> = stream::iter(links.iter())
+        .map(|(index, link_type, link)| async move {
+            let url = if is_ipfs(link) {
+                gateways.gateway_url(link)
+            } else {
+                Some(link.to_string())
+            };
+
+            let error = match url {
+                Some(url) => check_link(client, &url, *link_type).await.err(),
+                None => Some(anyhow!("invalid IPFS link")),
+            };
+            progress.inc(1);
+
+            error.map(|error| format!("Item {} {} ({}): {}", index, link_type, link, error))
+        })
+        .buffer_unordered(PARALLEL_LIMIT)
+        .filter_map(|error| async move { error })
+        .collect()
+        .await;
+
+    if !errors.is_empty() {
+        pb.abandon_with_message(format!("{}", style("Link check failed ").red().bold()));
+
+        let mut message = format!("{} link(s) are not available:", errors.len());
+        for error in errors {
+            message.push_str("\n=> ");
+            message.push_str(&error);
+        }
+
+        return Err(anyhow!(message));
+    }
+
+    pb.finish_with_message(format!("{}", style("Link check successful").green().bold()));
+
+    Ok(links.len())
+}
+
+async fn check_link(client: &HttpClient, url: &str, link_type: LinkType) -> Result<()> {
+    let response = match link_type {
+        // the body is needed to check that the metadata parses
+        LinkType::Metadata => client.get(url).timeout(LINK_TIMEOUT).send().await?,
+        LinkType::Image => {
+            let response = client.head(url).timeout(LINK_TIMEOUT).send().await?;
+
+            // not every server supports HEAD requests
+            if matches!(
+                response.status(),
+                StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED
+            ) {
+                client.get(url).timeout(LINK_TIMEOUT).send().await?
+            } else {
+                response
+            }
+        }
+    };
+
+    if response.status() != StatusCode::OK {
+        return Err(anyhow!("status {}", response.status()));
+    }
+
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match link_type {
+        LinkType::Metadata => {
+            // some gateways serve JSON files as plain text
+            if !(content_type.contains("json") || content_type.starts_with("text/plain")) {
+                return Err(anyhow!("wrong content type '{}'", content_type));
+            }
+
+            let content = response.bytes().await?;
+            serde_json::from_slice::<Value>(&content)
+                .map_err(|err| anyhow!("metadata is not valid JSON: {}", err))?;
+        }
+        LinkType::Image => {
+            if !content_type.starts_with("image/") {
+                return Err(anyhow!("wrong content type '{}'", content_type));
+            }
+        }
+    }
+
+    Ok(())
+}
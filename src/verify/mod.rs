@@ -1,5 +1,13 @@
 pub mod errors;
+pub mod links;
 pub mod process;
+pub mod repair;
+pub mod resolve;
+pub mod standards;
 
 pub use errors::*;
+pub use links::*;
 pub use process::*;
+pub use repair::*;
+pub use resolve::*;
+pub use standards::*;
@@ -2,7 +2,10 @@ use std::{thread, time::Duration};
 
 use anchor_lang::AccountDeserialize;
 use console::style;
+use futures::{stream, StreamExt};
+use serde::Serialize;
 use tars::Tars;
+use tokio::task::spawn_blocking;
 
 use crate::{
     cache::*,
@@ -10,15 +13,31 @@ use crate::{
     common::*,
     config::Cluster,
     constants::{TARS_EMOJI, PAPER_EMOJI},
+    ipfs::{is_ipfs, GatewayHealth, IpfsGateways},
+    metadata_cache::MetadataCache,
     pdas::get_collection_pda,
     utils::*,
-    verify::VerifyError,
+    verify::{
+        ask_resolution, check_links, check_metadata_standards, configured_marketplaces, keep_chain,
+        repair_config_lines, Conflict, Resolution, VerifyError,
+    },
 };
 
 pub struct VerifyArgs {
+    pub config: String,
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
     pub cache: String,
+    pub check_metadata: bool,
+    /// Request the metadata and image links of every item.
+    pub check_links: bool,
+    /// Gateways used to download `ipfs://` links.
+    pub ipfs_gateways: Vec<String>,
+    /// Rewrite the config lines that do not match the cache.
+    pub repair: bool,
+    /// Check the items against the requirements of the marketplaces.
+    pub metadata_standards: bool,
 }
 
 #[derive(Debug)]
@@ -27,15 +46,81 @@ pub struct OnChainItem {
     pub uri: String,
 }
 
-pub fn process_verify(args: VerifyArgs) -> Result<()> {
-    let case_config = case_setup(args.keypair, args.rpc_url)?;
+/// Result of the verify command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyOutcome {
+    pub tars: String,
+    pub cluster: String,
+    pub items_verified: u64,
+    /// Number of config lines rewritten (--repair).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items_repaired: Option<usize>,
+    /// Number of cache items updated with the on-chain config lines.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items_resolved: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata_verified: Option<usize>,
+    /// Number of links checked (--check-links).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub links_verified: Option<usize>,
+    /// Number of items checked against the marketplace requirements (--metadata-standards).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub standards_verified: Option<usize>,
+    /// Health of the IPFS gateways, when `ipfs://` links were checked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipfs_gateways: Option<Vec<GatewayHealth>>,
+    pub cache: CacheStats,
+}
+
+pub async fn process_verify(args: VerifyArgs) -> Result<VerifyOutcome> {
+    // the RPC requests and the prompts are blocking, so they run outside of the runtime
+    let (args, cache, mut outcome) = spawn_blocking(move || {
+        verify_on_chain(&args).map(|(cache, outcome)| (args, cache, outcome))
+    })
+    .await??;
+
+    if args.check_metadata {
+        let gateways = IpfsGateways::from_options(&args.ipfs_gateways)?;
+        let (verified, health) = check_metadata(&cache, gateways).await?;
+        outcome.metadata_verified = Some(verified);
+        outcome.ipfs_gateways = health;
+    }
+
+    if args.check_links {
+        let gateways = IpfsGateways::from_options(&args.ipfs_gateways)?;
+        outcome.links_verified = Some(check_links(&cache, &gateways).await?);
+    }
+
+    if args.metadata_standards {
+        let marketplaces = configured_marketplaces(&args.config, &args.global.address_book)?;
+        outcome.standards_verified =
+            Some(check_metadata_standards(&cache, &marketplaces, &args.ipfs_gateways).await?);
+    }
+
+    case_println!(
+        "\nVerification successful. You're good to go!\n\nSee your tars at:\n  -> https://www.solaneyes.com/address/{}?cluster={}",
+        cache.program.tars,
+        outcome.cluster
+    );
+
+    outcome.cache = cache.stats();
+
+    Ok(outcome)
+}
+
+/// Verifies the config lines and the collection of the tars against the cache file,
+/// returning the cache and the outcome of the verification.
+fn verify_on_chain(args: &VerifyArgs) -> Result<(Cache, VerifyOutcome)> {
+    let case_config = case_setup(args.keypair.clone(), args.rpc_url.clone(), &args.global)?;
 
     // loads the cache file (this needs to have been created by
     // the upload command)
-    let mut cache = load_cache(&args.cache, false)?;
+    let mut cache = load_cluster_cache(&case_config, &args.cache, false)?;
+    cache.set_signer(&case_config.keypair)?;
 
     if cache.items.is_empty() {
-        println!(
+        case_println!(
             "{}",
             style("No cache items found - run 'upload' to create the cache file first.")
                 .red()
@@ -43,10 +128,10 @@ pub fn process_verify(args: VerifyArgs) -> Result<()> {
         );
 
         // nothing else to do, just tell that the cache file was not found (or empty)
-        return Err(CacheError::CacheFileNotFound(args.cache).into());
+        return Err(CacheError::CacheFileNotFound(args.cache.clone()).into());
     }
 
-    println!(
+    case_println!(
         "{} {}Loading tars",
         style("[1/2]").bold().dim(),
         TARS_EMOJI
@@ -80,18 +165,23 @@ pub fn process_verify(args: VerifyArgs) -> Result<()> {
 
     pb.finish_with_message("Completed");
 
-    println!(
+    case_println!(
         "\n{} {}Verification",
         style("[2/2]").bold().dim(),
         PAPER_EMOJI
     );
 
+    let mut outcome = VerifyOutcome {
+        tars: cache.program.tars.clone(),
+        ..Default::default()
+    };
+
     if tars.data.hidden_settings.is_none() {
         let num_items = tars.data.items_available;
         let cache_items = &mut cache.items;
-        let mut errors = Vec::new();
+        let mut conflicts = Vec::new();
 
-        println!("Verifying {} config line(s): (Ctrl+C to abort)", num_items);
+        case_println!("Verifying {} config line(s): (Ctrl+C to abort)", num_items);
         let pb = progress_bar_with_style(num_items as u64);
         // sleeps for a about 1 second
         let step: u64 = 1_000_000 / num_items as u64;
@@ -128,26 +218,71 @@ pub fn process_verify(args: VerifyArgs) -> Result<()> {
 
             if let Err(err) = items_match(cache_item, &on_chain_item) {
                 cache_item.on_chain = false;
-                errors.push((i.to_string(), err.to_string()));
+                conflicts.push(Conflict {
+                    index: i.to_string(),
+                    error: err.to_string(),
+                    on_chain: on_chain_item,
+                });
             }
 
             pb.inc(1);
             thread::sleep(Duration::from_micros(step));
         }
 
-        if !errors.is_empty() {
+        if !conflicts.is_empty() {
             pb.abandon_with_message(format!("{}", style("Verification failed ").red().bold()));
             cache.sync_file()?;
 
-            let total = errors.len();
-            println!("\nInvalid items found: ");
+            let total = conflicts.len();
+            case_println!("\nInvalid items found: ");
+
+            let mut invalid = String::new();
 
-            for e in errors {
-                println!("- Item {}: {}", e.0, e.1);
+            for conflict in &conflicts {
+                case_println!("- Item {}: {}", conflict.index, conflict.error);
+                invalid.push_str(&format!("\n- Item {}: {}", conflict.index, conflict.error));
+            }
+
+            // the conflicts are only resolved interactively in a terminal, otherwise the
+            // command fails as before
+            let resolution = if args.repair {
+                Resolution::KeepCache
+            } else if !is_json_output() && console::user_attended() {
+                ask_resolution(&conflicts)?
+            } else {
+                Resolution::Abort
+            };
+
+            match resolution {
+                Resolution::Abort => {
+                    case_println!("\nCache updated - re-run `deploy` or `verify --repair`.");
+
+                    // the item list is only printed in text mode, so include it in the error
+                    return Err(if is_json_output() {
+                        anyhow!("{} invalid item(s) found:{}", total, invalid)
+                    } else {
+                        anyhow!("{} invalid item(s) found.", total)
+                    });
+                }
+                Resolution::KeepChain => {
+                    outcome.items_resolved = Some(keep_chain(&mut cache, &conflicts));
+                    cache.sync_file()?;
+                }
+                Resolution::KeepCache => {
+                    assert_correct_authority(&case_config.keypair.pubkey(), &tars.authority)?;
+
+                    outcome.items_repaired = Some(repair_config_lines(
+                        &case_config,
+                        &program,
+                        &tars_pubkey,
+                        &mut cache,
+                        num_items,
+                    )?);
+                }
             }
-            println!("\nCache updated - re-run `deploy`.");
-            return Err(anyhow!("{} invalid item(s) found.", total));
+            outcome.items_verified = num_items;
         } else {
+            outcome.items_verified = num_items;
             pb.finish_with_message(format!(
                 "{}",
                 style("Config line verification successful ").green().bold()
@@ -156,10 +291,10 @@ pub fn process_verify(args: VerifyArgs) -> Result<()> {
     } else {
         // nothing else to do, there are no config lines in a tars
         // with hidden settings
-        println!("\nHidden settings enabled. No config items to verify.");
+        case_println!("\nHidden settings enabled. No config items to verify.");
     }
     if tars.items_redeemed > 0 {
-        println!(
+        case_println!(
             "\nAn item has already been minted. Skipping tars collection verification..."
         );
     } else {
@@ -173,20 +308,20 @@ pub fn process_verify(args: VerifyArgs) -> Result<()> {
 
         if let Some((_, collection_pda_account)) = collection_info {
             if collection_pda_account.mint.to_string() != collection_mint_cache {
-                println!("\nInvalid collection state found");
+                case_println!("\nInvalid collection state found");
                 cache.program.collection_mint = collection_pda_account.mint.to_string();
                 if let Some(collection_item) = collection_item {
                     collection_item.on_chain = false;
                 }
                 cache.sync_file()?;
-                println!("Cache updated - re-run `deploy`.");
+                case_println!("Cache updated - re-run `deploy`.");
                 return Err(anyhow!(
                     "Collection mint in cache {} doesn't match on chain collection mint {}!",
                     collection_mint_cache,
                     collection_pda_account.mint.to_string()
                 ));
             } else if collection_needs_deploy {
-                println!("\nInvalid collection state found - re-run `deploy`.");
+                case_println!("\nInvalid collection state found - re-run `deploy`.");
                 return Err(CacheError::InvalidState.into());
             }
         } else {
@@ -201,23 +336,114 @@ pub fn process_verify(args: VerifyArgs) -> Result<()> {
             }
             if error_found {
                 cache.sync_file()?;
-                println!("\nInvalid collection state found - re-run `deploy`.");
+                case_println!("\nInvalid collection state found - re-run `deploy`.");
                 return Err(CacheError::InvalidState.into());
             }
         }
     }
 
-    let cluster = match get_cluster(program.rpc())? {
-        Cluster::Devnet => "devnet",
-        Cluster::Mainnet => "mainnet",
+    // clusters that cannot be identified (e.g. a local validator) are reported as custom
+    let cluster = match get_cluster(program.rpc()) {
+        Ok(Cluster::Devnet) => "devnet",
+        Ok(Cluster::Mainnet) => "mainnet",
+        Err(_) => "custom",
     };
+    outcome.cluster = cluster.to_string();
 
-    println!(
-        "\nVerification successful. You're good to go!\n\nSee your tars at:\n  -> https://www.solaneyes.com/address/{}?cluster={}",
-        cache.program.tars,
-        cluster
-    );
-    Ok(())
+    Ok((cache, outcome))
+}
+
+/// Downloads the metadata of every item (through the local metadata cache) and
+/// checks that its name matches the cache. Returns the number of items checked and the
+/// health of the IPFS gateways, if any `ipfs://` link was downloaded.
+async fn check_metadata(
+    cache: &Cache,
+    gateways: IpfsGateways,
+) -> Result<(usize, Option<Vec<GatewayHealth>>)> {
+    let metadata_cache = MetadataCache::new()?.with_gateways(gateways);
+    let items: Vec<(&String, &CacheItem)> = cache
+        .items
+        .0
+        .iter()
+        .filter(|(_, item)| !item.metadata_link.is_empty())
+        .collect();
+
+    case_println!("\nChecking {} metadata file(s):", items.len());
+    let pb = progress_bar_with_style(items.len() as u64);
+
+    let metadata_cache = &metadata_cache;
+    let progress = &pb;
+
+    let errors: Vec<String> = stream::iter(items.iter())
+        .map(|(index, item)| async move {
+            let error = match metadata_cache.fetch(&item.metadata_link).await {
+                Ok(metadata) => match metadata.get("name").and_then(Value::as_str) {
+                    Some(name) if name == item.name => None,
+                    Some(name) => Some(format!(
+                        "Item {}: {}",
+                        index,
+                        VerifyError::Mismatch(
+                            "name".to_string(),
+                            item.name.clone(),
+                            name.to_string()
+                        )
+                    )),
+                    None => Some(format!("Item {}: metadata has no name", index)),
+                },
+                Err(err) => Some(format!("Item {}: {}", index, err)),
+            };
+            progress.inc(1);
+            error
+        })
+        .buffer_unordered(PARALLEL_LIMIT)
+        .filter_map(|error| async move { error })
+        .collect()
+        .await;
+
+    let health = if items.iter().any(|(_, item)| is_ipfs(&item.metadata_link)) {
+        Some(metadata_cache.gateway_health())
+    } else {
+        None
+    };
+
+    if !errors.is_empty() {
+        pb.abandon_with_message(format!("{}", style("Metadata check failed ").red().bold()));
+        print_gateway_health(&health);
+
+        let mut message = format!("{} metadata file(s) do not match the cache:", errors.len());
+        for error in errors {
+            message.push_str("\n=> ");
+            message.push_str(&error);
+        }
+
+        return Err(anyhow!(message));
+    }
+
+    pb.finish_with_message(format!(
+        "{} ({} from local cache, {} downloaded)",
+        style("Metadata check successful").green().bold(),
+        metadata_cache.hits(),
+        metadata_cache.downloads()
+    ));
+    print_gateway_health(&health);
+
+    Ok((items.len(), health))
+}
+
+/// Prints the IPFS gateways that failed during the metadata check.
+fn print_gateway_health(health: &Option<Vec<GatewayHealth>>) {
+    for gateway in health.iter().flatten() {
+        if gateway.failures > 0 {
+            case_println!(
+                "{} IPFS gateway {}: {} failure(s), {} download(s){}",
+                WARNING_EMOJI,
+                gateway.url,
+                gateway.failures,
+                gateway.successes,
+                if gateway.healthy { "" } else { " (unhealthy)" }
+            );
+        }
+    }
 }
 
 fn items_match(cache_item: &CacheItem, on_chain_item: &OnChainItem) -> Result<()> {
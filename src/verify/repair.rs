@@ -0,0 +1,66 @@
+use console::style;
+
+use crate::{
+    common::*,
+    config::CaseConfig,
+    deploy::{add_config_lines_instructions, generate_config_lines},
+    tx::send_and_confirm,
+    utils::progress_bar_with_style,
+};
+
+/// Rewrites the config lines of the cache items that are not on-chain (the lines that
+/// failed the verification) at their index, marking each item back as on-chain once its
+/// transaction is confirmed. Returns the number of config lines repaired.
+pub fn repair_config_lines(
+    case_config: &CaseConfig,
+    program: &Program,
+    tars_pubkey: &Pubkey,
+    cache: &mut Cache,
+    num_items: u64,
+) -> Result<usize> {
+    let chunks = generate_config_lines(num_items, &cache.items, CONFIG_LINES_BATCH_SIZE)?;
+    let total = chunks.iter().map(Vec::len).sum::<usize>();
+
+    case_println!(
+        "\nRepairing {} config line(s) in {} transaction(s):",
+        total,
+        chunks.len()
+    );
+    let pb = progress_bar_with_style(total as u64);
+    let mut repaired = 0;
+
+    for chunk in chunks {
+        let indices: Vec<u32> = chunk.iter().map(|(index, _)| *index).collect();
+        let instructions = add_config_lines_instructions(program, tars_pubkey, chunk)?;
+
+        if let Err(err) = send_and_confirm(case_config, &instructions, &[]) {
+            pb.abandon_with_message(format!("{}", style("Repair failed ").red().bold()));
+            // the cache keeps the lines repaired so far, so the repair can be run again
+            return Err(anyhow!(
+                "Failed to repair config lines {}-{} ({} of {} repaired): {}",
+                indices[0],
+                indices[indices.len() - 1],
+                repaired,
+                total,
+                err
+            ));
+        }
+
+        for index in &indices {
+            if let Some(item) = cache.items.get_mut(&index.to_string()) {
+                item.on_chain = true;
+            }
+        }
+        cache.sync_file()?;
+
+        repaired += indices.len();
+        pb.inc(indices.len() as u64);
+    }
+
+    pb.finish_with_message(format!(
+        "{}",
+        style("Config line repair successful ").green().bold()
+    ));
+
+    Ok(repaired)
+}
@@ -0,0 +1,88 @@
+use console::style;
+use dialoguer::Select;
+
+use crate::{common::*, utils::get_dialoguer_theme, verify::OnChainItem};
+
+/// Config line of the cache that does not match the line on-chain.
+#[derive(Debug)]
+pub struct Conflict {
+    pub index: String,
+    pub error: String,
+    pub on_chain: OnChainItem,
+}
+
+/// How the conflicts between the cache and the chain are resolved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    /// Update the cache items with the on-chain config lines.
+    KeepChain,
+    /// Rewrite the on-chain config lines with the cache items.
+    KeepCache,
+    Abort,
+}
+
+/// Asks how to resolve the conflicts, e.g. after a deploy from another machine.
+pub fn ask_resolution(conflicts: &[Conflict]) -> Result<Resolution> {
+    let options = [
+        "Keep the chain: update the cache items with the on-chain config lines",
+        "Keep the cache: rewrite the on-chain config lines (requires the tars authority)",
+        "Abort",
+    ];
+
+    case_println!();
+    let selection = Select::with_theme(&get_dialoguer_theme())
+        .with_prompt(format!(
+            "How do you want to resolve the {} conflict(s)?",
+            conflicts.len()
+        ))
+        .items(&options)
+        .default(options.len() - 1)
+        .interact()?;
+
+    Ok(match selection {
+        0 => Resolution::KeepChain,
+        1 => Resolution::KeepCache,
+        _ => Resolution::Abort,
+    })
+}
+
+/// Updates the cache items with the on-chain config lines, returning the number of items
+/// updated. Lines not written yet (empty on-chain) are left for 'deploy' to write.
+pub fn keep_chain(cache: &mut Cache, conflicts: &[Conflict]) -> usize {
+    let mut updated = 0;
+
+    for conflict in conflicts {
+        if conflict.on_chain.name.is_empty() && conflict.on_chain.uri.is_empty() {
+            continue;
+        }
+
+        if let Some(item) = cache.items.get_mut(&conflict.index) {
+            item.name = conflict.on_chain.name.clone();
+            item.metadata_link = conflict.on_chain.uri.clone();
+            item.on_chain = true;
+            updated += 1;
+        }
+    }
+
+    let pending = conflicts.len() - updated;
+
+    case_println!(
+        "\n{} cache item(s) updated with the on-chain config lines.",
+        updated
+    );
+    if updated > 0 {
+        case_println!(
+            "{}",
+            style("The asset files were not changed, check that they match the updated items.")
+                .yellow()
+        );
+    }
+    if pending > 0 {
+        case_println!(
+            "{} config line(s) are not on-chain yet - re-run `deploy` to write them.",
+            pending
+        );
+    }
+
+    updated
+}
@@ -0,0 +1,359 @@
+use std::time::Duration;
+
+use console::style;
+use futures::{stream, StreamExt};
+
+use crate::{
+    common::*,
+    config::{get_config_data, Marketplace},
+    ipfs::{is_ipfs, IpfsGateways},
+    metadata_cache::MetadataCache,
+    utils::progress_bar_with_style,
+};
+
+/// Time to wait for an image to download.
+const IMAGE_TIMEOUT: Duration = Duration::from_secs(60);
+
+const MB: usize = 1_000_000;
+
+/// Listing requirements of a marketplace checked by `verify --metadata-standards`.
+pub struct MarketplaceRules {
+    pub marketplace: Marketplace,
+    /// Maximum size of the image file (in bytes).
+    pub max_image_size: usize,
+    /// Maximum width and height of the image (in pixels).
+    pub max_image_dimension: u32,
+    /// Maximum ratio between the longest and the shortest side of the image.
+    pub max_aspect_ratio: f64,
+    pub max_attributes: usize,
+    pub allows_emoji: bool,
+}
+
+impl MarketplaceRules {
+    pub fn of(marketplace: Marketplace) -> MarketplaceRules {
+        match marketplace {
+            Marketplace::MagicEden => MarketplaceRules {
+                marketplace,
+                max_image_size: 10 * MB,
+                max_image_dimension: 4096,
+                max_aspect_ratio: 2.0,
+                max_attributes: 50,
+                allows_emoji: false,
+            },
+            Marketplace::OpenSea => MarketplaceRules {
+                marketplace,
+                max_image_size: 100 * MB,
+                max_image_dimension: 10_000,
+                max_aspect_ratio: 4.0,
+                max_attributes: 100,
+                allows_emoji: true,
+            },
+            Marketplace::Tensor => MarketplaceRules {
+                marketplace,
+                max_image_size: 25 * MB,
+                max_image_dimension: 4096,
+                max_aspect_ratio: 3.0,
+                max_attributes: 50,
+                allows_emoji: false,
+            },
+        }
+    }
+
+    /// Returns the requirements that the item breaks.
+    fn check(&self, item: &ItemDetails) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if !self.allows_emoji && contains_emoji(&item.name) {
+            problems.push(format!("name '{}' contains emoji", item.name));
+        }
+
+        if item.attributes > self.max_attributes {
+            problems.push(format!(
+                "{} attributes (max {})",
+                item.attributes, self.max_attributes
+            ));
+        }
+
+        if item.image_size > self.max_image_size {
+            problems.push(format!(
+                "image is {} (max {})",
+                format_size(item.image_size),
+                format_size(self.max_image_size)
+            ));
+        }
+
+        if let Some((width, height)) = item.image_dimensions {
+            if width.max(height) > self.max_image_dimension {
+                problems.push(format!(
+                    "image is {}x{} pixels (max {} pixels per side)",
+                    width, height, self.max_image_dimension
+                ));
+            }
+
+            let ratio = width.max(height) as f64 / width.min(height).max(1) as f64;
+
+            if ratio > self.max_aspect_ratio {
+                problems.push(format!(
+                    "image aspect ratio is {:.2}:1 (max {:.2}:1)",
+                    ratio, self.max_aspect_ratio
+                ));
+            }
+        }
+
+        problems
+    }
+}
+
+/// Details of an item checked against the requirements.
+struct ItemDetails {
+    name: String,
+    attributes: usize,
+    image_size: usize,
+    /// Width and height of the image, when its format is known.
+    image_dimensions: Option<(u32, u32)>,
+}
+
+/// Returns the marketplaces of the config file or, without a config file or
+/// marketplaces, every supported marketplace.
+pub fn configured_marketplaces(config: &str, address_book: &str) -> Result<Vec<Marketplace>> {
+    let marketplaces = if Path::new(config).exists() {
+        get_config_data(config, address_book)?.marketplaces
+    } else {
+        Vec::new()
+    };
+
+    Ok(if marketplaces.is_empty() {
+        Marketplace::ALL.to_vec()
+    } else {
+        marketplaces
+    })
+}
+
+/// Downloads the metadata and image of every item and checks them against the
+/// requirements of the marketplaces. Returns the number of items checked.
+pub async fn check_metadata_standards(
+    cache: &Cache,
+    marketplaces: &[Marketplace],
+    ipfs_gateways: &[String],
+) -> Result<usize> {
+    let metadata_cache =
+        MetadataCache::new()?.with_gateways(IpfsGateways::from_options(ipfs_gateways)?);
+    let gateways = IpfsGateways::from_options(ipfs_gateways)?;
+    let rules: Vec<MarketplaceRules> = marketplaces
+        .iter()
+        .map(|marketplace| MarketplaceRules::of(*marketplace))
+        .collect();
+
+    let items: Vec<(&String, &CacheItem)> = cache
+        .items
+        .iter()
+        .filter(|(_, item)| !item.metadata_link.is_empty())
+        .collect();
+
+    case_println!(
+        "\nChecking {} item(s) against the requirements of {}:",
+        items.len(),
+        marketplaces
+            .iter()
+            .map(|marketplace| marketplace.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    let pb = progress_bar_with_style(items.len() as u64);
+
+    let client = &HttpClient::new();
+    let metadata_cache = &metadata_cache;
+    let gateways = &gateways;
+    let rules = &rules;
+    let progress = &pb;
+
+    let errors: Vec<String> = stream::iter(items.iter())
+        .map(|(index, item)| async move {
+            let problems = match item_details(item, metadata_cache, client, gateways).await {
+                Ok(details) => rules
+                    .iter()
+                    .flat_map(|marketplace_rules| {
+                        marketplace_rules
+                            .check(&details)
+                            .into_iter()
+                            .map(|problem| {
+                                format!("{}: {}", marketplace_rules.marketplace, problem)
+                            })
+                    })
+                    .collect(),
+                Err(err) => vec![err.to_string()],
+            };
+            progress.inc(1);
+
+            problems
+                .into_iter()
+                .map(|problem| format!("Item {}: {}", index, problem))
+                .collect::<Vec<String>>()
+        })
+        .buffer_unordered(PARALLEL_LIMIT)
+        .flat_map(stream::iter)
+        .collect()
+        .await;
+
+    if !errors.is_empty() {
+        pb.abandon_with_message(format!(
+            "{}",
+            style("Metadata standards check failed ").red().bold()
+        ));
+
+        let mut message = format!(
+            "{} problem(s) would prevent items from being listed:",
+            errors.len()
+        );
+        for error in errors {
+            message.push_str("\n=> ");
+            message.push_str(&error);
+        }
+
+        return Err(anyhow!(message));
+    }
+
+    pb.finish_with_message(format!(
+        "{}",
+        style("Metadata standards check successful").green().bold()
+    ));
+
+    Ok(items.len())
+}
+
+/// Downloads the metadata and image of an item.
+async fn item_details(
+    item: &CacheItem,
+    metadata_cache: &MetadataCache,
+    client: &HttpClient,
+    gateways: &IpfsGateways,
+) -> Result<ItemDetails> {
+    let metadata = metadata_cache.fetch(&item.metadata_link).await?;
+
+    let name = metadata
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or(&item.name)
+        .to_string();
+    let attributes = metadata
+        .get("attributes")
+        .and_then(Value::as_array)
+        .map_or(0, Vec::len);
+
+    let image_link = if item.image_link.is_empty() {
+        metadata
+            .get("image")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+    } else {
+        item.image_link.as_str()
+    };
+
+    if image_link.is_empty() {
+        return Err(anyhow!("metadata has no image"));
+    }
+
+    let image = download_image(client, gateways, image_link)
+        .await
+        .map_err(|err| anyhow!("image ({}): {}", image_link, err))?;
+
+    Ok(ItemDetails {
+        name,
+        attributes,
+        image_size: image.len(),
+        image_dimensions: image_dimensions(&image),
+    })
+}
+
+async fn download_image(
+    client: &HttpClient,
+    gateways: &IpfsGateways,
+    link: &str,
+) -> Result<Vec<u8>> {
+    if is_ipfs(link) {
+        return gateways.download(client, link).await;
+    }
+
+    let response = client.get(link).timeout(IMAGE_TIMEOUT).send().await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow!("status {}", response.status()));
+    }
+
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// Returns the width and height of a PNG, GIF, JPEG or WebP image, read from its
+/// header. Returns `None` for other formats.
+fn image_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |at: usize| Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?) as u32);
+    let le16 = |at: usize| Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?) as u32);
+    let be32 = |at: usize| Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?));
+    let le24 = |at: usize| Some(le16(at)? | (*data.get(at + 2)? as u32) << 16);
+
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some((be32(16)?, be32(20)?));
+    }
+
+    if data.starts_with(b"GIF8") {
+        return Some((le16(6)?, le16(8)?));
+    }
+
+    if data.starts_with(b"RIFF") && data.get(8..12) == Some(&b"WEBP"[..]) {
+        return match data.get(12..16)? {
+            b"VP8X" => Some((le24(24)? + 1, le24(27)? + 1)),
+            b"VP8 " => Some((le16(26)? & 0x3fff, le16(28)? & 0x3fff)),
+            b"VP8L" => {
+                let bits = u32::from_le_bytes(data.get(21..25)?.try_into().ok()?);
+                Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+            }
+            _ => None,
+        };
+    }
+
+    if data.starts_with(&[0xff, 0xd8]) {
+        let mut at = 2;
+
+        while at + 9 < data.len() {
+            if data[at] != 0xff {
+                return None;
+            }
+
+            let marker = data[at + 1];
+
+            // fill bytes and markers without a segment
+            if marker == 0xff {
+                at += 1;
+                continue;
+            }
+            if marker == 0x01 || (0xd0..=0xd7).contains(&marker) {
+                at += 2;
+                continue;
+            }
+
+            // start of frame segments (excluding DHT, JPG and DAC) hold the dimensions
+            if (0xc0..=0xcf).contains(&marker) && ![0xc4, 0xc8, 0xcc].contains(&marker) {
+                return Some((be16(at + 7)?, be16(at + 5)?));
+            }
+
+            at += 2 + be16(at + 2)? as usize;
+        }
+    }
+
+    None
+}
+
+/// Returns true if the text has emoji (pictographs, symbols, flags and the joiners used
+/// to combine them).
+fn contains_emoji(text: &str) -> bool {
+    text.chars().any(|c| {
+        matches!(
+            c as u32,
+            0x1f000..=0x1faff | 0x2600..=0x27bf | 0x2b00..=0x2bff | 0xfe0f | 0x200d
+        )
+    })
+}
+
+fn format_size(bytes: usize) -> String {
+    format!("{:.1} MB", bytes as f64 / MB as f64)
+}
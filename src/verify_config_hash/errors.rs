@@ -0,0 +1,11 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum VerifyConfigHashError {
+    #[error("Tars {0} does not use hidden settings.")]
+    NoHiddenSettings(String),
+    #[error("Hidden settings hash mismatch (cache='{0}', on-chain='{1}')")]
+    HashMismatch(String, String),
+    #[error("{0}")]
+    AssetsChanged(String),
+}
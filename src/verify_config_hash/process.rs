@@ -0,0 +1,237 @@
+use console::style;
+use data_encoding::HEXLOWER;
+use ring::digest::{digest, SHA256};
+use serde::Serialize;
+
+use crate::{
+    cache::*, common::*, config::ConfigData, tars::get_tars_state, upload::assets::get_asset_pairs,
+    verify_config_hash::VerifyConfigHashError,
+};
+
+/// Length of the hidden settings hash.
+pub const HASH_LENGTH: usize = 32;
+
+pub struct VerifyConfigHashArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub cache: String,
+    pub assets_dir: String,
+    pub tars: Option<String>,
+    /// Prints the hash of the cache without comparing it with a tars.
+    pub offline: bool,
+}
+
+/// Result of the verify-config-hash command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyConfigHashOutcome {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tars: Option<String>,
+    pub cache_hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_chain_hash: Option<String>,
+    /// Items whose asset files changed since they were uploaded.
+    pub changed_items: Vec<String>,
+}
+
+/// Computes the hidden settings hash of the cache items: the SHA-256 of the name and
+/// file hashes of every item (in index order), truncated to 32 hex characters.
+pub fn hidden_settings_hash(items: &CacheItems) -> String {
+    let mut indices: Vec<(isize, &CacheItem)> = items
+        .iter()
+        .filter_map(|(index, item)| match index.parse::<isize>() {
+            Ok(index) if index >= 0 => Some((index, item)),
+            _ => None,
+        })
+        .collect();
+    indices.sort_by_key(|(index, _)| *index);
+
+    let mut content = String::new();
+
+    for (index, item) in indices {
+        content.push_str(&format!(
+            "{}:{}:{}:{}:{}\n",
+            index,
+            item.name,
+            item.metadata_hash,
+            item.image_hash,
+            item.animation_hash.as_deref().unwrap_or_default()
+        ));
+    }
+
+    let mut hash = HEXLOWER.encode(digest(&SHA256, content.as_bytes()).as_ref());
+    hash.truncate(HASH_LENGTH);
+    hash
+}
+
+/// Fills an empty hidden settings hash of the config with the hash of the cache items,
+/// returning the hash when it was filled.
+pub fn fill_hidden_settings_hash(config_data: &mut ConfigData, cache: &Cache) -> Option<String> {
+    let hidden_settings = config_data.hidden_settings.as_mut()?;
+
+    if !hidden_settings.hash.is_empty() {
+        return None;
+    }
+
+    hidden_settings.hash = hidden_settings_hash(&cache.items);
+    Some(hidden_settings.hash.clone())
+}
+
+/// Fills an empty hidden settings hash of the config with the hash of the items of the
+/// cache file, which is only loaded when the hash is empty.
+pub fn fill_hidden_settings_hash_from_cache(
+    config_data: &mut ConfigData,
+    cache_path: &str,
+) -> Result<Option<String>> {
+    let empty_hash = config_data
+        .hidden_settings
+        .as_ref()
+        .map_or(false, |hidden_settings| hidden_settings.hash.is_empty());
+
+    if !empty_hash {
+        return Ok(None);
+    }
+
+    Ok(fill_hidden_settings_hash(
+        config_data,
+        &load_cache(cache_path, false)?,
+    ))
+}
+
+pub fn process_verify_config_hash(args: VerifyConfigHashArgs) -> Result<VerifyConfigHashOutcome> {
+    let mut cache = load_cache(&args.cache, false)?;
+
+    if cache.items.is_empty() {
+        return Err(CacheError::CacheFileNotFound(args.cache).into());
+    }
+
+    case_println!(
+        "{} {}Checking assets against the cache",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let changed_items = if Path::new(&args.assets_dir).is_dir() {
+        changed_items(&args.assets_dir, &cache)?
+    } else {
+        case_println!(
+            "Assets directory '{}' not found, skipping the assets check.",
+            args.assets_dir
+        );
+        Vec::new()
+    };
+
+    if changed_items.is_empty() {
+        case_println!("Assets match the cache.");
+    } else {
+        for item in &changed_items {
+            case_println!("{} {}", style("✘").red(), item);
+        }
+    }
+
+    case_println!(
+        "\n{} {}Comparing with the on-chain hash",
+        style("[2/2]").bold().dim(),
+        TARS_EMOJI
+    );
+
+    let cache_hash = hidden_settings_hash(&cache.items);
+
+    // the hash of the cache is set as "hiddenSettings.hash" before the deploy
+    if args.offline {
+        case_println!("{} {}", style("Cache hash:").bold(), cache_hash);
+        case_println!("Comparison with the on-chain hash skipped (--offline).");
+
+        if !changed_items.is_empty() {
+            return Err(VerifyConfigHashError::AssetsChanged(format!(
+                "{} item(s) changed since upload, re-run 'upload' before deploying.",
+                changed_items.len()
+            ))
+            .into());
+        }
+
+        return Ok(VerifyConfigHashOutcome {
+            cache_hash,
+            changed_items,
+            ..Default::default()
+        });
+    }
+
+    // the tars id specified takes precedence over the one from the cache
+    let tars_id = args.tars.unwrap_or_else(|| cache.program.tars.clone());
+    let tars_pubkey = resolve_pubkey(&tars_id, &args.global.address_book)?;
+
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    check_cache_cluster(&case_config, &mut cache)?;
+    let tars_state = get_tars_state(&case_config, &tars_pubkey)?;
+
+    let hidden_settings = tars_state
+        .data
+        .hidden_settings
+        .ok_or_else(|| VerifyConfigHashError::NoHiddenSettings(tars_pubkey.to_string()))?;
+
+    let on_chain_hash = String::from_utf8_lossy(&hidden_settings.hash).to_string();
+
+    case_println!("{} {}", style("Tars ID:").bold(), tars_pubkey);
+    case_println!("{} {}", style("Cache hash:").bold(), cache_hash);
+    case_println!("{} {}", style("On-chain hash:").bold(), on_chain_hash);
+
+    if on_chain_hash != cache_hash {
+        case_println!(
+            "\n{}",
+            style("The cache does not match the hash set at deploy - do not reveal.")
+                .red()
+                .bold()
+        );
+        return Err(VerifyConfigHashError::HashMismatch(cache_hash, on_chain_hash).into());
+    }
+
+    if !changed_items.is_empty() {
+        return Err(VerifyConfigHashError::AssetsChanged(format!(
+            "{} item(s) changed since upload, re-run 'upload' before revealing.",
+            changed_items.len()
+        ))
+        .into());
+    }
+
+    case_println!(
+        "\n{}",
+        style("Hidden settings hash matches the cache.")
+            .green()
+            .bold()
+    );
+
+    Ok(VerifyConfigHashOutcome {
+        tars: Some(tars_pubkey.to_string()),
+        cache_hash,
+        on_chain_hash: Some(on_chain_hash),
+        changed_items,
+    })
+}
+
+/// Returns the items whose metadata or media files differ from the hashes recorded in
+/// the cache at upload.
+fn changed_items(assets_dir: &str, cache: &Cache) -> Result<Vec<String>> {
+    let asset_pairs = get_asset_pairs(assets_dir)?;
+    let mut indices: Vec<&isize> = asset_pairs.keys().filter(|index| **index >= 0).collect();
+    indices.sort();
+
+    let mut changed = Vec::new();
+
+    for index in indices {
+        let pair = &asset_pairs[index];
+
+        let message = match cache.items.get(&index.to_string()) {
+            Some(item) if item.metadata_hash != pair.metadata_hash => "metadata changed",
+            Some(item) if item.image_hash != pair.image_hash => "image changed",
+            Some(item) if item.animation_hash != pair.animation_hash => "animation changed",
+            Some(_) => continue,
+            None => "missing from the cache",
+        };
+
+        changed.push(format!("Item {}: {}", index, message));
+    }
+
+    Ok(changed)
+}
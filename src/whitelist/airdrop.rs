@@ -0,0 +1,271 @@
+use std::{collections::HashSet, fs};
+
+use anchor_client::solana_sdk::program_pack::Pack;
+use chrono::Utc;
+use console::style;
+use serde::Serialize;
+use spl_associated_token_account::{create_associated_token_account, get_associated_token_address};
+use spl_token::{instruction::transfer, state::Account, ID as TOKEN_PROGRAM_ID};
+
+use crate::{
+    common::*, config::CaseConfig, denylist::load_denylist, tars::TARS_ID, tx::send_and_confirm,
+    utils::*, whitelist::whitelist_mint,
+};
+
+pub struct WhitelistAirdropArgs {
+    pub config: String,
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub mint: Option<String>,
+    pub list: String,
+    /// Tokens sent to each address of a list without amounts.
+    pub amount: u64,
+    pub progress: String,
+}
+
+/// Addresses of an airdrop list: an array of addresses, or an object of addresses and
+/// amounts.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AirdropList {
+    Addresses(Vec<String>),
+    Amounts(IndexMap<String, u64>),
+}
+
+/// Whitelist tokens sent to an address, written to the progress file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AirdropReceipt {
+    pub mint: String,
+    pub address: String,
+    pub amount: u64,
+    pub signature: String,
+    pub created_at: String,
+}
+
+/// Result of the whitelist airdrop command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhitelistAirdropOutcome {
+    pub mint: String,
+    pub progress_file: String,
+    /// Addresses that received their tokens in a previous run.
+    pub already_sent: usize,
+    /// Addresses of the deny list, left out of the airdrop.
+    pub denied: Vec<String>,
+    pub receipts: Vec<AirdropReceipt>,
+}
+
+pub fn process_whitelist_airdrop(args: WhitelistAirdropArgs) -> Result<WhitelistAirdropOutcome> {
+    let mint = whitelist_mint(
+        &args.config,
+        args.mint.as_deref(),
+        &args.global.address_book,
+    )?;
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+    let payer = program.payer();
+
+    case_println!(
+        "{} {}Loading airdrop list",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let recipients = load_list(&args.list, args.amount, &args.global.address_book)?;
+
+    // receipts of previous runs are kept, their addresses are skipped
+    let mut receipts: Vec<AirdropReceipt> = if Path::new(&args.progress).exists() {
+        serde_json::from_str(&fs::read_to_string(&args.progress)?)
+            .map_err(|err| anyhow!("Failed to parse progress file '{}': {}", args.progress, err))?
+    } else {
+        Vec::new()
+    };
+    let sent: HashSet<String> = receipts
+        .iter()
+        .filter(|receipt| receipt.mint == mint.to_string())
+        .map(|receipt| receipt.address.clone())
+        .collect();
+
+    let denylist = load_denylist(&args.global.denylist)?;
+    let mut outcome = WhitelistAirdropOutcome {
+        mint: mint.to_string(),
+        progress_file: args.progress.clone(),
+        ..Default::default()
+    };
+    let mut pending = Vec::new();
+
+    for (address, amount) in recipients {
+        if sent.contains(&address.to_string()) {
+            outcome.already_sent += 1;
+        } else if denylist.contains(&address) {
+            outcome.denied.push(address.to_string());
+        } else {
+            pending.push((address, amount));
+        }
+    }
+
+    case_println!(
+        "{} {} address(es), {} already sent, {} denied",
+        style("Airdrop list:").bold(),
+        pending.len() + outcome.already_sent + outcome.denied.len(),
+        outcome.already_sent,
+        outcome.denied.len()
+    );
+
+    if !outcome.denied.is_empty() {
+        case_println!(
+            "{}{}",
+            WARNING_EMOJI,
+            style("Skipped deny list addresses:").yellow()
+        );
+        for address in &outcome.denied {
+            case_println!("{} {}", style(" :..").dim(), address);
+        }
+    }
+
+    let source = get_associated_token_address(&payer, &mint);
+    let required: u64 = pending.iter().map(|(_, amount)| amount).sum();
+    let balance = match program.rpc().get_account_data(&source) {
+        Ok(data) if !data.is_empty() => Account::unpack_unchecked(&data)?.amount,
+        _ => 0,
+    };
+
+    if balance < required {
+        return Err(anyhow!(
+            "The token account {} holds {} whitelist token(s), {} are needed ({} short): \
+             use 'case whitelist mint' first",
+            source,
+            balance,
+            required,
+            required - balance
+        ));
+    }
+
+    case_println!(
+        "\n{} {}Sending {} whitelist token(s) to {} address(es)",
+        style("[2/2]").bold().dim(),
+        TICKET_EMOJI,
+        required,
+        pending.len()
+    );
+
+    let pb = progress_bar_with_style(pending.len() as u64);
+
+    for (address, amount) in pending {
+        let result =
+            send_tokens(&case_config, &program, &mint, &address, amount).map(|signature| {
+                AirdropReceipt {
+                    mint: mint.to_string(),
+                    address: address.to_string(),
+                    amount,
+                    signature: signature.to_string(),
+                    created_at: Utc::now().to_rfc3339(),
+                }
+            });
+
+        match result {
+            Ok(receipt) => {
+                receipts.push(receipt.clone());
+                outcome.receipts.push(receipt);
+                // written after every address, so that a re-run resumes the airdrop
+                write_progress(&args.progress, &receipts)?;
+            }
+            Err(err) => {
+                pb.abandon_with_message(format!("{}", style("Airdrop failed ").red().bold()));
+                case_println!(
+                    "{} address(es) sent, re-run the command to resume from '{}'",
+                    outcome.receipts.len(),
+                    args.progress
+                );
+                error!("{:?}", err);
+                return Err(err);
+            }
+        }
+
+        pb.inc(1);
+    }
+
+    pb.finish();
+
+    case_println!(
+        "\n{} {} address(es) sent, progress written to '{}'",
+        COMPLETE_EMOJI,
+        outcome.receipts.len(),
+        args.progress
+    );
+
+    Ok(outcome)
+}
+
+/// Reads the addresses of the list and their amounts; the amounts of an address listed
+/// more than once are added up.
+fn load_list(path: &str, amount: u64, address_book: &str) -> Result<IndexMap<Pubkey, u64>> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| anyhow!("Failed to open airdrop list '{}': {}", path, err))?;
+    let list: AirdropList = serde_json::from_str(&content).map_err(|err| {
+        anyhow!(
+            "Failed to parse airdrop list '{}', expected an array of addresses or an object \
+             of addresses and amounts: {}",
+            path,
+            err
+        )
+    })?;
+
+    let entries: Vec<(String, u64)> = match list {
+        AirdropList::Addresses(addresses) => addresses
+            .into_iter()
+            .map(|address| (address, amount))
+            .collect(),
+        AirdropList::Amounts(amounts) => amounts.into_iter().collect(),
+    };
+
+    let mut recipients = IndexMap::new();
+
+    for (address, amount) in entries {
+        let pubkey = resolve_pubkey(&address, address_book)
+            .map_err(|err| anyhow!("Invalid address in airdrop list '{}': {}", path, err))?;
+        *recipients.entry(pubkey).or_insert(0) += amount;
+    }
+
+    recipients.retain(|_, amount| *amount > 0);
+
+    Ok(recipients)
+}
+
+/// Transfers whitelist tokens from the token account of the payer, creating the token
+/// account of the recipient when needed.
+fn send_tokens(
+    case_config: &CaseConfig,
+    program: &Program,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+) -> Result<Signature> {
+    let payer = program.payer();
+    let source = get_associated_token_address(&payer, mint);
+    let destination = get_associated_token_address(recipient, mint);
+    let mut instructions = Vec::new();
+
+    if program.rpc().get_account(&destination).is_err() {
+        instructions.push(create_associated_token_account(&payer, recipient, mint));
+    }
+
+    instructions.push(transfer(
+        &TOKEN_PROGRAM_ID,
+        &source,
+        &destination,
+        &payer,
+        &[],
+        amount,
+    )?);
+
+    send_and_confirm(case_config, &instructions, &[])
+}
+
+fn write_progress(path: &str, receipts: &[AirdropReceipt]) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(receipts)?)
+        .map_err(|err| anyhow!("Failed to write progress file '{}': {}", path, err))
+}
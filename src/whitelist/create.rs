@@ -0,0 +1,101 @@
+use anchor_client::solana_sdk::system_instruction;
+use console::style;
+use serde::Serialize;
+use spl_associated_token_account::{create_associated_token_account, get_associated_token_address};
+use spl_token::{
+    instruction::{initialize_mint, mint_to},
+    ID as TOKEN_PROGRAM_ID,
+};
+
+use crate::{
+    common::*,
+    rng::{Rng, SystemRng},
+    tars::TARS_ID,
+    tx::send_and_confirm,
+    utils::*,
+};
+
+pub struct WhitelistCreateArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    /// Number of tokens minted to the payer once the token is created.
+    pub amount: u64,
+}
+
+/// Result of the whitelist create command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhitelistCreateOutcome {
+    pub mint: String,
+    pub token_account: String,
+    pub amount: u64,
+    pub signature: String,
+}
+
+pub fn process_whitelist_create(args: WhitelistCreateArgs) -> Result<WhitelistCreateOutcome> {
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+    let payer = program.payer();
+
+    case_println!(
+        "{} {}Creating whitelist token",
+        style("[1/1]").bold().dim(),
+        TICKET_EMOJI
+    );
+
+    let mint = SystemRng::default().new_keypair();
+    let token_account = get_associated_token_address(&payer, &mint.pubkey());
+    let min_rent = program
+        .rpc()
+        .get_minimum_balance_for_rent_exemption(MINT_LAYOUT as usize)?;
+
+    // whitelist tokens have no decimals, one token is burned or checked per mint
+    let mut instructions = vec![
+        system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            min_rent,
+            MINT_LAYOUT,
+            &TOKEN_PROGRAM_ID,
+        ),
+        initialize_mint(&TOKEN_PROGRAM_ID, &mint.pubkey(), &payer, Some(&payer), 0)?,
+        create_associated_token_account(&payer, &payer, &mint.pubkey()),
+    ];
+
+    if args.amount > 0 {
+        instructions.push(mint_to(
+            &TOKEN_PROGRAM_ID,
+            &mint.pubkey(),
+            &token_account,
+            &payer,
+            &[],
+            args.amount,
+        )?);
+    }
+
+    let pb = spinner_with_style();
+    pb.set_message("Sending create transaction...");
+    let signature = send_and_confirm(&case_config, &instructions, &[&mint])?;
+    pb.finish_with_message(format!("{} {}", style("Signature:").bold(), signature));
+
+    case_println!("{} {}", style("Whitelist token:").bold(), mint.pubkey());
+    case_println!(
+        "{} {} token(s) in {}",
+        style("Minted:").bold(),
+        args.amount,
+        token_account
+    );
+    case_println!(
+        "\n{}",
+        style("Set it as \"whitelistMintSettings.mint\" in the config file.").dim()
+    );
+
+    Ok(WhitelistCreateOutcome {
+        mint: mint.pubkey().to_string(),
+        token_account: token_account.to_string(),
+        amount: args.amount,
+        signature: signature.to_string(),
+    })
+}
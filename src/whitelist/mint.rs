@@ -0,0 +1,112 @@
+use anchor_client::solana_sdk::program_option::COption;
+use console::style;
+use serde::Serialize;
+use spl_associated_token_account::{create_associated_token_account, get_associated_token_address};
+use spl_token::{instruction::mint_to, ID as TOKEN_PROGRAM_ID};
+
+use crate::{common::*, config::get_config_data, tars::TARS_ID, tx::send_and_confirm, utils::*};
+
+pub struct WhitelistMintArgs {
+    pub config: String,
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub mint: Option<String>,
+    pub amount: u64,
+    /// Wallet receiving the tokens, defaults to the payer.
+    pub to: Option<String>,
+}
+
+/// Result of the whitelist mint command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhitelistMintOutcome {
+    pub mint: String,
+    pub recipient: String,
+    pub amount: u64,
+    pub signature: String,
+}
+
+pub fn process_whitelist_mint(args: WhitelistMintArgs) -> Result<WhitelistMintOutcome> {
+    let mint = whitelist_mint(
+        &args.config,
+        args.mint.as_deref(),
+        &args.global.address_book,
+    )?;
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+    let payer = program.payer();
+
+    let recipient = match &args.to {
+        Some(to) => resolve_pubkey(to, &args.global.address_book)?,
+        None => payer,
+    };
+
+    if args.amount == 0 {
+        return Err(anyhow!("The amount of tokens must be greater than zero"));
+    }
+
+    case_println!(
+        "{} {}Minting {} whitelist token(s) to {}",
+        style("[1/1]").bold().dim(),
+        TICKET_EMOJI,
+        args.amount,
+        recipient
+    );
+
+    let token_mint = check_spl_token(&program, &mint.to_string())?;
+
+    if token_mint.mint_authority != COption::Some(payer) {
+        return Err(anyhow!(
+            "The keypair ({}) is not the mint authority of the whitelist token {}",
+            payer,
+            mint
+        ));
+    }
+
+    let token_account = get_associated_token_address(&recipient, &mint);
+    let mut instructions = Vec::new();
+
+    if program.rpc().get_account(&token_account).is_err() {
+        instructions.push(create_associated_token_account(&payer, &recipient, &mint));
+    }
+
+    instructions.push(mint_to(
+        &TOKEN_PROGRAM_ID,
+        &mint,
+        &token_account,
+        &payer,
+        &[],
+        args.amount,
+    )?);
+
+    let pb = spinner_with_style();
+    pb.set_message("Sending mint transaction...");
+    let signature = send_and_confirm(&case_config, &instructions, &[])?;
+    pb.finish_with_message(format!("{} {}", style("Signature:").bold(), signature));
+
+    Ok(WhitelistMintOutcome {
+        mint: mint.to_string(),
+        recipient: recipient.to_string(),
+        amount: args.amount,
+        signature: signature.to_string(),
+    })
+}
+
+/// Returns the whitelist token specified or, without one, the token of the whitelist mint
+/// settings of the config file.
+pub fn whitelist_mint(config: &str, mint: Option<&str>, address_book: &str) -> Result<Pubkey> {
+    match mint {
+        Some(mint) => resolve_pubkey(mint, address_book),
+        None => get_config_data(config, address_book)?
+            .whitelist_mint_settings
+            .map(|settings| settings.mint())
+            .ok_or_else(|| {
+                anyhow!(
+                    "No whitelist token: use --mint or set \"whitelistMintSettings\" in the config file '{}'",
+                    config
+                )
+            }),
+    }
+}
@@ -0,0 +1,7 @@
+pub mod airdrop;
+pub mod create;
+pub mod mint;
+
+pub use airdrop::*;
+pub use create::*;
+pub use mint::*;
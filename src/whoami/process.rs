@@ -0,0 +1,176 @@
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    budget::to_sol,
+    cache::load_cluster_cache,
+    common::*,
+    config::{get_config_data, ConfigData},
+    pdas::{get_collection_pda, get_metadata_pda},
+    tars::{get_tars_state, TARS_ID},
+    upload::methods::{bundlr_node, BundlrMethod},
+    utils::*,
+};
+
+pub struct WhoamiArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
+    pub config: String,
+    pub cache: String,
+    pub tars: Option<String>,
+}
+
+/// Result of the whoami command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhoamiOutcome {
+    pub pubkey: String,
+    pub rpc_url: String,
+    pub cluster: Option<String>,
+    pub balance: u64,
+    pub tars: Option<String>,
+    pub tars_authority: Option<bool>,
+    pub collection_mint: Option<String>,
+    pub collection_update_authority: Option<bool>,
+    pub bundlr_balance: Option<u64>,
+}
+
+/// Prints the identity of the keypair and what it is allowed to do on the tars of the
+/// cache. Lookups that fail are reported as unavailable instead of failing the command.
+pub async fn process_whoami(args: WhoamiArgs) -> Result<WhoamiOutcome> {
+    case_println!(
+        "{} {}Looking up identity",
+        style("[1/1]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    let case_config = case_setup(args.keypair, args.rpc_url, &args.global)?;
+    let client = setup_client(&case_config)?;
+    let program = client.program(TARS_ID);
+    let pubkey = case_config.keypair.pubkey();
+
+    let mut outcome = WhoamiOutcome {
+        pubkey: pubkey.to_string(),
+        rpc_url: case_config.rpc_url.clone(),
+        balance: program.rpc().get_balance(&pubkey)?,
+        ..Default::default()
+    };
+
+    let cluster = get_cluster(program.rpc()).ok();
+    outcome.cluster = cluster
+        .as_ref()
+        .map(|cluster| format!("{:?}", cluster).to_lowercase());
+
+    // the tars id specified takes precedence over the one from the cache
+    let cache = load_cluster_cache(&case_config, &args.cache, false).ok();
+    let tars_id = match args.tars {
+        Some(tars_id) => Some(tars_id),
+        None => cache
+            .as_ref()
+            .map(|cache| cache.program.tars.clone())
+            .filter(|tars| !tars.is_empty()),
+    };
+
+    let mut collection_mint = None;
+
+    if let Some(tars_id) = tars_id {
+        let tars_pubkey = resolve_pubkey(&tars_id, &args.global.address_book)?;
+        outcome.tars = Some(tars_pubkey.to_string());
+
+        if let Ok(tars_state) = get_tars_state(&case_config, &tars_pubkey) {
+            outcome.tars_authority = Some(tars_state.authority == pubkey);
+        }
+
+        if let Ok((_, collection_pda)) = get_collection_pda(&tars_pubkey, &program) {
+            collection_mint = Some(collection_pda.mint);
+        }
+    }
+
+    // a collection that was created but not set yet is only in the cache
+    if collection_mint.is_none() {
+        collection_mint = cache
+            .as_ref()
+            .and_then(|cache| Pubkey::from_str(&cache.program.collection_mint).ok());
+    }
+
+    if let Some(mint) = collection_mint {
+        outcome.collection_mint = Some(mint.to_string());
+
+        if let Ok((_, metadata)) = get_metadata_pda(&mint, &program) {
+            outcome.collection_update_authority = Some(metadata.update_authority == pubkey);
+        }
+    }
+
+    if let Some(cluster) = cluster {
+        // the node set in the config file is the one used by the upload
+        let config_data = if Path::new(&args.config).exists() {
+            get_config_data(&args.config, &args.global.address_book)?
+        } else {
+            ConfigData::default()
+        };
+        let node = bundlr_node(&config_data, cluster);
+        outcome.bundlr_balance =
+            BundlrMethod::get_bundlr_balance(&HttpClient::new(), &pubkey.to_string(), node)
+                .await
+                .ok();
+    }
+
+    pb.finish_and_clear();
+
+    let unavailable = || style("unavailable").dim().to_string();
+    let yes_no = |value: Option<bool>| match value {
+        Some(true) => style("yes").green().to_string(),
+        Some(false) => style("no").red().to_string(),
+        None => unavailable(),
+    };
+
+    case_println!(
+        "\n{}{} {}",
+        PAPER_EMOJI,
+        style("Keypair:").dim(),
+        outcome.pubkey
+    );
+    case_println!(" {}", style(":").dim());
+    print_with_style("rpc url", outcome.rpc_url.clone());
+    print_with_style(
+        "cluster",
+        outcome.cluster.clone().unwrap_or_else(unavailable),
+    );
+    print_with_style("balance", format!("◎ {}", to_sol(outcome.balance)));
+    print_with_style(
+        "bundlr balance",
+        outcome
+            .bundlr_balance
+            .map(|balance| format!("◎ {}", to_sol(balance)))
+            .unwrap_or_else(unavailable),
+    );
+
+    match &outcome.tars {
+        Some(tars) => {
+            print_with_style("tars", tars.clone());
+            print_with_style("tars authority", yes_no(outcome.tars_authority));
+        }
+        None => print_with_style("tars", "none".to_string()),
+    }
+
+    match &outcome.collection_mint {
+        Some(collection_mint) => {
+            print_with_style("collection mint", collection_mint.clone());
+            print_with_style(
+                "collection update authority",
+                yes_no(outcome.collection_update_authority),
+            );
+        }
+        None => print_with_style("collection mint", "none".to_string()),
+    }
+
+    Ok(outcome)
+}
+
+fn print_with_style(key: &str, value: String) {
+    case_println!(" {} {}", style(format!(":.. {}:", key)).dim(), value);
+}
@@ -13,6 +13,7 @@ pub use anchor_client::{
 };
 use console::{style, Style};
 use dialoguer::{theme::ColorfulTheme, Confirm};
+use serde::Serialize;
 use tars::{accounts as nft_accounts, instruction as nft_instruction};
 use solana_account_decoder::UiAccountEncoding;
 use solana_client::{
@@ -22,8 +23,12 @@ use solana_client::{
 
 use crate::{
     tars::TARS_ID,
+    budget::to_sol,
     common::*,
+    config::CaseConfig,
+    deployments::{set_deployment_state, DeploymentState},
     setup::{setup_client, case_setup},
+    tx::send_and_confirm,
     utils::*,
 };
 
@@ -31,13 +36,58 @@ pub struct WithdrawArgs {
     pub tars: Option<String>,
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
+    pub global: GlobalOptions,
     pub list: bool,
+    /// Authority of the tarss to look up (defaults to the keypair).
+    pub authority: Option<String>,
+    /// Tarss that are never drained.
+    pub exclude: Vec<String>,
+    pub skip_confirmation: bool,
 }
 
-pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
+/// Balance of a tars account.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TarsBalance {
+    pub tars: String,
+    pub lamports: u64,
+    /// SPL token treasury, when the tars is paid with a token (--list).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub treasury: Option<TreasuryBalance>,
+}
+
+/// Balance of the SPL token account that receives the payments of a tars.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreasuryBalance {
+    pub account: String,
+    pub mint: String,
+    /// Amount in base units.
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+impl TreasuryBalance {
+    fn ui_amount(&self) -> f64 {
+        self.amount as f64 / 10f64.powi(self.decimals.into())
+    }
+}
+
+/// Result of the withdraw command.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawOutcome {
+    pub tarss: Vec<TarsBalance>,
+    pub signatures: Vec<String>,
+    pub not_drained: Vec<String>,
+    /// Tarss not drained because they were declined at the prompt.
+    pub skipped: Vec<String>,
+}
+
+pub fn process_withdraw(args: WithdrawArgs) -> Result<WithdrawOutcome> {
     // (1) Setting up connection
 
-    println!(
+    case_println!(
         "{} {}Initializing connection",
         style("[1/2]").bold().dim(),
         COMPUTER_EMOJI
@@ -46,11 +96,11 @@ pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
     let pb = spinner_with_style();
     pb.set_message("Connecting...");
 
-    let (program, payer) = setup_withdraw(args.keypair, args.rpc_url)?;
+    let (case_config, program, payer) = setup_withdraw(args.keypair, args.rpc_url, &args.global)?;
 
     pb.finish_with_message("Connected");
 
-    println!(
+    case_println!(
         "\n{} {}{} funds",
         style("[2/2]").bold().dim(),
         WITHDRAW_EMOJI,
@@ -61,16 +111,65 @@ pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
     // as an argument, we will list the tarss (no draining happens)
     let tars = if args.list { None } else { args.tars };
 
+    let authority = match &args.authority {
+        Some(authority) => resolve_pubkey(authority, &args.global.address_book)?,
+        None => payer,
+    };
+
+    if authority != payer && !args.list {
+        return Err(anyhow!(
+            "Only the tarss of the keypair ({}) can be drained, use --list to list the tarss of {}",
+            payer,
+            authority
+        ));
+    }
+
+    let exclude = args
+        .exclude
+        .iter()
+        .map(|tars| resolve_pubkey(tars, &args.global.address_book))
+        .collect::<Result<Vec<Pubkey>>>()?;
+
     // (2) Retrieving data for listing/draining
 
+    let mut outcome = WithdrawOutcome::default();
+
     match &tars {
         Some(tars) => {
-            let tars = Pubkey::from_str(tars)?;
+            let tars = resolve_pubkey(tars, &args.global.address_book)?;
+
+            if exclude.contains(&tars) {
+                return Err(anyhow!("Tars {} is excluded from the withdraw", tars));
+            }
+
+            let lamports = program.rpc().get_balance(&tars)?;
+            case_println!(
+                "
+Tars {} will be closed, reclaiming ◎ {}",
+                tars,
+                to_sol(lamports)
+            );
+
+            if !args.skip_confirmation && !confirm_drain(&tars, lamports)? {
+                return Err(anyhow!("Withdraw aborted"));
+            }
 
             let pb = spinner_with_style();
             pb.set_message("Draining tars...");
 
-            do_withdraw(Rc::new(program), tars, payer)?;
+            let sig = do_withdraw(
+                &case_config,
+                Rc::new(program),
+                tars,
+                payer,
+                &args.global.deployments,
+            )?;
+            outcome.signatures.push(sig.to_string());
+            outcome.tarss.push(TarsBalance {
+                tars: tars.to_string(),
+                lamports,
+                treasury: None,
+            });
 
             pb.finish_with_message("Done");
         }
@@ -78,7 +177,7 @@ pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
             let config = RpcProgramAccountsConfig {
                 filters: Some(vec![RpcFilterType::Memcmp(Memcmp {
                     offset: 8, // key
-                    bytes: MemcmpEncodedBytes::Base58(payer.to_string()),
+                    bytes: MemcmpEncodedBytes::Base58(authority.to_string()),
                     encoding: None,
                 })]),
                 account_config: RpcAccountInfoConfig {
@@ -95,38 +194,60 @@ pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
             pb.set_message("Looking up tarss...");
 
             let program = Rc::new(program);
-            let accounts = program
+            let mut accounts = program
                 .rpc()
                 .get_program_accounts_with_config(&program.id(), config)?;
 
             pb.finish_and_clear();
 
+            let found = accounts.len();
+            accounts.retain(|(pubkey, _)| !exclude.contains(pubkey));
+
+            if accounts.len() < found {
+                case_println!("\nExcluded {} tars(s)", found - accounts.len());
+            }
+
             let mut total = 0.0f64;
 
             accounts.iter().for_each(|account| {
-                let (_pubkey, account) = account;
+                let (pubkey, account) = account;
                 total += account.lamports as f64;
+                outcome.tarss.push(TarsBalance {
+                    tars: pubkey.to_string(),
+                    lamports: account.lamports,
+                    // only listed, since draining does not move the treasury funds
+                    treasury: if args.list {
+                        treasury_balance(&program, &account.data)
+                    } else {
+                        None
+                    },
+                });
             });
 
-            println!(
+            case_println!(
                 "\nFound {} tarss, total amount: ◎ {}",
                 accounts.len(),
                 total / LAMPORTS_PER_SOL as f64
             );
 
             if !accounts.is_empty() {
+                // the amount reclaimed from each account is shown before draining
                 if args.list {
-                    println!("\n{:48} Balance", "Tars ID");
-                    println!("{:-<61}", "-");
+                    print_treasuries(&outcome.tarss);
+                } else {
+                    case_println!("\n{:48} Balance", "Tars ID");
+                    case_println!("{:-<61}", "-");
 
-                    for (pubkey, account) in accounts {
-                        println!(
+                    for (pubkey, account) in &accounts {
+                        case_println!(
                             "{:48} {:>12.8}",
                             pubkey.to_string(),
                             account.lamports as f64 / LAMPORTS_PER_SOL as f64
                         );
                     }
-                } else {
+                }
+
+                if !args.list {
                     let warning = format!(
                         "\n\
                         +-----------------------------------------------------+\n\
@@ -135,39 +256,49 @@ pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
                         WARNING_EMOJI
                     );
 
-                    println!("{}\n", style(warning).bold().yellow());
-
-                    let theme = ColorfulTheme {
-                        success_prefix: style("✔".to_string()).yellow().force_styling(true),
-                        values_style: Style::new().yellow(),
-                        ..get_dialoguer_theme()
-                    };
-
-                    if !Confirm::with_theme(&theme)
-                        .with_prompt("Do you want to continue?")
-                        .interact()?
-                    {
-                        return Err(anyhow!("Withdraw aborted"));
+                    case_println!("{}\n", style(warning).bold().yellow());
+
+                    for (tars, account) in &accounts {
+                        // every tars is confirmed on its own, so that a live one is not
+                        // drained by accident
+                        if !args.skip_confirmation && !confirm_drain(tars, account.lamports)? {
+                            outcome.skipped.push(tars.to_string());
+                            continue;
+                        }
+
+                        match do_withdraw(
+                            &case_config,
+                            program.clone(),
+                            *tars,
+                            payer,
+                            &args.global.deployments,
+                        ) {
+                            Ok(sig) => {
+                                case_println!(
+                                    "Drained {}: ◎ {} reclaimed",
+                                    tars,
+                                    to_sol(account.lamports)
+                                );
+                                outcome.signatures.push(sig.to_string());
+                            }
+                            Err(e) => {
+                                outcome.not_drained.push(tars.to_string());
+                                error!("Error: {}", e);
+                            }
+                        }
                     }
 
-                    let pb = progress_bar_with_style(accounts.len() as u64);
-                    let mut not_drained = 0;
-
-                    accounts.iter().for_each(|account| {
-                        let (tars, _account) = account;
-                        do_withdraw(program.clone(), *tars, payer).unwrap_or_else(|e| {
-                            not_drained += 1;
-                            error!("Error: {}", e);
-                        });
-                        pb.inc(1);
-                    });
-
-                    pb.finish();
+                    if !outcome.skipped.is_empty() {
+                        case_println!("Skipped {} tars(s)", outcome.skipped.len());
+                    }
 
-                    if not_drained > 0 {
-                        println!(
+                    if !outcome.not_drained.is_empty() {
+                        case_println!(
                             "{}",
-                            style(format!("Could not drain {} tars(s)", not_drained))
+                            style(format!(
+                                "Could not drain {} tars(s)",
+                                outcome.not_drained.len()
+                            ))
                                 .red()
                                 .bold()
                                 .dim()
@@ -178,27 +309,111 @@ pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
         }
     }
 
-    Ok(())
+    Ok(outcome)
 }
 
-fn setup_withdraw(keypair: Option<String>, rpc_url: Option<String>) -> Result<(Program, Pubkey)> {
-    let case_config = case_setup(keypair, rpc_url)?;
+/// Returns the balance of the token treasury of a tars paid with an SPL token.
+fn treasury_balance(program: &Program, data: &[u8]) -> Option<TreasuryBalance> {
+    let tars = Tars::try_deserialize(&mut &data[..]).ok()?;
+    let mint = tars.token_mint?;
+
+    match program.rpc().get_token_account_balance(&tars.wallet) {
+        Ok(balance) => Some(TreasuryBalance {
+            account: tars.wallet.to_string(),
+            mint: mint.to_string(),
+            amount: balance.amount.parse().unwrap_or_default(),
+            decimals: balance.decimals,
+        }),
+        Err(err) => {
+            warn!(
+                "Failed to get the balance of treasury {}: {}",
+                tars.wallet, err
+            );
+            None
+        }
+    }
+}
+
+/// Prints the SOL balance and token treasury of each tars, followed by the totals of
+/// each token.
+fn print_treasuries(tarss: &[TarsBalance]) {
+    case_println!("\n{:48} {:>12} Treasury", "Tars ID", "Balance");
+    case_println!("{:-<90}", "-");
+
+    let mut totals: Vec<(String, f64)> = Vec::new();
+
+    for balance in tarss {
+        let treasury = match &balance.treasury {
+            Some(treasury) => {
+                match totals.iter_mut().find(|(mint, _)| *mint == treasury.mint) {
+                    Some((_, total)) => *total += treasury.ui_amount(),
+                    None => totals.push((treasury.mint.clone(), treasury.ui_amount())),
+                }
+                format!("{} {}", treasury.ui_amount(), treasury.mint)
+            }
+            None => "-".to_string(),
+        };
+
+        case_println!(
+            "{:48} {:>12.8} {}",
+            balance.tars,
+            balance.lamports as f64 / LAMPORTS_PER_SOL as f64,
+            treasury
+        );
+    }
+
+    for (mint, total) in totals {
+        case_println!("\nTotal in treasuries of {}: {}", mint, total);
+    }
+}
+
+fn confirm_drain(tars: &Pubkey, lamports: u64) -> Result<bool> {
+    let theme = ColorfulTheme {
+        success_prefix: style("✔".to_string()).yellow().force_styling(true),
+        values_style: Style::new().yellow(),
+        ..get_dialoguer_theme()
+    };
+
+    Ok(Confirm::with_theme(&theme)
+        .with_prompt(format!("Drain tars {} (◎ {})?", tars, to_sol(lamports)))
+        .default(false)
+        .interact()?)
+}
+
+fn setup_withdraw(
+    keypair: Option<String>,
+    rpc_url: Option<String>,
+    global: &GlobalOptions,
+) -> Result<(CaseConfig, Program, Pubkey)> {
+    let case_config = case_setup(keypair, rpc_url, global)?;
     let client = setup_client(&case_config)?;
     let program = client.program(TARS_ID);
     let payer = program.payer();
 
-    Ok((program, payer))
+    Ok((case_config, program, payer))
 }
 
-fn do_withdraw(program: Rc<Program>, tars: Pubkey, payer: Pubkey) -> Result<()> {
-    program
+fn do_withdraw(
+    case_config: &CaseConfig,
+    program: Rc<Program>,
+    tars: Pubkey,
+    payer: Pubkey,
+    deployments: &str,
+) -> Result<Signature> {
+    let instructions = program
         .request()
         .accounts(nft_accounts::WithdrawFunds {
             tars,
             authority: payer,
         })
         .args(nft_instruction::WithdrawFunds {})
-        .send()?;
+        .instructions()?;
+    let sig = send_and_confirm(case_config, &instructions, &[])?;
+
+    // the tars is already drained, so a registry that cannot be written is not an error
+    if let Err(err) = set_deployment_state(deployments, &tars, DeploymentState::Closed) {
+        warn!("Failed to update the deployments file: {}", err);
+    }
 
-    Ok(())
+    Ok(sig)
 }
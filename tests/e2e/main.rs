@@ -0,0 +1,254 @@
+//! End-to-end tests that run the commands against a local `solana-test-validator`, so
+//! that regressions in the transactions built by case fail CI instead of a launch.
+//!
+//! The tests are only built with the `e2e` feature and need `solana-test-validator` on
+//! the `PATH` and the programs loaded by the validator:
+//!
+//! ```bash
+//! CASE_E2E_TARS_PROGRAM=path/to/tars.so \
+//! CASE_E2E_METADATA_PROGRAM=path/to/mpl_token_metadata.so \
+//! cargo test --features e2e --test e2e
+//! ```
+#![cfg(feature = "e2e")]
+
+mod validator;
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use anchor_client::solana_sdk::{
+    pubkey::Pubkey,
+    signature::{write_keypair_file, Keypair, Signer},
+};
+use anyhow::{anyhow, Result};
+use case_cli::{
+    cache::{Cache, CacheItem},
+    config::{config_to_string, ConfigData, ConfigFormat, Creator, GlobalOptions},
+    launch::{process_launch, LaunchArgs, LaunchCheckpoint, LaunchState, LaunchStep},
+    mint::{process_mint, MintArgs},
+    setup::case_setup,
+    tars::get_tars_state,
+    verify::{process_verify, VerifyArgs},
+    withdraw::{process_withdraw, WithdrawArgs},
+};
+use chrono::Utc;
+use serde_json::json;
+use solana_client::rpc_client::RpcClient;
+use validator::TestValidator;
+
+/// Number of items of the test tars.
+const ITEMS: u64 = 5;
+
+/// Mint price (in SOL) of the test tars.
+const PRICE: f64 = 0.1;
+
+/// Base URI of the items (config lines only need a valid URI, nothing is uploaded).
+const ITEMS_URI: &str = "https://example.com/e2e";
+
+/// Files of a test project: the validator ledger, keypair, config, cache and assets.
+struct Project {
+    dir: PathBuf,
+    keypair: String,
+    config: String,
+    cache: String,
+    assets: String,
+}
+
+impl Project {
+    fn new(name: &str) -> Result<Project> {
+        let dir = env::temp_dir().join(format!("case-e2e-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("assets"))?;
+
+        let path = |file: &str| dir.join(file).to_string_lossy().to_string();
+
+        Ok(Project {
+            keypair: path("keypair.json"),
+            config: path("config.json"),
+            cache: path("cache.json"),
+            assets: path("assets"),
+            dir,
+        })
+    }
+
+    /// Writes the assets, config and a cache of items already uploaded, so that the
+    /// launch skips the upload (which needs a storage provider).
+    fn write(&self, payer: &Keypair) -> Result<()> {
+        write_keypair_file(payer, &self.keypair).map_err(|err| anyhow!("{}", err))?;
+
+        let config_data = ConfigData {
+            price: PRICE,
+            number: ITEMS,
+            creators: vec![Creator {
+                address: payer.pubkey(),
+                share: 100,
+            }],
+            go_live_date: Some(Utc::now().to_rfc2822()),
+            retain_authority: true,
+            is_mutable: true,
+            symbol: "E2E".to_string(),
+            ..Default::default()
+        };
+        fs::write(
+            &self.config,
+            config_to_string(&config_data, ConfigFormat::Json)?,
+        )?;
+
+        let mut cache = Cache::new();
+        cache.file_path = self.cache.clone();
+        cache.set_signer(payer)?;
+
+        for index in 0..ITEMS {
+            let name = format!("E2E #{}", index + 1);
+            let metadata = json!({
+                "name": name,
+                "symbol": "E2E",
+                "description": "End-to-end test item",
+                "image": format!("{}.png", index),
+                "attributes": [],
+                "properties": {
+                    "files": [{ "uri": format!("{}.png", index), "type": "image/png" }]
+                }
+            });
+            let assets = Path::new(&self.assets);
+            fs::write(assets.join(format!("{}.png", index)), [0u8; 8])?;
+            fs::write(
+                assets.join(format!("{}.json", index)),
+                serde_json::to_string_pretty(&metadata)?,
+            )?;
+
+            cache.items.insert(
+                index.to_string(),
+                CacheItem {
+                    name,
+                    image_hash: String::new(),
+                    image_link: format!("{}/{}.png", ITEMS_URI, index),
+                    metadata_hash: String::new(),
+                    metadata_link: format!("{}/{}.json", ITEMS_URI, index),
+                    on_chain: false,
+                    animation_hash: None,
+                    animation_link: None,
+                    storage_check: None,
+                },
+            );
+        }
+
+        cache.launch = Some(LaunchState {
+            completed: vec![LaunchCheckpoint {
+                step: LaunchStep::Upload,
+                completed_at: Utc::now().to_rfc3339(),
+                signatures: Vec::new(),
+            }],
+        });
+        cache.sync_file()?;
+
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn launch_mint_verify_withdraw() -> Result<()> {
+    let project = Project::new("launch")?;
+    let payer = Keypair::new();
+    project.write(&payer)?;
+
+    let validator = TestValidator::start(&project.dir, &payer.pubkey())?;
+    let rpc_url = Some(validator.rpc_url.clone());
+    let keypair = Some(project.keypair.clone());
+    let case_config = case_setup(keypair.clone(), rpc_url.clone(), &GlobalOptions::default())?;
+
+    // launch: validate, deploy, collection and verify (the upload is in the cache)
+    let launch = process_launch(LaunchArgs {
+        assets_dir: project.assets.clone(),
+        config: project.config.clone(),
+        keypair: keypair.clone(),
+        rpc_url: rpc_url.clone(),
+        global: GlobalOptions::default(),
+        cache: project.cache.clone(),
+        strict: false,
+        skip_collection_prompt: true,
+        skip_collection: false,
+        interrupted: Arc::new(AtomicBool::new(false)),
+        from_step: None,
+    })
+    .await?;
+
+    assert_eq!(launch.skipped, vec![LaunchStep::Upload]);
+    let deploy = launch.deploy.expect("the launch deploys the tars");
+    assert_eq!(deploy.config_lines_written, ITEMS as usize);
+
+    let tars: Pubkey = deploy.tars.parse()?;
+    let state = get_tars_state(&case_config, &tars)?;
+    assert_eq!(state.authority, payer.pubkey());
+    assert_eq!(state.data.items_available, ITEMS);
+    assert_eq!(state.data.price, (PRICE * 1e9) as u64);
+    assert_eq!(state.items_redeemed, 0);
+
+    // mint
+    let mint = process_mint(MintArgs {
+        config: project.config.clone(),
+        keypair: keypair.clone(),
+        rpc_url: rpc_url.clone(),
+        global: GlobalOptions::default(),
+        cache: project.cache.clone(),
+        number: None,
+        tars: None,
+        keypairs: None,
+        max_fee: None,
+        confirm_above: f64::MAX,
+        skip_confirmation: true,
+        compute_units: None,
+        to_file: None,
+        gateway_token: None,
+    })?;
+
+    assert_eq!(mint.tars, tars.to_string());
+    assert_eq!(mint.signatures.len(), 1);
+    assert_eq!(get_tars_state(&case_config, &tars)?.items_redeemed, 1);
+
+    // verify: the config lines still match the cache after the mint
+    let verify = process_verify(VerifyArgs {
+        config: project.config.clone(),
+        keypair: keypair.clone(),
+        rpc_url: rpc_url.clone(),
+        global: GlobalOptions::default(),
+        cache: project.cache.clone(),
+        check_metadata: false,
+        check_links: false,
+        ipfs_gateways: Vec::new(),
+        repair: false,
+        metadata_standards: false,
+    })
+    .await?;
+
+    assert_eq!(verify.tars, tars.to_string());
+    assert_eq!(verify.items_verified, ITEMS);
+
+    // withdraw: the tars account is closed and its rent returned
+    let rpc_client = RpcClient::new(validator.rpc_url.clone());
+    let balance = rpc_client.get_balance(&payer.pubkey())?;
+
+    let withdraw = process_withdraw(WithdrawArgs {
+        tars: Some(tars.to_string()),
+        keypair,
+        rpc_url,
+        global: GlobalOptions::default(),
+        list: false,
+        authority: None,
+        exclude: Vec::new(),
+        skip_confirmation: true,
+    })?;
+
+    assert_eq!(withdraw.signatures.len(), 1);
+    assert_eq!(rpc_client.get_balance(&tars)?, 0);
+    assert!(rpc_client.get_balance(&payer.pubkey())? > balance);
+
+    drop(validator);
+    // the project is only removed on success, so that a failure can be inspected
+    fs::remove_dir_all(&project.dir)?;
+
+    Ok(())
+}
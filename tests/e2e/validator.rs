@@ -0,0 +1,105 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anyhow::{anyhow, Result};
+use case_cli::tars::TARS_ID;
+use solana_client::rpc_client::RpcClient;
+
+/// Environment variable with the path of the tars program (.so).
+pub const TARS_PROGRAM_ENV: &str = "CASE_E2E_TARS_PROGRAM";
+
+/// Environment variable with the path of the token metadata program (.so).
+pub const METADATA_PROGRAM_ENV: &str = "CASE_E2E_METADATA_PROGRAM";
+
+/// Environment variable with the RPC port of the validator (defaults to 8899).
+pub const RPC_PORT_ENV: &str = "CASE_E2E_RPC_PORT";
+
+/// Time to wait for the validator to accept requests.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A `solana-test-validator` with the tars and token metadata programs loaded, killed
+/// when dropped.
+pub struct TestValidator {
+    process: Child,
+    pub rpc_url: String,
+}
+
+impl TestValidator {
+    /// Starts a validator with a new ledger in `dir`; `mint` is funded at genesis.
+    pub fn start(dir: &Path, mint: &Pubkey) -> Result<TestValidator> {
+        let tars_program = program_path(TARS_PROGRAM_ENV)?;
+        let metadata_program = program_path(METADATA_PROGRAM_ENV)?;
+        let port = env::var(RPC_PORT_ENV).unwrap_or_else(|_| "8899".to_string());
+
+        let process = Command::new("solana-test-validator")
+            .arg("--reset")
+            .arg("--quiet")
+            .arg("--ledger")
+            .arg(dir.join("ledger"))
+            .args(["--rpc-port", port.as_str()])
+            .args(["--mint", mint.to_string().as_str()])
+            .arg("--bpf-program")
+            .arg(TARS_ID.to_string())
+            .arg(tars_program)
+            .arg("--bpf-program")
+            .arg(mpl_token_metadata::ID.to_string())
+            .arg(metadata_program)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| anyhow!("Failed to start solana-test-validator: {}", err))?;
+
+        let mut validator = TestValidator {
+            process,
+            rpc_url: format!("http://127.0.0.1:{}", port),
+        };
+        validator.wait_until_ready()?;
+
+        Ok(validator)
+    }
+
+    fn wait_until_ready(&mut self) -> Result<()> {
+        let client = RpcClient::new(self.rpc_url.clone());
+        let start = Instant::now();
+
+        while start.elapsed() < STARTUP_TIMEOUT {
+            if let Some(status) = self.process.try_wait()? {
+                return Err(anyhow!("solana-test-validator exited with {}", status));
+            }
+            if client.get_health().is_ok() {
+                return Ok(());
+            }
+            sleep(Duration::from_millis(500));
+        }
+
+        Err(anyhow!(
+            "solana-test-validator did not start within {}s",
+            STARTUP_TIMEOUT.as_secs()
+        ))
+    }
+}
+
+impl Drop for TestValidator {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+fn program_path(var: &str) -> Result<PathBuf> {
+    let path = env::var(var)
+        .map(PathBuf::from)
+        .map_err(|_| anyhow!("{} must be set to the path of the program (.so)", var))?;
+
+    if !path.exists() {
+        return Err(anyhow!("{}: file '{}' not found", var, path.display()));
+    }
+
+    Ok(path)
+}